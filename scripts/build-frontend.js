@@ -104,6 +104,7 @@ const INDEX_FILE = join(
   'index.tsx'
 );
 const TILEMAP_GENERATOR_FILE = join(ROOT, 'scripts', 'generate-tilemaps.cjs');
+const QUICK_REFERENCE_GENERATOR_FILE = join(ROOT, 'scripts', 'generate-quick-reference.cjs');
 
 function log(msg) {
   console.log(`[build] ${msg}`);
@@ -624,6 +625,25 @@ function generateLocalTilemaps() {
   });
 }
 
+// The quick-reference panel is optional: a client whose tables moved still
+// builds, the panel just reports its data missing
+function generateQuickReference(buildVersion) {
+  log('Generating quick-reference data...');
+  try {
+    execFileSync(process.execPath, [
+      QUICK_REFERENCE_GENERATOR_FILE,
+      UPSTREAM_DIR,
+      join(DIST_DIR, 'quick-reference.json'),
+      buildVersion,
+    ], {
+      cwd: UPSTREAM_DIR,
+      stdio: 'inherit',
+    });
+  } catch (err) {
+    log(`Warning: quick-reference data not generated (${err.message})`);
+  }
+}
+
 async function main() {
   log('Building Pokemon Auto Chess frontend...');
 
@@ -741,6 +761,8 @@ async function main() {
     .substring(0, 12);
   log(`Build version: ${buildVersion}`);
 
+  generateQuickReference(buildVersion);
+
   // Create index.html with performance overlay
   log('Creating index.html with overlay...');
   createIndexHtml(buildVersion);
@@ -0,0 +1,56 @@
+#!/usr/bin/env node
+// Extracts synergy breakpoints and item recipes from the upstream sources
+// into the JSON file behind the overlay's quick-reference panel. The file is
+// stamped with the game version and this build's version, so the panel can
+// tell when it describes a different client than the one it runs in.
+const { mkdirSync, readFileSync, writeFileSync } = require('node:fs');
+const { dirname, join, resolve } = require('node:path');
+
+const upstreamDir = resolve(process.argv[2] || '');
+const outputFile = resolve(process.argv[3] || '');
+const buildVersion = process.argv[4] || 'unknown';
+
+if (!process.argv[2] || !process.argv[3]) {
+  console.error('Usage: node scripts/generate-quick-reference.cjs <upstreamDir> <outputFile> [buildVersion]');
+  process.exit(1);
+}
+
+require(require.resolve('ts-node/register/transpile-only', { paths: [upstreamDir] }));
+
+// Upstream has moved these tables before; take the first module that has them
+function findExport(name, candidates) {
+  for (const candidate of candidates) {
+    try {
+      const module = require(join(upstreamDir, ...candidate.split('/')));
+      if (module && module[name]) return module[name];
+    } catch (e) {
+      // Try the next location
+    }
+  }
+  throw new Error(`${name} not found in ${candidates.join(', ')}`);
+}
+
+const synergyTriggers = findExport('SynergyTriggers', ['app/types/Config', 'app/config']);
+const itemRecipe = findExport('ItemRecipe', ['app/types/enum/Item', 'app/types/Config']);
+
+const synergies = Object.entries(synergyTriggers)
+  .filter(([, breakpoints]) => Array.isArray(breakpoints) && breakpoints.length > 0)
+  .map(([name, breakpoints]) => ({ name, breakpoints: [...breakpoints].sort((a, b) => a - b) }))
+  .sort((a, b) => a.name.localeCompare(b.name));
+
+const recipes = Object.entries(itemRecipe)
+  .filter(([, components]) => Array.isArray(components) && components.length > 0)
+  .map(([item, components]) => ({ item, components: [...components] }))
+  .sort((a, b) => a.item.localeCompare(b.item));
+
+const { version } = JSON.parse(readFileSync(join(upstreamDir, 'package.json'), 'utf-8'));
+
+mkdirSync(dirname(outputFile), { recursive: true });
+writeFileSync(outputFile, JSON.stringify({
+  game_version: version || 'unknown',
+  build_version: buildVersion,
+  synergies,
+  recipes,
+}));
+
+console.log(`[generate-quick-reference] Wrote ${synergies.length} synergies and ${recipes.length} recipes to ${outputFile}`);
@@ -1,6 +1,6 @@
 #!/usr/bin/env node
 import { basename } from 'node:path';
-import { readFileSync, writeFileSync } from 'node:fs';
+import { existsSync, readFileSync, writeFileSync } from 'node:fs';
 import {
   assertExistingFile,
   getUpdaterManifestPath,
@@ -18,6 +18,20 @@ function readSignature(path) {
   return readFileSync(signaturePath, 'utf-8').trim();
 }
 
+// Patches from older installers, listed next to an installer as
+// `<installer>.deltas.json` ([{ from, url, size }], written for patches
+// made with `cargo run --example make_delta`). Clients that can't use one
+// download the full installer (docs/DELTA_UPDATES.md).
+function readDeltas(path) {
+  const deltasPath = `${path}.deltas.json`;
+  if (!existsSync(deltasPath)) return {};
+  const deltas = JSON.parse(readFileSync(deltasPath, 'utf-8'));
+  if (!Array.isArray(deltas)) {
+    throw new Error(`${basename(deltasPath)} must be an array`);
+  }
+  return { deltas };
+}
+
 function resolveReleaseContext() {
   const version = readPackageVersion();
   const tag = process.env.GITHUB_REF_NAME || `v${version}`;
@@ -46,14 +60,17 @@ const manifest = {
     'windows-x86_64': {
       signature: readSignature(msi.path),
       url: `${releaseBaseUrl}/${basename(msi.path)}`,
+      ...readDeltas(msi.path),
     },
     'windows-x86_64-msi': {
       signature: readSignature(msi.path),
       url: `${releaseBaseUrl}/${basename(msi.path)}`,
+      ...readDeltas(msi.path),
     },
     'windows-x86_64-nsis': {
       signature: readSignature(nsis.path),
       url: `${releaseBaseUrl}/${basename(nsis.path)}`,
+      ...readDeltas(nsis.path),
     },
   },
 };
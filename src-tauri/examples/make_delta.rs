@@ -0,0 +1,30 @@
+//! Write a patch update (docs/DELTA_UPDATES.md): a gzipped bsdiff patch
+//! that rebuilds `<new installer>` from `<old installer>`.
+//!
+//! cargo run --release --example make_delta -- <old installer> <new installer> <patch>
+
+use std::io::Write;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [old, new, out] = args.as_slice() else {
+        return Err("usage: make_delta <old installer> <new installer> <patch>".to_string());
+    };
+    let read = |path: &str| std::fs::read(path).map_err(|e| format!("{}: {}", path, e));
+    let (old_bytes, new_bytes) = (read(old)?, read(new)?);
+
+    let mut diff = Vec::new();
+    bsdiff::diff(&old_bytes, &new_bytes, &mut diff).map_err(|e| e.to_string())?;
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    gz.write_all(&diff).map_err(|e| e.to_string())?;
+    let patch = gz.finish().map_err(|e| e.to_string())?;
+    std::fs::write(out, &patch).map_err(|e| format!("{}: {}", out, e))?;
+
+    println!(
+        "{}: {} bytes ({:.0}% of the full installer)",
+        out,
+        patch.len(),
+        patch.len() as f64 * 100.0 / new_bytes.len().max(1) as f64
+    );
+    Ok(())
+}
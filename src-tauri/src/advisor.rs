@@ -0,0 +1,273 @@
+//! Performance Advisor - Cross-platform
+//!
+//! Turns raw performance stats into actionable hints for the overlay.
+//! Rules are evaluated on demand from the latest samples; nothing here
+//! changes system state.
+
+use serde::{Deserialize, Serialize};
+
+/// How urgently a piece of advice should be surfaced
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AdviceSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// A single actionable hint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceAdvice {
+    /// Stable identifier so the UI can de-duplicate and dismiss hints
+    pub id: String,
+    pub severity: AdviceSeverity,
+    /// Short headline shown in the overlay
+    pub title: String,
+    /// What the user can do about it
+    pub suggestion: String,
+}
+
+/// Inputs for one advisor pass
+#[derive(Debug, Clone, Default)]
+pub struct AdvisorInputs {
+    /// FPS measured by the injected runtime (None if not reported)
+    pub fps: Option<f32>,
+    /// System-wide CPU usage (0-100)
+    pub cpu_usage: f32,
+    /// PACDeluxe process memory in MB
+    pub memory_usage_mb: u64,
+    /// GPU utilization (None when GPU monitoring is unavailable)
+    pub gpu_usage: Option<f32>,
+    /// Hottest sensor reading and its critical threshold, in Celsius
+    pub max_temperature: Option<(f32, Option<f32>)>,
+    /// Webview compositor is running on the CPU
+    pub software_rendering: bool,
+    /// Latest used JS heap in MB (WebView2 only)
+    pub js_heap_used_mb: Option<f64>,
+    /// Latest live DOM node count (WebView2 only)
+    pub dom_nodes: Option<u64>,
+    /// Recent JS heap relative to the start of the session history
+    pub heap_growth_ratio: Option<f64>,
+    /// Main-thread time lost to long tasks over the last minute
+    pub main_thread_blocked_ms_per_min: f64,
+    /// Driver version Vulkan rendering was left out for at startup
+    pub vulkan_blocked_driver: Option<String>,
+}
+
+/// FPS below this is treated as "the game is struggling"
+const LOW_FPS: f32 = 30.0;
+/// Utilization at or above this is treated as pegged
+const PEGGED_PERCENT: f32 = 95.0;
+/// Renderer memory above this usually means a long-session leak
+const HIGH_MEMORY_MB: u64 = 3072;
+/// Main-thread blocking above this (ms per minute) makes the game JS-bound
+const JS_BOUND_BLOCKED_MS_PER_MIN: f64 = 3000.0;
+/// JS heap above this is far beyond what a single match needs
+const LEAK_HEAP_MB: f64 = 1024.0;
+/// The game UI normally stays well under this many DOM nodes
+const LEAK_DOM_NODES: u64 = 50_000;
+/// Heap growth over the session that indicates a leak rather than a spike
+const LEAK_GROWTH_RATIO: f64 = 2.0;
+/// Ignore growth below this heap size (small heaps fluctuate a lot)
+const LEAK_GROWTH_MIN_HEAP_MB: f64 = 256.0;
+/// Fallback thermal limit when a sensor does not report a critical value
+const DEFAULT_THERMAL_LIMIT_C: f32 = 90.0;
+/// How close to the critical threshold counts as throttling territory
+const THERMAL_MARGIN_C: f32 = 5.0;
+
+/// Evaluate all advisor rules, most severe first
+pub fn evaluate(inputs: &AdvisorInputs) -> Vec<PerformanceAdvice> {
+    let mut advice = Vec::new();
+    let low_fps = inputs.fps.is_some_and(|fps| fps > 0.0 && fps < LOW_FPS);
+
+    if inputs.software_rendering {
+        advice.push(PerformanceAdvice {
+            id: "software-rendering".to_string(),
+            severity: AdviceSeverity::Critical,
+            title: "GPU acceleration is off".to_string(),
+            suggestion: "The game is being drawn on the CPU. Update your GPU driver and make sure hardware acceleration isn't disabled for WebView2.".to_string(),
+        });
+    }
+
+    if low_fps && inputs.gpu_usage.is_some_and(|gpu| gpu >= PEGGED_PERCENT) {
+        advice.push(PerformanceAdvice {
+            id: "gpu-bound".to_string(),
+            severity: AdviceSeverity::Warning,
+            title: "GPU is maxed out".to_string(),
+            suggestion: "Close other GPU-heavy apps (browsers, recorders) or use a smaller window; turning off Windows HDR also helps the SDR game.".to_string(),
+        });
+    }
+
+    if low_fps && inputs.cpu_usage >= PEGGED_PERCENT {
+        advice.push(PerformanceAdvice {
+            id: "cpu-bound".to_string(),
+            severity: AdviceSeverity::Warning,
+            title: "CPU is maxed out".to_string(),
+            suggestion: "Another program is competing for CPU time. Close background apps or pause recording software while playing.".to_string(),
+        });
+    }
+
+    let gpu_pegged = inputs.gpu_usage.is_some_and(|gpu| gpu >= PEGGED_PERCENT);
+    if low_fps
+        && !gpu_pegged
+        && inputs.main_thread_blocked_ms_per_min >= JS_BOUND_BLOCKED_MS_PER_MIN
+    {
+        advice.push(PerformanceAdvice {
+            id: "js-bound".to_string(),
+            severity: AdviceSeverity::Warning,
+            title: format!(
+                "Game scripts are blocking ({:.1}s/min)",
+                inputs.main_thread_blocked_ms_per_min / 1000.0
+            ),
+            suggestion: "Slowness comes from the game's JavaScript, not your GPU. Graphics settings won't help; a page reload often does.".to_string(),
+        });
+    }
+
+    if inputs.memory_usage_mb >= HIGH_MEMORY_MB {
+        advice.push(PerformanceAdvice {
+            id: "high-memory".to_string(),
+            severity: AdviceSeverity::Info,
+            title: "High memory usage".to_string(),
+            suggestion: "The game grows over long sessions. Reload the page between matches to reclaim memory.".to_string(),
+        });
+    }
+
+    let heap = inputs.js_heap_used_mb.unwrap_or(0.0);
+    let heap_leak = heap >= LEAK_HEAP_MB
+        || (heap >= LEAK_GROWTH_MIN_HEAP_MB
+            && inputs
+                .heap_growth_ratio
+                .is_some_and(|ratio| ratio >= LEAK_GROWTH_RATIO));
+    let dom_leak = inputs.dom_nodes.is_some_and(|n| n >= LEAK_DOM_NODES);
+    if heap_leak || dom_leak {
+        advice.push(PerformanceAdvice {
+            id: "page-leak".to_string(),
+            severity: AdviceSeverity::Warning,
+            title: if dom_leak {
+                format!("Page is leaking ({} DOM nodes)", inputs.dom_nodes.unwrap_or(0))
+            } else {
+                format!("Page is leaking ({:.0} MB JS heap)", heap)
+            },
+            suggestion: "Long lobby sessions leak memory in the game page. Reload (F5) between matches to get smooth frame times back.".to_string(),
+        });
+    }
+
+    if let Some((temperature, critical)) = inputs.max_temperature {
+        let limit = critical.unwrap_or(DEFAULT_THERMAL_LIMIT_C);
+        if temperature >= limit - THERMAL_MARGIN_C {
+            advice.push(PerformanceAdvice {
+                id: "thermal-throttling".to_string(),
+                severity: AdviceSeverity::Critical,
+                title: format!("Running hot ({:.0}°C)", temperature),
+                suggestion: "Your hardware is close to its thermal limit and may be throttling. Check cooling or plug in a laptop on a hard surface.".to_string(),
+            });
+        }
+    }
+
+    if let Some(driver) = &inputs.vulkan_blocked_driver {
+        advice.push(PerformanceAdvice {
+            id: "vulkan-blocklisted".to_string(),
+            severity: AdviceSeverity::Info,
+            title: "Vulkan rendering skipped".to_string(),
+            suggestion: format!("GPU driver {} is known to break Vulkan in WebView2, so the game uses the default renderer. Update the driver to use Vulkan.", driver),
+        });
+    }
+
+    advice.sort_by(|a, b| b.severity.cmp(&a.severity));
+    advice
+}
+
+/// Hottest temperature sensor and its critical threshold, if any are exposed
+pub fn read_max_temperature() -> Option<(f32, Option<f32>)> {
+    max_temperature(&sysinfo::Components::new_with_refreshed_list())
+}
+
+/// Hottest of already refreshed components
+pub fn max_temperature(components: &sysinfo::Components) -> Option<(f32, Option<f32>)> {
+    components
+        .iter()
+        .map(|c| (c.temperature(), c.critical()))
+        .filter(|(t, _)| t.is_finite() && *t > 0.0)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(advice: &[PerformanceAdvice]) -> Vec<&str> {
+        advice.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    #[test]
+    fn healthy_stats_produce_no_advice() {
+        let inputs = AdvisorInputs {
+            fps: Some(60.0),
+            cpu_usage: 30.0,
+            memory_usage_mb: 800,
+            gpu_usage: Some(40.0),
+            max_temperature: Some((60.0, Some(100.0))),
+            software_rendering: false,
+            js_heap_used_mb: Some(180.0),
+            dom_nodes: Some(4000),
+            heap_growth_ratio: Some(1.1),
+            main_thread_blocked_ms_per_min: 200.0,
+            vulkan_blocked_driver: None,
+        };
+        assert!(evaluate(&inputs).is_empty());
+    }
+
+    #[test]
+    fn pegged_gpu_with_low_fps_is_gpu_bound() {
+        let inputs = AdvisorInputs {
+            fps: Some(20.0),
+            gpu_usage: Some(99.0),
+            ..Default::default()
+        };
+        assert_eq!(ids(&evaluate(&inputs)), vec!["gpu-bound"]);
+    }
+
+    #[test]
+    fn pegged_gpu_with_good_fps_is_fine() {
+        let inputs = AdvisorInputs {
+            fps: Some(144.0),
+            gpu_usage: Some(99.0),
+            ..Default::default()
+        };
+        assert!(evaluate(&inputs).is_empty());
+    }
+
+    #[test]
+    fn blocked_main_thread_is_js_bound() {
+        let inputs = AdvisorInputs {
+            fps: Some(25.0),
+            gpu_usage: Some(50.0),
+            main_thread_blocked_ms_per_min: 8000.0,
+            ..Default::default()
+        };
+        assert_eq!(ids(&evaluate(&inputs)), vec!["js-bound"]);
+    }
+
+    #[test]
+    fn heap_growth_is_a_leak() {
+        let inputs = AdvisorInputs {
+            js_heap_used_mb: Some(600.0),
+            heap_growth_ratio: Some(3.0),
+            ..Default::default()
+        };
+        assert_eq!(ids(&evaluate(&inputs)), vec!["page-leak"]);
+    }
+
+    #[test]
+    fn thermal_advice_sorts_first() {
+        let inputs = AdvisorInputs {
+            memory_usage_mb: 4096,
+            max_temperature: Some((97.0, Some(100.0))),
+            vulkan_blocked_driver: Some("536.40".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&evaluate(&inputs)),
+            vec!["thermal-throttling", "high-memory", "vulkan-blocklisted"]
+        );
+    }
+}
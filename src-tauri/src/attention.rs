@@ -0,0 +1,222 @@
+//! Attention Alerts - Cross-platform
+//!
+//! Gets the player back to the window when something needs them while it is
+//! in the background: a carousel round where they haven't moved for a few
+//! seconds, or a match starting. Both come from the event bus: the injected
+//! runtime reports carousel rounds it sees in the HUD (`pac-carousel`) and
+//! match starts arrive as page phase changes (`pac-page-state`).
+//!
+//! Each event type has its own settings (`AttentionSettings`): flash the
+//! taskbar entry, play the system alert sound, or both. Nothing is sent to
+//! the game.
+
+use crate::page_state::GamePhase;
+use crate::settings::{AlertSettings, SettingsState};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Listener, Manager, UserAttentionType};
+use tracing::{debug, info, warn};
+
+/// What an alert is for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttentionEvent {
+    CarouselIdle,
+    MatchStart,
+}
+
+/// `pac-carousel` payload from the page
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct CarouselReport {
+    active: bool,
+    /// The player pressed a key or moved the mouse in the window
+    #[serde(default)]
+    moved: bool,
+}
+
+/// The carousel round in progress, if any
+#[derive(Debug, Default)]
+struct Carousel {
+    /// Increases with every round, so a timer from an earlier round is
+    /// ignored
+    round: u64,
+    active: bool,
+    moved: bool,
+}
+
+/// Managed carousel state
+#[derive(Debug, Default)]
+pub struct CarouselWatch(Mutex<Carousel>);
+
+impl CarouselWatch {
+    /// Apply a report; returns the round to time when a new one began
+    fn report(&self, report: CarouselReport) -> Option<u64> {
+        let mut carousel = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match (carousel.active, report.active) {
+            (false, true) => {
+                carousel.round += 1;
+                carousel.active = true;
+                carousel.moved = report.moved;
+                Some(carousel.round)
+            }
+            (true, true) => {
+                carousel.moved |= report.moved;
+                None
+            }
+            (_, false) => {
+                carousel.active = false;
+                None
+            }
+        }
+    }
+
+    /// Whether `round` is still on and the player hasn't moved
+    fn is_idle(&self, round: u64) -> bool {
+        let carousel = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        carousel.active && carousel.round == round && !carousel.moved
+    }
+}
+
+fn should_alert(alert: &AlertSettings, focused: bool) -> bool {
+    alert.enabled && (alert.flash || alert.sound) && !focused
+}
+
+fn is_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/// Flash and/or beep for `event` as configured, unless the window has focus
+pub fn alert(app: &AppHandle, event: AttentionEvent) {
+    let attention = app.state::<SettingsState>().get().attention;
+    let alert = match event {
+        AttentionEvent::CarouselIdle => attention.carousel_idle,
+        AttentionEvent::MatchStart => attention.match_start,
+    };
+    if !should_alert(&alert, is_focused(app)) {
+        return;
+    }
+    info!("Attention alert: {:?}", event);
+    if alert.flash {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.request_user_attention(Some(UserAttentionType::Critical));
+        }
+    }
+    if alert.sound {
+        play_alert_sound();
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn play_alert_sound() {
+    use windows::Win32::UI::WindowsAndMessaging::{MessageBeep, MB_ICONEXCLAMATION};
+    // SAFETY: MessageBeep only queues the system sound
+    if let Err(e) = unsafe { MessageBeep(MB_ICONEXCLAMATION) } {
+        debug!("MessageBeep failed: {}", e);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn play_alert_sound() {
+    debug!("No alert sound on this platform");
+}
+
+fn carousel_started(app: &AppHandle, round: u64) {
+    let idle = Duration::from_secs(
+        app.state::<SettingsState>()
+            .get()
+            .attention
+            .validated()
+            .carousel_idle_seconds,
+    );
+    let handle = app.clone();
+    std::thread::Builder::new()
+        .name("carousel-watch".to_string())
+        .spawn(move || {
+            std::thread::sleep(idle);
+            if handle.state::<CarouselWatch>().is_idle(round) {
+                alert(&handle, AttentionEvent::CarouselIdle);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start carousel watch: {}", e));
+}
+
+/// Manage the carousel state and subscribe to the event bus
+pub fn install(app: &AppHandle) {
+    app.manage(CarouselWatch::default());
+
+    let handle = app.clone();
+    app.listen_any("pac-carousel", move |event| {
+        match serde_json::from_str::<CarouselReport>(event.payload()) {
+            Ok(report) => {
+                if let Some(round) = handle.state::<CarouselWatch>().report(report) {
+                    debug!("Carousel round {} started", round);
+                    carousel_started(&handle, round);
+                }
+            }
+            Err(e) => warn!("Unexpected carousel payload: {}", e),
+        }
+    });
+
+    let handle = app.clone();
+    app.listen_any("pac-page-state", move |event| {
+        if let Ok(phase) = serde_json::from_str::<GamePhase>(event.payload()) {
+            // A new page never starts inside a carousel
+            handle.state::<CarouselWatch>().report(CarouselReport {
+                active: false,
+                moved: false,
+            });
+            if phase == GamePhase::InGame {
+                alert(&handle, AttentionEvent::MatchStart);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(active: bool, moved: bool) -> CarouselReport {
+        CarouselReport { active, moved }
+    }
+
+    #[test]
+    fn only_an_untouched_current_round_is_idle() {
+        let watch = CarouselWatch::default();
+        let first = watch.report(report(true, false)).unwrap();
+        assert_eq!(watch.report(report(true, false)), None);
+        assert!(watch.is_idle(first));
+
+        watch.report(report(true, true));
+        assert!(!watch.is_idle(first));
+
+        watch.report(report(false, false));
+        let second = watch.report(report(true, false)).unwrap();
+        assert!(!watch.is_idle(first));
+        assert!(watch.is_idle(second));
+        watch.report(report(false, false));
+        assert!(!watch.is_idle(second));
+    }
+
+    #[test]
+    fn alerts_need_an_action_and_a_background_window() {
+        let alert = AlertSettings::default();
+        assert!(should_alert(&alert, false));
+        assert!(!should_alert(&alert, true));
+        let silent = AlertSettings {
+            flash: false,
+            sound: false,
+            ..alert
+        };
+        assert!(!should_alert(&silent, false));
+        let off = AlertSettings {
+            enabled: false,
+            ..alert
+        };
+        assert!(!should_alert(&off, false));
+    }
+}
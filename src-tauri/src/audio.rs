@@ -0,0 +1,214 @@
+//! Audio Session - Windows
+//!
+//! The game's sound plays from WebView2's audio service process, so the
+//! volume mixer lists it under a WebView2 name instead of PACDeluxe. The
+//! sessions belonging to our process tree are renamed "PACDeluxe" as they
+//! appear, and their volume can be read and set through WASAPI. A volume set
+//! through PACDeluxe is saved in the profile (`audio_volume`) and applied to
+//! sessions that appear later, e.g. after the webview is recreated.
+//!
+//! Linux: not available (no per-application mixer API).
+
+#[cfg(target_os = "windows")]
+use crate::profile::ProfileState;
+#[cfg(target_os = "windows")]
+use std::time::Duration;
+use tauri::AppHandle;
+#[cfg(target_os = "windows")]
+use tauri::Manager;
+use tracing::debug;
+#[cfg(target_os = "windows")]
+use tracing::{info, warn};
+#[cfg(target_os = "windows")]
+use windows::Win32::Media::Audio::IAudioSessionControl2;
+
+/// How often new audio sessions are looked for
+#[cfg(target_os = "windows")]
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reject volumes outside 0-1 (including NaN)
+pub fn validate_volume(volume: f32) -> Result<f32, String> {
+    if (0.0..=1.0).contains(&volume) {
+        Ok(volume)
+    } else {
+        Err(format!("Volume must be between 0 and 1, got {}", volume))
+    }
+}
+
+/// COM initialized for the current thread, uninitialized on drop
+#[cfg(target_os = "windows")]
+struct ComScope(bool);
+
+#[cfg(target_os = "windows")]
+impl ComScope {
+    fn enter() -> Self {
+        use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+        // Fails on threads that are already single-threaded apartments (the
+        // main thread), where COM is usable as is
+        Self(unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) }.is_ok())
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ComScope {
+    fn drop(&mut self) {
+        if self.0 {
+            unsafe { windows::Win32::System::Com::CoUninitialize() };
+        }
+    }
+}
+
+/// Sessions on the default output device played by this process or its
+/// WebView2 children
+#[cfg(target_os = "windows")]
+fn our_sessions() -> windows::core::Result<Vec<IAudioSessionControl2>> {
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::{
+        eMultimedia, eRender, IAudioSessionManager2, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+
+    let our_pid = std::process::id();
+    unsafe {
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let device = enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia)?;
+        let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+        let sessions = manager.GetSessionEnumerator()?;
+        let mut ours = Vec::new();
+        for i in 0..sessions.GetCount()? {
+            let control: IAudioSessionControl2 = sessions.GetSession(i)?.cast()?;
+            let Ok(pid) = control.GetProcessId() else {
+                continue;
+            };
+            if pid == our_pid || crate::performance::is_descendant_of_pid(pid, our_pid) {
+                ours.push(control);
+            }
+        }
+        Ok(ours)
+    }
+}
+
+/// Name the session "PACDeluxe" unless it already is. Returns true if it
+/// was renamed, i.e. the session is new to us.
+#[cfg(target_os = "windows")]
+fn claim_session(control: &IAudioSessionControl2) -> bool {
+    use windows::Win32::System::Com::CoTaskMemFree;
+
+    unsafe {
+        if let Ok(name) = control.GetDisplayName() {
+            let current = name.to_string().unwrap_or_default();
+            CoTaskMemFree(Some(name.0 as *const _));
+            if current == "PACDeluxe" {
+                return false;
+            }
+        }
+        control
+            .SetDisplayName(windows::core::w!("PACDeluxe"), std::ptr::null())
+            .is_ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_volume(control: &IAudioSessionControl2, volume: f32) -> windows::core::Result<()> {
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::ISimpleAudioVolume;
+
+    unsafe {
+        control
+            .cast::<ISimpleAudioVolume>()?
+            .SetMasterVolume(volume, std::ptr::null())
+    }
+}
+
+/// Volume of the game's audio (0-1), or `None` before it has played sound
+#[cfg(target_os = "windows")]
+pub fn session_volume() -> Result<Option<f32>, String> {
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::ISimpleAudioVolume;
+
+    let _com = ComScope::enter();
+    let sessions = our_sessions().map_err(|e| format!("Audio sessions unavailable: {}", e))?;
+    let Some(control) = sessions.first() else {
+        return Ok(None);
+    };
+    unsafe {
+        control
+            .cast::<ISimpleAudioVolume>()
+            .and_then(|volume| volume.GetMasterVolume())
+            .map(Some)
+            .map_err(|e| format!("Failed to read session volume: {}", e))
+    }
+}
+
+/// Set the volume of every current session. Returns how many were changed
+/// (0 before the game has played sound).
+#[cfg(target_os = "windows")]
+pub fn set_session_volume(volume: f32) -> Result<usize, String> {
+    let volume = validate_volume(volume)?;
+    let _com = ComScope::enter();
+    let sessions = our_sessions().map_err(|e| format!("Audio sessions unavailable: {}", e))?;
+    for control in &sessions {
+        set_volume(control, volume).map_err(|e| format!("Failed to set session volume: {}", e))?;
+    }
+    Ok(sessions.len())
+}
+
+/// Name new sessions and give them the saved volume
+#[cfg(target_os = "windows")]
+pub fn spawn_watcher(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("audio-session".to_string())
+        .spawn(move || {
+            let _com = ComScope::enter();
+            loop {
+                match our_sessions() {
+                    Ok(sessions) => {
+                        for control in sessions.iter().filter(|control| claim_session(control)) {
+                            let saved = app.state::<ProfileState>().get().audio_volume;
+                            if let Some(volume) = saved {
+                                if let Err(e) = set_volume(control, volume) {
+                                    warn!("Failed to restore audio volume: {}", e);
+                                }
+                            }
+                            info!("Audio session named (saved volume: {:?})", saved);
+                        }
+                    }
+                    Err(e) => debug!("Audio sessions unavailable: {}", e),
+                }
+                std::thread::sleep(WATCH_INTERVAL);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start audio session watcher: {}", e));
+}
+
+#[cfg(target_os = "linux")]
+pub fn session_volume() -> Result<Option<f32>, String> {
+    Err("Audio session control is not available on Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_session_volume(volume: f32) -> Result<usize, String> {
+    validate_volume(volume)?;
+    Err("Audio session control is not available on Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+pub fn spawn_watcher(_app: AppHandle) {
+    debug!("Audio session naming is not available on Linux");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_must_be_a_fraction() {
+        assert_eq!(validate_volume(0.0), Ok(0.0));
+        assert_eq!(validate_volume(0.35), Ok(0.35));
+        assert!(validate_volume(1.5).is_err());
+        assert!(validate_volume(-0.1).is_err());
+        assert!(validate_volume(f32::NAN).is_err());
+    }
+}
@@ -0,0 +1,341 @@
+//! Backup and Restore - Cross-platform
+//!
+//! `backup_user_data` archives the user's own data from the config directory
+//! into `backups/pacdeluxe-backup-<unix seconds>.zip`: settings, profiles,
+//! customization (theme, background, user scripts), macros, reminders,
+//! planner comps and session stats. Logs, reports, diagnostics and crash
+//! history are left out, and so is the webview's cache, which lives outside
+//! the config directory.
+//!
+//! `restore_user_data` checks an archive and stages its files in
+//! `restore-pending`; they replace the current ones at the next start,
+//! before anything is loaded, so nothing saved on exit overwrites them. The
+//! app restarts right after staging.
+//!
+//! Weekly backups (`backups.weekly`, off by default) keep the newest
+//! `backups.keep` archives and remove older ones.
+
+use crate::settings::{self, SettingsState};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const BACKUP_DIR_NAME: &str = "backups";
+const PENDING_DIR_NAME: &str = "restore-pending";
+const FILE_PREFIX: &str = "pacdeluxe-backup-";
+const WEEK_SECS: u64 = 7 * 24 * 60 * 60;
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Uncompressed size accepted from an archive
+const MAX_RESTORE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Entries of the config directory that hold the user's data
+const INCLUDED: &[&str] = &[
+    "settings.json",
+    "profiles",
+    "customization",
+    "macros.json",
+    "reminders.json",
+    "planner",
+    "sessions.json",
+];
+
+/// A backup archive, as listed by `list_backups`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BackupInfo {
+    pub path: String,
+    /// Unix seconds
+    pub created_at: u64,
+    pub size_bytes: u64,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn config_dir() -> Result<PathBuf, String> {
+    settings::config_dir().ok_or_else(|| "No config directory available".to_string())
+}
+
+/// Archive name (`/`-separated) of a config-relative path, if it is part
+/// of the user's data
+fn entry_name(relative: &Path) -> Option<String> {
+    let parts = relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let first = parts.first()?;
+    INCLUDED.contains(first).then(|| parts.join("/"))
+}
+
+/// Creation time of a backup from its file name
+fn backup_time(file_name: &str) -> Option<u64> {
+    file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(".zip")?
+        .parse()
+        .ok()
+}
+
+/// Regular files under `path` (symlinks are not followed)
+fn collect(path: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return;
+    };
+    if metadata.is_dir() {
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                collect(&entry.path(), files);
+            }
+        }
+    } else if metadata.is_file() {
+        files.push(path.to_path_buf());
+    }
+}
+
+fn write_archive(config: &Path, files: &[PathBuf], path: &Path) -> Result<(), String> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for file in files {
+        let Some(name) = file.strip_prefix(config).ok().and_then(entry_name) else {
+            continue;
+        };
+        let bytes =
+            std::fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        zip.start_file(name, options)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        zip.write_all(&bytes)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Archive the user's data; returns the archive path
+pub fn create() -> Result<PathBuf, String> {
+    let config = config_dir()?;
+    let mut files = Vec::new();
+    for name in INCLUDED {
+        collect(&config.join(name), &mut files);
+    }
+    if files.is_empty() {
+        return Err("There is no user data to back up yet".to_string());
+    }
+
+    let dir = config.join(BACKUP_DIR_NAME);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}{}.zip", FILE_PREFIX, unix_now()));
+    let tmp_path = path.with_extension("zip.tmp");
+    if let Err(e) = write_archive(&config, &files, &tmp_path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    std::fs::rename(&tmp_path, &path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    info!("Backed up {} files to {}", files.len(), path.display());
+    Ok(path)
+}
+
+/// Backups in the backup folder, newest first
+pub fn list() -> Vec<BackupInfo> {
+    let Some(dir) = settings::config_dir().map(|dir| dir.join(BACKUP_DIR_NAME)) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let created_at = backup_time(entry.file_name().to_str()?)?;
+            Some(BackupInfo {
+                path: entry.path().display().to_string(),
+                created_at,
+                size_bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    backups
+}
+
+/// Remove all but the newest `keep` backups
+fn prune(keep: usize) {
+    for old in list().into_iter().skip(keep) {
+        match std::fs::remove_file(&old.path) {
+            Ok(()) => debug!("Removed old backup {}", old.path),
+            Err(e) => warn!("Failed to remove old backup {}: {}", old.path, e),
+        }
+    }
+}
+
+fn unpack(archive_path: &Path, staging: &Path) -> Result<usize, String> {
+    let file = std::fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open {}: {}", archive_path.display(), e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("{} is not a backup archive: {}", archive_path.display(), e))?;
+
+    let mut total = 0;
+    let mut count = 0;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| e.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry
+            .enclosed_name()
+            .and_then(|name| entry_name(&name))
+            .ok_or_else(|| format!("Unexpected file {} in backup", entry.name()))?;
+        let mut bytes = Vec::new();
+        entry
+            .by_ref()
+            .take(MAX_RESTORE_BYTES - total + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read {} from backup: {}", name, e))?;
+        total += bytes.len() as u64;
+        if total > MAX_RESTORE_BYTES {
+            return Err("Backup is too large".to_string());
+        }
+        if name == "settings.json" {
+            serde_json::from_slice::<serde_json::Value>(&bytes)
+                .map_err(|e| format!("Backup has an unreadable settings file: {}", e))?;
+        }
+
+        let target = staging.join(&name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&target, bytes)
+            .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        count += 1;
+    }
+    if count == 0 {
+        return Err("Backup is empty".to_string());
+    }
+    Ok(count)
+}
+
+/// Check the archive at `archive_path` and stage its files for the next
+/// start; returns the number of files
+pub fn stage_restore(archive_path: &Path) -> Result<usize, String> {
+    let config = config_dir()?;
+    let staging = config.join(format!("{}.tmp", PENDING_DIR_NAME));
+    let _ = std::fs::remove_dir_all(&staging);
+    let count = match unpack(archive_path, &staging) {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+
+    let pending = config.join(PENDING_DIR_NAME);
+    let _ = std::fs::remove_dir_all(&pending);
+    std::fs::rename(&staging, &pending)
+        .map_err(|e| format!("Failed to stage the restore: {}", e))?;
+    info!(
+        "Staged {} files from {} for restore",
+        count,
+        archive_path.display()
+    );
+    Ok(count)
+}
+
+/// Move a staged restore into place. Runs at startup, before settings and
+/// profiles are loaded.
+pub fn apply_pending_restore() {
+    let Some(config) = settings::config_dir() else {
+        return;
+    };
+    let pending = config.join(PENDING_DIR_NAME);
+    if !pending.is_dir() {
+        return;
+    }
+    for name in INCLUDED {
+        let staged = pending.join(name);
+        if !staged.exists() {
+            continue;
+        }
+        let current = config.join(name);
+        let removed = if current.is_dir() {
+            std::fs::remove_dir_all(&current)
+        } else if current.exists() {
+            std::fs::remove_file(&current)
+        } else {
+            Ok(())
+        };
+        if let Err(e) = removed.and_then(|_| std::fs::rename(&staged, &current)) {
+            warn!("Failed to restore {}: {}", name, e);
+        }
+    }
+    if let Err(e) = std::fs::remove_dir_all(&pending) {
+        warn!("Failed to clean up {}: {}", pending.display(), e);
+    }
+    info!("Restored user data from backup");
+}
+
+/// Weekly backups while `backups.weekly` is on
+pub fn spawn(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("backup".to_string())
+        .spawn(move || loop {
+            let backups = app.state::<SettingsState>().get().backups.validated();
+            if backups.weekly {
+                let recent = list()
+                    .first()
+                    .is_some_and(|newest| unix_now().saturating_sub(newest.created_at) < WEEK_SECS);
+                if !recent {
+                    match create() {
+                        Ok(_) => prune(backups.keep as usize),
+                        Err(e) => warn!("Weekly backup failed: {}", e),
+                    }
+                }
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start weekly backups: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_user_data_is_archived() {
+        assert_eq!(
+            entry_name(Path::new("profiles/alt/profile.json")).as_deref(),
+            Some("profiles/alt/profile.json")
+        );
+        assert_eq!(
+            entry_name(Path::new("settings.json")).as_deref(),
+            Some("settings.json")
+        );
+        assert_eq!(entry_name(Path::new("logs/pacdeluxe.log")), None);
+        assert_eq!(entry_name(Path::new("crash-history.json")), None);
+        assert_eq!(entry_name(Path::new("../settings.json")), None);
+        assert_eq!(entry_name(Path::new("")), None);
+
+        assert_eq!(
+            backup_time("pacdeluxe-backup-1760400000.zip"),
+            Some(1760400000)
+        );
+        assert_eq!(backup_time("pacdeluxe-backup-1760400000.zip.tmp"), None);
+        assert_eq!(backup_time("notes.zip"), None);
+    }
+}
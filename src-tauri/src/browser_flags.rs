@@ -0,0 +1,488 @@
+//! Browser Flags - Windows (WebView2)
+//!
+//! Builds `WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS` from the base performance
+//! flags plus the user's networking preset, high-precision mouse and Vulkan
+//! options. Flags only apply when WebView2 starts, so changes take effect
+//! after a restart.
+//!
+//! Vulkan rendering (`vulkan_rendering`, off by default) is left out on GPU
+//! drivers known to break it (`gpu_driver::VULKAN_BLOCKLIST`); the decision
+//! is logged and shown by `get_performance_advice`.
+//!
+//! The "modern networking" preset turns on QUIC/HTTP3 and zstd content
+//! encoding. Whether that helps depends on the network, so the injected
+//! runtime reports resource timing samples (`report_network_benchmark`)
+//! and a summary is kept per preset for comparison.
+//!
+//! `get_effective_flags` checks which requested flags actually reached the
+//! running WebView2 processes. Chromium only copies switches it recognizes
+//! to its child processes, so a flag that stays on the browser process alone
+//! is either browser-only or silently ignored by that runtime version.
+//!
+//! Linux: WebKitGTK has no equivalent switches; the preset is stored but
+//! has no effect.
+
+use crate::gpu_driver;
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use ts_rs::TS;
+
+/// Always-on performance flags
+const BASE_FLAGS: &[&str] = &[
+    "--enable-gpu-rasterization",
+    "--enable-zero-copy",
+    "--disable-background-timer-throttling",
+    "--disable-renderer-backgrounding",
+];
+
+/// Extra flags for the modern networking preset
+const MODERN_NETWORKING_FLAGS: &[&str] =
+    &["--enable-quic", "--enable-features=ZstdContentEncoding"];
+
+/// Extra flags for high-precision mouse mode: `pointerrawupdate` events are
+/// dispatched as input arrives instead of once per frame. Current runtimes
+/// ship it enabled; requesting it lets `get_effective_flags` confirm it.
+const HIGH_PRECISION_MOUSE_FLAGS: &[&str] = &["--enable-blink-features=PointerRawUpdate"];
+
+/// Chromium feature that renders through Vulkan instead of ANGLE/D3D11
+const VULKAN_FEATURE: &str = "Vulkan";
+
+const ENABLE_FEATURES: &str = "--enable-features=";
+
+/// Environment variable WebView2 reads its extra arguments from
+pub const BROWSER_ARGS_ENV: &str = "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS";
+
+const BENCHMARK_FILE_NAME: &str = "network-benchmark.json";
+/// Samples kept per report
+const MAX_SAMPLES: usize = 300;
+
+/// Networking flag preset
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum NetworkingPreset {
+    /// WebView2 defaults
+    #[default]
+    Default,
+    /// QUIC/HTTP3 and zstd content encoding
+    Modern,
+}
+
+impl NetworkingPreset {
+    fn flags(self) -> &'static [&'static str] {
+        match self {
+            NetworkingPreset::Default => &[],
+            NetworkingPreset::Modern => MODERN_NETWORKING_FLAGS,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            NetworkingPreset::Default => "Default",
+            NetworkingPreset::Modern => "Modern",
+        }
+    }
+}
+
+/// Preset the running webview was started with
+static ACTIVE_PRESET: OnceLock<NetworkingPreset> = OnceLock::new();
+
+/// Driver version Vulkan was left out for in this run
+static VULKAN_BLOCKED_DRIVER: OnceLock<String> = OnceLock::new();
+
+/// Browser arguments for a preset, mouse mode and Vulkan choice
+pub fn additional_browser_args(
+    preset: NetworkingPreset,
+    high_precision_mouse: bool,
+    vulkan: bool,
+) -> String {
+    let mouse_flags = if high_precision_mouse {
+        HIGH_PRECISION_MOUSE_FLAGS
+    } else {
+        &[]
+    };
+    let mut args: Vec<String> = BASE_FLAGS
+        .iter()
+        .chain(preset.flags())
+        .chain(mouse_flags)
+        .map(|flag| flag.to_string())
+        .collect();
+    if vulkan {
+        // A second --enable-features would replace the first list
+        match args.iter_mut().find(|arg| arg.starts_with(ENABLE_FEATURES)) {
+            Some(features) => {
+                features.push(',');
+                features.push_str(VULKAN_FEATURE);
+            }
+            None => args.push(format!("{}{}", ENABLE_FEATURES, VULKAN_FEATURE)),
+        }
+    }
+    args.join(" ")
+}
+
+/// Vulkan as requested, unless the installed driver is blocklisted
+fn vulkan_allowed(requested: bool) -> bool {
+    if !requested {
+        return false;
+    }
+    match gpu_driver::detect() {
+        Some(driver) if driver.vulkan_blocked => {
+            let version = driver.display_version.unwrap_or(driver.version);
+            warn!(
+                "GPU driver {} ({:?}) is known to break Vulkan in WebView2; starting without it",
+                version, driver.vendor
+            );
+            let _ = VULKAN_BLOCKED_DRIVER.set(version);
+            false
+        }
+        _ => true,
+    }
+}
+
+/// Record the preset used at startup and return its browser arguments
+pub fn activate(preset: NetworkingPreset, high_precision_mouse: bool, vulkan: bool) -> String {
+    let _ = ACTIVE_PRESET.set(preset);
+    additional_browser_args(preset, high_precision_mouse, vulkan_allowed(vulkan))
+}
+
+/// Driver version Vulkan was dropped for, if it was requested and blocked
+pub fn vulkan_blocked_driver() -> Option<String> {
+    VULKAN_BLOCKED_DRIVER.get().cloned()
+}
+
+/// Preset in effect for this run
+pub fn active_preset() -> NetworkingPreset {
+    ACTIVE_PRESET.get().copied().unwrap_or_default()
+}
+
+/// One resource timing sample from the page (URLs are not sent)
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetworkSample {
+    /// `nextHopProtocol`, e.g. "h3", "h2", "http/1.1"
+    pub protocol: String,
+    /// Time to first byte; `None` when the origin hides timing details
+    pub ttfb_ms: Option<f64>,
+    pub duration_ms: f64,
+}
+
+/// Summary of one benchmark run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetworkBenchmark {
+    pub preset: NetworkingPreset,
+    pub samples: usize,
+    /// Requests per negotiated protocol
+    pub protocols: BTreeMap<String, u32>,
+    pub median_ttfb_ms: Option<f64>,
+    pub median_duration_ms: Option<f64>,
+    /// Seconds since the Unix epoch
+    pub recorded_at: u64,
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    values.retain(|v| v.is_finite() && *v >= 0.0);
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// Summarize a batch of samples for the preset in effect
+pub fn summarize(preset: NetworkingPreset, samples: &[NetworkSample]) -> NetworkBenchmark {
+    let samples = &samples[..samples.len().min(MAX_SAMPLES)];
+    let mut protocols = BTreeMap::new();
+    for sample in samples {
+        let protocol = if sample.protocol.is_empty() {
+            "unknown"
+        } else {
+            sample.protocol.as_str()
+        };
+        *protocols.entry(protocol.to_string()).or_insert(0) += 1;
+    }
+    NetworkBenchmark {
+        preset,
+        samples: samples.len(),
+        protocols,
+        median_ttfb_ms: median(samples.iter().filter_map(|s| s.ttfb_ms).collect()),
+        median_duration_ms: median(samples.iter().map(|s| s.duration_ms).collect()),
+        recorded_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    }
+}
+
+fn benchmark_path() -> Option<PathBuf> {
+    let dir = settings::config_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(BENCHMARK_FILE_NAME))
+}
+
+/// Latest benchmark per preset
+pub fn load_benchmarks() -> BTreeMap<String, NetworkBenchmark> {
+    benchmark_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Store a benchmark as the latest result for its preset
+pub fn save_benchmark(benchmark: &NetworkBenchmark) -> Result<(), String> {
+    let path = benchmark_path().ok_or("No config directory")?;
+    let mut all = load_benchmarks();
+    all.insert(benchmark.preset.key().to_string(), benchmark.clone());
+    let json = serde_json::to_string_pretty(&all).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save network benchmark: {}", e))?;
+    info!(
+        "Network benchmark ({:?}): {} samples, median TTFB {:?} ms, protocols {:?}",
+        benchmark.preset, benchmark.samples, benchmark.median_ttfb_ms, benchmark.protocols
+    );
+    Ok(())
+}
+
+/// Where a requested flag was found
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum FlagStatus {
+    /// Passed on to at least one child process, so Chromium recognized it
+    Propagated,
+    /// Only on the browser process command line: browser-only, or ignored
+    BrowserOnly,
+    /// Not on the browser process command line at all
+    Missing,
+}
+
+/// One requested flag and what happened to it
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EffectiveFlag {
+    /// Switch as requested; `--enable-features` lists are split per feature
+    pub flag: String,
+    pub status: FlagStatus,
+    /// `--type=` of the child processes that received it
+    pub process_types: Vec<String>,
+}
+
+/// Report for `get_effective_flags`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EffectiveFlags {
+    /// Value of `WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS` for this run
+    pub requested: String,
+    pub preset: NetworkingPreset,
+    /// Whether a WebView2 browser process belonging to us was found
+    pub browser_found: bool,
+    pub flags: Vec<EffectiveFlag>,
+}
+
+/// A requested switch, ready to look up in command lines
+enum Requested<'a> {
+    /// `--name` or `--name=value`, matched by exact token
+    Switch(&'a str),
+    /// One entry of `--enable-features=`/`--disable-features=`
+    Feature { switch: &'a str, feature: &'a str },
+}
+
+impl Requested<'_> {
+    fn label(&self) -> String {
+        match self {
+            Requested::Switch(token) => token.to_string(),
+            Requested::Feature { switch, feature } => format!("{}={}", switch, feature),
+        }
+    }
+
+    fn matches(&self, args: &[String]) -> bool {
+        match self {
+            Requested::Switch(token) => args.iter().any(|arg| arg == token),
+            Requested::Feature { switch, feature } => args.iter().any(|arg| {
+                arg.strip_prefix(switch)
+                    .and_then(|rest| rest.strip_prefix('='))
+                    .is_some_and(|list| list.split(',').any(|f| f == *feature))
+            }),
+        }
+    }
+}
+
+fn parse_requested(requested: &str) -> Vec<Requested<'_>> {
+    let mut parsed = Vec::new();
+    for token in requested.split_whitespace() {
+        match token.split_once('=') {
+            Some((
+                switch @ ("--enable-features" | "--disable-features" | "--enable-blink-features"),
+                list,
+            )) => {
+                parsed.extend(
+                    list.split(',')
+                        .filter(|feature| !feature.is_empty())
+                        .map(|feature| Requested::Feature { switch, feature }),
+                );
+            }
+            _ => parsed.push(Requested::Switch(token)),
+        }
+    }
+    parsed
+}
+
+/// Value of a `--type=` switch, if any
+fn process_type(args: &[String]) -> Option<&str> {
+    args.iter().find_map(|arg| arg.strip_prefix("--type="))
+}
+
+/// Compare requested flags with the command lines of the webview processes.
+/// The browser process is the one without `--type=`.
+fn evaluate(requested: &str, processes: &[Vec<String>]) -> (bool, Vec<EffectiveFlag>) {
+    let browser = processes.iter().find(|args| process_type(args).is_none());
+    let flags = parse_requested(requested)
+        .into_iter()
+        .map(|flag| {
+            let mut process_types: Vec<String> = processes
+                .iter()
+                .filter_map(|args| process_type(args).filter(|_| flag.matches(args)))
+                .map(str::to_string)
+                .collect();
+            process_types.sort();
+            process_types.dedup();
+            let status = if !process_types.is_empty() {
+                FlagStatus::Propagated
+            } else if browser.is_some_and(|args| flag.matches(args)) {
+                FlagStatus::BrowserOnly
+            } else {
+                FlagStatus::Missing
+            };
+            EffectiveFlag {
+                flag: flag.label(),
+                status,
+                process_types,
+            }
+        })
+        .collect();
+    (browser.is_some(), flags)
+}
+
+/// Command lines of the WebView2 processes descended from this process
+#[cfg(target_os = "windows")]
+fn webview_command_lines() -> Vec<Vec<String>> {
+    crate::rendering::webview_processes()
+        .into_iter()
+        .map(|process| process.args)
+        .collect()
+}
+
+/// WebKitGTK takes no browser switches
+#[cfg(target_os = "linux")]
+fn webview_command_lines() -> Vec<Vec<String>> {
+    Vec::new()
+}
+
+/// Which of this run's requested browser flags took effect
+pub fn effective_flags() -> EffectiveFlags {
+    let requested = std::env::var(BROWSER_ARGS_ENV).unwrap_or_default();
+    let (browser_found, flags) = evaluate(&requested, &webview_command_lines());
+    EffectiveFlags {
+        requested,
+        preset: active_preset(),
+        browser_found,
+        flags,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(protocol: &str, ttfb_ms: Option<f64>, duration_ms: f64) -> NetworkSample {
+        NetworkSample {
+            protocol: protocol.to_string(),
+            ttfb_ms,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn default_preset_keeps_base_flags() {
+        assert_eq!(
+            additional_browser_args(NetworkingPreset::Default, false, false),
+            "--enable-gpu-rasterization --enable-zero-copy --disable-background-timer-throttling --disable-renderer-backgrounding"
+        );
+        assert!(additional_browser_args(NetworkingPreset::Modern, false, false).ends_with(
+            "--disable-renderer-backgrounding --enable-quic --enable-features=ZstdContentEncoding"
+        ));
+    }
+
+    #[test]
+    fn high_precision_mouse_adds_its_own_switch() {
+        // A second --enable-features would replace the preset's list
+        let args = additional_browser_args(NetworkingPreset::Modern, true, false);
+        assert_eq!(args.matches("--enable-features=").count(), 1);
+        assert!(args.ends_with("--enable-blink-features=PointerRawUpdate"));
+    }
+
+    #[test]
+    fn vulkan_joins_the_existing_feature_list() {
+        let args = additional_browser_args(NetworkingPreset::Modern, true, true);
+        assert_eq!(args.matches("--enable-features=").count(), 1);
+        assert!(args.contains("--enable-features=ZstdContentEncoding,Vulkan"));
+        assert!(
+            additional_browser_args(NetworkingPreset::Default, false, true)
+                .ends_with("--disable-renderer-backgrounding --enable-features=Vulkan")
+        );
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn flags_are_classified_by_where_they_appear() {
+        let processes = [
+            args("msedgewebview2.exe --enable-quic --enable-zero-copy --old-flag --enable-features=ZstdContentEncoding"),
+            args("msedgewebview2.exe --type=gpu-process --enable-zero-copy"),
+            args("msedgewebview2.exe --type=renderer --enable-zero-copy --enable-features=Foo,ZstdContentEncoding"),
+        ];
+        let (browser_found, flags) = evaluate(
+            "--enable-zero-copy --old-flag --enable-features=ZstdContentEncoding,Bar --missing",
+            &processes,
+        );
+        assert!(browser_found);
+        let status: Vec<(&str, FlagStatus)> =
+            flags.iter().map(|f| (f.flag.as_str(), f.status)).collect();
+        assert_eq!(
+            status,
+            [
+                ("--enable-zero-copy", FlagStatus::Propagated),
+                ("--old-flag", FlagStatus::BrowserOnly),
+                (
+                    "--enable-features=ZstdContentEncoding",
+                    FlagStatus::Propagated
+                ),
+                ("--enable-features=Bar", FlagStatus::Missing),
+                ("--missing", FlagStatus::Missing),
+            ]
+        );
+        assert_eq!(flags[0].process_types, ["gpu-process", "renderer"]);
+        assert_eq!(flags[2].process_types, ["renderer"]);
+    }
+
+    #[test]
+    fn summary_counts_protocols_and_medians() {
+        let summary = summarize(
+            NetworkingPreset::Modern,
+            &[
+                sample("h3", Some(40.0), 100.0),
+                sample("h3", None, 200.0),
+                sample("h2", Some(60.0), 300.0),
+                sample("", Some(80.0), 400.0),
+            ],
+        );
+        assert_eq!(summary.samples, 4);
+        assert_eq!(summary.protocols["h3"], 2);
+        assert_eq!(summary.protocols["unknown"], 1);
+        assert_eq!(summary.median_ttfb_ms, Some(60.0));
+        assert_eq!(summary.median_duration_ms, Some(250.0));
+    }
+}
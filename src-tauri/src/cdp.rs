@@ -0,0 +1,502 @@
+//! DevTools Protocol Client - Windows (WebView2)
+//!
+//! Structured access to Chrome DevTools Protocol domains for diagnostics:
+//! performance metrics, network condition emulation, heap snapshots,
+//! screenshots and tracing. Uses WebView2's in-process DevTools channel
+//! (`CallDevToolsProtocolMethod`), so no remote-debugging port is opened.
+//!
+//! Only an allowlist of diagnostic methods can be called. Nothing here can
+//! evaluate script, read or modify the DOM, or dispatch input.
+//!
+//! Linux: WebKitGTK has no DevTools protocol; every call returns an error.
+
+use crate::commands;
+use crate::ws_inspector::WsInspector;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, WebviewWindow};
+use tracing::{debug, info, warn};
+
+/// How long to wait for a single method response
+const CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// CDP methods (exact names, or `Domain.` prefixes) the client may call
+const ALLOWED_METHODS: &[&str] = &[
+    "Performance.",
+    "Memory.",
+    "HeapProfiler.",
+    "Tracing.",
+    "Network.enable",
+    "Network.disable",
+    "Network.emulateNetworkConditions",
+    "Network.clearBrowserCache",
+    "Emulation.setCPUThrottlingRate",
+    "Page.captureScreenshot",
+];
+
+/// CDP events the client may subscribe to
+const ALLOWED_EVENTS: &[&str] = &[
+    "HeapProfiler.",
+    "Tracing.",
+    "Performance.",
+    // Frame sizes for the WebSocket inspector; payloads are dropped on arrival
+    "Network.webSocketFrameSent",
+    "Network.webSocketFrameReceived",
+];
+
+fn is_allowed(name: &str, allowlist: &[&str]) -> bool {
+    allowlist.iter().any(|allowed| {
+        if allowed.ends_with('.') {
+            name.starts_with(allowed) && name.len() > allowed.len()
+        } else {
+            name == *allowed
+        }
+    })
+}
+
+/// Whether `method` is on the diagnostic allowlist
+pub fn is_allowed_method(method: &str) -> bool {
+    is_allowed(method, ALLOWED_METHODS)
+}
+
+/// Whether `event` is on the diagnostic allowlist
+pub fn is_allowed_event(event: &str) -> bool {
+    is_allowed(event, ALLOWED_EVENTS)
+}
+
+/// Call a DevTools Protocol method and return its JSON result
+pub async fn call(window: &WebviewWindow, method: &str, params: Value) -> Result<Value, String> {
+    call_with_timeout(window, method, params, CALL_TIMEOUT).await
+}
+
+/// `call` with a custom response timeout, for slow methods such as heap snapshots
+#[cfg(target_os = "windows")]
+pub async fn call_with_timeout(
+    window: &WebviewWindow,
+    method: &str,
+    params: Value,
+    timeout: Duration,
+) -> Result<Value, String> {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows_core::HSTRING;
+
+    if !is_allowed_method(method) {
+        return Err(format!("DevTools method not allowed: {}", method));
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel::<Result<String, String>>();
+    let method_name = method.to_string();
+    let params_json = params.to_string();
+
+    window
+        .with_webview(move |webview| unsafe {
+            let send_error = tx.clone();
+            let result = (|| -> windows_core::Result<()> {
+                let core = webview.controller().CoreWebView2()?;
+                let handler = CallDevToolsProtocolMethodCompletedHandler::create(Box::new(
+                    move |status: windows_core::Result<()>, json: String| {
+                        let _ = tx.send(status.map(|_| json).map_err(|e| e.to_string()));
+                        Ok(())
+                    },
+                ));
+                core.CallDevToolsProtocolMethod(
+                    &HSTRING::from(method_name.as_str()),
+                    &HSTRING::from(params_json.as_str()),
+                    &handler,
+                )
+            })();
+            if let Err(e) = result {
+                let _ = send_error.send(Err(e.to_string()));
+            }
+        })
+        .map_err(|e| format!("Failed to access webview: {}", e))?;
+
+    let json = tauri::async_runtime::spawn_blocking(move || {
+        rx.recv_timeout(timeout)
+            .map_err(|_| "DevTools call timed out".to_string())?
+    })
+    .await
+    .map_err(|e| format!("DevTools call task failed: {}", e))??;
+
+    debug!("CDP {} -> {} bytes", method, json.len());
+    serde_json::from_str(&json).map_err(|e| format!("Invalid DevTools response: {}", e))
+}
+
+/// Subscribe to a DevTools Protocol event.
+/// `on_event` receives each event's parameters as JSON on the UI thread and
+/// should hand work off quickly. Subscriptions live as long as the webview.
+#[cfg(target_os = "windows")]
+pub async fn subscribe<F>(window: &WebviewWindow, event: &str, on_event: F) -> Result<(), String>
+where
+    F: Fn(Value) + Send + 'static,
+{
+    use webview2_com::DevToolsProtocolEventReceivedEventHandler;
+    use windows_core::{HSTRING, PWSTR};
+
+    if !is_allowed_event(event) {
+        return Err(format!("DevTools event not allowed: {}", event));
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel::<Result<(), String>>();
+    let event_name = event.to_string();
+    window
+        .with_webview(move |webview| unsafe {
+            let result = (|| -> windows_core::Result<()> {
+                let core = webview.controller().CoreWebView2()?;
+                let receiver =
+                    core.GetDevToolsProtocolEventReceiver(&HSTRING::from(event_name.as_str()))?;
+                let handler = DevToolsProtocolEventReceivedEventHandler::create(Box::new(
+                    move |_sender, args| {
+                        if let Some(args) = args {
+                            let mut json = PWSTR::null();
+                            args.ParameterObjectAsJson(&mut json)?;
+                            let json = webview2_com::take_pwstr(json);
+                            if let Ok(value) = serde_json::from_str(&json) {
+                                on_event(value);
+                            }
+                        }
+                        Ok(())
+                    },
+                ));
+                let mut token = 0;
+                receiver.add_DevToolsProtocolEventReceived(&handler, &mut token)
+            })();
+            let _ = tx
+                .send(result.map_err(|e| format!("Failed to subscribe to {}: {}", event_name, e)));
+        })
+        .map_err(|e| format!("Failed to access webview: {}", e))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        rx.recv_timeout(CALL_TIMEOUT)
+            .map_err(|_| "DevTools subscription timed out".to_string())?
+    })
+    .await
+    .map_err(|e| format!("DevTools subscription task failed: {}", e))?
+}
+
+#[cfg(target_os = "linux")]
+pub async fn call_with_timeout(
+    _window: &WebviewWindow,
+    method: &str,
+    _params: Value,
+    _timeout: Duration,
+) -> Result<Value, String> {
+    Err(format!(
+        "DevTools protocol is not available on WebKitGTK ({})",
+        method
+    ))
+}
+
+#[cfg(target_os = "linux")]
+pub async fn subscribe<F>(_window: &WebviewWindow, event: &str, _on_event: F) -> Result<(), String>
+where
+    F: Fn(Value) + Send + 'static,
+{
+    Err(format!(
+        "DevTools protocol is not available on WebKitGTK ({})",
+        event
+    ))
+}
+
+/// Network condition presets for reproducing "laggy connection" reports
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum NetworkPreset {
+    /// No emulation (real network)
+    Off,
+    Offline,
+    /// 400 ms RTT, 400 kbps each way
+    Slow3G,
+    /// 150 ms RTT, 1.6 Mbps down / 750 kbps up
+    Fast3G,
+    /// 300 ms RTT, unlimited bandwidth
+    HighLatency,
+    Custom {
+        latency_ms: u32,
+        /// 0 = unlimited
+        download_kbps: u32,
+        /// 0 = unlimited
+        upload_kbps: u32,
+    },
+}
+
+/// Resolved network conditions (CDP `Network.emulateNetworkConditions` shape)
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct NetworkConditions {
+    pub offline: bool,
+    pub latency_ms: u32,
+    pub download_kbps: u32,
+    pub upload_kbps: u32,
+}
+
+impl NetworkPreset {
+    /// Conditions to emulate, or None to disable emulation
+    pub fn conditions(self) -> Option<NetworkConditions> {
+        let (offline, latency_ms, download_kbps, upload_kbps) = match self {
+            NetworkPreset::Off => return None,
+            NetworkPreset::Offline => (true, 0, 0, 0),
+            NetworkPreset::Slow3G => (false, 400, 400, 400),
+            NetworkPreset::Fast3G => (false, 150, 1600, 750),
+            NetworkPreset::HighLatency => (false, 300, 0, 0),
+            NetworkPreset::Custom {
+                latency_ms,
+                download_kbps,
+                upload_kbps,
+            } => (false, latency_ms, download_kbps, upload_kbps),
+        };
+        Some(NetworkConditions {
+            offline,
+            latency_ms,
+            download_kbps,
+            upload_kbps,
+        })
+    }
+}
+
+/// CDP throughput is bytes/second, with -1 meaning unlimited
+fn kbps_to_throughput(kbps: u32) -> f64 {
+    if kbps == 0 {
+        -1.0
+    } else {
+        kbps as f64 * 1000.0 / 8.0
+    }
+}
+
+fn network_conditions_params(conditions: Option<NetworkConditions>) -> Value {
+    let c = conditions.unwrap_or(NetworkConditions {
+        offline: false,
+        latency_ms: 0,
+        download_kbps: 0,
+        upload_kbps: 0,
+    });
+    json!({
+        "offline": c.offline,
+        "latency": c.latency_ms,
+        "downloadThroughput": kbps_to_throughput(c.download_kbps),
+        "uploadThroughput": kbps_to_throughput(c.upload_kbps),
+    })
+}
+
+/// Apply network condition emulation to the webview (None = real network)
+pub async fn emulate_network_conditions(
+    window: &WebviewWindow,
+    conditions: Option<NetworkConditions>,
+) -> Result<(), String> {
+    call(window, "Network.enable", json!({})).await?;
+    call(
+        window,
+        "Network.emulateNetworkConditions",
+        network_conditions_params(conditions),
+    )
+    .await?;
+    // The WebSocket inspector needs the domain too
+    let inspecting = window
+        .app_handle()
+        .try_state::<WsInspector>()
+        .is_some_and(|inspector| inspector.is_capturing());
+    if conditions.is_none() && !inspecting {
+        call(window, "Network.disable", json!({})).await?;
+    }
+    Ok(())
+}
+
+/// How long a heap snapshot may take (large heaps take tens of seconds)
+const HEAP_SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Destination for `HeapProfiler.addHeapSnapshotChunk` events while a
+/// snapshot is being captured. The event handler is registered once and
+/// writes into whatever sink is active.
+static HEAP_SNAPSHOT_SINK: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+static HEAP_SNAPSHOT_SUBSCRIBED: AtomicBool = AtomicBool::new(false);
+
+fn write_heap_snapshot_chunk(params: Value) {
+    let Some(chunk) = params.get("chunk").and_then(Value::as_str) else {
+        return;
+    };
+    let mut sink = HEAP_SNAPSHOT_SINK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(writer) = sink.as_mut() {
+        if let Err(e) = writer.write_all(chunk.as_bytes()) {
+            warn!("Failed to write heap snapshot chunk: {}", e);
+            *sink = None;
+        }
+    }
+}
+
+/// Capture a V8 heap snapshot of the game page into `path`.
+/// The file opens in Chrome DevTools (Memory tab > Load).
+pub async fn capture_heap_snapshot(window: &WebviewWindow, path: &Path) -> Result<u64, String> {
+    if !HEAP_SNAPSHOT_SUBSCRIBED.swap(true, Ordering::SeqCst) {
+        if let Err(e) = subscribe(
+            window,
+            "HeapProfiler.addHeapSnapshotChunk",
+            write_heap_snapshot_chunk,
+        )
+        .await
+        {
+            HEAP_SNAPSHOT_SUBSCRIBED.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+    }
+
+    {
+        let mut sink = HEAP_SNAPSHOT_SINK.lock().unwrap_or_else(|e| e.into_inner());
+        if sink.is_some() {
+            return Err("A heap snapshot is already being captured".to_string());
+        }
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        *sink = Some(BufWriter::new(file));
+    }
+
+    // Chunks are delivered before takeHeapSnapshot responds
+    let result = async {
+        call(window, "HeapProfiler.enable", json!({})).await?;
+        call_with_timeout(
+            window,
+            "HeapProfiler.takeHeapSnapshot",
+            json!({ "reportProgress": false }),
+            HEAP_SNAPSHOT_TIMEOUT,
+        )
+        .await?;
+        call(window, "HeapProfiler.disable", json!({})).await
+    }
+    .await;
+
+    let writer = HEAP_SNAPSHOT_SINK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(path);
+        return Err(e);
+    }
+    let Some(mut writer) = writer else {
+        let _ = std::fs::remove_file(path);
+        return Err("Heap snapshot write failed".to_string());
+    };
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if size == 0 {
+        let _ = std::fs::remove_file(path);
+        return Err("Heap snapshot was empty (no chunks arrived)".to_string());
+    }
+    info!("Heap snapshot saved to {} ({} bytes)", path.display(), size);
+    Ok(size)
+}
+
+/// DevTools subscriptions and emulation belong to the webview; subscribe
+/// again on a rebuilt one and re-apply `set_network_conditions`
+pub fn resume_on_new_webview(app: &AppHandle) {
+    HEAP_SNAPSHOT_SUBSCRIBED.store(false, Ordering::SeqCst);
+    let Some(conditions) = commands::network_conditions() else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(window) = app.get_webview_window("main") else {
+            return;
+        };
+        match emulate_network_conditions(&window, Some(conditions)).await {
+            Ok(()) => info!("Network emulation re-applied to the new webview"),
+            Err(e) => warn!("Network emulation applied to proxy only (webview: {})", e),
+        }
+    });
+}
+
+/// Capture the visible page as PNG bytes
+pub async fn capture_screenshot(window: &WebviewWindow) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+
+    let result = call(window, "Page.captureScreenshot", json!({ "format": "png" })).await?;
+    let data = result
+        .get("data")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "Screenshot response had no data".to_string())?;
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid screenshot data: {}", e))
+}
+
+/// `Performance.getMetrics` as a name -> value map
+pub async fn performance_metrics(window: &WebviewWindow) -> Result<HashMap<String, f64>, String> {
+    // Performance.enable is idempotent and required before getMetrics
+    call(window, "Performance.enable", json!({})).await?;
+    let result = call(window, "Performance.getMetrics", json!({})).await?;
+    Ok(parse_metrics(&result))
+}
+
+fn parse_metrics(result: &Value) -> HashMap<String, f64> {
+    result
+        .get("metrics")
+        .and_then(Value::as_array)
+        .map(|metrics| {
+            metrics
+                .iter()
+                .filter_map(|m| {
+                    Some((
+                        m.get("name")?.as_str()?.to_string(),
+                        m.get("value")?.as_f64()?,
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostic_methods_are_allowed() {
+        assert!(is_allowed_method("Performance.getMetrics"));
+        assert!(is_allowed_method("HeapProfiler.takeHeapSnapshot"));
+        assert!(is_allowed_method("Network.emulateNetworkConditions"));
+        assert!(is_allowed_method("Network.clearBrowserCache"));
+        assert!(is_allowed_event("Tracing.dataCollected"));
+        assert!(is_allowed_event("Network.webSocketFrameReceived"));
+    }
+
+    #[test]
+    fn page_access_methods_are_rejected() {
+        assert!(!is_allowed_method("Runtime.evaluate"));
+        assert!(!is_allowed_method("DOM.getDocument"));
+        assert!(!is_allowed_method("Input.dispatchMouseEvent"));
+        assert!(!is_allowed_method("Network.getResponseBody"));
+        assert!(!is_allowed_method("Performance."));
+        assert!(!is_allowed_event("Network.responseReceived"));
+    }
+
+    #[test]
+    fn network_presets_map_to_cdp_units() {
+        assert_eq!(NetworkPreset::Off.conditions(), None);
+        let params = network_conditions_params(NetworkPreset::Slow3G.conditions());
+        assert_eq!(params["latency"], 400);
+        assert_eq!(params["downloadThroughput"], 50000.0);
+        let params = network_conditions_params(NetworkPreset::HighLatency.conditions());
+        assert_eq!(params["uploadThroughput"], -1.0);
+        assert_eq!(params["offline"], false);
+    }
+
+    #[test]
+    fn metrics_are_parsed_by_name() {
+        let result = json!({
+            "metrics": [
+                { "name": "JSHeapUsedSize", "value": 1024.0 },
+                { "name": "Nodes", "value": 12 }
+            ]
+        });
+        let metrics = parse_metrics(&result);
+        assert_eq!(metrics.get("JSHeapUsedSize"), Some(&1024.0));
+        assert_eq!(metrics.get("Nodes"), Some(&12.0));
+    }
+}
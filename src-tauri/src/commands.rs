@@ -3,23 +3,87 @@
 //! IPC commands for performance monitoring and window control.
 //! No game state access.
 
+use crate::advisor::{self, AdvisorInputs, PerformanceAdvice};
+use crate::audio;
+use crate::backup::{self, BackupInfo};
+use crate::browser_flags::{self, NetworkBenchmark, NetworkSample, NetworkingPreset};
+use crate::cdp::{self, NetworkConditions, NetworkPreset};
+use crate::contrast::{self, ContrastInfo, ContrastMode};
+use crate::customization::{self, CustomizationKind, Customizations};
+use crate::dashboard;
+use crate::deep_links;
+use crate::display_watch;
+use crate::elevation_policy;
+use crate::gamma::{self, GammaPreset};
+use crate::gpu_driver::{self, GpuDriver};
+use crate::hdr_control::{self, HdrSwitch};
+use crate::health::{self, HealthReport};
+use crate::history::{PerformanceHistory, PerformanceSample};
+use crate::http_log::{HttpLog, HttpLogEntry, HttpSource};
+use crate::idle_throttle;
+use crate::jank::{JankStats, JankTracker, LongTaskReport};
+use crate::keybinds::{KeyBindings, KeyRemap};
+use crate::load_sim::{self, LoadRun};
+use crate::low_spec;
+use crate::macros::{self, MacroAction, MacroStore, UiMacro};
+use crate::maintenance::{self, UpstreamIssue, UpstreamState};
+use crate::mouse_precision;
+use crate::observers::{ModuleInit, ObserverStats, ObserverTracker, ScriptBudget, WatcherStats};
+use crate::overlay_stream::OverlayStream;
+use crate::page_events::{self, PageEvent, ToastLevel};
+use crate::page_state::{GamePhase, PageState};
 use crate::performance::{
-    get_elevation_telemetry, get_gpu_stats as get_gpu_stats_impl, get_hdr_info, ElevationTelemetry,
-    GpuStats, HdrInfo, PerformanceMonitor, PerformanceStats,
+    get_elevation_telemetry, ElevationTelemetry, GpuStats, HdrInfo, PerformanceMonitor,
+    PerformanceSnapshot, PerformanceStats,
 };
+use crate::planner::{self, TeamComp};
+use crate::power_guard::{self, PowerEmergency, PowerGuard};
+use crate::process_failures::{self, ProcessFailure};
+use crate::profile::{self, OverlayState, ProfileInfo, ProfileSettings, ProfileState};
+use crate::quick_reference::{self, QuickReference};
+use crate::recovery::{self, FreezeAction, FreezeWatchdog, ReloadKind};
+use crate::refresh_rate::{self, RefreshRateInfo};
+use crate::reminders::{Reminder, ReminderStore};
+use crate::rendering;
+use crate::report::{self, MatchLog, ReportFormat, SessionReport};
+use crate::resource_limits;
+use crate::safe_mode::{self, SafeModeStatus};
+use crate::scheduler::{self, ScheduledTask};
+use crate::self_test::{self, SelfTestReport};
+use crate::server_status::{self, ServerStatus, ServerStatusStore};
+use crate::session::{PlayTimeSummary, SessionStore};
+use crate::settings::{
+    self, AttentionSettings, AudioMixSettings, BackupSettings, BreakReminderSettings, CspMode,
+    CursorSettings, ElevationPolicySettings, GammaSettings, GraphicsSettings, IdleThrottleSettings,
+    InjectionFlags, MisdropGuardSettings, OverlayLayout, OverlaySettings, PowerGuardSettings,
+    PowerProfile, QuickReferenceSettings, ResetScope, ResourceLimitSettings, ServerStatusSettings,
+    Settings, SettingsState, SoftReloadSettings, StartMode, ThemeFontSettings, TtsSettings,
+    TurboGuardSettings, UpdateCheckSettings, WsInspectorSettings,
+};
+use crate::soft_reload::SoftReloadState;
+use crate::system_keys;
+use crate::tts;
+use crate::updates::{self, UpdateState, UpdateTracker};
+use crate::ws_inspector::{self, WsInspector, WsStats};
 use reqwest::{
     header::{HeaderMap, HeaderName, HeaderValue},
     Method, Url,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Mutex, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 use tauri::{AppHandle, Emitter, Manager, State};
-use tauri_plugin_updater::UpdaterExt;
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tracing::{debug, info, warn};
+use ts_rs::TS;
 
 /// Window display mode
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
 pub enum WindowMode {
+    #[default]
     Windowed,
     Fullscreen,
     BorderlessWindowed,
@@ -30,6 +94,16 @@ pub static CURRENT_WINDOW_MODE: std::sync::atomic::AtomicU8 = std::sync::atomic:
 // 0 = Windowed, 1 = Fullscreen, 2 = BorderlessWindowed
 
 impl WindowMode {
+    /// Mode the main window is in
+    pub fn current() -> Self {
+        Self::from_u8(CURRENT_WINDOW_MODE.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    /// Record the startup mode before the main window is built in it
+    pub fn set_startup(self) {
+        CURRENT_WINDOW_MODE.store(self.to_u8(), std::sync::atomic::Ordering::SeqCst);
+    }
+
     fn to_u8(self) -> u8 {
         match self {
             WindowMode::Windowed => 0,
@@ -47,6 +121,20 @@ impl WindowMode {
     }
 }
 
+/// Network emulation currently applied via `set_network_conditions`.
+/// The proxy honors it too, since proxied requests bypass the webview network stack.
+static NETWORK_CONDITIONS: Mutex<Option<NetworkConditions>> = Mutex::new(None);
+
+/// Whether network condition emulation is on
+pub(crate) fn network_emulation_active() -> bool {
+    network_conditions().is_some()
+}
+
+/// Network emulation currently applied, if any
+pub(crate) fn network_conditions() -> Option<NetworkConditions> {
+    *NETWORK_CONDITIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 const PROD_ORIGIN: &str = "https://pokemon-auto-chess.com";
 const PROD_HOST: &str = "pokemon-auto-chess.com";
 const COMMUNITY_SERVERS_MANIFEST_URL: &str =
@@ -139,10 +227,50 @@ fn is_safe_proxy_redirect_target(url: &Url) -> bool {
     host == PROD_HOST || host.ends_with(&format!(".{}", PROD_HOST))
 }
 
+/// Apply `set_network_conditions` emulation to a proxied request: fail when
+/// offline, otherwise wait out the configured latency. Returns the
+/// conditions so the caller can pace the transfer by bandwidth.
+async fn apply_emulated_network_conditions() -> Result<Option<NetworkConditions>, String> {
+    let Some(conditions) = network_conditions() else {
+        return Ok(None);
+    };
+    if conditions.offline {
+        return Err("Network emulation: offline".to_string());
+    }
+    if conditions.latency_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(conditions.latency_ms as u64)).await;
+    }
+    Ok(Some(conditions))
+}
+
+/// How long `bytes` take at `kbps` (0 = unlimited)
+fn emulated_transfer_time(bytes: usize, kbps: u32) -> Duration {
+    if kbps == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(bytes as f64 * 8.0 / (kbps as f64 * 1000.0))
+}
+
+/// Hold a transfer that started at `started` until it has taken as long
+/// as it would at the emulated bandwidth
+async fn pace_emulated_transfer(started: Instant, bytes: usize, kbps: u32) {
+    let due = emulated_transfer_time(bytes, kbps);
+    if let Some(wait) = due.checked_sub(started.elapsed()) {
+        tokio::time::sleep(wait).await;
+    }
+}
+
 #[tauri::command]
-pub async fn proxy_http_request(request: ProxyHttpRequest) -> Result<ProxyHttpResponse, String> {
+pub async fn proxy_http_request(
+    app: AppHandle,
+    request: ProxyHttpRequest,
+) -> Result<ProxyHttpResponse, String> {
     let method = request.method.unwrap_or_else(|| "GET".to_string());
     let target = resolve_proxy_target(&request.url, &method)?;
+    if app.state::<health::UpstreamStatus>().is_offline() {
+        return Err("Offline mode: server features are disabled".to_string());
+    }
+    let emulated = apply_emulated_network_conditions().await?;
     let reqwest_method = Method::from_bytes(method.as_bytes())
         .map_err(|e| format!("Unsupported HTTP method {}: {}", method, e))?;
 
@@ -209,29 +337,60 @@ pub async fn proxy_http_request(request: ProxyHttpRequest) -> Result<ProxyHttpRe
 
     if !matches!(reqwest_method, Method::GET | Method::HEAD) {
         if let Some(body) = request.body {
+            if let Some(conditions) = emulated {
+                pace_emulated_transfer(Instant::now(), body.len(), conditions.upload_kbps).await;
+            }
             builder = builder.body(body);
         }
     }
 
+    let http_log = app.state::<HttpLog>();
+    let started = Instant::now();
+    let log_failure = |error: &str| {
+        http_log.record(
+            HttpSource::Proxy,
+            reqwest_method.as_str(),
+            target.as_str(),
+            Err(error.to_string()),
+            started.elapsed(),
+        );
+    };
+
     let response = builder.send().await.map_err(|e| {
         warn!(
             "Proxy request failed for {} {}: {}",
             reqwest_method, target, e
         );
+        log_failure(&e.to_string());
         format!("Proxy request failed: {}", e)
     })?;
 
     let status = response.status();
     let response_url = response.url().to_string();
     let response_header_map = response.headers().clone();
+    maintenance::report(
+        &app,
+        maintenance::classify(status.as_u16(), &response_header_map),
+    );
     let body = if reqwest_method == Method::HEAD {
         String::new()
     } else {
         response.text().await.map_err(|e| {
             warn!("Failed reading proxy response body from {}: {}", target, e);
+            log_failure(&e.to_string());
             format!("Failed to read proxy response body: {}", e)
         })?
     };
+    if let Some(conditions) = emulated {
+        pace_emulated_transfer(started, body.len(), conditions.download_kbps).await;
+    }
+    http_log.record(
+        HttpSource::Proxy,
+        reqwest_method.as_str(),
+        target.as_str(),
+        Ok((status.as_u16(), Some(body.len() as u64))),
+        started.elapsed(),
+    );
 
     let mut headers = HashMap::new();
     for (name, value) in response_header_map.iter() {
@@ -253,8 +412,12 @@ pub async fn proxy_http_request(request: ProxyHttpRequest) -> Result<ProxyHttpRe
 pub struct SystemInfo {
     pub os: String,
     pub cpu_cores: usize,
+    pub cpu_name: Option<String>,
     pub total_memory_mb: u64,
     pub gpu_name: Option<String>,
+    pub gpu_driver: Option<GpuDriver>,
+    /// Where the game is loaded from (official server or a configured mirror)
+    pub game_origin: String,
 }
 
 /// Get performance stats
@@ -262,7 +425,7 @@ pub struct SystemInfo {
 pub async fn get_performance_stats(
     monitor: State<'_, PerformanceMonitor>,
 ) -> Result<PerformanceStats, String> {
-    let stats = monitor.get_stats();
+    let stats = monitor.snapshot().performance;
     debug!(
         "Performance stats: CPU={:.1}%, MEM={}MB",
         stats.cpu_usage, stats.memory_usage_mb
@@ -270,9 +433,22 @@ pub async fn get_performance_stats(
     Ok(stats)
 }
 
+/// Get the shared performance snapshot (system and GPU stats from one
+/// refresh, with its sequence number and age)
+#[tauri::command]
+pub fn get_performance_snapshot(monitor: State<'_, PerformanceMonitor>) -> PerformanceSnapshot {
+    monitor.snapshot()
+}
+
 /// Get system info
 #[tauri::command]
-pub async fn get_system_info() -> Result<SystemInfo, String> {
+pub async fn get_system_info(
+    upstream: State<'_, health::UpstreamStatus>,
+) -> Result<SystemInfo, String> {
+    Ok(collect_system_info(&upstream))
+}
+
+fn collect_system_info(upstream: &health::UpstreamStatus) -> SystemInfo {
     use sysinfo::System;
     debug!("Querying system info");
 
@@ -284,15 +460,22 @@ pub async fn get_system_info() -> Result<SystemInfo, String> {
     let info = SystemInfo {
         os: os_name,
         cpu_cores: system.cpus().len(),
+        cpu_name: system
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .filter(|brand| !brand.is_empty()),
         total_memory_mb: system.total_memory() / 1024 / 1024,
         gpu_name: gpu_name.clone(),
+        gpu_driver: gpu_driver::detect(),
+        game_origin: upstream.origin(),
     };
 
     debug!(
         "System info: {} cores, {}MB RAM, GPU: {:?}",
         info.cpu_cores, info.total_memory_mb, gpu_name
     );
-    Ok(info)
+    info
 }
 
 /// Get the operating system name
@@ -399,28 +582,15 @@ pub async fn toggle_fullscreen(app: AppHandle) -> Result<bool, String> {
     })?;
 
     let current_mode = WindowMode::from_u8(CURRENT_WINDOW_MODE.load(Ordering::SeqCst));
-
-    if current_mode == WindowMode::Fullscreen {
-        // Exit fullscreen -> go to Windowed
-        window.set_fullscreen(false).map_err(|e| e.to_string())?;
-        std::thread::sleep(std::time::Duration::from_millis(50));
-        window.set_decorations(true).map_err(|e| e.to_string())?;
-        CURRENT_WINDOW_MODE.store(WindowMode::Windowed.to_u8(), Ordering::SeqCst);
-        debug!("Fullscreen toggled: Fullscreen -> Windowed");
-        Ok(false)
+    // Exit fullscreen -> Windowed; enter fullscreen from any other mode
+    let mode = if current_mode == WindowMode::Fullscreen {
+        WindowMode::Windowed
     } else {
-        // Enter fullscreen from any mode
-        if current_mode == WindowMode::BorderlessWindowed {
-            // First restore from borderless
-            window.set_decorations(true).map_err(|e| e.to_string())?;
-            window.unmaximize().map_err(|e| e.to_string())?;
-            std::thread::sleep(std::time::Duration::from_millis(50));
-        }
-        window.set_fullscreen(true).map_err(|e| e.to_string())?;
-        CURRENT_WINDOW_MODE.store(WindowMode::Fullscreen.to_u8(), Ordering::SeqCst);
-        debug!("Fullscreen toggled: {:?} -> Fullscreen", current_mode);
-        Ok(true)
-    }
+        WindowMode::Fullscreen
+    };
+    transition_window_mode(&window, current_mode, mode).await?;
+    debug!("Fullscreen toggled: {:?} -> {:?}", current_mode, mode);
+    Ok(mode == WindowMode::Fullscreen)
 }
 
 /// Get WebView2 process elevation telemetry
@@ -429,18 +599,125 @@ pub async fn toggle_fullscreen(app: AppHandle) -> Result<bool, String> {
 pub fn get_webview_telemetry() -> ElevationTelemetry {
     let telemetry = get_elevation_telemetry();
     debug!(
-        "Elevation telemetry: mode={}, elevated={}, active={}",
-        telemetry.mode, telemetry.processes_elevated, telemetry.is_active
+        "Elevation telemetry: mode={}, elevated={}, active={}, wmi events/min={:.1}",
+        telemetry.mode,
+        telemetry.processes_elevated,
+        telemetry.is_active,
+        telemetry.wmi_events_per_minute
     );
     telemetry
 }
 
+/// Choose which WebView2 process types are elevated and how many at most.
+/// Applies to processes started from now on; returns the stored policy.
+#[tauri::command]
+pub fn set_elevation_policy(
+    settings: State<'_, SettingsState>,
+    policy: ElevationPolicySettings,
+) -> Result<ElevationPolicySettings, String> {
+    let policy = policy.validated();
+    let updated = settings.update(|s| s.elevation_policy = policy)?;
+    elevation_policy::set(updated.elevation_policy.clone());
+    Ok(updated.elevation_policy)
+}
+
+/// Cap the committed memory and CPU share of the WebView2 processes; 0
+/// lifts a cap. Returns the stored limits.
+#[tauri::command]
+pub fn set_resource_limits(
+    settings: State<'_, SettingsState>,
+    limits: ResourceLimitSettings,
+) -> Result<ResourceLimitSettings, String> {
+    let limits = limits.validated();
+    resource_limits::apply(limits)?;
+    let updated = settings.update(|s| s.resource_limits = limits)?;
+    Ok(updated.resource_limits)
+}
+
+/// Slow the page down and release the timer resolution while the window
+/// is unfocused outside a match (Windows). Returns the stored settings.
+#[tauri::command]
+pub fn set_idle_throttle(
+    settings: State<'_, SettingsState>,
+    throttle: IdleThrottleSettings,
+) -> Result<IdleThrottleSettings, String> {
+    let throttle = throttle.validated();
+    idle_throttle::apply(throttle)?;
+    let updated = settings.update(|s| s.idle_throttle = throttle)?;
+    Ok(updated.idle_throttle)
+}
+
+/// Refresh rates of the game window's display, and whether it has
+/// Dynamic Refresh Rate (Windows 11)
+#[tauri::command]
+pub fn get_refresh_rate(app: AppHandle) -> Result<RefreshRateInfo, String> {
+    refresh_rate::info(&app)
+}
+
+/// Switch a Dynamic Refresh Rate panel to 60 Hz outside a match and back
+/// to its own mode in a room (Windows 11). Returns the stored value.
+#[tauri::command]
+pub fn set_dynamic_refresh(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    refresh_rate::set_enabled(&app, enabled)?;
+    let updated = settings.update(|s| s.dynamic_refresh = enabled)?;
+    Ok(updated.dynamic_refresh)
+}
+
+/// Result of the last game server status poll, if a feed is configured
+#[tauri::command]
+pub fn get_server_status(store: State<'_, ServerStatusStore>) -> Option<ServerStatus> {
+    store.current()
+}
+
+/// Configure the game server status feed. The poller picks the change up
+/// within seconds. Returns the stored settings.
+#[tauri::command]
+pub fn set_server_status(
+    settings: State<'_, SettingsState>,
+    feed: ServerStatusSettings,
+) -> Result<ServerStatusSettings, String> {
+    let mut feed = feed.validated();
+    if !feed.url.is_empty() {
+        feed.url = server_status::normalize_url(&feed.url)?;
+    } else if feed.enabled {
+        return Err("Set a status feed URL first".to_string());
+    }
+    let updated = settings.update(|s| s.server_status = feed)?;
+    Ok(updated.server_status)
+}
+
+/// Synergy breakpoints and item recipes bundled with this build
+#[tauri::command]
+pub fn get_quick_reference(app: AppHandle) -> Result<QuickReference, String> {
+    quick_reference::load(&app)
+}
+
+/// Turn the quick-reference panel on or off and set its hotkey. The page
+/// applies the change at once.
+#[tauri::command]
+pub fn set_quick_reference(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    reference: QuickReferenceSettings,
+) -> Result<QuickReferenceSettings, String> {
+    let reference = reference.validated()?;
+    let updated = settings.update(|s| s.quick_reference = reference)?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-quick-reference", &updated.quick_reference);
+    }
+    Ok(updated.quick_reference)
+}
+
 /// Get GPU usage statistics
 /// Windows: Uses Performance Counters (PDH API) for GPU engine utilization
 /// Linux: Basic GPU detection only (usage monitoring not available)
 #[tauri::command]
-pub fn get_gpu_stats() -> GpuStats {
-    let stats = get_gpu_stats_impl();
+pub fn get_gpu_stats(monitor: State<'_, PerformanceMonitor>) -> GpuStats {
+    let stats = monitor.snapshot().gpu;
     debug!(
         "GPU stats: usage={:.1}%, available={}, gpu={:?}",
         stats.usage_percent, stats.available, stats.name
@@ -452,8 +729,8 @@ pub fn get_gpu_stats() -> GpuStats {
 /// Windows: Detects HDR capability via DXGI 1.6
 /// Linux: Not available (returns stub)
 #[tauri::command]
-pub fn get_hdr_status() -> HdrInfo {
-    let info = get_hdr_info();
+pub fn get_hdr_status(app: AppHandle) -> HdrInfo {
+    let info = display_watch::game_window_hdr(&app);
     debug!(
         "HDR status: supported={}, enabled={}, color_space={}, max_nits={}",
         info.supported, info.enabled, info.color_space, info.max_luminance
@@ -461,194 +738,1581 @@ pub fn get_hdr_status() -> HdrInfo {
     info
 }
 
-/// Set window display mode (windowed, fullscreen, or borderless)
+/// Turn Windows HDR on or off for `display` (GDI name from
+/// `get_hdr_status().outputs`), by default the game window's display. Opens
+/// the HDR page of Settings where it can't be switched directly.
 #[tauri::command]
-pub async fn set_window_mode(app: AppHandle, mode: WindowMode) -> Result<WindowMode, String> {
-    debug!("Setting window mode to {:?}", mode);
-    use std::sync::atomic::Ordering;
+pub fn set_windows_hdr(
+    app: AppHandle,
+    display: Option<String>,
+    enabled: bool,
+) -> Result<HdrSwitch, String> {
+    let display = display.unwrap_or_else(|| display_watch::game_window_hdr(&app).display_name);
+    let result = hdr_control::set_windows_hdr(&display, enabled)?;
+    display_watch::schedule_check(&app, "HDR switch");
+    Ok(result)
+}
 
-    let window = app.get_webview_window("main").ok_or_else(|| {
-        warn!("Main window not found for window mode change");
-        "Main window not found".to_string()
-    })?;
+/// Turn HDR off on the game's display while running, and back on at exit
+/// (Windows)
+#[tauri::command]
+pub fn set_disable_hdr_in_game(
+    window: tauri::WebviewWindow,
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let updated = settings.update(|s| s.disable_hdr_in_game = enabled)?;
+    hdr_control::set_disabled_in_game(&window, updated.disable_hdr_in_game);
+    Ok(updated.disable_hdr_in_game)
+}
 
-    let current_mode = WindowMode::from_u8(CURRENT_WINDOW_MODE.load(Ordering::SeqCst));
+/// Get actionable performance hints for the current session.
+/// `fps` and `renderer` (WebGL renderer string) are measured by the injected
+/// runtime and passed in by the caller.
+#[tauri::command]
+pub async fn get_performance_advice(
+    monitor: State<'_, PerformanceMonitor>,
+    history: State<'_, PerformanceHistory>,
+    jank: State<'_, JankTracker>,
+    fps: Option<f32>,
+    renderer: Option<String>,
+) -> Result<Vec<PerformanceAdvice>, String> {
+    let PerformanceSnapshot {
+        performance: stats,
+        gpu,
+        ..
+    } = monitor.snapshot();
+    let latest = history.latest().unwrap_or_default();
+    let inputs = AdvisorInputs {
+        fps,
+        cpu_usage: stats.cpu_usage,
+        memory_usage_mb: stats.memory_usage_mb,
+        gpu_usage: gpu.available.then_some(gpu.usage_percent),
+        max_temperature: advisor::read_max_temperature(),
+        software_rendering: rendering::detect(renderer.as_deref()).is_software(),
+        js_heap_used_mb: latest.js_heap_used_mb,
+        dom_nodes: latest.dom_nodes,
+        heap_growth_ratio: history.heap_growth_ratio(),
+        main_thread_blocked_ms_per_min: jank.stats().blocked_ms_per_min,
+        vulkan_blocked_driver: browser_flags::vulkan_blocked_driver(),
+    };
+    let advice = advisor::evaluate(&inputs);
+    debug!(
+        "Performance advice: {:?}",
+        advice.iter().map(|a| a.id.as_str()).collect::<Vec<_>>()
+    );
+    Ok(advice)
+}
 
-    // Skip if already in requested mode
-    if current_mode == mode {
-        debug!("Already in {:?} mode, skipping", mode);
-        return Ok(mode);
+/// Get the sampled performance history (oldest first, up to one hour)
+#[tauri::command]
+pub fn get_performance_history(history: State<'_, PerformanceHistory>) -> Vec<PerformanceSample> {
+    history.snapshot()
+}
+
+/// Receive a batch of long tasks and layout shift from the page observer
+#[tauri::command]
+pub fn report_long_tasks(
+    app: AppHandle,
+    jank: State<'_, JankTracker>,
+    tasks: Vec<LongTaskReport>,
+    layout_shift: f64,
+) {
+    jank.record(&tasks, layout_shift);
+    if let Some(message) = jank.warning() {
+        warn!("{}", message);
+        page_events::emit(&app, PageEvent::HealthWarning { message });
     }
+}
 
-    // Delay between window operations to let the window manager process them
-    let delay = || std::thread::sleep(std::time::Duration::from_millis(50));
+/// Get main-thread jank stats for the last minute
+#[tauri::command]
+pub fn get_jank_stats(jank: State<'_, JankTracker>) -> JankStats {
+    jank.stats()
+}
 
-    match mode {
-        WindowMode::Windowed => {
-            // Exit fullscreen if needed
-            if current_mode == WindowMode::Fullscreen {
-                window.set_fullscreen(false).map_err(|e| e.to_string())?;
-                delay();
-            }
-            // Restore decorations
-            window.set_decorations(true).map_err(|e| e.to_string())?;
-            delay();
-            // Unmaximize if we were borderless
-            if current_mode == WindowMode::BorderlessWindowed {
-                window.unmaximize().map_err(|e| e.to_string())?;
-            }
-            debug!("Window mode set to Windowed");
-        }
-        WindowMode::Fullscreen => {
-            // Clean up borderless state first
-            if current_mode == WindowMode::BorderlessWindowed {
-                window.set_decorations(true).map_err(|e| e.to_string())?;
-                window.unmaximize().map_err(|e| e.to_string())?;
-                delay();
-            }
-            window.set_fullscreen(true).map_err(|e| e.to_string())?;
-            debug!("Window mode set to Fullscreen");
-        }
-        WindowMode::BorderlessWindowed => {
-            // Exit fullscreen first if needed
-            if current_mode == WindowMode::Fullscreen {
-                window.set_fullscreen(false).map_err(|e| e.to_string())?;
-                delay();
-            }
-            // Remove decorations then maximize
-            window.set_decorations(false).map_err(|e| e.to_string())?;
-            delay();
-            window.maximize().map_err(|e| e.to_string())?;
-            debug!("Window mode set to BorderlessWindowed");
-        }
-    }
+/// Receive the injected runtime's watcher totals, with the setup time of
+/// each runtime block and the page's uptime
+#[tauri::command]
+pub fn report_observer_stats(
+    observers: State<'_, ObserverTracker>,
+    watchers: Vec<WatcherStats>,
+    modules: Option<Vec<ModuleInit>>,
+    uptime_ms: Option<f64>,
+) {
+    observers.record(watchers, modules.unwrap_or_default(), uptime_ms);
+}
 
-    CURRENT_WINDOW_MODE.store(mode.to_u8(), Ordering::SeqCst);
-    Ok(mode)
+/// Get the intervals and DOM observers the injected runtime runs, busiest
+/// first, from its last report
+#[tauri::command]
+pub fn get_observer_stats(observers: State<'_, ObserverTracker>) -> ObserverStats {
+    observers.stats()
 }
 
-/// Get current window display mode
+/// Get the injected runtime's main-thread cost per block, setup and steady
+/// state, from its last report
 #[tauri::command]
-pub async fn get_window_mode(_app: AppHandle) -> Result<WindowMode, String> {
-    use std::sync::atomic::Ordering;
-    let mode = WindowMode::from_u8(CURRENT_WINDOW_MODE.load(Ordering::SeqCst));
-    debug!("Current window mode: {:?}", mode);
-    Ok(mode)
+pub fn get_script_budget(observers: State<'_, ObserverTracker>) -> ScriptBudget {
+    observers.budget()
 }
 
-/// Update info returned to JavaScript
-#[derive(Debug, Clone, Serialize)]
-pub struct UpdateInfo {
-    pub available: bool,
-    pub version: Option<String>,
-    pub body: Option<String>,
+/// Start or pause the `pac-overlay-stats` push. The runtime enables it
+/// while the overlay is visible and the page isn't hidden.
+#[tauri::command]
+pub fn set_overlay_stream(stream: State<'_, OverlayStream>, active: bool) {
+    stream.set_active(active);
 }
 
-/// Global state to store pending update for download
-pub struct PendingUpdate(pub Mutex<Option<tauri_plugin_updater::Update>>);
+/// Get the client health report (rendering backend and other checks).
+/// `renderer` is the page's WebGL renderer string, when it can be read.
+#[tauri::command]
+pub async fn get_health(renderer: Option<String>) -> Result<HealthReport, String> {
+    let report = health::check(renderer.as_deref());
+    if !report.healthy {
+        warn!("Health check found issues: {:?}", report.issues);
+    }
+    Ok(report)
+}
 
-/// Check for available updates
-/// Returns update info if available, or indicates no update needed
+/// Whether this launch is in safe mode, and what it turned off
+#[tauri::command]
+pub fn get_safe_mode() -> SafeModeStatus {
+    safe_mode::status()
+}
+
+/// WebView2 process failures recorded so far, oldest first
+#[tauri::command]
+pub fn get_crash_history() -> Vec<ProcessFailure> {
+    process_failures::history()
+}
+
+/// Check that each optimization is in effect right now
+#[tauri::command]
+pub fn run_self_test(app: AppHandle) -> SelfTestReport {
+    self_test::run(&app)
+}
+
+/// Busy the CPU (and, unless `gpu` is false, the GPU) for `seconds`, for
+/// support to check the stats and alerts respond. Not in the page API.
 #[tauri::command]
-pub async fn check_for_updates(
+pub fn simulate_load(
     app: AppHandle,
-    pending: State<'_, PendingUpdate>,
-) -> Result<UpdateInfo, String> {
-    info!("Checking for updates...");
+    seconds: Option<u64>,
+    gpu: Option<bool>,
+) -> Result<LoadRun, String> {
+    load_sim::start(&app, seconds, gpu.unwrap_or(true))
+}
 
-    let updater = app.updater_builder().build().map_err(|e| {
-        warn!("Failed to build updater: {}", e);
-        e.to_string()
-    })?;
+/// Get DevTools `Performance.getMetrics` for the main webview (WebView2 only)
+#[tauri::command]
+pub async fn get_devtools_metrics(
+    window: tauri::WebviewWindow,
+) -> Result<HashMap<String, f64>, String> {
+    cdp::performance_metrics(&window).await
+}
 
-    match updater.check().await {
-        Ok(Some(update)) => {
-            let version = update.version.clone();
-            let body = update.body.clone();
-            info!("Update available: v{}", version);
+/// Tear down and rebuild the main webview, keeping its URL, cookies, and
+/// window geometry. Heavier than `reload_page`; this page does not survive it.
+#[tauri::command]
+pub async fn recreate_webview(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || recovery::recreate_webview(&app))
+        .await
+        .map_err(|e| format!("Recreate task failed: {}", e))?
+}
 
-            // Store the update for later download
-            *pending.0.lock().unwrap() = Some(update);
+/// Choose the browser networking preset. Takes effect after a restart;
+/// returns the preset that will be used then.
+#[tauri::command]
+pub fn set_networking_preset(
+    settings: State<'_, SettingsState>,
+    preset: NetworkingPreset,
+) -> Result<NetworkingPreset, String> {
+    let updated = settings.update(|s| s.networking_preset = preset)?;
+    info!(
+        "Networking preset set to {:?} (restart required)",
+        updated.networking_preset
+    );
+    Ok(updated.networking_preset)
+}
 
-            Ok(UpdateInfo {
-                available: true,
-                version: Some(version),
-                body,
-            })
-        }
-        Ok(None) => {
-            info!("App is up to date");
-            Ok(UpdateInfo {
-                available: false,
-                version: None,
-                body: None,
-            })
-        }
-        Err(e) => {
-            warn!("Update check failed: {}", e);
-            Err(e.to_string())
-        }
-    }
+/// Turn Vulkan rendering on or off. Takes effect after a restart, and not
+/// at all on GPU drivers known to break it.
+#[tauri::command]
+pub fn set_vulkan_rendering(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let updated = settings.update(|s| s.vulkan_rendering = enabled)?;
+    info!(
+        "Vulkan rendering set to {} (restart required)",
+        updated.vulkan_rendering
+    );
+    Ok(updated.vulkan_rendering)
 }
 
-/// Download and install the pending update, emitting progress events
+/// Summarize resource timing samples for the preset this run started with
 #[tauri::command]
-pub async fn install_update(
+pub fn report_network_benchmark(samples: Vec<NetworkSample>) -> Result<NetworkBenchmark, String> {
+    let benchmark = browser_flags::summarize(browser_flags::active_preset(), &samples);
+    browser_flags::save_benchmark(&benchmark)?;
+    Ok(benchmark)
+}
+
+/// Latest benchmark for each preset, for comparison
+#[tauri::command]
+pub fn get_network_benchmarks() -> BTreeMap<String, NetworkBenchmark> {
+    browser_flags::load_benchmarks()
+}
+
+/// Which requested WebView2 flags reached the browser processes
+#[tauri::command]
+pub fn get_effective_flags() -> browser_flags::EffectiveFlags {
+    browser_flags::effective_flags()
+}
+
+/// Turn the debug HTTP request log on or off (persisted)
+#[tauri::command]
+pub fn set_http_logging(
+    settings: State<'_, SettingsState>,
+    log: State<'_, HttpLog>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let updated = settings.update(|s| s.http_logging = enabled)?;
+    log.set_enabled(updated.http_logging);
+    info!(
+        "HTTP request log {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(updated.http_logging)
+}
+
+/// Start recording a UI macro from the main window's current page
+#[tauri::command]
+pub fn record_macro(
     app: AppHandle,
-    pending: State<'_, PendingUpdate>,
+    macros: State<'_, MacroStore>,
+    page: State<'_, PageState>,
+    name: String,
 ) -> Result<(), String> {
-    info!("Installing update...");
+    if page.phase().is_match_in_progress() {
+        return Err("Macros can't be recorded during a match".to_string());
+    }
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    let url = window.url().map_err(|e| e.to_string())?;
+    macros.start_recording(&name, url.path())?;
+    let _ = window.emit("pac-macro-recording", true);
+    info!("Recording macro {:?}", name.trim());
+    Ok(())
+}
 
-    let update = pending
-        .0
-        .lock()
-        .unwrap()
-        .take()
-        .ok_or_else(|| "No pending update to install".to_string())?;
+/// Finish and save the macro being recorded
+#[tauri::command]
+pub fn stop_macro_recording(
+    app: AppHandle,
+    macros: State<'_, MacroStore>,
+) -> Result<UiMacro, String> {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-macro-recording", false);
+    }
+    macros.stop_recording()
+}
 
-    // Download and install with progress reporting via Tauri events
-    let app_handle = app.clone();
-    update
-        .download_and_install(
-            move |chunk_length, total_size| {
-                let _ = app_handle.emit(
-                    "update-progress",
-                    serde_json::json!({
-                        "chunk": chunk_length,
-                        "total": total_size.unwrap_or(0),
-                    }),
-                );
-            },
-            || {},
-        )
-        .await
-        .map_err(|e| {
-            warn!("Update installation failed: {}", e);
-            e.to_string()
-        })?;
+/// Add a click from the page to the macro being recorded
+#[tauri::command]
+pub fn report_macro_step(macros: State<'_, MacroStore>, action: MacroAction) -> Result<(), String> {
+    macros.record_step(action)
+}
 
-    info!("Update installed successfully");
-    Ok(())
+/// Play a saved macro (menus only, rate limited)
+#[tauri::command]
+pub fn play_macro(app: AppHandle, name: String) -> Result<(), String> {
+    macros::play(&app, &name)
 }
 
-/// Restart the application
+/// Stop the macro that is playing
 #[tauri::command]
-pub async fn restart_app(app: AppHandle) -> Result<(), String> {
-    info!("Restarting application...");
-    app.restart();
+pub fn stop_macro(macros: State<'_, MacroStore>) {
+    macros.cancel();
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[tauri::command]
+pub fn list_macros(macros: State<'_, MacroStore>) -> Vec<UiMacro> {
+    macros.list()
+}
 
-    #[test]
-    fn routes_relative_paths_to_production() {
-        let target = resolve_proxy_target("/profile?t=1", "GET").unwrap();
-        assert_eq!(
-            target.as_str(),
-            "https://pokemon-auto-chess.com/profile?t=1"
-        );
+#[tauri::command]
+pub fn delete_macro(macros: State<'_, MacroStore>, name: String) -> Result<(), String> {
+    macros.delete(&name)
+}
+
+/// Turn the WebSocket inspector on or off, or change its redaction
+#[tauri::command]
+pub async fn set_ws_inspector(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    inspector: WsInspectorSettings,
+) -> Result<WsInspectorSettings, String> {
+    // Start first so a failure (e.g. no DevTools on Linux) isn't saved
+    ws_inspector::apply(&app, &inspector).await?;
+    let updated = settings.update(|s| s.ws_inspector = inspector)?;
+    Ok(updated.ws_inspector)
+}
+
+/// WebSocket frame statistics over the last minute
+#[tauri::command]
+pub fn get_ws_stats(inspector: State<'_, WsInspector>) -> WsStats {
+    inspector.stats()
+}
+
+/// Logged requests, oldest first (sanitized; empty while logging is off)
+#[tauri::command]
+pub fn get_http_log(log: State<'_, HttpLog>) -> Vec<HttpLogEntry> {
+    log.snapshot()
+}
+
+/// Enter or leave experimental offline mode. While on, the bundled UI runs
+/// without the game server: the native proxy refuses every request.
+#[tauri::command]
+pub fn set_offline_mode(status: State<'_, health::UpstreamStatus>, enabled: bool) -> bool {
+    status.set_offline(enabled);
+    info!(
+        "Offline mode {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    enabled
+}
+
+/// Whether experimental offline mode is on
+#[tauri::command]
+pub fn get_offline_mode(status: State<'_, health::UpstreamStatus>) -> bool {
+    status.is_offline()
+}
+
+/// Configure fallback game URLs the startup check tries in order
+#[tauri::command]
+pub fn set_upstream_mirrors(
+    settings: State<'_, SettingsState>,
+    mirrors: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for mirror in &mirrors {
+        let mirror = settings::normalize_mirror_url(mirror)?;
+        if !normalized.contains(&mirror) {
+            normalized.push(mirror);
+        }
+    }
+    let updated = settings.update(|s| s.upstream_mirrors = normalized)?;
+    info!("Upstream mirrors set: {:?}", updated.upstream_mirrors);
+    Ok(updated.upstream_mirrors)
+}
+
+/// Probe the game server again (retry screen)
+#[tauri::command]
+pub async fn probe_upstream(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    status: State<'_, health::UpstreamStatus>,
+) -> Result<health::UpstreamProbe, String> {
+    let mirrors = settings.get().upstream_mirrors;
+    let probe = health::probe_upstream(&mirrors).await;
+    if probe.status.is_some() {
+        maintenance::report(&app, probe.issue.clone());
+    }
+    // The retry screen navigates to the mirror when one is offered
+    if probe.reachable {
+        status.set_mirror(None);
+    } else if probe.fallback.is_some() {
+        status.set_mirror(probe.fallback.clone());
+    }
+    status.set(probe.clone());
+    Ok(probe)
+}
+
+/// Current game server issue, if any (for pages loaded mid-outage)
+#[tauri::command]
+pub fn get_upstream_issue(state: State<'_, UpstreamState>) -> Option<UpstreamIssue> {
+    state.current()
+}
+
+/// Result of the most recent game server probe
+#[tauri::command]
+pub fn get_upstream_status(
+    status: State<'_, health::UpstreamStatus>,
+) -> Option<health::UpstreamProbe> {
+    status.get()
+}
+
+/// Page liveness heartbeat for the freeze watchdog (sent every second),
+/// with the current frame rate while the page is visible
+#[tauri::command]
+pub fn heartbeat(
+    watchdog: State<'_, FreezeWatchdog>,
+    history: State<'_, PerformanceHistory>,
+    fps: Option<f32>,
+) {
+    watchdog.heartbeat();
+    if let Some(fps) = fps {
+        history.report_fps(fps);
+    }
+}
+
+/// Choose what the freeze watchdog does when the page stops responding
+#[tauri::command]
+pub fn set_freeze_action(
+    settings: State<'_, SettingsState>,
+    action: FreezeAction,
+) -> Result<FreezeAction, String> {
+    let updated = settings.update(|s| s.freeze_action = action)?;
+    info!("Freeze action set to {:?}", updated.freeze_action);
+    Ok(updated.freeze_action)
+}
+
+/// Seconds since the Unix epoch, for export file names
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Reload the game page from native code
+#[tauri::command]
+pub fn reload_page(window: tauri::WebviewWindow) -> Result<(), String> {
+    info!("Page reload requested");
+    recovery::force_reload(&window, ReloadKind::Reload)
+}
+
+/// Save a PNG screenshot of the game page (WebView2 only).
+/// Saved to Pictures/PACDeluxe; returns the file path.
+#[tauri::command]
+pub async fn capture_screenshot(
+    app: AppHandle,
+    window: tauri::WebviewWindow,
+) -> Result<String, String> {
+    let dir = app
+        .path()
+        .picture_dir()
+        .map(|pictures| pictures.join("PACDeluxe"))
+        .or_else(|_| {
+            crate::settings::diagnostics_dir()
+                .ok_or_else(|| "No pictures or config directory available".to_string())
+        })?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let png = cdp::capture_screenshot(&window).await?;
+    let path = dir.join(format!("pacdeluxe-{}.png", unix_timestamp()));
+    std::fs::write(&path, png).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    info!("Screenshot saved to {}", path.display());
+    page_events::toast(
+        &app,
+        ToastLevel::Success,
+        format!("Screenshot saved: {}", path.display()),
+    );
+    Ok(path.display().to_string())
+}
+
+/// Capture a JS heap snapshot of the game page (WebView2 only).
+/// Saved as a .heapsnapshot in the diagnostics folder; returns its path.
+#[tauri::command]
+pub async fn capture_heap_snapshot(window: tauri::WebviewWindow) -> Result<String, String> {
+    let dir = crate::settings::diagnostics_dir()
+        .ok_or_else(|| "No config directory available".to_string())?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let path = dir.join(format!("pacdeluxe-{}.heapsnapshot", unix_timestamp()));
+    cdp::capture_heap_snapshot(&window, &path).await?;
+    Ok(path.display().to_string())
+}
+
+/// Longest text accepted by `copy_to_clipboard`
+const MAX_CLIPBOARD_TEXT: usize = 4096;
+
+/// Copy text (team codes, room IDs) to the system clipboard
+#[tauri::command]
+pub fn copy_to_clipboard(app: AppHandle, text: String) -> Result<(), String> {
+    if text.len() > MAX_CLIPBOARD_TEXT {
+        return Err(format!(
+            "Clipboard text too long ({} bytes, max {})",
+            text.len(),
+            MAX_CLIPBOARD_TEXT
+        ));
+    }
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to write clipboard: {}", e))
+}
+
+/// Read text from the system clipboard (e.g. to paste a team code)
+#[tauri::command]
+pub fn read_clipboard(app: AppHandle) -> Result<String, String> {
+    app.clipboard()
+        .read_text()
+        .map_err(|e| format!("Failed to read clipboard: {}", e))
+}
+
+/// Copy a `pacdeluxe://join/<id>` link for a lobby room; returns the link
+#[tauri::command]
+pub fn copy_room_link(app: AppHandle, room_id: String) -> Result<String, String> {
+    let link = deep_links::join_link(&room_id)?;
+    app.clipboard()
+        .write_text(link.clone())
+        .map_err(|e| format!("Failed to write clipboard: {}", e))?;
+    debug!("Copied room link {}", link);
+    Ok(link)
+}
+
+/// The room from an opened `pacdeluxe://join/<id>` link, once; the runtime
+/// asks at load and on `join_requested`
+#[tauri::command]
+pub fn take_pending_join() -> Option<String> {
+    deep_links::take_pending_join()
+}
+
+/// Custom background, theme and installed user scripts
+#[tauri::command]
+pub fn get_customizations() -> Customizations {
+    if safe_mode::is_active() {
+        return Customizations::default();
+    }
+    customization::load()
+}
+
+/// Set the background image from a dropped file (base64 data); returns the
+/// `data:` URL to apply
+#[tauri::command]
+pub fn set_custom_background(file_name: String, data: String) -> Result<String, String> {
+    customization::set_background(&file_name, &data)
+}
+
+/// Set the CSS theme from a dropped file
+#[tauri::command]
+pub fn set_custom_theme(css: String) -> Result<(), String> {
+    customization::set_theme(&css)
+}
+
+/// Set the CJK fallback and language exemptions for theme fonts. The page
+/// applies them to the current theme at once.
+#[tauri::command]
+pub fn set_theme_fonts(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    fonts: ThemeFontSettings,
+) -> Result<ThemeFontSettings, String> {
+    let fonts = fonts.validated()?;
+    let updated = settings.update(|s| s.theme_fonts = fonts)?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-theme-fonts", &updated.theme_fonts);
+    }
+    debug!("Theme fonts updated: {:?}", updated.theme_fonts);
+    Ok(updated.theme_fonts)
+}
+
+/// Contrast mode, the OS high contrast setting and the stylesheet in use
+#[tauri::command]
+pub fn get_contrast(app: AppHandle) -> ContrastInfo {
+    contrast::info(&app)
+}
+
+/// Choose the contrast mode; the page restyles at once
+#[tauri::command]
+pub fn set_contrast_mode(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    mode: ContrastMode,
+) -> Result<ContrastInfo, String> {
+    settings.update(|s| s.contrast_mode = mode)?;
+    let info = contrast::info(&app);
+    contrast::notify(&app, info);
+    debug!("Contrast mode {:?} ({:?})", mode, info.style);
+    Ok(info)
+}
+
+/// Install a dropped user script once the user confirms in a native
+/// dialog; it runs from the next page load. None when they decline.
+#[tauri::command]
+pub async fn install_user_script(
+    app: AppHandle,
+    file_name: String,
+    source: String,
+) -> Result<Option<String>, String> {
+    if !customization::confirm_user_script(&app, &file_name, source.len()).await? {
+        info!("User script {:?} not installed (declined)", file_name);
+        return Ok(None);
+    }
+    customization::install_user_script(&file_name, &source).map(Some)
+}
+
+/// Remove a customization (`script` picks one user script, otherwise all)
+#[tauri::command]
+pub fn clear_customization(kind: CustomizationKind, script: Option<String>) -> Result<(), String> {
+    customization::clear(kind, script.as_deref())
+}
+
+/// Emulate network conditions (latency/bandwidth/offline) for testing.
+/// Applies to the webview via DevTools and to the native HTTP proxy.
+/// Not persisted: restarting the app always returns to the real network.
+#[tauri::command]
+pub async fn set_network_conditions(
+    window: tauri::WebviewWindow,
+    preset: NetworkPreset,
+) -> Result<Option<NetworkConditions>, String> {
+    let conditions = preset.conditions();
+    let devtools = cdp::emulate_network_conditions(&window, conditions).await;
+    *NETWORK_CONDITIONS.lock().unwrap_or_else(|e| e.into_inner()) = conditions;
+
+    match (&devtools, conditions) {
+        (Err(e), _) => warn!(
+            "Network emulation {:?} applied to proxy only (webview: {})",
+            preset, e
+        ),
+        (Ok(()), Some(_)) => info!("Network emulation enabled: {:?}", preset),
+        (Ok(()), None) => info!("Network emulation disabled"),
+    }
+    Ok(conditions)
+}
+
+/// Set window display mode (windowed, fullscreen, or borderless)
+#[tauri::command]
+pub async fn set_window_mode(app: AppHandle, mode: WindowMode) -> Result<WindowMode, String> {
+    debug!("Setting window mode to {:?}", mode);
+    use std::sync::atomic::Ordering;
+
+    let window = app.get_webview_window("main").ok_or_else(|| {
+        warn!("Main window not found for window mode change");
+        "Main window not found".to_string()
+    })?;
+
+    let current_mode = WindowMode::from_u8(CURRENT_WINDOW_MODE.load(Ordering::SeqCst));
+
+    // Skip if already in requested mode
+    if current_mode == mode {
+        debug!("Already in {:?} mode, skipping", mode);
+        return Ok(mode);
+    }
+
+    transition_window_mode(&window, current_mode, mode).await?;
+    debug!("Window mode set to {:?}", mode);
+    Ok(mode)
+}
+
+/// Longest wait for the window to report one step of a mode change
+const WINDOW_CHANGE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Emitted by Tauri for the window's `Resized` event
+const WINDOW_RESIZED_EVENT: &str = "tauri://resize";
+
+/// Set while a window mode transition runs
+static WINDOW_MODE_CHANGING: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+/// Clears `WINDOW_MODE_CHANGING` when the transition ends, however it ends
+struct WindowModeChange;
+
+impl WindowModeChange {
+    fn begin() -> Result<Self, String> {
+        use std::sync::atomic::Ordering;
+        if WINDOW_MODE_CHANGING.swap(true, Ordering::SeqCst) {
+            return Err("Window mode change already in progress".to_string());
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for WindowModeChange {
+    fn drop(&mut self) {
+        WINDOW_MODE_CHANGING.store(false, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Apply one window change, then wait for the window to report the
+/// resulting resize before the next step. The wait runs on the blocking
+/// pool so the async runtime (and other IPC) is never stalled. Steps that
+/// don't resize (already in effect) continue after `WINDOW_CHANGE_TIMEOUT`.
+async fn window_step(
+    window: &tauri::WebviewWindow,
+    step: &str,
+    change: impl FnOnce(&tauri::WebviewWindow) -> tauri::Result<()>,
+) -> Result<(), String> {
+    use tauri::Listener;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let listener = window.listen(WINDOW_RESIZED_EVENT, move |_| {
+        let _ = tx.send(());
+    });
+    let result = change(window).map_err(|e| format!("{} failed: {}", step, e));
+    if result.is_ok() {
+        let resized = tauri::async_runtime::spawn_blocking(move || {
+            rx.recv_timeout(WINDOW_CHANGE_TIMEOUT).is_ok()
+        })
+        .await
+        .unwrap_or(false);
+        if !resized {
+            debug!("{}: no resize reported, continuing", step);
+        }
+    }
+    window.unlisten(listener);
+    result
+}
+
+/// Move the window from `current` to `mode` one step at a time and record
+/// the new mode. Overlapping transitions are rejected rather than queued.
+async fn transition_window_mode(
+    window: &tauri::WebviewWindow,
+    current: WindowMode,
+    mode: WindowMode,
+) -> Result<(), String> {
+    use std::sync::atomic::Ordering;
+
+    let _change = WindowModeChange::begin()?;
+
+    match mode {
+        WindowMode::Windowed => {
+            // Exit fullscreen if needed
+            if current == WindowMode::Fullscreen {
+                window_step(window, "Exit fullscreen", |w| w.set_fullscreen(false)).await?;
+            }
+            // Restore decorations
+            window_step(window, "Restore decorations", |w| w.set_decorations(true)).await?;
+            // Unmaximize if we were borderless
+            if current == WindowMode::BorderlessWindowed {
+                window.unmaximize().map_err(|e| e.to_string())?;
+            }
+        }
+        WindowMode::Fullscreen => {
+            // Clean up borderless state first
+            if current == WindowMode::BorderlessWindowed {
+                window.set_decorations(true).map_err(|e| e.to_string())?;
+                window_step(window, "Unmaximize", |w| w.unmaximize()).await?;
+            }
+            window.set_fullscreen(true).map_err(|e| e.to_string())?;
+        }
+        WindowMode::BorderlessWindowed => {
+            // Exit fullscreen first if needed
+            if current == WindowMode::Fullscreen {
+                window_step(window, "Exit fullscreen", |w| w.set_fullscreen(false)).await?;
+            }
+            // Remove decorations then maximize
+            window_step(window, "Remove decorations", |w| w.set_decorations(false)).await?;
+            window.maximize().map_err(|e| e.to_string())?;
+        }
+    }
+
+    CURRENT_WINDOW_MODE.store(mode.to_u8(), Ordering::SeqCst);
+    system_keys::on_window_mode(mode);
+    // Remembered for `StartMode::Last`
+    if let Err(e) = window
+        .state::<SettingsState>()
+        .update(|s| s.last_window_mode = mode)
+    {
+        warn!("Failed to save window mode: {}", e);
+    }
+    Ok(())
+}
+
+/// Re-enter `mode` from scratch for a window that drifted out of it (e.g.
+/// restored at a stale size after display sleep). Stepping out to Windowed
+/// first makes the window manager recompute the size for the monitor.
+pub async fn reapply_window_mode(
+    window: &tauri::WebviewWindow,
+    mode: WindowMode,
+) -> Result<(), String> {
+    if mode == WindowMode::Windowed {
+        return Ok(());
+    }
+    transition_window_mode(window, mode, WindowMode::Windowed).await?;
+    transition_window_mode(window, WindowMode::Windowed, mode).await
+}
+
+/// Get current window display mode
+#[tauri::command]
+pub async fn get_window_mode(_app: AppHandle) -> Result<WindowMode, String> {
+    use std::sync::atomic::Ordering;
+    let mode = WindowMode::from_u8(CURRENT_WINDOW_MODE.load(Ordering::SeqCst));
+    debug!("Current window mode: {:?}", mode);
+    Ok(mode)
+}
+
+/// Get the persisted user settings
+#[tauri::command]
+pub fn get_settings(settings: State<'_, SettingsState>) -> Settings {
+    settings.get()
+}
+
+/// Set the CSP injection mode for bundled documents.
+/// Takes effect on the next page load.
+#[tauri::command]
+pub fn set_csp_mode(settings: State<'_, SettingsState>, mode: CspMode) -> Result<CspMode, String> {
+    info!("Setting CSP mode to {:?}", mode);
+    settings.update(|s| s.csp_mode = mode).map(|s| s.csp_mode)
+}
+
+/// Set the window mode used at startup (`Last` restores the last mode).
+/// Takes effect on the next launch.
+#[tauri::command]
+pub fn set_start_mode(
+    settings: State<'_, SettingsState>,
+    mode: StartMode,
+) -> Result<StartMode, String> {
+    info!("Setting start mode to {:?}", mode);
+    settings
+        .update(|s| s.start_mode = mode)
+        .map(|s| s.start_mode)
+}
+
+/// Swallow the Windows key in exclusive fullscreen and turn off the
+/// Sticky/Filter Keys shortcut prompts (Windows only; restored on exit)
+#[tauri::command]
+pub fn set_suppress_system_keys(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let updated = settings.update(|s| s.suppress_system_keys = enabled)?;
+    system_keys::set_enabled(updated.suppress_system_keys);
+    Ok(updated.suppress_system_keys)
+}
+
+/// Apply a gamma change, keeping the previous one if Windows refuses it
+fn update_gamma(
+    settings: &SettingsState,
+    change: impl FnOnce(&mut GammaSettings),
+) -> Result<GammaSettings, String> {
+    let previous = settings.get().gamma;
+    let mut next = previous;
+    change(&mut next);
+    let next = next.validated();
+    if let Err(e) = gamma::apply(next) {
+        let _ = gamma::apply(previous);
+        return Err(e);
+    }
+    Ok(settings.update(|s| s.gamma = next)?.gamma)
+}
+
+/// Gamma of the game's display while the window is focused (Windows)
+#[tauri::command]
+pub fn set_gamma(settings: State<'_, SettingsState>, gamma: f32) -> Result<GammaSettings, String> {
+    update_gamma(&settings, |g| g.gamma = gamma)
+}
+
+/// White point of the game's display in Kelvin while the window is focused
+/// (Windows); 6500 is neutral
+#[tauri::command]
+pub fn set_color_temperature(
+    settings: State<'_, SettingsState>,
+    kelvin: u32,
+) -> Result<GammaSettings, String> {
+    update_gamma(&settings, |g| g.color_temperature = kelvin)
+}
+
+/// Gamma and white point from a preset (`off`, `evening`, `late_night`)
+#[tauri::command]
+pub fn apply_gamma_preset(
+    settings: State<'_, SettingsState>,
+    preset: GammaPreset,
+) -> Result<GammaSettings, String> {
+    update_gamma(&settings, |g| *g = preset.settings())
+}
+
+/// High-precision mouse mode: mouse acceleration off while the window is
+/// focused (Windows). The raw pointer event flag applies on next start.
+#[tauri::command]
+pub fn set_high_precision_mouse(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let updated = settings.update(|s| s.high_precision_mouse = enabled)?;
+    mouse_precision::set_enabled(updated.high_precision_mouse);
+    Ok(updated.high_precision_mouse)
+}
+
+/// Configure the sell-key guard for the end of planning. The runtime
+/// applies it on the next page load.
+#[tauri::command]
+pub fn set_misdrop_guard(
+    settings: State<'_, SettingsState>,
+    guard: MisdropGuardSettings,
+) -> Result<MisdropGuardSettings, String> {
+    let guard = guard.validated()?;
+    let updated = settings.update(|s| s.misdrop_guard = guard)?;
+    info!("Misdrop guard updated: {:?}", updated.misdrop_guard);
+    Ok(updated.misdrop_guard)
+}
+
+/// Turn the held-reroll rate limit on or off and apply it to the page
+#[tauri::command]
+pub fn set_turbo_guard(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    guard: TurboGuardSettings,
+) -> Result<TurboGuardSettings, String> {
+    let guard = guard.validated();
+    let updated = settings.update(|s| s.turbo_guard = guard)?;
+    info!("Turbo guard updated: {:?}", updated.turbo_guard);
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-turbo-guard", &updated.turbo_guard);
+    }
+    Ok(updated.turbo_guard)
+}
+
+/// Configure the carousel and match start alerts
+#[tauri::command]
+pub fn set_attention_alerts(
+    settings: State<'_, SettingsState>,
+    attention: AttentionSettings,
+) -> Result<AttentionSettings, String> {
+    let attention = attention.validated();
+    let updated = settings.update(|s| s.attention = attention)?;
+    info!("Attention alerts updated: {:?}", updated.attention);
+    Ok(updated.attention)
+}
+
+/// Choose the process tuning profile. During a battery/thermal emergency
+/// the battery saver profile stays in effect until it is over.
+#[tauri::command]
+pub fn set_power_profile(
+    settings: State<'_, SettingsState>,
+    guard: State<'_, PowerGuard>,
+    profile: PowerProfile,
+) -> Result<PowerProfile, String> {
+    let updated = settings.update(|s| s.power_profile = profile)?;
+    power_guard::apply_profile(guard.active_profile(updated.power_profile));
+    info!("Power profile: {:?}", updated.power_profile);
+    Ok(updated.power_profile)
+}
+
+#[tauri::command]
+pub fn set_power_guard(
+    settings: State<'_, SettingsState>,
+    power_guard: PowerGuardSettings,
+) -> Result<PowerGuardSettings, String> {
+    let power_guard = power_guard.validated();
+    let updated = settings.update(|s| s.power_guard = power_guard)?;
+    info!("Power guard updated: {:?}", updated.power_guard);
+    Ok(updated.power_guard)
+}
+
+/// The battery/thermal emergency in effect, if any
+#[tauri::command]
+pub fn get_power_emergency(guard: State<'_, PowerGuard>) -> Option<PowerEmergency> {
+    guard.emergency()
+}
+
+/// Volume of the game's audio session (0-1), or `None` before the game has
+/// played any sound
+#[tauri::command]
+pub fn get_audio_session_volume() -> Result<Option<f32>, String> {
+    audio::session_volume()
+}
+
+/// Set the game's audio volume (0-1) and save it in the profile. Sessions
+/// that don't exist yet get it when they appear.
+#[tauri::command]
+pub fn set_audio_session_volume(
+    profile: State<'_, ProfileState>,
+    volume: f32,
+) -> Result<f32, String> {
+    let changed = audio::set_session_volume(volume)?;
+    let updated = profile.update(|p| p.audio_volume = Some(volume))?;
+    debug!("Audio volume {} saved ({} sessions)", volume, changed);
+    Ok(updated.audio_volume.unwrap_or(volume))
+}
+
+/// Set the music and sound effect levels. The page applies them at once.
+#[tauri::command]
+pub fn set_audio_mix(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    mix: AudioMixSettings,
+) -> Result<AudioMixSettings, String> {
+    let mix = mix.validated()?;
+    let updated = settings.update(|s| s.audio_mix = mix)?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-audio-mix", &updated.audio_mix);
+    }
+    debug!("Audio mix updated: {:?}", updated.audio_mix);
+    Ok(updated.audio_mix)
+}
+
+/// Set the cursor style and size; the page switches cursors at once
+#[tauri::command]
+pub fn set_cursor(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    cursor: CursorSettings,
+) -> Result<CursorSettings, String> {
+    let updated = settings.update(|s| s.cursor = cursor.validated())?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-cursor", updated.cursor);
+    }
+    debug!("Cursor updated: {:?}", updated.cursor);
+    Ok(updated.cursor)
+}
+
+/// Set antialiasing, the frame rate cap and reduced motion. The cap and
+/// motion apply at once, antialiasing from the next page load.
+#[tauri::command]
+pub fn set_graphics(
+    settings: State<'_, SettingsState>,
+    graphics: GraphicsSettings,
+) -> Result<GraphicsSettings, String> {
+    let updated = settings.update(|s| s.graphics = graphics.validated())?;
+    debug!("Graphics updated: {:?}", updated.graphics);
+    Ok(updated.graphics)
+}
+
+/// Turn the low-spec preset on or off (see `low_spec.rs`); returns the
+/// updated settings
+#[tauri::command]
+pub fn set_low_spec_mode(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<Settings, String> {
+    let updated = settings.update(|s| low_spec::apply(s, enabled))?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-audio-mix", &updated.audio_mix);
+    }
+    info!("Low-spec mode {}", if enabled { "on" } else { "off" });
+    Ok(updated)
+}
+
+/// Archive settings, profiles, customization, macros, reminders, planner
+/// comps and session stats; returns the archive path
+#[tauri::command]
+pub fn backup_user_data() -> Result<String, String> {
+    backup::create().map(|path| path.display().to_string())
+}
+
+/// Backups in the backup folder, newest first
+#[tauri::command]
+pub fn list_backups() -> Vec<BackupInfo> {
+    backup::list()
+}
+
+/// Restore user data from a backup archive. The files are checked and
+/// staged, then the app restarts and puts them in place before loading.
+#[tauri::command]
+pub async fn restore_user_data(app: AppHandle, path: String) -> Result<(), String> {
+    backup::stage_restore(std::path::Path::new(&path))?;
+    info!("Restarting to restore user data...");
+    // Not a crash; Exit is not emitted on restart
+    safe_mode::finish();
+    crate::restore_system_state(&app);
+    app.restart();
+}
+
+/// Turn runtime fixes and additions on or off. The runtime reads them when
+/// the webview is built: from the next start or `recreate_webview`.
+#[tauri::command]
+pub fn set_injection_flags(
+    settings: State<'_, SettingsState>,
+    flags: InjectionFlags,
+) -> Result<InjectionFlags, String> {
+    let updated = settings.update(|s| s.injection_flags = flags)?;
+    info!(
+        "Injection flags set (from the next start): {:?}",
+        updated.injection_flags
+    );
+    Ok(updated.injection_flags)
+}
+
+/// Turn weekly backups on or off and set how many are kept
+#[tauri::command]
+pub fn set_backups(
+    settings: State<'_, SettingsState>,
+    backups: BackupSettings,
+) -> Result<BackupSettings, String> {
+    let updated = settings.update(|s| s.backups = backups.validated())?;
+    info!(
+        "Weekly backups {} (keeping {})",
+        if updated.backups.weekly { "on" } else { "off" },
+        updated.backups.keep
+    );
+    Ok(updated.backups)
+}
+
+/// Configure spoken announcements; turning them on says so, as a check
+/// that a voice is available. Returns the stored settings.
+#[tauri::command]
+pub fn set_tts(
+    settings: State<'_, SettingsState>,
+    tts: TtsSettings,
+) -> Result<TtsSettings, String> {
+    let tts = tts.validated();
+    let was_enabled = settings.get().tts.enabled;
+    let updated = settings.update(|s| s.tts = tts)?;
+    if updated.tts.enabled && !was_enabled {
+        tts::speak("Announcements on".to_string());
+    }
+    Ok(updated.tts)
+}
+
+/// Skip launch animations and autoplaying menu music (from the next page
+/// load)
+#[tauri::command]
+pub fn set_skip_intro(settings: State<'_, SettingsState>, enabled: bool) -> Result<bool, String> {
+    let updated = settings.update(|s| s.skip_intro = enabled)?;
+    info!(
+        "Intro suppression {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(updated.skip_intro)
+}
+
+/// Active profile name and its settings
+#[tauri::command]
+pub fn get_profile(profile: State<'_, ProfileState>) -> ProfileInfo {
+    ProfileInfo {
+        name: crate::profile::active().to_string(),
+        settings: profile.get(),
+    }
+}
+
+/// Set the page zoom factor for this profile
+#[tauri::command]
+pub fn set_zoom(
+    app: AppHandle,
+    profile: State<'_, ProfileState>,
+    zoom: f64,
+) -> Result<f64, String> {
+    let zoom = crate::profile::validate_zoom(zoom)?;
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    window.set_zoom(zoom).map_err(|e| e.to_string())?;
+    let updated = profile.update(|p| p.zoom = zoom)?;
+    Ok(updated.zoom)
+}
+
+/// Shop shortcut bindings of this profile and the keys the page translates
+#[tauri::command]
+pub fn get_key_bindings(profile: State<'_, ProfileState>) -> KeyRemap {
+    profile.get().key_bindings.into()
+}
+
+/// Rebind the shop shortcuts for this profile and apply them to the page
+#[tauri::command]
+pub fn set_key_bindings(
+    app: AppHandle,
+    profile: State<'_, ProfileState>,
+    bindings: KeyBindings,
+) -> Result<KeyRemap, String> {
+    let bindings = bindings.validated()?;
+    let updated = profile.update(|p| p.key_bindings = bindings)?;
+    let remap = KeyRemap::from(updated.key_bindings);
+    info!("Key bindings updated: {:?}", remap.translate);
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-key-bindings", &remap);
+    }
+    Ok(remap)
+}
+
+/// Remember the overlay's visibility and layout for this profile
+#[tauri::command]
+pub fn set_overlay_state(
+    profile: State<'_, ProfileState>,
+    visible: bool,
+    layout: OverlayLayout,
+) -> Result<(), String> {
+    profile.update(|p| p.overlay = Some(OverlayState { visible, layout }))?;
+    Ok(())
+}
+
+/// Replace the scheduled sessions. On Windows they are also registered with
+/// Task Scheduler so the app starts in time.
+#[tauri::command]
+pub fn set_scheduled_tasks(
+    settings: State<'_, SettingsState>,
+    tasks: Vec<ScheduledTask>,
+) -> Result<Vec<ScheduledTask>, String> {
+    let tasks = scheduler::validate(tasks)?;
+    let previous = settings.get().scheduled_tasks.len();
+    scheduler::register_launches(previous, &tasks)?;
+    let updated = settings.update(|s| s.scheduled_tasks = tasks)?;
+    info!("{} scheduled sessions", updated.scheduled_tasks.len());
+    Ok(updated.scheduled_tasks)
+}
+
+/// Add a lobby/tournament reminder (`at` in unix seconds)
+#[tauri::command]
+pub fn add_reminder(
+    reminders: State<'_, ReminderStore>,
+    label: String,
+    at: u64,
+) -> Result<Reminder, String> {
+    reminders.add(&label, at)
+}
+
+/// Add a reminder from a shared `pacdeluxe://remind` link
+#[tauri::command]
+pub fn add_reminder_link(
+    reminders: State<'_, ReminderStore>,
+    link: String,
+) -> Result<Reminder, String> {
+    reminders.add_link(&link)
+}
+
+/// Upcoming reminders, soonest first
+#[tauri::command]
+pub fn list_reminders(reminders: State<'_, ReminderStore>) -> Vec<Reminder> {
+    reminders.list()
+}
+
+#[tauri::command]
+pub fn remove_reminder(reminders: State<'_, ReminderStore>, id: u64) -> Result<(), String> {
+    reminders.remove(id)
+}
+
+/// Open the stats dashboard window (or focus it if open)
+#[tauri::command]
+pub fn open_dashboard(app: AppHandle) -> Result<(), String> {
+    dashboard::open(&app)
+}
+
+/// Open the team planner window (or focus it if open)
+#[tauri::command]
+pub fn open_planner(app: AppHandle) -> Result<(), String> {
+    planner::open(&app)
+}
+
+/// Keep the team planner beside the game window and above other windows,
+/// or let it go
+#[tauri::command]
+pub fn pin_planner(app: AppHandle, pinned: bool) -> Result<(), String> {
+    planner::set_pinned(&app, pinned)
+}
+
+/// Saved team comps, by name
+#[tauri::command]
+pub fn list_team_comps() -> Result<Vec<TeamComp>, String> {
+    planner::list()
+}
+
+/// Save a team comp, replacing one with the same name; returns it as saved
+#[tauri::command]
+pub fn save_team_comp(comp: TeamComp) -> Result<TeamComp, String> {
+    planner::save(comp)
+}
+
+#[tauri::command]
+pub fn delete_team_comp(name: String) -> Result<(), String> {
+    planner::delete(&name)
+}
+
+/// Share code for a team comp
+#[tauri::command]
+pub fn export_team_comp(comp: TeamComp) -> Result<String, String> {
+    planner::export_code(&comp)
+}
+
+/// Team comp from a share code; not saved until `save_team_comp`
+#[tauri::command]
+pub fn import_team_comp(code: String) -> Result<TeamComp, String> {
+    planner::import_code(&code)
+}
+
+/// Write a shareable report of this session (`html` or `png`) to the
+/// reports folder; returns the file path
+#[tauri::command]
+pub async fn render_report(
+    app: AppHandle,
+    history: State<'_, PerformanceHistory>,
+    sessions: State<'_, SessionStore>,
+    matches: State<'_, MatchLog>,
+    upstream: State<'_, health::UpstreamStatus>,
+    format: ReportFormat,
+) -> Result<String, String> {
+    let session_report = SessionReport {
+        generated_at: report::unix_now(),
+        version: app.package_info().version.to_string(),
+        system: collect_system_info(&upstream),
+        play_time: sessions.summary(),
+        matches: matches.matches(),
+        samples: history.snapshot(),
+    };
+    report::write(&session_report, format).map(|path| path.display().to_string())
+}
+
+/// Log bridge for the injected runtime.
+/// Routes page-side diagnostics (e.g. CSP violation reports) into native logs.
+#[tauri::command]
+pub fn log_page_event(level: String, source: String, message: String) {
+    match level.as_str() {
+        "error" | "warn" => warn!("[page:{}] {}", source, message),
+        "info" => info!("[page:{}] {}", source, message),
+        _ => debug!("[page:{}] {}", source, message),
+    }
+}
+
+/// Report the current SPA route from the injected runtime.
+/// Only the public route is used; it drives the `pac-page-state` event bus.
+#[tauri::command]
+pub fn report_page_state(app: AppHandle, page: State<'_, PageState>, route: String) -> GamePhase {
+    page.update(&app, &route)
+}
+
+/// Get the current game phase (derived from the route)
+#[tauri::command]
+pub fn get_page_state(page: State<'_, PageState>) -> GamePhase {
+    page.phase()
+}
+
+/// Put a group of settings back to their defaults, after copying the
+/// current files to `.bak`. `Window` also resets the profile's window
+/// geometry and zoom, `Hotkeys` its key bindings, `All` the whole profile.
+#[tauri::command]
+pub fn reset_settings(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    profile: State<'_, ProfileState>,
+    stream: State<'_, OverlayStream>,
+    guard: State<'_, PowerGuard>,
+    scope: ResetScope,
+) -> Result<Settings, String> {
+    settings::back_up()?;
+    profile::back_up()?;
+    let updated = settings.update(|s| s.reset(scope))?;
+
+    let defaults = ProfileSettings::default();
+    match scope {
+        ResetScope::All => {
+            profile.update(|p| *p = defaults)?;
+        }
+        ResetScope::Window => {
+            profile.update(|p| {
+                p.window = defaults.window;
+                p.zoom = defaults.zoom;
+            })?;
+        }
+        ResetScope::Hotkeys => {
+            profile.update(|p| p.key_bindings = defaults.key_bindings)?;
+        }
+        ResetScope::Performance | ResetScope::Injection => {}
+    }
+
+    // What the setters apply right away; the rest follows settings_changed
+    // or the next start
+    if matches!(scope, ResetScope::All | ResetScope::Performance) {
+        power_guard::apply_profile(guard.active_profile(updated.power_profile));
+        elevation_policy::set(updated.elevation_policy.clone());
+        let applied = resource_limits::apply(updated.resource_limits)
+            .and_then(|_| idle_throttle::apply(updated.idle_throttle))
+            .and_then(|_| refresh_rate::set_enabled(&app, updated.dynamic_refresh));
+        if let Err(e) = applied {
+            warn!("Reset settings not applied until restart: {}", e);
+        }
+    }
+    stream.set_interval(updated.overlay.stats_interval_ms);
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-audio-mix", &updated.audio_mix);
+        let _ = window.emit("pac-quick-reference", &updated.quick_reference);
+        let remap = KeyRemap::from(profile.get().key_bindings);
+        let _ = window.emit("pac-key-bindings", &remap);
+    }
+    info!("Settings reset: {:?}", scope);
+    Ok(updated)
+}
+
+/// Configure the stats overlay hotkey and layout cycle
+#[tauri::command]
+pub fn set_overlay_config(
+    settings: State<'_, SettingsState>,
+    stream: State<'_, OverlayStream>,
+    overlay: OverlaySettings,
+) -> Result<OverlaySettings, String> {
+    let overlay = overlay.validated()?;
+    let updated = settings.update(|s| s.overlay = overlay)?;
+    stream.set_interval(updated.overlay.stats_interval_ms);
+    info!(
+        "Overlay config updated: hotkey={}, layouts={:?}",
+        updated.overlay.hotkey, updated.overlay.layouts
+    );
+    Ok(updated.overlay)
+}
+
+/// Get play time for the current stretch, today, and recent days
+#[tauri::command]
+pub fn get_play_time(sessions: State<'_, SessionStore>) -> PlayTimeSummary {
+    sessions.summary()
+}
+
+/// Configure the break reminder
+#[tauri::command]
+pub fn set_break_reminder(
+    settings: State<'_, SettingsState>,
+    reminder: BreakReminderSettings,
+) -> Result<BreakReminderSettings, String> {
+    if reminder.interval_minutes == 0 {
+        return Err("Break reminder interval must be at least one minute".to_string());
+    }
+    let updated = settings.update(|s| s.break_reminder = reminder)?;
+    info!("Break reminder updated: {:?}", updated.break_reminder);
+    Ok(updated.break_reminder)
+}
+
+/// Configure automatic reload on renderer memory bloat
+#[tauri::command]
+pub fn set_soft_reload(
+    settings: State<'_, SettingsState>,
+    policy: SoftReloadSettings,
+) -> Result<SoftReloadSettings, String> {
+    let updated = settings.update(|s| s.soft_reload = policy)?;
+    info!("Soft reload policy updated: {:?}", updated.soft_reload);
+    Ok(updated.soft_reload)
+}
+
+/// Cancel a pending soft reload (from the pre-reload toast).
+/// Returns false when no reload was pending.
+#[tauri::command]
+pub fn cancel_soft_reload(state: State<'_, SoftReloadState>) -> bool {
+    state.cancel()
+}
+
+/// Update info returned to JavaScript
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub version: Option<String>,
+    pub body: Option<String>,
+    /// Signature check of the available update
+    pub signature: Option<updates::SignatureStatus>,
+    /// Key ID of the key updates are verified with
+    pub signing_key: Option<String>,
+}
+
+/// Global state to store pending update for download
+pub struct PendingUpdate(pub Mutex<Option<tauri_plugin_updater::Update>>);
+
+/// Check for available updates
+/// Returns update info if available, or indicates no update needed
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+    updates::check(&app).await
+}
+
+/// The update found by the last check, if any, without checking again
+#[tauri::command]
+pub fn get_available_update(pending: State<'_, PendingUpdate>) -> Option<UpdateInfo> {
+    updates::available(&pending)
+}
+
+/// Outcome of the last update check and when the next one is due
+#[tauri::command]
+pub fn get_update_state(tracker: State<'_, UpdateTracker>) -> UpdateState {
+    tracker.get()
+}
+
+/// Turn the background update checks on or off and set their interval
+#[tauri::command]
+pub fn set_update_checks(
+    settings: State<'_, SettingsState>,
+    checks: UpdateCheckSettings,
+) -> Result<UpdateCheckSettings, String> {
+    let checks = checks.validated();
+    let updated = settings.update(|s| s.update_checks = checks)?;
+    info!("Update checks updated: {:?}", updated.update_checks);
+    Ok(updated.update_checks)
+}
+
+/// Download and install the pending update, emitting progress events
+#[tauri::command]
+pub async fn install_update(
+    app: AppHandle,
+    pending: State<'_, PendingUpdate>,
+) -> Result<(), String> {
+    info!("Installing update...");
+
+    // Cloned, not taken: a failed download or signature check leaves it
+    // pending so the banner can retry without another check
+    let update = pending
+        .0
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| "No pending update to install".to_string())?;
+    updates::require_signature(&update)?;
+
+    // A patch against the kept installer when one is listed; the rebuilt
+    // installer is checked against the same signature as a full download
+    let bytes = match updates::patched_installer(&app, &update).await {
+        Some(bytes) => bytes,
+        None => {
+            // Download with progress reporting via Tauri events. The plugin
+            // checks the download against the signature before returning it.
+            let app_handle = app.clone();
+            let result = update
+                .download(
+                    move |chunk_length, total_size| {
+                        let _ = app_handle.emit(
+                            "update-progress",
+                            serde_json::json!({
+                                "chunk": chunk_length,
+                                "total": total_size.unwrap_or(0),
+                            }),
+                        );
+                    },
+                    || {},
+                )
+                .await;
+            match result {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    warn!("Update download failed: {}", e);
+                    if matches!(e, tauri_plugin_updater::Error::Minisign(_)) {
+                        updates::record_signature(&app, updates::SignatureStatus::Invalid);
+                        return Err(format!("Update signature check failed: {}", e));
+                    }
+                    return Err(e.to_string());
+                }
+            }
+        }
+    };
+    updates::record_signature(&app, updates::SignatureStatus::Verified);
+
+    // Kept before installing: on Windows the installer replaces this process
+    updates::keep_installer(&update, &bytes);
+    crate::restore_system_state(&app);
+    update.install(&bytes).map_err(|e| {
+        warn!("Update installation failed: {}", e);
+        e.to_string()
+    })?;
+    pending.0.lock().unwrap().take();
+
+    info!("Update installed successfully");
+    Ok(())
+}
+
+/// Restart the application
+#[tauri::command]
+pub async fn restart_app(app: AppHandle) -> Result<(), String> {
+    info!("Restarting application...");
+    // Not a crash; Exit is not emitted on restart
+    safe_mode::finish();
+    crate::restore_system_state(&app);
+    app.restart();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_relative_paths_to_production() {
+        let target = resolve_proxy_target("/profile?t=1", "GET").unwrap();
+        assert_eq!(
+            target.as_str(),
+            "https://pokemon-auto-chess.com/profile?t=1"
+        );
+    }
+
+    #[test]
+    fn emulated_bandwidth_paces_transfers() {
+        assert_eq!(emulated_transfer_time(50_000, 400), Duration::from_secs(1));
+        assert_eq!(emulated_transfer_time(50_000, 0), Duration::ZERO);
     }
 
     #[test]
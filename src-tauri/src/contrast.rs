@@ -0,0 +1,172 @@
+//! High Contrast and Dark UI - Cross-platform
+//!
+//! Accessibility option (`contrast_mode`, off by default): the injected
+//! runtime adds a dark or high-contrast stylesheet for the game's HTML
+//! panels (menus, shop and item tooltips, modals); the board canvas is left
+//! alone. `FollowSystem` applies the high-contrast sheet while the OS high
+//! contrast setting is on, which is polled and sent to the page as
+//! `pac-contrast` when it changes.
+//!
+//! - Windows: `SPI_GETHIGHCONTRAST`
+//! - Linux: GNOME's `org.gnome.desktop.a11y.interface high-contrast`, or a
+//!   `HighContrast` GTK theme
+
+use crate::settings::SettingsState;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+use ts_rs::TS;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// `contrast_mode` setting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum ContrastMode {
+    #[default]
+    Off,
+    Dark,
+    HighContrast,
+    /// High contrast while the OS high contrast setting is on
+    FollowSystem,
+}
+
+/// Stylesheet the runtime applies
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContrastStyle {
+    Dark,
+    HighContrast,
+}
+
+/// Payload of `pac-contrast` and `get_contrast`
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct ContrastInfo {
+    pub mode: ContrastMode,
+    pub system_high_contrast: bool,
+    pub style: Option<ContrastStyle>,
+}
+
+fn resolve(mode: ContrastMode, system_high_contrast: bool) -> Option<ContrastStyle> {
+    match mode {
+        ContrastMode::Off => None,
+        ContrastMode::Dark => Some(ContrastStyle::Dark),
+        ContrastMode::HighContrast => Some(ContrastStyle::HighContrast),
+        ContrastMode::FollowSystem => system_high_contrast.then_some(ContrastStyle::HighContrast),
+    }
+}
+
+/// Whether the OS high contrast setting is on
+#[cfg(target_os = "windows")]
+pub fn system_high_contrast() -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SYSTEM_PARAMETERS_INFO_ACTION, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    /// HIGHCONTRASTW; the Accessibility bindings aren't enabled
+    #[repr(C)]
+    struct HighContrast {
+        size: u32,
+        flags: u32,
+        default_scheme: *mut u16,
+    }
+    const SPI_GETHIGHCONTRAST: u32 = 0x0042;
+    const HCF_HIGHCONTRASTON: u32 = 0x1;
+
+    let mut value = HighContrast {
+        size: std::mem::size_of::<HighContrast>() as u32,
+        flags: 0,
+        default_scheme: std::ptr::null_mut(),
+    };
+    // SAFETY: `value` is a HIGHCONTRASTW with its size set
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SYSTEM_PARAMETERS_INFO_ACTION(SPI_GETHIGHCONTRAST),
+            value.size,
+            Some((&mut value as *mut HighContrast).cast()),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    }
+    .is_ok();
+    ok && value.flags & HCF_HIGHCONTRASTON != 0
+}
+
+#[cfg(target_os = "linux")]
+pub fn system_high_contrast() -> bool {
+    if std::env::var("GTK_THEME").is_ok_and(|theme| theme.contains("HighContrast")) {
+        return true;
+    }
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.a11y.interface", "high-contrast"])
+        .output()
+        .is_ok_and(|output| {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true"
+        })
+}
+
+/// Current mode, OS setting and resulting stylesheet
+pub fn info(app: &AppHandle) -> ContrastInfo {
+    let mode = app.state::<SettingsState>().get().contrast_mode;
+    let system_high_contrast = system_high_contrast();
+    ContrastInfo {
+        mode,
+        system_high_contrast,
+        style: resolve(mode, system_high_contrast),
+    }
+}
+
+/// Tell the page about a new mode or OS setting
+pub fn notify(app: &AppHandle, info: ContrastInfo) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("pac-contrast", info);
+    }
+}
+
+/// Follow the OS setting while `FollowSystem` is chosen
+pub fn spawn(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("contrast-watch".to_string())
+        .spawn(move || {
+            let mut last: Option<ContrastInfo> = None;
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                if app.state::<SettingsState>().get().contrast_mode != ContrastMode::FollowSystem {
+                    last = None;
+                    continue;
+                }
+                let current = info(&app);
+                if let Some(previous) = last {
+                    if previous.system_high_contrast != current.system_high_contrast {
+                        info!(
+                            "OS high contrast {}",
+                            if current.system_high_contrast {
+                                "on"
+                            } else {
+                                "off"
+                            }
+                        );
+                        notify(&app, current);
+                    }
+                }
+                last = Some(current);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start high contrast watch: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_system_only_applies_with_os_high_contrast() {
+        assert_eq!(resolve(ContrastMode::FollowSystem, false), None);
+        assert_eq!(
+            resolve(ContrastMode::FollowSystem, true),
+            Some(ContrastStyle::HighContrast)
+        );
+        assert_eq!(resolve(ContrastMode::Dark, true), Some(ContrastStyle::Dark));
+        assert_eq!(resolve(ContrastMode::Off, true), None);
+    }
+}
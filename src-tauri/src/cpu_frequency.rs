@@ -0,0 +1,221 @@
+//! CPU Frequency - Cross-platform
+//!
+//! Current, maximum (boost) and limited clock speeds plus the active power
+//! plan. A long-running game pinned at a capped clock or on "Power saver"
+//! looks fine in averaged CPU usage but stutters on a single busy core.
+//!
+//! - Windows: `CallNtPowerInformation(ProcessorInformation)` per logical
+//!   processor, and `PowerGetActiveScheme` for the power plan
+//! - Linux: cpufreq sysfs, and the ACPI platform profile or cpufreq governor
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Clock speeds across all logical processors
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, PartialEq)]
+pub struct CpuFrequency {
+    /// Average current clock
+    #[ts(type = "number | null")]
+    pub current_mhz: Option<u64>,
+    /// Highest clock the processors can reach (boost on Windows)
+    #[ts(type = "number | null")]
+    pub max_mhz: Option<u64>,
+    /// Clock cap below `max_mhz` (power plan, thermal or firmware limit)
+    #[ts(type = "number | null")]
+    pub limit_mhz: Option<u64>,
+    /// Active power plan or profile, e.g. "Balanced"
+    pub power_plan: Option<String>,
+}
+
+/// One logical processor's clocks, in MHz
+#[derive(Debug, Clone, Copy, Default)]
+struct CoreClock {
+    current: u64,
+    max: u64,
+    limit: u64,
+}
+
+/// Combine per-core readings. The limit is only reported when it is below
+/// the maximum, since most systems report limit == max when uncapped.
+fn summarize(cores: &[CoreClock], power_plan: Option<String>) -> CpuFrequency {
+    let current: Vec<u64> = cores
+        .iter()
+        .map(|c| c.current)
+        .filter(|&mhz| mhz > 0)
+        .collect();
+    let max_mhz = cores.iter().map(|c| c.max).filter(|&mhz| mhz > 0).max();
+    let limit_mhz = cores
+        .iter()
+        .map(|c| c.limit)
+        .filter(|&mhz| mhz > 0)
+        .min()
+        .filter(|&limit| max_mhz.is_some_and(|max| limit < max));
+    CpuFrequency {
+        current_mhz: (!current.is_empty())
+            .then(|| current.iter().sum::<u64>() / current.len() as u64),
+        max_mhz,
+        limit_mhz,
+        power_plan,
+    }
+}
+
+/// Name of a built-in Windows power scheme
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn power_scheme_name(guid: u128) -> Option<&'static str> {
+    match guid {
+        0xa1841308_3541_4fab_bc81_f71556f20b4a => Some("Power saver"),
+        0x381b4222_f694_41f0_9685_ff5bb260df2e => Some("Balanced"),
+        0x8c5e7fda_e8bf_4a96_9a85_a6e23a8c635c => Some("High performance"),
+        0xe9a42b02_d5df_448d_aa00_03f14749eb61 => Some("Ultimate performance"),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn core_clocks() -> Vec<CoreClock> {
+    use windows::Win32::System::Power::{CallNtPowerInformation, ProcessorInformation};
+
+    /// PROCESSOR_POWER_INFORMATION (not exported by the windows crate)
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct ProcessorPowerInformation {
+        number: u32,
+        max_mhz: u32,
+        current_mhz: u32,
+        mhz_limit: u32,
+        max_idle_state: u32,
+        current_idle_state: u32,
+    }
+
+    let count = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut info = vec![ProcessorPowerInformation::default(); count];
+    let status = unsafe {
+        CallNtPowerInformation(
+            ProcessorInformation,
+            None,
+            0,
+            Some(info.as_mut_ptr().cast()),
+            (info.len() * std::mem::size_of::<ProcessorPowerInformation>()) as u32,
+        )
+    };
+    if status.is_err() {
+        tracing::debug!("CallNtPowerInformation failed: {:?}", status);
+        return Vec::new();
+    }
+    info.iter()
+        .map(|core| CoreClock {
+            current: core.current_mhz as u64,
+            max: core.max_mhz as u64,
+            limit: core.mhz_limit as u64,
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn power_plan() -> Option<String> {
+    use windows::core::GUID;
+    use windows::Win32::Foundation::{LocalFree, ERROR_SUCCESS, HLOCAL};
+    use windows::Win32::System::Power::PowerGetActiveScheme;
+
+    unsafe {
+        let mut scheme: *mut GUID = std::ptr::null_mut();
+        if PowerGetActiveScheme(None, &mut scheme) != ERROR_SUCCESS || scheme.is_null() {
+            return None;
+        }
+        let guid = (*scheme).to_u128();
+        let _ = LocalFree(HLOCAL(scheme.cast()));
+        Some(power_scheme_name(guid).map_or_else(|| "Custom".to_string(), str::to_string))
+    }
+}
+
+/// cpufreq values are in kHz
+#[cfg(target_os = "linux")]
+fn read_khz(path: &std::path::Path) -> u64 {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map_or(0, |khz| khz / 1000)
+}
+
+#[cfg(target_os = "linux")]
+fn core_clocks() -> Vec<CoreClock> {
+    let Ok(entries) = std::fs::read_dir("/sys/devices/system/cpu") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.strip_prefix("cpu")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|entry| {
+            let cpufreq = entry.path().join("cpufreq");
+            CoreClock {
+                current: read_khz(&cpufreq.join("scaling_cur_freq")),
+                max: read_khz(&cpufreq.join("cpuinfo_max_freq")),
+                limit: read_khz(&cpufreq.join("scaling_max_freq")),
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn power_plan() -> Option<String> {
+    [
+        "/sys/firmware/acpi/platform_profile",
+        "/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor",
+    ]
+    .iter()
+    .find_map(|path| std::fs::read_to_string(path).ok())
+    .map(|value| value.trim().to_string())
+    .filter(|value| !value.is_empty())
+}
+
+/// Read the current clocks and power plan
+pub fn read() -> CpuFrequency {
+    summarize(&core_clocks(), power_plan())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(current: u64, max: u64, limit: u64) -> CoreClock {
+        CoreClock {
+            current,
+            max,
+            limit,
+        }
+    }
+
+    #[test]
+    fn uncapped_cores_report_no_limit() {
+        let summary = summarize(&[clock(3000, 4500, 4500), clock(4000, 4500, 4500)], None);
+        assert_eq!(summary.current_mhz, Some(3500));
+        assert_eq!(summary.max_mhz, Some(4500));
+        assert_eq!(summary.limit_mhz, None);
+    }
+
+    #[test]
+    fn capped_cores_report_lowest_limit() {
+        let summary = summarize(
+            &[clock(1800, 4500, 2000), clock(0, 4500, 2400)],
+            Some("Power saver".to_string()),
+        );
+        assert_eq!(summary.current_mhz, Some(1800));
+        assert_eq!(summary.limit_mhz, Some(2000));
+        assert_eq!(summary.power_plan.as_deref(), Some("Power saver"));
+        assert_eq!(summarize(&[], None), CpuFrequency::default());
+    }
+
+    #[test]
+    fn built_in_power_schemes_are_named() {
+        assert_eq!(
+            power_scheme_name(0x381b4222_f694_41f0_9685_ff5bb260df2e),
+            Some("Balanced")
+        );
+        assert_eq!(power_scheme_name(0), None);
+    }
+}
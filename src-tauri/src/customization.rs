@@ -0,0 +1,294 @@
+//! Customization - Cross-platform
+//!
+//! Custom background image, CSS theme and user scripts, stored in the
+//! `customization` folder of the config directory. Files usually arrive by
+//! dropping them on the window; the injected runtime routes each kind here.
+//!
+//! The runtime applies the background and theme. User scripts are only
+//! installed after the user confirms in a native dialog, which the page
+//! can't answer for them, and run after each game page load (see
+//! `main.rs`).
+
+use crate::settings;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+const CUSTOMIZATION_DIR_NAME: &str = "customization";
+const SCRIPTS_DIR_NAME: &str = "scripts";
+const BACKGROUND_STEM: &str = "background";
+const THEME_FILE_NAME: &str = "theme.css";
+
+const MAX_BACKGROUND_BYTES: usize = 10 * 1024 * 1024;
+const MAX_THEME_BYTES: usize = 512 * 1024;
+const MAX_SCRIPT_BYTES: usize = 1024 * 1024;
+
+/// Image types accepted as backgrounds, with their MIME type
+const BACKGROUND_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("webp", "image/webp"),
+    ("gif", "image/gif"),
+];
+
+/// What a file is used for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CustomizationKind {
+    Background,
+    Theme,
+    UserScript,
+}
+
+/// Current customizations, as sent to the runtime
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Customizations {
+    /// Background as a `data:` URL
+    pub background: Option<String>,
+    pub theme: Option<String>,
+    /// Installed user script file names
+    pub user_scripts: Vec<String>,
+}
+
+fn extension(file_name: &str) -> Option<String> {
+    Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+/// Which manager handles a dropped file, by extension
+pub fn classify(file_name: &str) -> Option<CustomizationKind> {
+    match extension(file_name)?.as_str() {
+        "css" => Some(CustomizationKind::Theme),
+        "js" => Some(CustomizationKind::UserScript),
+        ext if BACKGROUND_TYPES.iter().any(|(known, _)| *known == ext) => {
+            Some(CustomizationKind::Background)
+        }
+        _ => None,
+    }
+}
+
+/// Reduce a dropped script's name to a safe file name ending in `.js`
+fn script_file_name(file_name: &str) -> Result<String, String> {
+    let base = Path::new(file_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let stem: String = base
+        .strip_suffix(".js")
+        .unwrap_or(base)
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .take(64)
+        .collect();
+    let stem = stem.trim_matches('.');
+    if stem.is_empty() {
+        return Err(format!("Invalid script name: {:?}", file_name));
+    }
+    Ok(format!("{}.js", stem))
+}
+
+fn customization_dir() -> Result<PathBuf, String> {
+    settings::config_dir()
+        .map(|dir| dir.join(CUSTOMIZATION_DIR_NAME))
+        .ok_or_else(|| "No config directory available".to_string())
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn check_size(what: &str, len: usize, max: usize) -> Result<(), String> {
+    if len > max {
+        return Err(format!(
+            "{} too large ({} KB, max {} KB)",
+            what,
+            len / 1024,
+            max / 1024
+        ));
+    }
+    Ok(())
+}
+
+fn remove_backgrounds(dir: &Path) {
+    for (ext, _) in BACKGROUND_TYPES {
+        let _ = std::fs::remove_file(dir.join(format!("{}.{}", BACKGROUND_STEM, ext)));
+    }
+}
+
+/// Replace the background image with base64 `data`; returns its `data:` URL
+pub fn set_background(file_name: &str, data: &str) -> Result<String, String> {
+    let ext = extension(file_name)
+        .filter(|_| classify(file_name) == Some(CustomizationKind::Background))
+        .ok_or_else(|| format!("Unsupported image type: {}", file_name))?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid image data: {}", e))?;
+    check_size("Background image", bytes.len(), MAX_BACKGROUND_BYTES)?;
+
+    let dir = customization_dir()?;
+    remove_backgrounds(&dir);
+    write_file(&dir.join(format!("{}.{}", BACKGROUND_STEM, ext)), &bytes)?;
+    info!("Custom background set ({} KB)", bytes.len() / 1024);
+    Ok(background_data_url(&ext, data))
+}
+
+fn background_data_url(ext: &str, base64_data: &str) -> String {
+    let mime = BACKGROUND_TYPES
+        .iter()
+        .find(|(known, _)| *known == ext)
+        .map_or("application/octet-stream", |(_, mime)| mime);
+    format!("data:{};base64,{}", mime, base64_data)
+}
+
+/// Replace the CSS theme
+pub fn set_theme(css: &str) -> Result<(), String> {
+    check_size("Theme", css.len(), MAX_THEME_BYTES)?;
+    write_file(&customization_dir()?.join(THEME_FILE_NAME), css.as_bytes())?;
+    info!("Custom theme set ({} bytes)", css.len());
+    Ok(())
+}
+
+/// Ask the user whether to install a user script
+pub async fn confirm_user_script(
+    app: &AppHandle,
+    file_name: &str,
+    len: usize,
+) -> Result<bool, String> {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let name = script_file_name(file_name)?;
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.dialog()
+        .message(format!(
+            "Install {} ({} bytes) as a user script?\n\nIt will run on every game page load and can use PACDeluxe's commands. Only install scripts you trust.",
+            name, len
+        ))
+        .title("PACDeluxe: Install User Script")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Install".to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    tauri::async_runtime::spawn_blocking(move || rx.recv().unwrap_or(false))
+        .await
+        .map_err(|e| format!("Confirmation dialog failed: {}", e))
+}
+
+/// Install (or replace) a user script; returns the stored file name
+pub fn install_user_script(file_name: &str, source: &str) -> Result<String, String> {
+    check_size("User script", source.len(), MAX_SCRIPT_BYTES)?;
+    let name = script_file_name(file_name)?;
+    let path = customization_dir()?.join(SCRIPTS_DIR_NAME).join(&name);
+    write_file(&path, source.as_bytes())?;
+    info!("User script installed: {}", name);
+    Ok(name)
+}
+
+/// Remove one kind of customization. `script` selects a single user script;
+/// without it every user script is removed.
+pub fn clear(kind: CustomizationKind, script: Option<&str>) -> Result<(), String> {
+    let dir = customization_dir()?;
+    let result = match kind {
+        CustomizationKind::Background => {
+            remove_backgrounds(&dir);
+            Ok(())
+        }
+        CustomizationKind::Theme => std::fs::remove_file(dir.join(THEME_FILE_NAME)),
+        CustomizationKind::UserScript => match script {
+            Some(name) => {
+                std::fs::remove_file(dir.join(SCRIPTS_DIR_NAME).join(script_file_name(name)?))
+            }
+            None => std::fs::remove_dir_all(dir.join(SCRIPTS_DIR_NAME)),
+        },
+    };
+    match result {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove {:?}: {}", kind, e)),
+    }
+    info!("Cleared customization: {:?}", kind);
+    Ok(())
+}
+
+fn load_background(dir: &Path) -> Option<String> {
+    BACKGROUND_TYPES.iter().find_map(|(ext, _)| {
+        let bytes = std::fs::read(dir.join(format!("{}.{}", BACKGROUND_STEM, ext))).ok()?;
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+        Some(background_data_url(ext, &data))
+    })
+}
+
+/// Installed user scripts as (file name, source), sorted by name
+pub fn user_scripts() -> Vec<(String, String)> {
+    let Ok(dir) = customization_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir.join(SCRIPTS_DIR_NAME)) else {
+        return Vec::new();
+    };
+    let mut scripts: Vec<(String, String)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if !name.ends_with(".js") {
+                return None;
+            }
+            match std::fs::read_to_string(entry.path()) {
+                Ok(source) => Some((name, source)),
+                Err(e) => {
+                    warn!("Failed to read user script {}: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+/// Everything currently customized
+pub fn load() -> Customizations {
+    let Ok(dir) = customization_dir() else {
+        return Customizations::default();
+    };
+    Customizations {
+        background: load_background(&dir),
+        theme: std::fs::read_to_string(dir.join(THEME_FILE_NAME)).ok(),
+        user_scripts: user_scripts().into_iter().map(|(name, _)| name).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_files_are_classified_by_extension() {
+        assert_eq!(
+            classify("Wallpaper.PNG"),
+            Some(CustomizationKind::Background)
+        );
+        assert_eq!(classify("dark.css"), Some(CustomizationKind::Theme));
+        assert_eq!(classify("helper.js"), Some(CustomizationKind::UserScript));
+        assert_eq!(classify("notes.txt"), None);
+        assert_eq!(classify("no-extension"), None);
+    }
+
+    #[test]
+    fn script_names_are_sanitized() {
+        assert_eq!(script_file_name("my script.js").unwrap(), "myscript.js");
+        assert_eq!(script_file_name("../../evil.js").unwrap(), "evil.js");
+        assert!(script_file_name("...js").is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Stats Dashboard - Cross-platform
+//!
+//! A separate window charting the performance history (CPU, memory, JS heap,
+//! stutters, bandwidth, temperature) and play time. The page is served by
+//! the localhost server next to the retry screen and reads everything
+//! through the existing IPC queries; it never touches the game page.
+
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tracing::info;
+
+pub const DASHBOARD_WINDOW_LABEL: &str = "dashboard";
+/// Path of the dashboard page on the localhost server
+pub const DASHBOARD_PAGE_PATH: &str = "/pacdeluxe/dashboard";
+
+/// Port of the localhost server, set once it is bound
+static LOCAL_PORT: OnceLock<u16> = OnceLock::new();
+
+pub fn set_local_port(port: u16) {
+    let _ = LOCAL_PORT.set(port);
+}
+
+pub(crate) fn local_port() -> Option<u16> {
+    LOCAL_PORT.get().copied()
+}
+
+/// Open the dashboard, or bring it to the front if it is already open
+pub fn open(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(DASHBOARD_WINDOW_LABEL) {
+        let _ = window.unminimize();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    let port = local_port().ok_or_else(|| "Localhost server is not running".to_string())?;
+    let url = format!("http://localhost:{}{}", port, DASHBOARD_PAGE_PATH)
+        .parse()
+        .map_err(|e| format!("Invalid dashboard URL: {}", e))?;
+    WebviewWindowBuilder::new(app, DASHBOARD_WINDOW_LABEL, WebviewUrl::External(url))
+        .title("PACDeluxe - Stats")
+        .inner_size(960.0, 720.0)
+        .min_inner_size(640.0, 480.0)
+        .build()
+        .map_err(|e| format!("Failed to open dashboard: {}", e))?;
+    info!("Stats dashboard opened");
+    Ok(())
+}
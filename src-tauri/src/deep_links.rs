@@ -0,0 +1,158 @@
+//! Deep Links - Cross-platform
+//!
+//! Opens the `pacdeluxe://` links the app hands out:
+//! - `pacdeluxe://join/<room id>` (`copy_room_link`): the runtime joins the
+//!   room once the lobby lists it
+//! - `pacdeluxe://remind?at=<unix seconds>&label=<text>`: adds a reminder
+//!   (see `reminders.rs`)
+//!
+//! The installers register the scheme (`plugins.deep-link` in
+//! tauri.conf.json); Linux builds, which may run as an AppImage, register
+//! it at startup. A link opened while PACDeluxe runs starts a second
+//! process, which the single-instance plugin hands to the running one.
+
+use crate::page_events::{self, PageEvent, ToastLevel};
+use crate::reminders::ReminderStore;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tracing::{info, warn};
+
+pub const SCHEME: &str = "pacdeluxe";
+const JOIN_LINK_PREFIX: &str = "pacdeluxe://join/";
+
+/// Room from the last join link, until the runtime takes it
+static PENDING_JOIN: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug, PartialEq)]
+enum DeepLink {
+    Join(String),
+    Remind(String),
+}
+
+/// Room IDs are short alphanumeric strings (plus `-`/`_`)
+fn is_valid_room_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Build a `pacdeluxe://join/<id>` link, rejecting anything that isn't a
+/// room ID
+pub fn join_link(room_id: &str) -> Result<String, String> {
+    let id = room_id.trim();
+    if !is_valid_room_id(id) {
+        return Err(format!("Invalid room ID: {:?}", room_id));
+    }
+    Ok(format!("{}{}", JOIN_LINK_PREFIX, id))
+}
+
+fn parse(url: &Url) -> Result<DeepLink, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Not a {} link: {}", SCHEME, url));
+    }
+    match url.host_str() {
+        Some("join") => {
+            let id = url.path().trim_start_matches('/');
+            if !is_valid_room_id(id) {
+                return Err(format!("Invalid room ID in {}", url));
+            }
+            Ok(DeepLink::Join(id.to_string()))
+        }
+        Some("remind") => Ok(DeepLink::Remind(url.to_string())),
+        _ => Err(format!("Unknown link: {}", url)),
+    }
+}
+
+fn open(app: &AppHandle, url: &Url) {
+    match parse(url) {
+        Ok(DeepLink::Join(room_id)) => {
+            info!("Join link for room {}", room_id);
+            *PENDING_JOIN.lock().unwrap_or_else(|e| e.into_inner()) = Some(room_id);
+            crate::tray::show_main_window(app);
+            page_events::emit(app, PageEvent::JoinRequested);
+        }
+        Ok(DeepLink::Remind(link)) => match app.state::<ReminderStore>().add_link(&link) {
+            Ok(reminder) => {
+                crate::tray::show_main_window(app);
+                page_events::toast(
+                    app,
+                    ToastLevel::Success,
+                    format!("Reminder added: {}", reminder.label),
+                );
+            }
+            Err(e) => {
+                warn!("Reminder link not added: {}", e);
+                page_events::toast(app, ToastLevel::Warning, e);
+            }
+        },
+        Err(e) => warn!("Ignoring deep link: {}", e),
+    }
+}
+
+/// The room from the last join link, if the runtime hasn't joined it yet
+pub fn take_pending_join() -> Option<String> {
+    PENDING_JOIN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+}
+
+/// Handle the link this process was started with and any opened later
+pub fn install(app: &AppHandle) {
+    #[cfg(target_os = "linux")]
+    if let Err(e) = app.deep_link().register_all() {
+        warn!("Failed to register {}:// links: {}", SCHEME, e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(&handle, &url);
+        }
+    });
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            for url in urls {
+                open(app, &url);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!("Failed to read the startup link: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(link: &str) -> Result<DeepLink, String> {
+        parse(&Url::parse(link).unwrap())
+    }
+
+    #[test]
+    fn links_route_by_host() {
+        assert_eq!(
+            parse_str("pacdeluxe://join/AbC-12_x"),
+            Ok(DeepLink::Join("AbC-12_x".to_string()))
+        );
+        assert!(matches!(
+            parse_str("pacdeluxe://remind?at=1&label=Cup"),
+            Ok(DeepLink::Remind(_))
+        ));
+        assert!(parse_str("pacdeluxe://join/a%22b").is_err());
+        assert!(parse_str("pacdeluxe://join/").is_err());
+        assert!(parse_str("pacdeluxe://settings").is_err());
+        assert!(parse_str("https://join/abc").is_err());
+    }
+
+    #[test]
+    fn join_links_round_trip() {
+        let link = join_link(" room42 ").unwrap();
+        assert_eq!(link, "pacdeluxe://join/room42");
+        assert!(join_link("../x").is_err());
+        assert_eq!(parse_str(&link), Ok(DeepLink::Join("room42".to_string())));
+    }
+}
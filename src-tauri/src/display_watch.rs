@@ -0,0 +1,261 @@
+//! Display Change Watcher - Cross-platform
+//!
+//! Re-applies the stored window mode after the display wakes up or the
+//! monitor setup changes. A fullscreen or borderless window often comes back
+//! at a stale size after the display powers off or the monitor is switched.
+//! The cached HDR info is re-read at the same time (turning HDR on or off is
+//! a display change too), and the page is told when it changed.
+//!
+//! - Windows: subclasses the main window for `WM_DISPLAYCHANGE` and the
+//!   console display state power notification (display turned back on);
+//!   system sleep/wake broadcasts are passed on to `suspend.rs`
+//! - All platforms: `ScaleFactorChanged` (moved to a monitor with another DPI)
+//!
+//! Events come in bursts, so the check runs once things have settled.
+
+use crate::commands::{self, WindowMode};
+use crate::page_events::{self, PageEvent};
+use crate::performance::{self, HdrInfo};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalSize, WebviewWindow, WindowEvent};
+use tracing::{debug, info, warn};
+
+/// Wait after the last display event before checking the window
+const SETTLE_DELAY: Duration = Duration::from_millis(1500);
+
+/// Bumped by every display event; only the latest one's check runs
+static PENDING_CHECK: AtomicU64 = AtomicU64::new(0);
+
+/// Window properties relevant to its mode
+#[derive(Debug, Clone, Copy)]
+struct WindowState {
+    fullscreen: bool,
+    maximized: bool,
+    size: PhysicalSize<u32>,
+    monitor: Option<PhysicalSize<u32>>,
+}
+
+/// Whether the window no longer looks like `mode`. Windowed windows keep
+/// whatever size the user gave them.
+fn has_drifted(mode: WindowMode, state: &WindowState) -> bool {
+    match mode {
+        WindowMode::Windowed => false,
+        WindowMode::Fullscreen => {
+            !state.fullscreen || state.monitor.is_some_and(|monitor| monitor != state.size)
+        }
+        WindowMode::BorderlessWindowed => !state.maximized,
+    }
+}
+
+fn window_state(window: &WebviewWindow) -> Result<WindowState, String> {
+    Ok(WindowState {
+        fullscreen: window.is_fullscreen().map_err(|e| e.to_string())?,
+        maximized: window.is_maximized().map_err(|e| e.to_string())?,
+        size: window.outer_size().map_err(|e| e.to_string())?,
+        monitor: window
+            .current_monitor()
+            .map_err(|e| e.to_string())?
+            .map(|monitor| *monitor.size()),
+    })
+}
+
+fn restore(app: &AppHandle, reason: &str) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    // Minimized windows are restored by the user; check again after that
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let mode = WindowMode::current();
+    let state = match window_state(&window) {
+        Ok(state) => state,
+        Err(e) => {
+            debug!("Window state unavailable after {}: {}", reason, e);
+            return;
+        }
+    };
+    if !has_drifted(mode, &state) {
+        debug!("Window still {:?} after {}", mode, reason);
+        return;
+    }
+    info!("Re-applying {:?} after {} ({:?})", mode, reason, state);
+    if let Err(e) = tauri::async_runtime::block_on(commands::reapply_window_mode(&window, mode)) {
+        warn!("Failed to re-apply window mode: {}", e);
+    }
+}
+
+/// Cached HDR info summarized for the display the game window is on
+pub(crate) fn game_window_hdr(app: &AppHandle) -> HdrInfo {
+    let monitor = app
+        .get_webview_window("main")
+        .and_then(|window| window.current_monitor().ok().flatten())
+        .and_then(|monitor| monitor.name().cloned());
+    performance::get_hdr_info_on(monitor.as_deref())
+}
+
+fn refresh_hdr(app: &AppHandle, reason: &str) {
+    let before = performance::get_hdr_info().outputs;
+    performance::refresh_hdr_info();
+    let info = game_window_hdr(app);
+    if info.outputs != before {
+        info!(
+            "HDR state changed after {}: {} on {} ({} display(s))",
+            reason,
+            info.color_space,
+            info.display_name,
+            info.outputs.len()
+        );
+        page_events::emit(app, PageEvent::HdrChanged { info });
+    }
+}
+
+/// Check the window once display events stop for `SETTLE_DELAY`
+pub(crate) fn schedule_check(app: &AppHandle, reason: &'static str) {
+    let generation = PENDING_CHECK.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    std::thread::Builder::new()
+        .name("display-restore".to_string())
+        .spawn(move || {
+            std::thread::sleep(SETTLE_DELAY);
+            if PENDING_CHECK.load(Ordering::SeqCst) == generation {
+                restore(&app, reason);
+                refresh_hdr(&app, reason);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to schedule window mode check: {}", e));
+}
+
+/// Watch the main window for display changes
+pub fn install(window: &WebviewWindow) {
+    let app = window.app_handle().clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::ScaleFactorChanged { .. } = event {
+            schedule_check(&app, "scale factor change");
+        }
+    });
+    install_display_hook(window);
+}
+
+/// App handle for the window procedure (set when the hook is installed)
+#[cfg(target_os = "windows")]
+static HOOK_APP: std::sync::OnceLock<AppHandle> = std::sync::OnceLock::new();
+
+#[cfg(target_os = "windows")]
+fn install_display_hook(window: &WebviewWindow) {
+    use windows::Win32::Foundation::{HANDLE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::Power::{RegisterPowerSettingNotification, POWERBROADCAST_SETTING};
+    use windows::Win32::System::SystemServices::GUID_CONSOLE_DISPLAY_STATE;
+    use windows::Win32::UI::Shell::{DefSubclassProc, SetWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DEVICE_NOTIFY_WINDOW_HANDLE, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND,
+        PBT_POWERSETTINGCHANGE, WM_DISPLAYCHANGE, WM_POWERBROADCAST,
+    };
+
+    /// Identifies our subclass on the window
+    const SUBCLASS_ID: usize = 0x5041_4344; // "PACD"
+    /// Console display state data: 0 = off, 1 = on, 2 = dimmed
+    const DISPLAY_ON: u8 = 1;
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: usize,
+        _data: usize,
+    ) -> LRESULT {
+        // System sleep and wake (suspend.rs) arrive on the same message
+        if msg == WM_POWERBROADCAST {
+            if let Some(app) = HOOK_APP.get() {
+                match wparam.0 as u32 {
+                    PBT_APMSUSPEND => crate::suspend::suspended(app),
+                    PBT_APMRESUMEAUTOMATIC => crate::suspend::resumed(app),
+                    _ => {}
+                }
+            }
+        }
+        let reason = match msg {
+            WM_DISPLAYCHANGE => Some("display change"),
+            WM_POWERBROADCAST if wparam.0 == PBT_POWERSETTINGCHANGE as usize => {
+                let setting = &*(lparam.0 as *const POWERBROADCAST_SETTING);
+                (setting.PowerSetting == GUID_CONSOLE_DISPLAY_STATE
+                    && setting.Data[0] == DISPLAY_ON)
+                    .then_some("display wake")
+            }
+            _ => None,
+        };
+        if let (Some(reason), Some(app)) = (reason, HOOK_APP.get()) {
+            schedule_check(app, reason);
+        }
+        DefSubclassProc(hwnd, msg, wparam, lparam)
+    }
+
+    let Ok(hwnd) = window.hwnd() else {
+        warn!("Display change watcher unavailable: no window handle");
+        return;
+    };
+    HOOK_APP.get_or_init(|| window.app_handle().clone());
+    // Subclassing only works from the window's own thread; recreated
+    // windows are built off the main thread
+    let hwnd = hwnd.0 as isize;
+    let result = window.run_on_main_thread(move || unsafe {
+        let hwnd = HWND(hwnd as *mut std::ffi::c_void);
+        if !SetWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID, 0).as_bool() {
+            warn!("Failed to subclass main window for display changes");
+            return;
+        }
+        // Unregistered automatically when the window is destroyed
+        match RegisterPowerSettingNotification(
+            HANDLE(hwnd.0),
+            &GUID_CONSOLE_DISPLAY_STATE,
+            DEVICE_NOTIFY_WINDOW_HANDLE,
+        ) {
+            Ok(_) => info!("Display change watcher installed"),
+            Err(e) => warn!("Display power notifications unavailable: {}", e),
+        }
+    });
+    if let Err(e) = result {
+        warn!("Failed to install display change watcher: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn install_display_hook(_window: &WebviewWindow) {
+    debug!("Display power events are not watched on Linux (scale changes only)");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(fullscreen: bool, maximized: bool, size: (u32, u32)) -> WindowState {
+        WindowState {
+            fullscreen,
+            maximized,
+            size: PhysicalSize::new(size.0, size.1),
+            monitor: Some(PhysicalSize::new(2560, 1440)),
+        }
+    }
+
+    #[test]
+    fn fullscreen_at_a_stale_size_has_drifted() {
+        let mode = WindowMode::Fullscreen;
+        assert!(!has_drifted(mode, &state(true, false, (2560, 1440))));
+        assert!(has_drifted(mode, &state(true, false, (1920, 1080))));
+        assert!(has_drifted(mode, &state(false, false, (2560, 1440))));
+    }
+
+    #[test]
+    fn borderless_and_windowed_drift() {
+        let unmaximized = state(false, false, (1280, 900));
+        assert!(has_drifted(WindowMode::BorderlessWindowed, &unmaximized));
+        assert!(!has_drifted(
+            WindowMode::BorderlessWindowed,
+            &state(false, true, (2560, 1400))
+        ));
+        assert!(!has_drifted(WindowMode::Windowed, &unmaximized));
+    }
+}
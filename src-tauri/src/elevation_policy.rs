@@ -0,0 +1,264 @@
+//! Elevation Policy - Windows (WebView2)
+//!
+//! The WebView2 optimizer raises every runtime process it sees to above
+//! normal priority. On 4-core machines that can starve other apps (voice
+//! chat, the stream encoder), so `elevation_policy` can leave process types
+//! out (the audio service, utility processes) and cap how many processes
+//! are elevated at once.
+//!
+//! The decision is made once, when a process is first seen. A process left
+//! out because of the cap stays at normal priority even after elevated ones
+//! exit; renderers started later take the free slots.
+//!
+//! The GPU process can get its own priority class (`gpu_process_priority`):
+//! it paces frames, so raising it alone, with renderers and utilities left
+//! out, gives most of the frame-pacing benefit at little cost to other apps.
+//!
+//! Linux: the optimizer does not run; the policy is stored but unused.
+
+use crate::settings::ElevationPolicySettings;
+#[cfg(target_os = "windows")]
+use crate::settings::GpuPriority;
+#[cfg(target_os = "windows")]
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use tracing::debug;
+use tracing::info;
+
+/// Elevated PIDs remembered before exited ones are pruned
+#[cfg(target_os = "windows")]
+const MAX_TRACKED: usize = 256;
+
+struct Policy {
+    settings: ElevationPolicySettings,
+    /// PIDs elevated, pruned of exited ones when the cap is reached
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    elevated: Vec<u32>,
+    /// PIDs left at normal priority, so the polling fallback skips them
+    #[cfg(target_os = "windows")]
+    skipped: HashSet<u32>,
+    /// Admitted GPU processes, given `gpu_process_priority`
+    #[cfg(target_os = "windows")]
+    gpu: HashSet<u32>,
+}
+
+static POLICY: Mutex<Option<Policy>> = Mutex::new(None);
+
+/// Processes left at normal priority by the policy this run
+static SKIPPED: AtomicU32 = AtomicU32::new(0);
+
+/// Use `settings` for processes seen from now on
+pub fn set(settings: ElevationPolicySettings) {
+    let settings = settings.validated();
+    info!(
+        "Elevation policy: excluded {:?}, at most {}, GPU process {:?}",
+        settings.excluded_types,
+        match settings.max_elevated {
+            0 => "unlimited".to_string(),
+            max => max.to_string(),
+        },
+        settings.gpu_process_priority
+    );
+    let mut policy = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    match policy.as_mut() {
+        Some(policy) => policy.settings = settings,
+        None => {
+            *policy = Some(Policy {
+                settings,
+                elevated: Vec::new(),
+                #[cfg(target_os = "windows")]
+                skipped: HashSet::new(),
+                #[cfg(target_os = "windows")]
+                gpu: HashSet::new(),
+            })
+        }
+    }
+}
+
+/// Process type and, for utility processes, the service's first name part
+/// (`--utility-sub-type=audio.mojom.AudioService` -> "audio")
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn process_kind(args: &[String]) -> (String, Option<String>) {
+    let switch = |name: &str| {
+        args.iter()
+            .find_map(|arg| arg.strip_prefix(name))
+            .map(str::to_lowercase)
+    };
+    let kind = switch("--type=").unwrap_or_else(|| "browser".to_string());
+    let service = switch("--utility-sub-type=")
+        .map(|service| service.split('.').next().unwrap_or_default().to_string());
+    (kind, service)
+}
+
+/// Whether a process with command line `args` is excluded by type
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_excluded(settings: &ElevationPolicySettings, args: &[String]) -> bool {
+    let (kind, service) = process_kind(args);
+    settings
+        .excluded_types
+        .iter()
+        .any(|excluded| *excluded == kind || service.as_ref() == Some(excluded))
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_gpu_process(args: &[String]) -> bool {
+    process_kind(args).0 == "gpu-process"
+}
+
+/// Command line of a freshly started process
+#[cfg(target_os = "windows")]
+fn command_line(pid: u32) -> Vec<String> {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        ProcessRefreshKind::new().with_cmd(UpdateKind::Always),
+    );
+    system
+        .process(pid)
+        .map(|process| {
+            process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the optimizer may elevate `pid`. Call `record` once it did.
+#[cfg(target_os = "windows")]
+pub(crate) fn admit(pid: u32) -> bool {
+    let mut guard = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(policy) = guard.as_mut() else {
+        return true;
+    };
+    if policy.skipped.contains(&pid) {
+        return false;
+    }
+    let settings = &policy.settings;
+    let gpu_differs = settings.gpu_process_priority != GpuPriority::AboveNormal;
+    if settings.excluded_types.is_empty() && settings.max_elevated == 0 && !gpu_differs {
+        return true;
+    }
+
+    let args = command_line(pid);
+    let reason = if is_excluded(settings, &args) {
+        Some("excluded type")
+    } else if settings.max_elevated > 0 {
+        let max = settings.max_elevated as usize;
+        if policy.elevated.len() >= max {
+            policy
+                .elevated
+                .retain(|&other| crate::process_failures::is_running(other));
+        }
+        (policy.elevated.len() >= max).then_some("cap reached")
+    } else {
+        None
+    };
+    match reason {
+        Some(reason) => {
+            debug!(
+                "Leaving WebView2 process {} at normal priority ({})",
+                pid, reason
+            );
+            policy.skipped.insert(pid);
+            SKIPPED.fetch_add(1, Ordering::SeqCst);
+            false
+        }
+        None => {
+            if gpu_differs && is_gpu_process(&args) {
+                policy.gpu.insert(pid);
+            }
+            true
+        }
+    }
+}
+
+/// Priority class for an admitted process
+#[cfg(target_os = "windows")]
+pub(crate) fn priority_class(
+    pid: u32,
+) -> windows::Win32::System::Threading::PROCESS_CREATION_FLAGS {
+    use windows::Win32::System::Threading::{ABOVE_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS};
+
+    let guard = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    let gpu = guard
+        .as_ref()
+        .filter(|policy| policy.gpu.contains(&pid))
+        .map(|policy| policy.settings.gpu_process_priority);
+    match gpu {
+        Some(GpuPriority::High) => HIGH_PRIORITY_CLASS,
+        _ => ABOVE_NORMAL_PRIORITY_CLASS,
+    }
+}
+
+/// Count `pid` against the cap
+#[cfg(target_os = "windows")]
+pub(crate) fn record(pid: u32) {
+    if let Some(policy) = POLICY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        if policy.elevated.len() >= MAX_TRACKED {
+            policy
+                .elevated
+                .retain(|&other| crate::process_failures::is_running(other));
+        }
+        policy.elevated.push(pid);
+    }
+}
+
+/// `pid` exited; its number may come back for a new process
+#[cfg(target_os = "windows")]
+pub(crate) fn forget(pid: u32) {
+    if let Some(policy) = POLICY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        policy.elevated.retain(|&other| other != pid);
+        policy.skipped.remove(&pid);
+        policy.gpu.remove(&pid);
+    }
+}
+
+/// Processes the policy left at normal priority this run
+pub fn skipped_count() -> u32 {
+    SKIPPED.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn processes_are_matched_by_type_or_service() {
+        let settings = ElevationPolicySettings {
+            excluded_types: vec![" Audio ".to_string(), "gpu-process".to_string()],
+            ..Default::default()
+        }
+        .validated();
+        let audio =
+            args("msedgewebview2.exe --type=utility --utility-sub-type=audio.mojom.AudioService");
+        let network = args(
+            "msedgewebview2.exe --type=utility --utility-sub-type=network.mojom.NetworkService",
+        );
+        assert!(is_excluded(&settings, &audio));
+        assert!(!is_excluded(&settings, &network));
+        assert!(is_excluded(
+            &settings,
+            &args("msedgewebview2.exe --type=gpu-process")
+        ));
+        assert!(!is_excluded(
+            &settings,
+            &args("msedgewebview2.exe --type=renderer")
+        ));
+        assert_eq!(process_kind(&args("msedgewebview2.exe")).0, "browser");
+        assert!(is_gpu_process(&args(
+            "msedgewebview2.exe --type=gpu-process"
+        )));
+        assert!(!is_gpu_process(&args("msedgewebview2.exe --type=renderer")));
+    }
+}
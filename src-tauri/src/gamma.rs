@@ -0,0 +1,262 @@
+//! Display Gamma and Warmth - Windows
+//!
+//! `set_gamma` and `set_color_temperature` shape the gamma ramp of the
+//! display the game window is on, for late-night play without a system-wide
+//! night light. Like the high-precision mouse, the ramp is ours only while
+//! the main window has focus: the display's own ramp is read first and put
+//! back on focus loss and at exit.
+//!
+//! Windows refuses ramps too far from neutral unless the user raised the
+//! limit, so the warmest presets fail on some systems; the command says so.
+//!
+//! Linux: not available (the compositor owns the gamma tables).
+
+use crate::settings::GammaSettings;
+use serde::{Deserialize, Serialize};
+use tauri::{WebviewWindow, WindowEvent};
+use tracing::debug;
+#[cfg(target_os = "windows")]
+use tracing::{info, warn};
+
+/// Ready-made settings for `apply_gamma_preset`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GammaPreset {
+    /// Neutral: the display's own ramp
+    Off,
+    Evening,
+    LateNight,
+}
+
+impl GammaPreset {
+    pub fn settings(self) -> GammaSettings {
+        match self {
+            GammaPreset::Off => GammaSettings::default(),
+            GammaPreset::Evening => GammaSettings {
+                gamma: 1.0,
+                color_temperature: 4800,
+            },
+            // Warmer, with dark tones lifted a little to make up for it
+            GammaPreset::LateNight => GammaSettings {
+                gamma: 1.1,
+                color_temperature: 3400,
+            },
+        }
+    }
+}
+
+/// One 16-bit ramp per channel (red, green, blue), as `SetDeviceGammaRamp`
+/// takes it
+type Ramp = [[u16; 256]; 3];
+
+/// Red, green and blue of a black body at `kelvin`, 0-1 (Tanner Helland's
+/// approximation)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn white_point(kelvin: u32) -> [f64; 3] {
+    let t = kelvin as f64 / 100.0;
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2)
+    };
+    let green = if t <= 66.0 {
+        99.470_802_586_1 * t.ln() - 161.119_568_166_1
+    } else {
+        288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+    };
+    [red, green, blue].map(|c| c.clamp(0.0, 255.0) / 255.0)
+}
+
+/// Gamma ramp for `settings`; neutral settings give the identity ramp
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn ramp(settings: GammaSettings) -> Ramp {
+    let neutral = white_point(GammaSettings::default().color_temperature);
+    let white = white_point(settings.color_temperature);
+    let mut ramp = [[0u16; 256]; 3];
+    for (channel, values) in ramp.iter_mut().enumerate() {
+        let scale = (white[channel] / neutral[channel]).min(1.0);
+        for (i, value) in values.iter_mut().enumerate() {
+            let level = (i as f64 / 255.0).powf(1.0 / settings.gamma as f64);
+            *value = (level * scale * 65535.0).round() as u16;
+        }
+    }
+    ramp
+}
+
+#[cfg(target_os = "windows")]
+mod state {
+    use super::Ramp;
+    use crate::settings::GammaSettings;
+    use std::sync::Mutex;
+
+    pub struct State {
+        pub settings: GammaSettings,
+        pub focused: bool,
+        /// Display the game window was on at the last focus change
+        pub display: Option<String>,
+        /// The display's own ramp while ours is applied
+        pub saved: Option<(String, Box<Ramp>)>,
+    }
+
+    pub static STATE: Mutex<State> = Mutex::new(State {
+        settings: GammaSettings {
+            gamma: 1.0,
+            color_temperature: 6500,
+        },
+        focused: false,
+        display: None,
+        saved: None,
+    });
+}
+
+/// Read or write the gamma ramp of GDI display `display` (`\\.\DISPLAY1`)
+#[cfg(target_os = "windows")]
+fn device_ramp(display: &str, ramp: &mut Ramp, write: bool) -> Result<(), String> {
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Graphics::Gdi::{CreateDCW, DeleteDC};
+    use windows::Win32::UI::ColorSystem::{GetDeviceGammaRamp, SetDeviceGammaRamp};
+
+    let name: Vec<u16> = display.encode_utf16().chain(Some(0)).collect();
+    // SAFETY: the DC is deleted before returning; the ramp is 3 x 256 u16s
+    unsafe {
+        let dc = CreateDCW(
+            w!("DISPLAY"),
+            PCWSTR::from_raw(name.as_ptr()),
+            PCWSTR::null(),
+            None,
+        );
+        if dc.is_invalid() {
+            return Err(format!("No device context for {}", display));
+        }
+        let ok = if write {
+            SetDeviceGammaRamp(dc, ramp.as_ptr().cast())
+        } else {
+            GetDeviceGammaRamp(dc, ramp.as_mut_ptr().cast())
+        }
+        .as_bool();
+        let _ = DeleteDC(dc);
+        if ok {
+            Ok(())
+        } else if write {
+            Err(format!(
+                "Windows refused the gamma ramp for {} (too far from neutral)",
+                display
+            ))
+        } else {
+            Err(format!("Failed to read the gamma ramp of {}", display))
+        }
+    }
+}
+
+/// Apply or undo our ramp to match the settings and focus
+#[cfg(target_os = "windows")]
+fn update(state: &mut state::State) -> Result<(), String> {
+    let wanted = state.focused && !state.settings.is_neutral();
+    // Put the display's ramp back first: the settings or the display changed
+    if let Some((display, mut original)) = state.saved.take() {
+        if let Err(e) = device_ramp(&display, &mut original, true) {
+            warn!("Failed to restore gamma: {}", e);
+        }
+    }
+    if !wanted {
+        return Ok(());
+    }
+    let Some(display) = state.display.clone() else {
+        return Err("The game window's display is unknown".to_string());
+    };
+    let mut original: Box<Ramp> = Box::new([[0; 256]; 3]);
+    device_ramp(&display, &mut original, false)?;
+    let mut ours = ramp(state.settings);
+    device_ramp(&display, &mut ours, true)?;
+    state.saved = Some((display, original));
+    debug!("Gamma {:?} applied while focused", state.settings);
+    Ok(())
+}
+
+/// Use `settings` while the window is focused; fails if Windows refuses
+/// the ramp right now
+#[cfg(target_os = "windows")]
+pub fn apply(settings: GammaSettings) -> Result<(), String> {
+    let mut state = state::STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.settings = settings;
+    info!(
+        "Gamma {:.2}, {} K while focused",
+        settings.gamma, settings.color_temperature
+    );
+    update(&mut state)
+}
+
+#[cfg(target_os = "windows")]
+fn on_focus(window: &WebviewWindow, focused: bool) {
+    let mut state = state::STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.focused = focused;
+    if focused {
+        state.display = window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|monitor| monitor.name().cloned());
+    }
+    if let Err(e) = update(&mut state) {
+        warn!("{}", e);
+    }
+}
+
+/// Put the display's own ramp back (on exit)
+#[cfg(target_os = "windows")]
+pub fn restore() {
+    let mut state = state::STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.settings = GammaSettings::default();
+    let _ = update(&mut state);
+}
+
+#[cfg(target_os = "linux")]
+pub fn apply(settings: GammaSettings) -> Result<(), String> {
+    if settings.is_neutral() {
+        return Ok(());
+    }
+    debug!("Gamma ramps are not available on Linux");
+    Err("Gamma adjustment is only available on Windows".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn on_focus(_window: &WebviewWindow, _focused: bool) {}
+
+#[cfg(target_os = "linux")]
+pub fn restore() {}
+
+/// Follow the main window's focus
+pub fn install(window: &WebviewWindow) {
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(focused) = event {
+            on_focus(&handle, *focused);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_ramp_is_identity_and_warmth_cuts_blue() {
+        let neutral = ramp(GammaSettings::default());
+        for channel in neutral {
+            assert_eq!(channel[0], 0);
+            assert_eq!(channel[128], 128 * 257);
+            assert_eq!(channel[255], 65535);
+        }
+        let warm = ramp(GammaPreset::LateNight.settings());
+        assert_eq!(warm[0][255], 65535);
+        assert!(warm[2][255] < warm[1][255]);
+        assert!(warm[1][255] < 65535);
+    }
+}
@@ -0,0 +1,264 @@
+//! GPU Driver Version - Cross-platform
+//!
+//! Reads the graphics driver version and flags drivers older than the
+//! oldest known-good release for WebView2's GPU (ANGLE/Vulkan) backend.
+//! Older drivers are the usual cause of GPU process crashes and silent
+//! fallback to software rendering.
+//!
+//! A few newer releases break WebView2's Vulkan path outright; on those the
+//! browser flags leave Vulkan off (see `VULKAN_BLOCKLIST`).
+//!
+//! - Windows: user-mode driver version from DXGI `CheckInterfaceSupport`
+//! - Linux: `glxinfo -B` (Mesa or NVIDIA proprietary version)
+//!
+//! Detection runs once per process; drivers don't change while we run.
+
+use serde::Serialize;
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// Driver vendor, as far as the version format is concerned
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+    /// Mesa drivers on Linux (AMD, Intel and others)
+    Mesa,
+    Other,
+}
+
+/// Oldest driver without the known WebView2 Vulkan problems, per vendor.
+/// Windows entries use the Windows driver version format (a.b.c.d);
+/// Linux entries the version glxinfo reports.
+#[cfg(target_os = "windows")]
+const MIN_DRIVER_VERSIONS: &[(GpuVendor, &str)] = &[
+    // R460 (early 2021)
+    (GpuVendor::Nvidia, "27.21.14.6000"),
+    // Adrenalin 21.x
+    (GpuVendor::Amd, "27.20.20900.0"),
+    // 27.20.100.9xxx (2021)
+    (GpuVendor::Intel, "27.20.100.9000"),
+];
+#[cfg(target_os = "linux")]
+const MIN_DRIVER_VERSIONS: &[(GpuVendor, &str)] =
+    &[(GpuVendor::Nvidia, "460.0"), (GpuVendor::Mesa, "21.0")];
+
+/// Driver releases that break WebView2 rendering through Vulkan:
+/// (vendor, first bad version, first fixed version). Same version formats
+/// as `MIN_DRIVER_VERSIONS`.
+#[cfg(target_os = "windows")]
+const VULKAN_BLOCKLIST: &[(GpuVendor, &str, &str)] = &[
+    // 536.40 - 536.67: device lost as soon as the GPU process starts
+    (GpuVendor::Nvidia, "31.0.15.3640", "31.0.15.3699"),
+    // Adrenalin 23.7.1: black game canvas with Vulkan compositing
+    (GpuVendor::Amd, "31.0.21023.2010", "31.0.21023.2011"),
+    // 31.0.101.4502 - 4574: GPU process hangs on swapchain resize
+    (GpuVendor::Intel, "31.0.101.4502", "31.0.101.4575"),
+];
+/// WebKitGTK takes no Vulkan switch, so nothing to block
+#[cfg(target_os = "linux")]
+const VULKAN_BLOCKLIST: &[(GpuVendor, &str, &str)] = &[];
+
+/// Installed GPU driver
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GpuDriver {
+    pub vendor: GpuVendor,
+    /// Version as reported by the system
+    pub version: String,
+    /// Vendor-facing version when it differs (e.g. NVIDIA "537.13")
+    pub display_version: Option<String>,
+    /// Oldest known-good version, when this driver is older than it
+    pub outdated_below: Option<String>,
+    /// Known to break WebView2 with Vulkan (`VULKAN_BLOCKLIST`)
+    pub vulkan_blocked: bool,
+}
+
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split(['.', '-'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Whether `version` is older than `minimum` (missing parts count as 0)
+fn is_older(version: &str, minimum: &str) -> bool {
+    let (mut version, mut minimum) = (parse_version(version), parse_version(minimum));
+    let len = version.len().max(minimum.len());
+    version.resize(len, 0);
+    minimum.resize(len, 0);
+    version < minimum
+}
+
+fn outdated_below(vendor: GpuVendor, version: &str) -> Option<String> {
+    MIN_DRIVER_VERSIONS
+        .iter()
+        .find(|(known, _)| *known == vendor)
+        .filter(|(_, minimum)| is_older(version, minimum))
+        .map(|(_, minimum)| minimum.to_string())
+}
+
+fn vulkan_blocked(vendor: GpuVendor, version: &str) -> bool {
+    VULKAN_BLOCKLIST.iter().any(|(known, first_bad, fixed)| {
+        *known == vendor && !is_older(version, first_bad) && is_older(version, fixed)
+    })
+}
+
+/// NVIDIA's release number from a Windows driver version:
+/// 31.0.15.3713 -> 537.13 (last digit of the third part + the fourth part)
+fn nvidia_release(version: &str) -> Option<String> {
+    let parts = parse_version(version);
+    let [_, _, third, fourth] = parts[..] else {
+        return None;
+    };
+    let release = (third % 10) * 10000 + fourth;
+    Some(format!("{}.{:02}", release / 100, release % 100))
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn vendor_from_pci_id(vendor_id: u32) -> GpuVendor {
+    match vendor_id {
+        0x10de => GpuVendor::Nvidia,
+        0x1002 => GpuVendor::Amd,
+        0x8086 => GpuVendor::Intel,
+        _ => GpuVendor::Other,
+    }
+}
+
+/// Vendor and version from glxinfo's "OpenGL version string" line, e.g.
+/// "4.6 (Compatibility Profile) Mesa 23.2.1" or "4.6.0 NVIDIA 535.104.05"
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_glxinfo(output: &str) -> Option<(GpuVendor, String)> {
+    let line = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("OpenGL version string:"))?;
+    let words: Vec<&str> = line.split_whitespace().collect();
+    words.windows(2).find_map(|pair| {
+        let vendor = match pair[0] {
+            "Mesa" => GpuVendor::Mesa,
+            "NVIDIA" => GpuVendor::Nvidia,
+            _ => return None,
+        };
+        Some((vendor, pair[1].to_string()))
+    })
+}
+
+fn build(vendor: GpuVendor, version: String) -> GpuDriver {
+    // Linux NVIDIA versions are already release numbers
+    let display_version = if cfg!(target_os = "windows") && vendor == GpuVendor::Nvidia {
+        nvidia_release(&version)
+    } else {
+        None
+    };
+    GpuDriver {
+        vendor,
+        outdated_below: outdated_below(vendor, &version),
+        vulkan_blocked: vulkan_blocked(vendor, &version),
+        display_version,
+        version,
+    }
+}
+
+/// Primary adapter's driver (first non-software adapter, as in GPU detection)
+#[cfg(target_os = "windows")]
+fn query() -> Option<GpuDriver> {
+    use windows::core::Interface;
+    use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIDevice, IDXGIFactory1};
+
+    unsafe {
+        let factory = CreateDXGIFactory1::<IDXGIFactory1>().ok()?;
+        let mut i = 0u32;
+        while let Ok(adapter) = factory.EnumAdapters1(i) {
+            i += 1;
+            let Ok(desc) = adapter.GetDesc1() else {
+                continue;
+            };
+            let name = String::from_utf16_lossy(&desc.Description);
+            if name.contains("Basic") || name.contains("Microsoft") {
+                continue;
+            }
+            // The user-mode driver version, packed as four 16-bit parts
+            let Ok(packed) = adapter.CheckInterfaceSupport(&IDXGIDevice::IID) else {
+                continue;
+            };
+            let packed = packed as u64;
+            let version = format!(
+                "{}.{}.{}.{}",
+                (packed >> 48) & 0xffff,
+                (packed >> 32) & 0xffff,
+                (packed >> 16) & 0xffff,
+                packed & 0xffff
+            );
+            return Some(build(vendor_from_pci_id(desc.VendorId), version));
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn query() -> Option<GpuDriver> {
+    let output = std::process::Command::new("glxinfo")
+        .arg("-B")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let (vendor, version) = parse_glxinfo(&String::from_utf8_lossy(&output.stdout))?;
+    Some(build(vendor, version))
+}
+
+/// Installed GPU driver, detected on first call
+pub fn detect() -> Option<GpuDriver> {
+    static DRIVER: OnceLock<Option<GpuDriver>> = OnceLock::new();
+    DRIVER
+        .get_or_init(|| {
+            let driver = query();
+            debug!("GPU driver: {:?}", driver);
+            driver
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(is_older("27.21.14.5671", "27.21.14.6000"));
+        assert!(!is_older("31.0.15.3713", "27.21.14.6000"));
+        assert!(is_older("20.3", "21.0"));
+        assert!(!is_older("21", "21.0"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn blocked_vulkan_ranges_exclude_the_fix() {
+        assert!(vulkan_blocked(GpuVendor::Nvidia, "31.0.15.3640"));
+        assert!(vulkan_blocked(GpuVendor::Nvidia, "31.0.15.3667"));
+        assert!(!vulkan_blocked(GpuVendor::Nvidia, "31.0.15.3699"));
+        assert!(!vulkan_blocked(GpuVendor::Nvidia, "31.0.15.3623"));
+        assert!(!vulkan_blocked(GpuVendor::Intel, "31.0.15.3667"));
+    }
+
+    #[test]
+    fn nvidia_release_from_windows_version() {
+        assert_eq!(nvidia_release("31.0.15.3713").as_deref(), Some("537.13"));
+        assert_eq!(nvidia_release("27.21.14.6089").as_deref(), Some("460.89"));
+        assert_eq!(nvidia_release("1.2.3"), None);
+    }
+
+    #[test]
+    fn glxinfo_versions_are_parsed() {
+        let mesa = "OpenGL vendor string: AMD\n    OpenGL version string: 4.6 (Compatibility Profile) Mesa 23.2.1-1ubuntu3\n";
+        assert_eq!(
+            parse_glxinfo(mesa),
+            Some((GpuVendor::Mesa, "23.2.1-1ubuntu3".to_string()))
+        );
+        let nvidia = "OpenGL version string: 4.6.0 NVIDIA 535.104.05\n";
+        assert_eq!(
+            parse_glxinfo(nvidia),
+            Some((GpuVendor::Nvidia, "535.104.05".to_string()))
+        );
+        assert_eq!(parse_glxinfo("no opengl here"), None);
+    }
+}
@@ -0,0 +1,339 @@
+//! Windows HDR Switch - Windows
+//!
+//! The game renders in SDR, and on most displays SDR content looks washed
+//! out with Windows HDR on. `set_windows_hdr` turns HDR on or off for one
+//! display through the display configuration API: the Windows 11 24H2 HDR
+//! state when the system has it, the older advanced color state otherwise.
+//! Where neither works (driver refuses, older Windows) it opens the HDR page
+//! of the Settings app instead.
+//!
+//! The `disable_hdr_in_game` option (off by default) turns HDR off on the
+//! game window's display while PACDeluxe runs and turns it back on at exit.
+//! Manual switches are the user's choice and are left as they are.
+//!
+//! Linux: not available.
+
+use serde::Serialize;
+use tauri::{Manager, WebviewWindow};
+use tracing::debug;
+#[cfg(target_os = "windows")]
+use tracing::{info, warn};
+
+/// What `set_windows_hdr` did
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HdrSwitch {
+    Switched,
+    /// HDR was already in the requested state
+    Unchanged,
+    /// The display config API refused; the user has to flip it in Settings
+    SettingsOpened,
+}
+
+/// GDI device name from a fixed-size, NUL-padded buffer
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn gdi_name(raw: &[u16]) -> String {
+    let len = raw.iter().position(|&c| c == 0).unwrap_or(raw.len());
+    String::from_utf16_lossy(&raw[..len])
+}
+
+/// Displays the option turned HDR off on, turned back on at exit
+#[cfg(target_os = "windows")]
+static TURNED_OFF: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+#[cfg(target_os = "windows")]
+pub(crate) mod display_config {
+    use windows::Win32::Devices::Display::{
+        DisplayConfigGetDeviceInfo, DisplayConfigSetDeviceInfo, GetDisplayConfigBufferSizes,
+        QueryDisplayConfig, DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO,
+        DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_DEVICE_INFO_HEADER,
+        DISPLAYCONFIG_DEVICE_INFO_SET_ADVANCED_COLOR_STATE, DISPLAYCONFIG_DEVICE_INFO_TYPE,
+        DISPLAYCONFIG_MODE_INFO, DISPLAYCONFIG_PATH_INFO, DISPLAYCONFIG_SOURCE_DEVICE_NAME,
+        QDC_ONLY_ACTIVE_PATHS, QUERY_DISPLAY_CONFIG_FLAGS,
+    };
+    use windows::Win32::Foundation::LUID;
+
+    /// `DISPLAYCONFIG_DEVICE_INFO_SET_HDR_STATE` (Windows 11 24H2); newer
+    /// than the bindings
+    const SET_HDR_STATE: DISPLAYCONFIG_DEVICE_INFO_TYPE = DISPLAYCONFIG_DEVICE_INFO_TYPE(16);
+
+    /// `DISPLAYCONFIG_GET_ADVANCED_COLOR_INFO`
+    #[repr(C)]
+    struct AdvancedColorInfo {
+        header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
+        /// Bit 0: supported, bit 1: enabled
+        flags: u32,
+        color_encoding: i32,
+        bits_per_color_channel: u32,
+    }
+
+    /// `DISPLAYCONFIG_SET_ADVANCED_COLOR_STATE` and `DISPLAYCONFIG_SET_HDR_STATE`
+    #[repr(C)]
+    struct SetState {
+        header: DISPLAYCONFIG_DEVICE_INFO_HEADER,
+        /// Bit 0: enable
+        flags: u32,
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Target {
+        adapter: LUID,
+        id: u32,
+    }
+
+    fn header(
+        info: DISPLAYCONFIG_DEVICE_INFO_TYPE,
+        size: usize,
+        target: Target,
+    ) -> DISPLAYCONFIG_DEVICE_INFO_HEADER {
+        DISPLAYCONFIG_DEVICE_INFO_HEADER {
+            r#type: info,
+            size: size as u32,
+            adapterId: target.adapter,
+            id: target.id,
+        }
+    }
+
+    /// `QDC_VIRTUAL_REFRESH_RATE_AWARE` (Windows 11): report dynamic
+    /// refresh rate paths as such; newer than the bindings
+    pub const QDC_VIRTUAL_REFRESH_RATE_AWARE: u32 = 0x40;
+
+    fn active_paths(extra_flags: u32) -> Result<Vec<DISPLAYCONFIG_PATH_INFO>, String> {
+        let flags = QUERY_DISPLAY_CONFIG_FLAGS(QDC_ONLY_ACTIVE_PATHS.0 | extra_flags);
+        let mut path_count = 0u32;
+        let mut mode_count = 0u32;
+        // SAFETY: the buffers are sized by GetDisplayConfigBufferSizes
+        unsafe {
+            GetDisplayConfigBufferSizes(flags, &mut path_count, &mut mode_count)
+                .ok()
+                .map_err(|e| format!("Failed to size display config: {}", e))?;
+            let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
+            let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
+            QueryDisplayConfig(
+                flags,
+                &mut path_count,
+                paths.as_mut_ptr(),
+                &mut mode_count,
+                modes.as_mut_ptr(),
+                None,
+            )
+            .ok()
+            .map_err(|e| format!("Failed to query display config: {}", e))?;
+            paths.truncate(path_count as usize);
+            Ok(paths)
+        }
+    }
+
+    /// The active path showing GDI display `display` (`\\.\DISPLAY1`),
+    /// queried with `extra_flags` on top of the active paths
+    pub fn find_path(display: &str, extra_flags: u32) -> Result<DISPLAYCONFIG_PATH_INFO, String> {
+        for path in active_paths(extra_flags)? {
+            let source = Target {
+                adapter: path.sourceInfo.adapterId,
+                id: path.sourceInfo.id,
+            };
+            let mut name = DISPLAYCONFIG_SOURCE_DEVICE_NAME {
+                header: header(
+                    DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+                    std::mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>(),
+                    source,
+                ),
+                ..Default::default()
+            };
+            // SAFETY: the header describes `name`'s type and size
+            if unsafe { DisplayConfigGetDeviceInfo(&mut name.header) } != 0 {
+                continue;
+            }
+            if super::gdi_name(&name.viewGdiDeviceName) == display {
+                return Ok(path);
+            }
+        }
+        Err(format!("Display {} is not active", display))
+    }
+
+    /// The monitor target showing GDI display `display`
+    pub fn find_target(display: &str) -> Result<Target, String> {
+        find_path(display, 0).map(|path| Target {
+            adapter: path.targetInfo.adapterId,
+            id: path.targetInfo.id,
+        })
+    }
+
+    /// (supported, enabled)
+    pub fn hdr_state(target: Target) -> Result<(bool, bool), String> {
+        let mut info = AdvancedColorInfo {
+            header: header(
+                DISPLAYCONFIG_DEVICE_INFO_GET_ADVANCED_COLOR_INFO,
+                std::mem::size_of::<AdvancedColorInfo>(),
+                target,
+            ),
+            flags: 0,
+            color_encoding: 0,
+            bits_per_color_channel: 0,
+        };
+        // SAFETY: the header describes `info`'s layout and size
+        let status = unsafe { DisplayConfigGetDeviceInfo(&mut info.header) };
+        if status != 0 {
+            return Err(format!("Failed to read HDR state: error {}", status));
+        }
+        Ok((info.flags & 1 != 0, info.flags & 2 != 0))
+    }
+
+    pub fn set_hdr(target: Target, enabled: bool) -> Result<(), String> {
+        let mut last = 0;
+        for info in [
+            SET_HDR_STATE,
+            DISPLAYCONFIG_DEVICE_INFO_SET_ADVANCED_COLOR_STATE,
+        ] {
+            let state = SetState {
+                header: header(info, std::mem::size_of::<SetState>(), target),
+                flags: enabled as u32,
+            };
+            // SAFETY: the header describes `state`'s layout and size
+            last = unsafe { DisplayConfigSetDeviceInfo(&state.header) };
+            if last == 0 {
+                return Ok(());
+            }
+        }
+        Err(format!("Windows refused to switch HDR: error {}", last))
+    }
+}
+
+/// Open the HDR page of the Settings app
+#[cfg(target_os = "windows")]
+fn open_hdr_settings() -> Result<(), String> {
+    use windows::core::w;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    // SAFETY: constant NUL-terminated strings
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            w!("open"),
+            w!("ms-settings:display-hdr"),
+            None,
+            None,
+            SW_SHOWNORMAL,
+        )
+    };
+    // Values above 32 mean success
+    if result.0 as usize > 32 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to open HDR settings: {}",
+            result.0 as usize
+        ))
+    }
+}
+
+/// Switch through the display config API only
+#[cfg(target_os = "windows")]
+fn switch(display: &str, enabled: bool) -> Result<HdrSwitch, String> {
+    let target = display_config::find_target(display)?;
+    let (supported, current) = display_config::hdr_state(target)?;
+    if !supported {
+        return Err(format!("{} does not support HDR", display));
+    }
+    if current == enabled {
+        return Ok(HdrSwitch::Unchanged);
+    }
+    display_config::set_hdr(target, enabled).map(|()| HdrSwitch::Switched)
+}
+
+/// Turn HDR on or off for `display` (GDI name, as in `HdrOutput`)
+#[cfg(target_os = "windows")]
+pub fn set_windows_hdr(display: &str, enabled: bool) -> Result<HdrSwitch, String> {
+    match switch(display, enabled) {
+        Ok(result) => {
+            info!(
+                "HDR {} on {} ({:?})",
+                if enabled { "on" } else { "off" },
+                display,
+                result
+            );
+            Ok(result)
+        }
+        Err(e) => {
+            warn!("{}; opening HDR settings", e);
+            open_hdr_settings().map(|()| HdrSwitch::SettingsOpened)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_windows_hdr(_display: &str, _enabled: bool) -> Result<HdrSwitch, String> {
+    Err("Switching HDR is only available on Windows".to_string())
+}
+
+/// Apply `disable_hdr_in_game`: HDR off on the game window's display, or
+/// back on where the option turned it off
+#[cfg(target_os = "windows")]
+pub fn set_disabled_in_game(window: &WebviewWindow, disabled: bool) {
+    if !disabled {
+        restore();
+        return;
+    }
+    let mut turned_off = TURNED_OFF.lock().unwrap_or_else(|e| e.into_inner());
+    let hdr = crate::display_watch::game_window_hdr(window.app_handle());
+    if !hdr.enabled || turned_off.contains(&hdr.display_name) {
+        debug!("HDR already off on {}", hdr.display_name);
+        return;
+    }
+    // Only the direct switch counts: after opening Settings, what the user
+    // does there is theirs to undo
+    match set_windows_hdr(&hdr.display_name, false) {
+        Ok(HdrSwitch::Switched) => turned_off.push(hdr.display_name),
+        Ok(_) => {}
+        Err(e) => warn!("Failed to turn HDR off in game: {}", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_disabled_in_game(_window: &WebviewWindow, disabled: bool) {
+    if disabled {
+        debug!("Switching HDR is not available on Linux");
+    }
+}
+
+/// Apply the option to a newly built main window
+pub fn install(window: &WebviewWindow) {
+    let settings = window
+        .app_handle()
+        .state::<crate::settings::SettingsState>();
+    if settings.get().disable_hdr_in_game {
+        set_disabled_in_game(window, true);
+    }
+}
+
+/// Turn HDR back on where the option turned it off (on exit)
+#[cfg(target_os = "windows")]
+pub fn restore() {
+    let mut turned_off = TURNED_OFF.lock().unwrap_or_else(|e| e.into_inner());
+    // No Settings fallback here: nobody is around to use it at exit
+    for display in turned_off.drain(..) {
+        match switch(&display, true) {
+            Ok(_) => info!("HDR restored on {}", display),
+            Err(e) => warn!("Failed to turn HDR back on for {}: {}", display, e),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn restore() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gdi_names_stop_at_the_padding() {
+        let mut raw = [0u16; 32];
+        for (slot, c) in raw.iter_mut().zip("\\\\.\\DISPLAY2".encode_utf16()) {
+            *slot = c;
+        }
+        assert_eq!(gdi_name(&raw), "\\\\.\\DISPLAY2");
+        assert_eq!(gdi_name(&[]), "");
+    }
+}
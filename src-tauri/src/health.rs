@@ -0,0 +1,310 @@
+//! Health Report - Cross-platform
+//!
+//! Aggregates checks that decide whether the client is running the way it
+//! should. Each problem becomes a human-readable issue for the overlay.
+//!
+//! Also probes the game server at startup so an unreachable server shows a
+//! retry screen instead of a blank WebView2 error page. User-configured
+//! mirrors are tried in order when the official server is down, and an
+//! experimental offline mode lets the bundled UI run with server features
+//! disabled.
+
+use crate::gpu_driver::{self, GpuDriver};
+use crate::maintenance::{self, UpstreamIssue};
+use crate::preflight::{self, PreflightReport};
+use crate::rendering::{self, RenderingStatus};
+use crate::safe_mode::{self, SafeModeReason};
+use crate::settings;
+use crate::updates;
+use crate::white_window::{self, FlagLevel};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::Url;
+use tracing::{info, warn};
+
+/// Game server probed before the game is loaded
+pub const UPSTREAM_URL: &str = "https://pokemon-auto-chess.com/";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(6);
+const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_secs(4);
+/// Retry screen shown instead of the game when the server is unreachable
+/// (served by the localhost server)
+pub const UNREACHABLE_PAGE_PATH: &str = "/pacdeluxe/unreachable";
+/// Shown while the startup probe runs
+pub const LOADING_PAGE_PATH: &str = "/pacdeluxe/loading";
+
+/// Snapshot returned by the `get_health` command
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// True when no issues were found
+    pub healthy: bool,
+    pub rendering: RenderingStatus,
+    pub gpu_driver: Option<GpuDriver>,
+    /// Key ID updates are verified with; `None` turns updates off
+    pub update_signing_key: Option<String>,
+    /// Runtime, driver, codec and counter dependencies
+    pub preflight: PreflightReport,
+    /// Problems worth surfacing to the user, most important first
+    pub issues: Vec<String>,
+}
+
+/// Run all health checks.
+/// `webgl_renderer` is reported by the injected runtime when available.
+pub fn check(webgl_renderer: Option<&str>) -> HealthReport {
+    let rendering = rendering::detect(webgl_renderer);
+
+    let mut issues = Vec::new();
+    if rendering.is_software() {
+        issues.push(format!(
+            "Software rendering: {}. The game is being drawn on the CPU, so performance settings will have little effect. Update your GPU driver or re-enable hardware acceleration.",
+            rendering.reason.as_deref().unwrap_or("GPU acceleration is off")
+        ));
+    }
+
+    let gpu_driver = gpu_driver::detect();
+    if let Some(driver) = &gpu_driver {
+        if let Some(minimum) = &driver.outdated_below {
+            issues.push(format!(
+                "Outdated GPU driver: {} is older than {}, the oldest known to work well with WebView2. Update your {:?} graphics driver if the game crashes or stutters.",
+                driver.display_version.as_deref().unwrap_or(&driver.version),
+                minimum,
+                driver.vendor
+            ));
+        }
+    }
+
+    let safe_mode = safe_mode::status();
+    if let Some(reason) = safe_mode.reason {
+        let why = match reason {
+            SafeModeReason::CrashLoop => {
+                format!("after {} unexpected exits in a row", safe_mode.crashes)
+            }
+            SafeModeReason::Requested => format!("by {}", safe_mode::SAFE_MODE_ARG),
+        };
+        issues.insert(
+            0,
+            format!(
+                "Safe mode {}: {} are off for this launch.",
+                why,
+                safe_mode.disabled.join(", ")
+            ),
+        );
+    }
+
+    let flag_level = white_window::level();
+    if flag_level != FlagLevel::Full {
+        issues.push(format!(
+            "Blank window recovery: the window stayed white with the full browser flag set, so WebView2 runs with {}. The full set is tried again after the next WebView2 update.",
+            flag_level.describe()
+        ));
+    }
+
+    let preflight = preflight::run();
+    for item in preflight.missing() {
+        issues.push(format!(
+            "Missing dependency ({}): {}.",
+            item.name, item.detail
+        ));
+    }
+
+    let update_signing_key = updates::signing_key().map(|k| k.key_id.clone());
+    if update_signing_key.is_none() {
+        issues.push("Update signing key is missing or unreadable, so automatic updates are turned off. Reinstall PACDeluxe from the official release page.".to_string());
+    }
+
+    HealthReport {
+        healthy: issues.is_empty(),
+        rendering,
+        gpu_driver,
+        update_signing_key,
+        preflight,
+        issues,
+    }
+}
+
+/// Whether the main window's `url` runs the injected runtime, and so sends
+/// heartbeats: the game on PACDeluxe's localhost origin. The loading and
+/// retry screens skip the runtime, and a mirror's origin is not allowed to
+/// call native commands.
+pub fn runs_runtime(url: &Url) -> bool {
+    url.host_str() == Some("localhost")
+        && url.path() != UNREACHABLE_PAGE_PATH
+        && url.path() != LOADING_PAGE_PATH
+}
+
+/// Result of probing the game server
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamProbe {
+    /// True when the server answered with a non-5xx status
+    pub reachable: bool,
+    pub status: Option<u16>,
+    /// Maintenance, outage, or challenge recognized from the response
+    pub issue: Option<UpstreamIssue>,
+    /// Why the server could not be reached
+    pub error: Option<String>,
+    pub latency_ms: u64,
+    /// Seconds since the Unix epoch
+    pub checked_at: u64,
+    /// First reachable configured mirror, when the game server itself is down
+    pub fallback: Option<String>,
+}
+
+/// Most recent probe (shown on the retry screen) and the game origin in use
+#[derive(Default)]
+pub struct UpstreamStatus {
+    probe: Mutex<Option<UpstreamProbe>>,
+    /// Mirror the game was loaded from; `None` means the official server
+    mirror: Mutex<Option<String>>,
+    /// Experimental offline mode: bundled UI only, server features disabled
+    offline: AtomicBool,
+}
+
+impl UpstreamStatus {
+    pub fn get(&self) -> Option<UpstreamProbe> {
+        self.probe.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set(&self, probe: UpstreamProbe) {
+        *self.probe.lock().unwrap_or_else(|e| e.into_inner()) = Some(probe);
+    }
+
+    /// Record which origin the main window is loading the game from
+    pub fn set_mirror(&self, mirror: Option<String>) {
+        *self.mirror.lock().unwrap_or_else(|e| e.into_inner()) = mirror;
+    }
+
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::SeqCst)
+    }
+
+    /// Game origin in use (the official server unless a mirror was chosen)
+    pub fn origin(&self) -> String {
+        self.mirror
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_else(|| UPSTREAM_URL.to_string())
+    }
+}
+
+/// Check that the game server answers, then try each mirror in order if it
+/// doesn't. Redirects are not followed; any answer below 500 counts as
+/// reachable.
+pub async fn probe_upstream(mirrors: &[String]) -> UpstreamProbe {
+    let mut probe = probe_url(UPSTREAM_URL).await;
+    if probe.reachable {
+        info!("Game server reachable ({} ms)", probe.latency_ms);
+        return probe;
+    }
+    warn!(
+        "Game server unreachable: status {:?}, error {:?}",
+        probe.status, probe.error
+    );
+
+    // Settings may have been edited by hand; skip anything invalid
+    let mirrors = mirrors
+        .iter()
+        .filter_map(|mirror| settings::normalize_mirror_url(mirror).ok());
+    for mirror in mirrors {
+        let mirror_probe = probe_url(&mirror).await;
+        if mirror_probe.reachable {
+            info!("Using mirror {} ({} ms)", mirror, mirror_probe.latency_ms);
+            probe.fallback = Some(mirror);
+            break;
+        }
+        warn!(
+            "Mirror {} unreachable: status {:?}, error {:?}",
+            mirror, mirror_probe.status, mirror_probe.error
+        );
+    }
+    probe
+}
+
+async fn probe_url(url: &str) -> UpstreamProbe {
+    let started = Instant::now();
+    let result = match reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .connect_timeout(PROBE_CONNECT_TIMEOUT)
+        .timeout(PROBE_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client.get(url).send().await,
+        Err(e) => {
+            return probe_result(
+                None,
+                Some(format!("HTTP client unavailable: {}", e)),
+                started,
+            )
+        }
+    };
+
+    match result {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let mut probe = probe_result(Some(status), None, started);
+            probe.issue = maintenance::classify(status, response.headers());
+            probe
+        }
+        Err(e) => probe_result(None, Some(describe_error(&e)), started),
+    }
+}
+
+fn probe_result(status: Option<u16>, error: Option<String>, started: Instant) -> UpstreamProbe {
+    UpstreamProbe {
+        reachable: status.is_some_and(|code| code < 500),
+        status,
+        issue: None,
+        error,
+        latency_ms: started.elapsed().as_millis() as u64,
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        fallback: None,
+    }
+}
+
+fn describe_error(error: &reqwest::Error) -> String {
+    if error.is_timeout() {
+        format!("timed out after {}s", PROBE_TIMEOUT.as_secs())
+    } else if error.is_connect() {
+        // The source chain carries the DNS / TCP / TLS detail
+        let mut detail = error.to_string();
+        let mut source = std::error::Error::source(error);
+        while let Some(inner) = source {
+            detail = inner.to_string();
+            source = inner.source();
+        }
+        format!("connection failed: {}", detail)
+    } else {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_errors_are_unreachable() {
+        let started = Instant::now();
+        assert!(probe_result(Some(200), None, started).reachable);
+        assert!(probe_result(Some(301), None, started).reachable);
+        assert!(!probe_result(Some(503), None, started).reachable);
+        assert!(!probe_result(None, Some("timed out".into()), started).reachable);
+    }
+
+    #[test]
+    fn only_the_local_game_sends_heartbeats() {
+        let runs = |url: &str| runs_runtime(&url.parse().unwrap());
+        assert!(runs("http://localhost:37529/lobby"));
+        assert!(!runs("http://localhost:37529/pacdeluxe/unreachable"));
+        assert!(!runs("http://localhost:37529/pacdeluxe/loading"));
+        assert!(!runs("https://mirror.example.org/"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Performance History - Cross-platform
+//!
+//! Fixed-size ring buffer of periodic performance samples, filled by a
+//! background sampler. Page metrics (JS heap, DOM nodes, event listeners)
+//! come from DevTools `Performance.getMetrics` and are only available on
+//! WebView2; on Linux those fields stay `None`, as does the WebView2 GPU
+//! process VRAM. Network throughput is machine-wide (all adapters), and the
+//! temperature is the hottest sensor sysinfo exposes, if any. Power draw is
+//! the estimate from `power_draw.rs`, next to the profile in effect.
+
+use crate::advisor;
+use crate::cdp;
+use crate::jank::JankTracker;
+use crate::performance::PerformanceSnapshot;
+use crate::power_draw::PowerScope;
+use crate::power_guard::PowerGuard;
+use crate::settings::{PowerProfile, SettingsState};
+use crate::soft_reload;
+use crate::PerformanceMonitor;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, warn};
+use ts_rs::TS;
+
+/// Time between samples
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// One hour of history at the sample interval
+const HISTORY_CAPACITY: usize = 720;
+/// Samples averaged at each end when measuring heap growth
+const GROWTH_WINDOW: usize = 6;
+
+/// One history entry
+#[derive(Debug, Clone, Serialize, TS, PartialEq, Default)]
+pub struct PerformanceSample {
+    /// Seconds since app start
+    pub uptime_secs: f64,
+    /// Frame rate last reported by the page (`None` while it is hidden)
+    pub fps: Option<f32>,
+    pub cpu_usage: f32,
+    #[ts(type = "number")]
+    pub memory_usage_mb: u64,
+    /// Memory of the WebView processes (renderers included)
+    #[ts(type = "number")]
+    pub webview_memory_mb: u64,
+    /// Used JS heap in MB
+    pub js_heap_used_mb: Option<f64>,
+    /// Live DOM nodes
+    #[ts(type = "number | null")]
+    pub dom_nodes: Option<u64>,
+    /// Registered JS event listeners
+    #[ts(type = "number | null")]
+    pub js_event_listeners: Option<u64>,
+    /// Main-thread time lost to long tasks over the last minute
+    pub main_thread_blocked_ms_per_min: f64,
+    /// Long tasks (stutters) over the last minute
+    pub long_tasks_per_min: u32,
+    /// Dedicated VRAM committed by the WebView2 GPU process (Windows)
+    pub webview_vram_mb: Option<f64>,
+    /// Received/sent KB per second since the previous sample
+    pub network_rx_kbps: f64,
+    pub network_tx_kbps: f64,
+    /// Hottest temperature sensor in °C
+    pub max_temperature_c: Option<f32>,
+    /// Estimated power draw in watts, and what it covers
+    pub power_watts: Option<f64>,
+    pub power_scope: Option<PowerScope>,
+    /// Profile in effect, emergency switches included
+    pub power_profile: PowerProfile,
+}
+
+/// Managed history state
+#[derive(Default)]
+pub struct PerformanceHistory {
+    samples: Mutex<VecDeque<PerformanceSample>>,
+    /// Latest frame rate from the page heartbeat, consumed by the next sample
+    fps: Mutex<Option<f32>>,
+}
+
+impl PerformanceHistory {
+    pub fn push(&self, sample: PerformanceSample) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        if samples.len() == HISTORY_CAPACITY {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    /// All samples, oldest first
+    pub fn snapshot(&self) -> Vec<PerformanceSample> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        samples.iter().cloned().collect()
+    }
+
+    pub fn latest(&self) -> Option<PerformanceSample> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        samples.back().cloned()
+    }
+
+    /// Record the page's frame rate
+    pub fn report_fps(&self, fps: f32) {
+        if fps.is_finite() && fps >= 0.0 {
+            *self.fps.lock().unwrap_or_else(|e| e.into_inner()) = Some(fps);
+        }
+    }
+
+    /// Frame rate reported since the last sample, if any. Taking it means a
+    /// hidden or frozen page shows up as a gap instead of its last value.
+    fn take_fps(&self) -> Option<f32> {
+        self.fps.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Ratio of recent JS heap to the heap at the start of the history
+    pub fn heap_growth_ratio(&self) -> Option<f64> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let heaps: Vec<f64> = samples.iter().filter_map(|s| s.js_heap_used_mb).collect();
+        heap_growth_ratio(&heaps)
+    }
+}
+
+fn heap_growth_ratio(heaps: &[f64]) -> Option<f64> {
+    // Need two non-overlapping windows to compare
+    if heaps.len() < GROWTH_WINDOW * 2 {
+        return None;
+    }
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let early = mean(&heaps[..GROWTH_WINDOW]);
+    let recent = mean(&heaps[heaps.len() - GROWTH_WINDOW..]);
+    (early > 0.0).then(|| recent / early)
+}
+
+/// Start the background sampler thread
+pub fn spawn_sampler(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("perf-history".to_string())
+        .spawn(move || {
+            let mut page_metrics_available = true;
+            let mut networks = sysinfo::Networks::new_with_refreshed_list();
+            let mut components = sysinfo::Components::new_with_refreshed_list();
+            loop {
+                std::thread::sleep(SAMPLE_INTERVAL);
+                if crate::suspend::is_suspended() {
+                    continue;
+                }
+
+                let PerformanceSnapshot {
+                    performance: stats,
+                    gpu,
+                    ..
+                } = app.state::<PerformanceMonitor>().snapshot();
+                let metrics = app.get_webview_window("main").and_then(|window| {
+                    match tauri::async_runtime::block_on(cdp::performance_metrics(&window)) {
+                        Ok(metrics) => Some(metrics),
+                        Err(e) => {
+                            if page_metrics_available {
+                                debug!("Page metrics unavailable: {}", e);
+                                page_metrics_available = false;
+                            }
+                            None
+                        }
+                    }
+                });
+                let metric = |name: &str| metrics.as_ref().and_then(|m| m.get(name).copied());
+                networks.refresh();
+                let (received, transmitted) =
+                    networks.iter().fold((0, 0), |(rx, tx), (_, data)| {
+                        (rx + data.received(), tx + data.transmitted())
+                    });
+                let kbps = |bytes: u64| bytes as f64 / 1024.0 / SAMPLE_INTERVAL.as_secs_f64();
+                components.refresh();
+                let jank = app.state::<JankTracker>().stats();
+
+                let history = app.state::<PerformanceHistory>();
+                let sample = PerformanceSample {
+                    uptime_secs: stats.uptime_secs,
+                    fps: history.take_fps(),
+                    cpu_usage: stats.cpu_usage,
+                    memory_usage_mb: stats.memory_usage_mb,
+                    webview_memory_mb: stats.webview_memory_mb,
+                    js_heap_used_mb: metric("JSHeapUsedSize").map(|b| b / 1024.0 / 1024.0),
+                    dom_nodes: metric("Nodes").map(|n| n as u64),
+                    js_event_listeners: metric("JSEventListeners").map(|n| n as u64),
+                    main_thread_blocked_ms_per_min: jank.blocked_ms_per_min,
+                    long_tasks_per_min: jank.long_tasks_per_min,
+                    webview_vram_mb: gpu.webview_vram_dedicated_mb,
+                    network_rx_kbps: kbps(received),
+                    network_tx_kbps: kbps(transmitted),
+                    max_temperature_c: advisor::max_temperature(&components).map(|(t, _)| t),
+                    power_watts: stats.power_draw.watts,
+                    power_scope: stats.power_draw.scope,
+                    power_profile: app
+                        .state::<PowerGuard>()
+                        .active_profile(app.state::<SettingsState>().get().power_profile),
+                };
+                soft_reload::evaluate(&app, &sample);
+                history.push(sample);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start performance history sampler: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_bounded() {
+        let history = PerformanceHistory::default();
+        for i in 0..HISTORY_CAPACITY + 10 {
+            history.push(PerformanceSample {
+                uptime_secs: i as f64,
+                ..Default::default()
+            });
+        }
+        let samples = history.snapshot();
+        assert_eq!(samples.len(), HISTORY_CAPACITY);
+        assert_eq!(samples[0].uptime_secs, 10.0);
+    }
+
+    #[test]
+    fn reported_fps_is_used_once() {
+        let history = PerformanceHistory::default();
+        history.report_fps(f32::NAN);
+        assert_eq!(history.take_fps(), None);
+        history.report_fps(58.0);
+        assert_eq!(history.take_fps(), Some(58.0));
+        assert_eq!(history.take_fps(), None);
+    }
+
+    #[test]
+    fn heap_growth_needs_enough_samples() {
+        assert_eq!(heap_growth_ratio(&[100.0; 5]), None);
+        let mut heaps = vec![100.0; GROWTH_WINDOW];
+        heaps.extend(vec![250.0; GROWTH_WINDOW]);
+        assert_eq!(heap_growth_ratio(&heaps), Some(2.5));
+    }
+}
@@ -0,0 +1,181 @@
+//! HTTP Request Log - Cross-platform
+//!
+//! Debug-only record of requests handled by the localhost asset server and
+//! the native proxy (method, URL, status, timing, size), for chasing
+//! slow-asset complaints. Off unless `http_logging` is enabled in settings.
+//!
+//! URLs are stored without query strings or fragments and run through the
+//! log redactor, so entries are safe to share.
+
+use crate::redact;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+/// Entries kept in memory
+const LOG_CAPACITY: usize = 500;
+
+/// Which part of the client handled the request
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum HttpSource {
+    /// Bundled asset served by the localhost server
+    Local,
+    /// Forwarded to the game server by the native proxy
+    Proxy,
+}
+
+/// One logged request
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HttpLogEntry {
+    /// Milliseconds since the Unix epoch
+    pub at_ms: u64,
+    pub source: HttpSource,
+    pub method: String,
+    /// Sanitized: no query string or fragment
+    pub url: String,
+    /// `None` when the request failed before a response
+    pub status: Option<u16>,
+    pub duration_ms: f64,
+    /// Response body size
+    pub bytes: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Managed request log
+#[derive(Default)]
+pub struct HttpLog {
+    enabled: AtomicBool,
+    entries: Mutex<VecDeque<HttpLogEntry>>,
+}
+
+impl HttpLog {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: AtomicBool::new(enabled),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Turn logging on or off; turning it off drops collected entries
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.entries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clear();
+        }
+    }
+
+    /// Record a request (no-op while disabled). `outcome` is the status and
+    /// body size, or why no response was received.
+    pub fn record(
+        &self,
+        source: HttpSource,
+        method: &str,
+        url: &str,
+        outcome: Result<(u16, Option<u64>), String>,
+        duration: Duration,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let (status, bytes, error) = match outcome {
+            Ok((status, bytes)) => (Some(status), bytes, None),
+            Err(error) => (None, None, Some(error)),
+        };
+        let entry = HttpLogEntry {
+            at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            source,
+            method: method.to_string(),
+            url: sanitize_url(url),
+            status,
+            duration_ms: duration.as_secs_f64() * 1000.0,
+            bytes,
+            error: error.map(|e| redact::redact(&e).into_owned()),
+        };
+        info!(
+            "HTTP {:?} {} {} -> {} in {:.0} ms ({} bytes)",
+            entry.source,
+            entry.method,
+            entry.url,
+            entry
+                .status
+                .map_or_else(|| "failed".to_string(), |s| s.to_string()),
+            entry.duration_ms,
+            entry.bytes.unwrap_or(0)
+        );
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() == LOG_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// All entries, oldest first
+    pub fn snapshot(&self) -> Vec<HttpLogEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().cloned().collect()
+    }
+}
+
+/// Drop the query string and fragment, then redact anything token-like
+fn sanitize_url(url: &str) -> String {
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    redact::redact(&url[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_lose_query_and_fragment() {
+        assert_eq!(
+            sanitize_url("https://pokemon-auto-chess.com/profile?token=abc#x"),
+            "https://pokemon-auto-chess.com/profile"
+        );
+        assert_eq!(sanitize_url("/assets/app.js"), "/assets/app.js");
+    }
+
+    #[test]
+    fn disabled_log_records_nothing() {
+        let log = HttpLog::new(false);
+        log.record(
+            HttpSource::Local,
+            "GET",
+            "/",
+            Ok((200, Some(1))),
+            Duration::ZERO,
+        );
+        assert!(log.snapshot().is_empty());
+    }
+
+    #[test]
+    fn log_is_bounded() {
+        let log = HttpLog::new(true);
+        for i in 0..LOG_CAPACITY + 5 {
+            log.record(
+                HttpSource::Proxy,
+                "GET",
+                &format!("/{}", i),
+                Ok((200, None)),
+                Duration::ZERO,
+            );
+        }
+        let entries = log.snapshot();
+        assert_eq!(entries.len(), LOG_CAPACITY);
+        assert_eq!(entries[0].url, "/5");
+    }
+}
@@ -0,0 +1,190 @@
+//! Idle Throttling - Windows (WebView2)
+//!
+//! The browser flags turn Chromium's background throttling off for the whole
+//! run, so a match keeps its timers when the window is behind OBS. Outside a
+//! match that costs power for nothing. With `idle_throttle` on, PACDeluxe
+//! follows the `pac-page-state` phase and the main window's focus: while
+//! the window is unfocused and no room or match is in progress, the page's
+//! main thread is slowed down through the allowlisted DevTools method
+//! `Emulation.setCPUThrottlingRate` and the 1 ms timer resolution is
+//! released. Focusing the window or entering a room undoes both.
+//!
+//! Chromium's own throttling switches are read once when WebView2 starts,
+//! which is why the slowdown goes through DevTools rather than the flags.
+//!
+//! Linux: not available (no DevTools protocol, no timer resolution).
+
+use crate::cdp;
+use crate::page_state::GamePhase;
+use crate::performance;
+use crate::settings::IdleThrottleSettings;
+use serde_json::json;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Listener, Manager, WebviewWindow, WindowEvent};
+use tracing::{debug, info, warn};
+
+struct State {
+    settings: IdleThrottleSettings,
+    phase: GamePhase,
+    focused: bool,
+    /// Throttled right now
+    idle: bool,
+    /// The 1 ms timer resolution was held when the page went idle
+    released_timer: bool,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    settings: IdleThrottleSettings {
+        enabled: false,
+        cpu_slowdown: 4,
+    },
+    phase: GamePhase::Home,
+    focused: true,
+    idle: false,
+    released_timer: false,
+});
+
+/// CPU throttling rates for the worker, which applies them in order
+static RATES: OnceLock<Sender<u8>> = OnceLock::new();
+
+/// Whether the page should be throttled
+fn is_idle(settings: &IdleThrottleSettings, phase: GamePhase, focused: bool) -> bool {
+    settings.enabled && !focused && !phase.is_match_in_progress()
+}
+
+fn send_rate(rate: u8) {
+    if let Some(rates) = RATES.get() {
+        let _ = rates.send(rate);
+    }
+}
+
+/// Throttle or unthrottle to match the state
+fn update(state: &mut State) {
+    let idle = is_idle(&state.settings, state.phase, state.focused);
+    if idle == state.idle {
+        return;
+    }
+    state.idle = idle;
+    if idle {
+        state.released_timer = performance::high_timer_resolution_active();
+        performance::set_high_timer_resolution(false);
+    } else if std::mem::take(&mut state.released_timer) && !crate::suspend::is_suspended() {
+        performance::set_high_timer_resolution(true);
+    }
+    send_rate(if idle { state.settings.cpu_slowdown } else { 1 });
+    debug!(
+        "Idle throttling {} ({:?}, focused: {})",
+        if idle { "on" } else { "off" },
+        state.phase,
+        state.focused
+    );
+}
+
+/// Use `settings` from now on
+pub fn apply(settings: IdleThrottleSettings) -> Result<(), String> {
+    if cfg!(target_os = "linux") && settings.enabled {
+        return Err("Idle throttling is only available on Windows".to_string());
+    }
+    let settings = settings.validated();
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let slowdown_changed = state.settings.cpu_slowdown != settings.cpu_slowdown;
+    state.settings = settings;
+    info!(
+        "Idle throttling {} ({}x slowdown)",
+        if settings.enabled { "on" } else { "off" },
+        settings.cpu_slowdown
+    );
+    if state.idle && slowdown_changed {
+        send_rate(settings.cpu_slowdown);
+    }
+    update(&mut state);
+    Ok(())
+}
+
+/// Start the DevTools worker and follow the page phase
+pub fn spawn(app: AppHandle, settings: IdleThrottleSettings) {
+    let (tx, rx) = mpsc::channel::<u8>();
+    if RATES.set(tx).is_err() {
+        return;
+    }
+    let worker_app = app.clone();
+    std::thread::Builder::new()
+        .name("idle-throttle".to_string())
+        .spawn(move || {
+            while let Ok(mut rate) = rx.recv() {
+                // Only the newest rate matters
+                while let Ok(newer) = rx.try_recv() {
+                    rate = newer;
+                }
+                let Some(window) = worker_app.get_webview_window("main") else {
+                    continue;
+                };
+                let call = cdp::call(
+                    &window,
+                    "Emulation.setCPUThrottlingRate",
+                    json!({ "rate": rate }),
+                );
+                if let Err(e) = tauri::async_runtime::block_on(call) {
+                    warn!("Failed to set CPU throttling rate {}: {}", rate, e);
+                }
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start idle throttling: {}", e));
+
+    app.listen_any("pac-page-state", |event| {
+        let Ok(phase) = serde_json::from_str::<GamePhase>(event.payload()) else {
+            return;
+        };
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.phase = phase;
+        update(&mut state);
+    });
+    if settings.enabled {
+        if let Err(e) = apply(settings) {
+            warn!("{}", e);
+        }
+    }
+}
+
+/// Follow a newly built main window's focus
+pub fn install(window: &WebviewWindow) {
+    {
+        let state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        // A rebuilt webview starts unthrottled
+        if state.idle {
+            send_rate(state.settings.cpu_slowdown);
+        }
+    }
+    window.on_window_event(|event| {
+        if let WindowEvent::Focused(focused) = event {
+            let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+            state.focused = *focused;
+            update(&mut state);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unfocused_pages_outside_a_match_are_idle() {
+        let on = IdleThrottleSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(is_idle(&on, GamePhase::Lobby, false));
+        assert!(is_idle(&on, GamePhase::AfterGame, false));
+        assert!(!is_idle(&on, GamePhase::Lobby, true));
+        assert!(!is_idle(&on, GamePhase::InGame, false));
+        assert!(!is_idle(&on, GamePhase::Preparation, false));
+        assert!(!is_idle(
+            &IdleThrottleSettings::default(),
+            GamePhase::Lobby,
+            false
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+//! Runtime Injection - Cross-platform
+//!
+//! Schedules the bundled page scripts for the point in a document's life
+//! they need. Scripts are registered with the main webview when it is built
+//! and the engine runs them in every new document of the main frame, before
+//! any page script - reloads and client navigations included, with no
+//! polling from Rust:
+//!
+//! - Windows: WebView2 `AddScriptToExecuteOnDocumentCreated`
+//! - Linux: a WebKitGTK user script at document start
+//!
+//! A script that patches the page (fetch, `window.open`, prototypes the
+//! bundle looks up once) runs at [`InjectionPhase::DocumentStart`]; one that
+//! needs the DOM runs at [`InjectionPhase::DomReady`], which waits for
+//! `DOMContentLoaded` while the document is still parsing.
+//!
+//! Per-user configuration the scripts read at start (the feature toggles of
+//! `set_injection_flags`) is registered ahead of them with [`config_script`],
+//! as a frozen global. It is fixed when the webview is built and changes
+//! from the next start or `recreate_webview`.
+
+use serde::Serialize;
+use tauri::{Manager, Runtime, WebviewWindowBuilder};
+use tracing::debug;
+
+/// When a runtime script runs in each document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectionPhase {
+    /// Before the page's own scripts; `document.body` does not exist yet
+    DocumentStart,
+    /// Once the document has been parsed
+    DomReady,
+}
+
+/// A script bundled with the app
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeScript {
+    /// Shown in the console when the script throws
+    pub name: &'static str,
+    pub phase: InjectionPhase,
+    pub source: &'static str,
+}
+
+impl RuntimeScript {
+    /// The source as registered: not run on `skip_paths`, started at its
+    /// phase, and an exception is logged with the script's name instead of
+    /// stopping the scripts after it
+    pub fn wrapped(&self, skip_paths: &[&str]) -> String {
+        let skip = serde_json::to_string(skip_paths).unwrap_or_else(|_| "[]".to_string());
+        let name = serde_json::to_string(self.name).unwrap_or_default();
+        let start = match self.phase {
+            InjectionPhase::DocumentStart => "run();",
+            InjectionPhase::DomReady => {
+                "if (document.readyState === 'loading') {\n        \
+                 document.addEventListener('DOMContentLoaded', run, { once: true });\n    \
+                 } else {\n        run();\n    }"
+            }
+        };
+        format!(
+            "(function() {{\n    if ({skip}.includes(window.location.pathname)) return;\n    \
+             function run() {{\n        try {{\n{source}\n        }} catch (e) {{\n            \
+             console.error('[PACDeluxe] Runtime script ' + {name} + ' failed:', e);\n        \
+             }}\n    }}\n    {start}\n}})();\n",
+            skip = skip,
+            source = self.source,
+            name = name,
+            start = start,
+        )
+    }
+}
+
+/// A script that sets `window.<global>` to `value`, frozen, for the runtime
+/// scripts registered after it
+pub fn config_script<T: Serialize>(global: &str, value: &T) -> String {
+    let json = serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string());
+    format!("window.{} = Object.freeze({});\n", global, json)
+}
+
+/// Register `scripts`, in order, on the window being built
+pub fn register<'a, R: Runtime, M: Manager<R>>(
+    mut builder: WebviewWindowBuilder<'a, R, M>,
+    scripts: &[RuntimeScript],
+    skip_paths: &[&str],
+) -> WebviewWindowBuilder<'a, R, M> {
+    for script in scripts {
+        debug!("Registering {} at {:?}", script.name, script.phase);
+        builder = builder.initialization_script(&script.wrapped(skip_paths));
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(phase: InjectionPhase) -> RuntimeScript {
+        RuntimeScript {
+            name: "overlay",
+            phase,
+            source: "console.log('hi');",
+        }
+    }
+
+    #[test]
+    fn each_phase_starts_the_script_its_own_way() {
+        let early = script(InjectionPhase::DocumentStart).wrapped(&[]);
+        assert!(early.contains("console.log('hi');"));
+        assert!(early.contains("\n    run();\n"));
+        assert!(!early.contains("DOMContentLoaded"));
+
+        let ready = script(InjectionPhase::DomReady).wrapped(&[]);
+        assert!(ready.contains("document.readyState === 'loading'"));
+        assert!(ready.contains("addEventListener('DOMContentLoaded', run, { once: true })"));
+    }
+
+    #[test]
+    fn config_is_a_frozen_global() {
+        let flags = serde_json::json!({ "booster_fix": false });
+        assert_eq!(
+            config_script("__PAC_INJECTION_FLAGS__", &flags),
+            "window.__PAC_INJECTION_FLAGS__ = Object.freeze({\"booster_fix\":false});\n"
+        );
+    }
+
+    #[test]
+    fn skipped_paths_are_quoted_for_javascript() {
+        let wrapped =
+            script(InjectionPhase::DocumentStart).wrapped(&["/pacdeluxe/unreachable", "/a\"b"]);
+        assert!(wrapped.contains(r#"if (["/pacdeluxe/unreachable","/a\"b"].includes("#));
+        assert!(wrapped.contains(r#"'[PACDeluxe] Runtime script ' + "overlay" + ' failed:'"#));
+    }
+}
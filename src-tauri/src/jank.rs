@@ -0,0 +1,223 @@
+//! Main-Thread Jank Tracking - Cross-platform
+//!
+//! Aggregates long tasks and layout shifts reported by the injected
+//! runtime's PerformanceObserver. The headline number is "main-thread
+//! blocked ms per minute": time beyond the 50 ms long-task budget, which
+//! separates JS-bound slowness from GPU-bound slowness.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Rolling window for per-minute stats
+const WINDOW: Duration = Duration::from_secs(60);
+/// Long tasks ("longtask" entries) are at least this long by definition
+const LONG_TASK_BUDGET_MS: f64 = 50.0;
+/// Cap on remembered tasks so a pathological page can't grow this unbounded
+const MAX_TASKS: usize = 2000;
+/// Blocked time per minute worth a health warning on the page
+const WARNING_BLOCKED_MS: f64 = 5000.0;
+/// At most one warning this often
+const WARNING_COOLDOWN: Duration = Duration::from_secs(600);
+
+/// One long task as reported by the page
+#[derive(Debug, Clone, Deserialize)]
+pub struct LongTaskReport {
+    pub duration_ms: f64,
+    /// Script or frame blamed by the browser (e.g. "self", a script URL)
+    pub attribution: Option<String>,
+}
+
+/// Aggregated jank stats for the last minute
+#[derive(Debug, Clone, Serialize, TS, PartialEq, Default)]
+pub struct JankStats {
+    /// Main-thread time beyond the long-task budget, per minute
+    pub blocked_ms_per_min: f64,
+    pub long_tasks_per_min: u32,
+    /// Cumulative layout shift score over the last minute
+    pub layout_shift_per_min: f64,
+    /// Attributions sorted by blocked time, worst first (max 5)
+    pub top_sources: Vec<(String, f64)>,
+}
+
+struct Entry {
+    at: Instant,
+    blocked_ms: f64,
+    source: String,
+}
+
+#[derive(Default)]
+struct Inner {
+    tasks: VecDeque<Entry>,
+    layout_shifts: VecDeque<(Instant, f64)>,
+    warned_at: Option<Instant>,
+}
+
+/// Managed jank tracker
+#[derive(Default)]
+pub struct JankTracker(Mutex<Inner>);
+
+impl JankTracker {
+    /// Record a batch of page reports
+    pub fn record(&self, tasks: &[LongTaskReport], layout_shift: f64) {
+        self.record_at(Instant::now(), tasks, layout_shift);
+    }
+
+    fn record_at(&self, now: Instant, tasks: &[LongTaskReport], layout_shift: f64) {
+        let mut inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        for task in tasks {
+            if !task.duration_ms.is_finite() {
+                continue;
+            }
+            if inner.tasks.len() == MAX_TASKS {
+                inner.tasks.pop_front();
+            }
+            inner.tasks.push_back(Entry {
+                at: now,
+                blocked_ms: (task.duration_ms - LONG_TASK_BUDGET_MS).max(0.0),
+                source: task
+                    .attribution
+                    .clone()
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or_else(|| "unknown".to_string()),
+            });
+        }
+        if layout_shift.is_finite() && layout_shift > 0.0 {
+            inner.layout_shifts.push_back((now, layout_shift));
+        }
+        prune(&mut inner, now);
+    }
+
+    /// Stats for the last minute
+    pub fn stats(&self) -> JankStats {
+        self.stats_at(Instant::now())
+    }
+
+    fn stats_at(&self, now: Instant) -> JankStats {
+        let mut inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        prune(&mut inner, now);
+
+        let mut by_source: HashMap<&str, f64> = HashMap::new();
+        for task in &inner.tasks {
+            *by_source.entry(task.source.as_str()).or_default() += task.blocked_ms;
+        }
+        let mut top_sources: Vec<(String, f64)> = by_source
+            .into_iter()
+            .map(|(source, ms)| (source.to_string(), ms))
+            .collect();
+        top_sources.sort_by(|a, b| b.1.total_cmp(&a.1));
+        top_sources.truncate(5);
+
+        JankStats {
+            blocked_ms_per_min: inner.tasks.iter().map(|t| t.blocked_ms).sum(),
+            long_tasks_per_min: inner.tasks.len() as u32,
+            layout_shift_per_min: inner.layout_shifts.iter().map(|(_, v)| v).sum(),
+            top_sources,
+        }
+    }
+
+    /// A health warning when the main thread was blocked heavily in the
+    /// last minute, at most once per cooldown
+    pub fn warning(&self) -> Option<String> {
+        self.warning_at(Instant::now())
+    }
+
+    fn warning_at(&self, now: Instant) -> Option<String> {
+        let stats = self.stats_at(now);
+        if stats.blocked_ms_per_min < WARNING_BLOCKED_MS {
+            return None;
+        }
+        let mut inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if inner
+            .warned_at
+            .is_some_and(|at| now.duration_since(at) < WARNING_COOLDOWN)
+        {
+            return None;
+        }
+        inner.warned_at = Some(now);
+        let worst = stats
+            .top_sources
+            .first()
+            .map(|(source, _)| format!(", mostly in {}", source))
+            .unwrap_or_default();
+        Some(format!(
+            "The game was unresponsive for {:.1} s in the last minute{}. The performance overlay's advice may help.",
+            stats.blocked_ms_per_min / 1000.0,
+            worst
+        ))
+    }
+}
+
+fn prune(inner: &mut Inner, now: Instant) {
+    let expired = |at: Instant| now.duration_since(at) > WINDOW;
+    while inner.tasks.front().is_some_and(|t| expired(t.at)) {
+        inner.tasks.pop_front();
+    }
+    while inner
+        .layout_shifts
+        .front()
+        .is_some_and(|(at, _)| expired(*at))
+    {
+        inner.layout_shifts.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(duration_ms: f64, attribution: &str) -> LongTaskReport {
+        LongTaskReport {
+            duration_ms,
+            attribution: Some(attribution.to_string()),
+        }
+    }
+
+    #[test]
+    fn blocked_time_excludes_budget() {
+        let tracker = JankTracker::default();
+        let now = Instant::now();
+        tracker.record_at(now, &[task(80.0, "self"), task(150.0, "self")], 0.0);
+        let stats = tracker.stats_at(now);
+        assert_eq!(stats.blocked_ms_per_min, 130.0);
+        assert_eq!(stats.long_tasks_per_min, 2);
+    }
+
+    #[test]
+    fn sources_are_ranked() {
+        let tracker = JankTracker::default();
+        let now = Instant::now();
+        tracker.record_at(now, &[task(60.0, "a"), task(300.0, "b")], 0.1);
+        let stats = tracker.stats_at(now);
+        assert_eq!(stats.top_sources[0].0, "b");
+        assert_eq!(stats.layout_shift_per_min, 0.1);
+    }
+
+    #[test]
+    fn old_entries_expire() {
+        let tracker = JankTracker::default();
+        let start = Instant::now();
+        tracker.record_at(start, &[task(500.0, "self")], 0.5);
+        let stats = tracker.stats_at(start + Duration::from_secs(61));
+        assert_eq!(stats, JankStats::default());
+    }
+
+    #[test]
+    fn heavy_blocking_warns_once_per_cooldown() {
+        let tracker = JankTracker::default();
+        let start = Instant::now();
+        tracker.record_at(start, &[task(2000.0, "self")], 0.0);
+        assert_eq!(tracker.warning_at(start), None);
+
+        tracker.record_at(start, &[task(4000.0, "self")], 0.0);
+        let warning = tracker.warning_at(start).unwrap();
+        assert!(warning.contains("5.9 s"), "{}", warning);
+        assert_eq!(tracker.warning_at(start), None);
+
+        let later = start + Duration::from_secs(601);
+        tracker.record_at(later, &[task(6000.0, "self")], 0.0);
+        assert!(tracker.warning_at(later).is_some());
+    }
+}
@@ -0,0 +1,119 @@
+//! Key Bindings - Cross-platform
+//!
+//! Lets players rebind the game's shop shortcuts (buy XP, reroll, sell)
+//! per profile. The page keeps its own keys; the injected runtime swaps a
+//! pressed key for the one the game expects before the game's handlers see
+//! it, so every press still maps to exactly one game key press.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+/// The game's own keys
+pub const GAME_BUY_XP_KEY: &str = "f";
+pub const GAME_REROLL_KEY: &str = "d";
+pub const GAME_SELL_KEY: &str = "e";
+
+/// Keys pressed for each game shortcut
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub buy_xp: String,
+    pub reroll: String,
+    pub sell: String,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            buy_xp: GAME_BUY_XP_KEY.to_string(),
+            reroll: GAME_REROLL_KEY.to_string(),
+            sell: GAME_SELL_KEY.to_string(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Lowercase the keys, rejecting anything but single letters or digits
+    /// and two shortcuts on one key
+    pub fn validated(mut self) -> Result<Self, String> {
+        for key in [&mut self.buy_xp, &mut self.reroll, &mut self.sell] {
+            let lower = key.trim().to_lowercase();
+            let mut chars = lower.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() => *key = lower,
+                _ => return Err(format!("Key must be a single letter or digit: {:?}", key)),
+            }
+        }
+        if self.buy_xp == self.reroll || self.buy_xp == self.sell || self.reroll == self.sell {
+            return Err("Each shortcut needs its own key".to_string());
+        }
+        Ok(self)
+    }
+
+    /// Pressed key -> game key, for the keys that differ from the game's
+    pub fn translation(&self) -> HashMap<String, String> {
+        [
+            (&self.buy_xp, GAME_BUY_XP_KEY),
+            (&self.reroll, GAME_REROLL_KEY),
+            (&self.sell, GAME_SELL_KEY),
+        ]
+        .into_iter()
+        .filter(|(pressed, game)| pressed.as_str() != *game)
+        .map(|(pressed, game)| (pressed.clone(), game.to_string()))
+        .collect()
+    }
+}
+
+/// Bindings and the translation table the page applies, as returned by
+/// `get_key_bindings` and sent as `pac-key-bindings`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct KeyRemap {
+    pub bindings: KeyBindings,
+    pub translate: HashMap<String, String>,
+}
+
+impl From<KeyBindings> for KeyRemap {
+    fn from(bindings: KeyBindings) -> Self {
+        let translate = bindings.translation();
+        Self {
+            bindings,
+            translate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(buy_xp: &str, reroll: &str, sell: &str) -> KeyBindings {
+        KeyBindings {
+            buy_xp: buy_xp.to_string(),
+            reroll: reroll.to_string(),
+            sell: sell.to_string(),
+        }
+    }
+
+    #[test]
+    fn only_rebound_keys_are_translated() {
+        assert!(KeyBindings::default().translation().is_empty());
+
+        let remap = bindings("F", " r ", "e").validated().unwrap().translation();
+        assert_eq!(remap.len(), 1);
+        assert_eq!(remap.get("r").map(String::as_str), Some("d"));
+
+        // Swapping two shortcuts translates both ways
+        let swapped = bindings("d", "f", "e").validated().unwrap().translation();
+        assert_eq!(swapped.get("d").map(String::as_str), Some("f"));
+        assert_eq!(swapped.get("f").map(String::as_str), Some("d"));
+    }
+
+    #[test]
+    fn unusable_bindings_are_rejected() {
+        assert!(bindings("", "d", "e").validated().is_err());
+        assert!(bindings("ff", "d", "e").validated().is_err());
+        assert!(bindings(" ", "d", "e").validated().is_err());
+        assert!(bindings("f", "F", "e").validated().is_err());
+    }
+}
@@ -5,5 +5,87 @@
 
 pub mod performance;
 pub mod commands;
+pub mod settings;
+pub mod redact;
+pub mod advisor;
+pub mod rendering;
+pub mod health;
+pub mod cdp;
+pub mod history;
+pub mod page_state;
+pub mod soft_reload;
+pub mod jank;
+pub mod session;
+pub mod recovery;
+pub mod maintenance;
+pub mod tray;
+pub mod http_log;
+pub mod preconnect;
+pub mod browser_flags;
+pub mod cpu_frequency;
+pub mod customization;
+pub mod gpu_driver;
 
 pub use performance::PerformanceMonitor;
+pub mod overlay_stream;
+pub mod display_watch;
+pub mod shortcuts;
+pub mod system_keys;
+pub mod mouse_precision;
+pub mod audio;
+pub mod profile;
+pub mod scheduler;
+pub mod reminders;
+pub mod dashboard;
+pub mod deep_links;
+pub mod report;
+pub mod ws_inspector;
+pub mod macros;
+pub mod keybinds;
+pub mod attention;
+pub mod power_guard;
+pub mod suspend;
+pub mod injection;
+pub mod observers;
+pub mod page_api;
+pub mod page_events;
+pub mod updates;
+pub mod safe_mode;
+pub mod recovery_console;
+pub mod process_failures;
+pub mod self_test;
+pub mod load_sim;
+pub mod hdr_control;
+pub mod gamma;
+pub mod elevation_policy;
+pub mod resource_limits;
+pub mod preflight;
+pub mod white_window;
+pub mod idle_throttle;
+pub mod power_draw;
+pub mod refresh_rate;
+pub mod server_status;
+pub mod planner;
+pub mod quick_reference;
+pub mod contrast;
+pub mod tts;
+pub mod low_spec;
+pub mod settings_migration;
+pub mod backup;
+
+/// Put back the system settings changed while running (Sticky Keys
+/// prompts, mouse precision, gamma, refresh rate, HDR) and save play time
+/// and the window geometry. `RunEvent::Exit` is not emitted on restart, and
+/// on Windows the update installer replaces the process, so those call it
+/// first.
+pub fn restore_system_state(app: &tauri::AppHandle) {
+    use tauri::Manager;
+    // Persist play time collected since the last periodic save
+    app.state::<session::SessionStore>().save();
+    system_keys::restore();
+    mouse_precision::restore();
+    gamma::restore();
+    refresh_rate::restore();
+    hdr_control::restore();
+    profile::save_window_geometry(app);
+}
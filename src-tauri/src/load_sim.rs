@@ -0,0 +1,113 @@
+//! Simulated Load - Cross-platform
+//!
+//! `simulate_load` keeps the CPU and GPU busy for a fixed time, so support
+//! can check on a user's machine that the stats overlay, the power and
+//! thermal detection and the health warnings respond to load. CPU load is a
+//! busy loop on every logical core; GPU load is a WebGL draw loop the
+//! runtime runs on an `OffscreenCanvas` in a worker, so the page's own
+//! frames are not what stalls.
+//!
+//! Hidden: not in the page API or any UI. Support asks for it from DevTools
+//! or a user script: `__TAURI__.core.invoke('simulate_load', { seconds: 30 })`.
+
+use crate::page_events::{self, PageEvent};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+const DEFAULT_SECONDS: u64 = 30;
+const MAX_SECONDS: u64 = 120;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// `simulate_load` result
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadRun {
+    pub seconds: u64,
+    pub cpu_threads: usize,
+    /// Whether the page was asked to run the WebGL worker
+    pub gpu: bool,
+}
+
+fn duration(seconds: Option<u64>) -> Duration {
+    Duration::from_secs(seconds.unwrap_or(DEFAULT_SECONDS).clamp(1, MAX_SECONDS))
+}
+
+fn spin_until(deadline: Instant) {
+    let mut x: u64 = 0x9E37_79B9_7F4A_7C15;
+    while Instant::now() < deadline {
+        for _ in 0..10_000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+        }
+        std::hint::black_box(x);
+    }
+}
+
+/// Start a load run; fails while one is already going
+pub fn start(app: &AppHandle, seconds: Option<u64>, gpu: bool) -> Result<LoadRun, String> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("A simulated load is already running".to_string());
+    }
+    let length = duration(seconds);
+    let cpu_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let deadline = Instant::now() + length;
+
+    let spawned = std::thread::Builder::new()
+        .name("simulate-load".to_string())
+        .spawn(move || {
+            let workers: Vec<_> = (0..cpu_threads)
+                .filter_map(|i| {
+                    std::thread::Builder::new()
+                        .name(format!("simulate-load-{}", i))
+                        .spawn(move || spin_until(deadline))
+                        .map_err(|e| warn!("Failed to start load thread: {}", e))
+                        .ok()
+                })
+                .collect();
+            for worker in workers {
+                let _ = worker.join();
+            }
+            RUNNING.store(false, Ordering::SeqCst);
+            info!("Simulated load finished");
+        });
+    if let Err(e) = spawned {
+        RUNNING.store(false, Ordering::SeqCst);
+        return Err(format!("Failed to start simulated load: {}", e));
+    }
+
+    if gpu {
+        page_events::emit(
+            app,
+            PageEvent::SimulateLoad {
+                duration_ms: length.as_millis() as u64,
+            },
+        );
+    }
+    info!(
+        "Simulating load for {} s on {} thread(s){}",
+        length.as_secs(),
+        cpu_threads,
+        if gpu { " plus WebGL" } else { "" }
+    );
+    Ok(LoadRun {
+        seconds: length.as_secs(),
+        cpu_threads,
+        gpu,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_length_is_bounded() {
+        assert_eq!(duration(None).as_secs(), DEFAULT_SECONDS);
+        assert_eq!(duration(Some(0)).as_secs(), 1);
+        assert_eq!(duration(Some(3600)).as_secs(), MAX_SECONDS);
+    }
+}
@@ -17,15 +17,39 @@
 //!   - falls back to `index.html` only for known React Router routes
 //!   - returns explicit 404/501 responses for missing assets or upstream API
 //!     paths, so stale service workers cannot cache HTML as game data
+//!   - optionally attaches a Content-Security-Policy (or its report-only
+//!     variant) to HTML documents, per the user's `csp_mode` setting
+//!   - adds `Link: rel=preconnect` hints for the game's origins to HTML
+//!     documents
+//!   - serves the built-in "cannot reach pokemon-auto-chess.com" retry
+//!     page used when the startup probe fails, the page shown while it
+//!     runs, the stats dashboard and the team planner
 
 use std::net::{SocketAddr, TcpListener};
-
-use tauri::{AppHandle, Asset, Runtime};
+use std::time::Instant;
+
+use pac_deluxe_lib::dashboard;
+use pac_deluxe_lib::http_log::{HttpLog, HttpSource};
+use pac_deluxe_lib::planner;
+use pac_deluxe_lib::preconnect;
+use pac_deluxe_lib::settings::{CspMode, SettingsState};
+use tauri::{AppHandle, Asset, Manager, Runtime};
 use tiny_http::{Header, Method, Response, Server, StatusCode};
 use tracing::{debug, info, warn};
 
+/// Retry screen shown instead of the game when the server is unreachable,
+/// and the page shown while the server is probed at startup
+pub use pac_deluxe_lib::health::{LOADING_PAGE_PATH, UNREACHABLE_PAGE_PATH};
+
 const PREFERRED_LOCALHOST_PORT: u16 = 37529;
 
+const UNREACHABLE_PAGE: &str = include_str!("runtime/unreachable.html");
+const LOADING_PAGE: &str = include_str!("runtime/loading.html");
+/// Stats dashboard window (see `dashboard.rs`)
+const DASHBOARD_PAGE: &str = include_str!("runtime/dashboard.html");
+/// Team planner window (see `planner.rs`)
+const PLANNER_PAGE: &str = include_str!("runtime/planner.html");
+
 /// Start the localhost server, preferring a stable port for persisted auth
 /// state but falling back to another free port when that one is unavailable.
 /// Returns the actual bound port.
@@ -103,7 +127,19 @@ fn spawn_bound_server<R: Runtime>(
 
     std::thread::spawn(move || {
         for request in server.incoming_requests() {
-            handle(request, &app);
+            let started = Instant::now();
+            let method = request.method().to_string();
+            let url = request.url().to_string();
+            let (status, bytes) = handle(request, &app);
+            if let Some(log) = app.try_state::<HttpLog>() {
+                log.record(
+                    HttpSource::Local,
+                    &method,
+                    &url,
+                    Ok((status, Some(bytes))),
+                    started.elapsed(),
+                );
+            }
         }
     });
 
@@ -138,12 +174,29 @@ const LOCAL_STATIC_FETCH_EXTENSIONS: &[&str] = &[
     ".ico", ".mp3", ".ogg", ".wav", ".m4a", ".woff", ".woff2", ".ttf", ".otf",
 ];
 
-fn handle<R: Runtime>(request: tiny_http::Request, app: &AppHandle<R>) {
+/// Stricter-than-upstream policy for bundled documents. Covers the local
+/// origin, Tauri IPC, the production origin (HTTP + websocket), and the
+/// Firebase/Google auth hosts. Everything else (other script origins,
+/// plugins, foreign frames) is refused.
+const INJECTED_CSP: &str = "default-src 'self'; \
+script-src 'self' 'unsafe-inline' 'unsafe-eval' https://*.firebaseapp.com https://apis.google.com https://*.googleapis.com https://www.gstatic.com; \
+style-src 'self' 'unsafe-inline' https://fonts.googleapis.com; \
+img-src 'self' data: blob: https:; \
+font-src 'self' data: https://fonts.gstatic.com; \
+media-src 'self' data: blob:; \
+connect-src 'self' ipc: http://ipc.localhost https://pokemon-auto-chess.com https://*.pokemon-auto-chess.com wss://pokemon-auto-chess.com wss://*.pokemon-auto-chess.com https://*.googleapis.com https://*.firebaseio.com wss://*.firebaseio.com; \
+frame-src 'self' https://*.firebaseapp.com https://accounts.google.com; \
+worker-src 'self' blob:; \
+object-src 'none'; \
+base-uri 'self'";
+
+/// Serve one request; returns the status and body size for the request log
+fn handle<R: Runtime>(request: tiny_http::Request, app: &AppHandle<R>) -> (u16, u64) {
     match request.method() {
         Method::Get | Method::Head => {}
         _ => {
             let _ = request.respond(Response::empty(405));
-            return;
+            return (405, 0);
         }
     }
 
@@ -152,7 +205,45 @@ fn handle<R: Runtime>(request: tiny_http::Request, app: &AppHandle<R>) {
     let path = path.split('#').next().unwrap_or("/");
     let is_head = matches!(request.method(), Method::Head);
 
+    if path == UNREACHABLE_PAGE_PATH {
+        return respond_text(
+            request,
+            200,
+            "text/html; charset=utf-8",
+            UNREACHABLE_PAGE,
+            is_head,
+        );
+    }
+    if path == LOADING_PAGE_PATH {
+        return respond_text(
+            request,
+            200,
+            "text/html; charset=utf-8",
+            LOADING_PAGE,
+            is_head,
+        );
+    }
+    if path == dashboard::DASHBOARD_PAGE_PATH {
+        return respond_text(
+            request,
+            200,
+            "text/html; charset=utf-8",
+            DASHBOARD_PAGE,
+            is_head,
+        );
+    }
+    if path == planner::PLANNER_PAGE_PATH {
+        return respond_text(
+            request,
+            200,
+            "text/html; charset=utf-8",
+            PLANNER_PAGE,
+            is_head,
+        );
+    }
+
     let resolver = app.asset_resolver();
+    let csp = csp_header(app);
 
     // Classify before asking the resolver. Tauri's release AssetResolver can
     // itself return index.html for unknown paths, so calling it first would
@@ -166,9 +257,9 @@ fn handle<R: Runtime>(request: tiny_http::Request, app: &AppHandle<R>) {
             resolver.get(asset_key)
         };
 
-        match asset {
+        return match asset {
             Some(asset) if asset_matches_request(path, &asset.mime_type) => {
-                serve_asset(request, raw, asset, is_head);
+                serve_asset(request, raw, asset, is_head, csp)
             }
             Some(asset) => {
                 warn!(
@@ -181,7 +272,7 @@ fn handle<R: Runtime>(request: tiny_http::Request, app: &AppHandle<R>) {
                     "text/plain; charset=utf-8",
                     "Not Found",
                     is_head,
-                );
+                )
             }
             None => {
                 debug!("localhost GET {} -> missing local asset", raw);
@@ -191,23 +282,20 @@ fn handle<R: Runtime>(request: tiny_http::Request, app: &AppHandle<R>) {
                     "text/plain; charset=utf-8",
                     "Not Found",
                     is_head,
-                );
+                )
             }
-        }
-        return;
+        };
     }
 
     if is_spa_route_path(path) {
-        match resolver.get("/index.html".to_string()) {
-            Some(asset) => {
-                serve_asset(request, raw, asset, is_head);
-            }
+        return match resolver.get("/index.html".to_string()) {
+            Some(asset) => serve_asset(request, raw, asset, is_head, csp),
             None => {
                 warn!("localhost GET {} -> index.html fallback missing", raw);
                 let _ = request.respond(Response::empty(500));
+                (500, 0)
             }
-        }
-        return;
+        };
     }
 
     warn!(
@@ -223,10 +311,27 @@ fn handle<R: Runtime>(request: tiny_http::Request, app: &AppHandle<R>) {
             json_escape(path)
         ),
         is_head,
-    );
+    )
+}
+
+/// Build the CSP header for the current `csp_mode`, if any
+fn csp_header<R: Runtime>(app: &AppHandle<R>) -> Option<Header> {
+    let mode = app.try_state::<SettingsState>()?.get().csp_mode;
+    let name: &[u8] = match mode {
+        CspMode::Off => return None,
+        CspMode::ReportOnly => b"Content-Security-Policy-Report-Only",
+        CspMode::Enforce => b"Content-Security-Policy",
+    };
+    Header::from_bytes(name, INJECTED_CSP.as_bytes()).ok()
 }
 
-fn serve_asset(request: tiny_http::Request, raw: String, asset: Asset, is_head: bool) {
+fn serve_asset(
+    request: tiny_http::Request,
+    raw: String,
+    asset: Asset,
+    is_head: bool,
+    csp: Option<Header>,
+) -> (u16, u64) {
     debug!(
         "localhost GET {} -> {} bytes ({})",
         raw,
@@ -242,19 +347,41 @@ fn serve_asset(request: tiny_http::Request, raw: String, asset: Asset, is_head:
     let content_type = Header::from_bytes(&b"Content-Type"[..], mime_type.as_bytes()).unwrap();
     let cache_control = Header::from_bytes(&b"Cache-Control"[..], &b"no-cache"[..]).unwrap();
     let content_length = asset.bytes.len();
+    // Only documents carry a policy; subresources inherit it from the page.
+    let is_document = mime_type.starts_with("text/html");
+    let csp = csp.filter(|_| is_document);
+    // Documents also hint the game's origins so sockets open early
+    let link_hint = if is_document {
+        Header::from_bytes(&b"Link"[..], preconnect::link_header_value().as_bytes()).ok()
+    } else {
+        None
+    };
 
     if is_head {
-        let response = Response::empty(200)
+        let mut response = Response::empty(200)
             .with_header(content_type)
             .with_header(cache_control)
             .with_data(std::io::empty(), Some(content_length));
+        if let Some(csp) = csp {
+            response.add_header(csp);
+        }
+        if let Some(link_hint) = link_hint {
+            response.add_header(link_hint);
+        }
         let _ = request.respond(response);
     } else {
-        let response = Response::from_data(asset.bytes)
+        let mut response = Response::from_data(asset.bytes)
             .with_header(content_type)
             .with_header(cache_control);
+        if let Some(csp) = csp {
+            response.add_header(csp);
+        }
+        if let Some(link_hint) = link_hint {
+            response.add_header(link_hint);
+        }
         let _ = request.respond(response);
     }
+    (200, if is_head { 0 } else { content_length as u64 })
 }
 
 fn respond_text(
@@ -263,7 +390,7 @@ fn respond_text(
     content_type_value: &str,
     body: &str,
     is_head: bool,
-) {
+) -> (u16, u64) {
     let content_type =
         Header::from_bytes(&b"Content-Type"[..], content_type_value.as_bytes()).unwrap();
     let cache_control = Header::from_bytes(&b"Cache-Control"[..], &b"no-cache"[..]).unwrap();
@@ -279,6 +406,7 @@ fn respond_text(
             .with_header(cache_control);
         let _ = request.respond(response);
     }
+    (status, if is_head { 0 } else { body.len() as u64 })
 }
 
 fn is_spa_route_path(path: &str) -> bool {
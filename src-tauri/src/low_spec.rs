@@ -0,0 +1,59 @@
+//! Low-Spec Mode - Cross-platform
+//!
+//! One switch for old laptops (`set_low_spec_mode`, off by default) that sets
+//! several lighter options at once:
+//! - WebGL antialiasing off (from the next page load)
+//! - frame rate capped at 60
+//! - CSS animations and transitions finished at once
+//! - music muted in the audio mix, sound effects kept
+//!
+//! Each option can still be changed on its own afterwards (`set_graphics`,
+//! `set_audio_mix`). Turning the mode off puts these options back to their
+//! defaults.
+
+use crate::settings::{GraphicsSettings, Settings};
+
+/// Frame rate limit of the preset
+const LOW_SPEC_FPS: u32 = 60;
+
+/// The graphics options of the preset
+pub fn preset() -> GraphicsSettings {
+    GraphicsSettings {
+        antialias: false,
+        fps_cap: LOW_SPEC_FPS,
+        reduce_motion: true,
+    }
+}
+
+/// Apply or undo the preset in `settings`
+pub fn apply(settings: &mut Settings, enabled: bool) {
+    settings.low_spec_mode = enabled;
+    settings.graphics = if enabled {
+        preset()
+    } else {
+        GraphicsSettings::default()
+    };
+    settings.audio_mix.music_muted = enabled;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turning_the_preset_off_restores_defaults() {
+        let mut settings = Settings::default();
+        settings.audio_mix.sfx_muted = true;
+        apply(&mut settings, true);
+        assert!(settings.low_spec_mode);
+        assert_eq!(settings.graphics.fps_cap, 60);
+        assert!(!settings.graphics.antialias);
+        assert!(settings.audio_mix.music_muted);
+
+        apply(&mut settings, false);
+        assert_eq!(settings.graphics, GraphicsSettings::default());
+        assert!(!settings.audio_mix.music_muted);
+        // Not part of the preset
+        assert!(settings.audio_mix.sfx_muted);
+    }
+}
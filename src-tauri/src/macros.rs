@@ -0,0 +1,395 @@
+//! UI Macros - Cross-platform
+//!
+//! Record and replay short menu flows (open the boosters page, open
+//! settings, ...) for players on a Deck or a controller. Recording captures
+//! the route and the clicked menu elements on the page (`record_macro`);
+//! playback is paced from here and sends one step at a time to the page as
+//! `pac-macro-step` events (`play_macro`).
+//!
+//! Macros are for menus only: recording and playback are refused while a
+//! match or a pre-match room is open, playback stops as soon as one opens,
+//! and steps are rate limited. Saved in `macros.json` next to the settings.
+
+use crate::page_state::{GamePhase, PageState};
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info, warn};
+
+const MACROS_FILE_NAME: &str = "macros.json";
+const MAX_MACROS: usize = 20;
+const MAX_STEPS: usize = 40;
+const MAX_NAME_CHARS: usize = 40;
+const MAX_SELECTOR_CHARS: usize = 300;
+const MAX_TEXT_CHARS: usize = 40;
+/// Fastest replay pace, whatever the recording says
+const MIN_STEP_DELAY: Duration = Duration::from_millis(300);
+/// Pauses in a recording are shortened to this
+const MAX_STEP_DELAY: Duration = Duration::from_secs(10);
+/// Time between two playbacks
+const MIN_PLAY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// One recorded action
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MacroAction {
+    /// Go to an SPA route
+    Navigate { route: String },
+    /// Click the element matching `selector`; `text` (the element's label)
+    /// picks among similar elements when the selector no longer matches
+    Click {
+        selector: String,
+        text: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MacroStep {
+    /// Pause before this step
+    pub delay_ms: u64,
+    #[serde(flatten)]
+    pub action: MacroAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UiMacro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+/// Routes a macro may visit: menus, not rooms or matches
+fn is_menu_route(route: &str) -> bool {
+    route.starts_with('/')
+        && !route.starts_with("//")
+        && !route.contains("://")
+        && !GamePhase::from_route(route).is_match_in_progress()
+}
+
+fn validate_action(action: MacroAction) -> Result<MacroAction, String> {
+    match action {
+        MacroAction::Navigate { route } if is_menu_route(&route) => {
+            Ok(MacroAction::Navigate { route })
+        }
+        MacroAction::Navigate { route } => Err(format!("Macros can't navigate to {:?}", route)),
+        MacroAction::Click { selector, text } => {
+            if selector.is_empty() || selector.chars().count() > MAX_SELECTOR_CHARS {
+                return Err("Invalid macro click target".to_string());
+            }
+            let text = text
+                .map(|t| t.trim().chars().take(MAX_TEXT_CHARS).collect::<String>())
+                .filter(|t| !t.is_empty());
+            Ok(MacroAction::Click { selector, text })
+        }
+    }
+}
+
+/// Clamp a recorded pause to the replay limits
+fn clamp_delay(delay: Duration) -> u64 {
+    delay.clamp(MIN_STEP_DELAY, MAX_STEP_DELAY).as_millis() as u64
+}
+
+struct Recording {
+    name: String,
+    steps: Vec<MacroStep>,
+    last_step_at: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    macros: Vec<UiMacro>,
+    recording: Option<Recording>,
+    last_play: Option<Instant>,
+}
+
+/// Managed macro state
+pub struct MacroStore {
+    inner: Mutex<Inner>,
+    playing: AtomicBool,
+    cancel: AtomicBool,
+}
+
+fn macros_path() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join(MACROS_FILE_NAME))
+}
+
+impl MacroStore {
+    pub fn load() -> Self {
+        Self::with_macros(
+            macros_path()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+        )
+    }
+
+    fn with_macros(macros: Vec<UiMacro>) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                macros,
+                ..Default::default()
+            }),
+            playing: AtomicBool::new(false),
+            cancel: AtomicBool::new(false),
+        }
+    }
+
+    fn save(macros: &[UiMacro]) -> Result<(), String> {
+        let path = macros_path().ok_or_else(|| "No config directory available".to_string())?;
+        settings::write_json(&path, &macros)
+    }
+
+    pub fn list(&self) -> Vec<UiMacro> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .macros
+            .clone()
+    }
+
+    /// Start recording from the page's current route
+    pub fn start_recording(&self, name: &str, route: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+            return Err(format!(
+                "Macro name must be 1-{} characters",
+                MAX_NAME_CHARS
+            ));
+        }
+        let first = validate_action(MacroAction::Navigate {
+            route: route.to_string(),
+        })?;
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.recording.is_some() {
+            return Err("A macro is already being recorded".to_string());
+        }
+        inner.recording = Some(Recording {
+            name: name.to_string(),
+            steps: vec![MacroStep {
+                delay_ms: 0,
+                action: first,
+            }],
+            last_step_at: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Add a step reported by the page to the recording
+    pub fn record_step(&self, action: MacroAction) -> Result<(), String> {
+        let action = validate_action(action)?;
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let recording = inner
+            .recording
+            .as_mut()
+            .ok_or_else(|| "No macro is being recorded".to_string())?;
+        if recording.steps.len() >= MAX_STEPS {
+            return Err(format!("Macros are limited to {} steps", MAX_STEPS));
+        }
+        let now = Instant::now();
+        recording.steps.push(MacroStep {
+            delay_ms: clamp_delay(now.duration_since(recording.last_step_at)),
+            action,
+        });
+        recording.last_step_at = now;
+        Ok(())
+    }
+
+    /// Finish the recording and save it, replacing a macro of the same name
+    pub fn stop_recording(&self) -> Result<UiMacro, String> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let recording = inner
+            .recording
+            .take()
+            .ok_or_else(|| "No macro is being recorded".to_string())?;
+        if recording.steps.len() < 2 {
+            return Err("Nothing was recorded".to_string());
+        }
+        let recorded = UiMacro {
+            name: recording.name,
+            steps: recording.steps,
+        };
+        let others = inner
+            .macros
+            .iter()
+            .filter(|m| m.name != recorded.name)
+            .count();
+        if others >= MAX_MACROS {
+            return Err(format!("At most {} macros", MAX_MACROS));
+        }
+        inner.macros.retain(|m| m.name != recorded.name);
+        inner.macros.push(recorded.clone());
+        Self::save(&inner.macros)?;
+        info!(
+            "Macro {:?} recorded ({} steps)",
+            recorded.name,
+            recorded.steps.len()
+        );
+        Ok(recorded)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .recording
+            .is_some()
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let before = inner.macros.len();
+        inner.macros.retain(|m| m.name != name);
+        if inner.macros.len() == before {
+            return Err(format!("No macro named {:?}", name));
+        }
+        Self::save(&inner.macros)
+    }
+
+    /// The macro to play, if playback is allowed right now
+    fn begin_playback(&self, name: &str, now: Instant) -> Result<UiMacro, String> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.recording.is_some() {
+            return Err("Stop recording before playing a macro".to_string());
+        }
+        if self.playing.load(Ordering::SeqCst) {
+            return Err("A macro is already playing".to_string());
+        }
+        if let Some(last) = inner.last_play {
+            if now.duration_since(last) < MIN_PLAY_INTERVAL {
+                return Err("Macros can be played at most every 5 seconds".to_string());
+            }
+        }
+        let found = inner
+            .macros
+            .iter()
+            .find(|m| m.name == name)
+            .cloned()
+            .ok_or_else(|| format!("No macro named {:?}", name))?;
+        inner.last_play = Some(now);
+        self.playing.store(true, Ordering::SeqCst);
+        self.cancel.store(false, Ordering::SeqCst);
+        Ok(found)
+    }
+
+    /// Stop the macro that is playing, if any
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Play a saved macro on the main window
+pub fn play(app: &AppHandle, name: &str) -> Result<(), String> {
+    if app.state::<PageState>().phase().is_match_in_progress() {
+        return Err("Macros can't be played during a match".to_string());
+    }
+    let ui_macro = app
+        .state::<MacroStore>()
+        .begin_playback(name, Instant::now())?;
+    let app = app.clone();
+    std::thread::Builder::new()
+        .name("macro-player".to_string())
+        .spawn(move || {
+            let store = app.state::<MacroStore>();
+            let result = play_steps(&app, &store, &ui_macro);
+            store.playing.store(false, Ordering::SeqCst);
+            match result {
+                Ok(()) => info!("Macro {:?} played", ui_macro.name),
+                Err(e) => warn!("Macro {:?} stopped: {}", ui_macro.name, e),
+            }
+        })
+        .map(|_| ())
+        .map_err(|e| {
+            app.state::<MacroStore>()
+                .playing
+                .store(false, Ordering::SeqCst);
+            format!("Failed to start macro: {}", e)
+        })
+}
+
+fn play_steps(app: &AppHandle, store: &MacroStore, ui_macro: &UiMacro) -> Result<(), String> {
+    for step in &ui_macro.steps {
+        let delay = Duration::from_millis(step.delay_ms).max(MIN_STEP_DELAY);
+        std::thread::sleep(delay);
+        if store.cancel.load(Ordering::SeqCst) {
+            return Err("cancelled".to_string());
+        }
+        if app.state::<PageState>().phase().is_match_in_progress() {
+            return Err("a match started".to_string());
+        }
+        let window = app
+            .get_webview_window("main")
+            .ok_or_else(|| "main window closed".to_string())?;
+        window
+            .emit("pac-macro-step", &step.action)
+            .map_err(|e| e.to_string())?;
+        debug!("Macro step: {:?}", step.action);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click(selector: &str) -> MacroAction {
+        MacroAction::Click {
+            selector: selector.to_string(),
+            text: Some("  Boosters  ".to_string()),
+        }
+    }
+
+    #[test]
+    fn macros_stay_out_of_matches() {
+        assert!(is_menu_route("/lobby"));
+        assert!(is_menu_route("/collection"));
+        assert!(!is_menu_route("/game"));
+        assert!(!is_menu_route("/preparation"));
+        assert!(!is_menu_route("https://example.com/"));
+        assert!(!is_menu_route("//example.com/lobby"));
+    }
+
+    #[test]
+    fn recordings_are_validated_and_paced() {
+        let store = MacroStore::with_macros(Vec::new());
+        assert!(store.start_recording("Boosters", "/game").is_err());
+        store.start_recording("Boosters", "/lobby").unwrap();
+        store.record_step(click("#boosters")).unwrap();
+        assert!(store.record_step(click("")).is_err());
+        let inner = store.inner.lock().unwrap();
+        let steps = &inner.recording.as_ref().unwrap().steps;
+        assert_eq!(steps.len(), 2);
+        // Instant clicks still replay at the minimum pace
+        assert_eq!(steps[1].delay_ms, MIN_STEP_DELAY.as_millis() as u64);
+        assert_eq!(
+            steps[1].action,
+            MacroAction::Click {
+                selector: "#boosters".to_string(),
+                text: Some("Boosters".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn playback_is_rate_limited() {
+        let store = MacroStore::with_macros(vec![UiMacro {
+            name: "Boosters".to_string(),
+            steps: Vec::new(),
+        }]);
+        let now = Instant::now();
+        assert!(store.begin_playback("Boosters", now).is_ok());
+        store.playing.store(false, Ordering::SeqCst);
+        assert!(store
+            .begin_playback("Boosters", now + Duration::from_secs(1))
+            .is_err());
+        assert!(store
+            .begin_playback("Boosters", now + MIN_PLAY_INTERVAL)
+            .is_ok());
+        store.playing.store(false, Ordering::SeqCst);
+        assert!(store
+            .begin_playback("Other", now + MIN_PLAY_INTERVAL * 3)
+            .is_err());
+    }
+}
@@ -6,17 +6,27 @@
 
 mod localhost_server;
 
-use pac_deluxe_lib::{commands, performance, PerformanceMonitor};
+use pac_deluxe_lib::{
+    attention, audio, backup, commands, contrast, customization, dashboard, deep_links,
+    display_watch, elevation_policy, gamma, hdr_control, health, history, http_log, idle_throttle,
+    injection, jank, macros, maintenance, mouse_precision, observers, overlay_stream, page_api,
+    page_events, page_state, performance, planner, power_guard, preconnect, preflight,
+    process_failures, profile, recovery, recovery_console, redact, refresh_rate, reminders, report,
+    resource_limits, safe_mode, scheduler, self_test, server_status, session, settings, shortcuts,
+    soft_reload, suspend, system_keys, tray, tts, updates, white_window, ws_inspector,
+    PerformanceMonitor,
+};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tauri::webview::{NewWindowFeatures, NewWindowResponse};
+use tauri::webview::{NewWindowFeatures, NewWindowResponse, PageLoadEvent};
 use tauri::{
-    AppHandle, Emitter, Listener, Manager, Runtime, Url, WebviewUrl, WebviewWindowBuilder,
-    WindowEvent,
+    AppHandle, Emitter, Listener, Manager, Runtime, Url, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder, WindowEvent,
 };
 use tracing::{debug, info, warn, Level};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
 use tracing_subscriber::FmtSubscriber;
 
 /// Clean up problematic files from old installations (Windows)
@@ -175,6 +185,21 @@ const AUTH_POPUP_BRIDGE_SCRIPT: &str = include_str!("runtime/auth-popup-bridge.j
 /// This is the canonical frontend runtime for PACDeluxe.
 const OVERLAY_SCRIPT: &str = include_str!("runtime/overlay.js");
 
+/// Scripts registered on the main webview, in the order they run. The page
+/// API comes first so the overlay and user scripts can rely on it. The
+/// overlay starts at document start so its fetch proxy is in place before
+/// the game's first request, and waits for the DOM itself for the rest.
+fn runtime_scripts() -> [injection::RuntimeScript; 2] {
+    [
+        page_api::runtime_script(),
+        injection::RuntimeScript {
+            name: "overlay",
+            phase: injection::InjectionPhase::DocumentStart,
+            source: OVERLAY_SCRIPT,
+        },
+    ]
+}
+
 fn build_auth_popup<R: Runtime>(
     app_handle: &AppHandle<R>,
     url: Url,
@@ -328,113 +353,399 @@ fn build_auth_popup<R: Runtime>(
     }
 }
 
+/// Run each installed user script in its own try/catch so one failing
+/// script doesn't stop the others
+fn run_user_scripts<R: Runtime>(webview: &WebviewWindow<R>) {
+    if safe_mode::is_active() {
+        return;
+    }
+    for (name, source) in customization::user_scripts() {
+        let label = serde_json::to_string(&name).unwrap_or_default();
+        let script = format!(
+            "try {{\n{}\n}} catch (e) {{ console.error('[PACDeluxe] User script ' + {} + ' failed:', e); }}",
+            source, label
+        );
+        match webview.eval(&script) {
+            Ok(()) => debug!("Ran user script {}", name),
+            Err(e) => warn!("Failed to run user script {}: {}", name, e),
+        }
+    }
+}
+
+/// Build the main game window and apply native window tuning.
+/// Also used by `recreate_webview` to rebuild it after a renderer failure.
+fn build_main_window(app_handle: &AppHandle, url: Url) -> tauri::Result<WebviewWindow> {
+    // Get version for window title (add Dev suffix in debug builds)
+    let version = app_handle.package_info().version.to_string();
+    let title = if cfg!(debug_assertions) {
+        format!("PACDeluxe v{} (Dev)", version)
+    } else {
+        format!("PACDeluxe v{}", version)
+    };
+
+    // Built directly in the current mode: the startup mode on launch, the
+    // mode at the time when the webview is recreated
+    let mode = commands::WindowMode::current();
+
+    let popup_handle = app_handle.clone();
+    let builder = WebviewWindowBuilder::new(app_handle, "main", WebviewUrl::External(url))
+        .title(&title)
+        .inner_size(1280.0, 900.0)
+        .min_inner_size(1024.0, 768.0)
+        .resizable(true)
+        .center()
+        .fullscreen(mode == commands::WindowMode::Fullscreen)
+        .decorations(mode != commands::WindowMode::BorderlessWindowed)
+        .maximized(mode == commands::WindowMode::BorderlessWindowed)
+        .focused(true)
+        .visible(true)
+        // Required for HTML5 drag & drop to work in WebView2 on Windows
+        // (Tauri's default handler intercepts drag events, blocking tier list maker etc.)
+        .disable_drag_drop_handler()
+        .on_page_load(|webview, payload| {
+            if !matches!(payload.event(), PageLoadEvent::Finished) {
+                return;
+            }
+            white_window::page_loaded();
+            // User-installed scripts run only on the game's own pages (not
+            // the loading or retry screen), once the document has loaded;
+            // unlike the runtime they can change while the app runs, so
+            // they are evaluated per load
+            if health::runs_runtime(payload.url()) {
+                run_user_scripts(&webview);
+            }
+        })
+        // Handle OAuth popup windows (Google/Firebase auth)
+        .on_new_window(move |url, features| build_auth_popup(&popup_handle, url, features));
+    // Feature toggles ahead of the overlay, which reads them at start
+    let flags = app_handle
+        .state::<settings::SettingsState>()
+        .get()
+        .injection_flags;
+    let builder =
+        builder.initialization_script(&injection::config_script("__PAC_INJECTION_FLAGS__", &flags));
+    // The loading and retry screens are not the game; they have no use for
+    // the overlay
+    let window = injection::register(
+        builder,
+        &runtime_scripts(),
+        &[
+            localhost_server::LOADING_PAGE_PATH,
+            localhost_server::UNREACHABLE_PAGE_PATH,
+        ],
+    )
+    .build()?;
+
+    // Apply window optimizations
+    performance::optimize_window(&window);
+
+    // Fullscreen/borderless come back at the right size after display sleep
+    display_watch::install(&window);
+
+    // Ctrl+Shift+R works even when the page's JS is wedged
+    recovery::install_emergency_reload_hotkey(&window);
+
+    // Renderer and GPU process crashes end up in the crash history
+    process_failures::install(&window);
+
+    // The SDR game looks better with HDR off, if the user asked for that
+    hdr_control::install(&window);
+
+    // F11 / Alt+Enter / Shift+F11 work even while an input has focus
+    shortcuts::install(&window);
+    system_keys::set_window(&window);
+    mouse_precision::install(&window);
+    gamma::install(&window);
+    idle_throttle::install(&window);
+    planner::install(&window);
+    profile::install(&window);
+
+    Ok(window)
+}
+
 fn main() {
-    // Initialize logging
+    // Initialize logging. Output goes through the redacting writer so auth
+    // callback URLs, tokens, and emails never reach the console or log files.
+    // A plain-text copy goes to the log file the recovery console shows.
+    let log_file: std::sync::Mutex<Box<dyn std::io::Write + Send>> =
+        std::sync::Mutex::new(match recovery_console::open_log_file() {
+            Some(file) => Box::new(file),
+            None => Box::new(std::io::sink()),
+        });
     let subscriber = FmtSubscriber::builder()
         .with_max_level(if cfg!(debug_assertions) {
             Level::DEBUG
         } else {
             Level::INFO
         })
+        .with_ansi(false)
+        .with_writer(redact::RedactingMakeWriter::new(
+            std::io::stdout.and(log_file),
+        ))
         .finish();
     tracing::subscriber::set_global_default(subscriber).ok();
 
+    // The console replaces the app, webview and all
+    if recovery_console::requested() {
+        info!("Opening the recovery console");
+        recovery_console::run();
+        return;
+    }
+
     info!("Starting PACDeluxe");
 
+    // A Task Scheduler launch while PACDeluxe is already open: the running
+    // instance shows the reminder itself
+    if scheduler::launched_by_schedule() && scheduler::another_instance_running() {
+        info!("Already running; scheduled launch not needed");
+        return;
+    }
+
+    // A restore staged by the last run replaces the files it covers
+    backup::apply_pending_restore();
+
+    // Load user settings before anything that depends on them
+    let user_settings = settings::load();
+
+    // Before WebView2 is configured: after repeated crashes it starts
+    // without browser flags
+    let safe_mode_status = safe_mode::begin();
+
     // The localhost server is started inside setup() so the main window only
     // opens after we have a real bound port and a live asset server.
 
-    // Set WebView2 Chromium flags for real GPU performance gains
-    // Must be set before any WebView2 instance is created
+    // Set WebView2 Chromium flags for real GPU performance gains (plus the
+    // user's networking preset). Must be set before any WebView2 instance
+    // is created.
+    // After a white window, fewer of them (see white_window.rs).
     #[cfg(target_os = "windows")]
     {
+        let flag_level = white_window::begin();
+        let browser_args = if safe_mode_status.active {
+            info!("Safe mode: WebView2 starts without our browser arguments");
+            String::new()
+        } else {
+            pac_deluxe_lib::browser_flags::activate(
+                user_settings.networking_preset,
+                user_settings.high_precision_mouse,
+                user_settings.vulkan_rendering,
+            )
+        };
+        let browser_args = white_window::browser_args(flag_level, &browser_args);
         // SAFETY: called at startup before any threads spawn, single-threaded context
         unsafe {
-            std::env::set_var(
-                "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS",
-                "--enable-gpu-rasterization --enable-zero-copy --disable-background-timer-throttling --disable-renderer-backgrounding",
-            );
+            if browser_args.is_empty() {
+                std::env::remove_var(pac_deluxe_lib::browser_flags::BROWSER_ARGS_ENV);
+            } else {
+                info!("WebView2 browser arguments: {}", browser_args);
+                std::env::set_var(
+                    pac_deluxe_lib::browser_flags::BROWSER_ARGS_ENV,
+                    &browser_args,
+                );
+            }
         }
     }
 
     // Clean up any problematic files from old installations
     cleanup_old_installation();
 
-    // Apply system optimizations
+    // Apply system optimizations (the elevation policy first: the WebView2
+    // optimizer starts here)
+    elevation_policy::set(user_settings.elevation_policy.clone());
+    // A memory cap set too low would crash the page again in safe mode
+    resource_limits::install(if safe_mode_status.active {
+        settings::ResourceLimitSettings::default()
+    } else {
+        user_settings.resource_limits
+    });
     performance::apply_system_optimizations();
-
-    tauri::Builder::default()
+    preflight::spawn_first_run_check();
+
+    // Links opened while the app runs go to the running instance. Other
+    // profiles stay free to run alongside it.
+    let builder = tauri::Builder::default();
+    let builder = if profile::active() == profile::DEFAULT_PROFILE {
+        builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+            tray::show_main_window(app);
+        }))
+    } else {
+        builder
+    };
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
         .setup(move |app| {
-            let app_handle = app.handle().clone();
+            // Settings must be managed before the localhost server starts
+            // serving documents (it reads the CSP mode per request).
+            // The request log is read by the localhost server too
+            app.manage(http_log::HttpLog::new(user_settings.http_logging));
+            // The main window is built in this mode
+            let start_mode = user_settings
+                .start_mode
+                .resolve(user_settings.last_window_mode);
+            info!("Starting in {:?} mode", start_mode);
+            start_mode.set_startup();
+            system_keys::on_window_mode(start_mode);
+            system_keys::set_enabled(user_settings.suppress_system_keys);
+            mouse_precision::set_enabled(user_settings.high_precision_mouse);
+            if let Err(e) = gamma::apply(user_settings.gamma) {
+                warn!("Saved gamma not applied: {}", e);
+            }
+            app.manage(settings::SettingsState(std::sync::Mutex::new(
+                user_settings,
+            )));
+            // Window geometry, zoom, volume and overlay layout are per profile
+            info!("Profile: {}", profile::active());
+            app.manage(profile::ProfileState(
+                std::sync::Mutex::new(profile::load()),
+            ));
+
+            // Warm DNS for the game's hosts while the window is being set up
+            preconnect::spawn_dns_prefetch();
 
             // Start our static HTTP server before creating the main window so
             // the first navigation always targets a live localhost origin.
             // Firebase accepts `localhost` (any port) as an authorized origin,
             // but not Tauri's default `tauri.localhost` origin.
-            let localhost_port = localhost_server::spawn(app_handle.clone()).map_err(|e| {
+            let localhost_port = localhost_server::spawn(app.handle().clone()).map_err(|e| {
                 std::io::Error::other(format!("Failed to start localhost server: {}", e))
             })?;
             info!(
                 "Frontend runtime bound to http://localhost:{}/",
                 localhost_port
             );
+            dashboard::set_local_port(localhost_port);
 
-            // Get version for window title (add Dev suffix in debug builds)
-            let version = app.package_info().version.to_string();
-            let title = if cfg!(debug_assertions) {
-                format!("PACDeluxe v{} (Dev)", version)
-            } else {
-                format!("PACDeluxe v{}", version)
-            };
-
-            // Create window programmatically with on_page_load handler.
+            // Create window programmatically with the runtime scripts.
             // Load from the localhost plugin's HTTP server rather than the
             // custom tauri:// scheme so that Firebase accepts the origin
             // for OAuth popup sign-in.
-            let main_url = format!("http://localhost:{}/", localhost_port)
+            //
+            // The window opens on a loading page while the game server is
+            // probed in the background; if it can't be reached, the local
+            // retry screen is shown instead of a blank WebView2 error page.
+            app.manage(health::UpstreamStatus::default());
+            app.manage(maintenance::UpstreamState::default());
+            let local_url =
+                move |path: &str| format!("http://localhost:{}{}", localhost_port, path);
+            let loading_url = local_url(localhost_server::LOADING_PAGE_PATH)
                 .parse()
-                .expect("localhost URL must parse");
-            let window = WebviewWindowBuilder::new(app, "main", WebviewUrl::External(main_url))
-                .title(&title)
-                .inner_size(1280.0, 900.0)
-                .min_inner_size(1024.0, 768.0)
-                .resizable(true)
-                .center()
-                .focused(true)
-                .visible(true)
-                // Required for HTML5 drag & drop to work in WebView2 on Windows
-                // (Tauri's default handler intercepts drag events, blocking tier list maker etc.)
-                .disable_drag_drop_handler()
-                .on_page_load(|webview, _payload| {
-                    if let Err(e) = webview.eval(OVERLAY_SCRIPT) {
-                        tracing::warn!("Failed to inject overlay script: {}", e);
-                    } else {
-                        tracing::debug!("Overlay script injected successfully");
+                .expect("loading URL must parse");
+            build_main_window(app.handle(), loading_url).expect("Failed to create main window");
+            let mirrors = app
+                .state::<settings::SettingsState>()
+                .get()
+                .upstream_mirrors;
+            let probe_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let probe = health::probe_upstream(&mirrors).await;
+                let upstream = probe_handle.state::<health::UpstreamStatus>();
+                let main_url = if probe.reachable {
+                    local_url("/")
+                } else if let Some(mirror) = probe.fallback.clone() {
+                    // A mirror serves its own frontend; load it directly
+                    upstream.set_mirror(Some(mirror.clone()));
+                    mirror
+                } else {
+                    local_url(localhost_server::UNREACHABLE_PAGE_PATH)
+                };
+                upstream.set(probe);
+                let Some(window) = probe_handle.get_webview_window("main") else {
+                    return;
+                };
+                match main_url.parse() {
+                    Ok(url) => {
+                        if let Err(e) = window.navigate(url) {
+                            warn!("Failed to open the game: {}", e);
+                        }
                     }
-                })
-                // Handle OAuth popup windows (Google/Firebase auth)
-                .on_new_window(move |url, features| build_auth_popup(&app_handle, url, features))
-                .build()
-                .expect("Failed to create main window");
+                    Err(e) => warn!("Invalid game URL {}: {}", main_url, e),
+                }
+            });
 
-            // Apply window optimizations
-            performance::optimize_window(&window);
+            // recreate_webview rebuilds the window through the same path
+            app.manage(recovery::MainWindowFactory(Box::new(
+                |app: &AppHandle, url: Url| build_main_window(app, url).map_err(|e| e.to_string()),
+            )));
 
-            // Start performance monitor
+            // Tray icon shows a badge while the game server has problems
+            if let Err(e) = tray::install(app.handle()) {
+                warn!("Tray icon unavailable: {}", e);
+            }
+
+            // Reload the page if its heartbeat stops while visible
+            app.manage(recovery::FreezeWatchdog::default());
+            recovery::spawn_freeze_watchdog(app.handle().clone());
+            white_window::spawn_watch(app.handle().clone());
+
+            // Start performance monitor; GPU counters come up in the background
+            performance::spawn_gpu_monitor_init();
             let monitor = PerformanceMonitor::new();
             app.manage(monitor);
+            performance::spawn_snapshot_sampler(app.handle().clone());
+
+            // Sample stats and page metrics into the rolling history
+            app.manage(history::PerformanceHistory::default());
+            app.manage(page_state::PageState::default());
+            app.manage(soft_reload::SoftReloadState::default());
+            app.manage(jank::JankTracker::default());
+            app.manage(observers::ObserverTracker::default());
+
+            // Push overlay stats while the overlay is shown
+            let stats_interval_ms = app
+                .state::<settings::SettingsState>()
+                .get()
+                .overlay
+                .stats_interval_ms;
+            app.manage(overlay_stream::OverlayStream::new(stats_interval_ms));
+            overlay_stream::spawn(app.handle().clone());
+
+            // Play time tracking and break reminders
+            app.manage(session::SessionStore::load());
+            session::spawn_tracker(app.handle().clone());
+            scheduler::spawn(app.handle().clone());
+            app.manage(reminders::ReminderStore::load());
+            reminders::spawn(app.handle().clone());
+            deep_links::install(app.handle());
+            report::install(app.handle());
+            if safe_mode_status.active {
+                safe_mode::show_dialog(app.handle(), safe_mode_status);
+            }
+            safe_mode::spawn_stability_timer();
+            attention::install(app.handle());
+            tts::install(app.handle());
+            page_events::install(app.handle());
+            let idle = app.state::<settings::SettingsState>().get().idle_throttle;
+            idle_throttle::spawn(app.handle().clone(), idle);
+            let dynamic_refresh = app.state::<settings::SettingsState>().get().dynamic_refresh;
+            refresh_rate::spawn(app.handle().clone(), dynamic_refresh);
+            power_guard::spawn(app.handle().clone());
+            suspend::spawn(app.handle().clone());
+            app.manage(macros::MacroStore::load());
+
+            // Debug WebSocket statistics, if left on
+            app.manage(ws_inspector::WsInspector::default());
+            ws_inspector::restore(app.handle());
+
+            // Name the game's audio session in the volume mixer
+            audio::spawn_watcher(app.handle().clone());
+            history::spawn_sampler(app.handle().clone());
 
             // Initialize pending update state for updater
             app.manage(commands::PendingUpdate(std::sync::Mutex::new(None)));
+            updates::spawn(app.handle().clone());
+            server_status::spawn(app.handle().clone());
+            contrast::spawn(app.handle().clone());
+            backup::spawn(app.handle().clone());
 
             // Global listener: when the main-window mock popup calls
             // popup.close() (Firebase SDK does this after auth), it emits
             // pac-close-auth-popup. Close every open auth-popup-* window.
-            // Clone a fresh handle from `app` - the earlier `app_handle` was
-            // already moved into the on_new_window closure.
             let app_for_close_listener = app.handle().clone();
             app.listen_any("pac-close-auth-popup", move |_event| {
                 let windows = app_for_close_listener.webview_windows();
@@ -492,18 +803,157 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_performance_stats,
+            commands::get_performance_snapshot,
             commands::get_system_info,
             commands::toggle_fullscreen,
             commands::get_webview_telemetry,
             commands::get_gpu_stats,
             commands::get_hdr_status,
+            commands::get_performance_advice,
+            commands::get_performance_history,
+            commands::report_long_tasks,
+            commands::get_jank_stats,
+            commands::report_observer_stats,
+            commands::get_observer_stats,
+            commands::get_script_budget,
+            commands::set_overlay_stream,
+            commands::get_health,
+            commands::probe_upstream,
+            commands::get_upstream_status,
+            commands::get_upstream_issue,
+            commands::set_upstream_mirrors,
+            commands::set_networking_preset,
+            commands::set_vulkan_rendering,
+            commands::report_network_benchmark,
+            commands::get_network_benchmarks,
+            commands::get_effective_flags,
+            commands::set_http_logging,
+            commands::get_http_log,
+            commands::set_offline_mode,
+            commands::get_offline_mode,
+            commands::copy_to_clipboard,
+            commands::read_clipboard,
+            commands::copy_room_link,
+            commands::take_pending_join,
+            commands::get_customizations,
+            commands::set_custom_background,
+            commands::set_custom_theme,
+            commands::set_theme_fonts,
+            commands::get_contrast,
+            commands::set_contrast_mode,
+            commands::install_user_script,
+            commands::clear_customization,
+            commands::get_devtools_metrics,
+            commands::set_network_conditions,
+            commands::capture_heap_snapshot,
+            commands::reload_page,
+            commands::recreate_webview,
+            commands::heartbeat,
+            commands::set_freeze_action,
+            commands::capture_screenshot,
             commands::set_window_mode,
+            commands::set_start_mode,
+            commands::set_suppress_system_keys,
+            commands::set_high_precision_mouse,
+            commands::set_misdrop_guard,
+            commands::set_turbo_guard,
+            commands::set_attention_alerts,
+            commands::set_power_profile,
+            commands::set_power_guard,
+            commands::get_power_emergency,
+            commands::get_audio_session_volume,
+            commands::set_audio_session_volume,
+            commands::set_audio_mix,
+            commands::set_tts,
+            commands::set_cursor,
+            commands::set_graphics,
+            commands::set_low_spec_mode,
+            commands::backup_user_data,
+            commands::list_backups,
+            commands::restore_user_data,
+            commands::set_backups,
+            commands::set_injection_flags,
+            commands::reset_settings,
+            commands::set_skip_intro,
+            commands::get_profile,
+            commands::set_zoom,
+            commands::set_overlay_state,
+            commands::set_scheduled_tasks,
+            commands::add_reminder,
+            commands::add_reminder_link,
+            commands::list_reminders,
+            commands::remove_reminder,
+            commands::open_dashboard,
+            commands::open_planner,
+            commands::pin_planner,
+            commands::list_team_comps,
+            commands::save_team_comp,
+            commands::delete_team_comp,
+            commands::export_team_comp,
+            commands::import_team_comp,
+            commands::render_report,
+            commands::set_ws_inspector,
+            commands::get_ws_stats,
+            commands::record_macro,
+            commands::stop_macro_recording,
+            commands::report_macro_step,
+            commands::play_macro,
+            commands::stop_macro,
+            commands::list_macros,
+            commands::delete_macro,
+            commands::get_key_bindings,
+            commands::set_key_bindings,
             commands::get_window_mode,
             commands::proxy_http_request,
             commands::check_for_updates,
+            commands::get_available_update,
+            commands::get_update_state,
+            commands::set_update_checks,
             commands::install_update,
             commands::restart_app,
+            commands::get_settings,
+            commands::set_csp_mode,
+            commands::log_page_event,
+            commands::report_page_state,
+            commands::get_page_state,
+            commands::set_soft_reload,
+            commands::set_overlay_config,
+            commands::get_play_time,
+            commands::set_break_reminder,
+            commands::cancel_soft_reload,
+            commands::get_safe_mode,
+            commands::get_crash_history,
+            commands::run_self_test,
+            commands::simulate_load,
+            commands::set_windows_hdr,
+            commands::set_disable_hdr_in_game,
+            commands::set_gamma,
+            commands::set_color_temperature,
+            commands::apply_gamma_preset,
+            commands::set_elevation_policy,
+            commands::set_resource_limits,
+            commands::set_idle_throttle,
+            commands::get_refresh_rate,
+            commands::set_dynamic_refresh,
+            commands::get_server_status,
+            commands::set_server_status,
+            commands::get_quick_reference,
+            commands::set_quick_reference,
         ])
-        .run(tauri::generate_context!())
-        .expect("Failed to run application");
+        .build(tauri::generate_context!())
+        .expect("Failed to build application")
+        .run(|app, event| match event {
+            // Closing the main window during recreate_webview is not a quit
+            tauri::RunEvent::ExitRequested {
+                api, code: None, ..
+            } if recovery::is_recreating_webview() => {
+                api.prevent_exit();
+            }
+            tauri::RunEvent::Exit => {
+                safe_mode::finish();
+                performance::cancel_gpu_monitor_init();
+                pac_deluxe_lib::restore_system_state(app);
+            }
+            _ => {}
+        });
 }
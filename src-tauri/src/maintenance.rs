@@ -0,0 +1,127 @@
+//! Upstream Status - Cross-platform
+//!
+//! Recognizes when pokemon-auto-chess.com itself is down for maintenance or
+//! sitting behind a Cloudflare challenge, from the status code and headers
+//! of proxied responses (bodies are never inspected). Transitions are sent
+//! to the page as `pac-upstream-status`, where the overlay shows a banner
+//! with a retry countdown, and the tray icon gets a yellow badge, so users
+//! know the problem is upstream rather than in the client.
+
+use crate::tray;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+use ts_rs::TS;
+
+/// What is wrong upstream
+#[derive(Debug, Clone, Copy, Serialize, TS, PartialEq, Eq)]
+pub enum UpstreamIssueKind {
+    /// 503: the server says it is unavailable (usually planned maintenance)
+    Maintenance,
+    /// Bad gateway / timeout / Cloudflare origin errors
+    Outage,
+    /// Cloudflare is asking for a browser challenge
+    Challenge,
+}
+
+/// Payload of the `pac-upstream-status` event (null when the issue clears)
+#[derive(Debug, Clone, Serialize, TS, PartialEq, Eq)]
+pub struct UpstreamIssue {
+    pub kind: UpstreamIssueKind,
+    pub status: u16,
+    /// From the `Retry-After` header, when it holds a number of seconds
+    #[ts(type = "number | null")]
+    pub retry_after_secs: Option<u64>,
+}
+
+/// Classify a response from the game server
+pub fn classify(status: u16, headers: &HeaderMap) -> Option<UpstreamIssue> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let kind = if header("cf-mitigated").is_some_and(|v| v.eq_ignore_ascii_case("challenge")) {
+        UpstreamIssueKind::Challenge
+    } else if status == 503 {
+        UpstreamIssueKind::Maintenance
+    } else if matches!(status, 502 | 504 | 520..=530) {
+        UpstreamIssueKind::Outage
+    } else {
+        return None;
+    };
+
+    Some(UpstreamIssue {
+        kind,
+        status,
+        retry_after_secs: header("retry-after").and_then(|v| v.trim().parse().ok()),
+    })
+}
+
+/// Managed upstream status
+#[derive(Default)]
+pub struct UpstreamState(Mutex<Option<UpstreamIssue>>);
+
+impl UpstreamState {
+    pub fn current(&self) -> Option<UpstreamIssue> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Record the classification of a server response; notifies the page and
+/// the tray only when the status changes.
+pub fn report(app: &AppHandle, issue: Option<UpstreamIssue>) {
+    let state = app.state::<UpstreamState>();
+    {
+        let mut current = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        let kind = |issue: &Option<UpstreamIssue>| issue.as_ref().map(|i| i.kind);
+        if kind(&current) == kind(&issue) {
+            return;
+        }
+        *current = issue.clone();
+    }
+
+    match &issue {
+        Some(issue) => warn!(
+            "Game server issue: {:?} (HTTP {}, retry after {:?}s)",
+            issue.kind, issue.status, issue.retry_after_secs
+        ),
+        None => info!("Game server issue cleared"),
+    }
+    tray::set_upstream_badge(app, issue.is_some());
+    let _ = app.emit("pac-upstream-status", issue);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn ok_responses_are_not_issues() {
+        assert_eq!(classify(200, &HeaderMap::new()), None);
+        assert_eq!(classify(404, &HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn maintenance_reads_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("120"));
+        let issue = classify(503, &headers).unwrap();
+        assert_eq!(issue.kind, UpstreamIssueKind::Maintenance);
+        assert_eq!(issue.retry_after_secs, Some(120));
+    }
+
+    #[test]
+    fn cloudflare_challenge_is_detected() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cf-mitigated", HeaderValue::from_static("challenge"));
+        assert_eq!(
+            classify(403, &headers).map(|i| i.kind),
+            Some(UpstreamIssueKind::Challenge)
+        );
+        assert_eq!(
+            classify(522, &HeaderMap::new()).map(|i| i.kind),
+            Some(UpstreamIssueKind::Outage)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! High-Precision Mouse - Windows
+//!
+//! Optional (`high_precision_mouse`, off by default), for players who feel
+//! lag or drift dragging units. WebView2 has no pointer smoothing setting of
+//! its own: positions arrive already shaped by Windows' "Enhance pointer
+//! precision" acceleration. While the main window has focus, acceleration
+//! is turned off, the way games reading raw input bypass it, and the user's
+//! setting is put back when focus leaves or the app exits. The change is
+//! never written to the user profile.
+//!
+//! The option also requests the `PointerRawUpdate` Blink feature (see
+//! `browser_flags`); the injected runtime uses those events for its
+//! mouse-to-frame latency stat.
+//!
+//! Linux: not available (acceleration belongs to the compositor).
+
+#[cfg(target_os = "windows")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+use tauri::{WebviewWindow, WindowEvent};
+use tracing::debug;
+#[cfg(target_os = "windows")]
+use tracing::{info, warn};
+
+/// SPI_GETMOUSE parameters with acceleration off, or `None` if it already
+/// is. The parameters are [threshold 1, threshold 2, acceleration].
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn without_acceleration(params: [i32; 3]) -> Option<[i32; 3]> {
+    (params[2] != 0).then_some([0, 0, 0])
+}
+
+#[cfg(target_os = "windows")]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+#[cfg(target_os = "windows")]
+static FOCUSED: AtomicBool = AtomicBool::new(false);
+/// The user's mouse parameters while ours are applied
+#[cfg(target_os = "windows")]
+static SAVED_PARAMS: Mutex<Option<[i32; 3]>> = Mutex::new(None);
+
+#[cfg(target_os = "windows")]
+fn mouse_params(action: u32, params: &mut [i32; 3]) -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SYSTEM_PARAMETERS_INFO_ACTION, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+    unsafe {
+        SystemParametersInfoW(
+            SYSTEM_PARAMETERS_INFO_ACTION(action),
+            0,
+            Some(params.as_mut_ptr().cast()),
+            // Not persisted to the profile
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+        .is_ok()
+    }
+}
+
+/// Apply or undo our parameters to match the enabled/focused state
+#[cfg(target_os = "windows")]
+fn update() {
+    const SPI_GETMOUSE: u32 = 0x0003;
+    const SPI_SETMOUSE: u32 = 0x0004;
+
+    let wanted = ENABLED.load(Ordering::SeqCst) && FOCUSED.load(Ordering::SeqCst);
+    let mut saved = SAVED_PARAMS.lock().unwrap_or_else(|e| e.into_inner());
+    match (wanted, *saved) {
+        (true, None) => {
+            let mut params = [0i32; 3];
+            if !mouse_params(SPI_GETMOUSE, &mut params) {
+                warn!("Failed to read mouse acceleration settings");
+                return;
+            }
+            let Some(mut raw) = without_acceleration(params) else {
+                return;
+            };
+            if mouse_params(SPI_SETMOUSE, &mut raw) {
+                *saved = Some(params);
+                debug!("Mouse acceleration off while focused");
+            }
+        }
+        (false, Some(mut original)) => {
+            if !mouse_params(SPI_SETMOUSE, &mut original) {
+                warn!("Failed to restore mouse acceleration");
+            }
+            *saved = None;
+        }
+        _ => {}
+    }
+}
+
+/// Turn high-precision mode on or off
+#[cfg(target_os = "windows")]
+pub fn set_enabled(enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::SeqCst) != enabled {
+        info!(
+            "High-precision mouse {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+        update();
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn on_focus(focused: bool) {
+    FOCUSED.store(focused, Ordering::SeqCst);
+    update();
+}
+
+/// Put the user's mouse settings back (on exit)
+#[cfg(target_os = "windows")]
+pub fn restore() {
+    ENABLED.store(false, Ordering::SeqCst);
+    update();
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_enabled(enabled: bool) {
+    if enabled {
+        debug!("Mouse acceleration control is not available on Linux");
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn on_focus(_focused: bool) {}
+
+#[cfg(target_os = "linux")]
+pub fn restore() {}
+
+/// Follow the main window's focus
+pub fn install(window: &WebviewWindow) {
+    window.on_window_event(|event| {
+        if let WindowEvent::Focused(focused) = event {
+            on_focus(*focused);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acceleration_is_turned_off_once() {
+        // Windows default: thresholds 6/10, acceleration on
+        assert_eq!(without_acceleration([6, 10, 1]), Some([0, 0, 0]));
+        assert_eq!(without_acceleration([0, 0, 0]), None);
+    }
+}
@@ -0,0 +1,277 @@
+//! Page Watcher Stats - Cross-platform
+//!
+//! The injected runtime keeps a few intervals and DOM observers running to
+//! notice game UI it attaches to (the booster page, the lobby room list,
+//! the session checker, ...). They go through one lifecycle helper so each
+//! stops once its target has been found or left, and the helper reports
+//! what is running and how much main-thread time it took. The last report
+//! is kept here for `get_observer_stats`, to tell which watcher is to
+//! blame when a long session gets heavier.
+//!
+//! The same report carries how long each runtime block (`// === NAME ===`
+//! in overlay.js) took to set up, and the page's uptime. `get_script_budget`
+//! adds the watchers and listeners each block started to that, so the
+//! dashboard can show whether the wrapper itself is eating frame time.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Instant;
+use ts_rs::TS;
+
+/// A page report with more entries than this is truncated
+const MAX_WATCHERS: usize = 64;
+const MAX_MODULES: usize = 64;
+/// Watchers the page couldn't attribute to a block
+const UNATTRIBUTED: &str = "runtime";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherKind {
+    Interval,
+    Observer,
+    /// Route change and `pac-events` handlers
+    Listener,
+}
+
+/// One watcher as reported by the page
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WatcherStats {
+    pub name: String,
+    pub kind: WatcherKind,
+    /// Runtime block that started it
+    #[serde(default)]
+    pub module: Option<String>,
+    /// Still running; stopped watchers stay listed with their totals
+    pub active: bool,
+    /// Callback runs since the page loaded
+    #[ts(type = "number")]
+    pub runs: u64,
+    /// Main-thread time spent in those runs
+    pub busy_ms: f64,
+}
+
+/// `get_observer_stats` result
+#[derive(Debug, Clone, Serialize, TS, Default)]
+pub struct ObserverStats {
+    /// Busiest first
+    pub watchers: Vec<WatcherStats>,
+    pub active: usize,
+    pub busy_ms: f64,
+    /// Seconds since the page reported; `None` before the first report
+    #[ts(type = "number | null")]
+    pub age_secs: Option<u64>,
+}
+
+/// Setup time of one runtime block, as reported by the page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleInit {
+    pub name: String,
+    pub init_ms: f64,
+}
+
+/// One runtime block in `get_script_budget`
+#[derive(Debug, Clone, Serialize, TS, PartialEq)]
+pub struct ModuleCost {
+    pub name: String,
+    /// Main-thread time of its setup
+    pub init_ms: f64,
+    /// Main-thread time of its watchers and listeners since then
+    pub steady_ms: f64,
+    /// Callback runs of those
+    #[ts(type = "number")]
+    pub runs: u64,
+}
+
+/// `get_script_budget` result
+#[derive(Debug, Clone, Serialize, TS, Default)]
+pub struct ScriptBudget {
+    /// Costliest first
+    pub modules: Vec<ModuleCost>,
+    pub init_ms: f64,
+    pub steady_ms: f64,
+    /// Steady-state time per minute the page has been open
+    pub steady_ms_per_min: Option<f64>,
+    /// Seconds since the page reported; `None` before the first report
+    #[ts(type = "number | null")]
+    pub age_secs: Option<u64>,
+}
+
+struct Report {
+    at: Instant,
+    watchers: Vec<WatcherStats>,
+    modules: Vec<ModuleInit>,
+    uptime_ms: Option<f64>,
+}
+
+/// Managed last report
+#[derive(Default)]
+pub struct ObserverTracker(Mutex<Option<Report>>);
+
+impl ObserverTracker {
+    pub fn record(
+        &self,
+        mut watchers: Vec<WatcherStats>,
+        mut modules: Vec<ModuleInit>,
+        uptime_ms: Option<f64>,
+    ) {
+        watchers.truncate(MAX_WATCHERS);
+        modules.truncate(MAX_MODULES);
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(Report {
+            at: Instant::now(),
+            watchers,
+            modules,
+            uptime_ms,
+        });
+    }
+
+    pub fn stats(&self) -> ObserverStats {
+        let report = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(report) = report.as_ref() else {
+            return ObserverStats::default();
+        };
+        let mut stats = summarize(report.watchers.clone());
+        stats.age_secs = Some(report.at.elapsed().as_secs());
+        stats
+    }
+
+    pub fn budget(&self) -> ScriptBudget {
+        let report = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(report) = report.as_ref() else {
+            return ScriptBudget::default();
+        };
+        let mut budget = script_budget(&report.modules, &report.watchers, report.uptime_ms);
+        budget.age_secs = Some(report.at.elapsed().as_secs());
+        budget
+    }
+}
+
+fn summarize(mut watchers: Vec<WatcherStats>) -> ObserverStats {
+    watchers.sort_by(|a, b| b.busy_ms.total_cmp(&a.busy_ms));
+    ObserverStats {
+        active: watchers.iter().filter(|w| w.active).count(),
+        busy_ms: watchers.iter().map(|w| w.busy_ms).sum(),
+        watchers,
+        age_secs: None,
+    }
+}
+
+/// Charge each watcher to the block that started it
+fn script_budget(
+    modules: &[ModuleInit],
+    watchers: &[WatcherStats],
+    uptime_ms: Option<f64>,
+) -> ScriptBudget {
+    fn entry<'a>(costs: &'a mut Vec<ModuleCost>, name: &str) -> &'a mut ModuleCost {
+        let index = match costs.iter().position(|cost| cost.name == name) {
+            Some(index) => index,
+            None => {
+                costs.push(ModuleCost {
+                    name: name.to_string(),
+                    init_ms: 0.0,
+                    steady_ms: 0.0,
+                    runs: 0,
+                });
+                costs.len() - 1
+            }
+        };
+        &mut costs[index]
+    }
+    let mut costs: Vec<ModuleCost> = Vec::new();
+    for module in modules {
+        entry(&mut costs, &module.name).init_ms += module.init_ms;
+    }
+    for watcher in watchers {
+        let cost = entry(
+            &mut costs,
+            watcher.module.as_deref().unwrap_or(UNATTRIBUTED),
+        );
+        cost.steady_ms += watcher.busy_ms;
+        cost.runs += watcher.runs;
+    }
+    costs.sort_by(|a, b| (b.init_ms + b.steady_ms).total_cmp(&(a.init_ms + a.steady_ms)));
+
+    let steady_ms = costs.iter().map(|cost| cost.steady_ms).sum();
+    ScriptBudget {
+        init_ms: costs.iter().map(|cost| cost.init_ms).sum(),
+        steady_ms,
+        steady_ms_per_min: uptime_ms
+            .filter(|uptime| *uptime >= 1000.0)
+            .map(|uptime| steady_ms / (uptime / 60_000.0)),
+        modules: costs,
+        age_secs: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watcher(name: &str, active: bool, busy_ms: f64) -> WatcherStats {
+        WatcherStats {
+            name: name.to_string(),
+            kind: WatcherKind::Interval,
+            module: None,
+            active,
+            runs: 10,
+            busy_ms,
+        }
+    }
+
+    #[test]
+    fn busiest_watchers_come_first() {
+        let stats = summarize(vec![
+            watcher("heartbeat", true, 2.0),
+            watcher("flip-all", false, 40.0),
+            watcher("carousel", true, 8.0),
+        ]);
+        let names: Vec<_> = stats.watchers.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["flip-all", "carousel", "heartbeat"]);
+        assert_eq!(stats.active, 2);
+        assert_eq!(stats.busy_ms, 50.0);
+    }
+
+    #[test]
+    fn nothing_is_reported_before_the_page_does() {
+        let tracker = ObserverTracker::default();
+        assert_eq!(tracker.stats().age_secs, None);
+        tracker.record(
+            (0..100)
+                .map(|i| watcher(&i.to_string(), true, 1.0))
+                .collect(),
+            Vec::new(),
+            None,
+        );
+        let stats = tracker.stats();
+        assert_eq!(stats.watchers.len(), MAX_WATCHERS);
+        assert_eq!(stats.age_secs, Some(0));
+    }
+
+    #[test]
+    fn watchers_are_charged_to_the_block_that_started_them() {
+        let modules = [
+            ModuleInit {
+                name: "booster-fix".to_string(),
+                init_ms: 0.5,
+            },
+            ModuleInit {
+                name: "audio-mix".to_string(),
+                init_ms: 3.0,
+            },
+        ];
+        let mut flip_all = watcher("flip-all", true, 6.0);
+        flip_all.module = Some("booster-fix".to_string());
+        let budget = script_budget(
+            &modules,
+            &[flip_all, watcher("heartbeat", true, 1.0)],
+            Some(120_000.0),
+        );
+        let names: Vec<_> = budget.modules.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["booster-fix", "audio-mix", UNATTRIBUTED]);
+        assert_eq!(budget.modules[0].steady_ms, 6.0);
+        assert_eq!(budget.modules[0].runs, 10);
+        assert_eq!(budget.init_ms, 3.5);
+        assert_eq!(budget.steady_ms, 7.0);
+        assert_eq!(budget.steady_ms_per_min, Some(3.5));
+        assert_eq!(script_budget(&modules, &[], None).steady_ms_per_min, None);
+    }
+}
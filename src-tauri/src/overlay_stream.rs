@@ -0,0 +1,109 @@
+//! Overlay Stats Stream - Cross-platform
+//!
+//! Pushes native stats to the injected overlay as `pac-overlay-stats`
+//! events instead of the overlay polling several commands twice a second.
+//! The stream only runs while the overlay is visible and the page is shown
+//! (the runtime reports both through `set_overlay_stream`), and skips ticks
+//! while the window is minimized, so a hidden overlay costs no IPC or
+//! timer wakeups.
+
+use crate::jank::{JankStats, JankTracker};
+use crate::performance::{PerformanceMonitor, PerformanceSnapshot};
+use serde::Serialize;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, warn};
+
+/// Payload of `pac-overlay-stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlayStats {
+    #[serde(flatten)]
+    pub snapshot: PerformanceSnapshot,
+    pub jank: JankStats,
+}
+
+#[derive(Debug)]
+struct StreamInner {
+    active: bool,
+    interval: Duration,
+}
+
+/// Managed stream state
+pub struct OverlayStream {
+    inner: Mutex<StreamInner>,
+    changed: Condvar,
+}
+
+impl OverlayStream {
+    pub fn new(interval_ms: u64) -> Self {
+        Self {
+            inner: Mutex::new(StreamInner {
+                active: false,
+                interval: Duration::from_millis(interval_ms),
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Start or pause pushing
+    pub fn set_active(&self, active: bool) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.active != active {
+            debug!(
+                "Overlay stats stream {}",
+                if active { "resumed" } else { "paused" }
+            );
+        }
+        inner.active = active;
+        self.changed.notify_all();
+    }
+
+    pub fn set_interval(&self, interval_ms: u64) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.interval = Duration::from_millis(interval_ms);
+        self.changed.notify_all();
+    }
+
+    /// Block until the stream is active, then wait one interval. Returns
+    /// false if it was paused during the wait.
+    fn wait_for_tick(&self) -> bool {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let inner = self
+            .changed
+            .wait_while(inner, |inner| !inner.active)
+            .unwrap_or_else(|e| e.into_inner());
+        let interval = inner.interval;
+        let (inner, _) = self
+            .changed
+            .wait_timeout_while(inner, interval, |inner| inner.active)
+            .unwrap_or_else(|e| e.into_inner());
+        inner.active
+    }
+}
+
+/// Start the stream thread (idle until the overlay activates it)
+pub fn spawn(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("overlay-stats".to_string())
+        .spawn(move || loop {
+            if !app.state::<OverlayStream>().wait_for_tick() {
+                continue;
+            }
+            let Some(window) = app.get_webview_window("main") else {
+                continue;
+            };
+            if window.is_minimized().unwrap_or(false) {
+                continue;
+            }
+            let stats = OverlayStats {
+                snapshot: app.state::<PerformanceMonitor>().snapshot(),
+                jank: app.state::<JankTracker>().stats(),
+            };
+            if let Err(e) = window.emit("pac-overlay-stats", stats) {
+                debug!("Failed to push overlay stats: {}", e);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start overlay stats stream: {}", e));
+}
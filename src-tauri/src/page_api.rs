@@ -0,0 +1,323 @@
+//! Page API - Cross-platform
+//!
+//! A versioned contract for calling native commands from the page:
+//! `window.__PACD_API__` carries `version` and one wrapper per command
+//! (`getSettings()`, `setWindowMode(mode)`, `copyRoomLink(roomId)`, ...), so
+//! runtime modules and user scripts don't depend on raw
+//! `__TAURI__.core.invoke` strings.
+//!
+//! Each command is declared once below with its Rust argument and result
+//! types. The wrappers are generated from that declaration at document
+//! start, and `cargo test` generates `bindings/pacd-api.d.ts` from the same
+//! types through `ts-rs`. Tests check each declaration against the
+//! command's signature in commands.rs, so a changed signature fails the
+//! build until the declaration and the .d.ts follow it.
+//!
+//! Compatibility rule: a wrapper is never removed or changes the meaning of
+//! its arguments within a version. Adding commands keeps the version;
+//! anything else bumps it.
+
+use crate::commands::{UpdateInfo, WindowMode};
+use crate::history::PerformanceSample;
+use crate::injection::{InjectionPhase, RuntimeScript};
+use crate::jank::JankStats;
+use crate::keybinds::KeyRemap;
+use crate::maintenance::UpstreamIssue;
+use crate::observers::{ObserverStats, ScriptBudget};
+use crate::performance::{GpuStats, HdrInfo, PerformanceStats};
+use crate::power_guard::PowerEmergency;
+use crate::process_failures::ProcessFailure;
+use crate::profile::ProfileInfo;
+use crate::safe_mode::SafeModeStatus;
+use crate::settings::Settings;
+use serde::Serialize;
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use ts_rs::{TypeVisitor, TS};
+
+/// Bumped on incompatible changes to [`COMMANDS`]
+pub const API_VERSION: u32 = 1;
+
+/// A Rust type in a command's signature
+#[derive(Clone, Copy)]
+pub struct ApiType {
+    /// As written in the declaration
+    pub rust: &'static str,
+    /// TypeScript name of the type
+    pub ts: fn() -> String,
+    /// Adds the declarations the TypeScript name refers to
+    declare: fn(&mut Declarations),
+}
+
+/// A command exposed to the page
+#[derive(Clone, Copy)]
+pub struct ApiCommand {
+    /// Tauri command name
+    pub command: &'static str,
+    /// Arguments the page passes, in the wrapper's parameter order (Rust
+    /// names; the wrapper sends them in camelCase, as Tauri expects)
+    pub args: &'static [(&'static str, ApiType)],
+    /// What the wrapper's promise resolves to (the `Ok` type for commands
+    /// returning `Result<_, String>`, which reject with the error)
+    pub returns: ApiType,
+}
+
+macro_rules! api_type {
+    ($ty:ty) => {
+        ApiType {
+            rust: stringify!($ty),
+            ts: <$ty as TS>::name,
+            declare: |d| d.add::<$ty>(),
+        }
+    };
+}
+
+macro_rules! api_commands {
+    ($($command:ident($($arg:ident: $arg_ty:ty),*) -> $ret:ty;)*) => {
+        /// Commands in the contract
+        pub const COMMANDS: &[ApiCommand] = &[$(ApiCommand {
+            command: stringify!($command),
+            args: &[$((stringify!($arg), api_type!($arg_ty))),*],
+            returns: api_type!($ret),
+        }),*];
+    };
+}
+
+api_commands! {
+    get_settings() -> Settings;
+    get_profile() -> ProfileInfo;
+    get_performance_stats() -> PerformanceStats;
+    get_gpu_stats() -> GpuStats;
+    get_hdr_status() -> HdrInfo;
+    get_performance_history() -> Vec<PerformanceSample>;
+    get_jank_stats() -> JankStats;
+    get_observer_stats() -> ObserverStats;
+    get_script_budget() -> ScriptBudget;
+    get_window_mode() -> WindowMode;
+    set_window_mode(mode: WindowMode) -> WindowMode;
+    toggle_fullscreen() -> bool;
+    get_key_bindings() -> KeyRemap;
+    get_power_emergency() -> Option<PowerEmergency>;
+    get_upstream_issue() -> Option<UpstreamIssue>;
+    copy_room_link(room_id: String) -> String;
+    log_page_event(level: String, source: String, message: String) -> ();
+    check_for_updates() -> UpdateInfo;
+    get_available_update() -> Option<UpdateInfo>;
+    get_safe_mode() -> SafeModeStatus;
+    get_crash_history() -> Vec<ProcessFailure>;
+}
+
+/// TypeScript declarations of the crate's types, each once
+#[derive(Default)]
+struct Declarations {
+    seen: HashSet<TypeId>,
+    decls: Vec<String>,
+}
+
+impl Declarations {
+    /// Declare a type used in a signature and every type it refers to
+    fn add<T: TS + 'static + ?Sized>(&mut self) {
+        self.visit::<T>();
+        T::visit_generics(self);
+    }
+}
+
+impl TypeVisitor for Declarations {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        // Built-in types (`String`, `Vec<T>`, ...) have no declaration
+        if T::output_path().is_none() || !self.seen.insert(TypeId::of::<T>()) {
+            return;
+        }
+        self.decls.push(format!("export {}", T::decl()));
+        T::visit_dependencies(self);
+    }
+}
+
+/// `get_settings` -> `getSettings`
+fn method_name(command: &str) -> String {
+    let mut name = String::with_capacity(command.len());
+    let mut upper = false;
+    for c in command.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            name.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+/// What the runtime needs to call a command
+#[derive(Serialize)]
+struct WrapperSpec {
+    command: &'static str,
+    args: Vec<String>,
+}
+
+fn build_script() -> String {
+    let methods: serde_json::Map<String, serde_json::Value> = COMMANDS
+        .iter()
+        .map(|c| {
+            let spec = WrapperSpec {
+                command: c.command,
+                args: c.args.iter().map(|(name, _)| method_name(name)).collect(),
+            };
+            let spec = serde_json::to_value(spec).unwrap_or_default();
+            (method_name(c.command), spec)
+        })
+        .collect();
+    format!(
+        r#"const invoke = window.__TAURI__?.core?.invoke;
+if (!invoke || window.__PACD_API__) return;
+const methods = {methods};
+const api = {{ version: {version}, commands: Object.keys(methods) }};
+for (const [method, spec] of Object.entries(methods)) {{
+    api[method] = (...values) => invoke(spec.command,
+        Object.fromEntries(spec.args.map((arg, i) => [arg, values[i]])));
+}}
+Object.defineProperty(window, '__PACD_API__', {{ value: Object.freeze(api) }});"#,
+        methods = serde_json::Value::Object(methods),
+        version = API_VERSION,
+    )
+}
+
+/// Contents of `bindings/pacd-api.d.ts`
+pub fn typescript_declarations() -> String {
+    let mut types = Declarations::default();
+    let mut methods = Vec::with_capacity(COMMANDS.len());
+    for c in COMMANDS {
+        let params: Vec<String> = c
+            .args
+            .iter()
+            .map(|(name, ty)| {
+                (ty.declare)(&mut types);
+                format!("{}: {}", method_name(name), (ty.ts)())
+            })
+            .collect();
+        (c.returns.declare)(&mut types);
+        methods.push(format!(
+            "  {}({}): Promise<{}>;",
+            method_name(c.command),
+            params.join(", "),
+            (c.returns.ts)()
+        ));
+    }
+    format!(
+        "// Generated by `cargo test` from src-tauri/src/page_api.rs. Do not edit.\n\n\
+         {types}\n\n\
+         /** `window.__PACD_API__` */\n\
+         export interface PacdApi {{\n  \
+         readonly version: {version};\n  \
+         readonly commands: readonly string[];\n\
+         {methods}\n}}\n\n\
+         declare global {{\n  \
+         interface Window {{\n    \
+         readonly __PACD_API__?: PacdApi;\n  \
+         }}\n}}\n",
+        types = types.decls.join("\n\n"),
+        version = API_VERSION,
+        methods = methods.join("\n"),
+    )
+}
+
+/// The script defining `window.__PACD_API__`, for the injection scheduler
+pub fn runtime_script() -> RuntimeScript {
+    static SOURCE: OnceLock<String> = OnceLock::new();
+    RuntimeScript {
+        name: "page-api",
+        phase: InjectionPhase::DocumentStart,
+        source: SOURCE.get_or_init(build_script),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn without_spaces(s: &str) -> String {
+        s.chars().filter(|c| !c.is_whitespace()).collect()
+    }
+
+    #[test]
+    fn every_command_in_the_contract_is_registered() {
+        let main = include_str!("main.rs");
+        for c in COMMANDS {
+            assert!(
+                main.contains(&format!("commands::{},", c.command)),
+                "{} is not registered",
+                c.command
+            );
+        }
+    }
+
+    #[test]
+    fn declarations_match_the_command_signatures() {
+        let source = without_spaces(include_str!("commands.rs"));
+        for c in COMMANDS {
+            let start = source
+                .find(&format!("pubfn{}(", c.command))
+                .or_else(|| source.find(&format!("pubasyncfn{}(", c.command)))
+                .unwrap_or_else(|| panic!("{} is not in commands.rs", c.command));
+            let signature = &source[start..start + source[start..].find('{').unwrap()];
+            for (name, ty) in c.args {
+                assert!(
+                    signature.contains(&format!("{}:{},", name, without_spaces(ty.rust)))
+                        || signature.contains(&format!("{}:{})", name, without_spaces(ty.rust))),
+                    "{}: argument {}: {} doesn't match `{}`",
+                    c.command,
+                    name,
+                    ty.rust,
+                    signature
+                );
+            }
+            let ret = without_spaces(c.returns.rust);
+            let matches = if ret == "()" {
+                !signature.contains("->")
+            } else {
+                signature.ends_with(&format!("->{}", ret))
+                    || signature.ends_with(&format!("->Result<{},String>", ret))
+            };
+            assert!(
+                matches,
+                "{}: result {} doesn't match `{}`",
+                c.command, c.returns.rust, signature
+            );
+        }
+    }
+
+    #[test]
+    fn wrappers_are_named_in_camel_case() {
+        assert_eq!(method_name("get_settings"), "getSettings");
+        assert_eq!(method_name("log_page_event"), "logPageEvent");
+        let script = build_script();
+        assert!(script.contains(r#""copyRoomLink":{"command":"copy_room_link","args":["roomId"]}"#));
+        assert!(script.contains("version: 1,"));
+    }
+
+    /// Writes `bindings/pacd-api.d.ts` when it is missing or
+    /// `PACD_UPDATE_API_TYPES` is set, and otherwise fails if it is stale
+    #[test]
+    fn typescript_declarations_are_current() {
+        let expected = typescript_declarations();
+        assert!(expected.contains("copyRoomLink(roomId: string): Promise<string>;"));
+        assert!(expected.contains("export type Settings = "));
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("bindings/pacd-api.d.ts");
+        let current = std::fs::read_to_string(&path).ok();
+        if current.is_none() || std::env::var_os("PACD_UPDATE_API_TYPES").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &expected).unwrap();
+        } else {
+            assert!(
+                current.as_deref() == Some(expected.as_str()),
+                "{} is stale; run `PACD_UPDATE_API_TYPES=1 cargo test` and commit it",
+                path.display()
+            );
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Page Events - Cross-platform
+//!
+//! One channel, `pac-events`, for native state the page UI shows: changed
+//! settings, changed profile settings, an available update, health
+//! warnings and toasts. The injected runtime subscribes once and hands each event to
+//! the modules that registered for its `type`, so nothing in the page polls
+//! for these.
+//!
+//! Feature-specific pushes that predate the channel (`pac-key-bindings`,
+//! `pac-power-emergency`, ...) keep their own events.
+
+use crate::performance::HdrInfo;
+use crate::profile::{self, ProfileSettings};
+use crate::settings::{self, Settings};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+/// Toast styling
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToastLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// A `pac-events` payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PageEvent {
+    SettingsChanged {
+        settings: Settings,
+    },
+    ProfileChanged {
+        name: String,
+        settings: ProfileSettings,
+    },
+    UpdateAvailable {
+        version: String,
+        notes: Option<String>,
+        /// Key ID the update will be verified with
+        signing_key: Option<String>,
+    },
+    HealthWarning {
+        message: String,
+    },
+    /// Shown in the page's toast queue, one at a time
+    Toast {
+        message: String,
+        level: ToastLevel,
+        /// Page default (5 s) when unset
+        duration_ms: Option<u64>,
+    },
+    /// HDR turned on or off, or displays changed (display_watch.rs)
+    HdrChanged {
+        info: HdrInfo,
+    },
+    /// Run the WebGL stress worker (`simulate_load`)
+    SimulateLoad {
+        duration_ms: u64,
+    },
+    /// A join link was opened (`take_pending_join` has the room)
+    JoinRequested,
+}
+
+/// Send `event` to the main window's page
+pub fn emit(app: &AppHandle, event: PageEvent) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if let Err(e) = window.emit("pac-events", &event) {
+        warn!("Failed to send page event: {}", e);
+    }
+}
+
+/// Show `message` in the game window; complements OS notifications, which
+/// are for when the window isn't in front
+pub fn toast(app: &AppHandle, level: ToastLevel, message: impl Into<String>) {
+    emit(
+        app,
+        PageEvent::Toast {
+            message: message.into(),
+            level,
+            duration_ms: None,
+        },
+    );
+}
+
+/// Forward settings and profile changes to the page
+pub fn install(app: &AppHandle) {
+    let handle = app.clone();
+    settings::on_change(move |settings| {
+        emit(
+            &handle,
+            PageEvent::SettingsChanged {
+                settings: settings.clone(),
+            },
+        )
+    });
+    let handle = app.clone();
+    profile::on_change(move |settings| {
+        emit(
+            &handle,
+            PageEvent::ProfileChanged {
+                name: profile::active().to_string(),
+                settings: settings.clone(),
+            },
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_tagged_by_type() {
+        let event = PageEvent::HealthWarning {
+            message: "Slow".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "type": "health_warning", "message": "Slow" })
+        );
+        let toast = PageEvent::Toast {
+            message: "Saved".to_string(),
+            level: ToastLevel::Success,
+            duration_ms: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&toast).unwrap(),
+            serde_json::json!({
+                "type": "toast",
+                "message": "Saved",
+                "level": "success",
+                "duration_ms": null
+            })
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Page State - Cross-platform
+//!
+//! Tracks which screen the game is on, derived only from the public SPA route
+//! reported by the injected runtime. Native features use this to avoid doing
+//! anything disruptive mid-match. No game state is read.
+//!
+//! Phase changes are broadcast to the page as `pac-page-state` events.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tracing::debug;
+
+/// Which screen the game is showing
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GamePhase {
+    /// Landing / login page
+    Home,
+    Lobby,
+    /// Pre-match room
+    Preparation,
+    InGame,
+    AfterGame,
+    /// Tools such as the sprite viewer or bot builder
+    Other,
+}
+
+impl GamePhase {
+    /// Map an SPA route (see localhost_server SPA_ROUTE_PREFIXES) to a phase
+    pub fn from_route(route: &str) -> Self {
+        let path = route.split(['?', '#']).next().unwrap_or("");
+        let first = path.trim_matches('/').split('/').next().unwrap_or("");
+        match first {
+            "" | "index.html" | "auth" => GamePhase::Home,
+            "lobby" => GamePhase::Lobby,
+            "preparation" => GamePhase::Preparation,
+            "game" => GamePhase::InGame,
+            "after" => GamePhase::AfterGame,
+            _ => GamePhase::Other,
+        }
+    }
+
+    /// True while the player is in a room or a match
+    pub fn is_match_in_progress(self) -> bool {
+        matches!(self, GamePhase::Preparation | GamePhase::InGame)
+    }
+}
+
+/// Managed page state
+pub struct PageState(Mutex<(GamePhase, Instant)>);
+
+impl Default for PageState {
+    fn default() -> Self {
+        Self(Mutex::new((GamePhase::Home, Instant::now())))
+    }
+}
+
+impl PageState {
+    pub fn phase(&self) -> GamePhase {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).0
+    }
+
+    /// How long the current phase has been active
+    pub fn phase_duration(&self) -> Duration {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).1.elapsed()
+    }
+
+    /// Record the current route; emits `pac-page-state` when the phase changes
+    pub fn update(&self, app: &AppHandle, route: &str) -> GamePhase {
+        let phase = GamePhase::from_route(route);
+        let changed = {
+            let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+            if state.0 == phase {
+                false
+            } else {
+                *state = (phase, Instant::now());
+                true
+            }
+        };
+        if changed {
+            debug!("Page phase: {:?}", phase);
+            let _ = app.emit("pac-page-state", phase);
+        }
+        phase
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_map_to_phases() {
+        assert_eq!(GamePhase::from_route("/"), GamePhase::Home);
+        assert_eq!(GamePhase::from_route("/lobby"), GamePhase::Lobby);
+        assert_eq!(
+            GamePhase::from_route("/preparation?id=abc"),
+            GamePhase::Preparation
+        );
+        assert_eq!(GamePhase::from_route("/game"), GamePhase::InGame);
+        assert_eq!(GamePhase::from_route("/after/"), GamePhase::AfterGame);
+        assert_eq!(GamePhase::from_route("/sprite-viewer"), GamePhase::Other);
+    }
+
+    #[test]
+    fn only_rooms_and_matches_are_in_progress() {
+        assert!(GamePhase::InGame.is_match_in_progress());
+        assert!(GamePhase::Preparation.is_match_in_progress());
+        assert!(!GamePhase::Lobby.is_match_in_progress());
+        assert!(!GamePhase::AfterGame.is_match_in_progress());
+    }
+}
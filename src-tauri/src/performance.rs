@@ -3,37 +3,106 @@
 //! System-level performance optimizations for the native client.
 //! Affects only rendering and system performance, NOT gameplay.
 
+use crate::cpu_frequency::{self, CpuFrequency};
+use crate::power_draw::{PowerDraw, PowerMeter};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use sysinfo::{System, Pid};
 use tracing::{debug, info, warn};
+use ts_rs::TS;
 
 #[cfg(target_os = "windows")]
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+#[cfg(target_os = "linux")]
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 #[cfg(target_os = "windows")]
 use tauri::WebviewWindow;
 
-#[cfg(target_os = "windows")]
-use std::sync::Arc;
-
 #[cfg(target_os = "windows")]
 use wmi::{COMLibrary, WMIConnection};
 
 /// Performance statistics from native code.
 /// FPS is measured by the injected PACDeluxe runtime in main.rs, not here.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct PerformanceStats {
+    /// System-wide CPU usage, averaged over all cores
     pub cpu_usage: f32,
+    /// CPU used by PACDeluxe and its child processes (WebView2), on the
+    /// same whole-machine scale as `cpu_usage`
+    #[serde(default)]
+    pub process_tree_cpu_usage: f32,
+    #[ts(type = "number")]
     pub memory_usage_mb: u64,
+    /// Memory of PACDeluxe's child processes (the WebView2 browser, GPU and
+    /// renderer processes; WebKitGTK's web and network processes on Linux)
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub webview_memory_mb: u64,
     pub uptime_secs: f64,
+    /// Usage of each logical core (a single pegged core hides in the average)
+    #[serde(default)]
+    pub cpu_core_usage: Vec<f32>,
+    /// Usage of the busiest core
+    #[serde(default)]
+    pub cpu_busiest_core_usage: f32,
+    /// Clock speeds and power plan (refreshed every few seconds)
+    #[serde(default)]
+    pub cpu_frequency: CpuFrequency,
+    /// Estimated power draw (CPU package or battery discharge)
+    #[serde(default)]
+    pub power_draw: PowerDraw,
+}
+
+/// How often clock speeds and the power plan are re-read
+const FREQUENCY_REFRESH: Duration = Duration::from_secs(5);
+
+/// How often the snapshot sampler refreshes while someone is reading
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Older snapshots are refreshed by the reader instead of being served
+const SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(1);
+
+/// The sampler pauses when no snapshot was read for this long
+const SNAPSHOT_IDLE_AFTER: Duration = Duration::from_secs(2);
+
+/// System and GPU stats from one shared refresh.
+///
+/// sysinfo CPU usage and the PDH GPU counters are deltas since the previous
+/// refresh, so independent refreshes by each consumer both cost more and
+/// report noisier numbers over uneven windows.
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceSnapshot {
+    pub performance: PerformanceStats,
+    pub gpu: GpuStats,
+    /// Incremented on every refresh; equal sequences mean the same data
+    pub sequence: u64,
+    /// How old the data was when this snapshot was read
+    pub age_ms: u64,
+}
+
+struct CachedSample {
+    sampled_at: Instant,
+    sequence: u64,
+    performance: PerformanceStats,
+    gpu: GpuStats,
+}
+
+#[derive(Default)]
+struct SnapshotCache {
+    latest: Option<CachedSample>,
+    last_read: Option<Instant>,
 }
 
 /// Performance monitor
 pub struct PerformanceMonitor {
     start_time: Instant,
     system: Mutex<System>,
+    frequency: Mutex<Option<(Instant, CpuFrequency)>>,
+    power: Mutex<PowerMeter>,
+    snapshot: Mutex<SnapshotCache>,
 }
 
 impl PerformanceMonitor {
@@ -41,9 +110,29 @@ impl PerformanceMonitor {
         Self {
             start_time: Instant::now(),
             system: Mutex::new(System::new_all()),
+            frequency: Mutex::new(None),
+            power: Mutex::new(PowerMeter::new()),
+            snapshot: Mutex::new(SnapshotCache::default()),
         }
     }
 
+    /// Cached clock speeds, re-read after `FREQUENCY_REFRESH`
+    fn cpu_frequency(&self) -> CpuFrequency {
+        let mut cached = self.frequency.lock().unwrap_or_else(|e| e.into_inner());
+        match cached.as_ref() {
+            Some((read_at, frequency)) if read_at.elapsed() < FREQUENCY_REFRESH => {
+                frequency.clone()
+            }
+            _ => {
+                let frequency = cpu_frequency::read();
+                *cached = Some((Instant::now(), frequency.clone()));
+                frequency
+            }
+        }
+    }
+
+    /// Refresh and read the stats directly. Consumers should use
+    /// `snapshot()`, which shares one refresh between them.
     pub fn get_stats(&self) -> PerformanceStats {
         let mut system = self.system.lock().unwrap_or_else(|e| e.into_inner());
         system.refresh_cpu_usage();
@@ -51,22 +140,145 @@ impl PerformanceMonitor {
 
         let uptime = self.start_time.elapsed();
         let cpu_usage = system.global_cpu_usage();
+        let cpu_core_usage: Vec<f32> = system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
+        let cpu_busiest_core_usage = cpu_core_usage.iter().copied().fold(0.0, f32::max);
 
         // Get memory for this process specifically (not system-wide)
         let our_pid = Pid::from_u32(std::process::id());
+        let processes: Vec<(Pid, Option<Pid>, f32)> = system
+            .processes()
+            .iter()
+            .map(|(pid, process)| (*pid, process.parent(), process.cpu_usage()))
+            .collect();
+        // Process CPU is per core (100 = one core); scale to the whole machine
+        let process_tree_cpu_usage =
+            process_tree_cpu(&processes, our_pid) / cpu_core_usage.len().max(1) as f32;
         let memory_usage_mb = system
             .process(our_pid)
             .map(|p| p.memory() / 1024 / 1024)
             .unwrap_or(0);
+        let webview_memory_mb = process_tree(&processes, our_pid)
+            .into_iter()
+            .filter(|pid| *pid != our_pid)
+            .filter_map(|pid| system.process(pid))
+            .map(|p| p.memory())
+            .sum::<u64>()
+            / 1024
+            / 1024;
+
+        drop(system);
 
         PerformanceStats {
             cpu_usage,
+            process_tree_cpu_usage,
             memory_usage_mb,
+            webview_memory_mb,
             uptime_secs: uptime.as_secs_f64(),
+            cpu_core_usage,
+            cpu_busiest_core_usage,
+            cpu_frequency: self.cpu_frequency(),
+            power_draw: self.power.lock().unwrap_or_else(|e| e.into_inner()).read(),
+        }
+    }
+
+    fn sample(&self, previous: Option<&CachedSample>) -> CachedSample {
+        let performance = self.get_stats();
+        let gpu = get_gpu_stats();
+        CachedSample {
+            sampled_at: Instant::now(),
+            sequence: previous.map_or(0, |sample| sample.sequence) + 1,
+            performance,
+            gpu,
+        }
+    }
+
+    /// Latest shared stats. Refreshed by the snapshot sampler while read
+    /// regularly; a reader finding it older than `SNAPSHOT_MAX_AGE` (sampler
+    /// paused or not started) refreshes it itself.
+    pub fn snapshot(&self) -> PerformanceSnapshot {
+        let mut cache = self.snapshot.lock().unwrap_or_else(|e| e.into_inner());
+        cache.last_read = Some(Instant::now());
+        let sample = match cache.latest.take() {
+            Some(sample) if sample.sampled_at.elapsed() < SNAPSHOT_MAX_AGE => sample,
+            previous => self.sample(previous.as_ref()),
+        };
+        let snapshot = PerformanceSnapshot {
+            performance: sample.performance.clone(),
+            gpu: sample.gpu.clone(),
+            sequence: sample.sequence,
+            age_ms: sample.sampled_at.elapsed().as_millis() as u64,
+        };
+        cache.latest = Some(sample);
+        snapshot
+    }
+
+    /// Refresh the snapshot if it was read recently. Returns whether it did.
+    fn refresh_snapshot(&self) -> bool {
+        let mut cache = self.snapshot.lock().unwrap_or_else(|e| e.into_inner());
+        let in_use = cache
+            .last_read
+            .is_some_and(|read_at| read_at.elapsed() < SNAPSHOT_IDLE_AFTER);
+        if in_use {
+            cache.latest = Some(self.sample(cache.latest.as_ref()));
         }
+        in_use
     }
 }
 
+/// Start the thread that refreshes the shared snapshot every
+/// `SNAPSHOT_INTERVAL` while it is being read
+pub fn spawn_snapshot_sampler(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    std::thread::Builder::new()
+        .name("perf-snapshot".to_string())
+        .spawn(move || {
+            let mut sampling = false;
+            loop {
+                std::thread::sleep(SNAPSHOT_INTERVAL);
+                let refreshed = app.state::<PerformanceMonitor>().refresh_snapshot();
+                if refreshed != sampling {
+                    debug!("Snapshot sampler {}", if refreshed { "active" } else { "idle" });
+                    sampling = refreshed;
+                }
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start performance snapshot sampler: {}", e));
+}
+
+/// Total CPU of `root` and all its descendants. `processes` holds
+/// (pid, parent, cpu usage) for every running process.
+fn process_tree_cpu(processes: &[(Pid, Option<Pid>, f32)], root: Pid) -> f32 {
+    let tree = process_tree(processes, root);
+    processes
+        .iter()
+        .filter(|(pid, _, _)| tree.contains(pid))
+        .map(|(_, _, cpu)| cpu)
+        .sum()
+}
+
+/// `root` and every process descending from it
+fn process_tree(
+    processes: &[(Pid, Option<Pid>, f32)],
+    root: Pid,
+) -> std::collections::HashSet<Pid> {
+    let mut tree = std::collections::HashSet::from([root]);
+    // Grow the tree one generation per pass until nothing new joins
+    loop {
+        let before = tree.len();
+        for (pid, parent, _) in processes {
+            if parent.is_some_and(|parent| tree.contains(&parent)) {
+                tree.insert(*pid);
+            }
+        }
+        if tree.len() == before {
+            break;
+        }
+    }
+    tree
+}
+
 impl Default for PerformanceMonitor {
     fn default() -> Self {
         Self::new()
@@ -81,21 +293,107 @@ static WEBVIEW_OPTIMIZER_RUNNING: AtomicBool = AtomicBool::new(false);
 #[cfg(target_os = "windows")]
 static WMI_WATCHER_ACTIVE: AtomicBool = AtomicBool::new(false);
 
-/// Counter for number of WebView2 processes elevated (Windows only)
+/// Counter for number of WebView2 processes elevated this run (Windows only)
 #[cfg(target_os = "windows")]
 static PROCESSES_ELEVATED: AtomicU32 = AtomicU32::new(0);
 
+/// Elevated WebView2 PIDs that are still running (Windows only)
+#[cfg(target_os = "windows")]
+static LIVE_ELEVATED: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Flag to track if the WMI process exit subscription is active (Windows only)
+#[cfg(target_os = "windows")]
+static WMI_EXIT_WATCHER_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Process start events delivered by the WMI subscription (Windows only)
+#[cfg(target_os = "windows")]
+static WMI_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// When the WMI subscription started, for the event rate (Windows only)
+#[cfg(target_os = "windows")]
+static WMI_SUBSCRIBED_AT: std::sync::OnceLock<Instant> = std::sync::OnceLock::new();
+
+/// WQL for the process watcher: WMI drops other processes' start events
+/// itself instead of waking our sink for every process on the system
+#[cfg(target_os = "windows")]
+const WEBVIEW_START_QUERY: &str =
+    "SELECT * FROM Win32_ProcessStartTrace WHERE ProcessName = 'msedgewebview2.exe'";
+
+/// WQL for the exit watcher, filtered the same way
+#[cfg(target_os = "windows")]
+const WEBVIEW_STOP_QUERY: &str =
+    "SELECT * FROM Win32_ProcessStopTrace WHERE ProcessName = 'msedgewebview2.exe'";
+
 /// WebView2 elevation telemetry for monitoring optimization effectiveness
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElevationTelemetry {
-    /// Number of WebView2 processes that have been elevated
+    /// Number of elevated WebView2 processes still running
     pub processes_elevated: u32,
+    /// Number of WebView2 processes elevated since startup, exited included
+    #[serde(default)]
+    pub processes_elevated_total: u32,
     /// Current monitoring mode: "wmi" (event-driven) or "polling" (fallback)
     pub mode: String,
     /// Whether the optimizer thread is currently running
     pub is_active: bool,
     /// Whether WMI event subscription is working
     pub wmi_available: bool,
+    /// Processes the elevation policy left at normal priority
+    #[serde(default)]
+    pub processes_skipped: u32,
+    /// Process start events received from WMI (only WebView2 processes
+    /// match the subscription)
+    #[serde(default)]
+    pub wmi_events: u64,
+    /// Average WMI events per minute since the subscription started
+    #[serde(default)]
+    pub wmi_events_per_minute: f64,
+}
+
+/// Book-keeping after raising `pid`'s priority (Windows)
+#[cfg(target_os = "windows")]
+fn note_elevated(pid: u32) {
+    PROCESSES_ELEVATED.fetch_add(1, Ordering::SeqCst);
+    let mut live = LIVE_ELEVATED.lock().unwrap_or_else(|e| e.into_inner());
+    if !live.contains(&pid) {
+        live.push(pid);
+    }
+    drop(live);
+    crate::process_failures::record_elevation(pid);
+    crate::elevation_policy::record(pid);
+}
+
+/// Book-keeping after a WebView2 process exited. Forgetting the PID matters:
+/// Windows reuses PIDs, and a replacement renderer that gets an old one
+/// must be elevated again. (Windows)
+#[cfg(target_os = "windows")]
+fn note_exited(pid: u32) {
+    let mut live = LIVE_ELEVATED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(index) = live.iter().position(|&other| other == pid) {
+        live.swap_remove(index);
+        debug!("Elevated WebView2 process {} exited", pid);
+    }
+    drop(live);
+    crate::elevation_policy::forget(pid);
+}
+
+#[cfg(target_os = "windows")]
+fn is_live_elevated(pid: u32) -> bool {
+    LIVE_ELEVATED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&pid)
+}
+
+/// Elevated processes still running. Without the exit subscription,
+/// exits are found by checking each process (Windows)
+#[cfg(target_os = "windows")]
+fn live_elevated_count() -> u32 {
+    let mut live = LIVE_ELEVATED.lock().unwrap_or_else(|e| e.into_inner());
+    if !WMI_EXIT_WATCHER_ACTIVE.load(Ordering::SeqCst) {
+        live.retain(|&pid| crate::process_failures::is_running(pid));
+    }
+    live.len() as u32
 }
 
 /// Get current WebView2 elevation telemetry (Windows)
@@ -103,39 +401,63 @@ pub struct ElevationTelemetry {
 pub fn get_elevation_telemetry() -> ElevationTelemetry {
     let wmi_active = WMI_WATCHER_ACTIVE.load(Ordering::SeqCst);
     let optimizer_running = WEBVIEW_OPTIMIZER_RUNNING.load(Ordering::SeqCst);
+    let wmi_events = WMI_EVENTS.load(Ordering::SeqCst);
+    let wmi_minutes = WMI_SUBSCRIBED_AT
+        .get()
+        .map_or(0.0, |at| at.elapsed().as_secs_f64() / 60.0);
 
     ElevationTelemetry {
-        processes_elevated: PROCESSES_ELEVATED.load(Ordering::SeqCst),
+        processes_elevated: live_elevated_count(),
+        processes_elevated_total: PROCESSES_ELEVATED.load(Ordering::SeqCst),
         mode: if wmi_active { "wmi".to_string() } else { "polling".to_string() },
         is_active: optimizer_running,
         wmi_available: wmi_active,
+        processes_skipped: crate::elevation_policy::skipped_count(),
+        wmi_events,
+        // Below a minute the average says little; report the count as is
+        wmi_events_per_minute: wmi_events as f64 / wmi_minutes.max(1.0),
     }
 }
 
-/// Get elevation telemetry (Linux - not applicable)
+/// Get WebKitGTK tuning telemetry (Linux; always polling)
 #[cfg(target_os = "linux")]
 pub fn get_elevation_telemetry() -> ElevationTelemetry {
+    let live = LINUX_TUNED.lock().unwrap_or_else(|e| e.into_inner()).len() as u32;
     ElevationTelemetry {
-        processes_elevated: 0,
-        mode: "n/a".to_string(),
-        is_active: false,
+        processes_elevated: live,
+        processes_elevated_total: LINUX_TUNED_TOTAL.load(Ordering::SeqCst),
+        mode: "polling".to_string(),
+        is_active: LINUX_OPTIMIZER_RUNNING.load(Ordering::SeqCst),
         wmi_available: false,
+        processes_skipped: 0,
+        wmi_events: 0,
+        wmi_events_per_minute: 0.0,
     }
 }
 
 // ==================== GPU Monitoring ====================
 
 /// GPU usage statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct GpuStats {
-    /// GPU utilization percentage (0-100)
+    /// GPU utilization percentage (0-100): the busiest engine, all processes
     pub usage_percent: f32,
+    /// Busiest engine counting only PACDeluxe and its WebView2 processes
+    pub webview_usage_percent: Option<f32>,
     /// GPU name (from DXGI on Windows, /sys on Linux)
     pub name: Option<String>,
     /// Dedicated video memory in MB
+    #[ts(type = "number")]
     pub vram_total_mb: u64,
+    /// Dedicated VRAM committed by the WebView2 GPU process, in MB
+    pub webview_vram_dedicated_mb: Option<f64>,
+    /// Shared (system) memory committed by the WebView2 GPU process, in MB
+    pub webview_vram_shared_mb: Option<f64>,
     /// Whether GPU monitoring is available
     pub available: bool,
+    /// Counters are still being set up in the background
+    #[serde(default)]
+    pub initializing: bool,
     /// Error message if monitoring failed
     pub error: Option<String>,
 }
@@ -144,9 +466,13 @@ impl Default for GpuStats {
     fn default() -> Self {
         Self {
             usage_percent: 0.0,
+            webview_usage_percent: None,
             name: None,
             vram_total_mb: 0,
+            webview_vram_dedicated_mb: None,
+            webview_vram_shared_mb: None,
             available: false,
+            initializing: false,
             error: None,
         }
     }
@@ -158,9 +484,19 @@ impl Default for GpuStats {
 pub struct GpuMonitor {
     query_handle: Option<isize>,
     counter_handle: Option<isize>,
+    /// `GPU Process Memory` dedicated/shared usage counters (per process)
+    dedicated_memory_counter: Option<isize>,
+    shared_memory_counter: Option<isize>,
+    /// WebView2 GPU process, re-resolved when it disappears
+    webview_gpu_pid: Option<u32>,
+    /// PACDeluxe and its WebView2 processes, re-resolved when none of them
+    /// shows up in the engine counters
+    own_pids: Vec<u32>,
     gpu_name: Option<String>,
     vram_mb: u64,
     is_initialized: bool,
+    /// Why counters are unavailable: set at initialization, or by the last
+    /// read that failed
     last_error: Option<String>,
 }
 
@@ -178,6 +514,10 @@ impl GpuMonitor {
         let mut monitor = Self {
             query_handle: None,
             counter_handle: None,
+            dedicated_memory_counter: None,
+            shared_memory_counter: None,
+            webview_gpu_pid: None,
+            own_pids: Vec::new(),
             gpu_name: None,
             vram_mb: 0,
             is_initialized: false,
@@ -201,19 +541,23 @@ impl GpuMonitor {
 
         // First, get GPU info from DXGI
         self.detect_gpu_info();
+        if gpu_monitor_init_cancelled() {
+            return Err("GPU monitor initialization was cancelled".to_string());
+        }
 
         unsafe {
             // Open PDH query
             let mut query: isize = 0;
             let status = PdhOpenQueryW(PCWSTR::null(), 0, &mut query);
             if status != 0 {
-                return Err(format!("PdhOpenQueryW failed: 0x{:08X}", status));
+                return Err(pdh_error("Opening a performance counter query", status));
             }
             self.query_handle = Some(query);
 
-            // Add GPU Engine utilization counter
-            // This counter captures all GPU engines (3D, Copy, Video Encode/Decode, etc.)
-            // We use wildcard to get all engines, then take the max
+            // Add GPU Engine utilization counter. Instances are per process
+            // and engine (3D, Copy, Video Encode/Decode, ...), which lets
+            // the read sum them per engine and filter by PID. The English
+            // name works on every display language.
             let counter_path: Vec<u16> = "\\GPU Engine(*)\\Utilization Percentage\0"
                 .encode_utf16()
                 .collect();
@@ -226,30 +570,35 @@ impl GpuMonitor {
                 &mut counter,
             );
 
+            // No fallback to other GPU Engine counters: "Running Time" is a
+            // cumulative time, not a percentage
             if status != 0 {
-                // GPU counters might not be available (Windows 10 < 1709 or no GPU)
-                // Try alternative counter path
-                let alt_counter_path: Vec<u16> = "\\GPU Engine(*)\\Running Time\0"
-                    .encode_utf16()
-                    .collect();
+                return Err(pdh_error("Adding the GPU Engine utilization counter", status));
+            }
 
-                let status2 = PdhAddEnglishCounterW(
+            self.counter_handle = Some(counter);
+
+            // Per-process GPU memory (optional; same Windows version as the
+            // engine counters, but older drivers may not report it)
+            for (path, slot) in [
+                ("\\GPU Process Memory(*)\\Dedicated Usage\0", &mut self.dedicated_memory_counter),
+                ("\\GPU Process Memory(*)\\Shared Usage\0", &mut self.shared_memory_counter),
+            ] {
+                let wide: Vec<u16> = path.encode_utf16().collect();
+                let mut memory_counter: isize = 0;
+                let status = PdhAddEnglishCounterW(
                     query,
-                    PCWSTR::from_raw(alt_counter_path.as_ptr()),
+                    PCWSTR::from_raw(wide.as_ptr()),
                     0,
-                    &mut counter,
+                    &mut memory_counter,
                 );
-
-                if status2 != 0 {
-                    return Err(format!(
-                        "GPU counters not available (requires Windows 10 1709+): 0x{:08X}",
-                        status
-                    ));
+                if status == 0 {
+                    *slot = Some(memory_counter);
+                } else {
+                    debug!("GPU process memory counter unavailable: 0x{:08X}", status);
                 }
             }
 
-            self.counter_handle = Some(counter);
-
             // Collect initial data (first collection initializes the counters)
             let _ = PdhCollectQueryData(query);
 
@@ -288,35 +637,59 @@ impl GpuMonitor {
         }
     }
 
-    /// Get current GPU usage
-    pub fn get_usage(&self) -> f32 {
+    /// Current GPU usage: the busiest engine overall and among our own
+    /// processes. Collects fresh query data.
+    fn read_usage(&mut self) -> Result<EngineUsage, String> {
+        use windows::Win32::System::Performance::PdhCollectQueryData;
+
+        let (Some(query), Some(counter)) = (self.query_handle, self.counter_handle) else {
+            return Err("GPU counters are not initialized".to_string());
+        };
+
+        // SAFETY: the query handle stays open until drop
+        let status = unsafe { PdhCollectQueryData(query) };
+        if status != 0 {
+            return Err(pdh_error("Collecting GPU counter data", status));
+        }
+        let samples = Self::read_counter_instances(counter)?;
+
+        if self.own_pids.is_empty() {
+            self.refresh_own_pids();
+        }
+        let mut usage = aggregate_engine_usage(&samples, &self.own_pids)?;
+        if usage.ours.is_none() && !samples.is_empty() {
+            // WebView2 processes restarted since the last lookup
+            self.refresh_own_pids();
+            usage = aggregate_engine_usage(&samples, &self.own_pids)?;
+        }
+        Ok(usage)
+    }
+
+    fn refresh_own_pids(&mut self) {
+        self.own_pids = std::iter::once(std::process::id())
+            .chain(crate::rendering::webview_processes().into_iter().map(|p| p.pid))
+            .collect();
+    }
+
+    /// Get current GPU usage (busiest engine, all processes)
+    pub fn get_usage(&mut self) -> f32 {
         if !self.is_initialized {
             return 0.0;
         }
+        self.read_usage().map_or(0.0, |usage| usage.system)
+    }
 
-        let (query, counter) = match (self.query_handle, self.counter_handle) {
-            (Some(q), Some(c)) => (q, c),
-            _ => return 0.0,
-        };
-
+    /// Read every instance of a wildcard counter as (instance name, value).
+    /// Uses the data from the last `PdhCollectQueryData`.
+    pub(crate) fn read_counter_instances(counter: isize) -> Result<Vec<(String, f64)>, String> {
         use windows::Win32::System::Performance::{
-            PdhCollectQueryData, PdhGetFormattedCounterArrayW,
-            PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE,
+            PdhGetFormattedCounterArrayW, PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE,
         };
+        const PDH_MORE_DATA_VALUE: u32 = 0x800007D2;
 
         unsafe {
-            // Collect fresh data
-            let status = PdhCollectQueryData(query);
-            if status != 0 {
-                debug!("PdhCollectQueryData failed: 0x{:08X}", status);
-                return 0.0;
-            }
-
-            // Get the counter values (multiple engines)
             let mut buffer_size: u32 = 0;
             let mut item_count: u32 = 0;
-
-            // First call to get required buffer size
             let status = PdhGetFormattedCounterArrayW(
                 counter,
                 PDH_FMT_DOUBLE,
@@ -324,26 +697,19 @@ impl GpuMonitor {
                 &mut item_count,
                 None,
             );
-
-            // PDH_MORE_DATA (0x800007D2) means we need a bigger buffer
-            const PDH_MORE_DATA_VALUE: u32 = 0x800007D2;
             if status != PDH_MORE_DATA_VALUE && status != 0 {
-                debug!("PdhGetFormattedCounterArrayW size query failed: 0x{:08X}", status);
-                return 0.0;
+                return Err(pdh_error("Reading GPU counters", status));
             }
-
-            if buffer_size == 0 || item_count == 0 {
-                return 0.0;
+            if buffer_size == 0 {
+                return Ok(Vec::new());
             }
 
-            // Allocate buffer and get values
+            // The buffer also holds the instance name strings, so size it in
+            // bytes rather than items
             let item_size = std::mem::size_of::<PDH_FMT_COUNTERVALUE_ITEM_W>();
             let buffer_len = (buffer_size as usize + item_size - 1) / item_size;
-            let mut buffer: Vec<PDH_FMT_COUNTERVALUE_ITEM_W> = vec![
-                std::mem::zeroed();
-                buffer_len.max(item_count as usize)
-            ];
-
+            let mut buffer: Vec<PDH_FMT_COUNTERVALUE_ITEM_W> =
+                vec![std::mem::zeroed(); buffer_len.max(item_count as usize)];
             let status = PdhGetFormattedCounterArrayW(
                 counter,
                 PDH_FMT_DOUBLE,
@@ -351,35 +717,97 @@ impl GpuMonitor {
                 &mut item_count,
                 Some(buffer.as_mut_ptr()),
             );
-
             if status != 0 {
-                debug!("PdhGetFormattedCounterArrayW failed: 0x{:08X}", status);
-                return 0.0;
+                return Err(pdh_error("Reading GPU counters", status));
             }
 
-            // Find the maximum utilization across all GPU engines
-            let mut max_usage: f64 = 0.0;
-            for i in 0..item_count as usize {
-                if i < buffer.len() {
-                    let value = buffer[i].FmtValue.Anonymous.doubleValue;
-                    if value > max_usage {
-                        max_usage = value;
-                    }
-                }
-            }
+            Ok(buffer
+                .iter()
+                .take(item_count as usize)
+                // Instances whose value couldn't be formatted (e.g. an
+                // engine that just went away) carry a non-zero CStatus
+                .filter(|item| item.FmtValue.CStatus == 0)
+                .filter_map(|item| {
+                    let name = item.szName.to_string().ok()?;
+                    Some((name, item.FmtValue.Anonymous.doubleValue))
+                })
+                .collect())
+        }
+    }
 
-            // Clamp to 0-100
-            max_usage.clamp(0.0, 100.0) as f32
+    /// Dedicated and shared GPU memory of the WebView2 GPU process, in MB.
+    /// Call after `read_usage` so the query data is fresh.
+    fn webview_gpu_memory(&mut self) -> (Option<f64>, Option<f64>) {
+        let (Some(dedicated_counter), Some(shared_counter)) =
+            (self.dedicated_memory_counter, self.shared_memory_counter)
+        else {
+            return (None, None);
+        };
+        if self.webview_gpu_pid.is_none() {
+            self.webview_gpu_pid = crate::rendering::webview_processes()
+                .into_iter()
+                .find(|process| process.process_type() == Some("gpu-process"))
+                .map(|process| process.pid);
         }
+        let Some(pid) = self.webview_gpu_pid else {
+            return (None, None);
+        };
+
+        // Instances are named "pid_<pid>_luid_<adapter>_phys_<n>"; a process
+        // can have one per adapter
+        let prefix = format!("pid_{}_", pid);
+        let total_mb = |counter: isize| {
+            let values: Vec<f64> = Self::read_counter_instances(counter)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(name, bytes)| name.starts_with(&prefix) && valid_memory_bytes(*bytes))
+                .map(|(_, bytes)| bytes)
+                .collect();
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / (1024.0 * 1024.0))
+        };
+        let dedicated = total_mb(dedicated_counter);
+        let shared = total_mb(shared_counter);
+        if dedicated.is_none() && shared.is_none() {
+            // GPU process restarted (or not started yet): look it up again
+            self.webview_gpu_pid = None;
+        }
+        (dedicated, shared)
     }
 
     /// Get full GPU stats
-    pub fn get_stats(&self) -> GpuStats {
+    pub fn get_stats(&mut self) -> GpuStats {
+        if !self.is_initialized {
+            return GpuStats {
+                name: self.gpu_name.clone(),
+                vram_total_mb: self.vram_mb,
+                error: self.last_error.clone(),
+                ..Default::default()
+            };
+        }
+
+        let usage = match self.read_usage() {
+            Ok(usage) => {
+                self.last_error = None;
+                Some(usage)
+            }
+            Err(e) => {
+                if self.last_error.as_deref() != Some(e.as_str()) {
+                    debug!("GPU counters unavailable: {}", e);
+                }
+                self.last_error = Some(e);
+                None
+            }
+        };
+        let (webview_vram_dedicated_mb, webview_vram_shared_mb) = self.webview_gpu_memory();
         GpuStats {
-            usage_percent: self.get_usage(),
+            usage_percent: usage.map_or(0.0, |u| u.system),
+            webview_usage_percent: usage.and_then(|u| u.ours),
             name: self.gpu_name.clone(),
             vram_total_mb: self.vram_mb,
-            available: self.is_initialized,
+            webview_vram_dedicated_mb,
+            webview_vram_shared_mb,
+            available: usage.is_some(),
+            initializing: false,
             error: self.last_error.clone(),
         }
     }
@@ -391,6 +819,129 @@ impl GpuMonitor {
     }
 }
 
+/// Utilization slack above 100% from sampling jitter
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const UTILIZATION_TOLERANCE: f64 = 5.0;
+
+/// Busiest GPU engine, in percent
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EngineUsage {
+    system: f32,
+    /// `None` when none of the given PIDs uses the GPU
+    ours: Option<f32>,
+}
+
+/// PID of a `GPU Engine` / `GPU Process Memory` instance,
+/// "pid_<pid>_luid_<adapter>_phys_<n>[_eng_<n>_engtype_<type>]"
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn instance_pid(name: &str) -> Option<(u32, &str)> {
+    let rest = name.strip_prefix("pid_")?;
+    let (pid, engine) = rest.split_once('_')?;
+    Some((pid.parse().ok()?, engine))
+}
+
+/// Sum `GPU Engine` utilization per engine across processes and take the
+/// busiest engine, overall and for `pids`. Values outside 0-100% are
+/// dropped; an error says why nothing usable was left.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn aggregate_engine_usage(samples: &[(String, f64)], pids: &[u32]) -> Result<EngineUsage, String> {
+    use std::collections::HashMap;
+
+    let mut system: HashMap<&str, f64> = HashMap::new();
+    let mut ours: HashMap<&str, f64> = HashMap::new();
+    let mut rejected = None;
+    for (name, value) in samples {
+        let Some((pid, engine)) = instance_pid(name) else {
+            rejected = Some(format!("unexpected instance name \"{}\"", name));
+            continue;
+        };
+        if !value.is_finite() || *value < 0.0 || *value > 100.0 + UTILIZATION_TOLERANCE {
+            rejected = Some(format!("{:.1}%", value));
+            continue;
+        }
+        *system.entry(engine).or_default() += value;
+        if pids.contains(&pid) {
+            *ours.entry(engine).or_default() += value;
+        }
+    }
+
+    if system.is_empty() {
+        return Err(match rejected {
+            Some(example) => format!(
+                "GPU Engine counters report only out-of-range values (e.g. {})",
+                example
+            ),
+            None => "GPU Engine counters have no instances; the display driver \
+                     does not publish them (WDDM 2.0 or later is required)"
+                .to_string(),
+        });
+    }
+    let busiest = |engines: &HashMap<&str, f64>| {
+        engines.values().fold(0.0_f64, |max, &v| max.max(v)).min(100.0) as f32
+    };
+    Ok(EngineUsage {
+        system: busiest(&system),
+        ours: (!ours.is_empty()).then(|| busiest(&ours)),
+    })
+}
+
+/// GPU memory values above this are counter garbage
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn valid_memory_bytes(bytes: f64) -> bool {
+    const MAX_BYTES: f64 = 1024.0 * 1024.0 * 1024.0 * 1024.0;
+    bytes.is_finite() && (0.0..MAX_BYTES).contains(&bytes)
+}
+
+/// Describe a failed PDH call, naming the likely cause for common statuses
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn pdh_error(action: &str, status: u32) -> String {
+    let cause = match status {
+        // PDH_CSTATUS_NO_OBJECT
+        0xC0000BB8 => "the GPU Engine counter set is not registered (requires Windows 10 1709 or later; otherwise `lodctr /R` rebuilds the counter registry)",
+        // PDH_CSTATUS_NO_COUNTER, PDH_CSTATUS_BAD_COUNTERNAME
+        0xC0000BB9 | 0xC0000BC0 => "the counter does not exist on this system",
+        // PDH_ACCESS_DENIED
+        0xC0000BDB => "performance counters are disabled or access is denied",
+        // PDH_NO_DATA
+        0x800007D5 => "no data yet",
+        // PDH_INVALID_DATA
+        0xC0000BC6 => "the counter returned invalid data",
+        _ => "unexpected PDH status",
+    };
+    format!("{} failed: {} (PDH 0x{:08X})", action, cause, status)
+}
+
+/// Whether the GPU Engine counters can be added to a query (startup
+/// preflight; the query is closed again) (Windows)
+#[cfg(target_os = "windows")]
+pub(crate) fn check_gpu_counters() -> Result<(), String> {
+    use windows::core::w;
+    use windows::Win32::System::Performance::{
+        PdhAddEnglishCounterW, PdhCloseQuery, PdhOpenQueryW,
+    };
+
+    unsafe {
+        let mut query: isize = 0;
+        let status = PdhOpenQueryW(windows::core::PCWSTR::null(), 0, &mut query);
+        if status != 0 {
+            return Err(pdh_error("Opening a performance counter query", status));
+        }
+        let mut counter: isize = 0;
+        let status = PdhAddEnglishCounterW(
+            query,
+            w!("\\GPU Engine(*)\\Utilization Percentage"),
+            0,
+            &mut counter,
+        );
+        let _ = PdhCloseQuery(query);
+        if status != 0 {
+            return Err(pdh_error("Adding the GPU Engine utilization counter", status));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(target_os = "windows")]
 impl Default for GpuMonitor {
     fn default() -> Self {
@@ -474,12 +1025,16 @@ impl GpuMonitor {
     }
 
     /// Get full GPU stats
-    pub fn get_stats(&self) -> GpuStats {
+    pub fn get_stats(&mut self) -> GpuStats {
         GpuStats {
             usage_percent: 0.0,
+            webview_usage_percent: None,
             name: self.gpu_name.clone(),
             vram_total_mb: 0, // Would need vendor-specific tools
+            webview_vram_dedicated_mb: None,
+            webview_vram_shared_mb: None,
             available: false, // Usage monitoring not available
+            initializing: false,
             error: Some("GPU usage monitoring not available on Linux".to_string()),
         }
     }
@@ -497,21 +1052,72 @@ impl Default for GpuMonitor {
     }
 }
 
-// Global GPU monitor instance (lazy initialized)
+// Global GPU monitor instance, set up by a background thread: DXGI and PDH
+// setup takes long enough to hitch whatever thread asks first
 static GPU_MONITOR: std::sync::OnceLock<Mutex<GpuMonitor>> = std::sync::OnceLock::new();
+static GPU_MONITOR_INIT_STARTED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+static GPU_MONITOR_INIT_CANCELLED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
 
-/// Get or initialize the global GPU monitor
-pub fn get_gpu_monitor() -> &'static Mutex<GpuMonitor> {
-    GPU_MONITOR.get_or_init(|| {
-        info!("Initializing GPU monitor");
-        Mutex::new(GpuMonitor::new())
-    })
+/// Start setting up the GPU monitor in the background; called at startup,
+/// and by the first stats read if that comes earlier
+pub fn spawn_gpu_monitor_init() {
+    use std::sync::atomic::Ordering;
+
+    if GPU_MONITOR_INIT_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::Builder::new()
+        .name("gpu-monitor-init".to_string())
+        .spawn(|| {
+            let started = Instant::now();
+            let monitor = GpuMonitor::new();
+            if gpu_monitor_init_cancelled() {
+                debug!("GPU monitor initialization cancelled");
+                return;
+            }
+            let _ = GPU_MONITOR.set(Mutex::new(monitor));
+            info!("GPU monitor ready after {} ms", started.elapsed().as_millis());
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| {
+            GPU_MONITOR_INIT_STARTED.store(false, Ordering::SeqCst);
+            warn!("Failed to start GPU monitor initialization: {}", e);
+        });
+}
+
+/// Stop a pending initialization before it opens PDH queries (app exit)
+pub fn cancel_gpu_monitor_init() {
+    GPU_MONITOR_INIT_CANCELLED.store(true, std::sync::atomic::Ordering::SeqCst);
 }
 
-/// Get current GPU stats (convenience function)
+fn gpu_monitor_init_cancelled() -> bool {
+    GPU_MONITOR_INIT_CANCELLED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// The global GPU monitor; `None` while it is being set up
+pub fn get_gpu_monitor() -> Option<&'static Mutex<GpuMonitor>> {
+    let monitor = GPU_MONITOR.get();
+    if monitor.is_none() {
+        spawn_gpu_monitor_init();
+    }
+    monitor
+}
+
+/// Get current GPU stats (convenience function). Doesn't wait for the
+/// monitor: reports `initializing` until it is ready.
 pub fn get_gpu_stats() -> GpuStats {
-    match get_gpu_monitor().lock() {
-        Ok(monitor) => monitor.get_stats(),
+    let Some(monitor) = get_gpu_monitor() else {
+        let cancelled = gpu_monitor_init_cancelled();
+        return GpuStats {
+            initializing: !cancelled,
+            error: cancelled.then(|| "GPU monitor initialization was cancelled".to_string()),
+            ..Default::default()
+        };
+    };
+    match monitor.lock() {
+        Ok(mut monitor) => monitor.get_stats(),
         Err(e) => {
             warn!("Failed to lock GPU monitor: {}", e);
             GpuStats {
@@ -524,8 +1130,9 @@ pub fn get_gpu_stats() -> GpuStats {
 
 // ==================== HDR Support ====================
 
-/// HDR display information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// HDR display information. The top-level fields describe one display:
+/// the game window's when known, otherwise the first with HDR on.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct HdrInfo {
     /// Whether HDR is supported by the display
     pub supported: bool,
@@ -543,6 +1150,9 @@ pub struct HdrInfo {
     pub min_luminance: f32,
     /// Maximum full-frame luminance in nits
     pub max_full_frame_luminance: f32,
+    /// Every display output, in DXGI enumeration order
+    #[serde(default)]
+    pub outputs: Vec<HdrOutput>,
     /// Error message if detection failed
     pub error: Option<String>,
 }
@@ -558,14 +1168,60 @@ impl Default for HdrInfo {
             max_luminance: 0.0,
             min_luminance: 0.0,
             max_full_frame_luminance: 0.0,
+            outputs: Vec::new(),
             error: None,
         }
     }
 }
 
-/// Detect HDR capability and status for all displays (Windows)
+/// HDR state of one display output
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+pub struct HdrOutput {
+    /// GDI device name, e.g. `\\.\DISPLAY1` (what Tauri's `Monitor::name` reports)
+    pub display_name: String,
+    /// HDR color space active, or more than 8 bits per color
+    pub supported: bool,
+    pub enabled: bool,
+    pub color_space: String,
+    pub bits_per_color: u32,
+    pub max_luminance: f32,
+    pub min_luminance: f32,
+    pub max_full_frame_luminance: f32,
+}
+
+/// Summarize `outputs` for the display named `monitor` (the game window's),
+/// falling back to the first with HDR on, then the first that supports it,
+/// then the first
+fn summarize_hdr(outputs: Vec<HdrOutput>, monitor: Option<&str>) -> HdrInfo {
+    let chosen = monitor
+        .and_then(|name| outputs.iter().find(|o| o.display_name == name))
+        .or_else(|| outputs.iter().find(|o| o.enabled))
+        .or_else(|| outputs.iter().find(|o| o.supported))
+        .or_else(|| outputs.first())
+        .cloned();
+    let Some(chosen) = chosen else {
+        return HdrInfo {
+            error: Some("No display outputs found".to_string()),
+            ..Default::default()
+        };
+    };
+    HdrInfo {
+        supported: chosen.supported,
+        enabled: chosen.enabled,
+        color_space: chosen.color_space,
+        bits_per_color: chosen.bits_per_color,
+        display_name: chosen.display_name,
+        max_luminance: chosen.max_luminance,
+        min_luminance: chosen.min_luminance,
+        max_full_frame_luminance: chosen.max_full_frame_luminance,
+        outputs,
+        error: None,
+    }
+}
+
+/// Detect HDR capability and status of every display output (Windows)
 #[cfg(target_os = "windows")]
-pub fn detect_hdr_info() -> HdrInfo {
+fn detect_hdr_outputs() -> Result<Vec<HdrOutput>, String> {
     use windows::core::Interface;
     use windows::Win32::Graphics::Dxgi::{
         CreateDXGIFactory1, IDXGIFactory1, IDXGIOutput6,
@@ -575,17 +1231,13 @@ pub fn detect_hdr_info() -> HdrInfo {
         DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709,
     };
 
-    let mut info = HdrInfo::default();
+    let mut outputs = Vec::new();
 
     unsafe {
-        // Create DXGI factory
-        let factory: IDXGIFactory1 = match CreateDXGIFactory1() {
-            Ok(f) => f,
-            Err(e) => {
-                info.error = Some(format!("Failed to create DXGI factory: {:?}", e));
-                return info;
-            }
-        };
+        // A new factory each time: a factory's output list is fixed at
+        // creation and goes stale when displays change
+        let factory: IDXGIFactory1 = CreateDXGIFactory1()
+            .map_err(|e| format!("Failed to create DXGI factory: {:?}", e))?;
 
         // Enumerate adapters
         let mut adapter_index = 0u32;
@@ -593,116 +1245,106 @@ pub fn detect_hdr_info() -> HdrInfo {
             // Enumerate outputs for this adapter
             let mut output_index = 0u32;
             while let Ok(output) = adapter.EnumOutputs(output_index) {
-                // Try to get IDXGIOutput6 for HDR info
-                let output6_result: Result<IDXGIOutput6, _> = output.cast();
-                if let Ok(output6) = output6_result {
-                    if let Ok(desc1) = output6.GetDesc1() {
-                        // Get display name
-                        let name: String = desc1.DeviceName.iter()
-                            .take_while(|&&c| c != 0)
-                            .map(|&c| char::from_u32(c as u32).unwrap_or('?'))
-                            .collect();
-
-                        // Check HDR support via color space
-                        // HDR10 uses DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020
-                        let is_hdr = desc1.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020
-                            || desc1.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709;
-
-                        let color_space_name = match desc1.ColorSpace {
-                            DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020 => "HDR10 (BT.2020 PQ)",
-                            DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709 => "scRGB (Linear)",
-                            _ => "SDR (sRGB)",
-                        };
-
-                        // If this output supports HDR, use it
-                        if is_hdr || desc1.BitsPerColor > 8 {
-                            info.supported = true;
-                            info.enabled = is_hdr;
-                            info.color_space = color_space_name.to_string();
-                            info.bits_per_color = desc1.BitsPerColor;
-                            info.display_name = name.trim().to_string();
-                            info.max_luminance = desc1.MaxLuminance;
-                            info.min_luminance = desc1.MinLuminance;
-                            info.max_full_frame_luminance = desc1.MaxFullFrameLuminance;
-
-                            debug!(
-                                "HDR display found: {} - {} ({}-bit), Max: {} nits",
-                                info.display_name,
-                                info.color_space,
-                                info.bits_per_color,
-                                info.max_luminance
-                            );
-
-                            return info;
-                        }
-
-                        // Update with first display info even if not HDR
-                        if info.display_name == "Unknown" {
-                            info.display_name = name.trim().to_string();
-                            info.bits_per_color = desc1.BitsPerColor;
-                            info.max_luminance = desc1.MaxLuminance;
-                            info.min_luminance = desc1.MinLuminance;
-                            info.max_full_frame_luminance = desc1.MaxFullFrameLuminance;
-                        }
-                    }
-                }
                 output_index += 1;
+                // IDXGIOutput6 (DXGI 1.6) carries the HDR info
+                let Ok(output6) = output.cast::<IDXGIOutput6>() else {
+                    continue;
+                };
+                let Ok(desc1) = output6.GetDesc1() else {
+                    continue;
+                };
+                let name: String = desc1.DeviceName.iter()
+                    .take_while(|&&c| c != 0)
+                    .map(|&c| char::from_u32(c as u32).unwrap_or('?'))
+                    .collect();
+
+                // HDR10 uses DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020
+                let is_hdr = desc1.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020
+                    || desc1.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709;
+                let color_space_name = match desc1.ColorSpace {
+                    DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020 => "HDR10 (BT.2020 PQ)",
+                    DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709 => "scRGB (Linear)",
+                    _ => "SDR (sRGB)",
+                };
+
+                let output = HdrOutput {
+                    display_name: name.trim().to_string(),
+                    supported: is_hdr || desc1.BitsPerColor > 8,
+                    enabled: is_hdr,
+                    color_space: color_space_name.to_string(),
+                    bits_per_color: desc1.BitsPerColor,
+                    max_luminance: desc1.MaxLuminance,
+                    min_luminance: desc1.MinLuminance,
+                    max_full_frame_luminance: desc1.MaxFullFrameLuminance,
+                };
+                debug!(
+                    "Display {}: {} ({}-bit), Max: {} nits",
+                    output.display_name,
+                    output.color_space,
+                    output.bits_per_color,
+                    output.max_luminance
+                );
+                outputs.push(output);
             }
             adapter_index += 1;
         }
     }
 
-    debug!("HDR status: supported={}, enabled={}", info.supported, info.enabled);
-    info
+    Ok(outputs)
 }
 
 /// Detect HDR capability (Linux - not available)
 #[cfg(target_os = "linux")]
-pub fn detect_hdr_info() -> HdrInfo {
+fn detect_hdr_outputs() -> Result<Vec<HdrOutput>, String> {
     // HDR detection on Linux would require:
     // - XRandR properties for X11
     // - Wayland KMS/DRM queries
     // For now, we return a stub indicating HDR detection is not available
-    HdrInfo {
-        error: Some("HDR detection not available on Linux".to_string()),
-        ..Default::default()
-    }
+    Err("HDR detection not available on Linux".to_string())
+}
+
+/// Detect HDR capability and status for all displays
+pub fn detect_hdr_info() -> HdrInfo {
+    let info = match detect_hdr_outputs() {
+        Ok(outputs) => summarize_hdr(outputs, None),
+        Err(e) => HdrInfo {
+            error: Some(e),
+            ..Default::default()
+        },
+    };
+    debug!("HDR status: supported={}, enabled={}", info.supported, info.enabled);
+    info
 }
 
-/// Get cached HDR info (for frequent queries)
+/// Get cached HDR info (for frequent queries); refreshed on display changes
+/// (display_watch.rs)
 static HDR_INFO: std::sync::OnceLock<Mutex<HdrInfo>> = std::sync::OnceLock::new();
 
-/// Get or refresh HDR info
-pub fn get_hdr_info() -> HdrInfo {
-    // Initialize on first call
-    let hdr_lock = HDR_INFO.get_or_init(|| {
+fn hdr_cache() -> &'static Mutex<HdrInfo> {
+    HDR_INFO.get_or_init(|| {
         info!("Detecting HDR capability");
         Mutex::new(detect_hdr_info())
-    });
+    })
+}
 
-    match hdr_lock.lock() {
-        Ok(info) => info.clone(),
-        Err(e) => {
-            warn!("Failed to lock HDR info: {}", e);
-            HdrInfo {
-                error: Some("Lock failed".to_string()),
-                ..Default::default()
-            }
-        }
+/// Get cached HDR info
+pub fn get_hdr_info() -> HdrInfo {
+    hdr_cache().lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Cached HDR info, summarized for the display named `monitor`
+pub fn get_hdr_info_on(monitor: Option<&str>) -> HdrInfo {
+    let info = get_hdr_info();
+    if info.error.is_some() || monitor.is_none() {
+        return info;
     }
+    summarize_hdr(info.outputs, monitor)
 }
 
-/// Refresh HDR info (call when display settings change)
-#[allow(dead_code)]
+/// Re-detect HDR info (call when display settings change)
 pub fn refresh_hdr_info() -> HdrInfo {
     let new_info = detect_hdr_info();
-
-    if let Some(hdr_lock) = HDR_INFO.get() {
-        if let Ok(mut info) = hdr_lock.lock() {
-            *info = new_info.clone();
-        }
-    }
-
+    *hdr_cache().lock().unwrap_or_else(|e| e.into_inner()) = new_info.clone();
     new_info
 }
 
@@ -717,6 +1359,16 @@ struct ProcessStartTrace {
     parent_process_id: u32,
 }
 
+/// WMI event structure for process stop trace (Windows only)
+/// Maps to Win32_ProcessStopTrace WMI class
+#[cfg(target_os = "windows")]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct ProcessStopTrace {
+    process_id: u32,
+    exit_status: u32,
+}
+
 /// Enable per-monitor DPI awareness for crisp rendering on high-DPI displays (Windows)
 #[cfg(target_os = "windows")]
 fn enable_dpi_awareness() {
@@ -767,45 +1419,323 @@ pub fn apply_system_optimizations() {
     disable_power_throttling();
 
     // Enable 1ms timer resolution
+    set_high_timer_resolution(true);
+
+    // Start background thread to optimize WebView2 child processes
+    start_webview_optimizer();
+}
+
+/// Apply Linux system optimizations: the same treatment as on Windows, for
+/// PACDeluxe and its WebKitGTK processes (web, network and GPU)
+#[cfg(target_os = "linux")]
+pub fn apply_system_optimizations() {
+    info!("Applying Linux performance optimizations");
+
+    if tune_linux_process(std::process::id(), false) {
+        info!("Set process nice value to {} (elevated priority)", LINUX_NICE);
+    } else {
+        // This is expected to fail without CAP_SYS_NICE.
+        info!("Running at normal priority (elevated priority requires CAP_SYS_NICE)");
+    }
+
+    start_webkit_optimizer();
+
+    info!("Linux optimizations applied (DPI/scaling handled by window manager)");
+}
+
+/// Nice value while not saving power: -20 (highest priority) to 19
+/// (lowest), 0 is default. Negative values need CAP_SYS_NICE. (Linux)
+#[cfg(target_os = "linux")]
+const LINUX_NICE: i32 = -5;
+
+/// Best-effort I/O priority level while not saving power: 0 (highest) to 7,
+/// 4 is default (Linux)
+#[cfg(target_os = "linux")]
+const LINUX_IO_LEVEL: i32 = 2;
+
+// From linux/ioprio.h
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_SHIFT: i32 = 13;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_BE: i32 = 2;
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: i32 = 1;
+
+/// Whether the WebKitGTK optimizer thread is running (Linux)
+#[cfg(target_os = "linux")]
+static LINUX_OPTIMIZER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Power saving is on; processes found later are tuned for it (Linux)
+#[cfg(target_os = "linux")]
+static LINUX_POWER_SAVING: AtomicBool = AtomicBool::new(false);
+
+/// Tuned WebKitGTK PIDs that are still running (Linux)
+#[cfg(target_os = "linux")]
+static LINUX_TUNED: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// WebKitGTK processes tuned this run (Linux)
+#[cfg(target_os = "linux")]
+static LINUX_TUNED_TOTAL: AtomicU32 = AtomicU32::new(0);
+
+/// Nice value, I/O priority and scheduling policy of every thread of `pid`.
+/// Nice values are per thread on Linux, and threads started later inherit
+/// them. Returns whether the nice value could be set. (Linux)
+#[cfg(target_os = "linux")]
+fn tune_linux_process(pid: u32, saving: bool) -> bool {
+    use nix::libc;
+
+    let (nice, io_level) = if saving { (0, 4) } else { (LINUX_NICE, LINUX_IO_LEVEL) };
+    let threads: Vec<u32> = std::fs::read_dir(format!("/proc/{}/task", pid))
+        .map(|tasks| {
+            tasks
+                .flatten()
+                .filter_map(|task| task.file_name().to_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_else(|_| vec![pid]);
+
+    let mut nice_set = false;
+    for tid in threads {
+        // SAFETY: plain syscalls on a thread ID; failures are reported
+        // through the return value
+        unsafe {
+            // A launcher can hand down SCHED_BATCH or SCHED_IDLE (chrt,
+            // systemd CPUSchedulingPolicy=), which treats the game as
+            // background work
+            let policy = libc::sched_getscheduler(tid as libc::pid_t);
+            if policy == libc::SCHED_BATCH || policy == libc::SCHED_IDLE {
+                let param = libc::sched_param { sched_priority: 0 };
+                if libc::sched_setscheduler(tid as libc::pid_t, libc::SCHED_OTHER, &param) != 0 {
+                    debug!(
+                        "Could not reset scheduling policy of thread {}: {}",
+                        tid,
+                        std::io::Error::last_os_error()
+                    );
+                }
+            }
+
+            let ioprio = (IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | io_level;
+            if libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid as libc::c_int, ioprio) != 0 {
+                debug!(
+                    "Could not set I/O priority of thread {}: {}",
+                    tid,
+                    std::io::Error::last_os_error()
+                );
+            }
+
+            if libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) == 0 {
+                nice_set = true;
+            }
+        }
+    }
+    if !nice_set {
+        debug!(
+            "Could not set nice value {} for process {} (requires CAP_SYS_NICE or root)",
+            nice, pid
+        );
+    }
+    nice_set
+}
+
+/// Command name and parent PID from the contents of `/proc/<pid>/stat`
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_stat(stat: &str) -> Option<(&str, u32)> {
+    // The name is in parentheses and may itself contain spaces or ')'
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?;
+    let mut fields = stat.get(close + 1..)?.split_whitespace();
+    let _state = fields.next()?;
+    let parent = fields.next()?.parse().ok()?;
+    Some((name, parent))
+}
+
+/// Whether `pid` descends from `ancestor` in a PID to parent PID map
+#[cfg(any(target_os = "linux", test))]
+fn descends_from(
+    parents: &std::collections::HashMap<u32, u32>,
+    pid: u32,
+    ancestor: u32,
+) -> bool {
+    let mut current = pid;
+    // Bounded: a PID reused while scanning could form a loop
+    for _ in 0..64 {
+        match parents.get(&current) {
+            Some(&parent) if parent == ancestor => return true,
+            Some(&parent) if parent > 1 => current = parent,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// WebKitGTK processes started by PACDeluxe, directly or through the
+/// bubblewrap sandbox (Linux)
+#[cfg(target_os = "linux")]
+fn find_webkit_processes() -> Vec<u32> {
+    let our_pid = std::process::id();
+    let mut parents = std::collections::HashMap::new();
+    let mut webkit = Vec::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return webkit;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        if let Some((name, parent)) = parse_proc_stat(&stat) {
+            parents.insert(pid, parent);
+            // WebKitWebProcess, WebKitNetworkProcess, WebKitGPUProcess
+            // (truncated to 15 characters)
+            if name.starts_with("WebKit") {
+                webkit.push(pid);
+            }
+        }
+    }
+    webkit.retain(|&pid| descends_from(&parents, pid, our_pid));
+    webkit
+}
+
+/// Tune WebKitGTK processes as they appear, checking every 5 seconds (Linux)
+#[cfg(target_os = "linux")]
+fn start_webkit_optimizer() {
+    if LINUX_OPTIMIZER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let started = std::thread::Builder::new()
+        .name("webkit-optimizer".to_string())
+        .spawn(|| loop {
+            let running = find_webkit_processes();
+            let saving = LINUX_POWER_SAVING.load(Ordering::SeqCst);
+            let mut tuned = LINUX_TUNED.lock().unwrap_or_else(|e| e.into_inner());
+            // Exited processes are forgotten, so a reused PID is tuned again
+            tuned.retain(|pid| running.contains(pid));
+            for pid in running {
+                if !tuned.contains(&pid) {
+                    tune_linux_process(pid, saving);
+                    debug!("Tuned WebKitGTK process {}", pid);
+                    tuned.push(pid);
+                    LINUX_TUNED_TOTAL.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            drop(tuned);
+            std::thread::sleep(Duration::from_secs(5));
+        });
+    if let Err(e) = started {
+        warn!("Failed to start WebKitGTK optimizer: {}", e);
+        LINUX_OPTIMIZER_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Whether the 1ms timer resolution is currently requested (Windows)
+#[cfg(target_os = "windows")]
+static HIGH_TIMER_RESOLUTION: AtomicBool = AtomicBool::new(false);
+
+/// Request or release the 1ms timer resolution (Windows)
+#[cfg(target_os = "windows")]
+pub fn set_high_timer_resolution(enabled: bool) {
     #[link(name = "winmm")]
     extern "system" {
         fn timeBeginPeriod(uPeriod: u32) -> u32;
+        fn timeEndPeriod(uPeriod: u32) -> u32;
     }
 
+    if HIGH_TIMER_RESOLUTION.swap(enabled, Ordering::SeqCst) == enabled {
+        return;
+    }
     unsafe {
-        if timeBeginPeriod(1) == 0 {
-            debug!("Enabled 1ms timer resolution");
-            register_timer_cleanup();
+        if enabled {
+            if timeBeginPeriod(1) == 0 {
+                debug!("Enabled 1ms timer resolution");
+                register_timer_cleanup();
+            } else {
+                HIGH_TIMER_RESOLUTION.store(false, Ordering::SeqCst);
+            }
+        } else {
+            timeEndPeriod(1);
+            debug!("Released 1ms timer resolution");
         }
     }
+}
 
-    // Start background thread to optimize WebView2 child processes
-    start_webview_optimizer();
+#[cfg(target_os = "linux")]
+pub fn set_high_timer_resolution(_enabled: bool) {}
+
+/// Whether the 1ms timer resolution is held right now (Windows)
+#[cfg(target_os = "windows")]
+pub fn high_timer_resolution_active() -> bool {
+    HIGH_TIMER_RESOLUTION.load(Ordering::SeqCst)
 }
 
-/// Apply Linux system optimizations
 #[cfg(target_os = "linux")]
-pub fn apply_system_optimizations() {
-    info!("Applying Linux performance optimizations");
+pub fn high_timer_resolution_active() -> bool {
+    false
+}
 
-    // Nice values: -20 (highest priority) to 19 (lowest priority), 0 is default.
-    // Increasing priority (negative nice) requires CAP_SYS_NICE capability.
-    let target_nice = -5;
-    let result = unsafe { nix::libc::setpriority(nix::libc::PRIO_PROCESS, 0, target_nice) };
+/// Switch the process between the normal optimizations and a power-saving
+/// state: normal priority, power throttling allowed (EcoQoS) and the
+/// default timer resolution (Windows)
+#[cfg(target_os = "windows")]
+pub fn set_power_saving(saving: bool) {
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, SetPriorityClass, SetProcessInformation, ProcessPowerThrottling,
+        ABOVE_NORMAL_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+        PROCESS_POWER_THROTTLING_STATE, PROCESS_POWER_THROTTLING_EXECUTION_SPEED,
+    };
 
-    if result == 0 {
-        info!("Set process nice value to {} (elevated priority)", target_nice);
-    } else {
-        let e = std::io::Error::last_os_error();
-        // This is expected to fail without CAP_SYS_NICE.
-        debug!(
-            "Could not set elevated priority (requires CAP_SYS_NICE or root): {:?}",
-            e
-        );
-        info!("Running at normal priority (elevated priority requires CAP_SYS_NICE)");
+    unsafe {
+        let process = GetCurrentProcess();
+        let priority = if saving { NORMAL_PRIORITY_CLASS } else { ABOVE_NORMAL_PRIORITY_CLASS };
+        if let Err(e) = SetPriorityClass(process, priority) {
+            warn!("Failed to set process priority: {:?}", e);
+        }
+
+        if saving {
+            let state = PROCESS_POWER_THROTTLING_STATE {
+                Version: 1, // PROCESS_POWER_THROTTLING_CURRENT_VERSION
+                ControlMask: PROCESS_POWER_THROTTLING_EXECUTION_SPEED,
+                StateMask: PROCESS_POWER_THROTTLING_EXECUTION_SPEED, // opt in to EcoQoS
+            };
+            if SetProcessInformation(
+                process,
+                ProcessPowerThrottling,
+                &state as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<PROCESS_POWER_THROTTLING_STATE>() as u32,
+            )
+            .is_err()
+            {
+                warn!("Failed to enable power throttling");
+            }
+        }
     }
+    if !saving {
+        disable_power_throttling();
+    }
+    set_high_timer_resolution(!saving);
+    info!("Power saving {}", if saving { "on" } else { "off" });
+}
 
-    info!("Linux optimizations applied (DPI/scaling handled by window manager)");
+/// Drop back to the default nice value and I/O priority while saving
+/// power, for PACDeluxe and its WebKitGTK processes (Linux)
+#[cfg(target_os = "linux")]
+pub fn set_power_saving(saving: bool) {
+    LINUX_POWER_SAVING.store(saving, Ordering::SeqCst);
+    // Going back to -5 needs CAP_SYS_NICE, like at startup
+    let ok = tune_linux_process(std::process::id(), saving);
+    debug!(
+        "Set nice value to {} ({})",
+        if saving { 0 } else { LINUX_NICE },
+        if ok { "ok" } else { "denied" }
+    );
+    let tuned = LINUX_TUNED.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    for pid in tuned {
+        tune_linux_process(pid, saving);
+    }
+    info!("Power saving {}", if saving { "on" } else { "off" });
 }
 
 /// Disable Windows power throttling for the current process
@@ -855,6 +1785,7 @@ fn start_webview_optimizer() {
     // Try WMI event-driven approach first
     if start_wmi_process_watcher() {
         info!("WebView2 optimizer using WMI event-driven monitoring");
+        start_wmi_exit_watcher();
         return;
     }
 
@@ -868,8 +1799,6 @@ fn start_webview_optimizer() {
 #[cfg(target_os = "windows")]
 fn start_wmi_process_watcher() -> bool {
     let our_pid = std::process::id();
-    let optimized_pids = Arc::new(Mutex::new(std::collections::HashSet::<u32>::new()));
-    let optimized_pids_clone = optimized_pids.clone();
 
     // Try to initialize WMI in a separate thread
     let (tx, rx) = std::sync::mpsc::channel();
@@ -895,10 +1824,10 @@ fn start_wmi_process_watcher() -> bool {
             }
         };
 
-        // Subscribe to process start trace events using WMI notification API
+        // Subscribe to WebView2 process start trace events only; the WQL
+        // filter runs in WMI, so other process starts never reach us
         // Win32_ProcessStartTrace requires elevated privileges on some systems
-        // The wmi crate's notification() method subscribes to __InstanceCreationEvent
-        let iter_result = wmi_con.notification::<ProcessStartTrace>();
+        let iter_result = wmi_con.raw_notification::<ProcessStartTrace>(WEBVIEW_START_QUERY);
 
         let mut iter = match iter_result {
             Ok(i) => i,
@@ -910,6 +1839,7 @@ fn start_wmi_process_watcher() -> bool {
         };
 
         // Signal success
+        let _ = WMI_SUBSCRIBED_AT.set(Instant::now());
         WMI_WATCHER_ACTIVE.store(true, Ordering::SeqCst);
         let _ = tx.send(true);
 
@@ -920,6 +1850,8 @@ fn start_wmi_process_watcher() -> bool {
         loop {
             match iter.next() {
                 Some(Ok(event)) => {
+                    WMI_EVENTS.fetch_add(1, Ordering::SeqCst);
+                    // The query already filters; kept for case differences
                     let process_name_lower = event.process_name.to_lowercase();
                     if process_name_lower.contains("msedgewebview2") {
                         debug!(
@@ -932,16 +1864,17 @@ fn start_wmi_process_watcher() -> bool {
                             || is_descendant_of_pid(event.process_id, our_pid);
 
                         if is_our_child {
-                            let mut pids = optimized_pids_clone.lock().unwrap_or_else(|e| e.into_inner());
-                            if !pids.contains(&event.process_id) {
-                                if elevate_single_process(event.process_id) {
-                                    pids.insert(event.process_id);
-                                    info!(
-                                        "WMI: Elevated WebView2 process {} within ~0ms of spawn",
-                                        event.process_id
-                                    );
-                                }
-                            }
+                            crate::resource_limits::add_process(event.process_id);
+                        }
+                        if is_our_child
+                            && !is_live_elevated(event.process_id)
+                            && crate::elevation_policy::admit(event.process_id)
+                            && elevate_single_process(event.process_id)
+                        {
+                            info!(
+                                "WMI: Elevated WebView2 process {} within ~0ms of spawn",
+                                event.process_id
+                            );
                         }
                     }
                 }
@@ -970,26 +1903,74 @@ fn start_wmi_process_watcher() -> bool {
     }
 }
 
+/// Start WMI-based process exit watcher (Windows)
+/// Keeps the live elevated count current; without it exits are found by
+/// checking each elevated process when telemetry is read
+#[cfg(target_os = "windows")]
+fn start_wmi_exit_watcher() {
+    let spawned = std::thread::Builder::new()
+        .name("wmi-exit-watcher".to_string())
+        .spawn(|| {
+            let wmi_con = match COMLibrary::new().and_then(WMIConnection::new) {
+                Ok(w) => w,
+                Err(e) => {
+                    warn!("Failed to connect to WMI for process exits: {:?}", e);
+                    return;
+                }
+            };
+            let iter = match wmi_con.raw_notification::<ProcessStopTrace>(WEBVIEW_STOP_QUERY) {
+                Ok(i) => i,
+                Err(e) => {
+                    warn!("WMI process exit subscription failed: {:?}", e);
+                    return;
+                }
+            };
+
+            WMI_EXIT_WATCHER_ACTIVE.store(true, Ordering::SeqCst);
+            debug!("WMI process exit subscription active");
+            for event in iter {
+                match event {
+                    Ok(event) => {
+                        if is_live_elevated(event.process_id) {
+                            debug!(
+                                "WMI: elevated WebView2 process {} exited with status {}",
+                                event.process_id, event.exit_status
+                            );
+                        }
+                        note_exited(event.process_id);
+                    }
+                    Err(e) => debug!("WMI exit event error: {:?}", e),
+                }
+            }
+
+            WMI_EXIT_WATCHER_ACTIVE.store(false, Ordering::SeqCst);
+            warn!("WMI exit watcher terminated; checking processes instead");
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to start WMI exit watcher: {}", e);
+    }
+}
+
 /// Elevate a single process by PID (Windows)
 /// Returns true if elevation succeeded
 #[cfg(target_os = "windows")]
 fn elevate_single_process(pid: u32) -> bool {
     use windows::Win32::Foundation::CloseHandle;
     use windows::Win32::System::Threading::{
-        OpenProcess, SetPriorityClass, SetProcessPriorityBoost,
-        ABOVE_NORMAL_PRIORITY_CLASS, PROCESS_SET_INFORMATION,
+        OpenProcess, SetPriorityClass, SetProcessPriorityBoost, PROCESS_SET_INFORMATION,
     };
 
     unsafe {
         if let Ok(handle) = OpenProcess(PROCESS_SET_INFORMATION, false, pid) {
             let mut success = false;
 
-            if SetPriorityClass(handle, ABOVE_NORMAL_PRIORITY_CLASS).is_ok() {
-                debug!("Elevated process {} to above-normal priority", pid);
+            let priority = crate::elevation_policy::priority_class(pid);
+            if SetPriorityClass(handle, priority).is_ok() {
+                debug!("Elevated process {} to priority class 0x{:X}", pid, priority.0);
                 success = true;
 
-                // Increment telemetry counter
-                PROCESSES_ELEVATED.fetch_add(1, Ordering::SeqCst);
+                // Telemetry counters, crash history and the elevation cap
+                note_elevated(pid);
             }
 
             // Disable priority boost for consistent timing
@@ -1011,7 +1992,7 @@ fn elevate_single_process(pid: u32) -> bool {
 
 /// Check if a process is a descendant of another by PID only (no snapshot handle) (Windows)
 #[cfg(target_os = "windows")]
-fn is_descendant_of_pid(pid: u32, ancestor_pid: u32) -> bool {
+pub(crate) fn is_descendant_of_pid(pid: u32, ancestor_pid: u32) -> bool {
     use windows::Win32::Foundation::CloseHandle;
     use windows::Win32::System::Diagnostics::ToolHelp::{
         CreateToolhelp32Snapshot, Process32First, Process32Next, PROCESSENTRY32, TH32CS_SNAPPROCESS,
@@ -1075,6 +2056,16 @@ fn start_polling_optimizer() {
         let mut optimized_pids: std::collections::HashSet<u32> = std::collections::HashSet::new();
 
         loop {
+            // Forget exited processes so replacements with a reused PID
+            // (renderers restarted after a crash) are elevated again
+            optimized_pids.retain(|&pid| {
+                let running = crate::process_failures::is_running(pid);
+                if !running {
+                    note_exited(pid);
+                }
+                running
+            });
+
             if let Some(new_pids) = elevate_webview2_processes(&optimized_pids) {
                 for pid in new_pids {
                     optimized_pids.insert(pid);
@@ -1098,8 +2089,7 @@ fn elevate_webview2_processes(already_optimized: &std::collections::HashSet<u32>
         PROCESSENTRY32, TH32CS_SNAPPROCESS,
     };
     use windows::Win32::System::Threading::{
-        OpenProcess, SetPriorityClass, SetProcessPriorityBoost,
-        ABOVE_NORMAL_PRIORITY_CLASS, PROCESS_SET_INFORMATION,
+        OpenProcess, SetPriorityClass, SetProcessPriorityBoost, PROCESS_SET_INFORMATION,
     };
 
     let mut new_pids = Vec::new();
@@ -1136,13 +2126,21 @@ fn elevate_webview2_processes(already_optimized: &std::collections::HashSet<u32>
                            pid, parent_pid, is_child, is_descendant);
                 }
 
-                if is_webview
-                    && (parent_pid == our_pid || is_descendant_of(snapshot, pid, our_pid))
+                let is_ours = is_webview
+                    && (parent_pid == our_pid || is_descendant_of(snapshot, pid, our_pid));
+                if is_ours {
+                    crate::resource_limits::add_process(pid);
+                }
+
+                if is_ours
                     && !already_optimized.contains(&pid)
+                    && crate::elevation_policy::admit(pid)
                 {
                     if let Ok(handle) = OpenProcess(PROCESS_SET_INFORMATION, false, pid) {
-                        if SetPriorityClass(handle, ABOVE_NORMAL_PRIORITY_CLASS).is_ok() {
+                        let priority = crate::elevation_policy::priority_class(pid);
+                        if SetPriorityClass(handle, priority).is_ok() {
                             debug!("Elevated WebView2 process {} priority", pid);
+                            note_elevated(pid);
                             new_pids.push(pid);
                         }
                         // Also disable priority boost for consistent timing
@@ -1243,7 +2241,9 @@ fn register_timer_cleanup() {
             extern "system" {
                 fn timeEndPeriod(uPeriod: u32) -> u32;
             }
-            unsafe { timeEndPeriod(1); }
+            if HIGH_TIMER_RESOLUTION.swap(false, Ordering::SeqCst) {
+                unsafe { timeEndPeriod(1); }
+            }
         }
 
         extern "C" {
@@ -1371,4 +2371,88 @@ mod tests {
         let stats = monitor.get_stats();
         assert!(stats.uptime_secs >= 0.0);
     }
+
+    #[test]
+    fn gpu_engine_usage_is_summed_per_engine_and_validated() {
+        let sample = |name: &str, value: f64| (name.to_string(), value);
+        let samples = [
+            sample("pid_10_luid_0x0_0x1_phys_0_eng_0_engtype_3D", 30.0),
+            sample("pid_20_luid_0x0_0x1_phys_0_eng_0_engtype_3D", 25.0),
+            sample("pid_20_luid_0x0_0x1_phys_0_eng_3_engtype_Copy", 40.0),
+            // Garbage from a broken counter is dropped, not clamped
+            sample("pid_30_luid_0x0_0x1_phys_0_eng_0_engtype_3D", 1.0e9),
+        ];
+        let usage = aggregate_engine_usage(&samples, &[20]).unwrap();
+        assert_eq!(usage.system, 55.0);
+        assert_eq!(usage.ours, Some(40.0));
+        assert_eq!(aggregate_engine_usage(&samples, &[99]).unwrap().ours, None);
+
+        let error = aggregate_engine_usage(&samples[3..], &[]).unwrap_err();
+        assert!(error.contains("out-of-range"), "{}", error);
+        assert!(aggregate_engine_usage(&[], &[]).unwrap_err().contains("no instances"));
+    }
+
+    #[test]
+    fn hdr_summary_follows_the_game_window_display() {
+        let output = |name: &str, enabled: bool| HdrOutput {
+            display_name: name.to_string(),
+            supported: enabled,
+            enabled,
+            color_space: if enabled { "HDR10 (BT.2020 PQ)" } else { "SDR (sRGB)" }.to_string(),
+            bits_per_color: if enabled { 10 } else { 8 },
+            max_luminance: 400.0,
+            min_luminance: 0.1,
+            max_full_frame_luminance: 300.0,
+        };
+        let outputs = vec![output("\\\\.\\DISPLAY1", false), output("\\\\.\\DISPLAY2", true)];
+
+        let on_first = summarize_hdr(outputs.clone(), Some("\\\\.\\DISPLAY1"));
+        assert!(!on_first.enabled);
+        assert_eq!(on_first.outputs.len(), 2);
+        // Unknown window display: the HDR one, not just the first
+        assert!(summarize_hdr(outputs, None).enabled);
+        assert!(summarize_hdr(Vec::new(), None).error.is_some());
+    }
+
+    #[test]
+    fn snapshot_is_shared_until_it_ages() {
+        let monitor = PerformanceMonitor::new();
+        let first = monitor.snapshot();
+        let second = monitor.snapshot();
+        assert_eq!(first.sequence, 1);
+        assert_eq!(second.sequence, first.sequence);
+        assert!(monitor.refresh_snapshot());
+        assert_eq!(monitor.snapshot().sequence, 2);
+    }
+
+    #[test]
+    fn webkit_processes_are_found_through_the_sandbox() {
+        let stat = "4321 (WebKitWebProces) S 4300 4321 4000 0 -1 4194560";
+        assert_eq!(parse_proc_stat(stat), Some(("WebKitWebProces", 4300)));
+        assert_eq!(parse_proc_stat("77 (a) b) R 12 77"), Some(("a) b", 12)));
+        assert_eq!(parse_proc_stat("garbage"), None);
+
+        // 100 is us, 4300 is bwrap, 900 is an unrelated WebKit app
+        let parents = [(4300, 100), (4321, 4300), (950, 900), (100, 1)]
+            .into_iter()
+            .collect();
+        assert!(descends_from(&parents, 4321, 100));
+        assert!(!descends_from(&parents, 950, 100));
+        assert!(!descends_from(&parents, 12345, 100));
+    }
+
+    #[test]
+    fn process_tree_cpu_counts_descendants_only() {
+        let pid = Pid::from_u32;
+        let processes = [
+            (pid(1), None, 5.0),
+            (pid(10), Some(pid(1)), 2.0),
+            (pid(11), Some(pid(10)), 30.0),
+            (pid(12), Some(pid(11)), 8.0),
+            // OBS, unrelated to us
+            (pid(20), Some(pid(1)), 40.0),
+        ];
+        assert_eq!(process_tree_cpu(&processes, pid(10)), 40.0);
+        assert_eq!(process_tree_cpu(&processes, pid(99)), 0.0);
+    }
 }
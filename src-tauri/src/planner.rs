@@ -0,0 +1,352 @@
+//! Team Planner - Cross-platform
+//!
+//! A separate window for planning board comps between games. The page is
+//! bundled and served by the localhost server next to the stats dashboard;
+//! like the dashboard it never touches the game page. Comps are saved as
+//! one JSON file each in the `planner` folder next to the settings, and
+//! travel as short codes (`PAC1.` and URL-safe base64 of the comp) that can
+//! be pasted into chat.
+//!
+//! `pin_planner` puts the planner beside the game window, on whichever
+//! side has room on its display, keeps it above other windows and follows
+//! the game window while it moves.
+
+use crate::settings;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{
+    AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder, WindowEvent,
+};
+use tracing::{info, warn};
+
+pub const PLANNER_WINDOW_LABEL: &str = "planner";
+/// Path of the planner page on the localhost server
+pub const PLANNER_PAGE_PATH: &str = "/pacdeluxe/planner";
+
+const PLANNER_DIR_NAME: &str = "planner";
+const CODE_PREFIX: &str = "PAC1.";
+
+/// Board size: 8 columns, 3 rows
+pub const BOARD_WIDTH: u8 = 8;
+pub const BOARD_HEIGHT: u8 = 3;
+const MAX_NAME_CHARS: usize = 60;
+const MAX_NOTES_CHARS: usize = 2000;
+const MAX_ITEMS: usize = 3;
+const MAX_COMPS: usize = 200;
+/// Longest code accepted by `import_team_comp`
+const MAX_CODE_CHARS: usize = 16 * 1024;
+
+static PINNED: AtomicBool = AtomicBool::new(false);
+
+/// A unit on the board
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlacedUnit {
+    /// Column, 0 to 7
+    pub x: u8,
+    /// Row, 0 (front) to 2
+    pub y: u8,
+    pub pokemon: String,
+    #[serde(default)]
+    pub items: Vec<String>,
+}
+
+/// A saved board comp
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TeamComp {
+    pub name: String,
+    #[serde(default)]
+    pub units: Vec<PlacedUnit>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.trim().chars().take(max_chars).collect()
+}
+
+impl TeamComp {
+    /// Trim the text, drop units off the board or empty, keep the first unit
+    /// per cell
+    pub fn validated(self) -> Result<Self, String> {
+        let name = truncate(&self.name, MAX_NAME_CHARS);
+        if name.is_empty() {
+            return Err("The comp needs a name".to_string());
+        }
+        let mut units: Vec<PlacedUnit> = Vec::new();
+        for unit in self.units {
+            let pokemon = truncate(&unit.pokemon, MAX_NAME_CHARS);
+            if unit.x >= BOARD_WIDTH
+                || unit.y >= BOARD_HEIGHT
+                || pokemon.is_empty()
+                || units.iter().any(|u| u.x == unit.x && u.y == unit.y)
+            {
+                continue;
+            }
+            let items = unit
+                .items
+                .iter()
+                .map(|item| truncate(item, MAX_NAME_CHARS))
+                .filter(|item| !item.is_empty())
+                .take(MAX_ITEMS)
+                .collect();
+            units.push(PlacedUnit {
+                x: unit.x,
+                y: unit.y,
+                pokemon,
+                items,
+            });
+        }
+        units.sort_by_key(|u| (u.y, u.x));
+        Ok(Self {
+            name,
+            units,
+            notes: truncate(&self.notes, MAX_NOTES_CHARS),
+        })
+    }
+}
+
+/// File name of the comp called `name`: lowercase letters and digits,
+/// anything else as dashes
+fn file_name(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            stem.push(c);
+        } else if !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_matches('-');
+    format!("{}.json", if stem.is_empty() { "comp" } else { stem })
+}
+
+/// Share code for `comp`
+pub fn export_code(comp: &TeamComp) -> Result<String, String> {
+    let comp = comp.clone().validated()?;
+    let json = serde_json::to_vec(&comp).map_err(|e| e.to_string())?;
+    Ok(format!(
+        "{}{}",
+        CODE_PREFIX,
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+    ))
+}
+
+/// Comp from a share code
+pub fn import_code(code: &str) -> Result<TeamComp, String> {
+    let code = code.trim();
+    if code.len() > MAX_CODE_CHARS {
+        return Err("Comp code is too long".to_string());
+    }
+    let invalid = || "Not a PACDeluxe comp code".to_string();
+    let data = code.strip_prefix(CODE_PREFIX).ok_or_else(invalid)?;
+    let json = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(data)
+        .map_err(|_| invalid())?;
+    let comp: TeamComp = serde_json::from_slice(&json).map_err(|_| invalid())?;
+    comp.validated()
+}
+
+fn planner_dir() -> Result<PathBuf, String> {
+    let dir = settings::config_dir()
+        .ok_or_else(|| "No config directory available".to_string())?
+        .join(PLANNER_DIR_NAME);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Saved comps, by name
+pub fn list() -> Result<Vec<TeamComp>, String> {
+    let dir = planner_dir()?;
+    let entries =
+        std::fs::read_dir(&dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut comps: Vec<TeamComp> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let json = std::fs::read_to_string(&path).ok()?;
+            match serde_json::from_str::<TeamComp>(&json) {
+                Ok(comp) => comp.validated().ok(),
+                Err(e) => {
+                    warn!("Skipping comp {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+    comps.sort_by_key(|comp| comp.name.to_lowercase());
+    Ok(comps)
+}
+
+/// Save `comp`, replacing a saved comp with the same name
+pub fn save(comp: TeamComp) -> Result<TeamComp, String> {
+    let comp = comp.validated()?;
+    let path = planner_dir()?.join(file_name(&comp.name));
+    if !path.exists() && list()?.len() >= MAX_COMPS {
+        return Err(format!("At most {} comps can be saved", MAX_COMPS));
+    }
+    settings::write_json(&path, &comp)?;
+    info!("Team comp {:?} saved", comp.name);
+    Ok(comp)
+}
+
+/// Delete the saved comp called `name`
+pub fn delete(name: &str) -> Result<(), String> {
+    let path = planner_dir()?.join(file_name(name));
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete comp {:?}: {}", name, e))
+}
+
+/// Open the planner, or bring it to the front if it is already open
+pub fn open(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PLANNER_WINDOW_LABEL) {
+        let _ = window.unminimize();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    let port = crate::dashboard::local_port()
+        .ok_or_else(|| "Localhost server is not running".to_string())?;
+    let url = format!("http://localhost:{}{}", port, PLANNER_PAGE_PATH)
+        .parse()
+        .map_err(|e| format!("Invalid planner URL: {}", e))?;
+    let window = WebviewWindowBuilder::new(app, PLANNER_WINDOW_LABEL, WebviewUrl::External(url))
+        .title("PACDeluxe - Team planner")
+        .inner_size(440.0, 720.0)
+        .min_inner_size(360.0, 480.0)
+        .build()
+        .map_err(|e| format!("Failed to open team planner: {}", e))?;
+    window.on_window_event(|event| {
+        if let WindowEvent::Destroyed = event {
+            PINNED.store(false, Ordering::SeqCst);
+        }
+    });
+    info!("Team planner opened");
+    Ok(())
+}
+
+/// Left edge for a window `width` wide next to one at `main_x`, `main_width`
+/// wide: right of it if that fits on the display, else left of it, else
+/// against the display's left edge
+fn beside(main_x: i32, main_width: u32, width: u32, display_x: i32, display_width: u32) -> i32 {
+    let right = main_x + main_width as i32;
+    if right + width as i32 <= display_x + display_width as i32 {
+        right
+    } else if main_x - width as i32 >= display_x {
+        main_x - width as i32
+    } else {
+        display_x
+    }
+}
+
+/// Move the planner next to the main window, at its height
+fn place(main: &WebviewWindow, planner: &WebviewWindow) -> Result<(), String> {
+    let position = main.outer_position().map_err(|e| e.to_string())?;
+    let size = main.outer_size().map_err(|e| e.to_string())?;
+    let planner_size = planner.outer_size().map_err(|e| e.to_string())?;
+    let display = main
+        .current_monitor()
+        .map_err(|e| e.to_string())?
+        .ok_or("The game window is on no display")?;
+    let x = beside(
+        position.x,
+        size.width,
+        planner_size.width,
+        display.position().x,
+        display.size().width,
+    );
+    planner
+        .set_position(PhysicalPosition::new(x, position.y))
+        .map_err(|e| e.to_string())?;
+    planner
+        .set_size(PhysicalSize::new(planner_size.width, size.height))
+        .map_err(|e| e.to_string())
+}
+
+/// Pin the planner beside the game window, or unpin it
+pub fn set_pinned(app: &AppHandle, pinned: bool) -> Result<(), String> {
+    if pinned {
+        open(app)?;
+    }
+    let Some(planner) = app.get_webview_window(PLANNER_WINDOW_LABEL) else {
+        PINNED.store(false, Ordering::SeqCst);
+        return Ok(());
+    };
+    planner
+        .set_always_on_top(pinned)
+        .map_err(|e| e.to_string())?;
+    PINNED.store(pinned, Ordering::SeqCst);
+    if pinned {
+        let main = app
+            .get_webview_window("main")
+            .ok_or("No game window to pin to")?;
+        place(&main, &planner)?;
+    }
+    info!(
+        "Team planner {}",
+        if pinned { "pinned" } else { "unpinned" }
+    );
+    Ok(())
+}
+
+/// Keep a pinned planner beside a newly built main window
+pub fn install(window: &WebviewWindow) {
+    let main = window.clone();
+    window.on_window_event(move |event| {
+        if !matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_))
+            || !PINNED.load(Ordering::SeqCst)
+        {
+            return;
+        }
+        if let Some(planner) = main.get_webview_window(PLANNER_WINDOW_LABEL) {
+            let _ = place(&main, &planner);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(x: u8, y: u8, pokemon: &str) -> PlacedUnit {
+        PlacedUnit {
+            x,
+            y,
+            pokemon: pokemon.to_string(),
+            items: vec!["Leftovers".to_string()],
+        }
+    }
+
+    #[test]
+    fn codes_round_trip_and_units_are_cleaned() {
+        let comp = TeamComp {
+            name: " Dragons ".to_string(),
+            units: vec![
+                unit(3, 1, "Dragonite"),
+                unit(3, 1, "Gible"),
+                unit(8, 0, "Off the board"),
+                unit(0, 0, " "),
+            ],
+            notes: "Roll at 4".to_string(),
+        };
+        let code = export_code(&comp).unwrap();
+        assert!(code.starts_with(CODE_PREFIX));
+        let imported = import_code(&code).unwrap();
+        assert_eq!(imported.name, "Dragons");
+        assert_eq!(imported.units, vec![unit(3, 1, "Dragonite")]);
+        assert_eq!(file_name("Dragons / Fire!"), "dragons-fire.json");
+        assert!(import_code("PAC1.not-json").is_err());
+        assert!(import_code("hello").is_err());
+    }
+
+    #[test]
+    fn pinned_planner_goes_where_there_is_room() {
+        // 1920 wide display, 1280 wide game window at x = 100
+        assert_eq!(beside(100, 1280, 440, 0, 1920), 1380);
+        // Too close to the right edge: left side
+        assert_eq!(beside(600, 1280, 440, 0, 1920), 160);
+        // Fullscreen: against the left edge, over the game
+        assert_eq!(beside(0, 1920, 440, 0, 1920), 0);
+    }
+}
@@ -0,0 +1,328 @@
+//! Power Draw Estimate - Cross-platform
+//!
+//! Estimated power draw in watts, so laptop users can compare what the
+//! Performance and Battery saver profiles cost. Where the machine has one,
+//! the CPU package meter is used (it includes an integrated GPU); otherwise
+//! the battery's discharge rate, which covers the whole laptop but only
+//! while it runs on battery. Machines with neither report nothing.
+//!
+//! - Windows: `\Energy Meter(*)\Power` PDH counters (the Energy Estimation
+//!   meters, RAPL on most Intel and AMD systems), then `\Power Meter(*)\Power`
+//!   (battery)
+//! - Linux: RAPL energy counters (`/sys/class/powercap/intel-rapl:*`,
+//!   readable by root only on most distributions since 2020), then the
+//!   battery's `power_now`
+
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::time::Instant;
+use ts_rs::TS;
+
+/// What an estimate covers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerScope {
+    /// CPU package, including an integrated GPU
+    CpuPackage,
+    /// Whole machine, from the battery's discharge rate
+    Battery,
+}
+
+/// Estimated power draw
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, TS, PartialEq)]
+pub struct PowerDraw {
+    pub watts: Option<f64>,
+    pub scope: Option<PowerScope>,
+}
+
+impl PowerDraw {
+    fn new(watts: f64, scope: PowerScope) -> Self {
+        Self {
+            watts: Some(watts),
+            scope: Some(scope),
+        }
+    }
+}
+
+/// Package power from per-domain meter readings in milliwatts. Package
+/// domains already include the core and graphics domains, so those only
+/// count when no package domain is reported.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn package_watts(meters: &[(String, f64)]) -> Option<f64> {
+    let is_package = |name: &str| name.to_ascii_uppercase().ends_with("_PKG");
+    let packages: Vec<f64> = meters
+        .iter()
+        .filter(|(name, _)| is_package(name))
+        .map(|(_, mw)| *mw)
+        .collect();
+    let milliwatts: Vec<f64> = if packages.is_empty() {
+        meters.iter().map(|(_, mw)| *mw).collect()
+    } else {
+        packages
+    };
+    let total: f64 = milliwatts.iter().filter(|mw| mw.is_finite()).sum();
+    (total > 0.0).then_some(total / 1000.0)
+}
+
+/// Watts between two readings of cumulative energy counters (µJ), each
+/// with its wrap-around range
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn energy_watts(previous: &[u64], current: &[(u64, u64)], elapsed_secs: f64) -> Option<f64> {
+    if previous.len() != current.len() || current.is_empty() || elapsed_secs <= 0.0 {
+        return None;
+    }
+    let microjoules: u64 = previous
+        .iter()
+        .zip(current)
+        .map(|(&before, &(now, range))| {
+            if now >= before {
+                now - before
+            } else {
+                // The counter wrapped
+                range.saturating_sub(before) + now
+            }
+        })
+        .sum();
+    Some(microjoules as f64 / 1_000_000.0 / elapsed_secs)
+}
+
+/// Reads the meters; kept between reads for counter handles and deltas
+#[cfg(target_os = "windows")]
+pub struct PowerMeter {
+    query: Option<isize>,
+    energy_counter: Option<isize>,
+    battery_counter: Option<isize>,
+    initialized: bool,
+}
+
+#[cfg(target_os = "windows")]
+impl PowerMeter {
+    pub fn new() -> Self {
+        Self {
+            query: None,
+            energy_counter: None,
+            battery_counter: None,
+            initialized: false,
+        }
+    }
+
+    fn initialize(&mut self) {
+        use windows::core::{w, PCWSTR};
+        use windows::Win32::System::Performance::{
+            PdhAddEnglishCounterW, PdhCloseQuery, PdhOpenQueryW,
+        };
+
+        self.initialized = true;
+        // SAFETY: the query is closed on drop, or here if no counter exists
+        unsafe {
+            let mut query: isize = 0;
+            if PdhOpenQueryW(PCWSTR::null(), 0, &mut query) != 0 {
+                return;
+            }
+            for (path, slot) in [
+                (w!("\\Energy Meter(*)\\Power"), &mut self.energy_counter),
+                (w!("\\Power Meter(*)\\Power"), &mut self.battery_counter),
+            ] {
+                let mut counter: isize = 0;
+                let status = PdhAddEnglishCounterW(query, path, 0, &mut counter);
+                if status == 0 {
+                    *slot = Some(counter);
+                } else {
+                    tracing::debug!("Power counter unavailable: 0x{:08X}", status);
+                }
+            }
+            if self.energy_counter.is_none() && self.battery_counter.is_none() {
+                let _ = PdhCloseQuery(query);
+            } else {
+                self.query = Some(query);
+            }
+        }
+    }
+
+    pub fn read(&mut self) -> PowerDraw {
+        use crate::performance::GpuMonitor;
+        use windows::Win32::System::Performance::PdhCollectQueryData;
+
+        if !self.initialized {
+            self.initialize();
+        }
+        let Some(query) = self.query else {
+            return PowerDraw::default();
+        };
+        // SAFETY: the query handle stays open until drop
+        if unsafe { PdhCollectQueryData(query) } != 0 {
+            return PowerDraw::default();
+        }
+        let meters = |counter: Option<isize>| {
+            counter
+                .and_then(|counter| GpuMonitor::read_counter_instances(counter).ok())
+                .unwrap_or_default()
+        };
+        if let Some(watts) = package_watts(&meters(self.energy_counter)) {
+            return PowerDraw::new(watts, PowerScope::CpuPackage);
+        }
+        // Power meters read 0 while the machine is charging
+        let battery: f64 = meters(self.battery_counter)
+            .iter()
+            .map(|(_, mw)| *mw)
+            .filter(|mw| mw.is_finite())
+            .sum();
+        if battery > 0.0 {
+            return PowerDraw::new(battery / 1000.0, PowerScope::Battery);
+        }
+        PowerDraw::default()
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for PowerMeter {
+    fn drop(&mut self) {
+        if let Some(query) = self.query {
+            use windows::Win32::System::Performance::PdhCloseQuery;
+            // SAFETY: opened in `initialize` and not used after this
+            unsafe {
+                let _ = PdhCloseQuery(query);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub struct PowerMeter {
+    /// Package domain directories; empty when RAPL is missing or unreadable
+    domains: Vec<std::path::PathBuf>,
+    previous: Option<(Instant, Vec<u64>)>,
+    initialized: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl PowerMeter {
+    pub fn new() -> Self {
+        Self {
+            domains: Vec::new(),
+            previous: None,
+            initialized: false,
+        }
+    }
+
+    fn read_u64(path: &std::path::Path) -> Option<u64> {
+        std::fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    /// Top-level RAPL zones (`intel-rapl:0`, not `intel-rapl:0:0`) are the
+    /// packages
+    fn initialize(&mut self) {
+        self.initialized = true;
+        let Ok(entries) = std::fs::read_dir("/sys/class/powercap") else {
+            return;
+        };
+        self.domains = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.strip_prefix("intel-rapl:")
+                            .is_some_and(|rest| !rest.contains(':'))
+                    })
+            })
+            .filter(|path| Self::read_u64(&path.join("energy_uj")).is_some())
+            .collect();
+        self.domains.sort();
+        if self.domains.is_empty() {
+            tracing::debug!("RAPL energy counters missing or not readable");
+        }
+    }
+
+    fn rapl(&mut self) -> Option<f64> {
+        let now = Instant::now();
+        let current: Option<Vec<(u64, u64)>> = self
+            .domains
+            .iter()
+            .map(|domain| {
+                let energy = Self::read_u64(&domain.join("energy_uj"))?;
+                let range = Self::read_u64(&domain.join("max_energy_range_uj")).unwrap_or(0);
+                Some((energy, range))
+            })
+            .collect();
+        let current = current?;
+        let watts = self.previous.as_ref().and_then(|(at, previous)| {
+            energy_watts(previous, &current, now.duration_since(*at).as_secs_f64())
+        });
+        self.previous = Some((now, current.iter().map(|(energy, _)| *energy).collect()));
+        watts
+    }
+
+    /// Discharge rate of the first discharging battery
+    fn battery() -> Option<f64> {
+        let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .find_map(|path| {
+                let status = std::fs::read_to_string(path.join("status")).ok()?;
+                if status.trim() != "Discharging" {
+                    return None;
+                }
+                let microwatts = Self::read_u64(&path.join("power_now")).or_else(|| {
+                    let current = Self::read_u64(&path.join("current_now"))?;
+                    let voltage = Self::read_u64(&path.join("voltage_now"))?;
+                    Some(current * voltage / 1_000_000)
+                })?;
+                (microwatts > 0).then(|| microwatts as f64 / 1_000_000.0)
+            })
+    }
+
+    pub fn read(&mut self) -> PowerDraw {
+        if !self.initialized {
+            self.initialize();
+        }
+        if !self.domains.is_empty() {
+            // Nothing on the first read: a rate needs two
+            return self.rapl().map_or_else(PowerDraw::default, |watts| {
+                PowerDraw::new(watts, PowerScope::CpuPackage)
+            });
+        }
+        match Self::battery() {
+            Some(watts) => PowerDraw::new(watts, PowerScope::Battery),
+            None => PowerDraw::default(),
+        }
+    }
+}
+
+impl Default for PowerMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_domains_are_not_double_counted() {
+        let meters = [
+            ("RAPL_Package0_PKG".to_string(), 12_000.0),
+            ("RAPL_Package0_PP0".to_string(), 8_000.0),
+            ("RAPL_Package0_DRAM".to_string(), 2_000.0),
+        ];
+        assert_eq!(package_watts(&meters), Some(12.0));
+        assert_eq!(package_watts(&meters[1..]), Some(10.0));
+        assert_eq!(package_watts(&[]), None);
+    }
+
+    #[test]
+    fn energy_counters_survive_wrapping() {
+        assert_eq!(
+            energy_watts(&[1_000_000], &[(3_000_000, 10_000_000)], 2.0),
+            Some(1.0)
+        );
+        assert_eq!(
+            energy_watts(&[9_000_000], &[(1_000_000, 10_000_000)], 1.0),
+            Some(2.0)
+        );
+        assert_eq!(energy_watts(&[], &[(1, 1)], 1.0), None);
+    }
+}
@@ -0,0 +1,346 @@
+//! Power Guard - Cross-platform
+//!
+//! Switches to the battery saver profile when the laptop is about to run
+//! out of battery or a CPU/GPU sensor gets too hot, and tells the page why
+//! (`pac-power-emergency`, shown as a dismissible banner). The user's own
+//! profile choice is not changed: once the machine is plugged in or has
+//! cooled down, the chosen profile is applied again.
+
+use crate::performance;
+use crate::settings::{PowerGuardSettings, PowerProfile, SettingsState};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info, warn};
+use ts_rs::TS;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Charge above the threshold before switching back on battery
+const BATTERY_HYSTERESIS: u8 = 10;
+/// Cooling below the limit before switching back
+const TEMPERATURE_HYSTERESIS: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    pub percent: u8,
+    /// Discharging rather than on AC power
+    pub on_battery: bool,
+}
+
+/// What triggered the switch
+#[derive(Debug, Clone, Copy, Serialize, TS, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EmergencyReason {
+    LowBattery { percent: u8 },
+    CpuTemperature { celsius: f32 },
+    GpuTemperature { celsius: f32 },
+}
+
+impl EmergencyReason {
+    fn explain(&self) -> String {
+        let (cause, until) = match self {
+            EmergencyReason::LowBattery { percent } => {
+                (format!("Battery is at {}%", percent), "when you plug in")
+            }
+            EmergencyReason::CpuTemperature { celsius } => (
+                format!("The CPU reached {:.0}°C", celsius),
+                "once it has cooled down",
+            ),
+            EmergencyReason::GpuTemperature { celsius } => (
+                format!("The GPU reached {:.0}°C", celsius),
+                "once it has cooled down",
+            ),
+        };
+        format!(
+            "{}, so PACDeluxe switched to the Battery saver profile: normal process \
+             priority, power throttling allowed and the default timer resolution. \
+             It switches back {}.",
+            cause, until
+        )
+    }
+}
+
+/// `pac-power-emergency` payload
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PowerEmergency {
+    pub reason: EmergencyReason,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Readings {
+    battery: Option<BatteryStatus>,
+    cpu_temp_c: Option<f32>,
+    gpu_temp_c: Option<f32>,
+}
+
+fn emergency_reason(readings: &Readings, guard: &PowerGuardSettings) -> Option<EmergencyReason> {
+    if let Some(battery) = readings.battery {
+        if battery.on_battery && battery.percent < guard.battery_percent {
+            return Some(EmergencyReason::LowBattery {
+                percent: battery.percent,
+            });
+        }
+    }
+    if let Some(celsius) = readings.cpu_temp_c.filter(|t| *t >= guard.cpu_temp_c) {
+        return Some(EmergencyReason::CpuTemperature { celsius });
+    }
+    if let Some(celsius) = readings.gpu_temp_c.filter(|t| *t >= guard.gpu_temp_c) {
+        return Some(EmergencyReason::GpuTemperature { celsius });
+    }
+    None
+}
+
+/// Whether the cause of `reason` has gone away, with some margin so the
+/// profile doesn't flip back and forth
+fn has_recovered(
+    reason: &EmergencyReason,
+    readings: &Readings,
+    guard: &PowerGuardSettings,
+) -> bool {
+    let cooled = |reading: Option<f32>, limit: f32| match reading {
+        Some(t) => t < limit - TEMPERATURE_HYSTERESIS,
+        None => true,
+    };
+    match reason {
+        EmergencyReason::LowBattery { .. } => match readings.battery {
+            Some(b) => !b.on_battery || b.percent >= guard.battery_percent + BATTERY_HYSTERESIS,
+            None => true,
+        },
+        EmergencyReason::CpuTemperature { .. } => cooled(readings.cpu_temp_c, guard.cpu_temp_c),
+        EmergencyReason::GpuTemperature { .. } => cooled(readings.gpu_temp_c, guard.gpu_temp_c),
+    }
+}
+
+/// Managed emergency state
+#[derive(Default)]
+pub struct PowerGuard(Mutex<Option<EmergencyReason>>);
+
+impl PowerGuard {
+    pub fn emergency(&self) -> Option<PowerEmergency> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|reason| PowerEmergency {
+                reason,
+                message: reason.explain(),
+            })
+    }
+
+    /// The profile in effect: battery saver during an emergency, otherwise
+    /// the user's choice
+    pub fn active_profile(&self, chosen: PowerProfile) -> PowerProfile {
+        if self.0.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+            PowerProfile::BatterySaver
+        } else {
+            chosen
+        }
+    }
+}
+
+pub fn apply_profile(profile: PowerProfile) {
+    performance::set_power_saving(profile == PowerProfile::BatterySaver);
+}
+
+#[cfg(target_os = "windows")]
+fn battery() -> Option<BatteryStatus> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // SAFETY: fills the caller-owned struct
+    unsafe { GetSystemPowerStatus(&mut status) }.ok()?;
+    // 128: no system battery, 255: unknown
+    if status.BatteryFlag & 128 != 0 || status.BatteryLifePercent > 100 {
+        return None;
+    }
+    Some(BatteryStatus {
+        percent: status.BatteryLifePercent,
+        on_battery: status.ACLineStatus == 0,
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn battery() -> Option<BatteryStatus> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+        .find_map(|entry| {
+            let path = entry.path();
+            let percent = std::fs::read_to_string(path.join("capacity"))
+                .ok()?
+                .trim()
+                .parse::<u8>()
+                .ok()?;
+            let status = std::fs::read_to_string(path.join("status")).unwrap_or_default();
+            Some(BatteryStatus {
+                percent: percent.min(100),
+                on_battery: status.trim() == "Discharging",
+            })
+        })
+}
+
+/// Whether a sensor label belongs to a graphics card
+fn is_gpu_sensor(label: &str) -> bool {
+    let label = label.to_lowercase();
+    ["gpu", "amdgpu", "nouveau", "nvidia", "radeon"]
+        .iter()
+        .any(|name| label.contains(name))
+}
+
+/// Hottest CPU-side and GPU sensors
+fn temperatures(components: &sysinfo::Components) -> (Option<f32>, Option<f32>) {
+    let mut cpu: Option<f32> = None;
+    let mut gpu: Option<f32> = None;
+    for component in components.iter() {
+        let t = component.temperature();
+        if !t.is_finite() || t <= 0.0 {
+            continue;
+        }
+        let slot = if is_gpu_sensor(component.label()) {
+            &mut gpu
+        } else {
+            &mut cpu
+        };
+        *slot = Some(slot.map_or(t, |max| max.max(t)));
+    }
+    (cpu, gpu)
+}
+
+fn check(app: &AppHandle, readings: &Readings) {
+    let settings = app.state::<SettingsState>().get();
+    let guard = settings.power_guard.validated();
+    let state = app.state::<PowerGuard>();
+    let mut current = state.0.lock().unwrap_or_else(|e| e.into_inner());
+
+    let next = match *current {
+        Some(reason) if !guard.enabled || has_recovered(&reason, readings, &guard) => None,
+        Some(reason) => Some(reason),
+        // Nothing to switch when the user already chose battery saver
+        None if guard.enabled && settings.power_profile == PowerProfile::Performance => {
+            emergency_reason(readings, &guard)
+        }
+        None => None,
+    };
+    if next.is_some() == current.is_some() {
+        return;
+    }
+    *current = next;
+    drop(current);
+
+    let window = app.get_webview_window("main");
+    match next {
+        Some(reason) => {
+            warn!("Power emergency: {:?}", reason);
+            apply_profile(PowerProfile::BatterySaver);
+            if let Some(window) = window {
+                let _ = window.emit("pac-power-emergency", state.emergency());
+            }
+        }
+        None => {
+            info!("Power emergency over, back to {:?}", settings.power_profile);
+            apply_profile(settings.power_profile);
+            if let Some(window) = window {
+                let _ = window.emit("pac-power-emergency", None::<PowerEmergency>);
+            }
+        }
+    }
+}
+
+/// Apply the chosen profile and start watching battery and temperatures
+pub fn spawn(app: AppHandle) {
+    app.manage(PowerGuard::default());
+    let chosen = app.state::<SettingsState>().get().power_profile;
+    if chosen != PowerProfile::Performance {
+        apply_profile(chosen);
+    }
+    std::thread::Builder::new()
+        .name("power-guard".to_string())
+        .spawn(move || {
+            let mut components = sysinfo::Components::new_with_refreshed_list();
+            loop {
+                if crate::suspend::is_suspended() {
+                    std::thread::sleep(CHECK_INTERVAL);
+                    continue;
+                }
+                components.refresh();
+                let (cpu_temp_c, gpu_temp_c) = temperatures(&components);
+                let readings = Readings {
+                    battery: battery(),
+                    cpu_temp_c,
+                    gpu_temp_c,
+                };
+                check(&app, &readings);
+                std::thread::sleep(CHECK_INTERVAL);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start power guard: {}", e));
+    debug!("Power guard started");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on_battery(percent: u8) -> Readings {
+        Readings {
+            battery: Some(BatteryStatus {
+                percent,
+                on_battery: true,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn low_battery_and_heat_trigger_the_switch() {
+        let guard = PowerGuardSettings::default();
+        assert_eq!(emergency_reason(&on_battery(50), &guard), None);
+        assert_eq!(
+            emergency_reason(&on_battery(12), &guard),
+            Some(EmergencyReason::LowBattery { percent: 12 })
+        );
+        let charging = Readings {
+            battery: Some(BatteryStatus {
+                percent: 12,
+                on_battery: false,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(emergency_reason(&charging, &guard), None);
+        let hot_gpu = Readings {
+            gpu_temp_c: Some(93.0),
+            cpu_temp_c: Some(70.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            emergency_reason(&hot_gpu, &guard),
+            Some(EmergencyReason::GpuTemperature { celsius: 93.0 })
+        );
+    }
+
+    #[test]
+    fn switching_back_needs_a_margin() {
+        let guard = PowerGuardSettings::default();
+        let low = EmergencyReason::LowBattery { percent: 12 };
+        assert!(!has_recovered(&low, &on_battery(22), &guard));
+        assert!(has_recovered(&low, &on_battery(30), &guard));
+
+        let hot = EmergencyReason::CpuTemperature { celsius: 97.0 };
+        let cooling = |t| Readings {
+            cpu_temp_c: Some(t),
+            ..Default::default()
+        };
+        assert!(!has_recovered(&hot, &cooling(90.0), &guard));
+        assert!(has_recovered(&hot, &cooling(80.0), &guard));
+    }
+
+    #[test]
+    fn gpu_sensors_are_told_apart() {
+        assert!(is_gpu_sensor("amdgpu edge"));
+        assert!(is_gpu_sensor("GPU Core"));
+        assert!(!is_gpu_sensor("coretemp Package id 0"));
+    }
+}
@@ -0,0 +1,398 @@
+//! Startup Preflight - Cross-platform
+//!
+//! Checks what PACDeluxe depends on but does not ship: the webview runtime,
+//! the GPU driver, the Visual C++ runtime, the media codecs the game's
+//! music needs and the GPU performance counters. Most "it just shows a
+//! white window" reports come down to one of these, so the result is kept
+//! as readable text (`preflight.txt` next to the settings) to attach.
+//!
+//! Runs in the background on the first start (no report file yet) and
+//! again with every health check, which adds missing dependencies to the
+//! health issues.
+//!
+//! - Windows: WebView2 runtime, VC++ redistributable registration, Media
+//!   Foundation (missing on N editions without the Media Feature Pack),
+//!   PDH GPU Engine counters
+//! - Linux: webkit2gtk version and GStreamer decoders; no VC++ or PDH
+
+use crate::gpu_driver;
+use crate::report::unix_now;
+use crate::settings;
+use serde::Serialize;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+const REPORT_FILE_NAME: &str = "preflight.txt";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightStatus {
+    Ok,
+    /// Present, but known to cause trouble
+    Warning,
+    Missing,
+    /// Not used on this platform
+    Skipped,
+}
+
+/// One dependency
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightItem {
+    pub name: &'static str,
+    pub status: PreflightStatus,
+    pub version: Option<String>,
+    pub detail: String,
+}
+
+impl PreflightItem {
+    fn new(
+        name: &'static str,
+        status: PreflightStatus,
+        version: Option<String>,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            status,
+            version,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Preflight result, also written to `preflight.txt`
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    /// Nothing is missing
+    pub ok: bool,
+    pub items: Vec<PreflightItem>,
+    /// Unix seconds
+    pub ran_at: u64,
+}
+
+impl PreflightReport {
+    /// Plain text for the report file and bug reports
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "PACDeluxe {} preflight ({}, unix time {})\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            self.ran_at
+        );
+        for item in &self.items {
+            let status = match item.status {
+                PreflightStatus::Ok => "ok",
+                PreflightStatus::Warning => "WARNING",
+                PreflightStatus::Missing => "MISSING",
+                PreflightStatus::Skipped => "skipped",
+            };
+            text.push_str(&format!(
+                "{:<14} {:<8} {:<20} {}\n",
+                item.name,
+                status,
+                item.version.as_deref().unwrap_or("-"),
+                item.detail
+            ));
+        }
+        text
+    }
+
+    /// Missing dependencies
+    pub fn missing(&self) -> impl Iterator<Item = &PreflightItem> {
+        self.items
+            .iter()
+            .filter(|item| item.status == PreflightStatus::Missing)
+    }
+}
+
+fn summarize(items: Vec<PreflightItem>) -> PreflightReport {
+    PreflightReport {
+        ok: items
+            .iter()
+            .all(|item| item.status != PreflightStatus::Missing),
+        items,
+        ran_at: unix_now(),
+    }
+}
+
+fn webview_runtime() -> PreflightItem {
+    const NAME: &str = "webview";
+    match tauri::webview_version() {
+        Ok(version) => PreflightItem::new(NAME, PreflightStatus::Ok, Some(version), "Installed"),
+        Err(e) => PreflightItem::new(
+            NAME,
+            PreflightStatus::Missing,
+            None,
+            if cfg!(target_os = "windows") {
+                format!(
+                    "WebView2 runtime not found ({}); reinstall it from the recovery console",
+                    e
+                )
+            } else {
+                format!("webkit2gtk not found ({})", e)
+            },
+        ),
+    }
+}
+
+fn gpu_driver_item() -> PreflightItem {
+    const NAME: &str = "gpu_driver";
+    let Some(driver) = gpu_driver::detect() else {
+        return PreflightItem::new(
+            NAME,
+            PreflightStatus::Warning,
+            None,
+            "Could not read the GPU driver version",
+        );
+    };
+    let version = Some(
+        driver
+            .display_version
+            .clone()
+            .unwrap_or_else(|| driver.version.clone()),
+    );
+    if let Some(minimum) = &driver.outdated_below {
+        PreflightItem::new(
+            NAME,
+            PreflightStatus::Warning,
+            version,
+            format!("{:?}, older than {}", driver.vendor, minimum),
+        )
+    } else if driver.vulkan_blocked {
+        PreflightItem::new(
+            NAME,
+            PreflightStatus::Warning,
+            version,
+            format!("{:?}, known to break Vulkan rendering", driver.vendor),
+        )
+    } else {
+        PreflightItem::new(
+            NAME,
+            PreflightStatus::Ok,
+            version,
+            format!("{:?}", driver.vendor),
+        )
+    }
+}
+
+/// Visual C++ 2015-2022 redistributable registration
+#[cfg(target_os = "windows")]
+fn vc_runtime() -> PreflightItem {
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Registry::{
+        RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ, RRF_SUBKEY_WOW6464KEY,
+    };
+
+    const NAME: &str = "vc_runtime";
+    let arch = if cfg!(target_arch = "aarch64") {
+        "arm64"
+    } else if cfg!(target_arch = "x86") {
+        "x86"
+    } else {
+        "x64"
+    };
+    let key: Vec<u16> = format!(
+        "SOFTWARE\\Microsoft\\VisualStudio\\14.0\\VC\\Runtimes\\{}",
+        arch
+    )
+    .encode_utf16()
+    .chain(Some(0))
+    .collect();
+    let value: Vec<u16> = "Version".encode_utf16().chain(Some(0)).collect();
+    let mut buffer = [0u16; 64];
+    let mut size = std::mem::size_of_val(&buffer) as u32;
+    // SAFETY: the buffer and its size in bytes are passed together
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            PCWSTR::from_raw(key.as_ptr()),
+            PCWSTR::from_raw(value.as_ptr()),
+            RRF_RT_REG_SZ | RRF_SUBKEY_WOW6464KEY,
+            None,
+            Some(buffer.as_mut_ptr().cast()),
+            Some(&mut size),
+        )
+    };
+    if status.is_err() {
+        // We are running, so the DLLs are there; only the registration is
+        // missing (an app-local copy, or a broken install)
+        return PreflightItem::new(
+            NAME,
+            PreflightStatus::Warning,
+            None,
+            "Visual C++ 2015-2022 redistributable is not registered",
+        );
+    }
+    let len = (size as usize / 2).min(buffer.len());
+    let version = String::from_utf16_lossy(&buffer[..len])
+        .trim_end_matches('\0')
+        .to_string();
+    PreflightItem::new(NAME, PreflightStatus::Ok, Some(version), "Installed")
+}
+
+#[cfg(target_os = "linux")]
+fn vc_runtime() -> PreflightItem {
+    PreflightItem::new(
+        "vc_runtime",
+        PreflightStatus::Skipped,
+        None,
+        "Not used on Linux",
+    )
+}
+
+/// Media Foundation decodes the game's music; Windows N lacks it
+#[cfg(target_os = "windows")]
+fn media_codecs() -> PreflightItem {
+    const NAME: &str = "media_codecs";
+    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+    let mfplat = PathBuf::from(system_root)
+        .join("System32")
+        .join("mfplat.dll");
+    if mfplat.exists() {
+        PreflightItem::new(NAME, PreflightStatus::Ok, None, "Media Foundation present")
+    } else {
+        PreflightItem::new(
+            NAME,
+            PreflightStatus::Missing,
+            None,
+            "Media Foundation is missing, so there is no music; install the Media Feature Pack (Windows N)",
+        )
+    }
+}
+
+/// GStreamer decoders WebKitGTK plays the game's music with
+#[cfg(target_os = "linux")]
+fn media_codecs() -> PreflightItem {
+    const NAME: &str = "media_codecs";
+    const DECODERS: &[&str] = &["mpg123audiodec", "vorbisdec"];
+    let mut missing = Vec::new();
+    for decoder in DECODERS {
+        match std::process::Command::new("gst-inspect-1.0")
+            .args(["--exists", decoder])
+            .status()
+        {
+            Ok(status) if status.success() => {}
+            Ok(_) => missing.push(*decoder),
+            Err(_) => {
+                return PreflightItem::new(
+                    NAME,
+                    PreflightStatus::Warning,
+                    None,
+                    "gst-inspect-1.0 not found; cannot check GStreamer decoders",
+                )
+            }
+        }
+    }
+    if missing.is_empty() {
+        PreflightItem::new(
+            NAME,
+            PreflightStatus::Ok,
+            None,
+            "GStreamer decoders present",
+        )
+    } else {
+        PreflightItem::new(
+            NAME,
+            PreflightStatus::Missing,
+            None,
+            format!(
+                "GStreamer {} missing, so there is no music; install the good/ugly plugin sets",
+                missing.join(", ")
+            ),
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn pdh_counters() -> PreflightItem {
+    const NAME: &str = "pdh_counters";
+    match crate::performance::check_gpu_counters() {
+        Ok(()) => PreflightItem::new(NAME, PreflightStatus::Ok, None, "GPU Engine counters"),
+        // Only the GPU stats suffer; the game runs fine
+        Err(e) => PreflightItem::new(NAME, PreflightStatus::Warning, None, e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn pdh_counters() -> PreflightItem {
+    PreflightItem::new(
+        "pdh_counters",
+        PreflightStatus::Skipped,
+        None,
+        "Not used on Linux",
+    )
+}
+
+fn report_path() -> Option<PathBuf> {
+    let dir = settings::config_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(REPORT_FILE_NAME))
+}
+
+/// Check every dependency, log the result and write the report file
+pub fn run() -> PreflightReport {
+    let report = summarize(vec![
+        webview_runtime(),
+        gpu_driver_item(),
+        vc_runtime(),
+        media_codecs(),
+        pdh_counters(),
+    ]);
+    let text = report.to_text();
+    if report.ok {
+        info!("Preflight passed:\n{}", text);
+    } else {
+        warn!("Preflight found missing dependencies:\n{}", text);
+    }
+    if let Some(path) = report_path() {
+        if let Err(e) = std::fs::write(&path, &text) {
+            warn!("Failed to write {}: {}", path.display(), e);
+        }
+    }
+    report
+}
+
+/// Run the preflight in the background when no report exists yet
+pub fn spawn_first_run_check() {
+    if report_path().is_some_and(|path| path.exists()) {
+        return;
+    }
+    std::thread::Builder::new()
+        .name("preflight".to_string())
+        .spawn(|| {
+            run();
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start preflight: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_missing_items_fail_the_report() {
+        let report = summarize(vec![
+            PreflightItem::new(
+                "a",
+                PreflightStatus::Ok,
+                Some("1.2".to_string()),
+                "Installed",
+            ),
+            PreflightItem::new("b", PreflightStatus::Warning, None, "old"),
+        ]);
+        assert!(report.ok);
+        let text = report.to_text();
+        assert!(text.lines().nth(1).unwrap().starts_with("a "));
+        assert!(text.contains("WARNING"));
+
+        let report = summarize(vec![PreflightItem::new(
+            "c",
+            PreflightStatus::Missing,
+            None,
+            "gone",
+        )]);
+        assert!(!report.ok);
+        assert_eq!(report.missing().count(), 1);
+    }
+}
@@ -0,0 +1,315 @@
+//! WebView2 Process Failures - Windows (WebView2)
+//!
+//! Records every `ProcessFailed` the main webview reports: which process
+//! (renderer, GPU, utility, ...), why, its exit code and, on runtimes that
+//! tell, the module it failed in. Each failure is logged with structured
+//! fields and kept in `crash-history.json` next to the settings.
+//!
+//! The WebView2 optimizer raises the priority of the runtime's processes.
+//! Elevated PIDs are remembered here, and a failure lists those that have
+//! exited since, together with the browser flags in effect. A GPU process
+//! that keeps dying while elevated, or only with our flags, then shows up as
+//! ours rather than the driver's.
+//!
+//! Linux: WebKitGTK's web process is not watched; the history stays empty.
+
+use crate::report::unix_now;
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+use ts_rs::TS;
+
+const HISTORY_FILE_NAME: &str = "crash-history.json";
+/// Failures kept in the history file
+const MAX_FAILURES: usize = 50;
+/// Elevated PIDs remembered before exited ones are pruned
+const MAX_ELEVATED: usize = 256;
+
+/// Which WebView2 process failed (`COREWEBVIEW2_PROCESS_FAILED_KIND`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailedProcess {
+    Browser,
+    Renderer,
+    RendererUnresponsive,
+    FrameRenderer,
+    Utility,
+    SandboxHelper,
+    Gpu,
+    Plugin,
+    Unknown,
+}
+
+impl FailedProcess {
+    fn from_kind(kind: i32) -> Self {
+        match kind {
+            0 => FailedProcess::Browser,
+            1 => FailedProcess::Renderer,
+            2 => FailedProcess::RendererUnresponsive,
+            3 => FailedProcess::FrameRenderer,
+            4 => FailedProcess::Utility,
+            5 => FailedProcess::SandboxHelper,
+            6 => FailedProcess::Gpu,
+            7 | 8 => FailedProcess::Plugin,
+            _ => FailedProcess::Unknown,
+        }
+    }
+}
+
+/// `COREWEBVIEW2_PROCESS_FAILED_REASON`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureReason {
+    Unexpected,
+    Unresponsive,
+    Terminated,
+    Crashed,
+    LaunchFailed,
+    OutOfMemory,
+    ProfileDeleted,
+}
+
+impl FailureReason {
+    fn from_reason(reason: i32) -> Self {
+        match reason {
+            1 => FailureReason::Unresponsive,
+            2 => FailureReason::Terminated,
+            3 => FailureReason::Crashed,
+            4 => FailureReason::LaunchFailed,
+            5 => FailureReason::OutOfMemory,
+            6 => FailureReason::ProfileDeleted,
+            _ => FailureReason::Unexpected,
+        }
+    }
+}
+
+/// One `ProcessFailed` event
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProcessFailure {
+    /// Unix seconds
+    #[ts(type = "number")]
+    pub at: u64,
+    pub process: FailedProcess,
+    /// Not reported by runtimes older than 1.0.1072
+    pub reason: Option<FailureReason>,
+    pub exit_code: Option<i32>,
+    /// Runtime's description of the process, e.g. "Audio Service"
+    pub description: Option<String>,
+    /// Module the process failed in, when the runtime knows
+    pub module: Option<String>,
+    /// PIDs the optimizer elevated that have exited since the last failure
+    pub elevated_pids: Vec<u32>,
+    /// `WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS` of this run
+    pub browser_args: String,
+    pub safe_mode: bool,
+}
+
+/// Elevated PID -> unix seconds of the elevation
+static ELEVATED: OnceLock<Mutex<HashMap<u32, u64>>> = OnceLock::new();
+
+fn elevated() -> &'static Mutex<HashMap<u32, u64>> {
+    ELEVATED.get_or_init(Default::default)
+}
+
+/// Called when the optimizer raised `pid`'s priority
+pub fn record_elevation(pid: u32) {
+    let mut elevated = elevated().lock().unwrap_or_else(|e| e.into_inner());
+    // Processes that ended without a failure event
+    if elevated.len() >= MAX_ELEVATED {
+        elevated.retain(|&other, _| is_running(other));
+    }
+    elevated.insert(pid, unix_now());
+}
+
+/// Take the elevated PIDs whose process is gone
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn take_exited_elevated() -> Vec<u32> {
+    let mut elevated = elevated().lock().unwrap_or_else(|e| e.into_inner());
+    let mut exited: Vec<u32> = elevated
+        .keys()
+        .copied()
+        .filter(|&pid| !is_running(pid))
+        .collect();
+    exited.sort_unstable();
+    for pid in &exited {
+        elevated.remove(pid);
+    }
+    exited
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn is_running(pid: u32) -> bool {
+    use windows::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    // SAFETY: the handle is closed before returning
+    unsafe {
+        let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return false;
+        };
+        let mut code = 0u32;
+        let running =
+            GetExitCodeProcess(handle, &mut code).is_ok() && code == STILL_ACTIVE.0 as u32;
+        let _ = CloseHandle(handle);
+        running
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn is_running(_pid: u32) -> bool {
+    true
+}
+
+fn history_path() -> Option<PathBuf> {
+    let dir = settings::config_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(HISTORY_FILE_NAME))
+}
+
+/// Recorded failures, oldest first
+pub fn history() -> Vec<ProcessFailure> {
+    history_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn append(history: &mut Vec<ProcessFailure>, failure: ProcessFailure) {
+    history.push(failure);
+    let excess = history.len().saturating_sub(MAX_FAILURES);
+    history.drain(..excess);
+}
+
+/// Log a failure and add it to the history
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn record(failure: ProcessFailure) {
+    warn!(
+        process = ?failure.process,
+        reason = ?failure.reason,
+        exit_code = ?failure.exit_code,
+        description = failure.description.as_deref().unwrap_or(""),
+        module = failure.module.as_deref().unwrap_or(""),
+        elevated_pids = ?failure.elevated_pids,
+        safe_mode = failure.safe_mode,
+        "WebView2 process failed"
+    );
+    let Some(path) = history_path() else {
+        return;
+    };
+    let mut all = history();
+    append(&mut all, failure);
+    let saved = serde_json::to_string_pretty(&all)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+    if let Err(e) = saved {
+        warn!("Failed to save crash history: {}", e);
+    }
+}
+
+/// Watch the main webview for failed processes
+#[cfg(target_os = "windows")]
+pub fn install(window: &tauri::WebviewWindow) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2ProcessFailedEventArgs2, ICoreWebView2ProcessFailedEventArgs3,
+        COREWEBVIEW2_PROCESS_FAILED_KIND, COREWEBVIEW2_PROCESS_FAILED_REASON,
+    };
+    use webview2_com::ProcessFailedEventHandler;
+    use windows_core::{Interface, PWSTR};
+
+    let result = window.with_webview(|webview| unsafe {
+        let Ok(core) = webview.controller().CoreWebView2() else {
+            warn!("Process failure logging unavailable: no CoreWebView2");
+            return;
+        };
+        let handler = ProcessFailedEventHandler::create(Box::new(|_sender, args| {
+            let Some(args) = args else { return Ok(()) };
+            let mut kind = COREWEBVIEW2_PROCESS_FAILED_KIND::default();
+            args.ProcessFailedKind(&mut kind)?;
+
+            let mut failure = ProcessFailure {
+                at: unix_now(),
+                process: FailedProcess::from_kind(kind.0),
+                reason: None,
+                exit_code: None,
+                description: None,
+                module: None,
+                elevated_pids: take_exited_elevated(),
+                browser_args: std::env::var(crate::browser_flags::BROWSER_ARGS_ENV)
+                    .unwrap_or_default(),
+                safe_mode: crate::safe_mode::is_active(),
+            };
+            if let Ok(args2) = args.cast::<ICoreWebView2ProcessFailedEventArgs2>() {
+                let mut reason = COREWEBVIEW2_PROCESS_FAILED_REASON::default();
+                if args2.Reason(&mut reason).is_ok() {
+                    failure.reason = Some(FailureReason::from_reason(reason.0));
+                }
+                let mut code = 0;
+                if args2.ExitCode(&mut code).is_ok() {
+                    failure.exit_code = Some(code);
+                }
+                let mut description = PWSTR::null();
+                if args2.ProcessDescription(&mut description).is_ok() {
+                    failure.description =
+                        Some(webview2_com::take_pwstr(description)).filter(|d| !d.is_empty());
+                }
+            }
+            if let Ok(args3) = args.cast::<ICoreWebView2ProcessFailedEventArgs3>() {
+                let mut module = PWSTR::null();
+                if args3.FailureSourceModulePath(&mut module).is_ok() {
+                    failure.module =
+                        Some(webview2_com::take_pwstr(module)).filter(|m| !m.is_empty());
+                }
+            }
+            record(failure);
+            Ok(())
+        }));
+        let mut token = 0;
+        match core.add_ProcessFailed(&handler, &mut token) {
+            Ok(()) => tracing::info!("Logging WebView2 process failures"),
+            Err(e) => warn!("Failed to watch WebView2 process failures: {}", e),
+        }
+    });
+    if let Err(e) = result {
+        warn!("Failed to watch WebView2 process failures: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn install(_window: &tauri::WebviewWindow) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(at: u64) -> ProcessFailure {
+        ProcessFailure {
+            at,
+            process: FailedProcess::from_kind(6),
+            reason: Some(FailureReason::from_reason(3)),
+            exit_code: Some(-1073741819),
+            description: None,
+            module: None,
+            elevated_pids: vec![4242],
+            browser_args: "--enable-zero-copy".to_string(),
+            safe_mode: false,
+        }
+    }
+
+    #[test]
+    fn history_keeps_the_latest_failures() {
+        let mut history = Vec::new();
+        for at in 0..(MAX_FAILURES as u64 + 5) {
+            append(&mut history, failure(at));
+        }
+        assert_eq!(history.len(), MAX_FAILURES);
+        assert_eq!(history[0].at, 5);
+        let json = serde_json::to_value(&history[0]).unwrap();
+        assert_eq!(json["process"], "gpu");
+        assert_eq!(json["reason"], "crashed");
+    }
+}
@@ -0,0 +1,369 @@
+//! Profiles - Cross-platform
+//!
+//! `--profile <name>` picks a profile (default: "default"). Each profile
+//! keeps its own window geometry, page zoom, game volume, overlay layout and
+//! key bindings in `<config dir>/profiles/<name>/profile.json`, so an alt
+//! account on the laptop screen doesn't disturb the main setup. Everything
+//! else stays in the shared settings.
+
+use crate::commands::WindowMode;
+use crate::keybinds::KeyBindings;
+use crate::settings::{self, OverlayLayout};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
+use tracing::{debug, info, warn};
+use ts_rs::TS;
+
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILE_FILE_NAME: &str = "profile.json";
+
+/// Allowed page zoom factors
+pub const ZOOM_RANGE: std::ops::RangeInclusive<f64> = 0.5..=2.0;
+
+/// Outer position and inner size of the window while windowed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Overlay visibility and layout
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub struct OverlayState {
+    pub visible: bool,
+    pub layout: OverlayLayout,
+}
+
+/// Settings kept per profile
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct ProfileSettings {
+    /// Last windowed geometry; `None` centers the window
+    pub window: Option<WindowGeometry>,
+    /// Page zoom factor
+    pub zoom: f64,
+    /// Game audio volume (0-1) set through PACDeluxe; `None` leaves the
+    /// volume mixer alone
+    pub audio_volume: Option<f32>,
+    /// `None` until the overlay is first toggled in this profile
+    pub overlay: Option<OverlayState>,
+    /// Keys for the game's shop shortcuts
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            window: None,
+            zoom: 1.0,
+            audio_volume: None,
+            overlay: None,
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+/// Profile name and settings, as returned by `get_profile`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub settings: ProfileSettings,
+}
+
+/// Profile names become directory names: letters, digits, `-` and `_`
+fn is_valid_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The `--profile <name>` / `--profile=<name>` argument, if any
+fn profile_arg(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Name of the profile this process runs
+pub fn active() -> &'static str {
+    static ACTIVE: OnceLock<String> = OnceLock::new();
+    ACTIVE.get_or_init(|| match profile_arg(std::env::args().skip(1)) {
+        Some(name) if is_valid_name(&name) => name,
+        Some(name) => {
+            warn!(
+                "Invalid profile name {:?}, using {:?}",
+                name, DEFAULT_PROFILE
+            );
+            DEFAULT_PROFILE.to_string()
+        }
+        None => DEFAULT_PROFILE.to_string(),
+    })
+}
+
+/// Directory of the active profile
+pub fn dir() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join("profiles").join(active()))
+}
+
+fn profile_path() -> Option<PathBuf> {
+    dir().map(|dir| dir.join(PROFILE_FILE_NAME))
+}
+
+/// Load the active profile, falling back to defaults when missing or invalid
+pub fn load() -> ProfileSettings {
+    let Some(path) = profile_path() else {
+        return ProfileSettings::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(profile) => {
+                info!("Loaded profile {:?}", active());
+                adopt_migrated(profile)
+            }
+            Err(e) => {
+                warn!(
+                    "Invalid profile file {}, using defaults: {}",
+                    path.display(),
+                    e
+                );
+                ProfileSettings::default()
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!("New profile {:?}", active());
+            adopt_migrated(ProfileSettings::default())
+        }
+        Err(e) => {
+            warn!("Failed to read profile {}: {}", path.display(), e);
+            ProfileSettings::default()
+        }
+    }
+}
+
+/// Copy the active profile's file to `profile.json.bak` before a reset
+pub fn back_up() -> Result<(), String> {
+    let path = profile_path().ok_or_else(|| "No config directory available".to_string())?;
+    settings::back_up_file(&path)
+}
+
+/// Persist the active profile to disk
+pub fn save(profile: &ProfileSettings) -> Result<(), String> {
+    let path = profile_path().ok_or_else(|| "No config directory available".to_string())?;
+    settings::write_json(&path, profile)
+}
+
+/// Take the game volume of a settings file older than profiles
+fn adopt_migrated(mut profile: ProfileSettings) -> ProfileSettings {
+    let Some(volume) = crate::settings_migration::take_audio_volume() else {
+        return profile;
+    };
+    if profile.audio_volume.is_none() {
+        profile.audio_volume = Some(volume);
+        if let Some(path) = profile_path() {
+            if let Err(e) = settings::write_json(&path, &profile) {
+                warn!("Failed to save migrated volume: {}", e);
+            }
+        }
+    }
+    profile
+}
+
+/// Managed settings of the active profile
+pub struct ProfileState(pub Mutex<ProfileSettings>);
+
+type ChangeHook = Box<dyn Fn(&ProfileSettings) + Send + Sync>;
+static ON_CHANGE: OnceLock<ChangeHook> = OnceLock::new();
+
+/// Call `hook` with the new profile settings after every saved change
+pub fn on_change(hook: impl Fn(&ProfileSettings) + Send + Sync + 'static) {
+    if ON_CHANGE.set(Box::new(hook)).is_err() {
+        warn!("Profile change hook already installed");
+    }
+}
+
+impl ProfileState {
+    pub fn get(&self) -> ProfileSettings {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Apply a change, persist it, and return the updated profile
+    pub fn update(
+        &self,
+        change: impl FnOnce(&mut ProfileSettings),
+    ) -> Result<ProfileSettings, String> {
+        let mut profile = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        change(&mut profile);
+        save(&profile)?;
+        let updated = profile.clone();
+        drop(profile);
+        if let Some(hook) = ON_CHANGE.get() {
+            hook(&updated);
+        }
+        Ok(updated)
+    }
+}
+
+/// Reject zoom factors outside `ZOOM_RANGE` (including NaN)
+pub fn validate_zoom(zoom: f64) -> Result<f64, String> {
+    if ZOOM_RANGE.contains(&zoom) {
+        Ok(zoom)
+    } else {
+        Err(format!(
+            "Zoom must be between {} and {}, got {}",
+            ZOOM_RANGE.start(),
+            ZOOM_RANGE.end(),
+            zoom
+        ))
+    }
+}
+
+/// Whether the window's title bar area would be on one of the monitors
+/// (given as position and size), so a saved geometry from a disconnected
+/// screen isn't restored off-screen
+fn is_on_screen(
+    geometry: &WindowGeometry,
+    monitors: &[(PhysicalPosition<i32>, PhysicalSize<u32>)],
+) -> bool {
+    // A point a little inside the top-left corner, where the title bar is
+    let (x, y) = (geometry.x + 40, geometry.y + 10);
+    monitors.iter().any(|(position, size)| {
+        x >= position.x
+            && y >= position.y
+            && x < position.x + size.width as i32
+            && y < position.y + size.height as i32
+    })
+}
+
+/// Latest windowed geometry, saved to the profile on exit
+static WINDOW_GEOMETRY: Mutex<Option<WindowGeometry>> = Mutex::new(None);
+
+fn remember_geometry(window: &WebviewWindow) {
+    if WindowMode::current() != WindowMode::Windowed
+        || window.is_maximized().unwrap_or(false)
+        || window.is_minimized().unwrap_or(false)
+    {
+        return;
+    }
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return;
+    };
+    *WINDOW_GEOMETRY.lock().unwrap_or_else(|e| e.into_inner()) = Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    });
+}
+
+/// Restore the profile's geometry and zoom on a new main window, then keep
+/// track of where the user puts it
+pub fn install(window: &WebviewWindow) {
+    let profile = window.app_handle().state::<ProfileState>().get();
+    if let Some(geometry) = profile.window {
+        let monitors: Vec<_> = window
+            .available_monitors()
+            .unwrap_or_default()
+            .iter()
+            .map(|monitor| (*monitor.position(), *monitor.size()))
+            .collect();
+        if WindowMode::current() == WindowMode::Windowed && is_on_screen(&geometry, &monitors) {
+            let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+            let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+        } else {
+            debug!("Saved window geometry not applied: {:?}", geometry);
+        }
+        WINDOW_GEOMETRY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert(geometry);
+    }
+    if profile.zoom != 1.0 {
+        if let Err(e) = window.set_zoom(profile.zoom) {
+            warn!("Failed to apply zoom {}: {}", profile.zoom, e);
+        }
+    }
+
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        if matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_)) {
+            remember_geometry(&tracked);
+        }
+    });
+}
+
+/// Save the last windowed geometry (on exit)
+pub fn save_window_geometry(app: &AppHandle) {
+    let geometry = *WINDOW_GEOMETRY.lock().unwrap_or_else(|e| e.into_inner());
+    let profiles = app.state::<ProfileState>();
+    if geometry.is_none() || profiles.get().window == geometry {
+        return;
+    }
+    if let Err(e) = profiles.update(|p| p.window = geometry) {
+        warn!("Failed to save window geometry: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn profile_argument_forms() {
+        assert_eq!(
+            profile_arg(args(&["--profile", "alt"])),
+            Some("alt".to_string())
+        );
+        assert_eq!(
+            profile_arg(args(&["--profile=laptop"])),
+            Some("laptop".to_string())
+        );
+        assert_eq!(profile_arg(args(&["--verbose"])), None);
+    }
+
+    #[test]
+    fn profile_names_are_safe_directory_names() {
+        assert!(is_valid_name("alt_account-2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../settings"));
+        assert!(!is_valid_name("my profile"));
+    }
+
+    #[test]
+    fn geometry_on_a_disconnected_screen_is_not_restored() {
+        let monitors = [(PhysicalPosition::new(0, 0), PhysicalSize::new(1920, 1080))];
+        let geometry = |x, y| WindowGeometry {
+            x,
+            y,
+            width: 1280,
+            height: 900,
+        };
+        assert!(is_on_screen(&geometry(100, 100), &monitors));
+        // Was on a second monitor to the right
+        assert!(!is_on_screen(&geometry(2200, 100), &monitors));
+        // Partly off the left edge is fine while the title bar is reachable
+        assert!(is_on_screen(&geometry(-20, 0), &monitors));
+    }
+
+    #[test]
+    fn zoom_is_range_checked() {
+        assert_eq!(validate_zoom(1.25), Ok(1.25));
+        assert!(validate_zoom(3.0).is_err());
+        assert!(validate_zoom(f64::NAN).is_err());
+    }
+}
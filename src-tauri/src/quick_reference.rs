@@ -0,0 +1,91 @@
+//! Quick Reference - Cross-platform
+//!
+//! Synergy breakpoints and item recipes for the overlay's quick-reference
+//! panel (`quick_reference`, off by default). The data is extracted from
+//! the upstream sources at build time (`scripts/generate-quick-reference.cjs`)
+//! into `quick-reference.json` next to the bundled client, so it is the
+//! same information the game shows, for the same game version. Nothing is
+//! fetched at runtime.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+const ASSET_PATH: &str = "/quick-reference.json";
+
+/// Trigger counts of one synergy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SynergyBreakpoints {
+    /// Upstream synergy key (`FIRE`)
+    pub name: String,
+    /// Unit counts that activate a tier, lowest first
+    pub breakpoints: Vec<u32>,
+}
+
+/// Components of one combined item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ItemRecipe {
+    /// Upstream item key (`CHOICE_SCARF`)
+    pub item: String,
+    pub components: Vec<String>,
+}
+
+/// Payload of `get_quick_reference`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuickReference {
+    /// Upstream `package.json` version the data was extracted from
+    pub game_version: String,
+    /// `window.__PAC_BUILD_VERSION__` of the build that bundled it
+    pub build_version: String,
+    pub synergies: Vec<SynergyBreakpoints>,
+    pub recipes: Vec<ItemRecipe>,
+}
+
+/// Read the bundled file, rejecting data that cannot be shown as is
+fn parse(bytes: &[u8]) -> Result<QuickReference, String> {
+    let reference: QuickReference = serde_json::from_slice(bytes)
+        .map_err(|e| format!("Quick-reference data is unreadable: {}", e))?;
+    if reference.synergies.is_empty() && reference.recipes.is_empty() {
+        return Err("Quick-reference data is empty".to_string());
+    }
+    if let Some(synergy) = reference
+        .synergies
+        .iter()
+        .find(|s| s.breakpoints.is_empty() || !s.breakpoints.windows(2).all(|w| w[0] < w[1]))
+    {
+        return Err(format!(
+            "Quick-reference data has bad breakpoints for {}",
+            synergy.name
+        ));
+    }
+    Ok(reference)
+}
+
+/// The data bundled with this build
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Result<QuickReference, String> {
+    let asset = app
+        .asset_resolver()
+        .get(ASSET_PATH.to_string())
+        // The resolver can answer unknown paths with index.html
+        .filter(|asset| asset.mime_type.contains("json"))
+        .ok_or("This build has no quick-reference data")?;
+    parse(&asset.bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_data_is_checked() {
+        let good = br#"{"game_version":"5.1","build_version":"abc","synergies":[{"name":"FIRE","breakpoints":[2,4,6,8]}],"recipes":[{"item":"CHOICE_SCARF","components":["FOSSIL_STONE","MYSTIC_WATER"]}]}"#;
+        let reference = parse(good).unwrap();
+        assert_eq!(reference.synergies[0].breakpoints, vec![2, 4, 6, 8]);
+        assert_eq!(reference.recipes[0].components.len(), 2);
+
+        let unsorted = br#"{"game_version":"5.1","build_version":"abc","synergies":[{"name":"FIRE","breakpoints":[4,2]}],"recipes":[]}"#;
+        assert!(parse(unsorted).is_err());
+        let empty = br#"{"game_version":"5.1","build_version":"abc","synergies":[],"recipes":[]}"#;
+        assert!(parse(empty).is_err());
+        assert!(parse(b"<!DOCTYPE html>").is_err());
+    }
+}
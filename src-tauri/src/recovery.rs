@@ -0,0 +1,474 @@
+//! Renderer Recovery - Cross-platform
+//!
+//! Native reload paths that keep working when the page's JavaScript is
+//! wedged, plus the emergency reload hotkey:
+//! - Ctrl+Shift+R: force-reload the webview
+//! - Ctrl+Shift+R held for 1.5s: clear the browser cache, then reload
+//!
+//! Windows: the hotkey is handled by WebView2's AcceleratorKeyPressed event,
+//! which is raised by the browser process before the page sees the key.
+//! Linux: no native key hook; the hotkey is unavailable and reloads fall back
+//! to script evaluation.
+//!
+//! The freeze watchdog complements crash detection for silent hangs: the
+//! injected runtime sends a `heartbeat` every second, and when heartbeats
+//! stop while the window is visible the renderer is marked hung and
+//! (by default) force-reloaded. If heartbeats still don't return, the main
+//! webview is torn down and rebuilt (`recreate_webview`). The retry screen
+//! and mirrors don't run the runtime; while one is shown the watchdog starts
+//! over and waits for a first heartbeat.
+
+use crate::settings::SettingsState;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Url, WebviewWindow};
+use tracing::{info, warn};
+use ts_rs::TS;
+
+/// Hold time that turns a reload into clear-cache-and-reload
+pub const LONG_PRESS: Duration = Duration::from_millis(1500);
+
+/// What a recovery reload should do
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReloadKind {
+    Reload,
+    ClearCacheAndReload,
+}
+
+impl ReloadKind {
+    /// Reload kind for a hotkey held for `held`
+    pub fn for_press(held: Duration) -> Self {
+        if held >= LONG_PRESS {
+            ReloadKind::ClearCacheAndReload
+        } else {
+            ReloadKind::Reload
+        }
+    }
+}
+
+/// Reload through the WebView2 controller (does not depend on page script)
+#[cfg(target_os = "windows")]
+pub fn force_reload(window: &WebviewWindow, kind: ReloadKind) -> Result<(), String> {
+    window
+        .with_webview(move |webview| unsafe {
+            match webview.controller().CoreWebView2() {
+                Ok(core) => reload_core(&core, kind),
+                Err(e) => warn!("Force reload failed: {}", e),
+            }
+        })
+        .map_err(|e| format!("Failed to access webview: {}", e))
+}
+
+/// Linux fallback: script reload (cache clearing is not available)
+#[cfg(target_os = "linux")]
+pub fn force_reload(window: &WebviewWindow, kind: ReloadKind) -> Result<(), String> {
+    if kind == ReloadKind::ClearCacheAndReload {
+        warn!("Cache clearing is not supported on Linux; reloading only");
+    }
+    window
+        .eval("window.location.reload()")
+        .map_err(|e| format!("Failed to reload page: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn reload_core(
+    core: &webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2,
+    kind: ReloadKind,
+) {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows_core::HSTRING;
+
+    match kind {
+        ReloadKind::Reload => {
+            info!("Force reloading webview");
+            if let Err(e) = core.Reload() {
+                warn!("Force reload failed: {}", e);
+            }
+        }
+        ReloadKind::ClearCacheAndReload => {
+            info!("Clearing browser cache and reloading webview");
+            let core_for_reload = core.clone();
+            let handler = CallDevToolsProtocolMethodCompletedHandler::create(Box::new(
+                move |status: windows_core::Result<()>, _json: String| {
+                    if let Err(e) = status {
+                        warn!("Failed to clear browser cache: {}", e);
+                    }
+                    core_for_reload.Reload()
+                },
+            ));
+            let result = core.CallDevToolsProtocolMethod(
+                &HSTRING::from("Network.clearBrowserCache"),
+                &HSTRING::from("{}"),
+                &handler,
+            );
+            if let Err(e) = result {
+                warn!("Failed to clear browser cache: {}", e);
+                let _ = core.Reload();
+            }
+        }
+    }
+}
+
+/// Install the Ctrl+Shift+R emergency reload handler on the main webview
+#[cfg(target_os = "windows")]
+pub fn install_emergency_reload_hotkey(window: &WebviewWindow) {
+    use std::cell::Cell;
+    use std::time::Instant;
+    use webview2_com::AcceleratorKeyPressedEventHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        COREWEBVIEW2_KEY_EVENT_KIND, COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN,
+        COREWEBVIEW2_KEY_EVENT_KIND_KEY_UP,
+    };
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_SHIFT};
+
+    const VK_R: u32 = 0x52;
+
+    let result = window.with_webview(|webview| unsafe {
+        let controller = webview.controller();
+        let Ok(core) = controller.CoreWebView2() else {
+            warn!("Emergency reload hotkey unavailable: no CoreWebView2");
+            return;
+        };
+        // Set on the first (non-repeat) key down; taken on key up
+        let pressed_at: Cell<Option<Instant>> = Cell::new(None);
+
+        let handler = AcceleratorKeyPressedEventHandler::create(Box::new(move |_sender, args| {
+            let Some(args) = args else { return Ok(()) };
+            let mut key = 0u32;
+            args.VirtualKey(&mut key)?;
+            if key != VK_R {
+                return Ok(());
+            }
+            let mut kind = COREWEBVIEW2_KEY_EVENT_KIND::default();
+            args.KeyEventKind(&mut kind)?;
+
+            if kind == COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN {
+                let modifiers_down =
+                    GetKeyState(VK_CONTROL.0 as i32) < 0 && GetKeyState(VK_SHIFT.0 as i32) < 0;
+                if !modifiers_down {
+                    return Ok(());
+                }
+                if pressed_at.get().is_none() {
+                    pressed_at.set(Some(Instant::now()));
+                }
+                // Swallow the key (and its repeats) so the page never sees it
+                args.SetHandled(true)?;
+            } else if kind == COREWEBVIEW2_KEY_EVENT_KIND_KEY_UP {
+                if let Some(start) = pressed_at.take() {
+                    args.SetHandled(true)?;
+                    reload_core(&core, ReloadKind::for_press(start.elapsed()));
+                }
+            }
+            Ok(())
+        }));
+
+        let mut token = 0;
+        if let Err(e) = controller.add_AcceleratorKeyPressed(&handler, &mut token) {
+            warn!("Failed to install emergency reload hotkey: {}", e);
+        } else {
+            info!("Emergency reload hotkey installed (Ctrl+Shift+R)");
+        }
+    });
+    if let Err(e) = result {
+        warn!("Failed to install emergency reload hotkey: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn install_emergency_reload_hotkey(_window: &WebviewWindow) {
+    info!("Emergency reload hotkey is not available on Linux");
+}
+
+/// Heartbeats the page sends per second
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Missed heartbeats before the renderer counts as hung
+const MISSED_HEARTBEATS: u32 = 10;
+/// Time a reload gets to bring heartbeats back before the webview is rebuilt
+const RELOAD_GRACE: Duration = Duration::from_secs(30);
+/// Time allowed for the old main window to be torn down
+const DESTROY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What to do when the renderer stops responding
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum FreezeAction {
+    /// Only log the hang
+    LogOnly,
+    /// Force-reload the webview, then recreate it if that doesn't help
+    #[default]
+    Reload,
+}
+
+/// Recovery step requested by the watchdog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Recovery {
+    Reload,
+    Recreate,
+}
+
+fn is_hung(since_heartbeat: Option<Duration>) -> bool {
+    // No heartbeat yet means the page is still starting up
+    since_heartbeat.is_some_and(|elapsed| elapsed >= HEARTBEAT_INTERVAL * MISSED_HEARTBEATS)
+}
+
+#[derive(Debug, Default)]
+struct WatchdogInner {
+    last_heartbeat: Option<Instant>,
+    hung_since: Option<Instant>,
+    /// Last recovery step taken for the current hang, and when
+    last_step: Option<(Recovery, Instant)>,
+}
+
+/// Managed freeze watchdog state
+#[derive(Default)]
+pub struct FreezeWatchdog(Mutex<WatchdogInner>);
+
+impl FreezeWatchdog {
+    /// Record a heartbeat from the page
+    pub fn heartbeat(&self) {
+        let mut inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(since) = inner.hung_since.take() {
+            info!(
+                "Renderer responsive again after {:.1}s",
+                since.elapsed().as_secs_f32()
+            );
+        }
+        inner.last_step = None;
+        inner.last_heartbeat = Some(Instant::now());
+    }
+
+    /// Whether the renderer is currently considered hung
+    pub fn is_hung(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .hung_since
+            .is_some()
+    }
+
+    /// Forget heartbeats and recovery steps, as at startup (after resume the
+    /// page may take a while to send its first heartbeat)
+    pub fn restart(&self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = WatchdogInner::default();
+    }
+
+    /// When the page last sent a heartbeat
+    pub fn last_heartbeat(&self) -> Option<Instant> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last_heartbeat
+    }
+
+    /// Restart the timeouts without clearing hung state (window was hidden)
+    fn grace(&self, now: Instant) {
+        let mut inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.last_heartbeat.is_some() {
+            inner.last_heartbeat = Some(now);
+        }
+        if let Some((_, at)) = inner.last_step.as_mut() {
+            *at = now;
+        }
+    }
+
+    /// Next recovery step, if one is due. Each hang gets one reload and,
+    /// if heartbeats still don't return, one webview rebuild.
+    fn check(&self, now: Instant) -> Option<Recovery> {
+        let mut inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let step = match inner.last_step {
+            None if is_hung(inner.last_heartbeat.map(|at| now.duration_since(at))) => {
+                inner.hung_since = Some(now);
+                Recovery::Reload
+            }
+            Some((Recovery::Reload, at)) if now.duration_since(at) >= RELOAD_GRACE => {
+                Recovery::Recreate
+            }
+            _ => return None,
+        };
+        inner.last_step = Some((step, now));
+        Some(step)
+    }
+}
+
+/// Builds the main window; registered by the binary so `recreate_webview`
+/// goes through the same construction path as startup.
+pub struct MainWindowFactory(
+    pub Box<dyn Fn(&AppHandle, Url) -> Result<WebviewWindow, String> + Send + Sync>,
+);
+
+/// Set while the main window is being rebuilt so closing it doesn't quit
+static RECREATING_WEBVIEW: AtomicBool = AtomicBool::new(false);
+
+pub fn is_recreating_webview() -> bool {
+    RECREATING_WEBVIEW.load(Ordering::SeqCst)
+}
+
+/// Tear down and rebuild the main webview window, keeping its URL and
+/// geometry. Cookies and storage live in the shared WebView2 user data
+/// folder and survive the rebuild. Blocks until the new window exists.
+pub fn recreate_webview(app: &AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    if RECREATING_WEBVIEW.swap(true, Ordering::SeqCst) {
+        return Err("Webview is already being recreated".to_string());
+    }
+    let result = rebuild(app, window);
+    RECREATING_WEBVIEW.store(false, Ordering::SeqCst);
+    result
+}
+
+fn rebuild(app: &AppHandle, window: WebviewWindow) -> Result<(), String> {
+    let url = window
+        .url()
+        .map_err(|e| format!("Failed to read page URL: {}", e))?;
+    let position = window.outer_position().ok();
+    let size = window.inner_size().ok();
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+
+    warn!("Recreating main webview");
+    window
+        .destroy()
+        .map_err(|e| format!("Failed to close webview: {}", e))?;
+    drop(window);
+
+    // The label is only released once the destroy has been processed
+    let deadline = Instant::now() + DESTROY_TIMEOUT;
+    while app.get_webview_window("main").is_some() {
+        if Instant::now() >= deadline {
+            return Err("Timed out waiting for the old webview to close".to_string());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let factory = app.state::<MainWindowFactory>();
+    let window = (factory.0)(app, url)?;
+    if let Some(size) = size {
+        let _ = window.set_size(size);
+    }
+    if let Some(position) = position {
+        let _ = window.set_position(position);
+    }
+    if maximized {
+        let _ = window.maximize();
+    }
+    if fullscreen {
+        let _ = window.set_fullscreen(true);
+    }
+    crate::ws_inspector::resume_on_new_webview(app);
+    crate::cdp::resume_on_new_webview(app);
+    info!("Main webview recreated");
+    Ok(())
+}
+
+/// Start the freeze watchdog thread
+pub fn spawn_freeze_watchdog(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("freeze-watchdog".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(HEARTBEAT_INTERVAL);
+            if is_recreating_webview() || crate::suspend::is_suspended() {
+                continue;
+            }
+
+            let Some(window) = app.get_webview_window("main") else {
+                continue;
+            };
+            let watchdog = app.state::<FreezeWatchdog>();
+            // The retry screen and mirrors send no heartbeats; wait for the
+            // first one again once the game is back
+            if !window
+                .url()
+                .is_ok_and(|url| crate::health::runs_runtime(&url))
+            {
+                watchdog.restart();
+                continue;
+            }
+            let visible =
+                window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+            if !visible {
+                // Hidden pages may legitimately stall; don't count that time
+                watchdog.grace(Instant::now());
+                continue;
+            }
+            let Some(step) = watchdog.check(Instant::now()) else {
+                continue;
+            };
+
+            let action = app.state::<SettingsState>().get().freeze_action;
+            match (step, action) {
+                (Recovery::Reload, FreezeAction::LogOnly) => warn!(
+                    "Renderer hung: no heartbeat for {}s (recovery disabled)",
+                    MISSED_HEARTBEATS
+                ),
+                (Recovery::Reload, FreezeAction::Reload) => {
+                    warn!(
+                        "Renderer hung: no heartbeat for {}s, reloading",
+                        MISSED_HEARTBEATS
+                    );
+                    if let Err(e) = force_reload(&window, ReloadKind::Reload) {
+                        warn!("Freeze recovery reload failed: {}", e);
+                    }
+                }
+                (Recovery::Recreate, FreezeAction::LogOnly) => {}
+                (Recovery::Recreate, FreezeAction::Reload) => {
+                    warn!("Reload did not restore the renderer");
+                    drop(window);
+                    if let Err(e) = recreate_webview(&app) {
+                        warn!("Freeze recovery recreate failed: {}", e);
+                    }
+                }
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start freeze watchdog: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_press_reloads() {
+        assert_eq!(
+            ReloadKind::for_press(Duration::from_millis(100)),
+            ReloadKind::Reload
+        );
+    }
+
+    #[test]
+    fn watchdog_waits_for_first_heartbeat() {
+        assert!(!is_hung(None));
+        assert!(!is_hung(Some(Duration::from_secs(2))));
+        assert!(is_hung(Some(Duration::from_secs(10))));
+    }
+
+    #[test]
+    fn hang_escalates_from_reload_to_recreate() {
+        let watchdog = FreezeWatchdog::default();
+        let start = Instant::now();
+        watchdog.0.lock().unwrap().last_heartbeat = Some(start);
+
+        let hung_at = start + Duration::from_secs(10);
+        assert_eq!(watchdog.check(hung_at), Some(Recovery::Reload));
+        assert_eq!(watchdog.check(hung_at + Duration::from_secs(5)), None);
+        assert!(watchdog.is_hung());
+
+        let recreate_at = hung_at + RELOAD_GRACE;
+        assert_eq!(watchdog.check(recreate_at), Some(Recovery::Recreate));
+        assert_eq!(watchdog.check(recreate_at + RELOAD_GRACE), None);
+
+        watchdog.heartbeat();
+        assert!(!watchdog.is_hung());
+    }
+
+    #[test]
+    fn long_press_clears_cache() {
+        assert_eq!(
+            ReloadKind::for_press(LONG_PRESS),
+            ReloadKind::ClearCacheAndReload
+        );
+    }
+}
@@ -0,0 +1,496 @@
+//! Recovery Console - Windows (Win32), Linux (terminal)
+//!
+//! For when the game window won't even load. `--recovery` (or "Open
+//! recovery console" in the safe mode dialog) starts this instead of the
+//! app: a plain Win32 window with no webview, so it works with a broken
+//! WebView2 install, a corrupt cache or settings that crash at startup.
+//! On Linux the same actions are offered as a menu in the terminal.
+//!
+//! Actions:
+//! - view the log file
+//! - clear the webview's HTTP, code and GPU shader caches (sign-in and
+//!   local storage are kept)
+//! - reset settings and the active profile (the old files are kept as
+//!   `settings.json.bak` and `profile.json.bak`)
+//! - reinstall the WebView2 runtime (Windows)
+//! - start PACDeluxe, normally or in safe mode
+//!
+//! The log file lives here too: everything the app logs also goes to
+//! `<config dir>/logs/pacdeluxe.log`, which release builds on Windows
+//! otherwise have nowhere to show.
+
+use crate::profile;
+use crate::safe_mode;
+use crate::settings;
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::Command;
+use tracing::{info, warn};
+
+/// Command-line switch that opens the console instead of the app
+pub const RECOVERY_ARG: &str = "--recovery";
+/// Same as `identifier` in tauri.conf.json; names the webview data folder
+const APP_IDENTIFIER: &str = "com.pacdeluxe";
+const LOG_FILE_NAME: &str = "pacdeluxe.log";
+const OLD_LOG_FILE_NAME: &str = "pacdeluxe.old.log";
+/// The log is rotated at startup once it grows past this
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Evergreen WebView2 bootstrapper
+#[cfg(target_os = "windows")]
+const WEBVIEW2_BOOTSTRAPPER_URL: &str = "https://go.microsoft.com/fwlink/p/?LinkId=2124703";
+
+pub fn requested() -> bool {
+    std::env::args().any(|arg| arg == RECOVERY_ARG)
+}
+
+fn log_dir() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join("logs"))
+}
+
+/// Current log file
+pub fn log_path() -> Option<PathBuf> {
+    log_dir().map(|dir| dir.join(LOG_FILE_NAME))
+}
+
+/// Open the log for appending, first moving a large one to
+/// `pacdeluxe.old.log`
+pub fn open_log_file() -> Option<File> {
+    let dir = log_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(LOG_FILE_NAME);
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+        let _ = std::fs::rename(&path, dir.join(OLD_LOG_FILE_NAME));
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .ok()
+}
+
+/// A console action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    ViewLogs,
+    ClearCache,
+    ResetSettings,
+    ReinstallWebView2,
+    StartSafeMode,
+    Start,
+}
+
+impl Action {
+    const ALL: [Action; 6] = [
+        Action::ViewLogs,
+        Action::ClearCache,
+        Action::ResetSettings,
+        Action::ReinstallWebView2,
+        Action::StartSafeMode,
+        Action::Start,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Action::ViewLogs => "View logs",
+            Action::ClearCache => "Clear cache",
+            Action::ResetSettings => "Reset settings",
+            Action::ReinstallWebView2 => "Reinstall WebView2",
+            Action::StartSafeMode => "Start in safe mode",
+            Action::Start => "Start PACDeluxe",
+        }
+    }
+
+    /// Starts the app; the console closes afterwards
+    fn starts_app(self) -> bool {
+        matches!(self, Action::StartSafeMode | Action::Start)
+    }
+
+    /// Result text for the user
+    fn run(self) -> Result<String, String> {
+        info!("Recovery console: {}", self.label());
+        let result = match self {
+            Action::ViewLogs => view_logs(),
+            Action::ClearCache => clear_cache(),
+            Action::ResetSettings => reset_settings(),
+            Action::ReinstallWebView2 => reinstall_webview2(),
+            Action::StartSafeMode => start_app(true),
+            Action::Start => start_app(false),
+        };
+        if let Err(e) = &result {
+            warn!("Recovery console: {} failed: {}", self.label(), e);
+        }
+        result
+    }
+}
+
+fn view_logs() -> Result<String, String> {
+    let path = log_path().ok_or("No config directory")?;
+    if !path.exists() {
+        return Err("No log file yet".to_string());
+    }
+    #[cfg(target_os = "windows")]
+    let mut command = Command::new("notepad.exe");
+    #[cfg(target_os = "linux")]
+    let mut command = Command::new("xdg-open");
+    command
+        .arg(&path)
+        .spawn()
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    Ok(format!("Opened {}", path.display()))
+}
+
+/// Cache folders of the webview's data directory
+fn cache_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        let Some(data) = std::env::var("LOCALAPPDATA")
+            .ok()
+            .map(|dir| PathBuf::from(dir).join(APP_IDENTIFIER).join("EBWebView"))
+        else {
+            return Vec::new();
+        };
+        let profile = data.join("Default");
+        vec![
+            profile.join("Cache"),
+            profile.join("Code Cache"),
+            profile.join("GPUCache"),
+            data.join("GrShaderCache"),
+            data.join("ShaderCache"),
+        ]
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::env::var("XDG_CACHE_HOME")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var("HOME")
+                    .ok()
+                    .map(|home| PathBuf::from(home).join(".cache"))
+            })
+            .map(|cache| vec![cache.join(APP_IDENTIFIER)])
+            .unwrap_or_default()
+    }
+}
+
+fn clear_cache() -> Result<String, String> {
+    let mut cleared = 0;
+    for dir in cache_dirs().into_iter().filter(|dir| dir.exists()) {
+        std::fs::remove_dir_all(&dir)
+            .map_err(|e| format!("Failed to clear {}: {}", dir.display(), e))?;
+        cleared += 1;
+    }
+    Ok(if cleared == 0 {
+        "The cache was already empty".to_string()
+    } else {
+        format!("Cleared {} cache folder(s)", cleared)
+    })
+}
+
+fn reset_settings() -> Result<String, String> {
+    let dir = settings::config_dir().ok_or("No config directory")?;
+    let profile_dir = profile::dir().ok_or("No config directory")?;
+    if !dir.join("settings.json").exists() && !profile_dir.join("profile.json").exists() {
+        return Ok("Settings are already at their defaults".to_string());
+    }
+    // Same as reset_settings(All) in the app: shared settings and the
+    // active profile
+    settings::back_up()?;
+    profile::back_up()?;
+    let mut current = settings::load();
+    current.reset(settings::ResetScope::All);
+    settings::save(&current).map_err(|e| format!("Failed to reset settings: {}", e))?;
+    profile::save(&profile::ProfileSettings::default())
+        .map_err(|e| format!("Failed to reset profile {:?}: {}", profile::active(), e))?;
+    Ok(format!(
+        "Settings and profile {:?} reset; the old files are settings.json.bak and profile.json.bak",
+        profile::active()
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn reinstall_webview2() -> Result<String, String> {
+    let installer = std::env::temp_dir().join("MicrosoftEdgeWebview2Setup.exe");
+    let bytes = tauri::async_runtime::block_on(async {
+        let response = reqwest::get(WEBVIEW2_BOOTSTRAPPER_URL)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Download failed: {}", e))?;
+        response
+            .bytes()
+            .await
+            .map_err(|e| format!("Download failed: {}", e))
+    })?;
+    std::fs::write(&installer, &bytes)
+        .map_err(|e| format!("Failed to save the installer: {}", e))?;
+    Command::new(&installer)
+        .arg("/install")
+        .spawn()
+        .map_err(|e| format!("Failed to start the installer: {}", e))?;
+    Ok("WebView2 installer started; start PACDeluxe once it finishes".to_string())
+}
+
+/// Linux: WebKitGTK comes from the distribution
+#[cfg(target_os = "linux")]
+fn reinstall_webview2() -> Result<String, String> {
+    Err("Reinstall webkit2gtk-4.1 with your distribution's package manager".to_string())
+}
+
+fn start_app(safe: bool) -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut command = Command::new(exe);
+    if safe {
+        command.arg(safe_mode::SAFE_MODE_ARG);
+    }
+    command
+        .spawn()
+        .map_err(|e| format!("Failed to start PACDeluxe: {}", e))?;
+    Ok("Starting PACDeluxe".to_string())
+}
+
+/// Open the console (`--recovery` or the safe mode dialog) as a separate
+/// process
+pub fn launch() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    Command::new(exe)
+        .arg(RECOVERY_ARG)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open the recovery console: {}", e))
+}
+
+#[cfg(target_os = "windows")]
+mod window {
+    use super::Action;
+    use std::sync::atomic::{AtomicIsize, Ordering};
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::Graphics::Gdi::{
+        GetStockObject, GetSysColorBrush, COLOR_BTNFACE, DEFAULT_GUI_FONT,
+    };
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW, LoadCursorW,
+        PostQuitMessage, RegisterClassW, SendMessageW, SetWindowTextW, TranslateMessage,
+        BS_PUSHBUTTON, CW_USEDEFAULT, HMENU, IDC_ARROW, MSG, WINDOW_EX_STYLE, WINDOW_STYLE,
+        WM_COMMAND, WM_DESTROY, WM_SETFONT, WNDCLASSW, WS_CAPTION, WS_CHILD, WS_MINIMIZEBOX,
+        WS_OVERLAPPED, WS_SYSMENU, WS_VISIBLE,
+    };
+
+    const WIDTH: i32 = 400;
+    const BUTTON_HEIGHT: i32 = 30;
+    const ROW: i32 = 38;
+    const MARGIN: i32 = 16;
+    /// Child control IDs of the buttons are 100 + index in `Action::ALL`
+    const FIRST_BUTTON_ID: usize = 100;
+
+    /// Status line, updated from worker threads
+    static STATUS: AtomicIsize = AtomicIsize::new(0);
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn set_status(text: &str) {
+        let hwnd = HWND(STATUS.load(Ordering::SeqCst) as *mut std::ffi::c_void);
+        let text = wide(text);
+        // SAFETY: the text is NUL-terminated and outlives the call
+        unsafe {
+            let _ = SetWindowTextW(hwnd, PCWSTR::from_raw(text.as_ptr()));
+        }
+    }
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        match msg {
+            WM_COMMAND => {
+                let id = wparam.0 & 0xFFFF;
+                let Some(action) = id
+                    .checked_sub(FIRST_BUTTON_ID)
+                    .and_then(|i| Action::ALL.get(i).copied())
+                else {
+                    return LRESULT(0);
+                };
+                if action.starts_app() {
+                    match action.run() {
+                        Ok(_) => {
+                            let _ = DestroyWindow(hwnd);
+                        }
+                        Err(e) => set_status(&e),
+                    }
+                } else {
+                    // Downloads and deletes can take a while; keep the
+                    // window responsive
+                    set_status("Working...");
+                    std::thread::spawn(move || match action.run() {
+                        Ok(text) | Err(text) => set_status(&text),
+                    });
+                }
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+
+    pub fn run() -> Result<(), String> {
+        // SAFETY: plain Win32 window creation and message loop on this
+        // thread; every string passed is NUL-terminated and outlives its call
+        unsafe {
+            let instance: HINSTANCE = GetModuleHandleW(None).map_err(|e| e.to_string())?.into();
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance,
+                hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+                hbrBackground: GetSysColorBrush(COLOR_BTNFACE),
+                lpszClassName: w!("PACDeluxeRecovery"),
+                ..Default::default()
+            };
+            if RegisterClassW(&class) == 0 {
+                return Err("Failed to register the recovery window".to_string());
+            }
+
+            let buttons = Action::ALL.len() as i32;
+            let height = MARGIN * 2 + 52 + buttons * ROW + 80;
+            let window = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                w!("PACDeluxeRecovery"),
+                w!("PACDeluxe Recovery"),
+                WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX | WS_VISIBLE,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                WIDTH,
+                height,
+                None,
+                None,
+                instance,
+                None,
+            )
+            .map_err(|e| format!("Failed to open the recovery window: {}", e))?;
+
+            let font = GetStockObject(DEFAULT_GUI_FONT);
+            let child = |class: PCWSTR, text: &str, style: WINDOW_STYLE, y: i32, h: i32, id| {
+                let text = wide(text);
+                let control = CreateWindowExW(
+                    WINDOW_EX_STYLE::default(),
+                    class,
+                    PCWSTR::from_raw(text.as_ptr()),
+                    WS_CHILD | WS_VISIBLE | style,
+                    MARGIN,
+                    y,
+                    WIDTH - MARGIN * 3,
+                    h,
+                    window,
+                    HMENU(id as *mut std::ffi::c_void),
+                    instance,
+                    None,
+                )
+                .unwrap_or_default();
+                SendMessageW(control, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+                control
+            };
+
+            child(
+                w!("STATIC"),
+                "The game window is not started. Pick an action, then start PACDeluxe.",
+                WINDOW_STYLE::default(),
+                MARGIN,
+                36,
+                0,
+            );
+            for (i, action) in Action::ALL.iter().enumerate() {
+                child(
+                    w!("BUTTON"),
+                    action.label(),
+                    WINDOW_STYLE(BS_PUSHBUTTON as u32),
+                    MARGIN + 44 + i as i32 * ROW,
+                    BUTTON_HEIGHT,
+                    FIRST_BUTTON_ID + i,
+                );
+            }
+            let status = child(
+                w!("STATIC"),
+                "",
+                WINDOW_STYLE::default(),
+                MARGIN + 52 + buttons * ROW,
+                36,
+                0,
+            );
+            STATUS.store(status.0 as isize, Ordering::SeqCst);
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Show the console and return when it is closed
+#[cfg(target_os = "windows")]
+pub fn run() {
+    if let Err(e) = window::run() {
+        warn!("Recovery console: {}", e);
+    }
+}
+
+/// Linux: a numbered menu on the terminal
+#[cfg(target_os = "linux")]
+pub fn run() {
+    use std::io::{BufRead, Write};
+
+    let stdin = std::io::stdin();
+    loop {
+        println!("\nPACDeluxe recovery");
+        for (i, action) in Action::ALL.iter().enumerate() {
+            println!("  {}. {}", i + 1, action.label());
+        }
+        println!("  q. Quit");
+        print!("> ");
+        let _ = std::io::stdout().flush();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 || line.trim() == "q" {
+            return;
+        }
+        let Some(action) = line
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| Action::ALL.get(i).copied())
+        else {
+            continue;
+        };
+        match action.run() {
+            Ok(text) => {
+                println!("{}", text);
+                if action.starts_app() {
+                    return;
+                }
+            }
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_matches_the_tauri_config() {
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        assert_eq!(config["identifier"], APP_IDENTIFIER);
+    }
+}
@@ -0,0 +1,376 @@
+//! Log Redaction - Cross-platform
+//!
+//! Scrubs secrets from formatted log output before it is written anywhere.
+//! Covers OAuth callback URLs (the auth popup logs every navigation),
+//! sensitive query/JSON parameters, bearer tokens, JWTs, and email addresses.
+
+use std::borrow::Cow;
+use std::io::Write;
+use tracing_subscriber::fmt::MakeWriter;
+
+const REDACTED: &str = "<redacted>";
+
+/// Parameter names whose values are always scrubbed (compared case-insensitively)
+const SENSITIVE_KEYS: &[&str] = &[
+    "code",
+    "token",
+    "access_token",
+    "id_token",
+    "idtoken",
+    "refresh_token",
+    "refreshtoken",
+    "oauth_token",
+    "apikey",
+    "api_key",
+    "key",
+    "oobcode",
+    "state",
+    "password",
+    "secret",
+    "client_secret",
+    "authorization",
+    "session",
+    "sessionid",
+];
+
+/// URL fragments identifying an auth flow; everything after `?`/`#` is dropped
+const AUTH_URL_MARKERS: &[&str] = &["/__/auth/", "accounts.google.com", "/oauth", "oauth2"];
+
+/// Redact secrets from a single formatted log line (or any text)
+pub fn redact(input: &str) -> Cow<'_, str> {
+    if !might_contain_secret(input) {
+        return Cow::Borrowed(input);
+    }
+
+    // Parameters go first, on the whole line: a JSON value can sit after
+    // whitespace (`"key": "value"`), in the next word.
+    let input = redact_params(input);
+    let mut out = String::with_capacity(input.len());
+    let mut previous_word = "";
+    for (is_space, segment) in split_whitespace_runs(&input) {
+        if is_space {
+            out.push_str(segment);
+            continue;
+        }
+        if previous_word.eq_ignore_ascii_case("bearer") {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(&redact_word(segment));
+        }
+        previous_word = segment;
+    }
+    Cow::Owned(out)
+}
+
+/// Cheap pre-check so the common case (no secrets) does not allocate
+fn might_contain_secret(input: &str) -> bool {
+    input.contains('=')
+        || input.contains('@')
+        || input.contains("eyJ")
+        || (input.contains('"') && input.contains(':'))
+        || input.to_ascii_lowercase().contains("bearer")
+}
+
+/// Split text into alternating whitespace / non-whitespace runs
+fn split_whitespace_runs(input: &str) -> Vec<(bool, &str)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut current: Option<bool> = None;
+    for (i, c) in input.char_indices() {
+        let is_space = c.is_whitespace();
+        match current {
+            Some(kind) if kind == is_space => {}
+            Some(kind) => {
+                runs.push((kind, &input[start..i]));
+                start = i;
+                current = Some(is_space);
+            }
+            None => current = Some(is_space),
+        }
+    }
+    if let Some(kind) = current {
+        runs.push((kind, &input[start..]));
+    }
+    runs
+}
+
+fn redact_word(word: &str) -> String {
+    let lower = word.to_ascii_lowercase();
+
+    // Auth URLs: keep scheme/host/path for debugging, drop the query entirely.
+    if AUTH_URL_MARKERS.iter().any(|m| lower.contains(m)) {
+        if let Some(cut) = word.find(['?', '#']) {
+            return format!("{}?{}", &word[..cut], REDACTED);
+        }
+    }
+
+    let word = redact_jwts(word);
+    redact_emails(&word)
+}
+
+fn is_value_terminator(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '&' | '#' | '"' | '\'' | ',' | ';' | ')' | '}' | ']' | '>'
+        )
+}
+
+fn is_key_boundary(c: char) -> bool {
+    !(c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Scrub `key=value` and `"key":"value"` pairs for sensitive keys. JSON
+/// pairs may have whitespace around the colon, as pretty-printed JSON and
+/// most JS loggers write them.
+fn redact_params(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(pos) = rest.find(['=', ':']) {
+        let (before, after) = rest.split_at(pos);
+        let separator = &after[..1];
+        let after = &after[1..];
+
+        // Identify the key immediately preceding the separator, ignoring a
+        // closing JSON quote (and, for JSON, the whitespace after it).
+        let json_key = match separator {
+            ":" => before.trim_end().strip_suffix('"'),
+            _ => None,
+        };
+        let key_region = json_key.unwrap_or_else(|| before.strip_suffix('"').unwrap_or(before));
+        let key_start = key_region
+            .char_indices()
+            .rev()
+            .find(|(_, c)| is_key_boundary(*c))
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let key = &key_region[key_start..];
+        let is_json = json_key.is_some();
+        let is_sensitive = (separator == "=" || is_json)
+            && SENSITIVE_KEYS.iter().any(|k| k.eq_ignore_ascii_case(key));
+
+        out.push_str(before);
+        out.push_str(separator);
+
+        if !is_sensitive {
+            rest = after;
+            continue;
+        }
+
+        let spacing = if is_json {
+            &after[..after.len() - after.trim_start().len()]
+        } else {
+            ""
+        };
+        let after = &after[spacing.len()..];
+        out.push_str(spacing);
+
+        // JSON string values keep their quotes: "key":"<redacted>"
+        let (open_quote, value) = match after.strip_prefix('"') {
+            Some(v) => ("\"", v),
+            None => ("", after),
+        };
+        // Quoted values run to the closing quote, spaces included
+        let value_end = if open_quote.is_empty() {
+            value.find(is_value_terminator)
+        } else {
+            value.find('"')
+        }
+        .unwrap_or(value.len());
+        if value_end == 0 {
+            rest = after;
+            continue;
+        }
+        out.push_str(open_quote);
+        out.push_str(REDACTED);
+        rest = &value[value_end..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Replace JWT-shaped substrings (three base64url segments starting `eyJ`)
+fn redact_jwts(word: &str) -> String {
+    let mut out = String::with_capacity(word.len());
+    let mut rest = word;
+    while let Some(start) = rest.find("eyJ") {
+        out.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+            .unwrap_or(candidate.len());
+        let token = &candidate[..end];
+        if token.split('.').filter(|s| !s.is_empty()).count() >= 3 {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(token);
+        }
+        rest = &candidate[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replace `local@domain.tld` substrings with a placeholder
+fn redact_emails(word: &str) -> String {
+    let Some(at) = word.find('@') else {
+        return word.to_string();
+    };
+
+    let is_email_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '+');
+    let local_start = word[..at]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_email_char(*c))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let domain_end = word[at + 1..]
+        .find(|c: char| !is_email_char(c))
+        .map(|i| at + 1 + i)
+        .unwrap_or(word.len());
+    let local = &word[local_start..at];
+    let domain = word[at + 1..domain_end].trim_end_matches('.');
+
+    if local.is_empty() || !domain.contains('.') {
+        // Not an email (e.g. "user@" or an @mention); keep scanning after it.
+        return format!("{}@{}", &word[..at], redact_emails(&word[at + 1..]));
+    }
+
+    format!(
+        "{}<email>{}",
+        &word[..local_start],
+        redact_emails(&word[at + 1 + domain.len()..])
+    )
+}
+
+/// `MakeWriter` wrapper that redacts each formatted event before writing it
+pub struct RedactingMakeWriter<M> {
+    inner: M,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, M> MakeWriter<'a> for RedactingMakeWriter<M>
+where
+    M: MakeWriter<'a>,
+{
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.inner.make_writer(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// Buffers one formatted event and writes the redacted text on drop.
+/// tracing-subscriber creates a fresh writer per event, so the buffer
+/// always holds whole lines and secrets cannot be split across writes.
+pub struct RedactingWriter<W: Write> {
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write> Drop for RedactingWriter<W> {
+    fn drop(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let text = String::from_utf8_lossy(&self.buffer);
+        let _ = self.inner.write_all(redact(&text).as_bytes());
+        let _ = self.inner.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_lines_are_untouched() {
+        let line = "Localhost server listening on http://localhost:37529";
+        assert!(matches!(redact(line), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn auth_callback_urls_lose_their_query() {
+        let line = "Auth popup navigating to: https://x.firebaseapp.com/__/auth/handler?code=abc&state=xyz";
+        assert_eq!(
+            redact(line),
+            "Auth popup navigating to: https://x.firebaseapp.com/__/auth/handler?<redacted>"
+        );
+    }
+
+    #[test]
+    fn sensitive_query_params_are_scrubbed() {
+        assert_eq!(
+            redact("GET /profile?token=secret123&page=2"),
+            "GET /profile?token=<redacted>&page=2"
+        );
+        assert_eq!(redact("apiKey=AIzaSyXYZ"), "apiKey=<redacted>");
+    }
+
+    #[test]
+    fn json_secrets_are_scrubbed() {
+        assert_eq!(
+            redact(r#"{"idToken":"abc.def","name":"x"}"#),
+            r#"{"idToken":"<redacted>","name":"x"}"#
+        );
+    }
+
+    #[test]
+    fn spaced_json_secrets_are_scrubbed() {
+        assert_eq!(
+            redact(r#"{ "idToken": "abc def", "name": "x" }"#),
+            r#"{ "idToken": "<redacted>", "name": "x" }"#
+        );
+        assert_eq!(
+            redact(r#""password" : hunter2, "id": 3"#),
+            r#""password" : <redacted>, "id": 3"#
+        );
+    }
+
+    #[test]
+    fn non_sensitive_keys_survive() {
+        assert_eq!(redact("mode=wmi elevated=3"), "mode=wmi elevated=3");
+    }
+
+    #[test]
+    fn bearer_tokens_and_jwts_are_scrubbed() {
+        assert_eq!(
+            redact("Authorization: Bearer abc123"),
+            "Authorization: Bearer <redacted>"
+        );
+        assert_eq!(
+            redact("got eyJhbGciOi.eyJzdWIiOi.c2lnbmF0dXJl done"),
+            "got <redacted> done"
+        );
+    }
+
+    #[test]
+    fn emails_are_scrubbed() {
+        assert_eq!(
+            redact("signed in as player.one+pac@example.com."),
+            "signed in as <email>."
+        );
+        assert_eq!(redact("ping @everyone"), "ping @everyone");
+    }
+}
@@ -0,0 +1,310 @@
+//! Dynamic Refresh Rate - Windows 11
+//!
+//! Laptop panels with Windows 11 Dynamic Refresh Rate (DRR) run up to 120 Hz
+//! or more and fall back to 60 Hz while the screen is still. The game's
+//! board and menus animate all the time, so the panel never falls back and
+//! the battery pays for a rate nobody needs in the lobby.
+//!
+//! With `dynamic_refresh` on (off by default), PACDeluxe follows the
+//! `pac-page-state` phase: outside a room or match, the display the game
+//! window is on is switched to 60 Hz; when a room starts, the mode saved for
+//! the display (the user's DRR mode) comes back, and again at exit. The
+//! 60 Hz mode is never written to the registry, so a crash leaves nothing
+//! behind past the next sign-in.
+//!
+//! Only panels Windows reports as DRR-capable are switched: on those the
+//! mode change is seamless, while external monitors usually blank for a
+//! second.
+//!
+//! Linux: not available.
+
+use crate::page_state::GamePhase;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Listener, Manager};
+#[cfg(target_os = "windows")]
+use tracing::{debug, info, warn};
+
+/// Refresh rates of the game window's display
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshRateInfo {
+    /// GDI name (`\\.\DISPLAY1`)
+    pub display: String,
+    pub current_hz: u32,
+    /// Rates available at the current resolution, lowest first
+    pub rates: Vec<u32>,
+    /// Windows reports Dynamic Refresh Rate for the display. Reads false
+    /// while the option holds it at 60 Hz.
+    pub dynamic: bool,
+    /// Held at the lobby rate by `dynamic_refresh` right now
+    pub lowered: bool,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+struct State {
+    enabled: bool,
+    phase: GamePhase,
+    /// Display switched to the lobby rate
+    lowered: Option<String>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    enabled: false,
+    phase: GamePhase::Home,
+    lowered: None,
+});
+
+/// The rate to drop to outside a match: 60 Hz, or the closest one below
+/// (59 Hz on some panels). None when nothing is faster.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn lobby_rate(rates: &[u32]) -> Option<u32> {
+    let low = if rates.contains(&60) {
+        60
+    } else {
+        rates
+            .iter()
+            .copied()
+            .filter(|hz| (48..60).contains(hz))
+            .max()?
+    };
+    rates.iter().any(|&hz| hz > low).then_some(low)
+}
+
+/// Name of the display the main window is on
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn game_display(app: &AppHandle) -> Option<String> {
+    app.get_webview_window("main")?
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned())
+}
+
+#[cfg(target_os = "windows")]
+mod modes {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{
+        ChangeDisplaySettingsExW, EnumDisplaySettingsW, CDS_FULLSCREEN, CDS_TYPE, DEVMODEW,
+        DISP_CHANGE_SUCCESSFUL, DM_DISPLAYFREQUENCY, DM_PELSHEIGHT, DM_PELSWIDTH,
+        ENUM_CURRENT_SETTINGS, ENUM_DISPLAY_SETTINGS_MODE,
+    };
+
+    fn wide(display: &str) -> Vec<u16> {
+        display.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn mode(name: &[u16], index: ENUM_DISPLAY_SETTINGS_MODE) -> Option<DEVMODEW> {
+        let mut mode = DEVMODEW {
+            dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+            ..Default::default()
+        };
+        // SAFETY: `name` is NUL-terminated and dmSize gives the struct size
+        unsafe { EnumDisplaySettingsW(PCWSTR::from_raw(name.as_ptr()), index, &mut mode) }
+            .as_bool()
+            .then_some(mode)
+    }
+
+    /// The current mode and the refresh rates at its resolution
+    pub fn current(display: &str) -> Result<(DEVMODEW, Vec<u32>), String> {
+        let name = wide(display);
+        let current = mode(&name, ENUM_CURRENT_SETTINGS)
+            .ok_or_else(|| format!("No display mode for {}", display))?;
+        let mut rates: Vec<u32> = (0u32..)
+            .map_while(|i| mode(&name, ENUM_DISPLAY_SETTINGS_MODE(i)))
+            .filter(|m| {
+                m.dmPelsWidth == current.dmPelsWidth
+                    && m.dmPelsHeight == current.dmPelsHeight
+                    && m.dmBitsPerPel == current.dmBitsPerPel
+            })
+            .map(|m| m.dmDisplayFrequency)
+            .collect();
+        rates.sort_unstable();
+        rates.dedup();
+        Ok((current, rates))
+    }
+
+    fn change(display: &str, mode: Option<&DEVMODEW>, flags: CDS_TYPE) -> Result<(), String> {
+        let name = wide(display);
+        // SAFETY: `name` is NUL-terminated; `mode` came from EnumDisplaySettingsW
+        let result = unsafe {
+            ChangeDisplaySettingsExW(
+                PCWSTR::from_raw(name.as_ptr()),
+                mode.map(|m| m as *const DEVMODEW),
+                HWND::default(),
+                flags,
+                None,
+            )
+        };
+        if result == DISP_CHANGE_SUCCESSFUL {
+            Ok(())
+        } else {
+            Err(format!(
+                "Windows refused the display mode for {}: {}",
+                display, result.0
+            ))
+        }
+    }
+
+    /// Switch `display` to `hz` without saving it to the registry
+    pub fn set_rate(display: &str, hz: u32) -> Result<(), String> {
+        let (mut mode, _) = current(display)?;
+        mode.dmDisplayFrequency = hz;
+        mode.dmFields = DM_DISPLAYFREQUENCY | DM_PELSWIDTH | DM_PELSHEIGHT;
+        change(display, Some(&mode), CDS_FULLSCREEN)
+    }
+
+    /// Back to the mode saved in the registry (the user's, DRR included)
+    pub fn reset(display: &str) -> Result<(), String> {
+        change(display, None, CDS_TYPE(0))
+    }
+}
+
+/// `DISPLAYCONFIG_PATH_BOOST_REFRESH_RATE`: the path runs Dynamic Refresh
+/// Rate; newer than the bindings
+#[cfg(target_os = "windows")]
+const PATH_BOOST_REFRESH_RATE: u32 = 0x10;
+
+#[cfg(target_os = "windows")]
+fn is_dynamic(display: &str) -> bool {
+    use crate::hdr_control::display_config;
+
+    display_config::find_path(display, display_config::QDC_VIRTUAL_REFRESH_RATE_AWARE)
+        .is_ok_and(|path| path.flags & PATH_BOOST_REFRESH_RATE != 0)
+}
+
+#[cfg(target_os = "windows")]
+fn query(display: &str, lowered: bool) -> Result<RefreshRateInfo, String> {
+    let (current, rates) = modes::current(display)?;
+    Ok(RefreshRateInfo {
+        display: display.to_string(),
+        current_hz: current.dmDisplayFrequency,
+        rates,
+        dynamic: is_dynamic(display),
+        lowered,
+    })
+}
+
+/// Lower or restore the rate to match the state
+#[cfg(target_os = "windows")]
+fn update(state: &mut State, display: Option<String>) {
+    let lobby = state.enabled && !state.phase.is_match_in_progress();
+    if !lobby {
+        restore_locked(state);
+        return;
+    }
+    if state.lowered.is_some() {
+        return;
+    }
+    let Some(display) = display else {
+        return;
+    };
+    let info = match query(&display, false) {
+        Ok(info) => info,
+        Err(e) => {
+            warn!("{}", e);
+            return;
+        }
+    };
+    if !info.dynamic {
+        debug!("{} has no Dynamic Refresh Rate; rate left alone", display);
+        return;
+    }
+    let Some(hz) = lobby_rate(&info.rates) else {
+        return;
+    };
+    if info.current_hz <= hz {
+        return;
+    }
+    match modes::set_rate(&display, hz) {
+        Ok(()) => {
+            info!("{} at {} Hz outside a match", display, hz);
+            state.lowered = Some(display);
+        }
+        Err(e) => warn!("{}", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn update(_state: &mut State, _display: Option<String>) {}
+
+#[cfg(target_os = "windows")]
+fn restore_locked(state: &mut State) {
+    if let Some(display) = state.lowered.take() {
+        match modes::reset(&display) {
+            Ok(()) => info!("Saved display mode restored on {}", display),
+            Err(e) => warn!("Failed to restore the display mode of {}: {}", display, e),
+        }
+    }
+}
+
+/// Refresh rates of the game window's display
+#[cfg(target_os = "windows")]
+pub fn info(app: &AppHandle) -> Result<RefreshRateInfo, String> {
+    let display = game_display(app).ok_or("The game window is on no display")?;
+    let lowered = {
+        let state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.lowered.as_deref() == Some(display.as_str())
+    };
+    query(&display, lowered)
+}
+
+#[cfg(target_os = "linux")]
+pub fn info(_app: &AppHandle) -> Result<RefreshRateInfo, String> {
+    Err("Refresh rate control is only available on Windows".to_string())
+}
+
+/// Turn `dynamic_refresh` on or off
+pub fn set_enabled(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    if cfg!(target_os = "linux") && enabled {
+        return Err("Refresh rate control is only available on Windows".to_string());
+    }
+    // Asked before locking: the monitor query waits for the main thread,
+    // which takes the lock at exit
+    let display = game_display(app);
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.enabled = enabled;
+    update(&mut state, display);
+    Ok(())
+}
+
+/// Follow the page phase; the first phase event applies `enabled`
+pub fn spawn(app: AppHandle, enabled: bool) {
+    if cfg!(target_os = "linux") {
+        return;
+    }
+    STATE.lock().unwrap_or_else(|e| e.into_inner()).enabled = enabled;
+    let listener_app = app.clone();
+    app.listen_any("pac-page-state", move |event| {
+        let Ok(phase) = serde_json::from_str::<GamePhase>(event.payload()) else {
+            return;
+        };
+        let display = game_display(&listener_app);
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.phase = phase;
+        update(&mut state, display);
+    });
+}
+
+/// Put the saved display mode back (on exit)
+#[cfg(target_os = "windows")]
+pub fn restore() {
+    restore_locked(&mut STATE.lock().unwrap_or_else(|e| e.into_inner()));
+}
+
+#[cfg(target_os = "linux")]
+pub fn restore() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lobby_rate_needs_something_faster() {
+        assert_eq!(lobby_rate(&[48, 60, 120]), Some(60));
+        assert_eq!(lobby_rate(&[59, 144]), Some(59));
+        assert_eq!(lobby_rate(&[60]), None);
+        assert_eq!(lobby_rate(&[75, 120]), None);
+        assert_eq!(lobby_rate(&[]), None);
+    }
+}
@@ -0,0 +1,275 @@
+//! Lobby Reminders - Cross-platform
+//!
+//! One-off reminders for upcoming lobbies and tournaments, added by the
+//! user (`add_reminder`) or from a shared `pacdeluxe://remind?at=<unix
+//! seconds>&label=<text>` link (`add_reminder_link`). Native notifications
+//! fire at T-10 and T-1 minutes, also while the window is hidden in the
+//! tray; the last one also flashes the taskbar entry. Reminders are kept
+//! next to the settings file and dropped once their time has passed.
+
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Url, UserAttentionType};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{debug, info, warn};
+
+const REMINDERS_FILE_NAME: &str = "reminders.json";
+/// Notification lead times, earliest first
+const STAGES_MINUTES: [u64; 2] = [10, 1];
+const MAX_REMINDERS: usize = 32;
+const MAX_LABEL_CHARS: usize = 60;
+/// How far ahead a reminder can be set
+const MAX_AHEAD: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const TICK: Duration = Duration::from_secs(15);
+
+/// Link scheme for shared reminders
+const REMIND_LINK_PREFIX: &str = "pacdeluxe://remind";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Reminder {
+    pub id: u64,
+    pub label: String,
+    /// Start time, unix seconds
+    pub at: u64,
+    /// Lead times (minutes) already notified
+    #[serde(default)]
+    pub notified: Vec<u64>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The stage to notify for now, if any. A reminder added late only gets
+/// the closest stage, not a burst of all the missed ones.
+fn due_stage(reminder: &Reminder, now: u64) -> Option<u64> {
+    if now >= reminder.at {
+        return None;
+    }
+    STAGES_MINUTES
+        .iter()
+        .copied()
+        .filter(|minutes| now + minutes * 60 >= reminder.at)
+        .min()
+        .filter(|minutes| !reminder.notified.contains(minutes))
+}
+
+/// Check a label and start time for a new reminder
+fn validate(label: &str, at: u64, now: u64) -> Result<String, String> {
+    let label = label.trim();
+    if label.chars().count() > MAX_LABEL_CHARS {
+        return Err(format!(
+            "Reminder label is limited to {} characters",
+            MAX_LABEL_CHARS
+        ));
+    }
+    if at <= now {
+        return Err("Reminder time is in the past".to_string());
+    }
+    if at - now > MAX_AHEAD.as_secs() {
+        return Err("Reminders can be set at most 30 days ahead".to_string());
+    }
+    Ok(if label.is_empty() { "Lobby" } else { label }.to_string())
+}
+
+/// Label and start time from a `pacdeluxe://remind` link
+fn parse_link(link: &str) -> Result<(String, u64), String> {
+    let invalid = || format!("Invalid reminder link: {:?}", link);
+    if !link.starts_with(REMIND_LINK_PREFIX) {
+        return Err(invalid());
+    }
+    let url = Url::parse(link).map_err(|_| invalid())?;
+    let mut label = String::new();
+    let mut at = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "at" => at = value.parse::<u64>().ok(),
+            "label" => label = value.into_owned(),
+            _ => {}
+        }
+    }
+    Ok((label, at.ok_or_else(invalid)?))
+}
+
+/// Managed reminder list
+pub struct ReminderStore(Mutex<Vec<Reminder>>);
+
+fn reminders_path() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join(REMINDERS_FILE_NAME))
+}
+
+impl ReminderStore {
+    /// Load saved reminders, dropping past ones
+    pub fn load() -> Self {
+        let now = unix_now();
+        let mut reminders: Vec<Reminder> = reminders_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        reminders.retain(|reminder| reminder.at > now);
+        Self(Mutex::new(reminders))
+    }
+
+    fn save(reminders: &[Reminder]) -> Result<(), String> {
+        let path = reminders_path().ok_or_else(|| "No config directory available".to_string())?;
+        settings::write_json(&path, &reminders)
+    }
+
+    /// Upcoming reminders, soonest first
+    pub fn list(&self) -> Vec<Reminder> {
+        let mut reminders = self.0.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        reminders.sort_by_key(|reminder| reminder.at);
+        reminders
+    }
+
+    pub fn add(&self, label: &str, at: u64) -> Result<Reminder, String> {
+        let label = validate(label, at, unix_now())?;
+        let mut reminders = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if reminders.len() >= MAX_REMINDERS {
+            return Err(format!("At most {} reminders", MAX_REMINDERS));
+        }
+        let reminder = Reminder {
+            id: reminders.iter().map(|r| r.id).max().unwrap_or(0) + 1,
+            label,
+            at,
+            notified: Vec::new(),
+        };
+        reminders.push(reminder.clone());
+        Self::save(&reminders)?;
+        info!("Reminder {:?} added for {}", reminder.label, reminder.at);
+        Ok(reminder)
+    }
+
+    pub fn add_link(&self, link: &str) -> Result<Reminder, String> {
+        let (label, at) = parse_link(link)?;
+        self.add(&label, at)
+    }
+
+    pub fn remove(&self, id: u64) -> Result<(), String> {
+        let mut reminders = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let before = reminders.len();
+        reminders.retain(|reminder| reminder.id != id);
+        if reminders.len() == before {
+            return Err(format!("No reminder with id {}", id));
+        }
+        Self::save(&reminders)
+    }
+
+    /// Mark due stages as notified and drop past reminders. Returns the
+    /// reminders to notify with their stage.
+    fn take_due(&self, now: u64) -> Vec<(Reminder, u64)> {
+        let mut reminders = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut due = Vec::new();
+        for reminder in reminders.iter_mut() {
+            if let Some(stage) = due_stage(reminder, now) {
+                reminder.notified.push(stage);
+                due.push((reminder.clone(), stage));
+            }
+        }
+        let before = reminders.len();
+        reminders.retain(|reminder| reminder.at > now);
+        if !due.is_empty() || reminders.len() != before {
+            if let Err(e) = Self::save(&reminders) {
+                warn!("Failed to save reminders: {}", e);
+            }
+        }
+        due
+    }
+}
+
+fn notify(app: &AppHandle, reminder: &Reminder, stage: u64, now: u64) {
+    let minutes = (reminder.at.saturating_sub(now) + 59) / 60;
+    let body = if minutes <= 1 {
+        format!("{} starts in a minute", reminder.label)
+    } else {
+        format!("{} starts in {} minutes", reminder.label, minutes)
+    };
+    info!("Reminder {:?}: T-{} min", reminder.label, stage);
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("PACDeluxe")
+        .body(&body)
+        .show()
+    {
+        warn!("Failed to show reminder notification: {}", e);
+    }
+    // Last call: make the window hard to miss
+    if stage == STAGES_MINUTES[STAGES_MINUTES.len() - 1] {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.request_user_attention(Some(UserAttentionType::Critical));
+        }
+    }
+}
+
+/// Start the reminder thread
+pub fn spawn(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("reminders".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(TICK);
+            let now = unix_now();
+            for (reminder, stage) in app.state::<ReminderStore>().take_due(now) {
+                notify(&app, &reminder, stage, now);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start reminders: {}", e));
+    debug!("Reminder thread started");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reminder(at: u64, notified: &[u64]) -> Reminder {
+        Reminder {
+            id: 1,
+            label: "Cup".to_string(),
+            at,
+            notified: notified.to_vec(),
+        }
+    }
+
+    #[test]
+    fn stages_escalate_once_each() {
+        let at = 10_000;
+        assert_eq!(due_stage(&reminder(at, &[]), at - 11 * 60), None);
+        assert_eq!(due_stage(&reminder(at, &[]), at - 10 * 60), Some(10));
+        assert_eq!(due_stage(&reminder(at, &[10]), at - 5 * 60), None);
+        assert_eq!(due_stage(&reminder(at, &[10]), at - 60), Some(1));
+        assert_eq!(due_stage(&reminder(at, &[10, 1]), at - 30), None);
+        assert_eq!(due_stage(&reminder(at, &[]), at), None);
+    }
+
+    #[test]
+    fn late_reminders_skip_missed_stages() {
+        let at = 10_000;
+        // Added 30 seconds before: only the last call
+        assert_eq!(due_stage(&reminder(at, &[]), at - 30), Some(1));
+    }
+
+    #[test]
+    fn reminder_links_are_parsed() {
+        let (label, at) =
+            parse_link("pacdeluxe://remind?at=1760000000&label=Weekly%20cup").unwrap();
+        assert_eq!(label, "Weekly cup");
+        assert_eq!(at, 1_760_000_000);
+        assert!(parse_link("pacdeluxe://remind?label=x").is_err());
+        assert!(parse_link("https://example.com/remind?at=1").is_err());
+    }
+
+    #[test]
+    fn reminders_must_be_upcoming() {
+        let now = 1_000_000;
+        assert_eq!(validate("  ", now + 60, now), Ok("Lobby".to_string()));
+        assert!(validate("Cup", now, now).is_err());
+        assert!(validate("Cup", now + 31 * 24 * 3600, now).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! Rendering Backend Detection - Cross-platform
+//!
+//! Detects when the webview compositor has fallen back to software rendering.
+//! Windows: inspects the WebView2 process tree for a GPU process and for
+//! software-rendering switches on its command line.
+//! Linux: WebKitGTK has no GPU process, so only environment overrides and the
+//! page-reported WebGL renderer are used.
+//!
+//! The WebGL renderer string is reported by the injected runtime; it is the
+//! most reliable signal on both platforms (SwiftShader / llvmpipe).
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Whether the webview is GPU accelerated
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RenderingMode {
+    Hardware,
+    Software,
+    /// Not enough information yet (e.g. webview still starting)
+    Unknown,
+}
+
+/// Result of a rendering backend check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RenderingStatus {
+    pub mode: RenderingMode,
+    /// Windows: whether a WebView2 GPU process is running (None on Linux)
+    pub gpu_process: Option<bool>,
+    /// WebGL renderer string reported by the page, if any
+    pub renderer: Option<String>,
+    /// Why software rendering was detected
+    pub reason: Option<String>,
+}
+
+impl RenderingStatus {
+    pub fn is_software(&self) -> bool {
+        self.mode == RenderingMode::Software
+    }
+}
+
+/// Substrings of WebGL renderer names that identify CPU rasterizers
+const SOFTWARE_RENDERERS: &[&str] = &[
+    "swiftshader",
+    "llvmpipe",
+    "softpipe",
+    "software rasterizer",
+    "microsoft basic render driver",
+];
+
+/// Command-line switches that force Chromium onto the CPU
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const SOFTWARE_SWITCHES: &[&str] = &[
+    "--disable-gpu",
+    "--disable-gpu-compositing",
+    "--use-gl=swiftshader",
+    "--use-angle=swiftshader",
+];
+
+/// What we could observe about the webview processes
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Debug, Clone, Default)]
+struct ProcessEvidence {
+    /// Any webview processes found at all
+    webview_running: bool,
+    /// A `--type=gpu-process` child exists
+    gpu_process: bool,
+    /// First software switch found on a webview command line
+    software_switch: Option<String>,
+}
+
+/// Classify the rendering backend from the collected signals
+fn classify(
+    evidence: Option<&ProcessEvidence>,
+    renderer: Option<&str>,
+    env_override: Option<&str>,
+) -> RenderingStatus {
+    let renderer = renderer.map(str::trim).filter(|r| !r.is_empty());
+    let mut status = RenderingStatus {
+        mode: RenderingMode::Unknown,
+        gpu_process: evidence
+            .filter(|e| e.webview_running)
+            .map(|e| e.gpu_process),
+        renderer: renderer.map(str::to_string),
+        reason: None,
+    };
+
+    let software_renderer = renderer.filter(|r| {
+        SOFTWARE_RENDERERS
+            .iter()
+            .any(|s| r.to_ascii_lowercase().contains(s))
+    });
+
+    status.reason = if let Some(name) = software_renderer {
+        Some(format!("WebGL is using a software renderer ({})", name))
+    } else if let Some(switch) = evidence.and_then(|e| e.software_switch.as_deref()) {
+        Some(format!("WebView2 was started with {}", switch))
+    } else if let Some(var) = env_override {
+        Some(format!("{} forces software rendering", var))
+    } else if evidence.is_some_and(|e| e.webview_running && !e.gpu_process) {
+        Some("WebView2 has no GPU process".to_string())
+    } else {
+        None
+    };
+
+    status.mode = if status.reason.is_some() {
+        RenderingMode::Software
+    } else if renderer.is_some() || status.gpu_process == Some(true) {
+        RenderingMode::Hardware
+    } else {
+        RenderingMode::Unknown
+    };
+    status
+}
+
+/// A WebView2 process started by this app
+#[cfg(target_os = "windows")]
+pub(crate) struct WebviewProcess {
+    pub pid: u32,
+    pub args: Vec<String>,
+}
+
+#[cfg(target_os = "windows")]
+impl WebviewProcess {
+    /// Chromium process type (`--type=`); `None` for the browser process
+    pub fn process_type(&self) -> Option<&str> {
+        self.args.iter().find_map(|arg| arg.strip_prefix("--type="))
+    }
+}
+
+/// WebView2 processes descended from this process, with their command lines
+#[cfg(target_os = "windows")]
+pub(crate) fn webview_processes() -> Vec<WebviewProcess> {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        ProcessRefreshKind::new().with_cmd(UpdateKind::Always),
+    );
+
+    let our_pid = Pid::from_u32(std::process::id());
+    let is_descendant = |mut pid: Pid| {
+        // Bounded walk: WebView2 trees are shallow and PIDs can be reused.
+        for _ in 0..8 {
+            match system.process(pid).and_then(|p| p.parent()) {
+                Some(parent) if parent == our_pid => return true,
+                Some(parent) => pid = parent,
+                None => return false,
+            }
+        }
+        false
+    };
+
+    system
+        .processes()
+        .iter()
+        .filter(|(pid, process)| {
+            process
+                .name()
+                .to_string_lossy()
+                .to_lowercase()
+                .contains("msedgewebview2")
+                && is_descendant(**pid)
+        })
+        .map(|(pid, process)| WebviewProcess {
+            pid: pid.as_u32(),
+            args: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+        })
+        .collect()
+}
+
+/// Inspect WebView2 processes descended from this process
+#[cfg(target_os = "windows")]
+fn collect_process_evidence() -> Option<ProcessEvidence> {
+    let mut evidence = ProcessEvidence::default();
+    for process in webview_processes() {
+        evidence.webview_running = true;
+        if process.process_type() == Some("gpu-process") {
+            evidence.gpu_process = true;
+        }
+        if evidence.software_switch.is_none() {
+            evidence.software_switch = process
+                .args
+                .iter()
+                .map(|arg| arg.to_lowercase())
+                .find(|arg| SOFTWARE_SWITCHES.contains(&arg.as_str()));
+        }
+    }
+    Some(evidence)
+}
+
+/// WebKitGTK runs compositing in the web process; nothing to inspect
+#[cfg(target_os = "linux")]
+fn collect_process_evidence() -> Option<ProcessEvidence> {
+    None
+}
+
+/// Environment variables that force the webview onto the CPU
+fn env_override() -> Option<&'static str> {
+    const VARS: &[&str] = &["LIBGL_ALWAYS_SOFTWARE", "WEBKIT_DISABLE_COMPOSITING_MODE"];
+    VARS.iter()
+        .copied()
+        .find(|var| std::env::var(var).is_ok_and(|v| !v.is_empty() && v != "0"))
+}
+
+/// Detect whether the webview is rendering on the GPU.
+/// `renderer` is the WebGL `UNMASKED_RENDERER_WEBGL` string from the page.
+pub fn detect(renderer: Option<&str>) -> RenderingStatus {
+    let evidence = collect_process_evidence();
+    let status = classify(evidence.as_ref(), renderer, env_override());
+    debug!(
+        "Rendering: mode={:?}, gpu_process={:?}, reason={:?}",
+        status.mode, status.gpu_process, status.reason
+    );
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webview(gpu_process: bool) -> ProcessEvidence {
+        ProcessEvidence {
+            webview_running: true,
+            gpu_process,
+            software_switch: None,
+        }
+    }
+
+    #[test]
+    fn gpu_process_means_hardware() {
+        let status = classify(Some(&webview(true)), None, None);
+        assert_eq!(status.mode, RenderingMode::Hardware);
+        assert_eq!(status.gpu_process, Some(true));
+    }
+
+    #[test]
+    fn missing_gpu_process_means_software() {
+        let status = classify(Some(&webview(false)), None, None);
+        assert!(status.is_software());
+    }
+
+    #[test]
+    fn swiftshader_renderer_means_software() {
+        let status = classify(
+            Some(&webview(true)),
+            Some("ANGLE (Google, Vulkan 1.3.0 (SwiftShader Device (Subzero)), SwiftShader driver)"),
+            None,
+        );
+        assert!(status.is_software());
+    }
+
+    #[test]
+    fn linux_hardware_renderer_is_trusted() {
+        let status = classify(None, Some("Mesa Intel(R) UHD Graphics 620"), None);
+        assert_eq!(status.mode, RenderingMode::Hardware);
+        assert_eq!(status.gpu_process, None);
+    }
+
+    #[test]
+    fn no_signals_is_unknown() {
+        assert_eq!(classify(None, None, None).mode, RenderingMode::Unknown);
+    }
+}
@@ -0,0 +1,600 @@
+//! Session Report - Cross-platform
+//!
+//! `render_report` writes a shareable summary of the current session (frame
+//! rate, CPU and memory graphs, matches played, system specs) as a
+//! self-contained HTML page or a PNG image, for posting in tech-support
+//! threads. Reports are written to `<config dir>/reports/` and contain no
+//! account names or file paths.
+//!
+//! Matches are counted from page phase changes (`pac-page-state`); the game
+//! does not expose placements to the runtime, so they are not included.
+
+use crate::commands::SystemInfo;
+use crate::history::PerformanceSample;
+use crate::page_state::GamePhase;
+use crate::session::{self, PlayTimeSummary};
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Listener, Manager};
+use tracing::{info, warn};
+
+/// Matches kept for the report
+const MAX_MATCHES: usize = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Html,
+    Png,
+}
+
+impl ReportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Png => "png",
+        }
+    }
+}
+
+/// One match played this session
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct MatchRecord {
+    /// Start time, unix seconds
+    pub started_at: u64,
+    pub duration_secs: u64,
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Matches played since startup
+#[derive(Default)]
+pub struct MatchLog(Mutex<MatchLogInner>);
+
+#[derive(Default)]
+struct MatchLogInner {
+    /// Start of the match in progress
+    current: Option<u64>,
+    matches: Vec<MatchRecord>,
+}
+
+impl MatchLog {
+    fn phase_changed(&self, phase: GamePhase, now: u64) {
+        let mut inner = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match (phase, inner.current) {
+            (GamePhase::InGame, None) => inner.current = Some(now),
+            (GamePhase::InGame, Some(_)) => {}
+            (_, Some(started_at)) => {
+                inner.current = None;
+                if inner.matches.len() == MAX_MATCHES {
+                    inner.matches.remove(0);
+                }
+                inner.matches.push(MatchRecord {
+                    started_at,
+                    duration_secs: now.saturating_sub(started_at),
+                });
+            }
+            (_, None) => {}
+        }
+    }
+
+    /// Finished matches, oldest first
+    pub fn matches(&self) -> Vec<MatchRecord> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .matches
+            .clone()
+    }
+}
+
+/// Start recording matches
+pub fn install(app: &AppHandle) {
+    app.manage(MatchLog::default());
+    let handle = app.clone();
+    app.listen_any("pac-page-state", move |event| {
+        match serde_json::from_str::<GamePhase>(event.payload()) {
+            Ok(phase) => handle.state::<MatchLog>().phase_changed(phase, unix_now()),
+            Err(e) => warn!("Unexpected page state payload: {}", e),
+        }
+    });
+}
+
+/// Everything a report shows
+#[derive(Debug, Clone)]
+pub struct SessionReport {
+    /// Unix seconds
+    pub generated_at: u64,
+    pub version: String,
+    pub system: SystemInfo,
+    pub play_time: PlayTimeSummary,
+    pub matches: Vec<MatchRecord>,
+    pub samples: Vec<PerformanceSample>,
+}
+
+/// Average and minimum of the values, if there are any
+fn summarize(values: impl Iterator<Item = f32>) -> Option<(f32, f32)> {
+    let (count, sum, min) = values
+        .filter(|v| v.is_finite())
+        .fold((0u32, 0.0f32, f32::MAX), |(count, sum, min), v| {
+            (count + 1, sum + v, min.min(v))
+        });
+    (count > 0).then(|| (sum / count as f32, min))
+}
+
+fn format_duration(secs: u64) -> String {
+    let (hours, minutes) = (secs / 3600, secs % 3600 / 60);
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+impl SessionReport {
+    fn date(&self) -> String {
+        session::utc_date(UNIX_EPOCH + Duration::from_secs(self.generated_at))
+    }
+
+    /// One-line system description
+    fn specs(&self) -> String {
+        let system = &self.system;
+        let mut specs = format!(
+            "{}, {} cores, {} MB RAM",
+            system.os, system.cpu_cores, system.total_memory_mb
+        );
+        if let Some(cpu) = &system.cpu_name {
+            specs = format!("{} ({})", specs, cpu);
+        }
+        if let Some(gpu) = &system.gpu_name {
+            specs.push_str(", ");
+            specs.push_str(gpu);
+        }
+        if let Some(driver) = &system.gpu_driver {
+            specs.push_str(" driver ");
+            specs.push_str(driver.display_version.as_ref().unwrap_or(&driver.version));
+        }
+        specs
+    }
+
+    /// Play time, matches and frame rate summary lines
+    fn summary_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Played today: {}, current stretch: {}, matches: {}",
+            format_duration(self.play_time.today_secs),
+            format_duration(self.play_time.continuous_secs),
+            self.matches.len()
+        )];
+        if !self.matches.is_empty() {
+            let total: u64 = self.matches.iter().map(|m| m.duration_secs).sum();
+            lines.push(format!(
+                "Average match length: {}",
+                format_duration(total / self.matches.len() as u64)
+            ));
+        }
+        if let Some((avg, min)) = summarize(self.samples.iter().filter_map(|s| s.fps)) {
+            lines.push(format!("FPS: average {:.0}, lowest {:.0}", avg, min));
+        }
+        if let Some((avg, _)) = summarize(self.samples.iter().map(|s| s.cpu_usage)) {
+            let blocked = summarize(
+                self.samples
+                    .iter()
+                    .map(|s| s.main_thread_blocked_ms_per_min as f32),
+            )
+            .map_or(0.0, |(avg, _)| avg);
+            lines.push(format!(
+                "CPU: average {:.0}%, main thread blocked {:.0} ms/min on average",
+                avg, blocked
+            ));
+        }
+        lines
+    }
+
+    /// Charted series: title, color and (uptime, value) points
+    fn charts(&self) -> Vec<(&'static str, [u8; 3], Vec<(f64, f32)>)> {
+        let series = |value: fn(&PerformanceSample) -> Option<f32>| {
+            self.samples
+                .iter()
+                .filter_map(|s| value(s).map(|v| (s.uptime_secs, v)))
+                .collect::<Vec<_>>()
+        };
+        vec![
+            ("FPS", [74, 222, 128], series(|s| s.fps)),
+            ("CPU %", [96, 165, 250], series(|s| Some(s.cpu_usage))),
+            (
+                "Memory MB",
+                [167, 139, 250],
+                series(|s| Some(s.memory_usage_mb as f32)),
+            ),
+        ]
+    }
+}
+
+/// Upper bound for a chart's y axis
+fn chart_max(points: &[(f64, f32)]) -> f32 {
+    let max = points.iter().map(|p| p.1).fold(0.0, f32::max);
+    if max > 0.0 {
+        max * 1.15
+    } else {
+        1.0
+    }
+}
+
+/// Points scaled into a `width` x `height` box (y grows downwards)
+fn scale_points(points: &[(f64, f32)], width: f64, height: f64) -> Vec<(f64, f64)> {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    let span = (last.0 - first.0).max(1.0);
+    let max = chart_max(points) as f64;
+    points
+        .iter()
+        .map(|(x, y)| {
+            (
+                (x - first.0) / span * width,
+                height - *y as f64 / max * height,
+            )
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Self-contained HTML page with inline SVG charts
+pub fn render_html(report: &SessionReport) -> String {
+    const WIDTH: f64 = 760.0;
+    const HEIGHT: f64 = 120.0;
+
+    let mut html = String::new();
+    html.push_str(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>PACDeluxe session report</title>\n<style>\n\
+         body { margin: 0; padding: 20px; background: #1a1a2e; color: #fff; \
+         font-family: 'Segoe UI', system-ui, sans-serif; }\n\
+         h1 { margin: 0 0 4px; font-size: 20px; }\n\
+         p { margin: 4px 0; font-size: 14px; color: #fffc; }\n\
+         .chart { margin-top: 14px; padding: 8px 10px; background: rgba(255, 255, 255, 0.05); \
+         border: 1px solid rgba(255, 255, 255, 0.1); border-radius: 8px; }\n\
+         .chart h2 { margin: 0 0 6px; font-size: 13px; font-weight: normal; color: #fffc; }\n\
+         table { margin-top: 14px; border-collapse: collapse; font-size: 13px; }\n\
+         td, th { padding: 2px 12px 2px 0; text-align: left; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!(
+        "<h1>PACDeluxe {} session report</h1>\n<p>{} (UTC)</p>\n<p>{}</p>\n",
+        escape_html(&report.version),
+        report.date(),
+        escape_html(&report.specs())
+    ));
+    for line in report.summary_lines() {
+        html.push_str(&format!("<p>{}</p>\n", escape_html(&line)));
+    }
+    for (title, [r, g, b], points) in report.charts() {
+        html.push_str(&format!("<div class=\"chart\">\n<h2>{}", title));
+        if points.is_empty() {
+            html.push_str(" - no data</h2>\n</div>\n");
+            continue;
+        }
+        let polyline = scale_points(&points, WIDTH, HEIGHT)
+            .iter()
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        html.push_str(&format!(
+            " (max {:.0})</h2>\n<svg width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\
+             <polyline fill=\"none\" stroke=\"rgb({r},{g},{b})\" stroke-width=\"1.5\" \
+             points=\"{points}\"/></svg>\n</div>\n",
+            points.iter().map(|p| p.1).fold(0.0, f32::max),
+            w = WIDTH,
+            h = HEIGHT,
+            points = polyline
+        ));
+    }
+    if !report.matches.is_empty() {
+        html.push_str("<table>\n<tr><th>Match</th><th>Length</th></tr>\n");
+        for (i, record) in report.matches.iter().enumerate() {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                i + 1,
+                format_duration(record.duration_secs)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// 3x5 pixel glyphs, one row per entry (bit 2 = left column)
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 3, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 1, 1, 1],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        'A' => [2, 5, 7, 5, 5],
+        'B' => [6, 5, 6, 5, 6],
+        'C' => [3, 4, 4, 4, 3],
+        'D' => [6, 5, 5, 5, 6],
+        'E' => [7, 4, 6, 4, 7],
+        'F' => [7, 4, 6, 4, 4],
+        'G' => [3, 4, 5, 5, 3],
+        'H' => [5, 5, 7, 5, 5],
+        'I' => [7, 2, 2, 2, 7],
+        'J' => [1, 1, 1, 5, 2],
+        'K' => [5, 5, 6, 5, 5],
+        'L' => [4, 4, 4, 4, 7],
+        'M' => [5, 7, 7, 5, 5],
+        'N' => [6, 5, 5, 5, 5],
+        'O' => [2, 5, 5, 5, 2],
+        'P' => [6, 5, 6, 4, 4],
+        'Q' => [2, 5, 5, 6, 3],
+        'R' => [6, 5, 6, 5, 5],
+        'S' => [3, 4, 2, 1, 6],
+        'T' => [7, 2, 2, 2, 2],
+        'U' => [5, 5, 5, 5, 7],
+        'V' => [5, 5, 5, 5, 2],
+        'W' => [5, 5, 7, 7, 5],
+        'X' => [5, 5, 2, 5, 5],
+        'Y' => [5, 5, 2, 2, 2],
+        'Z' => [7, 1, 2, 4, 7],
+        ' ' => [0, 0, 0, 0, 0],
+        ':' => [0, 2, 0, 2, 0],
+        '.' => [0, 0, 0, 0, 2],
+        ',' => [0, 0, 0, 2, 4],
+        '%' => [5, 1, 2, 4, 5],
+        '-' => [0, 0, 7, 0, 0],
+        '+' => [0, 2, 7, 2, 0],
+        '/' => [1, 1, 2, 4, 4],
+        '(' => [1, 2, 2, 2, 1],
+        ')' => [4, 2, 2, 2, 4],
+        _ => [7, 1, 3, 0, 2],
+    }
+}
+
+/// RGBA image being drawn
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, background: [u8; 3]) -> Self {
+        let [r, g, b] = background;
+        Self {
+            width,
+            height,
+            pixels: [r, g, b, 255].repeat((width * height) as usize),
+        }
+    }
+
+    fn put(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        self.pixels[i..i + 3].copy_from_slice(&color);
+    }
+
+    fn fill_rect(&mut self, x: i64, y: i64, width: i64, height: i64, color: [u8; 3]) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.put(px, py, color);
+            }
+        }
+    }
+
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), color: [u8; 3]) {
+        let steps = (to.0 - from.0)
+            .abs()
+            .max((to.1 - from.1).abs())
+            .ceil()
+            .max(1.0);
+        for step in 0..=steps as i64 {
+            let t = step as f64 / steps;
+            let x = from.0 + (to.0 - from.0) * t;
+            let y = from.1 + (to.1 - from.1) * t;
+            // Two pixels tall so thin lines survive image downscaling
+            self.put(x.round() as i64, y.round() as i64, color);
+            self.put(x.round() as i64, y.round() as i64 + 1, color);
+        }
+    }
+
+    /// Draw text at `scale` pixels per glyph dot; returns the width used
+    fn text(&mut self, x: i64, y: i64, text: &str, scale: i64, color: [u8; 3]) -> i64 {
+        let mut cursor = x;
+        for c in text.chars() {
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (4 >> col) != 0 {
+                        self.fill_rect(
+                            cursor + col * scale,
+                            y + row as i64 * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+            cursor += 4 * scale;
+        }
+        cursor - x
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| format!("Failed to encode report: {}", e))?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(|e| format!("Failed to encode report: {}", e))?;
+        writer
+            .finish()
+            .map_err(|e| format!("Failed to encode report: {}", e))?;
+        Ok(out)
+    }
+}
+
+/// Fit text to `max_chars` glyphs
+fn clip(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut clipped: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        clipped.push_str("...");
+        clipped
+    }
+}
+
+/// PNG image of the report
+pub fn render_png(report: &SessionReport) -> Result<Vec<u8>, String> {
+    const WIDTH: u32 = 800;
+    const MARGIN: i64 = 20;
+    const CHART_HEIGHT: i64 = 110;
+    const BACKGROUND: [u8; 3] = [26, 26, 46];
+    const PANEL: [u8; 3] = [38, 38, 58];
+    const TEXT: [u8; 3] = [235, 235, 245];
+    const DIM: [u8; 3] = [160, 160, 180];
+
+    let summary = report.summary_lines();
+    let charts = report.charts();
+    let header_height = 70 + summary.len() as i64 * 16;
+    let height = header_height + charts.len() as i64 * (CHART_HEIGHT + 34) + MARGIN;
+    let mut canvas = Canvas::new(WIDTH, height as u32, BACKGROUND);
+    // Glyphs that fit a line at scale 2 (8 px each)
+    let max_chars = ((WIDTH as i64 - 2 * MARGIN) / 8) as usize;
+
+    canvas.text(
+        MARGIN,
+        MARGIN,
+        &format!("PACDeluxe {} session report", report.version),
+        3,
+        TEXT,
+    );
+    canvas.text(
+        MARGIN,
+        MARGIN + 24,
+        &clip(
+            &format!("{} UTC - {}", report.date(), report.specs()),
+            max_chars,
+        ),
+        2,
+        DIM,
+    );
+    for (i, line) in summary.iter().enumerate() {
+        canvas.text(
+            MARGIN,
+            MARGIN + 44 + i as i64 * 16,
+            &clip(line, max_chars),
+            2,
+            TEXT,
+        );
+    }
+
+    let chart_width = WIDTH as i64 - 2 * MARGIN;
+    for (i, (title, color, points)) in charts.iter().enumerate() {
+        let top = header_height + i as i64 * (CHART_HEIGHT + 34);
+        let max = points.iter().map(|p| p.1).fold(0.0, f32::max);
+        let label = if points.is_empty() {
+            format!("{} - no data", title)
+        } else {
+            format!("{} (max {:.0})", title, max)
+        };
+        canvas.text(MARGIN, top, &label, 2, *color);
+        canvas.fill_rect(MARGIN, top + 16, chart_width, CHART_HEIGHT, PANEL);
+        let scaled = scale_points(points, chart_width as f64, CHART_HEIGHT as f64 - 4.0);
+        for pair in scaled.windows(2) {
+            let offset = |(x, y): (f64, f64)| (x + MARGIN as f64, y + (top + 18) as f64);
+            canvas.line(offset(pair[0]), offset(pair[1]), *color);
+        }
+    }
+    canvas.encode()
+}
+
+fn reports_dir() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join("reports"))
+}
+
+/// Render the report and save it; returns the file path
+pub fn write(report: &SessionReport, format: ReportFormat) -> Result<PathBuf, String> {
+    let dir = reports_dir().ok_or_else(|| "No config directory available".to_string())?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!(
+        "session-{}.{}",
+        report.generated_at,
+        format.extension()
+    ));
+    let contents = match format {
+        ReportFormat::Html => render_html(report).into_bytes(),
+        ReportFormat::Png => render_png(report)?,
+    };
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    info!("Session report written to {}", path.display());
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_are_timed_from_phase_changes() {
+        let log = MatchLog::default();
+        log.phase_changed(GamePhase::Preparation, 0);
+        log.phase_changed(GamePhase::InGame, 100);
+        log.phase_changed(GamePhase::InGame, 200);
+        log.phase_changed(GamePhase::AfterGame, 1300);
+        log.phase_changed(GamePhase::Lobby, 1400);
+        assert_eq!(
+            log.matches(),
+            vec![MatchRecord {
+                started_at: 100,
+                duration_secs: 1200
+            }]
+        );
+    }
+
+    #[test]
+    fn summaries_skip_invalid_values() {
+        assert_eq!(summarize(std::iter::empty()), None);
+        assert_eq!(
+            summarize([60.0, f32::NAN, 30.0].into_iter()),
+            Some((45.0, 30.0))
+        );
+    }
+
+    #[test]
+    fn html_escapes_system_strings() {
+        assert_eq!(
+            escape_html("<GPU \"X\" & co>"),
+            "&lt;GPU &quot;X&quot; &amp; co&gt;"
+        );
+    }
+}
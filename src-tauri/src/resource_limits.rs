@@ -0,0 +1,209 @@
+//! Resource Limits - Windows
+//!
+//! PACDeluxe creates a job object at startup, and the WebView2 optimizer
+//! adds each runtime process it sees to it; processes those start inherit
+//! the job. `set_resource_limits` caps the job's committed memory and CPU
+//! share, for machines where the game shares 16 GB with OBS. Kill-on-close
+//! is always set: WebView2 processes left behind by a crash of ours end
+//! with it.
+//!
+//! PACDeluxe itself stays out of the job, so what it starts on purpose (a
+//! restart, the updater's installer, Notepad for the logs) outlives it.
+//!
+//! A process that runs into the memory cap fails its allocation; Chromium
+//! treats that as out of memory and the renderer dies (see
+//! `process_failures.rs`), which the recovery logic reloads.
+//!
+//! Linux: not available.
+
+use crate::settings::ResourceLimitSettings;
+#[cfg(target_os = "windows")]
+use tracing::{debug, info, warn};
+
+/// Limits as the job object takes them
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct JobLimits {
+    memory_bytes: Option<usize>,
+    /// Hundredths of a percent of total CPU time
+    cpu_rate: Option<u32>,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn job_limits(settings: ResourceLimitSettings) -> JobLimits {
+    let settings = settings.validated();
+    JobLimits {
+        memory_bytes: (settings.memory_limit_mb > 0)
+            .then(|| settings.memory_limit_mb as usize * 1024 * 1024),
+        cpu_rate: (settings.cpu_rate_percent > 0).then(|| settings.cpu_rate_percent as u32 * 100),
+    }
+}
+
+/// The job handle, kept open until exit (closing it ends the tree)
+#[cfg(target_os = "windows")]
+static JOB: std::sync::OnceLock<isize> = std::sync::OnceLock::new();
+
+/// PIDs added to the job, pruned of exited ones now and then
+#[cfg(target_os = "windows")]
+static MEMBERS: std::sync::Mutex<Vec<u32>> = std::sync::Mutex::new(Vec::new());
+
+/// Members remembered before exited ones are pruned
+#[cfg(target_os = "windows")]
+const MAX_MEMBERS: usize = 256;
+
+#[cfg(target_os = "windows")]
+fn set_limits(job: windows::Win32::Foundation::HANDLE, limits: JobLimits) -> Result<(), String> {
+    use windows::Win32::System::JobObjects::{
+        JobObjectCpuRateControlInformation, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+        JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_CPU_RATE_CONTROL, JOB_OBJECT_CPU_RATE_CONTROL_ENABLE,
+        JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP, JOB_OBJECT_LIMIT_JOB_MEMORY,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    let mut extended = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+    extended.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+    if let Some(bytes) = limits.memory_bytes {
+        extended.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+        extended.JobMemoryLimit = bytes;
+    }
+    let cpu = JOBOBJECT_CPU_RATE_CONTROL_INFORMATION {
+        ControlFlags: match limits.cpu_rate {
+            Some(_) => JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+            None => JOB_OBJECT_CPU_RATE_CONTROL(0),
+        },
+        Anonymous: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION_0 {
+            CpuRate: limits.cpu_rate.unwrap_or(0),
+        },
+    };
+
+    // SAFETY: each struct matches its information class and size
+    unsafe {
+        SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &extended as *const _ as *const std::ffi::c_void,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        )
+        .map_err(|e| format!("Failed to set the memory limit: {}", e))?;
+        SetInformationJobObject(
+            job,
+            JobObjectCpuRateControlInformation,
+            &cpu as *const _ as *const std::ffi::c_void,
+            std::mem::size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>() as u32,
+        )
+        .map_err(|e| format!("Failed to set the CPU rate limit: {}", e))
+    }
+}
+
+/// Create the job with `settings`' limits. Call before WebView2 starts.
+#[cfg(target_os = "windows")]
+pub fn install(settings: ResourceLimitSettings) {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::JobObjects::CreateJobObjectW;
+
+    // SAFETY: the handle is either stored for the rest of the run or closed
+    unsafe {
+        let job = match CreateJobObjectW(None, PCWSTR::null()) {
+            Ok(job) => job,
+            Err(e) => {
+                warn!("Failed to create job object: {}", e);
+                return;
+            }
+        };
+        let limits = job_limits(settings);
+        match set_limits(job, limits) {
+            Ok(()) => {
+                let _ = JOB.set(job.0 as isize);
+                info!("WebView2 job object created: {:?}", limits);
+            }
+            Err(e) => {
+                warn!("{}", e);
+                let _ = CloseHandle(job);
+            }
+        }
+    }
+}
+
+/// Add a WebView2 process of ours to the job
+#[cfg(target_os = "windows")]
+pub(crate) fn add_process(pid: u32) {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::System::JobObjects::AssignProcessToJobObject;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE};
+
+    let Some(job) = JOB.get() else {
+        return;
+    };
+    let mut members = MEMBERS.lock().unwrap_or_else(|e| e.into_inner());
+    if members.contains(&pid) {
+        return;
+    }
+    if members.len() >= MAX_MEMBERS {
+        members.retain(|&other| crate::process_failures::is_running(other));
+    }
+    // SAFETY: the process handle is closed before returning
+    unsafe {
+        let Ok(process) = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, false, pid) else {
+            debug!("Failed to open WebView2 process {} for the job", pid);
+            return;
+        };
+        match AssignProcessToJobObject(HANDLE(*job as *mut std::ffi::c_void), process) {
+            Ok(()) => {
+                debug!("WebView2 process {} added to the job", pid);
+                members.push(pid);
+            }
+            Err(e) => debug!("Failed to add WebView2 process {} to the job: {}", pid, e),
+        }
+        let _ = CloseHandle(process);
+    }
+}
+
+/// Change the limits of the running job
+#[cfg(target_os = "windows")]
+pub fn apply(settings: ResourceLimitSettings) -> Result<(), String> {
+    use windows::Win32::Foundation::HANDLE;
+
+    let job = JOB
+        .get()
+        .ok_or("No WebView2 job object (creating it failed at startup)")?;
+    let limits = job_limits(settings);
+    set_limits(HANDLE(*job as *mut std::ffi::c_void), limits)?;
+    info!("Resource limits set: {:?}", limits);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub fn install(_settings: ResourceLimitSettings) {}
+
+#[cfg(target_os = "linux")]
+pub fn apply(settings: ResourceLimitSettings) -> Result<(), String> {
+    if settings.is_unlimited() {
+        return Ok(());
+    }
+    Err("Resource limits are only available on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_means_no_limit_and_set_limits_are_clamped() {
+        assert_eq!(
+            job_limits(ResourceLimitSettings::default()),
+            JobLimits {
+                memory_bytes: None,
+                cpu_rate: None,
+            }
+        );
+        let limits = job_limits(ResourceLimitSettings {
+            memory_limit_mb: 100,
+            cpu_rate_percent: 50,
+        });
+        assert_eq!(limits.memory_bytes, Some(1024 * 1024 * 1024));
+        assert_eq!(limits.cpu_rate, Some(5000));
+    }
+}
@@ -1,6 +1,6 @@
 (function() {
-    // Skip on cross-origin pages. on_page_load fires for every navigation
-    // in the main webview, including cross-origin hops during Firebase
+    // Skip on cross-origin pages. The script runs in every document the
+    // main webview creates, including cross-origin hops during Firebase
     // redirect auth (accounts.google.com, <project>.firebaseapp.com). Our
     // overlay installs a fetch interceptor that routes non-local requests
     // through the Rust proxy - running that on Google's auth page would
@@ -13,80 +13,83 @@
         return;
     }
 
-    // Wait for body to exist
-    function init() {
-        if (!document.body) {
-            setTimeout(init, 50);
-            return;
-        }
+    // Each document runs the runtime once; a second run (a stray eval, a
+    // document the engine reuses) would wrap fetch and add every listener
+    // twice.
+    if (window.__pacDeluxeRuntime) {
+        return;
+    }
+    window.__pacDeluxeRuntime = true;
 
-        // Safe localStorage wrapper (setItem can throw QuotaExceededError)
-        function lsSet(key, value) {
-            try { localStorage.setItem(key, value); } catch(e) {}
-        }
+    // Fixes and additions the user can turn off (set_injection_flags),
+    // set by main.rs ahead of this script. A missing flag means on.
+    const injectionFlags = window.__PAC_INJECTION_FLAGS__ || {};
+    function flagOn(name) {
+        return injectionFlags[name] !== false;
+    }
 
-        // === SCROLLBAR BUG FIX ===
-        // Hide all scrollbars and fix viewport sizing issues
-        // Problem: 100vh/100vw can cause overflow due to browser UI
-        // Solution: Use 100% sizing with overflow containment
-        const scrollbarFix = document.createElement('style');
-        scrollbarFix.textContent = `
-            /* Hide all scrollbars globally */
-            * {
-                scrollbar-width: none !important;
-                -ms-overflow-style: none !important;
-            }
-            *::-webkit-scrollbar {
-                display: none !important;
-                width: 0 !important;
-                height: 0 !important;
-            }
-            /* Fix viewport sizing - use % instead of vh/vw to avoid browser UI issues */
-            html {
-                overflow: hidden !important;
-                width: 100% !important;
-                height: 100% !important;
-                max-width: 100% !important;
-                max-height: 100% !important;
-            }
-            body {
-                overflow: hidden !important;
-                width: 100% !important;
-                height: 100% !important;
-                max-width: 100% !important;
-                max-height: 100% !important;
-                margin: 0 !important;
-                padding: 0 !important;
-            }
-            /* Ensure root container fills viewport without overflow */
-            #root {
-                overflow: hidden !important;
-                width: 100% !important;
-                height: 100% !important;
-                max-width: 100vw !important;
-                max-height: 100vh !important;
-            }
-            /* Fix game container to prevent horizontal overflow */
-            /* Note: #game-wrapper must NOT have overflow:hidden - the sidebar
-               is positioned at left:-60px inside it and would be clipped */
-            #game {
-                max-width: 100% !important;
-                overflow: hidden !important;
-            }
-        `;
-        document.head.appendChild(scrollbarFix);
-        console.log('[PACDeluxe] Scrollbar/viewport fix applied');
+    // Setup cost of each runtime block, for get_script_budget: every
+    // `// === NAME ===` block starts with markModule(), which closes the
+    // previous block's span. Only the synchronous part is measured; the
+    // block's watchers and listeners are timed from then on (init()).
+    const moduleInits = [];
+    let currentModule = null;
+    let moduleStart = 0;
+    function markModule(name) {
+        const now = performance.now();
+        if (currentModule) moduleInits.push({ name: currentModule, init_ms: now - moduleStart });
+        currentModule = name;
+        moduleStart = now;
+    }
 
+    // Safe localStorage wrapper (setItem can throw QuotaExceededError)
+    function lsSet(key, value) {
+        try { localStorage.setItem(key, value); } catch(e) {}
+    }
+
+    // Document-start phase. main.rs registers this script to run as each
+    // document is created, before any page script, so the fetch proxy,
+    // window.open and the asset cache are in place before the game's
+    // bundle makes its first request. Nothing here needs the DOM.
+    function documentStart() {
         // === CONTEXT MENU FIX ===
         // Disable default WebView2 context menu on canvas elements.
         // Uses event delegation on document so it works even if Phaser
         // recreates the canvas - no setInterval, no duplicate listeners.
-        document.addEventListener('contextmenu', (e) => {
-            if (e.target && e.target.tagName === 'CANVAS') {
-                e.preventDefault();
-            }
-        });
-        console.log('[PACDeluxe] Canvas context menu disabled');
+        markModule('context-menu-fix');
+        if (flagOn('context_menu_fix')) {
+            document.addEventListener('contextmenu', (e) => {
+                if (e.target && e.target.tagName === 'CANVAS') {
+                    e.preventDefault();
+                }
+            });
+            console.log('[PACDeluxe] Canvas context menu disabled');
+        }
+
+        // === CSP VIOLATION REPORTING ===
+        // When csp_mode is ReportOnly/Enforce the localhost server attaches a
+        // policy to the document. Violations fire securitypolicyviolation on
+        // the document in both modes; forward them to the native log bridge.
+        // This runs at document start, so violations during the first
+        // parse are seen too.
+        markModule('csp-reporting');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+
+            const reported = new Set();
+            document.addEventListener('securitypolicyviolation', (e) => {
+                const key = e.effectiveDirective + ' ' + e.blockedURI;
+                if (reported.has(key) || reported.size >= 100) return;
+                reported.add(key);
+                invoke('log_page_event', {
+                    level: e.disposition === 'enforce' ? 'warn' : 'info',
+                    source: 'csp',
+                    message: e.disposition + ' ' + e.effectiveDirective + ' blocked ' +
+                        (e.blockedURI || 'inline') + ' (' + (e.sourceFile || 'unknown') + ':' + e.lineNumber + ')'
+                }).catch(() => {});
+            });
+        })();
 
         // === NATIVE HTTP PROXY FOR LOCAL SERVING (origin-scoped) ===
         //
@@ -99,6 +102,7 @@
         // Model: proxy everything that is NOT a local asset. This inverts the
         // previous allowlist approach - upstream can add new API endpoints
         // without requiring a PACDeluxe code change.
+        markModule('http-proxy');
         (function() {
             const PROD_HOST = 'pokemon-auto-chess.com';
             const COMMUNITY_SERVERS_URL = 'https://raw.githubusercontent.com/keldaanCommunity/pokemonAutoChess/master/community-servers.md';
@@ -234,6 +238,7 @@
         // auth/popup-blocked. We intercept window.open() to return a mock
         // Window that proxies every operation through Tauri events to the
         // actual popup webview.
+        markModule('auth-popup-bridge');
         (function() {
             const emit = window.__TAURI__?.event?.emit;
             const listen = window.__TAURI__?.event?.listen;
@@ -343,6 +348,7 @@
         // === ASSET CACHE WITH VERSION CHECK ===
         // Intercepts fetch() for static assets (images, JSON, audio)
         // Clears cache when game version changes
+        markModule('asset-cache');
         (function() {
             const CACHE_VERSION_KEY = '__pac_cache_version__';
             const CACHE_ASSETS_KEY = '__pac_discovered_assets__';
@@ -437,7 +443,349 @@
             console.log('[PACDeluxe] Asset cache initialized (v' + currentVersion + ')');
         })();
 
+        // === GRAPHICS ===
+        // settings.graphics (set_graphics, low_spec.rs): WebGL antialiasing,
+        // a frame rate cap and reduced motion. The game creates its WebGL
+        // context before settings can arrive from native code, so the last
+        // known settings are kept in localStorage and used at document
+        // start; antialiasing changes apply from the next page load.
+        markModule('graphics');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            const GRAPHICS_KEY = '__pac_graphics__';
+            const MOTION_STYLE_ID = 'pac-reduce-motion';
+            let graphics = { antialias: true, fps_cap: 0, reduce_motion: false };
+            try {
+                graphics = Object.assign(graphics, JSON.parse(localStorage.getItem(GRAPHICS_KEY) || '{}'));
+            } catch (e) {
+                // Keep the defaults
+            }
+
+            if (!graphics.antialias) {
+                const nativeGetContext = HTMLCanvasElement.prototype.getContext;
+                HTMLCanvasElement.prototype.getContext = function(type, attributes) {
+                    if (/^(webgl2?|experimental-webgl)$/.test(type)) {
+                        attributes = Object.assign({}, attributes, { antialias: false });
+                    }
+                    return nativeGetContext.call(this, type, attributes);
+                };
+            }
+
+            // Frame cap: callbacks wait for the next display frame at least
+            // 1/cap after the last one let through. Every callback of a let
+            // through frame runs in it, so loops stay in step.
+            const nativeRequest = window.requestAnimationFrame.bind(window);
+            const nativeCancel = window.cancelAnimationFrame.bind(window);
+            const pending = new Map();
+            let nextId = 1;
+            let lastFrame = -Infinity;
+            function frameAllowed(time) {
+                if (time === lastFrame) return true;
+                // A little slack, so a 60 Hz cap on a 60 Hz display skips nothing
+                if (time - lastFrame >= 1000 / graphics.fps_cap - 2) {
+                    lastFrame = time;
+                    return true;
+                }
+                return false;
+            }
+            window.requestAnimationFrame = function(callback) {
+                if (!graphics.fps_cap) return nativeRequest(callback);
+                const id = -(nextId++);
+                const step = (time) => {
+                    if (graphics.fps_cap && !frameAllowed(time)) {
+                        pending.set(id, nativeRequest(step));
+                        return;
+                    }
+                    pending.delete(id);
+                    callback(time);
+                };
+                pending.set(id, nativeRequest(step));
+                return id;
+            };
+            window.cancelAnimationFrame = function(id) {
+                if (pending.has(id)) {
+                    nativeCancel(pending.get(id));
+                    pending.delete(id);
+                    return;
+                }
+                nativeCancel(id);
+            };
+
+            function applyMotion() {
+                let style = document.getElementById(MOTION_STYLE_ID);
+                if (!graphics.reduce_motion) {
+                    if (style) style.remove();
+                    return;
+                }
+                if (!style) {
+                    style = document.createElement('style');
+                    style.id = MOTION_STYLE_ID;
+                    style.textContent = '*, *::before, *::after { animation-duration: 0s !important; ' +
+                        'animation-delay: 0s !important; transition-duration: 0s !important; ' +
+                        'transition-delay: 0s !important; scroll-behavior: auto !important; }';
+                    (document.head || document.documentElement).appendChild(style);
+                }
+            }
+
+            function apply(next) {
+                if (!next) return;
+                const antialiasChanged = next.antialias !== graphics.antialias;
+                graphics = Object.assign({}, next);
+                lsSet(GRAPHICS_KEY, JSON.stringify(graphics));
+                applyMotion();
+                if (antialiasChanged) {
+                    console.log('[PACDeluxe] Antialiasing ' + (graphics.antialias ? 'on' : 'off') + ' from the next page load');
+                }
+            }
+
+            applyMotion();
+            invoke('get_settings').then((settings) => apply(settings && settings.graphics)).catch(() => {});
+            window.__TAURI__?.event?.listen('pac-events', (event) => {
+                const payload = event.payload || {};
+                if (payload.type === 'settings_changed' && payload.settings) apply(payload.settings.graphics);
+            });
+        })();
+        markModule(null);
+    }
+
+    // DOM-ready phase: the page fixes and the overlay need document.body
+    function init() {
+        markModule('runtime');
+        // Small bottom-center notification, one at a time: a toast shown
+        // while another is up waits for it. Optional action button and level
+        // (info, success, warning, error); returns a function that dismisses
+        // the toast early, or drops it from the queue.
+        const TOAST_BORDERS = { info: '#0f04', success: '#4ade8088', warning: '#facc1588', error: '#f8717188' };
+        const toastQueue = [];
+        let toastShown = false;
+        function showToast(message, options = {}) {
+            const toast = document.createElement('div');
+            toast.className = 'pac-toast';
+            toast.style.cssText = 'position:fixed;bottom:24px;left:50%;transform:translateX(-50%);background:rgba(0,0,0,0.9);color:#fff;font:12px/1.5 sans-serif;padding:8px 14px;border-radius:8px;z-index:99999;border:1px solid ' + (TOAST_BORDERS[options.level] || TOAST_BORDERS.info) + ';box-shadow:0 2px 10px rgba(0,0,0,0.5);display:flex;gap:10px;align-items:center;';
+            const text = document.createElement('span');
+            text.textContent = message;
+            toast.appendChild(text);
+            let timer = null;
+            const dismiss = () => {
+                const queued = toastQueue.indexOf(show);
+                if (queued !== -1) {
+                    toastQueue.splice(queued, 1);
+                    return;
+                }
+                if (!toast.isConnected) return;
+                clearTimeout(timer);
+                toast.remove();
+                toastShown = false;
+                const next = toastQueue.shift();
+                if (next) next();
+            };
+            if (options.actionLabel) {
+                const button = document.createElement('button');
+                button.textContent = options.actionLabel;
+                button.style.cssText = 'background:rgba(255,255,255,0.1);color:#fff;border:1px solid rgba(255,255,255,0.2);padding:2px 10px;border-radius:6px;cursor:pointer;font-size:12px;';
+                button.onclick = () => {
+                    dismiss();
+                    if (options.onAction) options.onAction();
+                };
+                toast.appendChild(button);
+            }
+            function show() {
+                toastShown = true;
+                document.body.appendChild(toast);
+                timer = setTimeout(dismiss, options.durationMs || 5000);
+            }
+            if (toastShown) {
+                toastQueue.push(show);
+            } else {
+                show();
+            }
+            return dismiss;
+        }
+
+        // Watcher lifecycle: long-running intervals and DOM observers are
+        // started through every() / waitFor() / whilePresent() so each one
+        // stops when it is no longer needed, and their run counts and
+        // main-thread time are reported to observers.rs (get_observer_stats).
+        // Each is charged to the runtime block that started it, or whose
+        // callback was running at the time (get_script_budget).
+        const watchers = new Map();
+        let runningModule = null;
+        function watcherStats(name, kind) {
+            let stats = watchers.get(name);
+            if (!stats) {
+                stats = { name, kind, module: runningModule || currentModule, active: false, runs: 0, busy_ms: 0 };
+                watchers.set(name, stats);
+            }
+            stats.active = true;
+            return stats;
+        }
+        function timed(stats, fn) {
+            return function(...args) {
+                const start = performance.now();
+                const outer = runningModule;
+                runningModule = stats.module;
+                stats.runs++;
+                try {
+                    return fn.apply(this, args);
+                } finally {
+                    runningModule = outer;
+                    stats.busy_ms += performance.now() - start;
+                }
+            };
+        }
+        // Run fn every ms until the returned function is called
+        function every(name, fn, ms) {
+            const stats = watcherStats(name, 'interval');
+            const id = setInterval(timed(stats, fn), ms);
+            return () => {
+                clearInterval(id);
+                stats.active = false;
+            };
+        }
+        // Call found(element) once selector matches, then stop observing.
+        // Returns a function that stops waiting.
+        function waitFor(name, selector, found) {
+            const existing = document.querySelector(selector);
+            if (existing) {
+                found(existing);
+                return () => {};
+            }
+            const stats = watcherStats(name, 'observer');
+            const observer = new MutationObserver(timed(stats, () => {
+                const element = document.querySelector(selector);
+                if (!element) return;
+                stop();
+                found(element);
+            }));
+            function stop() {
+                observer.disconnect();
+                stats.active = false;
+            }
+            observer.observe(document.body, { childList: true, subtree: true });
+            return stop;
+        }
+        // Run fn every ms while selector matches; in between, wait for it
+        // to appear again
+        function whilePresent(name, selector, fn, ms) {
+            waitFor(name + ':wait', selector, () => {
+                fn();
+                const stop = every(name, () => {
+                    if (!document.querySelector(selector)) {
+                        stop();
+                        whilePresent(name, selector, fn, ms);
+                        return;
+                    }
+                    fn();
+                }, ms);
+            });
+        }
+
+        // Client-side navigation: the game routes with history.pushState,
+        // which fires no event, so features that depend on the page call
+        // onRouteChange() to run again after every route change.
+        const routeListeners = [];
+        function onRouteChange(callback) {
+            const module = runningModule || currentModule || 'runtime';
+            routeListeners.push(timed(watcherStats('route:' + module, 'listener'), callback));
+        }
+        (function hookHistory() {
+            let lastPath = window.location.pathname;
+            function notify() {
+                const path = window.location.pathname;
+                if (path === lastPath) return;
+                lastPath = path;
+                for (const callback of routeListeners) {
+                    try { callback(path); } catch (e) { console.error('[PACDeluxe] Route listener failed:', e); }
+                }
+            }
+            for (const name of ['pushState', 'replaceState']) {
+                const original = history[name];
+                history[name] = function(...args) {
+                    const result = original.apply(this, args);
+                    notify();
+                    return result;
+                };
+            }
+            window.addEventListener('popstate', notify);
+        })();
+
+        // Native pushes on the pac-events channel (page_events.rs): modules
+        // register for an event type instead of polling the native side.
+        const pageEventHandlers = {};
+        function onPageEvent(type, handler) {
+            const module = runningModule || currentModule || 'runtime';
+            const stats = watcherStats('event:' + type + ':' + module, 'listener');
+            (pageEventHandlers[type] = pageEventHandlers[type] || []).push(timed(stats, handler));
+        }
+        window.__TAURI__?.event?.listen('pac-events', (event) => {
+            const payload = event.payload || {};
+            for (const handler of pageEventHandlers[payload.type] || []) {
+                try { handler(payload); } catch (e) { console.error('[PACDeluxe] Page event handler failed:', e); }
+            }
+        });
+        // Messages from native code (page_events::toast)
+        onPageEvent('toast', ({ message, level, duration_ms }) => {
+            if (message) showToast(message, { level, durationMs: duration_ms });
+        });
+
+        // === SCROLLBAR BUG FIX ===
+        // Hide all scrollbars and fix viewport sizing issues
+        // Problem: 100vh/100vw can cause overflow due to browser UI
+        // Solution: Use 100% sizing with overflow containment
+        markModule('scrollbar-fix');
+        const scrollbarFix = document.createElement('style');
+        scrollbarFix.textContent = `
+            /* Hide all scrollbars globally */
+            * {
+                scrollbar-width: none !important;
+                -ms-overflow-style: none !important;
+            }
+            *::-webkit-scrollbar {
+                display: none !important;
+                width: 0 !important;
+                height: 0 !important;
+            }
+            /* Fix viewport sizing - use % instead of vh/vw to avoid browser UI issues */
+            html {
+                overflow: hidden !important;
+                width: 100% !important;
+                height: 100% !important;
+                max-width: 100% !important;
+                max-height: 100% !important;
+            }
+            body {
+                overflow: hidden !important;
+                width: 100% !important;
+                height: 100% !important;
+                max-width: 100% !important;
+                max-height: 100% !important;
+                margin: 0 !important;
+                padding: 0 !important;
+            }
+            /* Ensure root container fills viewport without overflow */
+            #root {
+                overflow: hidden !important;
+                width: 100% !important;
+                height: 100% !important;
+                max-width: 100vw !important;
+                max-height: 100vh !important;
+            }
+            /* Fix game container to prevent horizontal overflow */
+            /* Note: #game-wrapper must NOT have overflow:hidden - the sidebar
+               is positioned at left:-60px inside it and would be clipped */
+            #game {
+                max-width: 100% !important;
+                overflow: hidden !important;
+            }
+        `;
+        if (flagOn('scrollbar_fix')) {
+            document.head.appendChild(scrollbarFix);
+            console.log('[PACDeluxe] Scrollbar/viewport fix applied');
+        }
+
         // === MINIMAL STYLING (kept intentionally small for upstream compatibility) ===
+        markModule('styling');
         const perfStyles = document.createElement('style');
         perfStyles.id = 'pac-perf-styles';
         perfStyles.textContent = `
@@ -446,41 +794,42 @@
                 transform: translateY(-25px) !important;
             }
         `;
-        document.head.appendChild(perfStyles);
-        console.log('[PACDeluxe] Minimal styling applied');
-
-        // Prevent duplicate injection - remove any existing overlay and bail if already injected
-        ['pac-perf', 'pac-perf-rust'].forEach(id => {
-            const existing = document.getElementById(id);
-            if (existing) existing.remove();
-        });
-        if (window._pacOverlayInjected) {
-            console.log('[PACDeluxe] Overlay already initialized, skipping');
-            return;
+        if (flagOn('layout_fix')) {
+            document.head.appendChild(perfStyles);
+            console.log('[PACDeluxe] Minimal styling applied');
         }
-        window._pacOverlayInjected = true;
 
         // Create overlay element
         const overlay = document.createElement('div');
         overlay.id = 'pac-perf-rust';
         overlay.innerHTML = `
-            <div style="color:#0f8;font-weight:bold;margin-bottom:6px;border-bottom:1px solid #0f03;padding-bottom:4px;">⚡ PACDeluxe</div>
-            <div>FPS: <span class="fps-val">--</span></div>
-            <div>CPU: <span class="cpu-val">--</span>%</div>
+            <div class="overlay-title" style="color:#0f8;font-weight:bold;margin-bottom:6px;border-bottom:1px solid #0f03;padding-bottom:4px;">⚡ PACDeluxe</div>
+            <div class="row-fps">FPS: <span class="fps-val">--</span></div>
+            <div class="row-cpu">CPU: <span class="cpu-val">--</span>%<span class="cpu-core-val" style="color:#0f08;"></span></div>
             <div>GPU: <span class="gpu-val">--</span>%</div>
+            <div class="row-vram" style="display:none;" title="Video memory used by the WebView2 GPU process (dedicated / shared)">VRAM: <span class="vram-val">--</span> MB</div>
             <div>MEM: <span class="mem-val">--</span> GB</div>
             <div>HZ: <span class="hz-val">--</span></div>
             <div>RTT: <span class="rtt-val">--</span> ms</div>
+            <div title="Mouse-to-frame latency: time from pointer input to the next rendered frame">INPUT: <span class="input-val">--</span> ms</div>
             <div>HDR: <span class="hdr-val">--</span></div>
+            <div title="Main-thread time blocked by long tasks">JS: <span class="jank-val">--</span> ms/min</div>
+            <div title="Continuous play / today">SES: <span class="ses-val">--</span></div>
+            <div class="row-server" style="display:none;" title="Game server status feed">SRV: <span class="server-val">--</span></div>
+            <canvas class="graph-val" width="200" height="48" style="display:none;margin-top:6px;"></canvas>
+            <div class="quick-actions" style="display:flex;gap:4px;margin-top:6px;padding-top:4px;border-top:1px solid #0f03;"></div>
+            <div class="advice-val" style="display:none;margin-top:6px;padding-top:4px;border-top:1px solid #0f03;max-width:220px;white-space:normal;"></div>
         `;
         overlay.style.cssText = 'display:none;position:fixed;top:8px;right:8px;background:rgba(0,0,0,0.9);color:#0f0;font:12px/1.4 monospace;padding:10px 14px;border-radius:6px;z-index:99999;border:1px solid #0f04;min-width:140px;box-shadow:0 2px 10px rgba(0,0,0,0.5);cursor:move;user-select:none;';
         document.body.appendChild(overlay);
 
         // === OVERLAY DRAGGABLE/DOCKABLE ===
+        markModule('overlay');
         let isDragging = false;
         let dragStartX = 0, dragStartY = 0, overlayStartX = 0, overlayStartY = 0;
 
         overlay.addEventListener('mousedown', (e) => {
+            if (e.target.closest('.quick-actions')) return; // buttons, not a drag
             isDragging = true;
             dragStartX = e.clientX;
             dragStartY = e.clientY;
@@ -534,18 +883,176 @@
         // Store element references (not IDs)
         const fpsEl = overlay.querySelector('.fps-val');
         const cpuEl = overlay.querySelector('.cpu-val');
+        const cpuCoreEl = overlay.querySelector('.cpu-core-val');
         const gpuEl = overlay.querySelector('.gpu-val');
+        const vramEl = overlay.querySelector('.vram-val');
         const memEl = overlay.querySelector('.mem-val');
         const hzEl = overlay.querySelector('.hz-val');
         const rttEl = overlay.querySelector('.rtt-val');
+        const inputEl = overlay.querySelector('.input-val');
         const hdrEl = overlay.querySelector('.hdr-val');
+        // Fetched again on the next update
+        onPageEvent('hdr_changed', () => {
+            if (hdrEl) hdrEl.textContent = '--';
+        });
+        const adviceEl = overlay.querySelector('.advice-val');
+        const jankEl = overlay.querySelector('.jank-val');
+        const sesEl = overlay.querySelector('.ses-val');
+        const serverEl = overlay.querySelector('.server-val');
+
+        const graphEl = overlay.querySelector('.graph-val');
+
+        // === QUICK ACTIONS ===
+        // Small buttons for common actions so hotkeys don't need memorizing.
+        // Each calls a native command; results are reported with a toast.
+        markModule('quick-actions');
+        const quickActionsEl = overlay.querySelector('.quick-actions');
+        const QUICK_ACTIONS = [
+            { icon: '⟳', title: 'Reload page', command: 'reload_page' },
+            { icon: '⛶', title: 'Toggle fullscreen (F11)', command: 'toggle_fullscreen' },
+            // Confirmed by a native toast
+            { icon: '📷', title: 'Save screenshot', command: 'capture_screenshot' },
+        ];
+        for (const action of QUICK_ACTIONS) {
+            const button = document.createElement('button');
+            button.textContent = action.icon;
+            button.title = action.title;
+            button.style.cssText = 'background:rgba(255,255,255,0.08);color:#0f0;border:1px solid #0f03;border-radius:4px;padding:1px 6px;cursor:pointer;font-size:12px;';
+            button.addEventListener('click', async (e) => {
+                e.stopPropagation();
+                const invoke = window.__TAURI__?.core?.invoke;
+                if (!invoke) return;
+                try {
+                    const result = await invoke(action.command);
+                    if (action.done) action.done(result);
+                } catch(err) {
+                    showToast(action.title + ' failed: ' + (err.message || err));
+                }
+            });
+            quickActionsEl.appendChild(button);
+        }
 
         // === SETTINGS PERSISTENCE ===
+        markModule('settings-persistence');
         let visible = localStorage.getItem('pac_overlay_visible') === 'true';
         overlay.style.display = visible ? 'block' : 'none';
 
+        // === OVERLAY LAYOUTS ===
+        // Repeated hotkey presses cycle hidden -> layouts[0] -> ... -> hidden.
+        // Hotkey and layout list come from native settings (set_overlay_config);
+        // the current layout is remembered like visibility/position, and per
+        // profile on the native side (set_overlay_state).
+        markModule('overlay-layouts');
+        let overlayConfig = { hotkey: 'Ctrl+Shift+P', layouts: ['Standard', 'Minimal', 'Expanded'] };
+        let layoutIndex = parseInt(localStorage.getItem('pac_overlay_layout') || '0', 10) || 0;
+
+        function currentLayout() {
+            return overlayConfig.layouts[layoutIndex % overlayConfig.layouts.length];
+        }
+
+        function applyLayout() {
+            const layout = currentLayout();
+            for (const child of overlay.children) {
+                if (child === graphEl) {
+                    child.style.display = layout === 'Expanded' ? 'block' : 'none';
+                } else if (child === adviceEl) {
+                    child.style.display = layout !== 'Minimal' && child.childElementCount > 0 ? 'block' : 'none';
+                } else if (child.classList.contains('row-vram') || child.classList.contains('row-server')) {
+                    child.style.display = layout !== 'Minimal' && child.classList.contains('has-data') ? '' : 'none';
+                } else if (!child.classList.contains('row-fps')) {
+                    child.style.display = layout === 'Minimal' ? 'none' : '';
+                }
+            }
+            overlay.style.minWidth = layout === 'Minimal' ? '0' : '140px';
+        }
+
+        function parseHotkey(hotkey) {
+            const parts = hotkey.split('+').map(p => p.trim().toLowerCase());
+            return {
+                ctrl: parts.includes('ctrl'),
+                shift: parts.includes('shift'),
+                alt: parts.includes('alt'),
+                key: parts.filter(p => !['ctrl', 'shift', 'alt'].includes(p))[0] || ''
+            };
+        }
+        let overlayHotkey = parseHotkey(overlayConfig.hotkey);
+
+        function matchesOverlayHotkey(e) {
+            return e.ctrlKey === overlayHotkey.ctrl &&
+                e.shiftKey === overlayHotkey.shift &&
+                e.altKey === overlayHotkey.alt &&
+                e.key.toLowerCase() === overlayHotkey.key;
+        }
+
+        (async function loadOverlayConfig() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            try {
+                const settings = await invoke('get_settings');
+                if (settings && settings.overlay && settings.overlay.layouts.length > 0) {
+                    overlayConfig = settings.overlay;
+                    overlayHotkey = parseHotkey(overlayConfig.hotkey);
+                    applyLayout();
+                }
+                // The profile's state wins over this browser's storage, which
+                // all profiles share
+                const profile = await invoke('get_profile');
+                const saved = profile && profile.settings && profile.settings.overlay;
+                if (saved) {
+                    visible = saved.visible;
+                    layoutIndex = Math.max(0, overlayConfig.layouts.indexOf(saved.layout));
+                    overlay.style.display = visible ? 'block' : 'none';
+                    applyLayout();
+                    syncStatsStream();
+                }
+            } catch(e) {}
+        })();
+
+        onPageEvent('settings_changed', ({ settings }) => {
+            if (!settings || !settings.overlay || settings.overlay.layouts.length === 0) return;
+            const layout = currentLayout();
+            overlayConfig = settings.overlay;
+            overlayHotkey = parseHotkey(overlayConfig.hotkey);
+            layoutIndex = Math.max(0, overlayConfig.layouts.indexOf(layout));
+            applyLayout();
+        });
+
+        applyLayout();
+
+        // Rolling FPS/CPU samples for the Expanded layout graphs
+        const graphFps = [];
+        const graphCpu = [];
+        const GRAPH_POINTS = 100;
+
+        function pushGraphSample(series, value) {
+            series.push(value);
+            if (series.length > GRAPH_POINTS) series.shift();
+        }
+
+        function drawGraphs() {
+            if (!graphEl || currentLayout() !== 'Expanded') return;
+            const ctx = graphEl.getContext('2d');
+            const w = graphEl.width;
+            const h = graphEl.height;
+            ctx.clearRect(0, 0, w, h);
+            const maxFps = Math.max(60, ...graphFps);
+            const plot = (series, max, color) => {
+                ctx.strokeStyle = color;
+                ctx.beginPath();
+                series.forEach((v, i) => {
+                    const x = (i / (GRAPH_POINTS - 1)) * w;
+                    const y = h - (Math.min(v, max) / max) * (h - 2) - 1;
+                    if (i === 0) ctx.moveTo(x, y); else ctx.lineTo(x, y);
+                });
+                ctx.stroke();
+            };
+            plot(graphCpu, 100, '#0ff8');
+            plot(graphFps, maxFps, '#0f0');
+        }
+
         // === COMBINED FPS & REFRESH RATE MEASUREMENT ===
         // Single rAF loop for both metrics to reduce overhead
+        markModule('fps-meter');
         let frameCount = 0;
         let lastFpsTime = performance.now();
         let lastFrameTime = performance.now();
@@ -553,9 +1060,26 @@
         let refreshRate = 0;
         const frameTimes = [];
 
+        // Input latency: oldest pointer event since the last frame, measured
+        // to the start of the next one (event timestamps share the
+        // performance.now() clock)
+        let pendingInputAt = null;
+        let inputLatency = 0;
+        const inputSamples = [];
+
         function measureFrame() {
             const now = performance.now();
 
+            if (pendingInputAt !== null) {
+                const latency = now - pendingInputAt;
+                pendingInputAt = null;
+                if (latency >= 0 && latency < 500) {
+                    inputSamples.push(latency);
+                    if (inputSamples.length > 60) inputSamples.shift();
+                    inputLatency = inputSamples.reduce((a, b) => a + b, 0) / inputSamples.length;
+                }
+            }
+
             // FPS: count frames per second
             frameCount++;
             if (now - lastFpsTime >= 1000) {
@@ -582,7 +1106,15 @@
         }
         requestAnimationFrame(measureFrame);
 
+        // pointerrawupdate fires as input arrives (high-precision mouse mode
+        // requests it); pointermove is aligned to frames and hides queueing
+        const pointerEvent = 'onpointerrawupdate' in window ? 'pointerrawupdate' : 'pointermove';
+        window.addEventListener(pointerEvent, (e) => {
+            if (visible && pendingInputAt === null) pendingInputAt = e.timeStamp;
+        }, { capture: true, passive: true });
+
         // === NETWORK METRICS ===
+        markModule('network-metrics');
         let networkRtt = 0;
         const rttSamples = [];
 
@@ -623,33 +1155,404 @@
             ]);
         }
 
-        // Update overlay using stored element references
-        async function updateOverlay() {
-            if (!visible) return;
-            if (fpsEl) fpsEl.textContent = fps;
-            if (hzEl) hzEl.textContent = refreshRate;
-            if (rttEl) rttEl.textContent = networkRtt || '--';
+        // WebGL renderer string (e.g. "ANGLE (NVIDIA ...)" or "SwiftShader").
+        // Read once from a throwaway context; the game's own context is not touched.
+        let webglRenderer;
+        function getWebglRenderer() {
+            if (webglRenderer !== undefined) return webglRenderer;
+            webglRenderer = null;
+            try {
+                const gl = document.createElement('canvas').getContext('webgl');
+                const info = gl && gl.getExtension('WEBGL_debug_renderer_info');
+                if (info) webglRenderer = gl.getParameter(info.UNMASKED_RENDERER_WEBGL) || null;
+                const lose = gl && gl.getExtension('WEBGL_lose_context');
+                if (lose) lose.loseContext();
+            } catch(e) {}
+            return webglRenderer;
+        }
 
-            // Tauri v2: invoke is at window.__TAURI__.core.invoke
+        // === PERFORMANCE ADVICE ===
+        // Rust evaluates the advisor rules; we supply the page-measured FPS.
+        // Polled every ~10s while the overlay is visible.
+        markModule('performance-advice');
+        const ADVICE_COLORS = { Info: '#0ff', Warning: '#fa0', Critical: '#f55' };
+        // Refreshed every 10 s whatever the stats interval is
+        let lastAdviceAt = 0;
+
+        function renderAdvice(advice) {
+            if (!adviceEl) return;
+            adviceEl.textContent = '';
+            if (!advice || advice.length === 0) {
+                adviceEl.style.display = 'none';
+                return;
+            }
+            for (const item of advice.slice(0, 2)) {
+                const row = document.createElement('div');
+                row.style.color = ADVICE_COLORS[item.severity] || '#0f0';
+                row.textContent = '⚠ ' + item.title;
+                const hint = document.createElement('div');
+                hint.style.cssText = 'color:#aaa;font-size:10px;margin-bottom:3px;';
+                hint.textContent = item.suggestion;
+                adviceEl.appendChild(row);
+                adviceEl.appendChild(hint);
+            }
+            adviceEl.style.display = currentLayout() === 'Minimal' ? 'none' : 'block';
+        }
+
+        async function updateAdvice(invoke) {
+            if (Date.now() - lastAdviceAt < 10000) return;
+            lastAdviceAt = Date.now();
+            try {
+                renderAdvice(await invokeWithTimeout(invoke, 'get_performance_advice', { fps, renderer: getWebglRenderer() }));
+            } catch(e) {
+                console.log('[PACDeluxe] Advice unavailable:', e.message || e);
+            }
+        }
+
+        // === SOFTWARE RENDERING CHECK ===
+        // Runs once after startup (GPU process needs a moment to spawn) and
+        // also reports an outdated GPU driver. Software rendering shows a
+        // banner regardless of overlay visibility: every other optimization
+        // is moot if the compositor is on the CPU.
+        markModule('software-rendering-check');
+        setTimeout(async () => {
             const invoke = window.__TAURI__?.core?.invoke;
-            if (invoke) {
-                try {
-                    // Fetch CPU/Memory stats (with timeout)
-                    const stats = await invokeWithTimeout(invoke, 'get_performance_stats');
-                    if (stats) {
-                        if (cpuEl) cpuEl.textContent = typeof stats.cpu_usage === 'number' ? stats.cpu_usage.toFixed(1) : '--';
-                        if (memEl) memEl.textContent = typeof stats.memory_usage_mb === 'number' ? (stats.memory_usage_mb / 1024).toFixed(2) : '--';
-                    }
-                    // Fetch GPU stats (with timeout)
-                    const gpuStats = await invokeWithTimeout(invoke, 'get_gpu_stats');
-                    if (gpuStats && gpuEl) {
-                        if (gpuStats.available) {
-                            gpuEl.textContent = gpuStats.usage_percent.toFixed(1);
+            if (!invoke) return;
+            let health;
+            try {
+                health = await invokeWithTimeout(invoke, 'get_health', { renderer: getWebglRenderer() });
+            } catch(e) {
+                console.log('[PACDeluxe] Health check unavailable:', e.message || e);
+                return;
+            }
+            if (!health) return;
+
+            // Outdated driver: mention once per driver version
+            const driver = health.gpu_driver;
+            if (driver?.outdated_below) {
+                const shown = 'pac-driver-warning-' + driver.version;
+                if (!localStorage.getItem(shown)) {
+                    lsSet(shown, '1');
+                    showToast('Your ' + driver.vendor + ' GPU driver (' + (driver.display_version || driver.version) +
+                        ') is older than recommended for WebView2. Updating it can fix crashes and stutter.', { durationMs: 12000 });
+                }
+            }
+
+            if (health.rendering.mode !== 'Software') return;
+            console.warn('[PACDeluxe] Software rendering detected:', health.rendering.reason);
+
+            const banner = document.createElement('div');
+            banner.id = 'pac-software-rendering';
+            banner.style.cssText = 'position:fixed;top:12px;left:50%;transform:translateX(-50%);background:rgba(120,0,0,0.95);color:#fff;font:12px/1.5 sans-serif;padding:10px 14px;border-radius:8px;z-index:99998;max-width:420px;border:1px solid #f55;box-shadow:0 2px 10px rgba(0,0,0,0.5);cursor:pointer;';
+            const title = document.createElement('div');
+            title.style.cssText = 'font-weight:bold;margin-bottom:4px;';
+            title.textContent = 'GPU acceleration is off';
+            const body = document.createElement('div');
+            body.style.color = '#fffc';
+            body.textContent = (health.rendering.reason || 'The webview is rendering on the CPU') +
+                '. Performance settings will have little effect until hardware acceleration works. Click to dismiss.';
+            banner.appendChild(title);
+            banner.appendChild(body);
+            banner.onclick = () => banner.remove();
+            document.body.appendChild(banner);
+        }, 8000);
+
+        // === OFFLINE MODE INDICATOR ===
+        // Experimental offline mode (chosen on the retry screen) runs the
+        // bundled UI with the native proxy disabled. Keep that visible and
+        // offer a way back.
+        markModule('offline-indicator');
+        (async function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            let offline = false;
+            try {
+                offline = await invoke('get_offline_mode');
+            } catch (e) {}
+            if (!offline) return;
+
+            const pill = document.createElement('div');
+            pill.id = 'pac-offline-mode';
+            pill.style.cssText = 'position:fixed;bottom:12px;left:50%;transform:translateX(-50%);background:rgba(30,30,50,0.95);color:#fff;font:12px/1.5 sans-serif;padding:6px 12px;border-radius:14px;z-index:99998;border:1px solid #facc15;display:flex;gap:8px;align-items:center;';
+            const label = document.createElement('span');
+            label.textContent = 'Offline mode (experimental): server features are disabled';
+            const online = document.createElement('button');
+            online.textContent = 'Go online';
+            online.style.cssText = 'background:#facc15;color:#1a1a00;border:none;padding:2px 10px;border-radius:4px;cursor:pointer;font-weight:bold;';
+            online.onclick = async () => {
+                try {
+                    await invoke('set_offline_mode', { enabled: false });
+                } catch (e) {}
+                // The retry screen loads the game once the server answers
+                window.location.replace('/pacdeluxe/unreachable');
+            };
+            pill.appendChild(label);
+            pill.appendChild(online);
+            document.body.appendChild(pill);
+        })();
+
+        // === GAME SERVER STATUS BANNER ===
+        // Native side classifies proxied responses (maintenance, outage,
+        // Cloudflare challenge). Counts down, re-probes the server, and
+        // reloads once it answers normally.
+        markModule('server-status-banner');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const listen = window.__TAURI__?.event?.listen;
+            if (!invoke || !listen) return;
+
+            const MESSAGES = {
+                Maintenance: 'Pokemon Auto Chess is down for maintenance',
+                Outage: 'Pokemon Auto Chess servers are not responding',
+                Challenge: 'Pokemon Auto Chess is showing a Cloudflare check'
+            };
+            let banner = null;
+            let timer = null;
+            let delay = 30;
+
+            function hide() {
+                clearInterval(timer);
+                if (banner) banner.remove();
+                banner = null;
+            }
+
+            function show(issue) {
+                hide();
+                delay = Math.min(Math.max(issue.retry_after_secs || 30, 10), 300);
+                banner = document.createElement('div');
+                banner.id = 'pac-upstream-status';
+                banner.style.cssText = 'position:fixed;top:12px;left:50%;transform:translateX(-50%);background:rgba(90,70,0,0.95);color:#fff;font:12px/1.5 sans-serif;padding:10px 14px;border-radius:8px;z-index:99998;max-width:440px;border:1px solid #facc15;box-shadow:0 2px 10px rgba(0,0,0,0.5);';
+                const title = document.createElement('div');
+                title.style.cssText = 'font-weight:bold;margin-bottom:4px;color:#facc15;';
+                title.textContent = (MESSAGES[issue.kind] || 'Game server problem') + ' (HTTP ' + issue.status + ')';
+                const body = document.createElement('div');
+                body.style.color = '#fffc';
+                body.textContent = 'This is a server-side issue, not a PACDeluxe problem.';
+                const countdown = document.createElement('div');
+                countdown.style.cssText = 'margin-top:6px;display:flex;gap:8px;align-items:center;';
+                const label = document.createElement('span');
+                const retry = document.createElement('button');
+                retry.textContent = 'Retry now';
+                retry.style.cssText = 'background:#facc15;color:#1a1a00;border:none;padding:2px 10px;border-radius:4px;cursor:pointer;font-weight:bold;';
+                countdown.appendChild(label);
+                countdown.appendChild(retry);
+                banner.appendChild(title);
+                banner.appendChild(body);
+                banner.appendChild(countdown);
+                document.body.appendChild(banner);
+
+                let remaining = delay;
+                async function attempt() {
+                    clearInterval(timer);
+                    label.textContent = 'Checking...';
+                    try {
+                        const probe = await invoke('probe_upstream');
+                        if (probe.reachable && !probe.issue) {
+                            hide();
+                            window.location.reload();
+                            return;
+                        }
+                    } catch (e) {}
+                    delay = Math.min(delay * 2, 300);
+                    remaining = delay;
+                    tick();
+                    timer = setInterval(tick, 1000);
+                }
+                function tick() {
+                    if (remaining <= 0) {
+                        attempt();
+                        return;
+                    }
+                    label.textContent = 'Retrying in ' + remaining + 's';
+                    remaining--;
+                }
+                retry.onclick = attempt;
+                tick();
+                timer = setInterval(tick, 1000);
+            }
+
+            listen('pac-upstream-status', (event) => {
+                if (event.payload) show(event.payload);
+                else hide();
+            });
+            invoke('get_upstream_issue')
+                .then((issue) => { if (issue) show(issue); })
+                .catch(() => {});
+        })();
+
+        // === SERVER STATUS ROW ===
+        // Only shown while a status feed is configured (set_server_status)
+        markModule('server-status-row');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const listen = window.__TAURI__?.event?.listen;
+            if (!invoke || !listen || !serverEl) return;
+            const COLORS = { online: '#0f0', degraded: '#fa0', maintenance: '#facc15', down: '#f55', unknown: '#888' };
+
+            function show(status) {
+                const row = serverEl.parentElement;
+                if (!status) {
+                    row.classList.remove('has-data');
+                    row.style.display = 'none';
+                    return;
+                }
+                serverEl.textContent = status.state + (typeof status.players === 'number' ? ' (' + status.players + ')' : '');
+                serverEl.style.color = COLORS[status.state] || '#888';
+                row.title = status.error || status.description || 'Game server status feed';
+                row.classList.add('has-data');
+                if (currentLayout() !== 'Minimal') row.style.display = '';
+            }
+
+            listen('pac-server-status', (event) => show(event.payload));
+            invoke('get_server_status').then(show).catch(() => {});
+        })();
+
+        // === LONG TASK / LAYOUT SHIFT OBSERVER ===
+        // Batches main-thread long tasks (with the browser's attribution) and
+        // layout shift to Rust every 5s. Lets the overlay tell JS-bound
+        // slowness apart from GPU-bound slowness.
+        markModule('jank-observer');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke || typeof PerformanceObserver === 'undefined') return;
+            const supported = PerformanceObserver.supportedEntryTypes || [];
+
+            let pendingTasks = [];
+            let pendingShift = 0;
+
+            function attributionOf(entry) {
+                const a = entry.attribution && entry.attribution[0];
+                if (!a) return entry.name || null;
+                // Strip query strings so URLs never carry tokens into logs
+                const src = (a.containerSrc || '').split('?')[0];
+                return src || a.containerName || a.name || entry.name || null;
+            }
+
+            if (supported.includes('longtask')) {
+                new PerformanceObserver((list) => {
+                    for (const entry of list.getEntries()) {
+                        if (pendingTasks.length >= 500) break;
+                        pendingTasks.push({ duration_ms: entry.duration, attribution: attributionOf(entry) });
+                    }
+                }).observe({ type: 'longtask', buffered: true });
+            }
+            if (supported.includes('layout-shift')) {
+                new PerformanceObserver((list) => {
+                    for (const entry of list.getEntries()) {
+                        if (!entry.hadRecentInput) pendingShift += entry.value;
+                    }
+                }).observe({ type: 'layout-shift', buffered: true });
+            }
+
+            every('long-tasks', () => {
+                if (pendingTasks.length === 0 && pendingShift === 0) return;
+                const tasks = pendingTasks;
+                const layoutShift = pendingShift;
+                pendingTasks = [];
+                pendingShift = 0;
+                invoke('report_long_tasks', { tasks, layoutShift }).catch(() => {});
+            }, 5000);
+        })();
+
+        // === SESSION TIMER, BREAK REMINDER & SCHEDULED SESSIONS ===
+        markModule('session-timer');
+        function formatDuration(secs) {
+            const h = Math.floor(secs / 3600);
+            const m = Math.floor((secs % 3600) / 60);
+            return h > 0 ? h + 'h ' + String(m).padStart(2, '0') + 'm' : m + 'm';
+        }
+
+        let lastSessionAt = 0;
+        async function updateSessionTime(invoke) {
+            if (!sesEl || Date.now() - lastSessionAt < 10000) return;
+            lastSessionAt = Date.now();
+            const playTime = await invokeWithTimeout(invoke, 'get_play_time');
+            if (playTime) {
+                sesEl.textContent = formatDuration(playTime.continuous_secs) + ' / ' + formatDuration(playTime.today_secs);
+            }
+        }
+
+        (function() {
+            const listen = window.__TAURI__?.event?.listen;
+            if (!listen) return;
+            listen('pac-break-reminder', (event) => {
+                const minutes = (event.payload && event.payload.continuous_minutes) || 0;
+                showToast('You have been playing for ' + formatDuration(minutes * 60) + '. Time for a short break?', {
+                    actionLabel: 'OK',
+                    durationMs: 30000
+                });
+            });
+            listen('pac-scheduled-task', (event) => {
+                const task = event.payload || {};
+                showToast((task.label || 'Scheduled session') + ' starts at ' + task.time + '.', {
+                    actionLabel: 'OK',
+                    durationMs: 60000
+                });
+            });
+        })();
+
+        // Update overlay using stored element references. `snapshot` is a
+        // pac-overlay-stats payload; without one only the page-side values
+        // (FPS, refresh rate, RTT) are refreshed.
+        async function updateOverlay(snapshot) {
+            if (!visible) return;
+            if (fpsEl) fpsEl.textContent = fps;
+            if (hzEl) hzEl.textContent = refreshRate;
+            if (rttEl) rttEl.textContent = networkRtt || '--';
+            if (inputEl) inputEl.textContent = inputSamples.length ? inputLatency.toFixed(1) : '--';
+
+            // Tauri v2: invoke is at window.__TAURI__.core.invoke
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (invoke) {
+                try {
+                    const stats = snapshot && snapshot.performance;
+                    if (stats) {
+                        if (cpuEl) cpuEl.textContent = typeof stats.cpu_usage === 'number' ? stats.cpu_usage.toFixed(1) : '--';
+                        if (cpuCoreEl && Array.isArray(stats.cpu_core_usage) && stats.cpu_core_usage.length > 1) {
+                            // Busiest core: a single pegged core hides in the average
+                            // App share first: system CPU includes OBS, browsers etc.
+                            const app = typeof stats.process_tree_cpu_usage === 'number'
+                                ? 'app ' + stats.process_tree_cpu_usage.toFixed(0) + '%, ' : '';
+                            cpuCoreEl.textContent = ' (' + app + 'max ' + stats.cpu_busiest_core_usage.toFixed(0) + '%)';
+                            const freq = stats.cpu_frequency || {};
+                            const details = [
+                                'System: ' + stats.cpu_usage.toFixed(1) + '% (PACDeluxe + WebView2: ' + (stats.process_tree_cpu_usage || 0).toFixed(1) + '%)',
+                                'Per core: ' + stats.cpu_core_usage.map(u => u.toFixed(0)).join(' ')
+                            ];
+                            if (freq.current_mhz) details.push('Clock: ' + freq.current_mhz + ' MHz' + (freq.max_mhz ? ' / ' + freq.max_mhz + ' MHz max' : ''));
+                            if (freq.limit_mhz) details.push('Limited to ' + freq.limit_mhz + ' MHz');
+                            if (freq.power_plan) details.push('Power plan: ' + freq.power_plan);
+                            const power = stats.power_draw || {};
+                            if (typeof power.watts === 'number') {
+                                details.push('Power draw: ' + power.watts.toFixed(1) + ' W' + (power.scope === 'battery' ? ' (battery discharge)' : ' (CPU package)'));
+                            }
+                            cpuCoreEl.parentElement.title = details.join('\n');
+                        }
+                        pushGraphSample(graphFps, fps);
+                        pushGraphSample(graphCpu, stats.cpu_usage || 0);
+                        drawGraphs();
+                        if (memEl) memEl.textContent = typeof stats.memory_usage_mb === 'number' ? (stats.memory_usage_mb / 1024).toFixed(2) : '--';
+                    }
+                    const gpuStats = snapshot && snapshot.gpu;
+                    if (gpuStats && gpuEl) {
+                        if (gpuStats.available) {
+                            gpuEl.textContent = gpuStats.usage_percent.toFixed(1);
+                            gpuEl.parentElement.title = typeof gpuStats.webview_usage_percent === 'number'
+                                ? 'PACDeluxe: ' + gpuStats.webview_usage_percent.toFixed(1) + '%'
+                                : '';
                         } else {
-                            gpuEl.textContent = 'N/A';
+                            gpuEl.textContent = gpuStats.initializing ? '...' : 'N/A';
+                            gpuEl.parentElement.title = gpuStats.initializing ? 'GPU counters starting up' : (gpuStats.error || '');
                         }
                     }
-                    // Fetch HDR status (only once, doesn't change often)
+                    // VRAM row only exists where per-process counters do (Windows)
+                    if (gpuStats && vramEl && typeof gpuStats.webview_vram_dedicated_mb === 'number') {
+                        const shared = gpuStats.webview_vram_shared_mb || 0;
+                        vramEl.textContent = gpuStats.webview_vram_dedicated_mb.toFixed(0) + ' / ' + shared.toFixed(0);
+                        vramEl.parentElement.classList.add('has-data');
+                        if (currentLayout() !== 'Minimal') vramEl.parentElement.style.display = '';
+                    }
+                    // Fetch HDR status (once, and again after hdr_changed)
                     if (hdrEl && hdrEl.textContent === '--') {
                         const hdrInfo = await invokeWithTimeout(invoke, 'get_hdr_status');
                         if (hdrInfo) {
@@ -665,6 +1568,19 @@
                             }
                         }
                     }
+                    // Main-thread blocked time (from the long task observer)
+                    const jank = snapshot && snapshot.jank;
+                    if (jank && jankEl) {
+                        const blocked = Math.round(jank.blocked_ms_per_min);
+                        jankEl.textContent = blocked;
+                        jankEl.style.color = blocked >= 3000 ? '#f55' : blocked >= 1000 ? '#fa0' : '#0f0';
+                        const top = jank.top_sources && jank.top_sources[0];
+                        jankEl.parentElement.title = top
+                            ? 'Main-thread time blocked by long tasks. Top source: ' + top[0] + ' (' + Math.round(top[1]) + ' ms)'
+                            : 'Main-thread time blocked by long tasks';
+                    }
+                    await updateSessionTime(invoke);
+                    await updateAdvice(invoke);
                 } catch(e) {
                     console.error('[PACDeluxe] Stats error:', e);
                 }
@@ -672,21 +1588,53 @@
                 console.warn('[PACDeluxe] Tauri invoke not found');
             }
         }
-        setInterval(updateOverlay, 500);
 
-        // Toggle overlay with Ctrl+Shift+P
-        // Toggle fullscreen with F11
+        // Native code pushes stats at the configured interval while the
+        // overlay is visible and the page isn't hidden; no polling otherwise
+        function syncStatsStream() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            invoke('set_overlay_stream', { active: visible && !document.hidden }).catch(() => {});
+        }
+        (function() {
+            const listen = window.__TAURI__?.event?.listen;
+            if (!listen) return;
+            listen('pac-overlay-stats', (event) => updateOverlay(event.payload));
+            document.addEventListener('visibilitychange', syncStatsStream);
+            syncStatsStream();
+        })();
+
+        // Cycle overlay layouts with the overlay hotkey (default Ctrl+Shift+P)
+        // Toggle fullscreen with F11 or Alt+Enter
         // Toggle borderless windowed with Shift+F11
+        // On Windows the window mode keys are handled natively and never
+        // reach the page; this covers Linux. Capture phase, so inputs that
+        // stop propagation can't swallow them.
         let windowModeChanging = false;
         document.addEventListener('keydown', async e => {
-            if (e.ctrlKey && e.shiftKey && e.key.toLowerCase() === 'p') {
+            if (matchesOverlayHotkey(e)) {
                 e.preventDefault();
-                visible = !visible;
+                if (!visible) {
+                    visible = true;
+                    layoutIndex = 0;
+                } else if (layoutIndex < overlayConfig.layouts.length - 1) {
+                    layoutIndex++;
+                } else {
+                    visible = false;
+                }
                 overlay.style.display = visible ? 'block' : 'none';
                 lsSet('pac_overlay_visible', visible);
+                lsSet('pac_overlay_layout', layoutIndex);
+                applyLayout();
+                syncStatsStream();
+                if (window.__TAURI__) {
+                    window.__TAURI__.core.invoke('set_overlay_state', { visible, layout: currentLayout() })
+                        .catch(() => {});
+                }
                 if (visible) updateOverlay();
             }
-            if (e.key === 'F11' && e.repeat) {
+            const altEnter = e.altKey && e.key === 'Enter' && !e.ctrlKey && !e.shiftKey;
+            if ((e.key === 'F11' || altEnter) && e.repeat) {
                 e.preventDefault();
                 return;
             }
@@ -705,8 +1653,8 @@
                     finally { windowModeChanging = false; }
                 }
             }
-            // F11 for exclusive fullscreen (only if Shift not pressed)
-            else if (e.key === 'F11' && !e.shiftKey) {
+            // F11 or Alt+Enter for exclusive fullscreen (only if Shift not pressed)
+            else if ((e.key === 'F11' && !e.shiftKey) || altEnter) {
                 e.preventDefault();
                 if (windowModeChanging) return;
                 if (window.__TAURI__) {
@@ -717,17 +1665,20 @@
                     finally { windowModeChanging = false; }
                 }
             }
-        });
+        }, true);
 
         // === BOOSTER BUG FIX ===
         // Upstream bug: opening boosters and navigating away can corrupt session state
         // Detection: user appears logged out (login UI shown) but Firebase auth is valid
         // Fix: detect broken state and auto-recover by refreshing the page
+        markModule('booster-fix');
         (function boosterFix() {
+            if (!flagOn('booster_fix')) return;
             let wasAuthenticated = false;
             let checkCount = 0;
+            let stateChecker = null;
 
-            const stateChecker = setInterval(() => {
+            function checkState() {
                 checkCount++;
 
                 // Check for Firebase auth state
@@ -771,9 +1722,18 @@
 
                 // Stop checking after 5 minutes to save resources
                 if (checkCount > 300) {
-                    clearInterval(stateChecker);
+                    stateChecker();
+                    stateChecker = null;
                 }
-            }, 1000);
+            }
+
+            function startChecking() {
+                checkCount = 0;
+                if (!stateChecker) stateChecker = every('session-check', checkState, 1000);
+            }
+            startChecking();
+            // Sessions often break over sleep: check again after a resume (suspend.rs)
+            window.__TAURI__?.event?.listen('pac-resumed', startChecking);
 
             console.log('[PACDeluxe] Session recovery monitor active');
         })();
@@ -781,7 +1741,9 @@
         // === BOOSTER "FLIP ALL" BUTTON ===
         // Adds a dedicated "Flip All" button instead of replacing "Open a Booster".
         // This keeps the original open-pack behavior and avoids modal close side effects.
+        markModule('flip-all-button');
         (function dynamicBoosterButton() {
+            if (!flagOn('flip_all_button')) return;
             const FLIP_ALL_BUTTON_ID = 'pac-flip-all-btn';
 
             function flipAllCards(boostersPage) {
@@ -862,173 +1824,1415 @@
                 flipAllBtn.disabled = !shouldShowFlipAll;
             }
 
-            updateFlipAllButton();
-            setInterval(updateFlipAllButton, 250);
+            onRouteChange(updateFlipAllButton);
+            whilePresent('flip-all', '#boosters-page', updateFlipAllButton, 250);
             console.log('[PACDeluxe] Booster Flip All button ready');
         })();
 
-        // === AUTO-UPDATER ===
-        // Check for updates on startup and show user-friendly notification
-        // with release notes, progress bar, and clear messaging for major updates
-        (async function checkForUpdates() {
-            // Wait for Tauri to be fully initialized
-            await new Promise(resolve => setTimeout(resolve, 5000));
-
+        // === CUSTOMIZATION & FILE DROP ===
+        // Applies the saved background and theme, and handles files dropped
+        // on the window: images become the background, .css the theme, and
+        // .js is offered as a user script (installed only after confirming).
+        // Only drags carrying files are handled, so the game's own drag and
+        // drop (tier list maker etc.) is unaffected.
+        markModule('customization');
+        (function customization() {
             const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            const BACKGROUND_STYLE_ID = 'pac-custom-background';
+            const THEME_STYLE_ID = 'pac-custom-theme';
+            const IMAGE_EXTENSIONS = ['png', 'jpg', 'jpeg', 'webp', 'gif'];
+
+            function setStyle(id, css) {
+                let style = document.getElementById(id);
+                if (!css) {
+                    if (style) style.remove();
+                    return;
+                }
+                if (!style) {
+                    style = document.createElement('style');
+                    style.id = id;
+                    document.head.appendChild(style);
+                }
+                style.textContent = css;
+            }
+
+            function applyBackground(dataUrl) {
+                setStyle(BACKGROUND_STYLE_ID, dataUrl
+                    ? 'body { background: url("' + dataUrl + '") center / cover fixed no-repeat !important; }'
+                    : null);
+            }
+
+            // Theme fonts (settings.theme_fonts): in exempt game languages
+            // the theme's font-family declarations are dropped; otherwise
+            // each family the theme names gets extra faces covering kana,
+            // hangul and Han from system CJK fonts, so those characters
+            // don't render as boxes in a Latin-only theme font.
+            const GENERIC_FAMILIES = ['serif', 'sans-serif', 'monospace', 'cursive', 'fantasy', 'system-ui',
+                'ui-sans-serif', 'ui-serif', 'ui-monospace', 'emoji', 'math', 'inherit', 'initial', 'unset', 'revert'];
+            const CJK_FONTS = {
+                ja: ['Yu Gothic UI', 'Meiryo', 'Noto Sans CJK JP', 'Noto Sans JP'],
+                ko: ['Malgun Gothic', 'Noto Sans CJK KR', 'Noto Sans KR'],
+                zh: ['Microsoft YaHei', 'Noto Sans CJK SC', 'Noto Sans SC'],
+                'zh-tw': ['Microsoft JhengHei', 'Noto Sans CJK TC', 'Noto Sans TC']
+            };
+            const KANA_RANGE = 'U+3040-30FF, U+31F0-31FF, U+FF65-FF9F';
+            const HANGUL_RANGE = 'U+1100-11FF, U+3130-318F, U+AC00-D7AF';
+            const HAN_RANGE = 'U+2E80-2FDF, U+3000-303F, U+3400-4DBF, U+4E00-9FFF, U+F900-FAFF, U+FF00-FF64';
+            let themeFonts = { cjk_fallback: true, exempt_languages: [] };
+            let themeCss = null;
+
+            function gameLanguage() {
+                let lang = null;
+                try {
+                    lang = localStorage.getItem('i18nextLng');
+                } catch (e) {
+                    // Storage can be unavailable
+                }
+                return String(lang || document.documentElement.lang || navigator.language || 'en')
+                    .toLowerCase().replace('_', '-');
+            }
+
+            function isExempt(lang) {
+                return (themeFonts.exempt_languages || []).some(code => lang === code || lang.startsWith(code + '-'));
+            }
+
+            function familiesOf(declarations) {
+                const families = new Set();
+                for (const match of declarations) {
+                    for (const raw of match[1].replace(/!important/i, '').split(',')) {
+                        const name = raw.trim().replace(/^["']|["']$/g, '');
+                        if (name && !name.includes('(') && !GENERIC_FAMILIES.includes(name.toLowerCase())) {
+                            families.add(name);
+                        }
+                    }
+                }
+                return families;
+            }
+
+            function cjkFallbackCss(css, lang) {
+                const families = familiesOf(css.matchAll(/font-family\s*:\s*([^;}]+)/gi));
+                const ownFaces = familiesOf(css.matchAll(/@font-face\s*{[^}]*?font-family\s*:\s*([^;}]+)/gi));
+                // Han characters look different per language; default to Japanese
+                const hanFonts = lang === 'zh-tw' || lang === 'zh-hk' ? CJK_FONTS['zh-tw']
+                    : lang.startsWith('zh') ? CJK_FONTS.zh
+                        : lang.startsWith('ko') ? CJK_FONTS.ko : CJK_FONTS.ja;
+                const local = (fonts) => fonts.map(font => 'local("' + font + '")').join(', ');
+                let rules = '';
+                for (const family of families) {
+                    const quoted = '"' + family.replace(/["\\]/g, '') + '"';
+                    // An installed font named by the theme would otherwise be
+                    // hidden behind the faces added here
+                    if (!ownFaces.has(family)) {
+                        rules += '@font-face { font-family: ' + quoted + '; src: local(' + quoted + '); }\n';
+                    }
+                    rules += '@font-face { font-family: ' + quoted + '; src: ' + local(CJK_FONTS.ja) + '; unicode-range: ' + KANA_RANGE + '; }\n';
+                    rules += '@font-face { font-family: ' + quoted + '; src: ' + local(CJK_FONTS.ko) + '; unicode-range: ' + HANGUL_RANGE + '; }\n';
+                    rules += '@font-face { font-family: ' + quoted + '; src: ' + local(hanFonts) + '; unicode-range: ' + HAN_RANGE + '; }\n';
+                }
+                return rules;
+            }
+
+            function themeWithFonts(css) {
+                if (!css) return css;
+                const lang = gameLanguage();
+                if (isExempt(lang)) return css.replace(/font-family\s*:[^;}]+;?/gi, '');
+                if (!themeFonts.cjk_fallback) return css;
+                return cjkFallbackCss(css, lang) + css;
+            }
+
+            function applyTheme(css) {
+                themeCss = css;
+                setStyle(THEME_STYLE_ID, themeWithFonts(css));
+            }
+
+            function removableToast(message, kind, onRemoved) {
+                showToast(message, {
+                    actionLabel: 'Remove',
+                    durationMs: 8000,
+                    onAction: () => invoke('clear_customization', { kind })
+                        .then(onRemoved)
+                        .catch((err) => showToast('Remove failed: ' + err))
+                });
+            }
+
+            function readFile(file, asDataUrl) {
+                return new Promise((resolve, reject) => {
+                    const reader = new FileReader();
+                    reader.onload = () => resolve(reader.result);
+                    reader.onerror = () => reject(reader.error);
+                    if (asDataUrl) reader.readAsDataURL(file);
+                    else reader.readAsText(file);
+                });
+            }
+
+            async function handleFile(file) {
+                const ext = (file.name.split('.').pop() || '').toLowerCase();
+                if (IMAGE_EXTENSIONS.includes(ext)) {
+                    const dataUrl = await readFile(file, true);
+                    const data = String(dataUrl).split(',')[1] || '';
+                    applyBackground(await invoke('set_custom_background', { fileName: file.name, data }));
+                    removableToast('Background set from ' + file.name, 'Background', () => applyBackground(null));
+                } else if (ext === 'css') {
+                    const css = await readFile(file, false);
+                    await invoke('set_custom_theme', { css });
+                    applyTheme(css);
+                    removableToast('Theme applied from ' + file.name, 'Theme', () => applyTheme(null));
+                } else if (ext === 'js') {
+                    const source = await readFile(file, false);
+                    // The native side asks the user before installing
+                    const name = await invoke('install_user_script', { fileName: file.name, source });
+                    if (name) {
+                        showToast('Installed ' + name + ', reload to run it', {
+                            actionLabel: 'Reload',
+                            onAction: () => window.location.reload()
+                        });
+                    }
+                } else {
+                    showToast('Unsupported file: ' + file.name);
+                }
+            }
+
+            function hasFiles(e) {
+                return Array.from(e.dataTransfer?.types || []).includes('Files');
+            }
+
+            // Without this, dropping a file navigates the webview to it
+            window.addEventListener('dragover', (e) => {
+                if (!hasFiles(e)) return;
+                e.preventDefault();
+                e.dataTransfer.dropEffect = 'copy';
+            });
+            window.addEventListener('drop', (e) => {
+                if (!hasFiles(e)) return;
+                e.preventDefault();
+                Array.from(e.dataTransfer.files).forEach((file) => {
+                    handleFile(file).catch((err) => showToast('Could not use ' + file.name + ': ' + err));
+                });
+            });
+
             const listen = window.__TAURI__?.event?.listen;
-            if (!invoke) {
-                console.log('[PACDeluxe] Tauri not available, skipping update check');
-                return;
+            if (listen) {
+                listen('pac-theme-fonts', (event) => {
+                    if (!event.payload) return;
+                    themeFonts = event.payload;
+                    applyTheme(themeCss);
+                });
             }
+            // The game's language can change without a reload; checked again
+            // on route changes
+            let themeLanguage = gameLanguage();
+            onRouteChange(() => {
+                const lang = gameLanguage();
+                if (lang === themeLanguage) return;
+                themeLanguage = lang;
+                applyTheme(themeCss);
+            });
 
-            try {
-                const update = await invoke('check_for_updates');
-                console.log('[PACDeluxe] Update check result:', update);
-
-                if (update && update.available) {
-                    console.log('[PACDeluxe] Update available:', update.version);
-
-                    // Detect major version bump by comparing current vs target major version
-                    const currentMajor = (document.title.match(/v(\d+)\./) || [])[1] || '0';
-                    const targetMajor = (update.version.match(/^(\d+)\./) || [])[1] || '0';
-                    const isMajor = targetMajor !== currentMajor;
-
-                    // Build release notes summary (strip markdown, truncate)
-                    let notes = '';
-                    if (update.body) {
-                        notes = update.body
-                            .replace(/#{1,6}\s*/g, '')
-                            .replace(/\*\*/g, '')
-                            .replace(/\[([^\]]+)\]\([^)]+\)/g, '$1')
-                            .replace(/\n{2,}/g, '\n')
-                            .trim();
-                        if (notes.length > 300) notes = notes.substring(0, 297) + '...';
+            invoke('get_settings')
+                .then((settings) => {
+                    if (settings && settings.theme_fonts) themeFonts = settings.theme_fonts;
+                })
+                .catch(() => {})
+                .then(() => invoke('get_customizations'))
+                .then((current) => {
+                    applyBackground(current.background);
+                    applyTheme(current.theme);
+                    if (current.user_scripts.length > 0) {
+                        console.log('[PACDeluxe] User scripts:', current.user_scripts.join(', '));
                     }
+                })
+                .catch(() => {});
+        })();
 
-                    // Create update notification
-                    const banner = document.createElement('div');
-                    banner.id = 'pac-update-banner';
-                    banner.style.cssText = 'position:fixed;top:0;left:50%;transform:translateX(-50%);background:linear-gradient(135deg,#1a3a5c,#1a5a1a);color:#fff;padding:0;border-radius:0 0 12px 12px;z-index:99998;font:13px/1.5 sans-serif;box-shadow:0 4px 20px rgba(0,0,0,0.4);max-width:480px;width:90vw;overflow:hidden;';
+        // === CURSOR ===
+        // Enlarged or high-visibility arrow (settings.cursor) in place of the
+        // system and game cursors, which get lost on 4K displays during
+        // fights. Text fields keep their caret cursor.
+        markModule('cursor');
+        (function cursor() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            const STYLE_ID = 'pac-cursor';
+            const FILLS = { Large: '#fff', HighVisibility: '#ff0' };
+
+            function arrow(fill, size) {
+                // 16x24 arrow with a black outline, scaled to `size` high
+                const width = Math.round(size * 2 / 3);
+                const svg = '<svg xmlns="http://www.w3.org/2000/svg" width="' + width + '" height="' + size +
+                    '" viewBox="0 0 16 24"><path d="M1 1 L1 19 L5.5 15 L8.5 22 L11.5 20.8 L8.6 14 L14.5 14 Z" fill="' +
+                    fill + '" stroke="#000" stroke-width="1.5" stroke-linejoin="round"/></svg>';
+                return 'url("data:image/svg+xml,' + encodeURIComponent(svg) + '") 1 1';
+            }
 
-                    let bannerHTML = '<div style="padding:12px 16px;">';
-                    bannerHTML += '<div style="display:flex;align-items:center;justify-content:space-between;margin-bottom:8px;">';
-                    bannerHTML += '<span style="font-weight:bold;font-size:14px;">PACDeluxe v' + update.version + ' available</span>';
-                    bannerHTML += '<button id="pac-update-dismiss" style="background:transparent;color:#fff8;border:none;padding:2px 6px;cursor:pointer;font-size:18px;line-height:1;" title="Dismiss">&times;</button>';
-                    bannerHTML += '</div>';
+            function apply(config) {
+                let style = document.getElementById(STYLE_ID);
+                const fill = config && FILLS[config.style];
+                if (!fill) {
+                    if (style) style.remove();
+                    return;
+                }
+                if (!style) {
+                    style = document.createElement('style');
+                    style.id = STYLE_ID;
+                    document.head.appendChild(style);
+                }
+                style.textContent = '*, *::before, *::after, canvas { cursor: ' + arrow(fill, config.size) + ', auto !important; }\n' +
+                    'input, textarea, [contenteditable="true"] { cursor: text !important; }\n';
+            }
+
+            const listen = window.__TAURI__?.event?.listen;
+            if (listen) listen('pac-cursor', (event) => apply(event.payload));
+            invoke('get_settings').then((settings) => apply(settings && settings.cursor)).catch(() => {});
+        })();
+
+        // === CONTRAST MODE ===
+        // Dark or high-contrast stylesheet for the game's HTML panels
+        // (settings.contrast_mode, see contrast.rs). Only DOM panels are
+        // restyled; canvases and sprites are not touched.
+        markModule('contrast-mode');
+        (function contrastMode() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            const STYLE_ID = 'pac-contrast';
+            const PANELS = '.my-container, .my-box, .nes-container, .modal-content, dialog, .react-tooltip, ' +
+                '[class*="tooltip"], [class*="detail"]';
+            const SHEETS = {
+                dark: PANELS + ' { background-color: #1e1e24 !important; color: #e8e8e8 !important; ' +
+                    'border-color: #555 !important; }\n' +
+                    'input, select, textarea { background-color: #2a2a32 !important; color: #e8e8e8 !important; }\n',
+                high_contrast: PANELS + ' { background-color: #000 !important; color: #fff !important; ' +
+                    'border: 2px solid #ff0 !important; text-shadow: none !important; }\n' +
+                    'button, input, select, textarea { background-color: #000 !important; color: #fff !important; ' +
+                    'border: 2px solid #fff !important; }\n' +
+                    'button:hover, button:focus-visible { background-color: #ff0 !important; color: #000 !important; }\n' +
+                    'a { color: #0ff !important; text-decoration: underline !important; }\n' +
+                    ':focus-visible { outline: 3px solid #0ff !important; outline-offset: 1px; }\n'
+            };
+
+            function apply(info) {
+                const css = info && info.style ? SHEETS[info.style] : null;
+                let style = document.getElementById(STYLE_ID);
+                if (!css) {
+                    if (style) style.remove();
+                    return;
+                }
+                if (!style) {
+                    style = document.createElement('style');
+                    style.id = STYLE_ID;
+                }
+                style.textContent = css;
+                // Last in <head>, after any custom theme
+                document.head.appendChild(style);
+            }
+
+            const listen = window.__TAURI__?.event?.listen;
+            if (listen) listen('pac-contrast', (event) => apply(event.payload));
+            invoke('get_contrast').then(apply).catch(() => {});
+        })();
+
+        // === COPY ROOM LINK BUTTON ===
+        // Adds a small button next to each lobby room name that copies a
+        // pacdeluxe://join/<id> link. The room ID is the list item's React key,
+        // the same public ID the lobby uses to join. Opening such a link
+        // (deep_links.rs) joins the room once the lobby lists it.
+        markModule('copy-room-link');
+        (function copyRoomLinks() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            const BUTTON_CLASS = 'pac-copy-room-link';
+            const ROOM_ITEMS = '.room-list li, [class*="room-item"]';
+            // How long a join link waits for its room to be listed
+            const JOIN_WAIT_MS = 60000;
+
+            function roomIdOf(item) {
+                const fiberKey = Object.keys(item).find((key) => key.startsWith('__reactFiber$'));
+                const key = fiberKey ? item[fiberKey]?.key : null;
+                return typeof key === 'string' && /^[A-Za-z0-9_-]{1,64}$/.test(key) ? key : null;
+            }
 
-                    if (isMajor) {
-                        bannerHTML += '<div style="background:rgba(255,255,255,0.1);border-radius:6px;padding:8px 10px;margin-bottom:8px;font-size:12px;">';
-                        bannerHTML += 'This is a major update. The download may be larger than usual.';
-                        bannerHTML += '</div>';
+            let stopJoinWait = null;
+            function joinRoom(roomId) {
+                if (!roomId) return;
+                if (stopJoinWait) stopJoinWait();
+                const deadline = Date.now() + JOIN_WAIT_MS;
+                function tryJoin() {
+                    const item = Array.from(document.querySelectorAll(ROOM_ITEMS)).find((el) => roomIdOf(el) === roomId);
+                    if (item) {
+                        stopJoinWait();
+                        const button = item.querySelector('button:not(.' + BUTTON_CLASS + ')');
+                        (button || item).click();
+                        showToast('Joining room ' + roomId);
+                    } else if (Date.now() > deadline) {
+                        stopJoinWait();
+                        showToast('Room ' + roomId + ' is not in the lobby list', { level: 'warning' });
                     }
+                }
+                stopJoinWait = every('join-room-link', tryJoin, 1000);
+                tryJoin();
+            }
+            const takeJoin = () => invoke('take_pending_join').then(joinRoom).catch(() => {});
+            onPageEvent('join_requested', takeJoin);
+            takeJoin();
+
+            if (!flagOn('copy_room_link')) return;
+
+            function addButtons() {
+                document.querySelectorAll(ROOM_ITEMS).forEach((item) => {
+                    if (item.querySelector('.' + BUTTON_CLASS)) return;
+                    const roomId = roomIdOf(item);
+                    const nameEl = item.querySelector('.room-name, [class*="room-name"]');
+                    if (!roomId || !nameEl) return;
+
+                    const button = document.createElement('button');
+                    button.type = 'button';
+                    button.className = BUTTON_CLASS;
+                    button.title = 'Copy room link';
+                    button.textContent = '🔗';
+                    button.style.cssText = 'margin-left:6px;padding:0 4px;background:none;border:none;cursor:pointer;font-size:0.9em;opacity:0.7;';
+                    button.addEventListener('click', (e) => {
+                        e.preventDefault();
+                        e.stopPropagation();
+                        invoke('copy_room_link', { roomId })
+                            .then(() => showToast('Room link copied'))
+                            .catch((err) => showToast('Copy failed: ' + err));
+                    });
+                    nameEl.after(button);
+                });
+            }
 
-                    if (notes) {
-                        bannerHTML += '<div style="background:rgba(0,0,0,0.2);border-radius:6px;padding:8px 10px;margin-bottom:10px;font-size:11px;max-height:100px;overflow-y:auto;white-space:pre-wrap;line-height:1.4;color:#fffc;">';
-                        bannerHTML += notes;
-                        bannerHTML += '</div>';
+            onRouteChange(addButtons);
+            whilePresent('room-links', '.room-list, [class*="room-item"]', addButtons, 1000);
+        })();
+
+        // === PAGE STATE ===
+        // Report the public SPA route so native features know whether a
+        // match is in progress. Polled because the game navigates with the
+        // History API; only the pathname is sent.
+        markModule('page-state');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+
+            let lastRoute = null;
+            function reportRoute() {
+                const route = window.location.pathname;
+                if (route === lastRoute) return;
+                lastRoute = route;
+                invoke('report_page_state', { route }).catch(() => {});
+            }
+            reportRoute();
+            onRouteChange(reportRoute);
+            // Fallback for route changes that bypass the history hooks
+            every('page-state', reportRoute, 5000);
+        })();
+
+        // === KEY BINDINGS ===
+        // Per-profile rebinding of the shop shortcuts (keybinds.rs). A
+        // rebound key is swallowed in the window capture phase and the key the
+        // game expects is sent in its place: one press in, one press out.
+        // The optional turbo guard (settings.turbo_guard) also drops reroll
+        // key repeats beyond a few per second, so a held or sticky key can't
+        // dump gold; separate presses always go through.
+        markModule('key-bindings');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const listen = window.__TAURI__?.event?.listen;
+            if (!invoke) return;
+
+            // Mirrors GAME_REROLL_KEY in keybinds.rs
+            const GAME_REROLL_KEY = 'd';
+            let translate = {};
+            let turbo = null;
+            let lastReroll = 0;
+
+            function apply(remap) {
+                translate = (remap && remap.translate) || {};
+            }
+            function applyTurbo(guard) {
+                turbo = guard && guard.enabled ? guard : null;
+            }
+            invoke('get_key_bindings').then(apply).catch(() => {});
+            invoke('get_settings').then((settings) => applyTurbo(settings && settings.turbo_guard)).catch(() => {});
+            if (listen) {
+                listen('pac-key-bindings', (event) => apply(event.payload));
+                listen('pac-turbo-guard', (event) => applyTurbo(event.payload));
+            }
+
+            function turboBlocks(e) {
+                if (!turbo || e.type !== 'keydown') return false;
+                const now = performance.now();
+                if (e.repeat && now - lastReroll < 1000 / turbo.max_per_second) return true;
+                lastReroll = now;
+                return false;
+            }
+
+            function gameKeyEvent(type, original, key) {
+                const code = 'Key' + key.toUpperCase();
+                const keyCode = key.toUpperCase().charCodeAt(0);
+                const event = new KeyboardEvent(type, {
+                    key,
+                    code: /[0-9]/.test(key) ? 'Digit' + key : code,
+                    repeat: original.repeat,
+                    bubbles: true,
+                    cancelable: true,
+                });
+                // Legacy fields some canvas input libraries still read
+                Object.defineProperty(event, 'keyCode', { get: () => keyCode });
+                Object.defineProperty(event, 'which', { get: () => keyCode });
+                return event;
+            }
+
+            function translateKey(e) {
+                if (!e.isTrusted || e.ctrlKey || e.altKey || e.metaKey) return;
+                const target = e.target;
+                if (target && (target.isContentEditable || /^(INPUT|TEXTAREA|SELECT)$/.test(target.tagName))) return;
+                const pressed = e.key.toLowerCase();
+                const gameKey = translate[pressed] || pressed;
+                if (gameKey === GAME_REROLL_KEY && turboBlocks(e)) {
+                    e.stopImmediatePropagation();
+                    e.preventDefault();
+                    return;
+                }
+                if (gameKey === pressed) return;
+                e.stopImmediatePropagation();
+                e.preventDefault();
+                (target || document).dispatchEvent(gameKeyEvent(e.type, e, gameKey));
+            }
+
+            // Window capture phase: runs before the game's key handlers
+            window.addEventListener('keydown', translateKey, true);
+            window.addEventListener('keyup', translateKey, true);
+        })();
+
+        // === MISDROP GUARD ===
+        // Optional (settings.misdrop_guard, off by default). In the last
+        // seconds of the round timer the sell key only sells on a second
+        // press, so a stray tap while rearranging doesn't sell the unit under
+        // the cursor. The timer is read from the visible HUD text; drops on
+        // the canvas board and bench are left to the game.
+        markModule('misdrop-guard');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+
+            const CONFIRM_WINDOW_MS = 700;
+            let guard = null;
+            let armedAt = 0;
+
+            invoke('get_settings').then((settings) => {
+                if (settings && settings.misdrop_guard && settings.misdrop_guard.enabled) {
+                    guard = settings.misdrop_guard;
+                }
+            }).catch(() => {});
+
+            function secondsLeft() {
+                if (!window.location.pathname.startsWith('/game')) return null;
+                for (const el of document.querySelectorAll('[class*="timer"]')) {
+                    const text = el.textContent.trim();
+                    if (/^\d{1,3}$/.test(text)) return Number(text);
+                }
+                return null;
+            }
+
+            // Window capture phase: runs before the game's key handlers
+            window.addEventListener('keydown', (e) => {
+                if (!guard || e.key.toLowerCase() !== guard.sell_key) return;
+                if (e.ctrlKey || e.altKey || e.metaKey) return;
+                const target = e.target;
+                if (target && (target.isContentEditable || /^(INPUT|TEXTAREA|SELECT)$/.test(target.tagName))) return;
+                const left = secondsLeft();
+                if (left === null || left > guard.final_seconds) return;
+
+                const now = performance.now();
+                if (!e.repeat && now - armedAt < CONFIRM_WINDOW_MS) {
+                    armedAt = 0;
+                    return;
+                }
+                e.stopImmediatePropagation();
+                e.preventDefault();
+                // Holding the key doesn't confirm
+                if (!e.repeat) {
+                    armedAt = now;
+                    showToast('Press ' + guard.sell_key.toUpperCase() + ' again to sell', {
+                        durationMs: CONFIRM_WINDOW_MS
+                    });
+                }
+            }, true);
+        })();
+
+        // === CAROUSEL WATCH ===
+        // Reports carousel rounds seen in the HUD text, and whether the
+        // player has touched the window since, on the event bus
+        // (`pac-carousel`). attention.rs decides whether to flash or beep;
+        // this never moves anything.
+        markModule('carousel-watch');
+        (function() {
+            const emit = window.__TAURI__?.event?.emit;
+            if (!emit) return;
+
+            let active = false;
+            let moved = false;
+
+            function carouselShown() {
+                if (!window.location.pathname.startsWith('/game')) return false;
+                for (const el of document.querySelectorAll('[class*="phase"], [class*="stage"], [class*="round"]')) {
+                    if (/carousel|portal/i.test(el.textContent)) return true;
+                }
+                return false;
+            }
+
+            function onInput(e) {
+                if (!active || moved || !e.isTrusted) return;
+                moved = true;
+                emit('pac-carousel', { active: true, moved: true }).catch(() => {});
+            }
+            for (const type of ['pointerdown', 'pointermove', 'keydown']) {
+                window.addEventListener(type, onInput, { capture: true, passive: true });
+            }
+
+            every('carousel', () => {
+                const shown = carouselShown();
+                if (shown === active) return;
+                active = shown;
+                moved = false;
+                emit('pac-carousel', { active, moved: false }).catch(() => {});
+            }, 1000);
+        })();
+
+        // === HUD ANNOUNCEMENTS ===
+        // While spoken announcements are on (settings.tts), reports the round
+        // number and the player's own life shown in the HUD text on the event
+        // bus (`pac-hud`); tts.rs decides what to say. Read-only.
+        markModule('hud-announcements');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const emit = window.__TAURI__?.event?.emit;
+            if (!invoke || !emit) return;
+
+            let enabled = false;
+            let last = '';
+
+            function numberIn(selector, pattern) {
+                for (const el of document.querySelectorAll(selector)) {
+                    const match = pattern.exec(el.textContent || '');
+                    if (match) return parseInt(match[1], 10);
+                }
+                return null;
+            }
+
+            function report() {
+                if (!enabled || !window.location.pathname.startsWith('/game')) return;
+                const round = numberIn('[class*="stage"], [class*="round"], [class*="turn"]', /(\d{1,3})/);
+                // The player's own portrait is the one marked as theirs
+                const life = numberIn('[class*="player"][class*="self"] [class*="life"], ' +
+                    '[class*="player"][class*="current"] [class*="life"], [class*="my-life"]', /(\d{1,3})/);
+                const key = round + '/' + life;
+                if (key === last) return;
+                last = key;
+                emit('pac-hud', { round, life }).catch(() => {});
+            }
+
+            invoke('get_settings').then((settings) => {
+                enabled = !!(settings && settings.tts && settings.tts.enabled);
+            }).catch(() => {});
+            onPageEvent('settings_changed', ({ settings }) => {
+                enabled = !!(settings && settings.tts && settings.tts.enabled);
+            });
+            onRouteChange(() => {
+                last = '';
+            });
+            every('hud-announcements', report, 1000);
+        })();
+
+        // === INTRO SUPPRESSION ===
+        // Optional (settings.skip_intro, off by default). For a few seconds
+        // after a page load outside a match, CSS animations and transitions
+        // finish at once and music that starts on its own stays silent until
+        // it ends (see AUDIO MIX), so the menus are usable sooner.
+        markModule('intro-suppression');
+        const INTRO_WINDOW_MS = 4000;
+        let introUntil = 0;
+        function introSuppressed() {
+            return performance.now() < introUntil;
+        }
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            invoke('get_settings').then((settings) => {
+                if (!settings || !settings.skip_intro) return;
+                if (window.location.pathname.startsWith('/game')) return;
+                introUntil = performance.now() + INTRO_WINDOW_MS;
+                const style = document.createElement('style');
+                style.textContent = `
+                    *, *::before, *::after {
+                        animation-duration: 0s !important;
+                        animation-delay: 0s !important;
+                        transition-duration: 0s !important;
+                        transition-delay: 0s !important;
                     }
+                `;
+                document.head.appendChild(style);
+                setTimeout(() => style.remove(), INTRO_WINDOW_MS);
+            }).catch(() => {});
+        })();
 
-                    // Progress bar (hidden initially)
-                    bannerHTML += '<div id="pac-update-progress-wrap" style="display:none;margin-bottom:10px;">';
-                    bannerHTML += '<div style="background:rgba(255,255,255,0.15);border-radius:4px;height:6px;overflow:hidden;">';
-                    bannerHTML += '<div id="pac-update-progress-bar" style="background:#4ade80;height:100%;width:0%;transition:width 0.3s ease;border-radius:4px;"></div>';
-                    bannerHTML += '</div>';
-                    bannerHTML += '<div id="pac-update-progress-text" style="font-size:11px;color:#fffa;margin-top:4px;text-align:center;">Downloading...</div>';
-                    bannerHTML += '</div>';
+        // === AUDIO MIX ===
+        // Separate music and sound effect levels (settings.audio_mix) that
+        // survive reloads, unlike the game's own mixer. Sounds are told apart
+        // by their clips: looping or long ones are music, the rest effects.
+        // Each WebAudio source gets a gain stage in front of the node it
+        // connects to; <audio> elements are only muted, never re-routed.
+        // Music held by INTRO SUPPRESSION stays silent until it ends.
+        markModule('audio-mix');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke || typeof AudioBufferSourceNode === 'undefined') return;
+            // The runtime can be injected twice into one document
+            if (window.__pacAudioMix) return;
+
+            const MUSIC_MIN_SECONDS = 20;
+            const mixer = window.__pacAudioMix = {
+                config: null,
+                gates: { music: new Set(), sfx: new Set() },
+                media: new Set()
+            };
+            const gateOf = new WeakMap();
+            const mutedByMix = new WeakSet();
+            const held = new WeakSet();
 
-                    // Status text (shown during install)
-                    bannerHTML += '<div id="pac-update-status" style="display:none;text-align:center;padding:4px 0;font-size:12px;color:#4ade80;"></div>';
+            function categoryOf(duration, loop) {
+                return loop || duration >= MUSIC_MIN_SECONDS ? 'music' : 'sfx';
+            }
 
-                    // Buttons
-                    bannerHTML += '<div id="pac-update-buttons" style="display:flex;gap:8px;justify-content:flex-end;">';
-                    bannerHTML += '<button id="pac-update-later" style="background:rgba(255,255,255,0.1);color:#fff;border:1px solid rgba(255,255,255,0.2);padding:6px 14px;border-radius:6px;cursor:pointer;font-size:12px;">Later</button>';
-                    bannerHTML += '<button id="pac-update-btn" style="background:#4ade80;color:#1a3a2a;border:none;padding:6px 14px;border-radius:6px;cursor:pointer;font-weight:bold;font-size:12px;">Update Now</button>';
-                    bannerHTML += '</div>';
+            function levelOf(category) {
+                const config = mixer.config;
+                if (!config) return 1;
+                if (category === 'music') return config.music_muted ? 0 : config.music_volume;
+                return config.sfx_muted ? 0 : config.sfx_volume;
+            }
 
-                    bannerHTML += '</div>';
-                    banner.innerHTML = bannerHTML;
-                    document.body.appendChild(banner);
-
-                    // Dismiss / Later handlers
-                    document.getElementById('pac-update-dismiss').onclick = () => banner.remove();
-                    document.getElementById('pac-update-later').onclick = () => banner.remove();
-
-                    // Update button handler with progress tracking
-                    document.getElementById('pac-update-btn').onclick = async () => {
-                        const buttons = document.getElementById('pac-update-buttons');
-                        const progressWrap = document.getElementById('pac-update-progress-wrap');
-                        const progressBar = document.getElementById('pac-update-progress-bar');
-                        const progressText = document.getElementById('pac-update-progress-text');
-                        const statusEl = document.getElementById('pac-update-status');
-
-                        // Hide buttons, show progress
-                        buttons.style.display = 'none';
-                        progressWrap.style.display = 'block';
-
-                        // Track download progress via Tauri events
-                        let downloaded = 0;
-                        let totalSize = 0;
-                        let unlisten = null;
-
-                        if (listen) {
-                            try {
-                                unlisten = await listen('update-progress', (event) => {
-                                    const data = event.payload;
-                                    downloaded += (data.chunk || 0);
-                                    if (data.total > 0) totalSize = data.total;
-
-                                    if (totalSize > 0) {
-                                        const pct = Math.min(100, Math.round((downloaded / totalSize) * 100));
-                                        progressBar.style.width = pct + '%';
-                                        const dlMB = (downloaded / 1048576).toFixed(1);
-                                        const totalMB = (totalSize / 1048576).toFixed(1);
-                                        progressText.textContent = dlMB + ' / ' + totalMB + ' MB (' + pct + '%)';
-                                    } else {
-                                        const dlMB = (downloaded / 1048576).toFixed(1);
-                                        progressText.textContent = dlMB + ' MB downloaded...';
-                                        // Animate indeterminate progress
-                                        progressBar.style.width = Math.min(90, (downloaded / 1048576) * 2) + '%';
-                                    }
-                                });
-                            } catch(e) {
-                                console.log('[PACDeluxe] Could not listen for progress events:', e);
-                            }
-                        }
+            function gateLevel(gate, category) {
+                return held.has(gate) ? 0 : levelOf(category);
+            }
 
-                        try {
-                            await invoke('install_update');
+            function applyMediaMute(el) {
+                const silent = held.has(el) || levelOf(categoryOf(el.duration || 0, el.loop)) === 0;
+                if (silent && !el.muted) {
+                    el.muted = true;
+                    mutedByMix.add(el);
+                } else if (!silent && mutedByMix.has(el)) {
+                    el.muted = false;
+                    mutedByMix.delete(el);
+                }
+            }
+
+            function applyMix() {
+                for (const category of ['music', 'sfx']) {
+                    for (const gate of mixer.gates[category]) gate.gain.value = gateLevel(gate, category);
+                }
+                for (const el of mixer.media) applyMediaMute(el);
+            }
+
+            const nativeConnect = AudioBufferSourceNode.prototype.connect;
+            AudioBufferSourceNode.prototype.connect = function(destination, output = 0, input = 0) {
+                if (!(destination instanceof AudioNode) || !this.buffer || gateOf.has(this)) {
+                    return nativeConnect.apply(this, arguments);
+                }
+                const entry = {
+                    gate: this.context.createGain(),
+                    category: categoryOf(this.buffer.duration, this.loop)
+                };
+                if (entry.category === 'music' && introSuppressed()) held.add(entry.gate);
+                entry.gate.gain.value = gateLevel(entry.gate, entry.category);
+                entry.gate.connect(destination, 0, input);
+                nativeConnect.call(this, entry.gate, output);
+                mixer.gates[entry.category].add(entry.gate);
+                gateOf.set(this, entry);
+                this.addEventListener('ended', () => {
+                    mixer.gates[entry.category].delete(entry.gate);
+                    entry.gate.disconnect();
+                }, { once: true });
+                return destination;
+            };
+
+            // `loop` is often set after connecting
+            const nativeStart = AudioBufferSourceNode.prototype.start;
+            AudioBufferSourceNode.prototype.start = function() {
+                const entry = gateOf.get(this);
+                if (entry && this.loop && entry.category !== 'music') {
+                    mixer.gates[entry.category].delete(entry.gate);
+                    entry.category = 'music';
+                    mixer.gates.music.add(entry.gate);
+                    if (introSuppressed()) held.add(entry.gate);
+                    entry.gate.gain.value = gateLevel(entry.gate, 'music');
+                }
+                return nativeStart.apply(this, arguments);
+            };
+
+            const nativePlay = HTMLMediaElement.prototype.play;
+            HTMLMediaElement.prototype.play = function() {
+                if (!mixer.media.has(this)) {
+                    mixer.media.add(this);
+                    const forget = () => mixer.media.delete(this);
+                    this.addEventListener('pause', forget, { once: true });
+                    this.addEventListener('ended', () => {
+                        forget();
+                        held.delete(this);
+                    }, { once: true });
+                }
+                if (introSuppressed() && categoryOf(this.duration || 0, this.loop) === 'music') {
+                    held.add(this);
+                }
+                applyMediaMute(this);
+                return nativePlay.apply(this, arguments);
+            };
+
+            invoke('get_settings').then((settings) => {
+                if (settings && settings.audio_mix) {
+                    mixer.config = settings.audio_mix;
+                    applyMix();
+                }
+            }).catch(() => {});
+
+            const listen = window.__TAURI__?.event?.listen;
+            if (listen) {
+                listen('pac-audio-mix', (event) => {
+                    if (!event.payload) return;
+                    mixer.config = event.payload;
+                    applyMix();
+                });
+            }
 
-                            if (unlisten) unlisten();
-                            progressWrap.style.display = 'none';
-                            statusEl.style.display = 'block';
-                            statusEl.textContent = 'Update installed! Restarting...';
+            function matchesHotkey(e, hotkey) {
+                const parsed = parseHotkey(hotkey);
+                return e.ctrlKey === parsed.ctrl &&
+                    e.shiftKey === parsed.shift &&
+                    e.altKey === parsed.alt &&
+                    e.key.toLowerCase() === parsed.key;
+            }
+
+            window.addEventListener('keydown', (e) => {
+                const config = mixer.config;
+                if (!config || e.repeat) return;
+                const category = matchesHotkey(e, config.music_hotkey) ? 'music'
+                    : matchesHotkey(e, config.sfx_hotkey) ? 'sfx' : null;
+                if (!category) return;
+                e.preventDefault();
+                const flag = category + '_muted';
+                config[flag] = !config[flag];
+                applyMix();
+                showToast((category === 'music' ? 'Music' : 'Sound effects') +
+                    (config[flag] ? ' muted' : ' unmuted'), { durationMs: 1500 });
+                invoke('set_audio_mix', { mix: config }).catch(() => {});
+            }, true);
+        })();
+
+        // === QUICK REFERENCE ===
+        // Synergy breakpoints and item recipes bundled with the build
+        // (get_quick_reference), shown and hidden with settings.quick_reference.
+        // Display only: the same tables the game shows, nothing read from or
+        // sent to the match.
+        markModule('quick-reference');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke || window.__pacQuickReference) return;
+            window.__pacQuickReference = true;
 
-                            setTimeout(async () => {
-                                try { await invoke('restart_app'); } catch(e) {}
-                            }, 1500);
+            let config = null;
+            let data = null;
+            let panel = null;
+
+            // FOSSIL_STONE -> Fossil Stone
+            function label(key) {
+                return String(key).toLowerCase().split('_')
+                    .map(word => word.charAt(0).toUpperCase() + word.slice(1)).join(' ');
+            }
+
+            function section(title, lines) {
+                const box = document.createElement('div');
+                box.style.cssText = 'margin-top:6px;';
+                const heading = document.createElement('div');
+                heading.style.cssText = 'color:#0f0;font-weight:bold;margin-bottom:2px;';
+                heading.textContent = title;
+                box.appendChild(heading);
+                for (const line of lines) {
+                    const row = document.createElement('div');
+                    row.textContent = line;
+                    box.appendChild(row);
+                }
+                return box;
+            }
+
+            function build() {
+                const box = document.createElement('div');
+                box.id = 'pac-quick-reference';
+                box.style.cssText = 'position:fixed;top:10px;left:10px;width:280px;max-height:80vh;overflow-y:auto;background:rgba(0,0,0,0.85);color:#fff;font:11px/1.4 monospace;padding:8px 10px;border:1px solid #0f04;border-radius:6px;z-index:99998;';
+                const header = document.createElement('div');
+                header.style.cssText = 'color:#fff8;';
+                header.textContent = 'Quick reference - game ' + data.game_version +
+                    ' (' + config.hotkey + ' to hide)';
+                box.appendChild(header);
+                const pageVersion = window.__PAC_BUILD_VERSION__;
+                if (pageVersion && data.build_version !== pageVersion) {
+                    const stale = document.createElement('div');
+                    stale.style.cssText = 'color:#facc15;';
+                    stale.textContent = 'From a different build than this client; may be out of date';
+                    box.appendChild(stale);
+                }
+                box.appendChild(section('Synergies',
+                    data.synergies.map(s => label(s.name) + ': ' + s.breakpoints.join(' / '))));
+                box.appendChild(section('Item recipes',
+                    data.recipes.map(r => label(r.item) + ' = ' + r.components.map(label).join(' + '))));
+                return box;
+            }
+
+            function hide() {
+                if (panel) panel.remove();
+                panel = null;
+            }
+
+            async function toggle() {
+                if (panel) {
+                    hide();
+                    return;
+                }
+                if (!data) {
+                    try {
+                        data = await invoke('get_quick_reference');
+                    } catch (e) {
+                        showToast(String(e), { level: 'warning', durationMs: 3000 });
+                        return;
+                    }
+                }
+                if (!config || !config.enabled || panel) return;
+                panel = build();
+                document.body.appendChild(panel);
+            }
+
+            function apply(next) {
+                config = next;
+                if (!config || !config.enabled) hide();
+            }
+
+            invoke('get_settings').then((settings) => {
+                if (settings && settings.quick_reference) apply(settings.quick_reference);
+            }).catch(() => {});
+
+            const listen = window.__TAURI__?.event?.listen;
+            if (listen) {
+                listen('pac-quick-reference', (event) => {
+                    if (event.payload) apply(event.payload);
+                });
+            }
+
+            window.addEventListener('keydown', (e) => {
+                if (!config || !config.enabled || e.repeat) return;
+                const parsed = parseHotkey(config.hotkey);
+                if (e.ctrlKey !== parsed.ctrl || e.shiftKey !== parsed.shift ||
+                    e.altKey !== parsed.alt || e.key.toLowerCase() !== parsed.key) return;
+                e.preventDefault();
+                toggle();
+            }, true);
+        })();
+
+        // === NETWORK BENCHMARK ===
+        // Once per run, summarize resource timing for remote origins so the
+        // networking presets can be compared. Only protocol and timings are
+        // sent, never URLs.
+        markModule('network-benchmark');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke || typeof performance.getEntriesByType !== 'function') return;
+            setTimeout(() => {
+                const samples = performance.getEntriesByType('resource')
+                    .filter((entry) => {
+                        try {
+                            return new URL(entry.name).hostname !== window.location.hostname;
                         } catch (e) {
-                            if (unlisten) unlisten();
-                            console.error('[PACDeluxe] Update failed:', e);
-                            progressWrap.style.display = 'none';
-                            statusEl.style.display = 'block';
-                            statusEl.style.color = '#f87171';
-                            statusEl.textContent = 'Update failed: ' + (e.message || e);
-                            // Show buttons again so user can retry or dismiss
-                            setTimeout(() => {
-                                buttons.style.display = 'flex';
-                                statusEl.style.display = 'none';
-                            }, 5000);
+                            return false;
                         }
-                    };
+                    })
+                    .slice(0, 300)
+                    .map((entry) => ({
+                        protocol: entry.nextHopProtocol || '',
+                        // Zero when the origin doesn't allow timing details
+                        ttfb_ms: entry.responseStart > 0 ? entry.responseStart - entry.requestStart : null,
+                        duration_ms: entry.duration
+                    }));
+                if (samples.length === 0) return;
+                invoke('report_network_benchmark', { samples })
+                    .then((result) => console.log('[PACDeluxe] Network benchmark:', result))
+                    .catch(() => {});
+            }, 30000);
+        })();
+
+        // === HEARTBEAT ===
+        // Liveness signal for the native freeze watchdog. If the main thread
+        // wedges these stop, and the window is reloaded natively. Carries the
+        // frame rate for the performance history (rAF is paused while hidden).
+        markModule('heartbeat');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            every('heartbeat', () => {
+                const visibleFps = document.visibilityState === 'visible' ? fps : null;
+                invoke('heartbeat', { fps: visibleFps }).catch(() => {});
+            }, 1000);
+        })();
+
+        // === WATCHER STATS ===
+        // Totals from the watcher lifecycle helpers, for get_observer_stats,
+        // and the setup cost of each block, for get_script_budget
+        markModule('watcher-stats');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            if (!invoke) return;
+            every('watcher-stats', () => {
+                invoke('report_observer_stats', {
+                    watchers: Array.from(watchers.values()),
+                    modules: moduleInits,
+                    uptimeMs: performance.now(),
+                }).catch(() => {});
+            }, 10000);
+        })();
+
+        // === HEALTH WARNINGS ===
+        // Problems the native side notices on its own (e.g. a main thread
+        // blocked for seconds per minute, jank.rs)
+        markModule('health-warnings');
+        onPageEvent('health_warning', ({ message }) => {
+            if (message) showToast(message, { level: 'warning', durationMs: 10000 });
+        });
+
+        // === SOFT RELOAD WARNING ===
+        // Native side reloads the page on memory bloat when the user opted
+        // in; give them a chance to cancel.
+        markModule('soft-reload-warning');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const listen = window.__TAURI__?.event?.listen;
+            if (!invoke || !listen) return;
+
+            listen('pac-soft-reload-pending', (event) => {
+                const { reason, delay_secs } = event.payload || {};
+                showToast('Reloading in ' + delay_secs + 's to reclaim memory (' + reason + ')', {
+                    actionLabel: 'Cancel',
+                    onAction: () => invoke('cancel_soft_reload').catch(() => {}),
+                    durationMs: delay_secs * 1000
+                });
+            });
+        })();
+
+        // === POWER EMERGENCY ===
+        // power_guard.rs switched to the battery saver profile (low battery
+        // or overheating); say what changed and why until dismissed.
+        markModule('power-emergency');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const listen = window.__TAURI__?.event?.listen;
+            if (!invoke || !listen) return;
+
+            let banner = null;
+            let dismissed = null;
+
+            function hide() {
+                if (banner) banner.remove();
+                banner = null;
+            }
+
+            function show(emergency) {
+                hide();
+                if (!emergency || dismissed === emergency.reason.kind) return;
+                banner = document.createElement('div');
+                banner.id = 'pac-power-emergency';
+                banner.style.cssText = 'position:fixed;top:12px;left:50%;transform:translateX(-50%);background:rgba(90,30,0,0.95);color:#fff;font:12px/1.5 sans-serif;padding:10px 14px;border-radius:8px;z-index:99998;max-width:440px;border:1px solid #fb923c;box-shadow:0 2px 10px rgba(0,0,0,0.5);display:flex;gap:10px;align-items:flex-start;';
+                const text = document.createElement('div');
+                text.textContent = emergency.message;
+                const close = document.createElement('button');
+                close.textContent = 'Dismiss';
+                close.style.cssText = 'background:#fb923c;color:#1a0a00;border:none;padding:2px 10px;border-radius:4px;cursor:pointer;font-weight:bold;flex-shrink:0;';
+                close.onclick = () => {
+                    dismissed = emergency.reason.kind;
+                    hide();
+                };
+                banner.appendChild(text);
+                banner.appendChild(close);
+                document.body.appendChild(banner);
+            }
+
+            listen('pac-power-emergency', (event) => {
+                if (event.payload) {
+                    show(event.payload);
                 } else {
-                    console.log('[PACDeluxe] App is up to date');
+                    dismissed = null;
+                    showToast('Power emergency over - back to your chosen profile');
+                    hide();
                 }
-            } catch (e) {
-                console.log('[PACDeluxe] Update check failed:', e.message || e);
+            });
+            invoke('get_power_emergency').then(show).catch(() => {});
+        })();
+
+        // === SIMULATED LOAD ===
+        // load_sim.rs asked for GPU load: a WebGL draw loop on an
+        // OffscreenCanvas in a worker, stopped after the requested time.
+        markModule('simulated-load');
+        (function() {
+            if (typeof OffscreenCanvas === 'undefined' || typeof Worker === 'undefined') return;
+
+            const WORKER_SOURCE = `
+                self.onmessage = (event) => {
+                    const deadline = Date.now() + event.data.durationMs;
+                    const canvas = new OffscreenCanvas(1024, 1024);
+                    const gl = canvas.getContext('webgl');
+                    if (!gl) { self.postMessage('no-webgl'); return; }
+                    const vs = gl.createShader(gl.VERTEX_SHADER);
+                    gl.shaderSource(vs, 'attribute vec2 p; void main() { gl_Position = vec4(p, 0.0, 1.0); }');
+                    gl.compileShader(vs);
+                    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+                    gl.shaderSource(fs, 'precision highp float; uniform float t; void main() {' +
+                        ' vec2 z = gl_FragCoord.xy / 512.0 - 1.0; float a = 0.0;' +
+                        ' for (int i = 0; i < 256; i++) { z = vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + vec2(sin(t), cos(t)) * 0.7; a += length(z); }' +
+                        ' gl_FragColor = vec4(fract(a), 0.0, 0.0, 1.0); }');
+                    gl.compileShader(fs);
+                    const program = gl.createProgram();
+                    gl.attachShader(program, vs);
+                    gl.attachShader(program, fs);
+                    gl.linkProgram(program);
+                    gl.useProgram(program);
+                    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+                    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+                    gl.enableVertexAttribArray(0);
+                    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+                    const time = gl.getUniformLocation(program, 't');
+                    const pixel = new Uint8Array(4);
+                    function frame() {
+                        for (let i = 0; i < 8; i++) {
+                            gl.uniform1f(time, Date.now() / 1000 + i);
+                            gl.drawArrays(gl.TRIANGLES, 0, 3);
+                        }
+                        // Wait for the GPU so the next batch doesn't just queue up
+                        gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+                        if (Date.now() < deadline) {
+                            setTimeout(frame, 0);
+                        } else {
+                            self.postMessage('done');
+                        }
+                    }
+                    frame();
+                };
+            `;
+
+            let worker = null;
+
+            onPageEvent('simulate_load', ({ duration_ms }) => {
+                if (worker) return;
+                try {
+                    const url = URL.createObjectURL(new Blob([WORKER_SOURCE], { type: 'text/javascript' }));
+                    worker = new Worker(url);
+                    URL.revokeObjectURL(url);
+                } catch (e) {
+                    console.warn('[PACDeluxe] Simulated GPU load unavailable:', e);
+                    return;
+                }
+                const stop = (result) => {
+                    if (!worker) return;
+                    worker.terminate();
+                    worker = null;
+                    console.log('[PACDeluxe] Simulated GPU load ' + result);
+                };
+                worker.onmessage = (event) => stop(event.data === 'done' ? 'finished' : 'unavailable: ' + event.data);
+                worker.onerror = (event) => stop('failed: ' + event.message);
+                // In case the worker can't keep its own time
+                setTimeout(() => stop('stopped'), duration_ms + 5000);
+                worker.postMessage({ durationMs: duration_ms });
+                console.log('[PACDeluxe] Simulating GPU load for ' + Math.round(duration_ms / 1000) + 's');
+            });
+        })();
+
+        // === UI MACROS ===
+        // Menu flows recorded from the stats dashboard. While recording,
+        // clicks outside matches are reported as selector + label; playback
+        // is paced by Rust and arrives here one step at a time. Nothing runs
+        // in /game or /preparation, which Rust also refuses.
+        markModule('ui-macros');
+        (function() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const listen = window.__TAURI__?.event?.listen;
+            if (!invoke || !listen) return;
+
+            let recording = false;
+
+            function inMatch() {
+                const route = window.location.pathname;
+                return route.startsWith('/game') || route.startsWith('/preparation');
             }
+
+            function labelOf(el) {
+                const text = (el.textContent || '').trim().replace(/\s+/g, ' ');
+                return text ? text.slice(0, 40) : null;
+            }
+
+            // An id when there is one, otherwise a short tag:nth-of-type path
+            function selectorFor(el) {
+                const parts = [];
+                while (el && el !== document.body && parts.length < 6) {
+                    if (el.id) {
+                        parts.unshift('#' + CSS.escape(el.id));
+                        break;
+                    }
+                    let part = el.tagName.toLowerCase();
+                    const parent = el.parentElement;
+                    if (parent) {
+                        const same = Array.from(parent.children).filter(c => c.tagName === el.tagName);
+                        if (same.length > 1) part += ':nth-of-type(' + (same.indexOf(el) + 1) + ')';
+                    }
+                    parts.unshift(part);
+                    el = parent;
+                }
+                return parts.join(' > ');
+            }
+
+            function findTarget(step) {
+                let el = null;
+                try { el = document.querySelector(step.selector); } catch (e) {}
+                if (el && (!step.text || labelOf(el) === step.text)) return el;
+                if (!step.text) return el;
+                // Layout changed: fall back to an element with the same label
+                const tag = step.selector.split('>').pop().trim().split(/[:.#\[]/)[0] || '*';
+                for (const candidate of document.querySelectorAll(tag)) {
+                    if (labelOf(candidate) === step.text) return candidate;
+                }
+                return null;
+            }
+
+            document.addEventListener('click', (e) => {
+                if (!recording || !e.isTrusted || inMatch()) return;
+                const target = e.target instanceof Element
+                    ? e.target.closest('button, a, [role="button"], [role="tab"], li, img') || e.target
+                    : null;
+                if (!target || target.closest('[id^="pac-"], .pac-toast')) return;
+                invoke('report_macro_step', {
+                    action: { type: 'click', selector: selectorFor(target), text: labelOf(target) }
+                }).catch((err) => showToast('Macro: ' + err));
+            }, true);
+
+            listen('pac-macro-recording', (event) => {
+                recording = !!event.payload;
+                if (recording) {
+                    showToast('Recording macro - click through the menus', {
+                        actionLabel: 'Stop',
+                        durationMs: 8000,
+                        onAction: () => invoke('stop_macro_recording').catch(() => {})
+                    });
+                }
+            });
+
+            listen('pac-macro-step', (event) => {
+                const step = event.payload;
+                if (!step || inMatch()) {
+                    invoke('stop_macro').catch(() => {});
+                    return;
+                }
+                if (step.type === 'navigate') {
+                    if (window.location.pathname !== step.route) {
+                        history.pushState(null, '', step.route);
+                        window.dispatchEvent(new PopStateEvent('popstate'));
+                    }
+                } else if (step.type === 'click') {
+                    const el = findTarget(step);
+                    if (el) {
+                        el.click();
+                    } else {
+                        invoke('stop_macro').catch(() => {});
+                        showToast('Macro stopped: ' + (step.text || 'a menu item') + ' was not found');
+                    }
+                }
+            });
+        })();
+
+        // === AUTO-UPDATER ===
+        // The native side checks for updates on a timer (updates.rs) and
+        // announces one on pac-events; a page loaded afterwards asks for it.
+        // The banner shows release notes, a progress bar, and clear messaging
+        // for major updates. "Later" hides it for that version until the app
+        // restarts.
+        markModule('auto-updater');
+        (function updateBanner() {
+            const invoke = window.__TAURI__?.core?.invoke;
+            const listen = window.__TAURI__?.event?.listen;
+            if (!invoke) {
+                console.log('[PACDeluxe] Tauri not available, no update banner');
+                return;
+            }
+            const LATER_KEY = 'pac_update_later';
+
+            function showUpdateBanner(update) {
+                if (!update || !update.available) return;
+                if (document.getElementById('pac-update-banner')) return;
+                try {
+                    if (sessionStorage.getItem(LATER_KEY) === update.version) return;
+                } catch (e) {}
+                console.log('[PACDeluxe] Update available:', update.version);
+
+                // Detect major version bump by comparing current vs target major version
+                const currentMajor = (document.title.match(/v(\d+)\./) || [])[1] || '0';
+                const targetMajor = (update.version.match(/^(\d+)\./) || [])[1] || '0';
+                const isMajor = targetMajor !== currentMajor;
+
+                // Build release notes summary (strip markdown, truncate)
+                let notes = '';
+                if (update.body) {
+                    notes = update.body
+                        .replace(/#{1,6}\s*/g, '')
+                        .replace(/\*\*/g, '')
+                        .replace(/\[([^\]]+)\]\([^)]+\)/g, '$1')
+                        .replace(/\n{2,}/g, '\n')
+                        .trim();
+                    if (notes.length > 300) notes = notes.substring(0, 297) + '...';
+                }
+
+                // Create update notification
+                const banner = document.createElement('div');
+                banner.id = 'pac-update-banner';
+                banner.style.cssText = 'position:fixed;top:0;left:50%;transform:translateX(-50%);background:linear-gradient(135deg,#1a3a5c,#1a5a1a);color:#fff;padding:0;border-radius:0 0 12px 12px;z-index:99998;font:13px/1.5 sans-serif;box-shadow:0 4px 20px rgba(0,0,0,0.4);max-width:480px;width:90vw;overflow:hidden;';
+
+                let bannerHTML = '<div style="padding:12px 16px;">';
+                bannerHTML += '<div style="display:flex;align-items:center;justify-content:space-between;margin-bottom:8px;">';
+                bannerHTML += '<span style="font-weight:bold;font-size:14px;">PACDeluxe v' + update.version + ' available</span>';
+                bannerHTML += '<button id="pac-update-dismiss" style="background:transparent;color:#fff8;border:none;padding:2px 6px;cursor:pointer;font-size:18px;line-height:1;" title="Dismiss">&times;</button>';
+                bannerHTML += '</div>';
+
+                if (isMajor) {
+                    bannerHTML += '<div style="background:rgba(255,255,255,0.1);border-radius:6px;padding:8px 10px;margin-bottom:8px;font-size:12px;">';
+                    bannerHTML += 'This is a major update. The download may be larger than usual.';
+                    bannerHTML += '</div>';
+                }
+
+                if (notes) {
+                    bannerHTML += '<div style="background:rgba(0,0,0,0.2);border-radius:6px;padding:8px 10px;margin-bottom:10px;font-size:11px;max-height:100px;overflow-y:auto;white-space:pre-wrap;line-height:1.4;color:#fffc;">';
+                    bannerHTML += notes;
+                    bannerHTML += '</div>';
+                }
+
+                if (update.signing_key) {
+                    bannerHTML += '<div style="font-size:11px;color:#fffa;margin-bottom:10px;" title="The download is checked against this key before it is installed">';
+                    bannerHTML += 'Signed update &middot; key ' + update.signing_key;
+                    bannerHTML += '</div>';
+                }
+
+                // Progress bar (hidden initially)
+                bannerHTML += '<div id="pac-update-progress-wrap" style="display:none;margin-bottom:10px;">';
+                bannerHTML += '<div style="background:rgba(255,255,255,0.15);border-radius:4px;height:6px;overflow:hidden;">';
+                bannerHTML += '<div id="pac-update-progress-bar" style="background:#4ade80;height:100%;width:0%;transition:width 0.3s ease;border-radius:4px;"></div>';
+                bannerHTML += '</div>';
+                bannerHTML += '<div id="pac-update-progress-text" style="font-size:11px;color:#fffa;margin-top:4px;text-align:center;">Downloading...</div>';
+                bannerHTML += '</div>';
+
+                // Status text (shown during install)
+                bannerHTML += '<div id="pac-update-status" style="display:none;text-align:center;padding:4px 0;font-size:12px;color:#4ade80;"></div>';
+
+                // Buttons
+                bannerHTML += '<div id="pac-update-buttons" style="display:flex;gap:8px;justify-content:flex-end;">';
+                bannerHTML += '<button id="pac-update-later" style="background:rgba(255,255,255,0.1);color:#fff;border:1px solid rgba(255,255,255,0.2);padding:6px 14px;border-radius:6px;cursor:pointer;font-size:12px;">Later</button>';
+                bannerHTML += '<button id="pac-update-btn" style="background:#4ade80;color:#1a3a2a;border:none;padding:6px 14px;border-radius:6px;cursor:pointer;font-weight:bold;font-size:12px;">Restart to update</button>';
+                bannerHTML += '</div>';
+
+                bannerHTML += '</div>';
+                banner.innerHTML = bannerHTML;
+                document.body.appendChild(banner);
+
+                // Dismiss / Later handlers
+                function later() {
+                    try { sessionStorage.setItem(LATER_KEY, update.version); } catch (e) {}
+                    banner.remove();
+                }
+                document.getElementById('pac-update-dismiss').onclick = later;
+                document.getElementById('pac-update-later').onclick = later;
+
+                // Update button handler with progress tracking
+                document.getElementById('pac-update-btn').onclick = async () => {
+                    const buttons = document.getElementById('pac-update-buttons');
+                    const progressWrap = document.getElementById('pac-update-progress-wrap');
+                    const progressBar = document.getElementById('pac-update-progress-bar');
+                    const progressText = document.getElementById('pac-update-progress-text');
+                    const statusEl = document.getElementById('pac-update-status');
+
+                    // Hide buttons, show progress
+                    buttons.style.display = 'none';
+                    progressWrap.style.display = 'block';
+
+                    // Track download progress via Tauri events
+                    let downloaded = 0;
+                    let totalSize = 0;
+                    let unlisten = null;
+
+                    if (listen) {
+                        try {
+                            unlisten = await listen('update-progress', (event) => {
+                                const data = event.payload;
+                                downloaded += (data.chunk || 0);
+                                if (data.total > 0) totalSize = data.total;
+
+                                if (totalSize > 0) {
+                                    const pct = Math.min(100, Math.round((downloaded / totalSize) * 100));
+                                    progressBar.style.width = pct + '%';
+                                    const dlMB = (downloaded / 1048576).toFixed(1);
+                                    const totalMB = (totalSize / 1048576).toFixed(1);
+                                    progressText.textContent = dlMB + ' / ' + totalMB + ' MB (' + pct + '%)';
+                                } else {
+                                    const dlMB = (downloaded / 1048576).toFixed(1);
+                                    progressText.textContent = dlMB + ' MB downloaded...';
+                                    // Animate indeterminate progress
+                                    progressBar.style.width = Math.min(90, (downloaded / 1048576) * 2) + '%';
+                                }
+                            });
+                        } catch(e) {
+                            console.log('[PACDeluxe] Could not listen for progress events:', e);
+                        }
+                    }
+
+                    try {
+                        await invoke('install_update');
+
+                        if (unlisten) unlisten();
+                        progressWrap.style.display = 'none';
+                        statusEl.style.display = 'block';
+                        statusEl.textContent = 'Signature verified, update installed! Restarting...';
+
+                        setTimeout(async () => {
+                            try { await invoke('restart_app'); } catch(e) {}
+                        }, 1500);
+                    } catch (e) {
+                        if (unlisten) unlisten();
+                        console.error('[PACDeluxe] Update failed:', e);
+                        progressWrap.style.display = 'none';
+                        statusEl.style.display = 'block';
+                        statusEl.style.color = '#f87171';
+                        statusEl.textContent = 'Update failed: ' + (e.message || e);
+                        // Show buttons again so user can retry or dismiss
+                        setTimeout(() => {
+                            buttons.style.display = 'flex';
+                            statusEl.style.display = 'none';
+                        }, 5000);
+                    }
+                };
+            }
+
+            onPageEvent('update_available', ({ version, notes, signing_key }) => {
+                showUpdateBanner({ available: true, version, body: notes, signing_key });
+            });
+            invoke('get_available_update').then(showUpdateBanner).catch(() => {});
         })();
 
         console.log('[PACDeluxe] Ready - Ctrl+Shift+P: overlay, F11: fullscreen, Shift+F11: borderless');
+        markModule(null);
+    }
+
+    documentStart();
+    if (document.readyState === 'loading') {
+        document.addEventListener('DOMContentLoaded', init, { once: true });
+    } else {
+        init();
     }
-    init();
 })();
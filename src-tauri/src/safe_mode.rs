@@ -0,0 +1,256 @@
+//! Crash-Loop Safe Mode - Cross-platform
+//!
+//! A marker file is written at startup and removed on a clean exit, so a
+//! marker found at the next launch means the previous run ended abnormally
+//! (crash, abort on panic, killed). The count goes up while runs keep dying
+//! within a few minutes; a run that stays up past [`STABLE_AFTER`] resets
+//! it. After [`CRASH_LOOP_THRESHOLD`] crashes in a row the
+//! app starts in safe mode, as it does with `--safe-mode`:
+//!
+//! - no WebView2 browser flags (ours or an inherited
+//!   `WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS`)
+//! - no user scripts, custom theme or background
+//!
+//! The bundled runtime still runs; the game's requests and sign-in go
+//! through it. A dialog lists what was turned off and offers the recovery
+//! console (Windows), and the health
+//! report carries it on every platform. Safe mode ends after a clean exit
+//! or a stable run.
+
+use crate::recovery_console;
+use crate::report::unix_now;
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::AppHandle;
+use tracing::{info, warn};
+use ts_rs::TS;
+
+const MARKER_FILE_NAME: &str = "running.json";
+/// Abnormal exits in a row that start safe mode
+pub const CRASH_LOOP_THRESHOLD: u32 = 3;
+/// A run that lasts this long is not part of a crash loop
+const STABLE_AFTER: Duration = Duration::from_secs(180);
+/// Command-line switch that forces safe mode
+pub const SAFE_MODE_ARG: &str = "--safe-mode";
+
+/// What safe mode turns off
+pub const DISABLED: &[&str] = &[
+    "WebView2 browser flags (GPU flags, networking preset, high-precision mouse)",
+    "User scripts",
+    "Custom theme and background",
+    "Memory and CPU limits",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+struct Marker {
+    started_at: u64,
+    pid: u32,
+    /// Abnormal exits in a row before this run
+    crashes: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeModeReason {
+    CrashLoop,
+    /// Started with `--safe-mode`
+    Requested,
+}
+
+/// `get_safe_mode` result
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SafeModeStatus {
+    pub active: bool,
+    pub reason: Option<SafeModeReason>,
+    /// Abnormal exits in a row before this run
+    pub crashes: u32,
+    /// Empty when not active
+    pub disabled: Vec<&'static str>,
+}
+
+static STATUS: OnceLock<SafeModeStatus> = OnceLock::new();
+
+fn marker_path() -> Option<PathBuf> {
+    let dir = settings::config_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(MARKER_FILE_NAME))
+}
+
+fn write_marker(marker: &Marker) {
+    let Some(path) = marker_path() else {
+        return;
+    };
+    let written = serde_json::to_string(marker)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        warn!("Failed to write startup marker: {}", e);
+    }
+}
+
+/// Status for a run, given the marker the previous run left behind
+fn evaluate(previous: Option<&Marker>, requested: bool) -> SafeModeStatus {
+    let crashes = previous.map_or(0, |m| m.crashes.saturating_add(1));
+    let reason = if requested {
+        Some(SafeModeReason::Requested)
+    } else if crashes >= CRASH_LOOP_THRESHOLD {
+        Some(SafeModeReason::CrashLoop)
+    } else {
+        None
+    };
+    SafeModeStatus {
+        active: reason.is_some(),
+        reason,
+        crashes,
+        disabled: if reason.is_some() {
+            DISABLED.to_vec()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Read the previous run's marker, decide on safe mode and leave this run's
+/// marker. Called once at startup, before WebView2 is configured.
+pub fn begin() -> &'static SafeModeStatus {
+    STATUS.get_or_init(|| {
+        let previous: Option<Marker> = marker_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        if let Some(marker) = &previous {
+            warn!(
+                "Previous run (pid {}) did not exit cleanly; {} abnormal exit(s) in a row",
+                marker.pid,
+                marker.crashes + 1
+            );
+        }
+        let requested = std::env::args().any(|arg| arg == SAFE_MODE_ARG);
+        let status = evaluate(previous.as_ref(), requested);
+        write_marker(&Marker {
+            started_at: unix_now(),
+            pid: std::process::id(),
+            crashes: status.crashes,
+        });
+        if status.active {
+            warn!("Starting in safe mode ({:?})", status.reason);
+        }
+        status
+    })
+}
+
+/// This run's status; inactive before [`begin`]
+pub fn status() -> SafeModeStatus {
+    STATUS
+        .get()
+        .cloned()
+        .unwrap_or_else(|| evaluate(None, false))
+}
+
+pub fn is_active() -> bool {
+    STATUS.get().is_some_and(|s| s.active)
+}
+
+/// Reset the crash count once this run has lasted past [`STABLE_AFTER`]
+pub fn spawn_stability_timer() {
+    std::thread::Builder::new()
+        .name("safe-mode-stable".to_string())
+        .spawn(|| {
+            std::thread::sleep(STABLE_AFTER);
+            info!("Running stable; crash count reset");
+            write_marker(&Marker {
+                started_at: unix_now(),
+                pid: std::process::id(),
+                crashes: 0,
+            });
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start stability timer: {}", e));
+}
+
+/// Remove the marker; call on every clean exit, restarts included
+pub fn finish() {
+    if let Some(path) = marker_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Tell the user what safe mode turned off, offering the recovery console
+#[cfg(target_os = "windows")]
+pub fn show_dialog(app: &AppHandle, status: &SafeModeStatus) {
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, IDYES, MB_ICONWARNING, MB_YESNO};
+
+    let why = match status.reason {
+        Some(SafeModeReason::CrashLoop) => format!(
+            "PACDeluxe closed unexpectedly {} times in a row, so it started in safe mode.",
+            status.crashes
+        ),
+        _ => "PACDeluxe was started in safe mode.".to_string(),
+    };
+    let text = format!(
+        "{}\n\nTurned off for this launch:\n- {}\n\nOnce this launch exits normally or runs for a few minutes, the next one starts normally again. If the crashes continue, remove recently added user scripts or switch the networking preset back to Default.\n\nClose PACDeluxe and open the recovery console?",
+        why,
+        status.disabled.join("\n- ")
+    );
+    let text: Vec<u16> = text.encode_utf16().chain(Some(0)).collect();
+    let app = app.clone();
+    // The dialog waits for the user; keep startup going meanwhile
+    std::thread::Builder::new()
+        .name("safe-mode-dialog".to_string())
+        .spawn(move || {
+            // SAFETY: the text is NUL-terminated and outlives the call
+            let choice = unsafe {
+                MessageBoxW(
+                    HWND::default(),
+                    PCWSTR::from_raw(text.as_ptr()),
+                    w!("PACDeluxe Safe Mode"),
+                    MB_YESNO | MB_ICONWARNING,
+                )
+            };
+            if choice == IDYES {
+                match recovery_console::launch() {
+                    Ok(()) => app.exit(0),
+                    Err(e) => warn!("{}", e),
+                }
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to show safe mode dialog: {}", e));
+}
+
+/// Linux: the health report carries the notice
+#[cfg(target_os = "linux")]
+pub fn show_dialog(_app: &AppHandle, status: &SafeModeStatus) {
+    info!("Safe mode turned off: {}", status.disabled.join(", "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(crashes: u32) -> Marker {
+        Marker {
+            started_at: 0,
+            pid: 1,
+            crashes,
+        }
+    }
+
+    #[test]
+    fn repeated_abnormal_exits_start_safe_mode() {
+        assert!(!evaluate(None, false).active);
+        assert_eq!(evaluate(Some(&marker(0)), false).crashes, 1);
+        assert!(!evaluate(Some(&marker(1)), false).active);
+
+        let status = evaluate(Some(&marker(2)), false);
+        assert_eq!(status.reason, Some(SafeModeReason::CrashLoop));
+        assert_eq!(status.disabled, DISABLED);
+
+        let requested = evaluate(None, true);
+        assert_eq!(requested.reason, Some(SafeModeReason::Requested));
+    }
+}
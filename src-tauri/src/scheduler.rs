@@ -0,0 +1,376 @@
+//! Scheduled Sessions - Cross-platform
+//!
+//! Players with scheduled lobbies or tournaments can add entries like
+//! "Tuesdays and Thursdays at 20:00, 10 minutes early". At
+//! `time - lead_minutes` a running PACDeluxe shows and focuses its window,
+//! warms DNS for the game's hosts, and the page shows a reminder toast
+//! (`pac-scheduled-task`).
+//!
+//! Windows: each entry is also registered with Task Scheduler (folder
+//! `PACDeluxe`) to start the app with `--scheduled` if it isn't running. A
+//! scheduled start exits right away when another instance is already up;
+//! that one handles the reminder itself. Times are local.
+//! Linux: in-app only, and times are UTC (no time zone database is read).
+
+use crate::settings::SettingsState;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info, warn};
+use ts_rs::TS;
+
+/// Entries allowed in `scheduled_tasks`
+pub const MAX_TASKS: usize = 16;
+/// Longest warm-up before a scheduled start
+pub const MAX_LEAD_MINUTES: u32 = 120;
+/// Passed to launches started by Task Scheduler
+pub const SCHEDULED_ARG: &str = "--scheduled";
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+const CHECK_INTERVAL: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+
+    /// 0 = Monday; wraps around
+    pub fn from_index(index: i64) -> Self {
+        Self::ALL[index.rem_euclid(7) as usize]
+    }
+
+    fn index(self) -> i64 {
+        self as i64
+    }
+
+    /// Day name as `schtasks /D` expects it
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn schtasks_name(self) -> &'static str {
+        match self {
+            Weekday::Mon => "MON",
+            Weekday::Tue => "TUE",
+            Weekday::Wed => "WED",
+            Weekday::Thu => "THU",
+            Weekday::Fri => "FRI",
+            Weekday::Sat => "SAT",
+            Weekday::Sun => "SUN",
+        }
+    }
+}
+
+/// A point in the week, to the minute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekTime {
+    pub weekday: Weekday,
+    /// Minutes since midnight
+    pub minute: u32,
+}
+
+/// One scheduled session
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct ScheduledTask {
+    /// Shown in the reminder, e.g. "Weekly cup"
+    pub label: String,
+    /// Start time, "HH:MM"
+    pub time: String,
+    /// Days it happens on; empty means every day
+    pub days: Vec<Weekday>,
+    /// How long before `time` to open and warm up
+    pub lead_minutes: u32,
+}
+
+impl Default for ScheduledTask {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            time: "20:00".to_string(),
+            days: Vec::new(),
+            lead_minutes: 10,
+        }
+    }
+}
+
+/// Minutes since midnight for "HH:MM"
+pub fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    if minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes) = (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+impl ScheduledTask {
+    /// Normalize the time and days, rejecting unusable entries
+    pub fn validated(mut self) -> Result<Self, String> {
+        self.label = self.label.trim().to_string();
+        if self.label.chars().count() > 40 {
+            return Err("Schedule label is limited to 40 characters".to_string());
+        }
+        let minute = parse_time(&self.time)
+            .ok_or_else(|| format!("Invalid time {:?}, expected HH:MM", self.time))?;
+        self.time = format!("{:02}:{:02}", minute / 60, minute % 60);
+        if self.lead_minutes > MAX_LEAD_MINUTES {
+            return Err(format!(
+                "Lead time is limited to {} minutes",
+                MAX_LEAD_MINUTES
+            ));
+        }
+        self.days.sort();
+        self.days.dedup();
+        Ok(self)
+    }
+
+    /// When the warm-up fires, per day. A lead past midnight moves it to
+    /// the previous day.
+    pub fn triggers(&self) -> Vec<WeekTime> {
+        let Some(start) = parse_time(&self.time) else {
+            return Vec::new();
+        };
+        let offset = start as i64 - self.lead_minutes as i64;
+        let days: &[Weekday] = if self.days.is_empty() {
+            &Weekday::ALL
+        } else {
+            &self.days
+        };
+        days.iter()
+            .map(|day| WeekTime {
+                weekday: Weekday::from_index(day.index() + offset.div_euclid(MINUTES_PER_DAY)),
+                minute: offset.rem_euclid(MINUTES_PER_DAY) as u32,
+            })
+            .collect()
+    }
+}
+
+/// Validate a full schedule
+pub fn validate(tasks: Vec<ScheduledTask>) -> Result<Vec<ScheduledTask>, String> {
+    if tasks.len() > MAX_TASKS {
+        return Err(format!("At most {} scheduled sessions", MAX_TASKS));
+    }
+    tasks.into_iter().map(ScheduledTask::validated).collect()
+}
+
+/// Tasks whose warm-up is at `now`
+fn due(tasks: &[ScheduledTask], now: WeekTime) -> impl Iterator<Item = &ScheduledTask> {
+    tasks
+        .iter()
+        .filter(move |task| task.triggers().contains(&now))
+}
+
+/// Current local time (Windows)
+#[cfg(target_os = "windows")]
+pub fn now() -> WeekTime {
+    use windows::Win32::System::SystemInformation::GetLocalTime;
+    let time = unsafe { GetLocalTime() };
+    WeekTime {
+        // wDayOfWeek: 0 = Sunday
+        weekday: Weekday::from_index(time.wDayOfWeek as i64 - 1),
+        minute: time.wHour as u32 * 60 + time.wMinute as u32,
+    }
+}
+
+/// Current UTC time (Linux)
+#[cfg(target_os = "linux")]
+pub fn now() -> WeekTime {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    WeekTime {
+        // 1970-01-01 was a Thursday
+        weekday: Weekday::from_index(secs.div_euclid(86_400) + 3),
+        minute: (secs.rem_euclid(86_400) / 60) as u32,
+    }
+}
+
+fn fire(app: &AppHandle, task: &ScheduledTask) {
+    info!("Scheduled session {:?} at {}", task.label, task.time);
+    crate::tray::show_main_window(app);
+    crate::preconnect::spawn_dns_prefetch();
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.emit("pac-scheduled-task", task) {
+            debug!("Failed to send scheduled session reminder: {}", e);
+        }
+    }
+}
+
+/// Check the schedule a few times a minute
+pub fn spawn(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("scheduler".to_string())
+        .spawn(move || {
+            let mut last_checked = None;
+            loop {
+                let now = now();
+                if last_checked != Some(now) {
+                    last_checked = Some(now);
+                    let tasks = app.state::<SettingsState>().get().scheduled_tasks;
+                    for task in due(&tasks, now) {
+                        fire(&app, task);
+                    }
+                }
+                std::thread::sleep(CHECK_INTERVAL);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start scheduler: {}", e));
+}
+
+/// Whether Task Scheduler started this process
+pub fn launched_by_schedule() -> bool {
+    std::env::args().skip(1).any(|arg| arg == SCHEDULED_ARG)
+}
+
+/// Whether another PACDeluxe process is running
+pub fn another_instance_running() -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All);
+    let our_pid = sysinfo::Pid::from_u32(std::process::id());
+    system
+        .processes()
+        .values()
+        .any(|process| process.pid() != our_pid && process.exe() == Some(exe.as_path()))
+}
+
+#[cfg(target_os = "windows")]
+fn schtasks(args: &[&str]) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("schtasks")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run schtasks: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Replace the Task Scheduler entries: `previous` is how many the old
+/// schedule had
+#[cfg(target_os = "windows")]
+pub fn register_launches(previous: usize, tasks: &[ScheduledTask]) -> Result<(), String> {
+    for i in tasks.len()..previous {
+        let name = format!("PACDeluxe\\Launch {}", i + 1);
+        if let Err(e) = schtasks(&["/Delete", "/F", "/TN", &name]) {
+            debug!("Failed to remove {}: {}", name, e);
+        }
+    }
+
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut command = format!("\"{}\" {}", exe.display(), SCHEDULED_ARG);
+    let profile = crate::profile::active();
+    if profile != crate::profile::DEFAULT_PROFILE {
+        command.push_str(&format!(" --profile {}", profile));
+    }
+    for (i, task) in tasks.iter().enumerate() {
+        let triggers = task.triggers();
+        let Some(first) = triggers.first() else {
+            continue;
+        };
+        // All of a task's triggers share the time, only the day differs
+        let start = format!("{:02}:{:02}", first.minute / 60, first.minute % 60);
+        let days: Vec<_> = triggers.iter().map(|t| t.weekday.schtasks_name()).collect();
+        let name = format!("PACDeluxe\\Launch {}", i + 1);
+        schtasks(&[
+            "/Create",
+            "/F",
+            "/TN",
+            &name,
+            "/TR",
+            &command,
+            "/SC",
+            "WEEKLY",
+            "/D",
+            &days.join(","),
+            "/ST",
+            &start,
+        ])
+        .map_err(|e| format!("Failed to register {}: {}", name, e))?;
+    }
+    info!("Registered {} scheduled launches", tasks.len());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub fn register_launches(_previous: usize, tasks: &[ScheduledTask]) -> Result<(), String> {
+    if !tasks.is_empty() {
+        debug!("Scheduled launches are not available on Linux (in-app reminders only)");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(time: &str, days: &[Weekday], lead_minutes: u32) -> ScheduledTask {
+        ScheduledTask {
+            label: "Cup".to_string(),
+            time: time.to_string(),
+            days: days.to_vec(),
+            lead_minutes,
+        }
+    }
+
+    #[test]
+    fn times_are_parsed_and_normalized() {
+        assert_eq!(parse_time("20:05"), Some(20 * 60 + 5));
+        assert_eq!(parse_time("7:30"), Some(7 * 60 + 30));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("12:60"), None);
+        assert_eq!(parse_time("noon"), None);
+        let task = task("7:30", &[Weekday::Fri, Weekday::Mon, Weekday::Fri], 5);
+        let task = task.validated().unwrap();
+        assert_eq!(task.time, "07:30");
+        assert_eq!(task.days, vec![Weekday::Mon, Weekday::Fri]);
+    }
+
+    #[test]
+    fn lead_time_can_move_the_trigger_to_the_previous_day() {
+        let triggers = task("00:05", &[Weekday::Mon], 10).triggers();
+        assert_eq!(
+            triggers,
+            vec![WeekTime {
+                weekday: Weekday::Sun,
+                minute: 23 * 60 + 55
+            }]
+        );
+    }
+
+    #[test]
+    fn tasks_are_due_only_at_their_trigger() {
+        let tasks = vec![task("20:00", &[Weekday::Tue, Weekday::Thu], 15)];
+        let at = |weekday, minute| WeekTime { weekday, minute };
+        assert_eq!(due(&tasks, at(Weekday::Thu, 19 * 60 + 45)).count(), 1);
+        assert_eq!(due(&tasks, at(Weekday::Wed, 19 * 60 + 45)).count(), 0);
+        assert_eq!(due(&tasks, at(Weekday::Thu, 20 * 60)).count(), 0);
+        // No days means every day
+        let daily = vec![task("20:00", &[], 0)];
+        assert_eq!(due(&daily, at(Weekday::Sat, 20 * 60)).count(), 1);
+    }
+}
@@ -0,0 +1,295 @@
+//! Optimization Self-Test - Cross-platform
+//!
+//! `run_self_test` checks that each part of the optimization stack is in
+//! effect right now, rather than that it was requested at startup: the 1ms
+//! timer resolution, the process priority, the DWM window attributes, the
+//! WMI process watcher, the GPU counters, the HDR query and the injected
+//! runtime. The report is shown on the stats dashboard.
+//!
+//! A check that doesn't apply (battery saver released the timer on
+//! purpose, Linux has no DWM) is skipped rather than failed.
+
+use crate::performance;
+use crate::power_guard::{PowerGuard, PowerProfile};
+use crate::recovery::FreezeWatchdog;
+use crate::report::unix_now;
+use crate::settings::SettingsState;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+/// The runtime sends a heartbeat every second; this much silence fails
+const HEARTBEAT_STALE: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+/// One check of the report
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// What was found, or why it was skipped
+    pub detail: String,
+}
+
+impl SelfTestCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn pass_if(name: &'static str, ok: bool, pass: &str, fail: impl Into<String>) -> Self {
+        if ok {
+            Self::new(name, CheckStatus::Pass, pass)
+        } else {
+            Self::new(name, CheckStatus::Fail, fail)
+        }
+    }
+}
+
+/// `run_self_test` result
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// No check failed
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+    /// Unix seconds
+    pub ran_at: u64,
+}
+
+fn summarize(checks: Vec<SelfTestCheck>) -> SelfTestReport {
+    SelfTestReport {
+        passed: checks.iter().all(|c| c.status != CheckStatus::Fail),
+        checks,
+        ran_at: unix_now(),
+    }
+}
+
+/// Priority and timer resolution are dropped on purpose in battery saver
+/// and while suspended
+fn power_saving(app: &AppHandle) -> bool {
+    let chosen = app.state::<SettingsState>().get().power_profile;
+    app.state::<PowerGuard>().active_profile(chosen) == PowerProfile::BatterySaver
+        || crate::suspend::is_suspended()
+}
+
+fn timer_resolution(saving: bool) -> SelfTestCheck {
+    const NAME: &str = "timer_resolution";
+    if cfg!(target_os = "linux") {
+        return SelfTestCheck::new(NAME, CheckStatus::Skipped, "Not used on Linux");
+    }
+    if saving {
+        return SelfTestCheck::new(NAME, CheckStatus::Skipped, "Released while saving power");
+    }
+    SelfTestCheck::pass_if(
+        NAME,
+        performance::high_timer_resolution_active(),
+        "1 ms timer resolution held",
+        "1 ms timer resolution is not held (timeBeginPeriod failed)",
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn priority(saving: bool) -> SelfTestCheck {
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, GetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS,
+    };
+
+    const NAME: &str = "process_priority";
+    if saving {
+        return SelfTestCheck::new(
+            NAME,
+            CheckStatus::Skipped,
+            "Normal priority while saving power",
+        );
+    }
+    // SAFETY: pseudo-handle of the current process
+    let class = unsafe { GetPriorityClass(GetCurrentProcess()) };
+    SelfTestCheck::pass_if(
+        NAME,
+        class == ABOVE_NORMAL_PRIORITY_CLASS.0,
+        "Above normal",
+        format!("Priority class is 0x{:X}, not above normal", class),
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn priority(saving: bool) -> SelfTestCheck {
+    const NAME: &str = "process_priority";
+    if saving {
+        return SelfTestCheck::new(
+            NAME,
+            CheckStatus::Skipped,
+            "Default nice value while saving power",
+        );
+    }
+    // SAFETY: plain syscall on the current process
+    let nice = unsafe { nix::libc::getpriority(nix::libc::PRIO_PROCESS, 0) };
+    if nice < 0 {
+        SelfTestCheck::new(NAME, CheckStatus::Pass, format!("Nice value {}", nice))
+    } else {
+        SelfTestCheck::new(
+            NAME,
+            CheckStatus::Skipped,
+            "Default nice value; raising it needs CAP_SYS_NICE",
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn dwm_attributes(app: &AppHandle) -> SelfTestCheck {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_TRANSITIONS_FORCEDISABLED};
+
+    const NAME: &str = "dwm_attributes";
+    let Some(hwnd) = app
+        .get_webview_window("main")
+        .and_then(|window| window.hwnd().ok())
+    else {
+        return SelfTestCheck::new(NAME, CheckStatus::Fail, "No main window");
+    };
+    let mut disabled: i32 = 0;
+    // SAFETY: reads one BOOL-sized attribute into a local
+    let read = unsafe {
+        DwmGetWindowAttribute(
+            HWND(hwnd.0 as *mut std::ffi::c_void),
+            DWMWA_TRANSITIONS_FORCEDISABLED,
+            &mut disabled as *mut i32 as *mut std::ffi::c_void,
+            std::mem::size_of::<i32>() as u32,
+        )
+    };
+    match read {
+        Ok(()) => SelfTestCheck::pass_if(
+            NAME,
+            disabled != 0,
+            "Window transitions disabled",
+            "Window transitions are still enabled",
+        ),
+        Err(e) => SelfTestCheck::new(NAME, CheckStatus::Fail, format!("DWM query failed: {}", e)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn dwm_attributes(_app: &AppHandle) -> SelfTestCheck {
+    SelfTestCheck::new(
+        "dwm_attributes",
+        CheckStatus::Skipped,
+        "The window manager composites on Linux",
+    )
+}
+
+fn wmi_watcher() -> SelfTestCheck {
+    const NAME: &str = "wmi_watcher";
+    if cfg!(target_os = "linux") {
+        return SelfTestCheck::new(NAME, CheckStatus::Skipped, "Windows only");
+    }
+    let telemetry = performance::get_elevation_telemetry();
+    if !telemetry.is_active {
+        return SelfTestCheck::new(NAME, CheckStatus::Fail, "WebView2 optimizer is not running");
+    }
+    SelfTestCheck::pass_if(
+        NAME,
+        telemetry.wmi_available,
+        &format!(
+            "Event-driven, {} process(es) elevated",
+            telemetry.processes_elevated
+        ),
+        "WMI subscription is down; using the polling fallback",
+    )
+}
+
+fn gpu_counters() -> SelfTestCheck {
+    let stats = performance::get_gpu_stats();
+    if stats.initializing {
+        return SelfTestCheck::new("gpu_counters", CheckStatus::Skipped, "Still initializing");
+    }
+    SelfTestCheck::pass_if(
+        "gpu_counters",
+        stats.available && stats.error.is_none(),
+        &format!("{:.0}% usage", stats.usage_percent),
+        stats
+            .error
+            .unwrap_or_else(|| "GPU counters unavailable".to_string()),
+    )
+}
+
+fn hdr_query() -> SelfTestCheck {
+    let hdr = performance::get_hdr_info();
+    match hdr.error {
+        None => SelfTestCheck::new(
+            "hdr_query",
+            CheckStatus::Pass,
+            format!("{} ({} bits)", hdr.color_space, hdr.bits_per_color),
+        ),
+        Some(e) => SelfTestCheck::new("hdr_query", CheckStatus::Fail, e),
+    }
+}
+
+fn injection(app: &AppHandle) -> SelfTestCheck {
+    const NAME: &str = "injection";
+    match app.state::<FreezeWatchdog>().last_heartbeat() {
+        None => SelfTestCheck::new(NAME, CheckStatus::Fail, "The runtime has not reported yet"),
+        Some(at) => {
+            let age = at.elapsed();
+            SelfTestCheck::pass_if(
+                NAME,
+                age < HEARTBEAT_STALE,
+                "Runtime heartbeat is current",
+                format!("Last runtime heartbeat {} s ago", age.as_secs()),
+            )
+        }
+    }
+}
+
+/// Run every check
+pub fn run(app: &AppHandle) -> SelfTestReport {
+    let saving = power_saving(app);
+    let report = summarize(vec![
+        timer_resolution(saving),
+        priority(saving),
+        dwm_attributes(app),
+        wmi_watcher(),
+        gpu_counters(),
+        hdr_query(),
+        injection(app),
+    ]);
+    let failed: Vec<_> = report
+        .checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .map(|c| c.name)
+        .collect();
+    if failed.is_empty() {
+        info!("Self-test passed");
+    } else {
+        warn!("Self-test failed: {}", failed.join(", "));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skipped_checks_do_not_fail_the_report() {
+        let report = summarize(vec![
+            SelfTestCheck::new("a", CheckStatus::Pass, ""),
+            SelfTestCheck::new("b", CheckStatus::Skipped, ""),
+        ]);
+        assert!(report.passed);
+        let failed = summarize(vec![SelfTestCheck::pass_if("c", false, "", "broken")]);
+        assert!(!failed.passed);
+        assert_eq!(failed.checks[0].detail, "broken");
+    }
+}
@@ -0,0 +1,352 @@
+//! Game Server Status - Cross-platform
+//!
+//! Polls a status feed the user configured (`server_status`, off by
+//! default) and shows the result in the tray tooltip and an overlay row
+//! (`pac-server-status`), with a notification when the servers are back
+//! after maintenance or an outage. `maintenance.rs` only notices problems
+//! once the page talks to the server; the feed also covers the time the
+//! game sits closed or in the lobby.
+//!
+//! Understood feeds, all JSON:
+//! - Statuspage `status.json` / `summary.json`: `status.indicator`
+//! - a game status object: `status` (`online`, `maintenance`, ...) and a
+//!   player count (`players`, `player_count`, `playerCount` or `online`)
+//! - a Discord server widget (`widget.json`): online members stand in for
+//!   the player count
+
+use crate::maintenance::{self, UpstreamIssueKind};
+use crate::report::unix_now;
+use crate::settings::SettingsState;
+use crate::tray;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{debug, info, warn};
+
+/// How often the poller looks at its due time and the settings
+const TICK: Duration = Duration::from_secs(15);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Feeds larger than this are not status feeds
+const MAX_BODY_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerState {
+    Online,
+    /// Up, with problems (Statuspage "minor")
+    Degraded,
+    Maintenance,
+    Down,
+    /// The feed could not be read or said nothing we understand
+    Unknown,
+}
+
+impl ServerState {
+    fn label(self) -> &'static str {
+        match self {
+            ServerState::Online => "online",
+            ServerState::Degraded => "degraded",
+            ServerState::Maintenance => "maintenance",
+            ServerState::Down => "down",
+            ServerState::Unknown => "unknown",
+        }
+    }
+}
+
+/// Payload of `pac-server-status` and `get_server_status`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ServerStatus {
+    pub state: ServerState,
+    pub players: Option<u64>,
+    /// The feed's own wording, when it has one
+    pub description: Option<String>,
+    /// Why the feed could not be read
+    pub error: Option<String>,
+    /// Unix seconds
+    pub checked_at: u64,
+}
+
+impl ServerStatus {
+    fn unknown(error: String) -> Self {
+        Self {
+            state: ServerState::Unknown,
+            players: None,
+            description: None,
+            error: Some(error),
+            checked_at: unix_now(),
+        }
+    }
+
+    /// One line for the tray tooltip
+    pub fn summary(&self) -> String {
+        match self.players {
+            Some(players) => format!("Servers {}, {} online", self.state.label(), players),
+            None => format!("Servers {}", self.state.label()),
+        }
+    }
+}
+
+/// Managed status of the last poll
+#[derive(Default)]
+pub struct ServerStatusStore(Mutex<Option<ServerStatus>>);
+
+impl ServerStatusStore {
+    pub fn current(&self) -> Option<ServerStatus> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+fn state_from_word(word: &str) -> ServerState {
+    match word.trim().to_ascii_lowercase().as_str() {
+        "ok" | "up" | "online" | "operational" | "none" => ServerState::Online,
+        "minor" | "degraded" | "degraded_performance" | "partial" => ServerState::Degraded,
+        "maintenance" | "under_maintenance" => ServerState::Maintenance,
+        "major" | "critical" | "down" | "offline" | "outage" | "major_outage" => ServerState::Down,
+        _ => ServerState::Unknown,
+    }
+}
+
+/// Read a feed body; see the module docs for the formats
+fn parse(body: &str) -> Result<(ServerState, Option<u64>, Option<String>), String> {
+    let json: Value =
+        serde_json::from_str(body).map_err(|e| format!("Status feed is not JSON: {}", e))?;
+    let text = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+    let players = ["players", "player_count", "playerCount", "online"]
+        .iter()
+        .find_map(|key| json.get(key).and_then(Value::as_u64))
+        .or_else(|| json.get("presence_count").and_then(Value::as_u64));
+
+    let (mut state, description) = match json.get("status") {
+        // Statuspage
+        Some(Value::Object(status)) => (
+            text(status.get("indicator")).map_or(ServerState::Unknown, |i| state_from_word(&i)),
+            text(status.get("description")),
+        ),
+        Some(Value::String(word)) => (state_from_word(word), text(json.get("message"))),
+        _ => (ServerState::Unknown, None),
+    };
+    // A Statuspage summary lists maintenance in progress separately
+    let maintenance = json
+        .get("scheduled_maintenances")
+        .and_then(Value::as_array)
+        .is_some_and(|list| {
+            list.iter()
+                .any(|m| m.get("status").and_then(Value::as_str) == Some("in_progress"))
+        });
+    if maintenance {
+        state = ServerState::Maintenance;
+    } else if state == ServerState::Unknown && players.is_some() {
+        // A player count without a status (Discord widget): it answered
+        state = ServerState::Online;
+    }
+    Ok((state, players, description))
+}
+
+/// Whether a change from `previous` to `next` is worth a notification
+fn came_back(previous: Option<ServerState>, next: ServerState) -> bool {
+    matches!(
+        previous,
+        Some(ServerState::Maintenance) | Some(ServerState::Down)
+    ) && matches!(next, ServerState::Online | ServerState::Degraded)
+}
+
+/// Validate a feed URL: HTTPS (plain HTTP only on this machine) and no
+/// credentials
+pub fn normalize_url(raw: &str) -> Result<String, String> {
+    let mut url =
+        tauri::Url::parse(raw.trim()).map_err(|e| format!("Invalid URL {}: {}", raw, e))?;
+    let loopback = matches!(
+        url.host_str(),
+        Some("localhost") | Some("127.0.0.1") | Some("[::1]")
+    );
+    match url.scheme() {
+        "https" => {}
+        "http" if loopback => {}
+        _ => return Err(format!("Status feed must use https: {}", raw)),
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(format!("Status feed must not contain credentials: {}", raw));
+    }
+    url.set_fragment(None);
+    Ok(url.to_string())
+}
+
+async fn fetch(url: &str) -> ServerStatus {
+    let client = match reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return ServerStatus::unknown(format!("HTTP client unavailable: {}", e)),
+    };
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => return ServerStatus::unknown(format!("Status feed unreachable: {}", e)),
+    };
+    let status = response.status().as_u16();
+    if !response.status().is_success() {
+        // A feed hosted on the game server fails like the game does
+        let state = match maintenance::classify(status, response.headers()).map(|i| i.kind) {
+            Some(UpstreamIssueKind::Maintenance) => ServerState::Maintenance,
+            Some(UpstreamIssueKind::Outage) => ServerState::Down,
+            _ => ServerState::Unknown,
+        };
+        return ServerStatus {
+            state,
+            ..ServerStatus::unknown(format!("Status feed answered HTTP {}", status))
+        };
+    }
+    let body = match response.bytes().await {
+        Ok(body) if body.len() <= MAX_BODY_BYTES => body,
+        Ok(_) => return ServerStatus::unknown("Status feed is too large".to_string()),
+        Err(e) => return ServerStatus::unknown(format!("Failed to read status feed: {}", e)),
+    };
+    match parse(&String::from_utf8_lossy(&body)) {
+        Ok((state, players, description)) => ServerStatus {
+            state,
+            players,
+            description,
+            error: None,
+            checked_at: unix_now(),
+        },
+        Err(e) => ServerStatus::unknown(e),
+    }
+}
+
+/// Store a poll result and tell the tray, the page and the user.
+/// `last_known` is the last state other than unknown, so a feed that
+/// fails once during maintenance still announces the recovery.
+fn record(app: &AppHandle, status: Option<ServerStatus>, last_known: &mut Option<ServerState>) {
+    let previous = {
+        let store = app.state::<ServerStatusStore>();
+        let mut current = store.0.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *current, status.clone())
+    };
+    let previous_state = previous.as_ref().map(|s| s.state);
+    tray::set_server_status(app, status.as_ref().map(ServerStatus::summary));
+    if status != previous {
+        let _ = app.emit("pac-server-status", &status);
+    }
+    let Some(status) = status else {
+        return;
+    };
+    if previous_state != Some(status.state) {
+        info!(
+            "Game server status: {:?} ({:?} players){}",
+            status.state,
+            status.players,
+            status
+                .error
+                .as_deref()
+                .map(|e| format!(": {}", e))
+                .unwrap_or_default()
+        );
+    }
+    if status.state != ServerState::Unknown {
+        let announce = came_back(*last_known, status.state);
+        *last_known = Some(status.state);
+        if !announce {
+            return;
+        }
+        let body = match status.players {
+            Some(players) => format!("The game servers are back ({} online)", players),
+            None => "The game servers are back".to_string(),
+        };
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title("PACDeluxe")
+            .body(&body)
+            .show()
+        {
+            warn!("Failed to show server status notification: {}", e);
+        }
+    }
+}
+
+/// Poll the configured feed at its interval
+pub fn spawn(app: AppHandle) {
+    app.manage(ServerStatusStore::default());
+    std::thread::Builder::new()
+        .name("server-status".to_string())
+        .spawn(move || {
+            let mut due = Instant::now();
+            let mut polled_url: Option<String> = None;
+            let mut last_known = None;
+            loop {
+                let feed = app.state::<SettingsState>().get().server_status.validated();
+                let url = (feed.enabled && !feed.url.is_empty()).then_some(feed.url);
+                if url != polled_url {
+                    // Turned off or pointed elsewhere: the old result is stale
+                    if polled_url.is_some() {
+                        record(&app, None, &mut last_known);
+                    }
+                    last_known = None;
+                    polled_url = url.clone();
+                    due = Instant::now();
+                }
+                if let Some(url) = &url {
+                    if Instant::now() >= due && !crate::suspend::is_suspended() {
+                        let status = tauri::async_runtime::block_on(fetch(url));
+                        record(&app, Some(status), &mut last_known);
+                        due = Instant::now() + Duration::from_secs(u64::from(feed.interval_secs));
+                        debug!("Next server status poll in {}s", feed.interval_secs);
+                    }
+                }
+                std::thread::sleep(TICK);
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start server status polling: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feeds_are_understood() {
+        let statuspage =
+            r#"{"status":{"indicator":"minor","description":"Partially Degraded Service"}}"#;
+        assert_eq!(
+            parse(statuspage).unwrap(),
+            (
+                ServerState::Degraded,
+                None,
+                Some("Partially Degraded Service".to_string())
+            )
+        );
+        let summary = r#"{"status":{"indicator":"none"},"scheduled_maintenances":[{"status":"in_progress"}]}"#;
+        assert_eq!(parse(summary).unwrap().0, ServerState::Maintenance);
+        let game = r#"{"status":"online","players":1234}"#;
+        assert_eq!(
+            parse(game).unwrap(),
+            (ServerState::Online, Some(1234), None)
+        );
+        let discord = r#"{"id":"1","name":"PAC","presence_count":560}"#;
+        assert_eq!(
+            parse(discord).unwrap(),
+            (ServerState::Online, Some(560), None)
+        );
+        assert!(parse("<html>").is_err());
+    }
+
+    #[test]
+    fn only_recovery_is_announced() {
+        assert!(came_back(
+            Some(ServerState::Maintenance),
+            ServerState::Online
+        ));
+        assert!(came_back(Some(ServerState::Down), ServerState::Degraded));
+        assert!(!came_back(Some(ServerState::Online), ServerState::Online));
+        assert!(!came_back(
+            Some(ServerState::Maintenance),
+            ServerState::Unknown
+        ));
+        assert!(!came_back(None, ServerState::Online));
+    }
+}
@@ -0,0 +1,266 @@
+//! Session Store - Cross-platform
+//!
+//! Tracks play time: the current continuous stretch and a per-day total,
+//! persisted as JSON next to the settings file. Time only counts while the
+//! main window is focused. Days are UTC calendar days.
+//!
+//! Also drives the optional break reminder (`pac-break-reminder` event),
+//! which is held back while a match is in progress.
+
+use crate::page_state::PageState;
+use crate::settings::{self, SettingsState};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info, warn};
+
+const SESSIONS_FILE_NAME: &str = "sessions.json";
+/// How often play time is sampled
+const TICK: Duration = Duration::from_secs(30);
+/// Ticks between saves (5 minutes)
+const SAVE_EVERY_TICKS: u32 = 10;
+/// Unfocused for this long ends the continuous stretch
+const BREAK_GAP: Duration = Duration::from_secs(5 * 60);
+/// Daily totals older than this are dropped
+const MAX_DAYS: usize = 90;
+
+/// Persisted session data
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SessionData {
+    /// "YYYY-MM-DD" (UTC) -> seconds played
+    pub daily_play_secs: BTreeMap<String, u64>,
+}
+
+/// Play time summary for the overlay and dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayTimeSummary {
+    pub today_secs: u64,
+    /// Current continuous stretch
+    pub continuous_secs: u64,
+    /// Daily totals, oldest first
+    pub daily: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Default)]
+struct Tracker {
+    data: SessionData,
+    continuous_secs: u64,
+    unfocused_secs: u64,
+    reminded: bool,
+}
+
+impl Tracker {
+    /// Advance by one tick. Returns true when a break reminder is due.
+    fn tick(
+        &mut self,
+        day: &str,
+        focused: bool,
+        elapsed: u64,
+        reminder_after: Option<u64>,
+    ) -> bool {
+        if focused {
+            self.unfocused_secs = 0;
+            self.continuous_secs += elapsed;
+            *self
+                .data
+                .daily_play_secs
+                .entry(day.to_string())
+                .or_default() += elapsed;
+        } else {
+            self.unfocused_secs += elapsed;
+            if self.unfocused_secs >= BREAK_GAP.as_secs() {
+                self.continuous_secs = 0;
+                self.reminded = false;
+            }
+        }
+
+        while self.data.daily_play_secs.len() > MAX_DAYS {
+            self.data.daily_play_secs.pop_first();
+        }
+
+        match reminder_after {
+            Some(limit) if focused && !self.reminded && self.continuous_secs >= limit => {
+                self.reminded = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Managed session store
+#[derive(Default)]
+pub struct SessionStore(Mutex<Tracker>);
+
+impl SessionStore {
+    /// Load persisted session data (defaults when missing or invalid)
+    pub fn load() -> Self {
+        let data = sessions_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self(Mutex::new(Tracker {
+            data,
+            ..Default::default()
+        }))
+    }
+
+    pub fn summary(&self) -> PlayTimeSummary {
+        let tracker = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let today = utc_date(SystemTime::now());
+        PlayTimeSummary {
+            today_secs: tracker
+                .data
+                .daily_play_secs
+                .get(&today)
+                .copied()
+                .unwrap_or(0),
+            continuous_secs: tracker.continuous_secs,
+            daily: tracker
+                .data
+                .daily_play_secs
+                .iter()
+                .map(|(day, secs)| (day.clone(), *secs))
+                .collect(),
+        }
+    }
+
+    /// Persist session data (also called periodically by the tracker)
+    pub fn save(&self) {
+        let data = self
+            .0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .data
+            .clone();
+        let Some(path) = sessions_path() else { return };
+        let result = serde_json::to_string(&data)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                let tmp_path = path.with_extension("json.tmp");
+                std::fs::write(&tmp_path, json).map_err(|e| e.to_string())?;
+                std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => debug!("Saved session data to {}", path.display()),
+            Err(e) => warn!("Failed to save session data: {}", e),
+        }
+    }
+}
+
+/// Payload of the `pac-break-reminder` event
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakReminder {
+    pub continuous_minutes: u64,
+}
+
+fn sessions_path() -> Option<PathBuf> {
+    let dir = settings::config_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(SESSIONS_FILE_NAME))
+}
+
+/// UTC calendar date as "YYYY-MM-DD"
+pub(crate) fn utc_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Start the play time tracker thread
+pub fn spawn_tracker(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("session-tracker".to_string())
+        .spawn(move || {
+            let mut ticks = 0u32;
+            let mut reminder_pending = false;
+            loop {
+                std::thread::sleep(TICK);
+
+                let focused = app.get_webview_window("main").is_some_and(|window| {
+                    window.is_focused().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+                });
+                let reminder = app.state::<SettingsState>().get().break_reminder;
+                let reminder_after = reminder
+                    .enabled
+                    .then_some(reminder.interval_minutes.max(1) * 60);
+
+                let store = app.state::<SessionStore>();
+                let due = store.0.lock().unwrap_or_else(|e| e.into_inner()).tick(
+                    &utc_date(SystemTime::now()),
+                    focused,
+                    TICK.as_secs(),
+                    reminder_after,
+                );
+                reminder_pending |= due;
+
+                // Hold the reminder until the player is out of the match
+                if reminder_pending && !app.state::<PageState>().phase().is_match_in_progress() {
+                    reminder_pending = false;
+                    let continuous_minutes = store.summary().continuous_secs / 60;
+                    info!("Break reminder after {} min of play", continuous_minutes);
+                    let _ = app.emit("pac-break-reminder", BreakReminder { continuous_minutes });
+                }
+
+                ticks += 1;
+                if ticks % SAVE_EVERY_TICKS == 0 {
+                    store.save();
+                }
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start session tracker: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utc_dates_are_civil() {
+        assert_eq!(utc_date(UNIX_EPOCH), "1970-01-01");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+        assert_eq!(utc_date(leap_day), "2024-02-29");
+    }
+
+    #[test]
+    fn focused_time_accumulates_per_day() {
+        let mut tracker = Tracker::default();
+        tracker.tick("2026-01-01", true, 30, None);
+        tracker.tick("2026-01-01", false, 30, None);
+        tracker.tick("2026-01-02", true, 30, None);
+        assert_eq!(tracker.data.daily_play_secs["2026-01-01"], 30);
+        assert_eq!(tracker.data.daily_play_secs["2026-01-02"], 30);
+        assert_eq!(tracker.continuous_secs, 60);
+    }
+
+    #[test]
+    fn reminder_fires_once_per_stretch() {
+        let mut tracker = Tracker::default();
+        assert!(!tracker.tick("d", true, 30, Some(60)));
+        assert!(tracker.tick("d", true, 30, Some(60)));
+        assert!(!tracker.tick("d", true, 30, Some(60)));
+
+        // A long enough break starts a new stretch
+        tracker.tick("d", false, BREAK_GAP.as_secs(), Some(60));
+        assert_eq!(tracker.continuous_secs, 0);
+        tracker.tick("d", true, 30, Some(60));
+        assert!(tracker.tick("d", true, 30, Some(60)));
+    }
+}
@@ -0,0 +1,1532 @@
+//! User Settings - Cross-platform
+//!
+//! Persisted user preferences for the native client.
+//! Stored as JSON in the PACDeluxe config directory so they can be read
+//! before the Tauri runtime (and its path resolver) exists.
+
+use crate::browser_flags::NetworkingPreset;
+use crate::commands::WindowMode;
+use crate::contrast::ContrastMode;
+use crate::recovery::FreezeAction;
+use crate::scheduler::ScheduledTask;
+use crate::settings_migration::{self, SCHEMA_VERSION};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tracing::{debug, info, warn};
+use ts_rs::TS;
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// Content Security Policy injected into bundled HTML documents.
+///
+/// The CSP in tauri.conf.json only applies to Tauri's custom protocol. The
+/// game is served by our own localhost server, so without this the page runs
+/// with no policy at all.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum CspMode {
+    /// No policy header (upstream behavior)
+    #[default]
+    Off,
+    /// Send `Content-Security-Policy-Report-Only`; violations are logged
+    ReportOnly,
+    /// Send `Content-Security-Policy`; violations are blocked and logged
+    Enforce,
+}
+
+/// Opt-in automatic page reload when the renderer bloats
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct SoftReloadSettings {
+    pub enabled: bool,
+    /// Reload when the used JS heap exceeds this (MB)
+    #[ts(type = "number")]
+    pub heap_threshold_mb: u64,
+    /// Reload when the WebView's processes (renderers included) use more
+    /// memory than this (MB)
+    #[ts(type = "number")]
+    pub rss_threshold_mb: u64,
+    /// Minimum time between automatic reloads
+    #[ts(type = "number")]
+    pub cooldown_minutes: u64,
+}
+
+impl Default for SoftReloadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            heap_threshold_mb: 1536,
+            rss_threshold_mb: 3072,
+            cooldown_minutes: 30,
+        }
+    }
+}
+
+/// Optional reminder to take a break after continuous play
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct BreakReminderSettings {
+    pub enabled: bool,
+    /// Continuous play time before the reminder
+    #[ts(type = "number")]
+    pub interval_minutes: u64,
+}
+
+impl Default for BreakReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 90,
+        }
+    }
+}
+
+/// Optional guard against accidental sells near the end of planning
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct MisdropGuardSettings {
+    pub enabled: bool,
+    /// Seconds left on the visible timer when the guard starts
+    #[ts(type = "number")]
+    pub final_seconds: u64,
+    /// The game's sell key (single character)
+    pub sell_key: String,
+}
+
+/// Allowed range for `MisdropGuardSettings::final_seconds`
+pub const MISDROP_GUARD_SECONDS: std::ops::RangeInclusive<u64> = 1..=15;
+
+impl Default for MisdropGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            final_seconds: 3,
+            sell_key: "e".to_string(),
+        }
+    }
+}
+
+impl MisdropGuardSettings {
+    /// Lowercase the sell key and clamp the window, rejecting unusable keys
+    pub fn validated(mut self) -> Result<Self, String> {
+        let key = self.sell_key.trim().to_lowercase();
+        if key.chars().count() != 1 {
+            return Err(format!(
+                "Sell key must be a single character: {:?}",
+                self.sell_key
+            ));
+        }
+        self.sell_key = key;
+        self.final_seconds = self
+            .final_seconds
+            .clamp(*MISDROP_GUARD_SECONDS.start(), *MISDROP_GUARD_SECONDS.end());
+        Ok(self)
+    }
+}
+
+/// Optional limit on how fast a held reroll key repeats
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct TurboGuardSettings {
+    pub enabled: bool,
+    /// Rerolls per second let through while the key is held
+    pub max_per_second: u32,
+}
+
+/// Allowed range for `TurboGuardSettings::max_per_second`
+pub const TURBO_GUARD_RATE: std::ops::RangeInclusive<u32> = 1..=20;
+
+impl Default for TurboGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_per_second: 4,
+        }
+    }
+}
+
+impl TurboGuardSettings {
+    pub fn validated(mut self) -> Self {
+        self.max_per_second = self
+            .max_per_second
+            .clamp(*TURBO_GUARD_RATE.start(), *TURBO_GUARD_RATE.end());
+        self
+    }
+}
+
+/// Separate music and sound effect levels, applied by the injected runtime
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct AudioMixSettings {
+    /// Music level (0-1)
+    pub music_volume: f32,
+    /// Sound effect level (0-1)
+    pub sfx_volume: f32,
+    pub music_muted: bool,
+    pub sfx_muted: bool,
+    /// Toggles `music_muted`
+    pub music_hotkey: String,
+    /// Toggles `sfx_muted`
+    pub sfx_hotkey: String,
+}
+
+impl Default for AudioMixSettings {
+    fn default() -> Self {
+        Self {
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            music_muted: false,
+            sfx_muted: false,
+            music_hotkey: "Ctrl+Shift+M".to_string(),
+            sfx_hotkey: "Ctrl+Shift+K".to_string(),
+        }
+    }
+}
+
+impl AudioMixSettings {
+    /// Check the levels and normalize the hotkeys
+    pub fn validated(mut self) -> Result<Self, String> {
+        crate::audio::validate_volume(self.music_volume)?;
+        crate::audio::validate_volume(self.sfx_volume)?;
+        self.music_hotkey = normalize_hotkey(&self.music_hotkey)?;
+        self.sfx_hotkey = normalize_hotkey(&self.sfx_hotkey)?;
+        if self.music_hotkey == self.sfx_hotkey {
+            return Err("Music and sound effect hotkeys must differ".to_string());
+        }
+        Ok(self)
+    }
+}
+
+/// Window mode applied at startup
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum StartMode {
+    /// The mode the window was last in
+    #[default]
+    Last,
+    Windowed,
+    Fullscreen,
+    BorderlessWindowed,
+}
+
+impl StartMode {
+    /// The mode to start in, given the last used one
+    pub fn resolve(self, last: WindowMode) -> WindowMode {
+        match self {
+            StartMode::Last => last,
+            StartMode::Windowed => WindowMode::Windowed,
+            StartMode::Fullscreen => WindowMode::Fullscreen,
+            StartMode::BorderlessWindowed => WindowMode::BorderlessWindowed,
+        }
+    }
+}
+
+/// Stats overlay layouts, cycled by repeated hotkey presses
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+pub enum OverlayLayout {
+    /// FPS only
+    Minimal,
+    /// All stat rows
+    Standard,
+    /// Stat rows plus FPS/CPU graphs
+    Expanded,
+}
+
+/// Stats overlay configuration
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct OverlaySettings {
+    /// Toggle/cycle hotkey, e.g. "Ctrl+Shift+P"
+    pub hotkey: String,
+    /// Layouts in cycle order; after the last one the overlay hides
+    pub layouts: Vec<OverlayLayout>,
+    /// How often native stats are pushed to the visible overlay
+    #[ts(type = "number")]
+    pub stats_interval_ms: u64,
+}
+
+/// Allowed range for `OverlaySettings::stats_interval_ms`
+pub const OVERLAY_STATS_INTERVAL_MS: std::ops::RangeInclusive<u64> = 250..=5000;
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            hotkey: "Ctrl+Shift+P".to_string(),
+            layouts: vec![
+                OverlayLayout::Standard,
+                OverlayLayout::Minimal,
+                OverlayLayout::Expanded,
+            ],
+            stats_interval_ms: 500,
+        }
+    }
+}
+
+impl OverlaySettings {
+    /// Normalize the hotkey and layout list, rejecting unusable values
+    pub fn validated(mut self) -> Result<Self, String> {
+        self.hotkey = normalize_hotkey(&self.hotkey)?;
+        let mut seen = Vec::new();
+        self.layouts.retain(|layout| {
+            let first = !seen.contains(layout);
+            seen.push(*layout);
+            first
+        });
+        if self.layouts.is_empty() {
+            return Err("At least one overlay layout is required".to_string());
+        }
+        self.stats_interval_ms = self.stats_interval_ms.clamp(
+            *OVERLAY_STATS_INTERVAL_MS.start(),
+            *OVERLAY_STATS_INTERVAL_MS.end(),
+        );
+        Ok(self)
+    }
+}
+
+/// Normalize a hotkey like "shift+ctrl+p" to "Ctrl+Shift+P".
+/// Requires at least one modifier unless the key is a function key.
+pub fn normalize_hotkey(hotkey: &str) -> Result<String, String> {
+    let (mut ctrl, mut shift, mut alt) = (false, false, false);
+    let mut key = None;
+    for part in hotkey.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => ctrl = true,
+            "shift" => shift = true,
+            "alt" => alt = true,
+            "" => return Err(format!("Invalid hotkey: {}", hotkey)),
+            _ if key.is_some() => return Err(format!("Hotkey has more than one key: {}", hotkey)),
+            _ => key = Some(part),
+        }
+    }
+    let key = key.ok_or_else(|| format!("Hotkey has no key: {}", hotkey))?;
+    let is_function_key = key.len() > 1
+        && (key.starts_with('F') || key.starts_with('f'))
+        && key[1..].parse::<u8>().is_ok_and(|n| (1..=24).contains(&n));
+    if !(ctrl || shift || alt || is_function_key) {
+        return Err(format!("Hotkey needs a modifier: {}", hotkey));
+    }
+
+    let key = if key.chars().count() == 1 || is_function_key {
+        key.to_ascii_uppercase()
+    } else {
+        key.to_string()
+    };
+    let mut parts = Vec::new();
+    if ctrl {
+        parts.push("Ctrl");
+    }
+    if shift {
+        parts.push("Shift");
+    }
+    if alt {
+        parts.push("Alt");
+    }
+    parts.push(&key);
+    Ok(parts.join("+"))
+}
+
+/// Validate a fallback game URL. Mirrors must use HTTPS (plain HTTP is
+/// allowed for a self-hosted server on this machine) and carry no
+/// credentials, query, or fragment.
+pub fn normalize_mirror_url(raw: &str) -> Result<String, String> {
+    let url = tauri::Url::parse(raw.trim()).map_err(|e| format!("Invalid URL {}: {}", raw, e))?;
+    let loopback = matches!(
+        url.host_str(),
+        Some("localhost") | Some("127.0.0.1") | Some("[::1]")
+    );
+    match url.scheme() {
+        "https" => {}
+        "http" if loopback => {}
+        _ => return Err(format!("Mirror must use https: {}", raw)),
+    }
+    if url.host_str().is_none() {
+        return Err(format!("Mirror has no host: {}", raw));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(format!("Mirror must not contain credentials: {}", raw));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(format!(
+            "Mirror must not contain a query or fragment: {}",
+            raw
+        ));
+    }
+    Ok(url.to_string())
+}
+
+/// Debug WebSocket frame statistics (see `ws_inspector.rs`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct WsInspectorSettings {
+    pub enabled: bool,
+    /// Keep only protocol codes, not message type names
+    pub redact: bool,
+}
+
+impl Default for WsInspectorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            redact: true,
+        }
+    }
+}
+
+/// How one kind of attention alert gets the player's attention
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub enabled: bool,
+    /// Flash the taskbar entry
+    pub flash: bool,
+    /// Play the system alert sound
+    pub sound: bool,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            flash: true,
+            sound: false,
+        }
+    }
+}
+
+/// Alerts while the window is in the background (see `attention.rs`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct AttentionSettings {
+    /// A carousel round started and the player hasn't moved
+    pub carousel_idle: AlertSettings,
+    /// Seconds without input into a carousel round before alerting
+    #[ts(type = "number")]
+    pub carousel_idle_seconds: u64,
+    /// A match started
+    pub match_start: AlertSettings,
+}
+
+/// Allowed range for `AttentionSettings::carousel_idle_seconds`
+pub const CAROUSEL_IDLE_SECONDS: std::ops::RangeInclusive<u64> = 2..=20;
+
+impl Default for AttentionSettings {
+    fn default() -> Self {
+        Self {
+            carousel_idle: AlertSettings::default(),
+            carousel_idle_seconds: 5,
+            match_start: AlertSettings {
+                enabled: false,
+                ..AlertSettings::default()
+            },
+        }
+    }
+}
+
+impl AttentionSettings {
+    pub fn validated(mut self) -> Self {
+        self.carousel_idle_seconds = self
+            .carousel_idle_seconds
+            .clamp(*CAROUSEL_IDLE_SECONDS.start(), *CAROUSEL_IDLE_SECONDS.end());
+        self
+    }
+}
+
+/// Process tuning profile
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum PowerProfile {
+    /// Above-normal priority, no power throttling, 1ms timer resolution
+    #[default]
+    Performance,
+    /// Normal priority, power throttling allowed, default timer resolution
+    BatterySaver,
+}
+
+/// Automatic switch to the battery saver profile on low battery or
+/// overheating (see `power_guard.rs`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct PowerGuardSettings {
+    pub enabled: bool,
+    /// Switch when running on battery below this charge (percent)
+    pub battery_percent: u8,
+    /// Switch when a CPU sensor reaches this (°C)
+    pub cpu_temp_c: f32,
+    /// Switch when a GPU sensor reaches this (°C)
+    pub gpu_temp_c: f32,
+}
+
+impl Default for PowerGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            battery_percent: 20,
+            cpu_temp_c: 95.0,
+            gpu_temp_c: 90.0,
+        }
+    }
+}
+
+impl PowerGuardSettings {
+    pub fn validated(mut self) -> Self {
+        self.battery_percent = self.battery_percent.clamp(5, 50);
+        for (limit, fallback) in [(&mut self.cpu_temp_c, 95.0), (&mut self.gpu_temp_c, 90.0)] {
+            *limit = if limit.is_finite() {
+                limit.clamp(60.0, 105.0)
+            } else {
+                fallback
+            };
+        }
+        self
+    }
+}
+
+/// How often the updater looks for a new release
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct UpdateCheckSettings {
+    pub enabled: bool,
+    /// Hours between checks; each one is moved by up to a fifth at random
+    pub interval_hours: u32,
+}
+
+/// Allowed range for `UpdateCheckSettings::interval_hours`
+pub const UPDATE_CHECK_HOURS: std::ops::RangeInclusive<u32> = 1..=168;
+
+impl Default for UpdateCheckSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 6,
+        }
+    }
+}
+
+impl UpdateCheckSettings {
+    pub fn validated(mut self) -> Self {
+        self.interval_hours = self
+            .interval_hours
+            .clamp(*UPDATE_CHECK_HOURS.start(), *UPDATE_CHECK_HOURS.end());
+        self
+    }
+}
+
+/// Priority class the optimizer gives the WebView2 GPU process
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum GpuPriority {
+    /// Like the other elevated processes
+    #[default]
+    AboveNormal,
+    /// One class higher than the rest; the GPU process paces frames, so this
+    /// does most for frame pacing at the least cost to other apps
+    High,
+}
+
+/// Which WebView2 processes the optimizer raises the priority of (see
+/// `elevation_policy.rs`); everything, uncapped, by default
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Default)]
+#[serde(default)]
+pub struct ElevationPolicySettings {
+    /// Chromium process types (`renderer`, `gpu-process`, `utility`) or
+    /// utility services (`audio`, `network`) left at normal priority
+    pub excluded_types: Vec<String>,
+    /// Most processes elevated at once; 0 for no limit
+    pub max_elevated: u32,
+    /// Priority class of the GPU process, unless `excluded_types` leaves it
+    /// out
+    pub gpu_process_priority: GpuPriority,
+}
+
+/// Upper bound for `ElevationPolicySettings::max_elevated`
+pub const MAX_ELEVATED_LIMIT: u32 = 64;
+
+impl ElevationPolicySettings {
+    pub fn validated(mut self) -> Self {
+        let mut excluded: Vec<String> = Vec::new();
+        for kind in &self.excluded_types {
+            let kind = kind.trim().to_lowercase();
+            if !kind.is_empty() && !excluded.contains(&kind) {
+                excluded.push(kind);
+            }
+        }
+        excluded.truncate(16);
+        self.excluded_types = excluded;
+        self.max_elevated = self.max_elevated.min(MAX_ELEVATED_LIMIT);
+        self
+    }
+}
+
+/// Memory and CPU caps for PACDeluxe and its WebView2 processes (see
+/// `resource_limits.rs`); no caps by default
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Default)]
+#[serde(default)]
+pub struct ResourceLimitSettings {
+    /// Committed memory of all processes together, in MB; 0 for no limit
+    pub memory_limit_mb: u32,
+    /// Share of total CPU time, in percent; 0 for no limit
+    pub cpu_rate_percent: u8,
+}
+
+/// Allowed range for a set `ResourceLimitSettings::memory_limit_mb`
+pub const MEMORY_LIMIT_MB_RANGE: std::ops::RangeInclusive<u32> = 1024..=65536;
+/// Allowed range for a set `ResourceLimitSettings::cpu_rate_percent`
+pub const CPU_RATE_PERCENT_RANGE: std::ops::RangeInclusive<u8> = 10..=100;
+
+impl ResourceLimitSettings {
+    pub fn validated(mut self) -> Self {
+        if self.memory_limit_mb > 0 {
+            self.memory_limit_mb = self
+                .memory_limit_mb
+                .clamp(*MEMORY_LIMIT_MB_RANGE.start(), *MEMORY_LIMIT_MB_RANGE.end());
+        }
+        if self.cpu_rate_percent > 0 {
+            self.cpu_rate_percent = self.cpu_rate_percent.clamp(
+                *CPU_RATE_PERCENT_RANGE.start(),
+                *CPU_RATE_PERCENT_RANGE.end(),
+            );
+        }
+        self
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Renderer slowdown while the window sits unfocused outside a match (see
+/// `idle_throttle.rs`); off by default
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct IdleThrottleSettings {
+    pub enabled: bool,
+    /// How many times slower the page's main thread runs while idle
+    pub cpu_slowdown: u8,
+}
+
+/// Allowed range for `IdleThrottleSettings::cpu_slowdown`
+pub const CPU_SLOWDOWN_RANGE: std::ops::RangeInclusive<u8> = 2..=10;
+
+impl Default for IdleThrottleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cpu_slowdown: 4,
+        }
+    }
+}
+
+impl IdleThrottleSettings {
+    pub fn validated(mut self) -> Self {
+        self.cpu_slowdown = self
+            .cpu_slowdown
+            .clamp(*CPU_SLOWDOWN_RANGE.start(), *CPU_SLOWDOWN_RANGE.end());
+        self
+    }
+}
+
+/// Game server status feed polling (see `server_status.rs`); off by default
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct ServerStatusSettings {
+    pub enabled: bool,
+    /// Status feed: a Statuspage `status.json`, a Discord server widget or
+    /// the game's own status endpoint; empty until the user sets one
+    pub url: String,
+    /// Seconds between polls
+    pub interval_secs: u32,
+}
+
+/// Allowed range for `ServerStatusSettings::interval_secs`
+pub const SERVER_STATUS_INTERVAL_SECS: std::ops::RangeInclusive<u32> = 30..=3600;
+
+impl Default for ServerStatusSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            interval_secs: 120,
+        }
+    }
+}
+
+impl ServerStatusSettings {
+    pub fn validated(mut self) -> Self {
+        self.url = self.url.trim().to_string();
+        self.interval_secs = self.interval_secs.clamp(
+            *SERVER_STATUS_INTERVAL_SECS.start(),
+            *SERVER_STATUS_INTERVAL_SECS.end(),
+        );
+        self
+    }
+}
+
+/// Synergy and item recipe panel in the overlay (see `quick_reference.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct QuickReferenceSettings {
+    pub enabled: bool,
+    /// Shows and hides the panel
+    pub hotkey: String,
+}
+
+impl Default for QuickReferenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hotkey: "Ctrl+Shift+Q".to_string(),
+        }
+    }
+}
+
+impl QuickReferenceSettings {
+    /// Normalize the hotkey
+    pub fn validated(mut self) -> Result<Self, String> {
+        self.hotkey = normalize_hotkey(&self.hotkey)?;
+        Ok(self)
+    }
+}
+
+/// How the runtime treats the fonts of a custom CSS theme
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct ThemeFontSettings {
+    /// Render Japanese, Korean and Chinese text in the theme's fonts with
+    /// a system CJK font; theme fonts rarely have those glyphs
+    pub cjk_fallback: bool,
+    /// Game languages (`ja`, `zh-TW`) for which the theme's fonts are left
+    /// out and the game's own are kept; a bare code covers its regions
+    pub exempt_languages: Vec<String>,
+}
+
+impl Default for ThemeFontSettings {
+    fn default() -> Self {
+        Self {
+            cjk_fallback: true,
+            exempt_languages: Vec::new(),
+        }
+    }
+}
+
+impl ThemeFontSettings {
+    /// Normalize the language codes to lowercase, dropping duplicates
+    pub fn validated(mut self) -> Result<Self, String> {
+        let mut languages: Vec<String> = Vec::new();
+        for raw in &self.exempt_languages {
+            let code = raw.trim().to_ascii_lowercase().replace('_', "-");
+            let valid = !code.is_empty()
+                && code.len() <= 12
+                && code.split('-').all(|part| {
+                    !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric())
+                });
+            if !valid {
+                return Err(format!("Invalid language code: {}", raw));
+            }
+            if !languages.contains(&code) {
+                languages.push(code);
+            }
+        }
+        self.exempt_languages = languages;
+        Ok(self)
+    }
+}
+
+/// Spoken announcements (see `tts.rs`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct TtsSettings {
+    pub enabled: bool,
+    /// A match started
+    pub match_found: bool,
+    /// Each new round seen in the HUD
+    pub rounds: bool,
+    /// The player's life dropped to `health_threshold` or below
+    pub low_health: bool,
+    pub health_threshold: u32,
+}
+
+/// Allowed range for `TtsSettings::health_threshold`
+pub const TTS_HEALTH_THRESHOLD: std::ops::RangeInclusive<u32> = 1..=100;
+
+impl Default for TtsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            match_found: true,
+            rounds: true,
+            low_health: true,
+            health_threshold: 20,
+        }
+    }
+}
+
+impl TtsSettings {
+    pub fn validated(mut self) -> Self {
+        self.health_threshold = self
+            .health_threshold
+            .clamp(*TTS_HEALTH_THRESHOLD.start(), *TTS_HEALTH_THRESHOLD.end());
+        self
+    }
+}
+
+/// Cursor drawn by the injected runtime in place of the system one
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+pub enum CursorStyle {
+    /// The game's and the system's own cursors
+    #[default]
+    Default,
+    /// White arrow with a black outline, at `size`
+    Large,
+    /// Yellow arrow with a black outline, at `size`
+    HighVisibility,
+}
+
+/// Enlarged or high-visibility cursor over the game
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct CursorSettings {
+    pub style: CursorStyle,
+    /// Arrow height in CSS pixels
+    pub size: u32,
+}
+
+/// Allowed range for `CursorSettings::size`; the webview ignores cursor
+/// images above 128 pixels
+pub const CURSOR_SIZE: std::ops::RangeInclusive<u32> = 24..=128;
+
+impl Default for CursorSettings {
+    fn default() -> Self {
+        Self {
+            style: CursorStyle::Default,
+            size: 48,
+        }
+    }
+}
+
+impl CursorSettings {
+    pub fn validated(mut self) -> Self {
+        self.size = self.size.clamp(*CURSOR_SIZE.start(), *CURSOR_SIZE.end());
+        self
+    }
+}
+
+/// Rendering knobs applied by the injected runtime (see `low_spec.rs`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// WebGL antialiasing (applied on next page load)
+    pub antialias: bool,
+    /// Frame rate limit; 0 for the display's rate
+    pub fps_cap: u32,
+    /// Finish CSS animations and transitions at once
+    pub reduce_motion: bool,
+}
+
+/// Allowed range for a non-zero `GraphicsSettings::fps_cap`
+pub const FPS_CAP: std::ops::RangeInclusive<u32> = 30..=240;
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            antialias: true,
+            fps_cap: 0,
+            reduce_motion: false,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    pub fn validated(mut self) -> Self {
+        if self.fps_cap != 0 {
+            self.fps_cap = self.fps_cap.clamp(*FPS_CAP.start(), *FPS_CAP.end());
+        }
+        self
+    }
+}
+
+/// Automatic backups of the user's data (see `backup.rs`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Back up once a week (off by default)
+    pub weekly: bool,
+    /// Archives kept by weekly backups; older ones are removed
+    pub keep: u32,
+}
+
+/// Allowed range for `BackupSettings::keep`
+pub const BACKUP_KEEP: std::ops::RangeInclusive<u32> = 1..=52;
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            weekly: false,
+            keep: 4,
+        }
+    }
+}
+
+impl BackupSettings {
+    pub fn validated(mut self) -> Self {
+        self.keep = self.keep.clamp(*BACKUP_KEEP.start(), *BACKUP_KEEP.end());
+        self
+    }
+}
+
+/// Fixes and additions of the injected runtime that can be turned off one
+/// by one (applied from the next start or `recreate_webview`); all on by
+/// default
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(default)]
+pub struct InjectionFlags {
+    /// No browser context menu on the game canvas
+    pub context_menu_fix: bool,
+    /// Hidden scrollbars and viewport sizing without overflow
+    pub scrollbar_fix: bool,
+    /// Additional Picks popup moved up so its bottom row is visible
+    pub layout_fix: bool,
+    /// Reload when the booster page leaves the session looking logged out
+    pub booster_fix: bool,
+    /// "Flip All" button on the booster page
+    pub flip_all_button: bool,
+    /// Copy-link button next to lobby room names
+    pub copy_room_link: bool,
+}
+
+impl Default for InjectionFlags {
+    fn default() -> Self {
+        Self {
+            context_menu_fix: true,
+            scrollbar_fix: true,
+            layout_fix: true,
+            booster_fix: true,
+            flip_all_button: true,
+            copy_room_link: true,
+        }
+    }
+}
+
+/// Gamma and color temperature of the game's display while the window is
+/// focused (see `gamma.rs`); neutral by default
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct GammaSettings {
+    /// Exponent applied to the ramp; above 1 brightens dark tones
+    pub gamma: f32,
+    /// White point in Kelvin; 6500 is neutral, lower is warmer
+    pub color_temperature: u32,
+}
+
+/// Allowed range for `GammaSettings::gamma`
+pub const GAMMA_RANGE: std::ops::RangeInclusive<f32> = 0.6..=1.6;
+/// Allowed range for `GammaSettings::color_temperature`
+pub const COLOR_TEMPERATURE_RANGE: std::ops::RangeInclusive<u32> = 3000..=6500;
+
+impl Default for GammaSettings {
+    fn default() -> Self {
+        Self {
+            gamma: 1.0,
+            color_temperature: 6500,
+        }
+    }
+}
+
+impl GammaSettings {
+    pub fn validated(mut self) -> Self {
+        self.gamma = if self.gamma.is_finite() {
+            self.gamma.clamp(*GAMMA_RANGE.start(), *GAMMA_RANGE.end())
+        } else {
+            1.0
+        };
+        self.color_temperature = self.color_temperature.clamp(
+            *COLOR_TEMPERATURE_RANGE.start(),
+            *COLOR_TEMPERATURE_RANGE.end(),
+        );
+        self
+    }
+
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Persisted user settings.
+///
+/// Every field has a default so settings files written by older versions
+/// keep loading after new fields are added.
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Format version of the file (see `settings_migration.rs`)
+    pub schema_version: u32,
+    /// CSP injection mode for documents served by the localhost server
+    pub csp_mode: CspMode,
+    /// Automatic reload on memory bloat (off by default)
+    pub soft_reload: SoftReloadSettings,
+    /// Stats overlay hotkey and layout cycle
+    pub overlay: OverlaySettings,
+    /// Break reminder after continuous play (off by default)
+    pub break_reminder: BreakReminderSettings,
+    /// What the freeze watchdog does when the page stops responding
+    pub freeze_action: FreezeAction,
+    /// Fallback game URLs tried in order when pokemon-auto-chess.com is
+    /// unreachable at startup
+    pub upstream_mirrors: Vec<String>,
+    /// Debug: record HTTP requests (method, URL, status, timing, size)
+    pub http_logging: bool,
+    /// Browser networking flags (applied on next start)
+    pub networking_preset: NetworkingPreset,
+    /// Render through Vulkan instead of ANGLE/D3D11 (applied on next start;
+    /// skipped on blocklisted GPU drivers, off by default)
+    pub vulkan_rendering: bool,
+    /// Window mode at startup
+    pub start_mode: StartMode,
+    /// Window mode when last changed, used by `StartMode::Last`
+    pub last_window_mode: WindowMode,
+    /// Swallow the Windows key in exclusive fullscreen and turn off the
+    /// Sticky/Filter Keys shortcut prompts while running (Windows)
+    pub suppress_system_keys: bool,
+    /// Mouse acceleration off while focused, and raw pointer events
+    /// (Windows; the browser flag applies on next start)
+    pub high_precision_mouse: bool,
+    /// Confirm sell-key presses in the last seconds of planning (off by
+    /// default)
+    pub misdrop_guard: MisdropGuardSettings,
+    /// Rate-limit the reroll key while it is held (off by default)
+    pub turbo_guard: TurboGuardSettings,
+    /// Music and sound effect levels and mute hotkeys
+    pub audio_mix: AudioMixSettings,
+    /// Skip menu animations and autoplaying music for a few seconds after
+    /// each page load (off by default)
+    pub skip_intro: bool,
+    /// Scheduled lobby/tournament sessions to open and warm up for
+    pub scheduled_tasks: Vec<ScheduledTask>,
+    /// Debug: count WebSocket frames by kind, size and rate
+    pub ws_inspector: WsInspectorSettings,
+    /// Flash/sound alerts for carousel rounds and match starts while in
+    /// the background
+    pub attention: AttentionSettings,
+    /// Process tuning profile chosen by the user
+    pub power_profile: PowerProfile,
+    /// Battery/thermal emergency switch to the battery saver profile
+    pub power_guard: PowerGuardSettings,
+    /// Background update checks (see `updates.rs`)
+    pub update_checks: UpdateCheckSettings,
+    /// Turn Windows HDR off on the game's display while running; the SDR
+    /// game looks washed out with it on (off by default)
+    pub disable_hdr_in_game: bool,
+    /// Display gamma and warmth while the window is focused (Windows)
+    pub gamma: GammaSettings,
+    /// WebView2 process types to leave out of priority elevation, and a
+    /// cap on elevated processes (Windows)
+    pub elevation_policy: ElevationPolicySettings,
+    /// Memory and CPU caps for the app and its webview (Windows)
+    pub resource_limits: ResourceLimitSettings,
+    /// Throttle the page and release the timer resolution while unfocused
+    /// outside a match (Windows)
+    pub idle_throttle: IdleThrottleSettings,
+    /// Drop Dynamic Refresh Rate panels to 60 Hz outside a match (Windows 11)
+    pub dynamic_refresh: bool,
+    /// Game server status feed for the tray tooltip and overlay
+    pub server_status: ServerStatusSettings,
+    /// Synergy breakpoints and item recipes panel in the overlay (off by
+    /// default)
+    pub quick_reference: QuickReferenceSettings,
+    /// CJK fallback and per-language exemptions for custom theme fonts
+    pub theme_fonts: ThemeFontSettings,
+    /// Dark or high-contrast stylesheet for the game's HTML panels (off by
+    /// default)
+    pub contrast_mode: ContrastMode,
+    /// Spoken announcements of match starts, rounds and low health (off by
+    /// default)
+    pub tts: TtsSettings,
+    /// Enlarged or high-visibility cursor (the default cursor unless set)
+    pub cursor: CursorSettings,
+    /// Antialiasing, frame rate cap and reduced motion
+    pub graphics: GraphicsSettings,
+    /// The low-spec preset is applied (see `low_spec.rs`)
+    pub low_spec_mode: bool,
+    /// Weekly backups of the user's data
+    pub backups: BackupSettings,
+    /// Runtime fixes and additions turned on (see `injection.rs`)
+    pub injection_flags: InjectionFlags,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            csp_mode: CspMode::Off,
+            soft_reload: SoftReloadSettings::default(),
+            overlay: OverlaySettings::default(),
+            break_reminder: BreakReminderSettings::default(),
+            freeze_action: FreezeAction::Reload,
+            upstream_mirrors: Vec::new(),
+            http_logging: false,
+            networking_preset: NetworkingPreset::Default,
+            vulkan_rendering: false,
+            start_mode: StartMode::Last,
+            last_window_mode: WindowMode::Windowed,
+            suppress_system_keys: false,
+            high_precision_mouse: false,
+            misdrop_guard: MisdropGuardSettings::default(),
+            turbo_guard: TurboGuardSettings::default(),
+            audio_mix: AudioMixSettings::default(),
+            skip_intro: false,
+            scheduled_tasks: Vec::new(),
+            ws_inspector: WsInspectorSettings::default(),
+            attention: AttentionSettings::default(),
+            power_profile: PowerProfile::default(),
+            power_guard: PowerGuardSettings::default(),
+            update_checks: UpdateCheckSettings::default(),
+            disable_hdr_in_game: false,
+            gamma: GammaSettings::default(),
+            elevation_policy: ElevationPolicySettings::default(),
+            resource_limits: ResourceLimitSettings::default(),
+            idle_throttle: IdleThrottleSettings::default(),
+            dynamic_refresh: false,
+            server_status: ServerStatusSettings::default(),
+            quick_reference: QuickReferenceSettings::default(),
+            theme_fonts: ThemeFontSettings::default(),
+            contrast_mode: ContrastMode::Off,
+            tts: TtsSettings::default(),
+            cursor: CursorSettings::default(),
+            graphics: GraphicsSettings::default(),
+            low_spec_mode: false,
+            backups: BackupSettings::default(),
+            injection_flags: InjectionFlags::default(),
+        }
+    }
+}
+
+/// Group of settings `reset_settings` puts back to their defaults
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ResetScope {
+    All,
+    /// Start mode and last window mode
+    Window,
+    /// Power, process and rendering tuning
+    Performance,
+    /// Runtime toggles and CSP mode
+    Injection,
+    /// Overlay, audio and quick-reference hotkeys and the sell key
+    Hotkeys,
+}
+
+impl Settings {
+    /// Put the settings in `scope` back to their defaults
+    pub fn reset(&mut self, scope: ResetScope) {
+        let defaults = Settings::default();
+        match scope {
+            ResetScope::All => *self = defaults,
+            ResetScope::Window => {
+                self.start_mode = defaults.start_mode;
+                self.last_window_mode = defaults.last_window_mode;
+            }
+            ResetScope::Performance => {
+                self.power_profile = defaults.power_profile;
+                self.power_guard = defaults.power_guard;
+                self.elevation_policy = defaults.elevation_policy;
+                self.resource_limits = defaults.resource_limits;
+                self.idle_throttle = defaults.idle_throttle;
+                self.dynamic_refresh = defaults.dynamic_refresh;
+                self.soft_reload = defaults.soft_reload;
+                self.networking_preset = defaults.networking_preset;
+                self.vulkan_rendering = defaults.vulkan_rendering;
+                self.graphics = defaults.graphics;
+                self.low_spec_mode = defaults.low_spec_mode;
+            }
+            ResetScope::Injection => {
+                self.injection_flags = defaults.injection_flags;
+                self.csp_mode = defaults.csp_mode;
+            }
+            ResetScope::Hotkeys => {
+                self.overlay.hotkey = defaults.overlay.hotkey;
+                self.audio_mix.music_hotkey = defaults.audio_mix.music_hotkey;
+                self.audio_mix.sfx_hotkey = defaults.audio_mix.sfx_hotkey;
+                self.quick_reference.hotkey = defaults.quick_reference.hotkey;
+                self.misdrop_guard.sell_key = defaults.misdrop_guard.sell_key;
+            }
+        }
+    }
+}
+
+/// Managed settings state shared by commands and the localhost server
+pub struct SettingsState(pub Mutex<Settings>);
+
+type ChangeHook = Box<dyn Fn(&Settings) + Send + Sync>;
+static ON_CHANGE: OnceLock<ChangeHook> = OnceLock::new();
+
+/// Call `hook` with the new settings after every saved change
+/// (`page_events.rs` uses it to tell the page)
+pub fn on_change(hook: impl Fn(&Settings) + Send + Sync + 'static) {
+    if ON_CHANGE.set(Box::new(hook)).is_err() {
+        warn!("Settings change hook already installed");
+    }
+}
+
+impl SettingsState {
+    /// Snapshot the current settings
+    pub fn get(&self) -> Settings {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Apply a change, persist it, and return the updated settings. When
+    /// the save fails the change is dropped, so it can't reach disk with a
+    /// later save.
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let mut settings = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = settings.clone();
+        change(&mut updated);
+        save(&updated)?;
+        *settings = updated.clone();
+        drop(settings);
+        if let Some(hook) = ON_CHANGE.get() {
+            hook(&updated);
+        }
+        Ok(updated)
+    }
+}
+
+/// PACDeluxe config directory (Windows: %APPDATA%\PACDeluxe)
+#[cfg(target_os = "windows")]
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var("APPDATA")
+        .ok()
+        .map(|appdata| PathBuf::from(appdata).join("PACDeluxe"))
+}
+
+/// PACDeluxe config directory (Linux: $XDG_CONFIG_HOME/pacdeluxe)
+#[cfg(target_os = "linux")]
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".config"))
+        })
+        .map(|config| config.join("pacdeluxe"))
+}
+
+/// Folder for user-exported diagnostics (heap snapshots, reports)
+pub fn diagnostics_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("diagnostics"))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SETTINGS_FILE_NAME))
+}
+
+/// Load settings from disk, falling back to defaults when missing or invalid
+pub fn load() -> Settings {
+    let Some(path) = settings_path() else {
+        warn!("No config directory available, using default settings");
+        return Settings::default();
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => match parse(&path, &contents) {
+            Ok(settings) => {
+                info!("Loaded settings from {}", path.display());
+                settings
+            }
+            Err(e) => {
+                warn!(
+                    "Invalid settings file {}, using defaults: {}",
+                    path.display(),
+                    e
+                );
+                Settings::default()
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!("No settings file at {}, using defaults", path.display());
+            Settings::default()
+        }
+        Err(e) => {
+            warn!("Failed to read settings {}: {}", path.display(), e);
+            Settings::default()
+        }
+    }
+}
+
+/// Parse a settings file, migrating it first when it was written by an
+/// older version
+fn parse(path: &Path, contents: &str) -> Result<Settings, String> {
+    let mut value: serde_json::Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let migration = settings_migration::migrate(&mut value)?;
+    let settings: Settings = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    if migration.from > SCHEMA_VERSION {
+        warn!(
+            "Settings file is from a newer version (schema {}); settings this version doesn't know are dropped on the next save",
+            migration.from
+        );
+    } else if migration.from < SCHEMA_VERSION {
+        // Keep the old file for going back to an older build
+        let backup = path.with_file_name(format!("settings.v{}.json", migration.from));
+        if let Err(e) = std::fs::write(&backup, contents) {
+            warn!("Failed to back up {}: {}", backup.display(), e);
+        }
+        info!(
+            "Migrated settings from schema {} to {}",
+            migration.from, SCHEMA_VERSION
+        );
+        settings_migration::stash(migration.moved);
+        // The migrated settings are used either way; the next save writes
+        // them in the new format
+        if let Err(e) = save(&settings) {
+            warn!("Failed to save the migrated settings: {}", e);
+        }
+    }
+    Ok(settings)
+}
+
+/// Copy the settings file to `settings.json.bak` before a reset
+pub fn back_up() -> Result<(), String> {
+    let path = settings_path().ok_or_else(|| "No config directory available".to_string())?;
+    back_up_file(&path)
+}
+
+/// Copy `path` to `<path>.bak`, if it exists
+pub(crate) fn back_up_file(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    std::fs::copy(path, &backup)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))
+}
+
+/// Persist settings to disk
+pub fn save(settings: &Settings) -> Result<(), String> {
+    let path = settings_path().ok_or_else(|| "No config directory available".to_string())?;
+    write_json(&path, settings)?;
+    debug!("Saved settings to {}", path.display());
+    Ok(())
+}
+
+/// Write `value` as JSON to `path` (write to a temp file, then rename)
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, json)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirrors_must_be_https_or_loopback() {
+        assert_eq!(
+            normalize_mirror_url(" https://pac.example.org ").as_deref(),
+            Ok("https://pac.example.org/")
+        );
+        assert!(normalize_mirror_url("http://localhost:9000/").is_ok());
+        assert!(normalize_mirror_url("http://pac.example.org/").is_err());
+        assert!(normalize_mirror_url("https://user:pw@pac.example.org/").is_err());
+        assert!(normalize_mirror_url("https://pac.example.org/?token=1").is_err());
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn hotkeys_are_normalized() {
+        assert_eq!(normalize_hotkey("shift+ctrl+p").unwrap(), "Ctrl+Shift+P");
+        assert_eq!(normalize_hotkey("F10").unwrap(), "F10");
+        assert!(normalize_hotkey("p").is_err());
+        assert!(normalize_hotkey("Ctrl+P+O").is_err());
+    }
+
+    #[test]
+    fn overlay_layouts_are_deduplicated() {
+        let overlay = OverlaySettings {
+            layouts: vec![OverlayLayout::Minimal, OverlayLayout::Minimal],
+            ..Default::default()
+        };
+        assert_eq!(
+            overlay.validated().unwrap().layouts,
+            vec![OverlayLayout::Minimal]
+        );
+    }
+
+    #[test]
+    fn overlay_stats_interval_is_clamped() {
+        let overlay = OverlaySettings {
+            stats_interval_ms: 10,
+            ..Default::default()
+        };
+        assert_eq!(overlay.validated().unwrap().stats_interval_ms, 250);
+    }
+
+    #[test]
+    fn misdrop_guard_key_is_normalized() {
+        let guard = MisdropGuardSettings {
+            sell_key: " E ".to_string(),
+            final_seconds: 60,
+            ..Default::default()
+        }
+        .validated()
+        .unwrap();
+        assert_eq!(guard.sell_key, "e");
+        assert_eq!(guard.final_seconds, 15);
+        let guard = MisdropGuardSettings {
+            sell_key: "Enter".to_string(),
+            ..Default::default()
+        };
+        assert!(guard.validated().is_err());
+    }
+
+    #[test]
+    fn turbo_guard_rate_is_clamped() {
+        let guard = |max_per_second| {
+            TurboGuardSettings {
+                enabled: true,
+                max_per_second,
+            }
+            .validated()
+            .max_per_second
+        };
+        assert_eq!(guard(0), 1);
+        assert_eq!(guard(6), 6);
+        assert_eq!(guard(500), 20);
+    }
+
+    #[test]
+    fn update_check_interval_is_clamped() {
+        let hours = |interval_hours| {
+            UpdateCheckSettings {
+                enabled: true,
+                interval_hours,
+            }
+            .validated()
+            .interval_hours
+        };
+        assert_eq!(hours(0), 1);
+        assert_eq!(hours(24), 24);
+        assert_eq!(hours(10_000), 168);
+    }
+
+    #[test]
+    fn audio_mix_hotkeys_must_differ() {
+        let mix = AudioMixSettings {
+            sfx_hotkey: "shift+ctrl+m".to_string(),
+            ..Default::default()
+        };
+        assert!(mix.validated().is_err());
+        let mix = AudioMixSettings {
+            music_volume: 2.0,
+            ..Default::default()
+        };
+        assert!(mix.validated().is_err());
+        assert!(AudioMixSettings::default().validated().is_ok());
+    }
+
+    #[test]
+    fn cursor_size_is_clamped() {
+        let size = |size| {
+            CursorSettings {
+                size,
+                ..Default::default()
+            }
+            .validated()
+            .size
+        };
+        assert_eq!(size(8), 24);
+        assert_eq!(size(64), 64);
+        assert_eq!(size(512), 128);
+    }
+
+    #[test]
+    fn backup_count_is_clamped() {
+        let backups = BackupSettings {
+            weekly: true,
+            keep: 0,
+        }
+        .validated();
+        assert_eq!(backups.keep, 1);
+        assert_eq!(
+            BackupSettings {
+                keep: 500,
+                ..backups
+            }
+            .validated()
+            .keep,
+            52
+        );
+    }
+
+    #[test]
+    fn reset_scopes_leave_other_settings_alone() {
+        let mut settings = Settings {
+            start_mode: StartMode::Fullscreen,
+            vulkan_rendering: true,
+            http_logging: true,
+            ..Default::default()
+        };
+        settings.overlay.hotkey = "Ctrl+Alt+O".to_string();
+
+        settings.reset(ResetScope::Hotkeys);
+        assert_eq!(settings.overlay.hotkey, "Ctrl+Shift+P");
+        assert_eq!(settings.start_mode, StartMode::Fullscreen);
+
+        settings.reset(ResetScope::Window);
+        assert_eq!(settings.start_mode, StartMode::Last);
+        assert!(settings.vulkan_rendering);
+
+        settings.reset(ResetScope::Performance);
+        assert!(!settings.vulkan_rendering);
+        assert!(settings.http_logging);
+
+        settings.reset(ResetScope::All);
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn theme_font_languages_are_normalized() {
+        let fonts = ThemeFontSettings {
+            exempt_languages: vec!["JA".to_string(), "zh_TW".to_string(), "ja".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(fonts.validated().unwrap().exempt_languages, ["ja", "zh-tw"]);
+        let fonts = ThemeFontSettings {
+            exempt_languages: vec!["ko; x".to_string()],
+            ..Default::default()
+        };
+        assert!(fonts.validated().is_err());
+    }
+
+    #[test]
+    fn start_mode_falls_back_to_last_mode() {
+        let settings: Settings =
+            serde_json::from_str(r#"{"last_window_mode":"BorderlessWindowed"}"#).unwrap();
+        assert_eq!(
+            settings.start_mode.resolve(settings.last_window_mode),
+            WindowMode::BorderlessWindowed
+        );
+        assert_eq!(
+            StartMode::Windowed.resolve(WindowMode::Fullscreen),
+            WindowMode::Windowed
+        );
+    }
+
+    #[test]
+    fn csp_mode_round_trips() {
+        let settings: Settings = serde_json::from_str(r#"{"csp_mode":"ReportOnly"}"#).unwrap();
+        assert_eq!(settings.csp_mode, CspMode::ReportOnly);
+    }
+}
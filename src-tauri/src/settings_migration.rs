@@ -0,0 +1,114 @@
+//! Settings Migrations - Cross-platform
+//!
+//! `settings.json` carries a `schema_version`. A file written by an older
+//! version is brought up to date one step at a time before it is parsed, so
+//! a renamed or moved setting keeps the user's value instead of falling back
+//! to its default. Files without a version predate versioning (version 0).
+//!
+//! To change the format: bump `SCHEMA_VERSION` and append a step to `STEPS`
+//! that turns the previous layout into the new one. Steps work on the raw
+//! JSON and must accept files where the keys they expect are missing.
+
+use serde_json::{Map, Value};
+use std::sync::Mutex;
+
+/// Version of the settings format this build writes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Values a migration moved out of `settings.json`, for the module that
+/// owns them now
+#[derive(Debug, Default, PartialEq)]
+pub struct Moved {
+    /// Game volume, kept per profile since profiles were added
+    pub audio_volume: Option<f32>,
+}
+
+/// Result of `migrate`
+#[derive(Debug, PartialEq)]
+pub struct Migration {
+    /// Version the file was written with
+    pub from: u32,
+    pub moved: Moved,
+}
+
+type Step = fn(&mut Map<String, Value>, &mut Moved);
+
+/// `STEPS[n]` turns version `n` into version `n + 1`
+const STEPS: &[Step] = &[v0_to_v1];
+
+/// Version 1: the game volume moved into the profile
+fn v0_to_v1(settings: &mut Map<String, Value>, moved: &mut Moved) {
+    if let Some(volume) = settings.remove("audio_volume") {
+        moved.audio_volume = volume.as_f64().map(|volume| volume as f32);
+    }
+}
+
+/// Bring the settings in `value` up to `SCHEMA_VERSION`. Files from a newer
+/// build are left as they are.
+pub fn migrate(value: &mut Value) -> Result<Migration, String> {
+    let settings = value
+        .as_object_mut()
+        .ok_or("Settings file is not a JSON object")?;
+    let from = match settings.get("schema_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("Invalid settings schema_version {}", version))?,
+    };
+
+    let mut moved = Moved::default();
+    if from < SCHEMA_VERSION {
+        for step in &STEPS[from as usize..] {
+            step(settings, &mut moved);
+        }
+        settings.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+    }
+    Ok(Migration { from, moved })
+}
+
+/// Values moved by this run's migration, until their owner takes them
+static PENDING: Mutex<Option<Moved>> = Mutex::new(None);
+
+/// Keep `moved` for `take_audio_volume`
+pub fn stash(moved: Moved) {
+    *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some(moved);
+}
+
+/// Game volume from a settings file older than profiles, once
+pub fn take_audio_volume() -> Option<f32> {
+    PENDING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .and_then(|moved| moved.audio_volume.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_files_are_migrated_in_order() {
+        let mut value: Value =
+            serde_json::from_str(r#"{"audio_volume":0.5,"http_logging":true}"#).unwrap();
+        let migration = migrate(&mut value).unwrap();
+        assert_eq!(migration.from, 0);
+        assert_eq!(migration.moved.audio_volume, Some(0.5));
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert!(value.get("audio_volume").is_none());
+        assert_eq!(value["http_logging"], true);
+
+        // Already current: nothing to do
+        let again = migrate(&mut value).unwrap();
+        assert_eq!(again.from, SCHEMA_VERSION);
+        assert_eq!(again.moved, Moved::default());
+
+        // Newer build: left alone
+        let mut newer: Value = serde_json::from_str(r#"{"schema_version":99}"#).unwrap();
+        assert_eq!(migrate(&mut newer).unwrap().from, 99);
+        assert_eq!(newer["schema_version"], 99);
+
+        assert!(migrate(&mut Value::Null).is_err());
+    }
+}
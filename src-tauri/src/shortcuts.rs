@@ -0,0 +1,165 @@
+//! Window Shortcuts - Cross-platform
+//!
+//! Window mode shortcuts handled natively, so they work even when the page
+//! has keyboard focus in an input box that swallows key events:
+//! - F11 or Alt+Enter: toggle exclusive fullscreen
+//! - Shift+F11: toggle borderless windowed
+//!
+//! Windows: WebView2's AcceleratorKeyPressed event, raised by the browser
+//! process before the page sees the key. Handled keys never reach the page.
+//! Linux: no native key hook; the injected runtime handles the same keys.
+
+use crate::commands::{self, WindowMode};
+use tauri::{AppHandle, WebviewWindow};
+use tracing::{debug, info, warn};
+
+/// Window mode action bound to a key combination
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shortcut {
+    ToggleFullscreen,
+    ToggleBorderless,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const VK_RETURN: u32 = 0x0D;
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const VK_F11: u32 = 0x7A;
+
+/// Modifier keys held with a shortcut key
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, Default)]
+struct Modifiers {
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+}
+
+/// The shortcut for a virtual key, if any. Extra modifiers don't match, so
+/// e.g. Ctrl+Alt+Enter stays available to the page.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn shortcut_for(key: u32, modifiers: Modifiers) -> Option<Shortcut> {
+    let Modifiers { ctrl, shift, alt } = modifiers;
+    match (key, ctrl, shift, alt) {
+        (VK_F11, false, false, false) => Some(Shortcut::ToggleFullscreen),
+        (VK_F11, false, true, false) => Some(Shortcut::ToggleBorderless),
+        (VK_RETURN, false, false, true) => Some(Shortcut::ToggleFullscreen),
+        _ => None,
+    }
+}
+
+/// Run a shortcut off the UI thread (mode changes wait for window events
+/// that the UI thread delivers)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn run(app: &AppHandle, shortcut: Shortcut) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match shortcut {
+            Shortcut::ToggleFullscreen => commands::toggle_fullscreen(app).await.map(|_| ()),
+            Shortcut::ToggleBorderless => {
+                let mode = if WindowMode::current() == WindowMode::BorderlessWindowed {
+                    WindowMode::Windowed
+                } else {
+                    WindowMode::BorderlessWindowed
+                };
+                commands::set_window_mode(app, mode).await.map(|_| ())
+            }
+        };
+        if let Err(e) = result {
+            // Usually a press during a transition that is still running
+            debug!("{:?} shortcut not applied: {}", shortcut, e);
+        }
+    });
+}
+
+/// Install the window mode shortcuts on the main webview
+#[cfg(target_os = "windows")]
+pub fn install(window: &WebviewWindow) {
+    use tauri::Manager;
+    use webview2_com::AcceleratorKeyPressedEventHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        COREWEBVIEW2_KEY_EVENT_KIND, COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN,
+        COREWEBVIEW2_KEY_EVENT_KIND_SYSTEM_KEY_DOWN, COREWEBVIEW2_PHYSICAL_KEY_STATUS,
+    };
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_MENU, VK_SHIFT};
+
+    let app = window.app_handle().clone();
+    let result = window.with_webview(move |webview| unsafe {
+        let controller = webview.controller();
+        let handler = AcceleratorKeyPressedEventHandler::create(Box::new(move |_sender, args| {
+            let Some(args) = args else { return Ok(()) };
+            let mut kind = COREWEBVIEW2_KEY_EVENT_KIND::default();
+            args.KeyEventKind(&mut kind)?;
+            // Keys pressed with Alt arrive as system keys
+            if kind != COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN
+                && kind != COREWEBVIEW2_KEY_EVENT_KIND_SYSTEM_KEY_DOWN
+            {
+                return Ok(());
+            }
+            let mut key = 0u32;
+            args.VirtualKey(&mut key)?;
+            let modifiers = Modifiers {
+                ctrl: GetKeyState(VK_CONTROL.0 as i32) < 0,
+                shift: GetKeyState(VK_SHIFT.0 as i32) < 0,
+                alt: GetKeyState(VK_MENU.0 as i32) < 0,
+            };
+            let Some(shortcut) = shortcut_for(key, modifiers) else {
+                return Ok(());
+            };
+            args.SetHandled(true)?;
+            // Holding the key toggles once
+            let mut status = COREWEBVIEW2_PHYSICAL_KEY_STATUS::default();
+            args.PhysicalKeyStatus(&mut status)?;
+            if !status.WasKeyDown.as_bool() {
+                run(&app, shortcut);
+            }
+            Ok(())
+        }));
+
+        let mut token = 0;
+        if let Err(e) = controller.add_AcceleratorKeyPressed(&handler, &mut token) {
+            warn!("Failed to install window shortcuts: {}", e);
+        } else {
+            info!("Window shortcuts installed (F11, Alt+Enter, Shift+F11)");
+        }
+    });
+    if let Err(e) = result {
+        warn!("Failed to install window shortcuts: {}", e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn install(_window: &WebviewWindow) {
+    info!("Native window shortcuts are not available on Linux (handled by the runtime)");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifiers(ctrl: bool, shift: bool, alt: bool) -> Modifiers {
+        Modifiers { ctrl, shift, alt }
+    }
+
+    #[test]
+    fn window_mode_keys_are_recognized() {
+        let none = Modifiers::default();
+        assert_eq!(shortcut_for(VK_F11, none), Some(Shortcut::ToggleFullscreen));
+        assert_eq!(
+            shortcut_for(VK_RETURN, modifiers(false, false, true)),
+            Some(Shortcut::ToggleFullscreen)
+        );
+        assert_eq!(
+            shortcut_for(VK_F11, modifiers(false, true, false)),
+            Some(Shortcut::ToggleBorderless)
+        );
+    }
+
+    #[test]
+    fn other_combinations_reach_the_page() {
+        assert_eq!(shortcut_for(VK_RETURN, Modifiers::default()), None);
+        assert_eq!(shortcut_for(VK_RETURN, modifiers(true, false, true)), None);
+        assert_eq!(shortcut_for(VK_F11, modifiers(true, false, false)), None);
+        assert_eq!(shortcut_for(0x52, modifiers(false, false, true)), None);
+    }
+}
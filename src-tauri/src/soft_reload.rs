@@ -0,0 +1,189 @@
+//! Soft Reload - Cross-platform
+//!
+//! Opt-in policy that reloads the game page when the renderer has bloated,
+//! but only outside a room or match. The page is warned first
+//! (`pac-soft-reload-pending`) and the user can cancel the reload.
+
+use crate::history::PerformanceSample;
+use crate::page_state::{GamePhase, PageState};
+use crate::settings::{SettingsState, SoftReloadSettings};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+/// Time between the pre-reload toast and the reload itself
+const WARNING_DELAY: Duration = Duration::from_secs(10);
+
+/// Managed soft reload state
+#[derive(Default)]
+pub struct SoftReloadState {
+    last_reload: Mutex<Option<Instant>>,
+    pending: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl SoftReloadState {
+    /// Cancel a pending reload; returns false if none was pending
+    pub fn cancel(&self) -> bool {
+        if !self.pending.load(Ordering::SeqCst) {
+            return false;
+        }
+        self.cancelled.store(true, Ordering::SeqCst);
+        true
+    }
+}
+
+/// Payload of the `pac-soft-reload-pending` event
+#[derive(Debug, Clone, Serialize)]
+pub struct SoftReloadPending {
+    pub reason: String,
+    pub delay_secs: u64,
+}
+
+/// Why a reload is warranted, if it is
+fn reload_reason(
+    policy: &SoftReloadSettings,
+    sample: &PerformanceSample,
+    phase: GamePhase,
+    since_last_reload: Option<Duration>,
+) -> Option<String> {
+    if !policy.enabled || phase.is_match_in_progress() {
+        return None;
+    }
+    let cooldown = Duration::from_secs(policy.cooldown_minutes * 60);
+    if since_last_reload.is_some_and(|elapsed| elapsed < cooldown) {
+        return None;
+    }
+    if let Some(heap) = sample.js_heap_used_mb {
+        if heap >= policy.heap_threshold_mb as f64 {
+            return Some(format!("JS heap at {:.0} MB", heap));
+        }
+    }
+    // The WebView's processes, not PACDeluxe's own: only their memory is
+    // given back by a reload
+    if sample.webview_memory_mb >= policy.rss_threshold_mb {
+        return Some(format!("WebView memory at {} MB", sample.webview_memory_mb));
+    }
+    None
+}
+
+/// Check a new history sample against the policy and schedule a reload
+pub fn evaluate(app: &AppHandle, sample: &PerformanceSample) {
+    let policy = app.state::<SettingsState>().get().soft_reload;
+    let state = app.state::<SoftReloadState>();
+    if state.pending.load(Ordering::SeqCst) {
+        return;
+    }
+    let phase = app.state::<PageState>().phase();
+    let since_last = state
+        .last_reload
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .map(|at| at.elapsed());
+    let Some(reason) = reload_reason(&policy, sample, phase, since_last) else {
+        return;
+    };
+
+    state.pending.store(true, Ordering::SeqCst);
+    state.cancelled.store(false, Ordering::SeqCst);
+    info!("Soft reload scheduled: {}", reason);
+    let _ = app.emit(
+        "pac-soft-reload-pending",
+        SoftReloadPending {
+            reason,
+            delay_secs: WARNING_DELAY.as_secs(),
+        },
+    );
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(WARNING_DELAY);
+        let state = app.state::<SoftReloadState>();
+        let phase = app.state::<PageState>().phase();
+
+        if state.cancelled.load(Ordering::SeqCst) {
+            info!("Soft reload cancelled by user");
+        } else if phase.is_match_in_progress() {
+            info!("Soft reload skipped: match started ({:?})", phase);
+        } else if let Some(window) = app.get_webview_window("main") {
+            match window.eval("window.location.reload()") {
+                Ok(()) => info!("Soft reload performed"),
+                Err(e) => warn!("Soft reload failed: {}", e),
+            }
+        }
+        // A cancelled or skipped reload also starts the cooldown so the
+        // user is not nagged again on the next sample.
+        *state.last_reload.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        state.pending.store(false, Ordering::SeqCst);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> SoftReloadSettings {
+        SoftReloadSettings {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    fn bloated() -> PerformanceSample {
+        PerformanceSample {
+            js_heap_used_mb: Some(2000.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let policy = SoftReloadSettings::default();
+        assert_eq!(
+            reload_reason(&policy, &bloated(), GamePhase::Lobby, None),
+            None
+        );
+    }
+
+    #[test]
+    fn reloads_bloated_lobby() {
+        assert!(reload_reason(&enabled(), &bloated(), GamePhase::Lobby, None).is_some());
+    }
+
+    #[test]
+    fn never_reloads_mid_match() {
+        assert_eq!(
+            reload_reason(&enabled(), &bloated(), GamePhase::InGame, None),
+            None
+        );
+    }
+
+    #[test]
+    fn only_webview_memory_counts() {
+        let own = PerformanceSample {
+            memory_usage_mb: 8000,
+            ..Default::default()
+        };
+        assert_eq!(
+            reload_reason(&enabled(), &own, GamePhase::Lobby, None),
+            None
+        );
+        let webview = PerformanceSample {
+            webview_memory_mb: 4000,
+            ..Default::default()
+        };
+        assert!(reload_reason(&enabled(), &webview, GamePhase::Lobby, None).is_some());
+    }
+
+    #[test]
+    fn respects_cooldown() {
+        let recent = Some(Duration::from_secs(60));
+        assert_eq!(
+            reload_reason(&enabled(), &bloated(), GamePhase::Lobby, recent),
+            None
+        );
+    }
+}
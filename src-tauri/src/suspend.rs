@@ -0,0 +1,149 @@
+//! System Suspend/Resume - Cross-platform
+//!
+//! Closing the laptop lid used to leave PACDeluxe holding the 1ms timer
+//! resolution into sleep and, on wake, often showing a black webview. On
+//! suspend the background monitors pause and the timer resolution is
+//! released; on resume the power profile is re-applied, the freeze watchdog
+//! starts over, the webview is asked to repaint, the window mode is checked
+//! again and the page re-checks its session (`pac-resumed`). A page that
+//! doesn't come back is reloaded.
+//!
+//! - Windows: `PBT_APMSUSPEND` / `PBT_APMRESUMEAUTOMATIC` through the main
+//!   window subclass in `display_watch.rs`
+//! - Linux: resume is noticed from the wall clock running ahead of the
+//!   monotonic clock (which stops while suspended); there is no suspend
+//!   notification
+
+use crate::page_events::{self, ToastLevel};
+use crate::power_guard::{self, PowerGuard};
+use crate::recovery::{self, FreezeWatchdog, ReloadKind};
+use crate::settings::SettingsState;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+use tracing::{debug, info, warn};
+
+/// Let drivers and the network come back before touching the webview
+const RESUME_SETTLE: Duration = Duration::from_secs(3);
+/// Reload the page if it hasn't sent a heartbeat this long after resume
+const RESUME_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
+
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// True between suspend and resume; monitors skip their work meanwhile
+pub fn is_suspended() -> bool {
+    SUSPENDED.load(Ordering::SeqCst)
+}
+
+/// The system is about to sleep
+pub fn suspended(_app: &AppHandle) {
+    if SUSPENDED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    info!("System suspending: pausing monitors and releasing timer resolution");
+    crate::performance::set_high_timer_resolution(false);
+}
+
+/// The system woke up
+pub fn resumed(app: &AppHandle) {
+    SUSPENDED.store(false, Ordering::SeqCst);
+    info!("System resumed");
+    let chosen = app.state::<SettingsState>().get().power_profile;
+    power_guard::apply_profile(app.state::<PowerGuard>().active_profile(chosen));
+    app.state::<FreezeWatchdog>().restart();
+
+    let app = app.clone();
+    std::thread::Builder::new()
+        .name("resume-check".to_string())
+        .spawn(move || {
+            let resumed_at = Instant::now();
+            std::thread::sleep(RESUME_SETTLE);
+            let Some(window) = app.get_webview_window("main") else {
+                return;
+            };
+            repaint(&window);
+            crate::display_watch::schedule_check(&app, "resume");
+            let _ = window.emit("pac-resumed", ());
+            page_events::toast(&app, ToastLevel::Info, "Reconnecting after sleep...");
+
+            std::thread::sleep(RESUME_HEARTBEAT_TIMEOUT.saturating_sub(RESUME_SETTLE));
+            let heartbeat = app.state::<FreezeWatchdog>().last_heartbeat();
+            let visible =
+                window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+            if visible && !heartbeat.is_some_and(|at| at >= resumed_at) {
+                warn!("No heartbeat since resume, reloading");
+                if let Err(e) = recovery::force_reload(&window, ReloadKind::Reload) {
+                    warn!("Reload after resume failed: {}", e);
+                }
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start resume check: {}", e));
+}
+
+/// Hide and show the WebView2 controller so it recreates its surface
+#[cfg(target_os = "windows")]
+fn repaint(window: &WebviewWindow) {
+    let result = window.with_webview(|webview| unsafe {
+        let controller = webview.controller();
+        let _ = controller.SetIsVisible(false);
+        let _ = controller.SetIsVisible(true);
+        let _ = controller.NotifyParentWindowPositionChanged();
+    });
+    match result {
+        Ok(()) => debug!("Webview repainted after resume"),
+        Err(e) => warn!("Failed to repaint webview: {}", e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn repaint(_window: &WebviewWindow) {}
+
+/// Whether the wall clock moved by more than the monotonic clock plus some
+/// slack, meaning the machine slept in between
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn slept(wall: Duration, monotonic: Duration) -> bool {
+    wall > monotonic + Duration::from_secs(20)
+}
+
+/// Watch for resumes on Linux
+#[cfg(target_os = "linux")]
+pub fn spawn(app: AppHandle) {
+    use std::time::SystemTime;
+
+    std::thread::Builder::new()
+        .name("suspend-watch".to_string())
+        .spawn(move || {
+            let mut wall = SystemTime::now();
+            let mut monotonic = Instant::now();
+            loop {
+                std::thread::sleep(Duration::from_secs(5));
+                let wall_elapsed = wall.elapsed().unwrap_or_default();
+                if slept(wall_elapsed, monotonic.elapsed()) {
+                    resumed(&app);
+                }
+                wall = SystemTime::now();
+                monotonic = Instant::now();
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start suspend watch: {}", e));
+}
+
+/// Windows gets power broadcasts through the main window
+#[cfg(target_os = "windows")]
+pub fn spawn(_app: AppHandle) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_wall_clock_jump_means_the_machine_slept() {
+        let five = Duration::from_secs(5);
+        assert!(!slept(five, five));
+        // NTP adjustments and a busy machine stay under the slack
+        assert!(!slept(Duration::from_secs(12), five));
+        assert!(slept(Duration::from_secs(600), five));
+    }
+}
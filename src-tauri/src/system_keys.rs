@@ -0,0 +1,274 @@
+//! System Key Suppression - Windows
+//!
+//! Optional (`suppress_system_keys`, off by default):
+//! - The Windows key is swallowed while the window is in exclusive
+//!   fullscreen and focused. The low-level keyboard hook doing this only
+//!   exists while in fullscreen, so it adds no input latency otherwise.
+//! - The Sticky/Filter/Toggle Keys shortcut prompts (Shift five times,
+//!   holding right Shift, holding Num Lock) are turned off for this session.
+//!   Features the user has switched on are left alone, and the original
+//!   settings are restored on exit. Changes are not written to the user
+//!   profile, so a crash can't lose them past sign-out.
+//!
+//! Linux: not applicable (no-op).
+
+use crate::commands::WindowMode;
+#[cfg(target_os = "windows")]
+use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+use tauri::WebviewWindow;
+use tracing::debug;
+#[cfg(target_os = "windows")]
+use tracing::{info, warn};
+
+/// Flags shared by STICKYKEYS, FILTERKEYS and TOGGLEKEYS
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const FEATURE_ON: u32 = 0x1;
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const HOTKEY_ACTIVE: u32 = 0x4;
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const CONFIRM_HOTKEY: u32 = 0x8;
+
+/// Flags with the shortcut prompt turned off, or `None` when nothing should
+/// change: the feature is on (the shortcut is how the user turns it off) or
+/// the prompt is already off.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn without_prompt(flags: u32) -> Option<u32> {
+    let quiet = flags & !(HOTKEY_ACTIVE | CONFIRM_HOTKEY);
+    (flags & FEATURE_ON == 0 && quiet != flags).then_some(quiet)
+}
+
+#[cfg(target_os = "windows")]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+#[cfg(target_os = "windows")]
+static FULLSCREEN: AtomicBool = AtomicBool::new(false);
+/// Main window handle, for the foreground check
+#[cfg(target_os = "windows")]
+static MAIN_HWND: AtomicIsize = AtomicIsize::new(0);
+/// Thread running the keyboard hook's message loop
+#[cfg(target_os = "windows")]
+static HOOK_THREAD: Mutex<Option<u32>> = Mutex::new(None);
+/// Accessibility settings as found, for restoring: (set action, buffer)
+#[cfg(target_os = "windows")]
+static SAVED_FEATURES: Mutex<Vec<(u32, [u32; 6])>> = Mutex::new(Vec::new());
+
+/// (get action, set action, struct size) for each accessibility feature.
+/// STICKYKEYS and TOGGLEKEYS are {cbSize, dwFlags}; FILTERKEYS adds four
+/// timing fields, which the shared 6-word buffer has room for.
+#[cfg(target_os = "windows")]
+const FEATURES: [(u32, u32, u32); 3] = [
+    // SPI_GETSTICKYKEYS / SPI_SETSTICKYKEYS
+    (0x003A, 0x003B, 8),
+    // SPI_GETFILTERKEYS / SPI_SETFILTERKEYS
+    (0x0032, 0x0033, 24),
+    // SPI_GETTOGGLEKEYS / SPI_SETTOGGLEKEYS
+    (0x0034, 0x0035, 8),
+];
+
+#[cfg(target_os = "windows")]
+fn system_parameter(action: u32, buffer: &mut [u32; 6]) -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SYSTEM_PARAMETERS_INFO_ACTION, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+    unsafe {
+        SystemParametersInfoW(
+            SYSTEM_PARAMETERS_INFO_ACTION(action),
+            buffer[0],
+            Some(buffer.as_mut_ptr().cast()),
+            // Not persisted to the profile
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+        .is_ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn disable_prompts() {
+    let mut saved = SAVED_FEATURES.lock().unwrap_or_else(|e| e.into_inner());
+    if !saved.is_empty() {
+        return;
+    }
+    for (get, set, size) in FEATURES {
+        let mut buffer = [0u32; 6];
+        buffer[0] = size;
+        if !system_parameter(get, &mut buffer) {
+            continue;
+        }
+        let Some(quiet) = without_prompt(buffer[1]) else {
+            continue;
+        };
+        let original = buffer;
+        buffer[1] = quiet;
+        if system_parameter(set, &mut buffer) {
+            saved.push((set, original));
+        }
+    }
+    debug!(
+        "Accessibility shortcut prompts disabled ({} changed)",
+        saved.len()
+    );
+}
+
+#[cfg(target_os = "windows")]
+fn restore_prompts() {
+    let mut saved = SAVED_FEATURES.lock().unwrap_or_else(|e| e.into_inner());
+    for (set, mut original) in saved.drain(..) {
+        if !system_parameter(set, &mut original) {
+            warn!("Failed to restore accessibility setting {:#06x}", set);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn keyboard_proc(
+    code: i32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::LRESULT;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{VK_LWIN, VK_RWIN};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetForegroundWindow, HC_ACTION, KBDLLHOOKSTRUCT,
+    };
+
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let win_key = info.vkCode == VK_LWIN.0 as u32 || info.vkCode == VK_RWIN.0 as u32;
+        if win_key && GetForegroundWindow().0 as isize == MAIN_HWND.load(Ordering::SeqCst) {
+            return LRESULT(1);
+        }
+    }
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+/// Install the hook when enabled and fullscreen, remove it otherwise
+#[cfg(target_os = "windows")]
+fn update_hook() {
+    use windows::Win32::Foundation::{HINSTANCE, LPARAM, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DispatchMessageW, GetMessageW, PeekMessageW, PostThreadMessageW, SetWindowsHookExW,
+        UnhookWindowsHookEx, MSG, PM_NOREMOVE, WH_KEYBOARD_LL, WM_QUIT, WM_USER,
+    };
+
+    let wanted = ENABLED.load(Ordering::SeqCst) && FULLSCREEN.load(Ordering::SeqCst);
+    let mut thread = HOOK_THREAD.lock().unwrap_or_else(|e| e.into_inner());
+    match (wanted, *thread) {
+        (true, None) => {
+            // The hook is called on the installing thread, which must pump
+            // messages for as long as it is installed
+            let (tx, rx) = std::sync::mpsc::channel();
+            let spawned = std::thread::Builder::new()
+                .name("win-key-hook".to_string())
+                .spawn(move || unsafe {
+                    // Create the message queue before publishing the thread
+                    // id, so an early WM_QUIT isn't lost
+                    let mut msg = MSG::default();
+                    let _ = PeekMessageW(&mut msg, None, WM_USER, WM_USER, PM_NOREMOVE);
+                    let _ = tx.send(GetCurrentThreadId());
+                    let module: HINSTANCE =
+                        GetModuleHandleW(None).map(Into::into).unwrap_or_default();
+                    let hook =
+                        match SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), module, 0) {
+                            Ok(hook) => hook,
+                            Err(e) => {
+                                warn!("Failed to install Windows key hook: {}", e);
+                                return;
+                            }
+                        };
+                    debug!("Windows key suppressed (fullscreen)");
+                    while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                        DispatchMessageW(&msg);
+                    }
+                    let _ = UnhookWindowsHookEx(hook);
+                    debug!("Windows key hook removed");
+                });
+            match spawned {
+                Ok(_) => *thread = rx.recv().ok(),
+                Err(e) => warn!("Failed to start Windows key hook thread: {}", e),
+            }
+        }
+        (false, Some(thread_id)) => {
+            unsafe {
+                let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+            *thread = None;
+        }
+        _ => {}
+    }
+}
+
+/// Remember the main window (the Win key is only swallowed while it has
+/// focus)
+#[cfg(target_os = "windows")]
+pub fn set_window(window: &WebviewWindow) {
+    if let Ok(hwnd) = window.hwnd() {
+        MAIN_HWND.store(hwnd.0 as isize, Ordering::SeqCst);
+    }
+}
+
+/// Turn suppression on or off
+#[cfg(target_os = "windows")]
+pub fn set_enabled(enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::SeqCst) == enabled {
+        return;
+    }
+    if enabled {
+        disable_prompts();
+    } else {
+        restore_prompts();
+    }
+    update_hook();
+    info!(
+        "System key suppression {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+}
+
+/// Track the window mode; the Win key hook only runs in exclusive fullscreen
+#[cfg(target_os = "windows")]
+pub fn on_window_mode(mode: WindowMode) {
+    FULLSCREEN.store(mode == WindowMode::Fullscreen, Ordering::SeqCst);
+    update_hook();
+}
+
+/// Remove the hook and restore accessibility settings (on exit)
+#[cfg(target_os = "windows")]
+pub fn restore() {
+    ENABLED.store(false, Ordering::SeqCst);
+    update_hook();
+    restore_prompts();
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_window(_window: &WebviewWindow) {}
+
+#[cfg(target_os = "linux")]
+pub fn set_enabled(enabled: bool) {
+    if enabled {
+        debug!("System key suppression is not available on Linux");
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn on_window_mode(_mode: WindowMode) {}
+
+#[cfg(target_os = "linux")]
+pub fn restore() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompts_are_disabled_only_for_features_that_are_off() {
+        // Default Sticky Keys flags: available, hotkey and confirmation on
+        assert_eq!(without_prompt(0x7e), Some(0x72));
+        // Sticky Keys on: the shortcut is how the user turns it off
+        assert_eq!(without_prompt(0x7f), None);
+        // Prompt already off
+        assert_eq!(without_prompt(0x72), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Tray Icon - Cross-platform
+//!
+//! System tray entry with a small menu. The icon gets a yellow badge while
+//! the game server reports a problem (see `maintenance.rs`), and a green
+//! one while an update is ready to install (see `updates.rs`); the server
+//! problem wins when both apply. With a status feed configured, the
+//! tooltip has a second line with the server status (`server_status.rs`).
+//!
+//! Linux: needs an AppIndicator implementation at runtime; without one the
+//! tray is skipped and everything else keeps working.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager};
+use tracing::{debug, warn};
+
+const TRAY_ID: &str = "main";
+const TOOLTIP: &str = "PACDeluxe";
+const BADGE_COLOR: [u8; 4] = [250, 204, 21, 255];
+const UPDATE_BADGE_COLOR: [u8; 4] = [74, 222, 128, 255];
+
+static UPSTREAM_PROBLEM: AtomicBool = AtomicBool::new(false);
+static UPDATE_AVAILABLE: AtomicBool = AtomicBool::new(false);
+/// Tooltip line from the status feed
+static SERVER_STATUS: Mutex<Option<String>> = Mutex::new(None);
+
+/// Create the tray icon
+pub fn install(app: &AppHandle) -> Result<(), String> {
+    let icon = app
+        .default_window_icon()
+        .cloned()
+        .ok_or("No application icon")?;
+    let show = MenuItem::with_id(app, "show", "Show PACDeluxe", true, None::<&str>)
+        .map_err(|e| e.to_string())?;
+    let dashboard = MenuItem::with_id(app, "dashboard", "Stats dashboard", true, None::<&str>)
+        .map_err(|e| e.to_string())?;
+    let planner = MenuItem::with_id(app, "planner", "Team planner", true, None::<&str>)
+        .map_err(|e| e.to_string())?;
+    let quit =
+        MenuItem::with_id(app, "quit", "Quit", true, None::<&str>).map_err(|e| e.to_string())?;
+    let menu =
+        Menu::with_items(app, &[&show, &dashboard, &planner, &quit]).map_err(|e| e.to_string())?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(icon)
+        .tooltip(TOOLTIP)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "show" => show_main_window(app),
+            "dashboard" => {
+                if let Err(e) = crate::dashboard::open(app) {
+                    warn!("{}", e);
+                }
+            }
+            "planner" => {
+                if let Err(e) = crate::planner::open(app) {
+                    warn!("{}", e);
+                }
+            }
+            "quit" => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        })
+        .build(app)
+        .map_err(|e| format!("Failed to create tray icon: {}", e))?;
+    debug!("Tray icon installed");
+    Ok(())
+}
+
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Show or clear the yellow "game server problem" badge
+pub fn set_upstream_badge(app: &AppHandle, active: bool) {
+    UPSTREAM_PROBLEM.store(active, Ordering::SeqCst);
+    refresh_badge(app);
+}
+
+/// Show or clear the green "update available" badge
+pub fn set_update_badge(app: &AppHandle, available: bool) {
+    UPDATE_AVAILABLE.store(available, Ordering::SeqCst);
+    refresh_badge(app);
+}
+
+/// Show or clear the server status line of the tooltip
+pub fn set_server_status(app: &AppHandle, summary: Option<String>) {
+    let mut current = SERVER_STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    if *current == summary {
+        return;
+    }
+    *current = summary;
+    drop(current);
+    refresh_badge(app);
+}
+
+fn refresh_badge(app: &AppHandle) {
+    let (Some(tray), Some(icon)) = (app.tray_by_id(TRAY_ID), app.default_window_icon()) else {
+        return;
+    };
+    let badge = if UPSTREAM_PROBLEM.load(Ordering::SeqCst) {
+        Some((
+            BADGE_COLOR,
+            "PACDeluxe - game server problem (not a PACDeluxe issue)",
+        ))
+    } else if UPDATE_AVAILABLE.load(Ordering::SeqCst) {
+        Some((UPDATE_BADGE_COLOR, "PACDeluxe - update available"))
+    } else {
+        None
+    };
+    let (icon, tooltip) = match badge {
+        Some((color, tooltip)) => {
+            let rgba = with_badge(icon.rgba(), icon.width(), icon.height(), color);
+            (Image::new_owned(rgba, icon.width(), icon.height()), tooltip)
+        }
+        None => (
+            Image::new_owned(icon.rgba().to_vec(), icon.width(), icon.height()),
+            TOOLTIP,
+        ),
+    };
+    if let Err(e) = tray.set_icon(Some(icon)) {
+        warn!("Failed to update tray icon: {}", e);
+    }
+    let tooltip = match &*SERVER_STATUS.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some(status) => format!("{}\n{}", tooltip, status),
+        None => tooltip.to_string(),
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+/// Draw a filled circle in the bottom-right quarter of an RGBA image
+fn with_badge(rgba: &[u8], width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+    let mut out = rgba.to_vec();
+    let radius = width.min(height) as f32 / 4.0;
+    let cx = width as f32 - radius;
+    let cy = height as f32 - radius;
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                if let Some(pixel) = out.get_mut(i..i + 4) {
+                    pixel.copy_from_slice(&color);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_is_drawn_bottom_right() {
+        let size = 16;
+        let image = with_badge(&vec![0; size * size * 4], 16, 16, BADGE_COLOR);
+        let pixel = |x: usize, y: usize| &image[(y * size + x) * 4..(y * size + x) * 4 + 4];
+        assert_eq!(pixel(13, 13), &BADGE_COLOR);
+        assert_eq!(pixel(1, 1), &[0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,221 @@
+//! Spoken Announcements - Cross-platform
+//!
+//! Opt-in text-to-speech (`tts`, off by default) for players who can't
+//! watch the screen, or keep the game on a second one. Announcements come
+//! from the event bus: match starts from page phase changes
+//! (`pac-page-state`), and the round number and the player's life the
+//! injected runtime reads from the HUD text (`pac-hud`). Nothing is sent to
+//! the game.
+//!
+//! - Windows: SAPI (`ISpVoice`) with the voice chosen in the system settings
+//! - Linux: speech-dispatcher (`spd-say`)
+
+use crate::page_state::GamePhase;
+use crate::settings::{SettingsState, TtsSettings};
+use serde::Deserialize;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use tauri::{AppHandle, Listener, Manager};
+use tracing::{debug, warn};
+
+/// `pac-hud` payload from the page
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+struct HudReport {
+    round: Option<u32>,
+    life: Option<u32>,
+}
+
+/// Something worth saying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Announcement {
+    MatchFound,
+    RoundStarting(u32),
+    Health(u32),
+}
+
+impl Announcement {
+    fn text(self) -> String {
+        match self {
+            Announcement::MatchFound => "Match found".to_string(),
+            Announcement::RoundStarting(round) => format!("Round {} starting", round),
+            Announcement::Health(life) => format!("You are at {} HP", life),
+        }
+    }
+}
+
+/// Last HUD values of the current match
+#[derive(Debug, Default)]
+struct Hud {
+    round: Option<u32>,
+    life: Option<u32>,
+}
+
+impl Hud {
+    /// Apply a report; returns what changed and is enabled. The first values
+    /// of a match are only remembered.
+    fn report(&mut self, report: HudReport, settings: &TtsSettings) -> Vec<Announcement> {
+        let mut said = Vec::new();
+        if let Some(round) = report.round {
+            if self.round.is_some_and(|previous| round > previous) && settings.rounds {
+                said.push(Announcement::RoundStarting(round));
+            }
+            self.round = Some(round);
+        }
+        if let Some(life) = report.life {
+            let dropped = self.life.is_some_and(|previous| life < previous);
+            if dropped && settings.low_health && life <= settings.health_threshold {
+                said.push(Announcement::Health(life));
+            }
+            self.life = Some(life);
+        }
+        said
+    }
+}
+
+static HUD: Mutex<Hud> = Mutex::new(Hud {
+    round: None,
+    life: None,
+});
+
+/// Queue of the speech thread, started on first use
+static SPEAKER: Mutex<Option<Sender<String>>> = Mutex::new(None);
+
+#[cfg(target_os = "windows")]
+fn speech_loop(queue: mpsc::Receiver<String>) {
+    use windows::core::HSTRING;
+    use windows::Win32::Media::Speech::{ISpVoice, SpVoice};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    // SAFETY: this thread owns its apartment and the voice
+    unsafe {
+        if CoInitializeEx(None, COINIT_MULTITHREADED).is_err() {
+            warn!("COM unavailable; announcements are off");
+            return;
+        }
+        match CoCreateInstance::<_, ISpVoice>(&SpVoice, None, CLSCTX_ALL) {
+            Ok(voice) => {
+                for text in queue {
+                    // Synchronous, so announcements don't talk over each other
+                    if let Err(e) = voice.Speak(&HSTRING::from(text.as_str()), 0, None) {
+                        warn!("Speech failed: {}", e);
+                    }
+                }
+            }
+            Err(e) => warn!("No speech voice available: {}", e),
+        }
+        CoUninitialize();
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn speech_loop(queue: mpsc::Receiver<String>) {
+    for text in queue {
+        // --wait keeps announcements in order
+        match std::process::Command::new("spd-say")
+            .args(["--wait", "--", text.as_str()])
+            .status()
+        {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("spd-say exited with {}", status),
+            Err(e) => {
+                warn!("speech-dispatcher (spd-say) unavailable: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Say `text` after anything already queued
+pub fn speak(text: String) {
+    let mut speaker = SPEAKER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(queue) = speaker.as_ref() {
+        if queue.send(text.clone()).is_ok() {
+            return;
+        }
+    }
+    // Not started yet, or the thread gave up: start it again
+    let (queue, receiver) = mpsc::channel();
+    let started = std::thread::Builder::new()
+        .name("tts".to_string())
+        .spawn(move || speech_loop(receiver));
+    match started {
+        Ok(_) => {
+            let _ = queue.send(text);
+            *speaker = Some(queue);
+        }
+        Err(e) => warn!("Failed to start speech: {}", e),
+    }
+}
+
+fn announce(announcements: Vec<Announcement>) {
+    for announcement in announcements {
+        debug!("Announcing {:?}", announcement);
+        speak(announcement.text());
+    }
+}
+
+/// Subscribe to the event bus
+pub fn install(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any("pac-hud", move |event| {
+        let settings = handle.state::<SettingsState>().get().tts.validated();
+        if !settings.enabled {
+            return;
+        }
+        match serde_json::from_str::<HudReport>(event.payload()) {
+            Ok(report) => {
+                let said = HUD
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .report(report, &settings);
+                announce(said);
+            }
+            Err(e) => warn!("Unexpected HUD payload: {}", e),
+        }
+    });
+
+    let handle = app.clone();
+    app.listen_any("pac-page-state", move |event| {
+        let Ok(phase) = serde_json::from_str::<GamePhase>(event.payload()) else {
+            return;
+        };
+        // Every match starts counting from scratch
+        *HUD.lock().unwrap_or_else(|e| e.into_inner()) = Hud::default();
+        let settings = handle.state::<SettingsState>().get().tts;
+        if settings.enabled && settings.match_found && phase == GamePhase::InGame {
+            announce(vec![Announcement::MatchFound]);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_new_rounds_and_low_health_drops_are_announced() {
+        let settings = TtsSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        let mut hud = Hud::default();
+        let report = |round, life| HudReport {
+            round: Some(round),
+            life: Some(life),
+        };
+        assert!(hud.report(report(1, 100), &settings).is_empty());
+        assert_eq!(
+            hud.report(report(2, 100), &settings),
+            [Announcement::RoundStarting(2)]
+        );
+        assert!(hud.report(report(2, 30), &settings).is_empty());
+        assert_eq!(
+            hud.report(report(2, 18), &settings),
+            [Announcement::Health(18)]
+        );
+        assert!(hud.report(report(2, 18), &settings).is_empty());
+        assert_eq!(Announcement::Health(18).text(), "You are at 18 HP");
+    }
+}
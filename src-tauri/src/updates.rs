@@ -0,0 +1,564 @@
+//! Automatic Update Checks - Cross-platform
+//!
+//! Looks for a new release shortly after startup and then at the interval
+//! in `UpdateCheckSettings` (6 hours by default), so players no longer have
+//! to check themselves. Each interval is moved by up to a fifth at random,
+//! so clients that started together don't all hit the release feed at
+//! the same moment on release day.
+//!
+//! A found update is kept for `install_update` and announced to the page
+//! (`update_available` on `pac-events`), which shows the banner with
+//! "Restart to update" and "Later"; the tray icon gets a green badge. The
+//! outcome of the last check is cached for `get_update_state`.
+//!
+//! Updates must be signed with the minisign key from `tauri.conf.json`.
+//! The key is pinned on the updater here so no config overlay can swap or
+//! drop it, and an update without a signature is refused at check time and
+//! again before install, in debug builds too. The key ID (the fingerprint
+//! `minisign -V` prints) and whether the last download passed the
+//! signature check are part of `UpdateInfo` and the health report.
+//!
+//! Patch updates (`docs/DELTA_UPDATES.md`): the verified installer of each
+//! update is kept in `installers/` next to the settings, and a platform
+//! entry of `latest.json` may list `deltas`, gzipped bsdiff patches from an
+//! older version's installer. With a patch from the running version, the
+//! new installer is rebuilt from the kept one and checked against the full
+//! artifact's signature; if anything fails, the full installer is
+//! downloaded instead.
+
+use crate::commands::{PendingUpdate, UpdateInfo};
+use crate::page_events::{self, PageEvent};
+use crate::report::unix_now;
+use crate::settings::{self, SettingsState};
+use crate::tray;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+use tracing::{debug, info, warn};
+use ts_rs::TS;
+
+/// Give the game time to load before the first check
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(10);
+/// How often the scheduler looks at its due time and the settings
+const TICK: Duration = Duration::from_secs(60);
+
+/// Source of the pinned updater key
+const TAURI_CONFIG: &str = include_str!("../tauri.conf.json");
+/// Installers kept to patch the next update against
+const INSTALLER_DIR_NAME: &str = "installers";
+/// How long a patch download may take
+const DELTA_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How far an update's signature has been checked
+#[derive(Debug, Clone, Copy, Serialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    /// Signed; the signature is checked against the download before the
+    /// installer runs
+    Pending,
+    /// The download matched the signature
+    Verified,
+    /// The download did not match; nothing was installed
+    Invalid,
+}
+
+/// The minisign public key updates are verified with
+#[derive(Debug)]
+pub struct SigningKey {
+    /// As in `plugins.updater.pubkey`
+    pub pubkey: String,
+    /// Key ID in hex, as minisign prints it
+    pub key_id: String,
+}
+
+/// Key ID of a base64-encoded minisign public key file
+fn key_id(pubkey: &str) -> Option<String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let file = String::from_utf8(engine.decode(pubkey.trim()).ok()?).ok()?;
+    let line = file
+        .lines()
+        .find(|l| !l.trim().is_empty() && !l.starts_with("untrusted comment:"))?;
+    let key = engine.decode(line.trim()).ok()?;
+    // "Ed", 8-byte key ID (little-endian), 32-byte Ed25519 key
+    if key.len() != 42 || &key[..2] != b"Ed" {
+        return None;
+    }
+    Some(
+        key[2..10]
+            .iter()
+            .rev()
+            .map(|b| format!("{:02X}", b))
+            .collect(),
+    )
+}
+
+/// The bundled signing key; `None` when missing or unreadable, which turns
+/// updates off
+pub fn signing_key() -> Option<&'static SigningKey> {
+    static KEY: OnceLock<Option<SigningKey>> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let config: serde_json::Value = serde_json::from_str(TAURI_CONFIG).ok()?;
+        let pubkey = config
+            .pointer("/plugins/updater/pubkey")?
+            .as_str()?
+            .to_string();
+        let key_id = key_id(&pubkey)?;
+        Some(SigningKey { pubkey, key_id })
+    })
+    .as_ref()
+}
+
+/// Refuse an update that carries no signature
+pub fn require_signature(update: &Update) -> Result<(), String> {
+    if update.signature.trim().is_empty() {
+        warn!("Refusing unsigned update v{}", update.version);
+        return Err(format!(
+            "Update v{} is not signed; it will not be installed",
+            update.version
+        ));
+    }
+    Ok(())
+}
+
+fn info_for(update: &Update, signature: SignatureStatus) -> UpdateInfo {
+    UpdateInfo {
+        available: true,
+        version: Some(update.version.clone()),
+        body: update.body.clone(),
+        signature: Some(signature),
+        signing_key: signing_key().map(|k| k.key_id.clone()),
+    }
+}
+
+/// Record the outcome of verifying the downloaded update
+pub fn record_signature(app: &AppHandle, status: SignatureStatus) {
+    app.state::<UpdateTracker>().with(|state| {
+        if let Some(info) = state.last_result.as_mut().filter(|i| i.available) {
+            info.signature = Some(status);
+        }
+    });
+}
+
+/// `get_update_state` result
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct UpdateState {
+    /// A check is running right now
+    pub checking: bool,
+    /// Unix seconds of the last finished check
+    pub last_checked: Option<u64>,
+    /// Result of the last successful check
+    pub last_result: Option<UpdateInfo>,
+    /// Error of the last check, cleared by the next successful one
+    pub last_error: Option<String>,
+    /// Unix seconds of the next scheduled check; `None` while turned off
+    pub next_check: Option<u64>,
+}
+
+/// Managed cache of the last check
+#[derive(Default)]
+pub struct UpdateTracker(Mutex<UpdateState>);
+
+impl UpdateTracker {
+    pub fn get(&self) -> UpdateState {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn with(&self, change: impl FnOnce(&mut UpdateState)) {
+        change(&mut self.0.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+}
+
+/// `interval` moved by up to a fifth either way, picked by `seed`
+fn jittered(interval: Duration, seed: u64) -> Duration {
+    let spread = interval.as_secs() / 5;
+    if spread == 0 {
+        return interval;
+    }
+    let offset = seed % (2 * spread + 1);
+    Duration::from_secs(interval.as_secs() - spread + offset)
+}
+
+/// splitmix64, enough to spread check times without a rand dependency
+fn next_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Check the update feed now; an available update is stored and announced
+pub async fn check(app: &AppHandle) -> Result<UpdateInfo, String> {
+    let tracker = app.state::<UpdateTracker>();
+    tracker.with(|state| state.checking = true);
+    let result = run_check(app).await;
+    tracker.with(|state| {
+        state.checking = false;
+        state.last_checked = Some(unix_now());
+        match &result {
+            Ok(info) => {
+                state.last_result = Some(info.clone());
+                state.last_error = None;
+            }
+            Err(e) => state.last_error = Some(e.clone()),
+        }
+    });
+    if let Ok(info) = &result {
+        tray::set_update_badge(app, info.available);
+    }
+    result
+}
+
+async fn run_check(app: &AppHandle) -> Result<UpdateInfo, String> {
+    info!("Checking for updates...");
+
+    let key = signing_key().ok_or_else(|| {
+        warn!("No readable updater signing key; not checking for updates");
+        "Update signing key is missing".to_string()
+    })?;
+    let updater = app
+        .updater_builder()
+        .pubkey(&key.pubkey)
+        .build()
+        .map_err(|e| {
+            warn!("Failed to build updater: {}", e);
+            e.to_string()
+        })?;
+
+    match updater.check().await {
+        Ok(Some(update)) => {
+            require_signature(&update)?;
+            let info = info_for(&update, SignatureStatus::Pending);
+            info!("Update available: v{} (key {})", update.version, key.key_id);
+
+            // Store the update for later download
+            *app.state::<PendingUpdate>()
+                .0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(update);
+            page_events::emit(
+                app,
+                PageEvent::UpdateAvailable {
+                    version: info.version.clone().unwrap_or_default(),
+                    notes: info.body.clone(),
+                    signing_key: info.signing_key.clone(),
+                },
+            );
+            Ok(info)
+        }
+        Ok(None) => {
+            info!("App is up to date");
+            Ok(UpdateInfo {
+                available: false,
+                version: None,
+                body: None,
+                signature: None,
+                signing_key: Some(key.key_id.clone()),
+            })
+        }
+        Err(e) => {
+            warn!("Update check failed: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// A patch listed for an update (`deltas` in its `latest.json` entry)
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Delta {
+    /// Version whose installer the patch applies to
+    pub from: String,
+    pub url: String,
+    /// Of the patch file, in bytes
+    pub size: u64,
+}
+
+/// Patches listed in the platform entry `download_url` came from
+fn listed_deltas(raw_json: &serde_json::Value, download_url: &str) -> Vec<Delta> {
+    let Some(platforms) = raw_json.get("platforms").and_then(|p| p.as_object()) else {
+        return Vec::new();
+    };
+    platforms
+        .values()
+        .find(|entry| entry.get("url").and_then(|url| url.as_str()) == Some(download_url))
+        .and_then(|entry| entry.get("deltas"))
+        .and_then(|deltas| serde_json::from_value(deltas.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn installer_dir(version: &str) -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join(INSTALLER_DIR_NAME).join(version))
+}
+
+/// The kept installer of `version`, if there is one
+fn kept_installer(version: &str) -> Option<PathBuf> {
+    std::fs::read_dir(installer_dir(version)?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.is_file())
+}
+
+/// Keep the verified installer of `update`, replacing older ones
+pub fn keep_installer(update: &Update, bytes: &[u8]) {
+    let Some(dir) = installer_dir(&update.version) else {
+        return;
+    };
+    let name = update
+        .download_url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("installer");
+    if let Some(parent) = dir.parent() {
+        let _ = std::fs::remove_dir_all(parent);
+    }
+    let kept = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(dir.join(name), bytes));
+    match kept {
+        Ok(()) => debug!("Kept the v{} installer", update.version),
+        Err(e) => warn!("Failed to keep the v{} installer: {}", update.version, e),
+    }
+}
+
+/// A listed patch from the running version, with its kept installer
+fn usable_delta(update: &Update) -> Option<(Delta, PathBuf)> {
+    let deltas = listed_deltas(&update.raw_json, update.download_url.as_str());
+    let delta = deltas
+        .into_iter()
+        .find(|delta| delta.from == update.current_version)?;
+    kept_installer(&update.current_version).map(|base| (delta, base))
+}
+
+/// Check `bytes` against a minisign signature (base64, as in
+/// `latest.json`) with the bundled key, as the updater plugin does for a
+/// full download
+fn verify_signature(bytes: &[u8], signature: &str) -> Result<(), String> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let decode = |text: &str| {
+        engine
+            .decode(text.trim())
+            .ok()
+            .and_then(|raw| String::from_utf8(raw).ok())
+    };
+    let key = signing_key().ok_or("No updater key")?;
+    let key = decode(&key.pubkey)
+        .and_then(|text| minisign_verify::PublicKey::decode(&text).ok())
+        .ok_or("Invalid updater key")?;
+    let signature = decode(signature)
+        .and_then(|text| minisign_verify::Signature::decode(&text).ok())
+        .ok_or("Invalid update signature")?;
+    key.verify(bytes, &signature, true)
+        .map_err(|e| format!("Signature check failed: {}", e))
+}
+
+/// Apply a gzipped bsdiff patch to `base`
+fn apply_patch(base: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let mut installer = Vec::new();
+    bsdiff::patch(
+        base,
+        &mut flate2::read::GzDecoder::new(patch),
+        &mut installer,
+    )
+    .map_err(|e| format!("Invalid patch: {}", e))?;
+    Ok(installer)
+}
+
+/// Download a listed patch, reporting progress like a full download
+async fn download_delta(app: &AppHandle, delta: &Delta) -> Result<Vec<u8>, String> {
+    let url = Url::parse(&delta.url)
+        .ok()
+        .filter(|url| url.scheme() == "https")
+        .ok_or_else(|| format!("Invalid patch URL: {}", delta.url))?;
+    let client = reqwest::Client::builder()
+        .timeout(DELTA_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Patch download failed: {}", e))?;
+    let mut patch = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Patch download failed: {}", e))?
+    {
+        patch.extend_from_slice(&chunk);
+        if patch.len() as u64 > delta.size {
+            return Err("Patch is larger than listed".to_string());
+        }
+        let _ = app.emit(
+            "update-progress",
+            serde_json::json!({ "chunk": chunk.len(), "total": delta.size }),
+        );
+    }
+    if patch.len() as u64 != delta.size {
+        return Err("Patch is smaller than listed".to_string());
+    }
+    Ok(patch)
+}
+
+async fn rebuild_installer(
+    app: &AppHandle,
+    update: &Update,
+    delta: &Delta,
+    base: &Path,
+) -> Result<Vec<u8>, String> {
+    let patch = download_delta(app, delta).await?;
+    let base =
+        std::fs::read(base).map_err(|e| format!("Failed to read {}: {}", base.display(), e))?;
+    let installer = tauri::async_runtime::spawn_blocking(move || apply_patch(&base, &patch))
+        .await
+        .map_err(|e| format!("Patching failed: {}", e))??;
+    verify_signature(&installer, &update.signature)?;
+    Ok(installer)
+}
+
+/// The installer of `update` rebuilt from the running version's kept
+/// installer and a listed patch, signature checked. None when there is no
+/// usable patch or any step fails; the caller then downloads in full.
+pub async fn patched_installer(app: &AppHandle, update: &Update) -> Option<Vec<u8>> {
+    let (delta, base) = usable_delta(update)?;
+    info!(
+        "Updating with a {} byte patch from v{}",
+        delta.size, delta.from
+    );
+    match rebuild_installer(app, update, &delta, &base).await {
+        Ok(installer) => {
+            info!("Patched installer verified ({} bytes)", installer.len());
+            Some(installer)
+        }
+        Err(e) => {
+            warn!("Patch update failed, downloading the full installer: {}", e);
+            None
+        }
+    }
+}
+
+/// The stored update, if a check found one
+pub fn available(pending: &PendingUpdate) -> Option<UpdateInfo> {
+    let pending = pending.0.lock().unwrap_or_else(|e| e.into_inner());
+    pending
+        .as_ref()
+        .map(|update| info_for(update, SignatureStatus::Pending))
+}
+
+/// Check after startup and then at the configured, jittered interval
+pub fn spawn(app: AppHandle) {
+    app.manage(UpdateTracker::default());
+    let mut seed = next_seed(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+            ^ u64::from(std::process::id()),
+    );
+    std::thread::Builder::new()
+        .name("update-check".to_string())
+        .spawn(move || {
+            let mut due = Instant::now() + FIRST_CHECK_DELAY;
+            loop {
+                let checks = app.state::<SettingsState>().get().update_checks.validated();
+                let tracker = app.state::<UpdateTracker>();
+                if !checks.enabled {
+                    tracker.with(|state| state.next_check = None);
+                } else if Instant::now() >= due && !crate::suspend::is_suspended() {
+                    // Errors are logged by check(); the next round tries again
+                    let _ = tauri::async_runtime::block_on(check(&app));
+                    seed = next_seed(seed);
+                    let interval = Duration::from_secs(u64::from(checks.interval_hours) * 3600);
+                    let wait = jittered(interval, seed);
+                    due = Instant::now() + wait;
+                    debug!("Next update check in {:?}", wait);
+                }
+                if checks.enabled {
+                    let wait = due.saturating_duration_since(Instant::now());
+                    tracker.with(|state| state.next_check = Some(unix_now() + wait.as_secs()));
+                }
+                std::thread::sleep(
+                    TICK.min(due.saturating_duration_since(Instant::now()))
+                        .max(Duration::from_secs(1)),
+                );
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start update checks: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_key_id_matches_its_comment() {
+        let key = signing_key().expect("tauri.conf.json has an updater key");
+        let file = base64::engine::general_purpose::STANDARD
+            .decode(&key.pubkey)
+            .unwrap();
+        let comment = String::from_utf8(file).unwrap();
+        assert!(comment.starts_with(&format!(
+            "untrusted comment: minisign public key: {}\n",
+            key.key_id
+        )));
+        assert_eq!(key_id("not a key"), None);
+    }
+
+    #[test]
+    fn deltas_come_from_the_downloaded_entry() {
+        let raw: serde_json::Value = serde_json::from_str(
+            r#"{"platforms":{
+                "windows-x86_64":{"url":"https://example.org/a.msi"},
+                "windows-x86_64-nsis":{"url":"https://example.org/a.exe","deltas":[
+                    {"from":"1.6.1","url":"https://example.org/1.6.1.patch","size":10}
+                ]}
+            }}"#,
+        )
+        .unwrap();
+        let deltas = listed_deltas(&raw, "https://example.org/a.exe");
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].from, "1.6.1");
+        assert!(listed_deltas(&raw, "https://example.org/a.msi").is_empty());
+        assert!(listed_deltas(&serde_json::Value::Null, "https://example.org/a.exe").is_empty());
+    }
+
+    #[test]
+    fn patches_rebuild_the_installer() {
+        use std::io::Write;
+
+        let base = b"PACDeluxe installer v1.6.1 with some payload".repeat(50);
+        let mut target = base.clone();
+        target[100..108].copy_from_slice(b"v1.6.2!!");
+        let mut diff = Vec::new();
+        bsdiff::diff(&base, &target, &mut diff).unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&diff).unwrap();
+        let patch = gz.finish().unwrap();
+
+        assert_eq!(apply_patch(&base, &patch).unwrap(), target);
+        assert!(apply_patch(&base, b"not a patch").is_err());
+        assert!(verify_signature(&target, "not a signature").is_err());
+    }
+
+    #[test]
+    fn checks_are_spread_around_the_interval() {
+        let interval = Duration::from_secs(6 * 3600);
+        let mut seed = 1;
+        let mut earliest = interval;
+        let mut latest = interval;
+        for _ in 0..1000 {
+            seed = next_seed(seed);
+            let at = jittered(interval, seed);
+            earliest = earliest.min(at);
+            latest = latest.max(at);
+        }
+        assert!(earliest >= Duration::from_secs(4 * 3600 + 48 * 60));
+        assert!(latest <= Duration::from_secs(7 * 3600 + 12 * 60));
+        // Actually spread, not stuck at one value
+        assert!(latest - earliest > Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,511 @@
+//! White Window Recovery - Windows (WebView2)
+//!
+//! On some GPU and driver combinations WebView2 opens a window that stays
+//! white: the page never finishes loading and nothing is painted. After
+//! startup the watcher waits [`LOAD_TIMEOUT`] for the main window's load
+//! event and the runtime's first heartbeat. When either is missing it
+//! samples the window's pixels through DXGI desktop duplication; a window
+//! that is (nearly) all white counts as blank, one showing anything else is
+//! left alone (a slow network paints the loading screen). When the pixels
+//! can't be sampled (another duplicator, a window on another adapter, a
+//! remote or locked session) the outcome is unknown and nothing changes.
+//!
+//! The timeout only runs while the main window shows a page that sends
+//! heartbeats: the loading and retry screens and mirrors don't.
+//!
+//! A blank window restarts the app one step down the [`FlagLevel`] ladder,
+//! each step dropping the browser flags most likely to be at fault. The
+//! level is kept in `white-window.json` next to the settings, and the level
+//! that finally loaded is logged with its browser arguments. A new WebView2
+//! runtime version starts over from the full flag set.
+//!
+//! Safe mode starts without our flags anyway; the ladder still applies its
+//! last step (`--disable-gpu`) there.
+//!
+//! Linux: WebKitGTK takes no browser flags; a blank window is logged only.
+
+use crate::recovery::FreezeWatchdog;
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, WebviewWindow};
+use tracing::{debug, info, warn};
+
+const STATE_FILE_NAME: &str = "white-window.json";
+/// Time the page gets to load and send its first heartbeat
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Rec. 709 luma (0-1) from which a pixel counts as white
+const WHITE_LUMA: f64 = 0.94;
+/// Share of white samples that makes a window blank
+const BLANK_SHARE: f64 = 0.98;
+/// Sample grid across the window's client area
+const GRID: (u32, u32) = (16, 9);
+
+/// Browser flag sets, from the full one down to software rendering
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagLevel {
+    /// Everything the settings ask for
+    #[default]
+    Full,
+    /// No Vulkan and no zero-copy texture uploads
+    NoVulkan,
+    /// Also no GPU rasterization
+    NoGpuRasterization,
+    /// No browser flags at all
+    NoFlags,
+    /// `--disable-gpu`: drawn on the CPU, slow but reliable
+    DisableGpu,
+}
+
+impl FlagLevel {
+    /// The next safer level, `None` at the last one
+    pub fn next(self) -> Option<Self> {
+        match self {
+            FlagLevel::Full => Some(FlagLevel::NoVulkan),
+            FlagLevel::NoVulkan => Some(FlagLevel::NoGpuRasterization),
+            FlagLevel::NoGpuRasterization => Some(FlagLevel::NoFlags),
+            FlagLevel::NoFlags => Some(FlagLevel::DisableGpu),
+            FlagLevel::DisableGpu => None,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            FlagLevel::Full => "the full browser flag set",
+            FlagLevel::NoVulkan => "Vulkan and zero-copy off",
+            FlagLevel::NoGpuRasterization => "Vulkan, zero-copy and GPU rasterization off",
+            FlagLevel::NoFlags => "no browser flags",
+            FlagLevel::DisableGpu => "the GPU disabled",
+        }
+    }
+}
+
+/// Switches each level drops, on top of the previous levels'
+fn dropped_switches(level: FlagLevel) -> &'static [&'static str] {
+    match level {
+        FlagLevel::Full | FlagLevel::NoFlags | FlagLevel::DisableGpu => &[],
+        FlagLevel::NoVulkan => &["--enable-zero-copy"],
+        FlagLevel::NoGpuRasterization => &["--enable-gpu-rasterization"],
+    }
+}
+
+/// `requested` browser arguments cut down to `level`
+pub fn browser_args(level: FlagLevel, requested: &str) -> String {
+    match level {
+        FlagLevel::Full => return requested.to_string(),
+        FlagLevel::NoFlags => return String::new(),
+        FlagLevel::DisableGpu => return "--disable-gpu".to_string(),
+        FlagLevel::NoVulkan | FlagLevel::NoGpuRasterization => {}
+    }
+    let dropped: Vec<&str> = [FlagLevel::NoVulkan, FlagLevel::NoGpuRasterization]
+        .into_iter()
+        .filter(|step| *step <= level)
+        .flat_map(dropped_switches)
+        .copied()
+        .collect();
+    requested
+        .split_whitespace()
+        .filter(|token| !dropped.contains(token))
+        .filter_map(|token| match token.strip_prefix("--enable-features=") {
+            Some(list) => {
+                let features: Vec<&str> = list
+                    .split(',')
+                    .filter(|feature| !feature.is_empty() && *feature != "Vulkan")
+                    .collect();
+                (!features.is_empty()).then(|| format!("--enable-features={}", features.join(",")))
+            }
+            None => Some(token.to_string()),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether the window is blank: it never loaded or never sent a heartbeat,
+/// and the pixel sample did not show content. `None` when that can't be
+/// told because the sample (`None`) is unavailable.
+fn is_blank(loaded: bool, heartbeat: bool, lumas: Option<&[f64]>) -> Option<bool> {
+    if loaded && heartbeat {
+        return Some(false);
+    }
+    match lumas {
+        Some(lumas) if !lumas.is_empty() => {
+            let white = lumas.iter().filter(|&&luma| luma >= WHITE_LUMA).count();
+            Some(white as f64 >= lumas.len() as f64 * BLANK_SHARE)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct State {
+    level: FlagLevel,
+    /// Runtime version the level was found on
+    webview_version: String,
+    /// A run at this level loaded the page
+    confirmed: bool,
+}
+
+fn state_path() -> Option<PathBuf> {
+    let dir = settings::config_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(STATE_FILE_NAME))
+}
+
+fn load_state() -> State {
+    state_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &State) {
+    let Some(path) = state_path() else {
+        return;
+    };
+    if state.level == FlagLevel::Full {
+        let _ = std::fs::remove_file(path);
+        return;
+    }
+    let written = serde_json::to_string(state)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        warn!("Failed to save white window state: {}", e);
+    }
+}
+
+/// Level for this run
+static LEVEL: OnceLock<FlagLevel> = OnceLock::new();
+
+/// The main window finished loading a page
+static LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Pick this run's flag level. Called once at startup, before WebView2 is
+/// configured.
+pub fn begin() -> FlagLevel {
+    *LEVEL.get_or_init(|| {
+        let state = load_state();
+        if state.level == FlagLevel::Full {
+            return FlagLevel::Full;
+        }
+        let version = tauri::webview_version().unwrap_or_default();
+        if state.webview_version != version {
+            info!(
+                "WebView2 {} replaces {}; trying the full browser flag set again",
+                version, state.webview_version
+            );
+            save_state(&State::default());
+            return FlagLevel::Full;
+        }
+        info!(
+            "Starting with {} after a blank window{}",
+            state.level.describe(),
+            if state.confirmed { "" } else { " (untested)" }
+        );
+        state.level
+    })
+}
+
+/// This run's flag level; `Full` before [`begin`]
+pub fn level() -> FlagLevel {
+    LEVEL.get().copied().unwrap_or_default()
+}
+
+/// The main window fired its load-finished event
+pub fn page_loaded() {
+    LOADED.store(true, Ordering::SeqCst);
+}
+
+/// Luma of a grid of points in the window's client area, from a DXGI
+/// duplication of the output it is on
+#[cfg(target_os = "windows")]
+fn sample_lumas(window: &WebviewWindow) -> Result<Vec<f64>, String> {
+    use windows::core::Interface;
+    use windows::Win32::Foundation::HMODULE;
+    use windows::Win32::Graphics::Direct3D::D3D_DRIVER_TYPE_UNKNOWN;
+    use windows::Win32::Graphics::Direct3D11::{
+        D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D,
+        D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_MAPPED_SUBRESOURCE,
+        D3D11_MAP_READ, D3D11_SDK_VERSION, D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING,
+    };
+    use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM;
+    use windows::Win32::Graphics::Dxgi::{
+        CreateDXGIFactory1, IDXGIFactory1, IDXGIOutput1, IDXGIResource, DXGI_OUTDUPL_FRAME_INFO,
+    };
+
+    let position = window.inner_position().map_err(|e| e.to_string())?;
+    let size = window.inner_size().map_err(|e| e.to_string())?;
+    if size.width == 0 || size.height == 0 {
+        return Err("The window has no client area".to_string());
+    }
+    let display = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned())
+        .ok_or("The window's display is unknown")?;
+
+    // SAFETY: every COM object is owned by a wrapper that releases it; the
+    // mapped pointer is only read within the mapped texture's bounds
+    unsafe {
+        let factory: IDXGIFactory1 =
+            CreateDXGIFactory1().map_err(|e| format!("Failed to create DXGI factory: {}", e))?;
+        let mut found = None;
+        let mut adapter_index = 0u32;
+        'adapters: while let Ok(adapter) = factory.EnumAdapters1(adapter_index) {
+            adapter_index += 1;
+            let mut output_index = 0u32;
+            while let Ok(output) = adapter.EnumOutputs(output_index) {
+                output_index += 1;
+                let Ok(desc) = output.GetDesc() else {
+                    continue;
+                };
+                if crate::hdr_control::gdi_name(&desc.DeviceName) == display {
+                    found = Some((adapter, output, desc.DesktopCoordinates));
+                    break 'adapters;
+                }
+            }
+        }
+        let (adapter, output, bounds) =
+            found.ok_or_else(|| format!("No DXGI output for {}", display))?;
+
+        let mut device: Option<ID3D11Device> = None;
+        let mut context: Option<ID3D11DeviceContext> = None;
+        D3D11CreateDevice(
+            &adapter,
+            D3D_DRIVER_TYPE_UNKNOWN,
+            HMODULE::default(),
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+            None,
+            D3D11_SDK_VERSION,
+            Some(&mut device),
+            None,
+            Some(&mut context),
+        )
+        .map_err(|e| format!("Failed to create D3D11 device: {}", e))?;
+        let device = device.ok_or("No D3D11 device")?;
+        let context = context.ok_or("No D3D11 context")?;
+
+        let output: IDXGIOutput1 = output.cast().map_err(|e| e.to_string())?;
+        let duplication = output
+            .DuplicateOutput(&device)
+            .map_err(|e| format!("Desktop duplication unavailable: {}", e))?;
+        let mut info = DXGI_OUTDUPL_FRAME_INFO::default();
+        let mut resource: Option<IDXGIResource> = None;
+        duplication
+            .AcquireNextFrame(500, &mut info, &mut resource)
+            .map_err(|e| format!("No desktop frame: {}", e))?;
+        let sampled = (|| -> Result<Vec<f64>, String> {
+            let frame: ID3D11Texture2D = resource
+                .ok_or("Empty desktop frame")?
+                .cast()
+                .map_err(|e| e.to_string())?;
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            frame.GetDesc(&mut desc);
+            // HDR desktops hand out float frames; not worth decoding here
+            if desc.Format != DXGI_FORMAT_B8G8R8A8_UNORM {
+                return Err(format!("Unsupported desktop format {:?}", desc.Format));
+            }
+            desc.Usage = D3D11_USAGE_STAGING;
+            desc.BindFlags = 0;
+            desc.CPUAccessFlags = D3D11_CPU_ACCESS_READ.0 as u32;
+            desc.MiscFlags = 0;
+            let mut staging: Option<ID3D11Texture2D> = None;
+            device
+                .CreateTexture2D(&desc, None, Some(&mut staging))
+                .map_err(|e| format!("Failed to create staging texture: {}", e))?;
+            let staging = staging.ok_or("No staging texture")?;
+            context.CopyResource(&staging, &frame);
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            context
+                .Map(&staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))
+                .map_err(|e| format!("Failed to map desktop frame: {}", e))?;
+
+            let mut lumas = Vec::new();
+            for row in 0..GRID.1 {
+                for column in 0..GRID.0 {
+                    // Cell centers, clear of the window border
+                    let x = position.x + ((2 * column + 1) * size.width / (2 * GRID.0)) as i32
+                        - bounds.left;
+                    let y = position.y + ((2 * row + 1) * size.height / (2 * GRID.1)) as i32
+                        - bounds.top;
+                    if x < 0 || y < 0 || x as u32 >= desc.Width || y as u32 >= desc.Height {
+                        continue;
+                    }
+                    let pixel = (mapped.pData as *const u8)
+                        .add(y as usize * mapped.RowPitch as usize + x as usize * 4);
+                    let [b, g, r] = [*pixel, *pixel.add(1), *pixel.add(2)];
+                    lumas.push((0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64) / 255.0);
+                }
+            }
+            context.Unmap(&staging, 0);
+            Ok(lumas)
+        })();
+        let _ = duplication.ReleaseFrame();
+        sampled
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn sample_lumas(_window: &WebviewWindow) -> Result<Vec<f64>, String> {
+    Err("Pixel sampling is not available on Linux".to_string())
+}
+
+/// Move to the next level and restart, or give up at the last one
+#[cfg(target_os = "windows")]
+fn escalate(app: &AppHandle) {
+    let current = level();
+    let Some(next) = current.next() else {
+        warn!(
+            "Window still blank with {}; no safer browser flags left",
+            current.describe()
+        );
+        return;
+    };
+    warn!(
+        "Window blank with {}; restarting with {}",
+        current.describe(),
+        next.describe()
+    );
+    save_state(&State {
+        level: next,
+        webview_version: tauri::webview_version().unwrap_or_default(),
+        confirmed: false,
+    });
+    // Not a crash: the next run should not count towards safe mode
+    crate::safe_mode::finish();
+    crate::restore_system_state(app);
+    app.restart();
+}
+
+#[cfg(target_os = "linux")]
+fn escalate(_app: &AppHandle) {
+    warn!("Window blank; WebKitGTK has no browser flags to fall back on");
+}
+
+/// The page loaded; remember the level that worked
+fn confirm() {
+    let mut state = load_state();
+    if state.level == FlagLevel::Full || state.confirmed {
+        return;
+    }
+    info!(
+        "Page loaded with {} (browser arguments: {:?})",
+        state.level.describe(),
+        std::env::var(crate::browser_flags::BROWSER_ARGS_ENV).unwrap_or_default()
+    );
+    state.confirmed = true;
+    save_state(&state);
+}
+
+/// Watch the main window's first load; call once the freeze watchdog is
+/// managed
+pub fn spawn_watch(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("white-window".to_string())
+        .spawn(move || {
+            let mut started = Instant::now();
+            let mut heartbeat = false;
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let Some(window) = app.get_webview_window("main") else {
+                    return;
+                };
+                // Pages without the runtime never send a heartbeat; time the
+                // game from when it is navigated to
+                if !window.url().is_ok_and(|url| crate::health::runs_runtime(&url)) {
+                    started = Instant::now();
+                    LOADED.store(false, Ordering::SeqCst);
+                    continue;
+                }
+                heartbeat = heartbeat || app.state::<FreezeWatchdog>().last_heartbeat().is_some();
+                let loaded = LOADED.load(Ordering::SeqCst);
+                if loaded && heartbeat {
+                    debug!("Page loaded after {:?}", started.elapsed());
+                    confirm();
+                    return;
+                }
+                if started.elapsed() < LOAD_TIMEOUT {
+                    continue;
+                }
+
+                // Neither hidden nor sleeping windows paint; nothing to judge
+                let visible =
+                    window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+                if !visible || crate::suspend::is_suspended() {
+                    debug!("Main window not visible at the load timeout; not checking");
+                    return;
+                }
+                let lumas = sample_lumas(&window)
+                    .map_err(|e| debug!("Pixel sample unavailable: {}", e))
+                    .ok();
+                let missing = if loaded { "heartbeat" } else { "page load" };
+                match is_blank(loaded, heartbeat, lumas.as_deref()) {
+                    Some(true) => {
+                        warn!("No {} after {}s", missing, LOAD_TIMEOUT.as_secs());
+                        escalate(&app);
+                    }
+                    Some(false) => info!("Page slow to load, but the window shows content"),
+                    None => info!(
+                        "No {} after {}s, but the window can't be sampled; keeping the browser flags",
+                        missing,
+                        LOAD_TIMEOUT.as_secs()
+                    ),
+                }
+                return;
+            }
+        })
+        .map(|_| ())
+        .unwrap_or_else(|e| warn!("Failed to start white window watch: {}", e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUESTED: &str = "--enable-gpu-rasterization --enable-zero-copy --disable-background-timer-throttling --enable-features=ZstdContentEncoding,Vulkan";
+
+    #[test]
+    fn each_level_drops_more_flags() {
+        assert_eq!(browser_args(FlagLevel::Full, REQUESTED), REQUESTED);
+        assert_eq!(
+            browser_args(FlagLevel::NoVulkan, REQUESTED),
+            "--enable-gpu-rasterization --disable-background-timer-throttling --enable-features=ZstdContentEncoding"
+        );
+        assert_eq!(
+            browser_args(
+                FlagLevel::NoGpuRasterization,
+                "--enable-gpu-rasterization --enable-features=Vulkan"
+            ),
+            ""
+        );
+        assert_eq!(browser_args(FlagLevel::NoFlags, REQUESTED), "");
+        assert_eq!(browser_args(FlagLevel::DisableGpu, ""), "--disable-gpu");
+
+        let mut level = FlagLevel::Full;
+        let mut steps = 0;
+        while let Some(next) = level.next() {
+            assert!(next > level);
+            level = next;
+            steps += 1;
+        }
+        assert_eq!(steps, 4);
+    }
+
+    #[test]
+    fn content_on_screen_vetoes_a_retry() {
+        assert_eq!(is_blank(true, true, None), Some(false));
+        // A failed sample is no evidence either way
+        assert_eq!(is_blank(false, false, None), None);
+        assert_eq!(is_blank(false, false, Some(&[])), None);
+        assert_eq!(is_blank(false, true, Some(&[1.0; 100])), Some(true));
+        let mut lumas = [1.0; 100];
+        lumas[..10].fill(0.1);
+        assert_eq!(is_blank(false, false, Some(&lumas)), Some(false));
+    }
+}
@@ -0,0 +1,415 @@
+//! WebSocket Inspector - Windows (WebView2)
+//!
+//! Debug tool for "is it the server or my PC" questions. While enabled,
+//! the game's WebSocket frames are counted through the DevTools
+//! `Network.webSocketFrameSent`/`Received` events: direction, size and
+//! arrival time. Frame contents are dropped as soon as the event is read.
+//!
+//! Each frame gets a kind from its first byte, the Colyseus protocol code
+//! (room data, state patch, ...). With `redact` turned off, room data frames
+//! are also tagged with their message type name; message bodies are never
+//! kept. Nothing is sent anywhere. Off by default (`ws_inspector` setting).
+//!
+//! Linux: WebKitGTK has no DevTools protocol, so the inspector cannot start.
+
+use crate::cdp;
+use crate::commands;
+use crate::settings::{SettingsState, WsInspectorSettings};
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, WebviewWindow};
+use tracing::{info, warn};
+
+/// Frames older than this are dropped
+const WINDOW: Duration = Duration::from_secs(60);
+/// Hard cap on kept frames (a busy match sends a few hundred per minute)
+const MAX_FRAMES: usize = 20_000;
+/// Longest message type name kept
+const MAX_TYPE_CHARS: usize = 32;
+
+/// One counted frame
+struct Frame {
+    at: Instant,
+    outgoing: bool,
+    size: usize,
+    kind: String,
+}
+
+#[derive(Default)]
+struct Inner {
+    frames: VecDeque<Frame>,
+}
+
+/// Managed inspector state
+#[derive(Default)]
+pub struct WsInspector {
+    inner: Mutex<Inner>,
+    capturing: AtomicBool,
+    redact: AtomicBool,
+}
+
+/// Rates for one direction over the window
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct DirectionStats {
+    pub messages: u32,
+    pub bytes: u64,
+    pub messages_per_sec: f64,
+    pub bytes_per_sec: f64,
+    pub max_size: usize,
+}
+
+/// Frames of one kind and direction over the window
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct KindStats {
+    pub kind: String,
+    pub outgoing: bool,
+    pub messages: u32,
+    pub bytes: u64,
+}
+
+/// One second of traffic, oldest bucket first
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct TrafficBucket {
+    pub sent_messages: u32,
+    pub sent_bytes: u64,
+    pub received_messages: u32,
+    pub received_bytes: u64,
+}
+
+/// Inspector summary, as returned by `get_ws_stats`
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct WsStats {
+    pub capturing: bool,
+    pub redact: bool,
+    pub window_secs: u64,
+    pub sent: DirectionStats,
+    pub received: DirectionStats,
+    /// Longest silence between received frames. Long gaps while the page
+    /// isn't stuttering point at the server or the network.
+    pub longest_receive_gap_ms: u64,
+    /// Busiest kinds first
+    pub kinds: Vec<KindStats>,
+    /// Per-second traffic over the window
+    pub timeline: Vec<TrafficBucket>,
+}
+
+/// Name of a Colyseus protocol code
+fn protocol_kind(code: u8) -> String {
+    match code {
+        9 => "handshake".to_string(),
+        10 => "join_room".to_string(),
+        11 => "error".to_string(),
+        12 => "leave_room".to_string(),
+        13 => "room_data".to_string(),
+        14 => "room_state".to_string(),
+        15 => "state_patch".to_string(),
+        16 => "room_data_schema".to_string(),
+        17 => "room_data_bytes".to_string(),
+        _ => format!("code_{}", code),
+    }
+}
+
+/// Message type of a room data frame (string or number, as encoded by the
+/// Colyseus client), without reading further into the message
+fn message_type(bytes: &[u8]) -> Option<String> {
+    let (&prefix, rest) = bytes.split_first()?;
+    let name = match prefix {
+        // fixstr and str8
+        0xa0..=0xbf => rest.get(..(prefix & 0x1f) as usize)?,
+        0xd9 => rest.get(1..1 + *rest.first()? as usize)?,
+        0x00..=0x7f => return Some(prefix.to_string()),
+        0xcc => return rest.first().map(|n| n.to_string()),
+        0xcd => {
+            return rest
+                .get(..2)
+                .map(|n| u16::from_le_bytes([n[0], n[1]]).to_string())
+        }
+        _ => return None,
+    };
+    let name: String = std::str::from_utf8(name)
+        .ok()?
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(MAX_TYPE_CHARS)
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Size and kind of a frame from its DevTools opcode and payload. Binary
+/// payloads arrive base64-encoded; only their first bytes are decoded.
+fn classify(opcode: u64, payload: &str, redact: bool) -> (usize, String) {
+    if opcode != 2 {
+        return (payload.len(), "text".to_string());
+    }
+    let padding = payload.bytes().rev().take_while(|&b| b == b'=').count();
+    let size = (payload.len() / 4 * 3).saturating_sub(padding);
+    // 48 base64 characters = 36 bytes, enough for the code and a type name
+    let head = payload.get(..payload.len().min(48) / 4 * 4).unwrap_or("");
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(head) else {
+        return (size, "binary".to_string());
+    };
+    let Some(&code) = bytes.first() else {
+        return (size, "empty".to_string());
+    };
+    let kind = protocol_kind(code);
+    if code == 13 && !redact {
+        if let Some(name) = message_type(&bytes[1..]) {
+            return (size, format!("{}:{}", kind, name));
+        }
+    }
+    (size, kind)
+}
+
+impl WsInspector {
+    pub fn is_capturing(&self) -> bool {
+        self.capturing.load(Ordering::SeqCst)
+    }
+
+    fn record(&self, outgoing: bool, params: &Value) {
+        if !self.is_capturing() {
+            return;
+        }
+        let Some(response) = params.get("response") else {
+            return;
+        };
+        let opcode = response.get("opcode").and_then(Value::as_u64).unwrap_or(1);
+        let payload = response
+            .get("payloadData")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let (size, kind) = classify(opcode, payload, self.redact.load(Ordering::SeqCst));
+        self.push(Frame {
+            at: Instant::now(),
+            outgoing,
+            size,
+            kind,
+        });
+    }
+
+    fn push(&self, frame: Frame) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        while inner.frames.len() >= MAX_FRAMES
+            || inner
+                .frames
+                .front()
+                .is_some_and(|f| frame.at.duration_since(f.at) > WINDOW)
+        {
+            inner.frames.pop_front();
+        }
+        inner.frames.push_back(frame);
+    }
+
+    pub fn stats(&self) -> WsStats {
+        self.stats_at(Instant::now())
+    }
+
+    fn stats_at(&self, now: Instant) -> WsStats {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let window_secs = WINDOW.as_secs();
+        let mut stats = WsStats {
+            capturing: self.is_capturing(),
+            redact: self.redact.load(Ordering::SeqCst),
+            window_secs,
+            timeline: vec![TrafficBucket::default(); window_secs as usize],
+            ..Default::default()
+        };
+        let mut kinds: BTreeMap<(String, bool), (u32, u64)> = BTreeMap::new();
+        let mut last_received: Option<Instant> = None;
+        let mut longest_gap = Duration::ZERO;
+
+        for frame in inner
+            .frames
+            .iter()
+            .filter(|f| now.duration_since(f.at) < WINDOW)
+        {
+            let direction = if frame.outgoing {
+                &mut stats.sent
+            } else {
+                &mut stats.received
+            };
+            direction.messages += 1;
+            direction.bytes += frame.size as u64;
+            direction.max_size = direction.max_size.max(frame.size);
+
+            let entry = kinds
+                .entry((frame.kind.clone(), frame.outgoing))
+                .or_default();
+            entry.0 += 1;
+            entry.1 += frame.size as u64;
+
+            let age = now.duration_since(frame.at).as_secs() as usize;
+            let bucket = &mut stats.timeline[window_secs as usize - 1 - age];
+            if frame.outgoing {
+                bucket.sent_messages += 1;
+                bucket.sent_bytes += frame.size as u64;
+            } else {
+                bucket.received_messages += 1;
+                bucket.received_bytes += frame.size as u64;
+                if let Some(previous) = last_received {
+                    longest_gap = longest_gap.max(frame.at.duration_since(previous));
+                }
+                last_received = Some(frame.at);
+            }
+        }
+
+        for direction in [&mut stats.sent, &mut stats.received] {
+            direction.messages_per_sec = direction.messages as f64 / window_secs as f64;
+            direction.bytes_per_sec = direction.bytes as f64 / window_secs as f64;
+        }
+        stats.longest_receive_gap_ms = longest_gap.as_millis() as u64;
+        stats.kinds = kinds
+            .into_iter()
+            .map(|((kind, outgoing), (messages, bytes))| KindStats {
+                kind,
+                outgoing,
+                messages,
+                bytes,
+            })
+            .collect();
+        stats.kinds.sort_by(|a, b| b.messages.cmp(&a.messages));
+        stats
+    }
+
+    fn clear(&self) {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .frames
+            .clear();
+    }
+}
+
+/// Whether the frame events are subscribed on the current webview
+static SUBSCRIBED: AtomicBool = AtomicBool::new(false);
+
+/// Start or stop capturing on the main window
+pub async fn apply(app: &AppHandle, config: &WsInspectorSettings) -> Result<(), String> {
+    let inspector = app.state::<WsInspector>();
+    inspector.redact.store(config.redact, Ordering::SeqCst);
+    if config.enabled == inspector.is_capturing() {
+        return Ok(());
+    }
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    if config.enabled {
+        subscribe(app, &window).await?;
+        cdp::call(&window, "Network.enable", json!({})).await?;
+        inspector.clear();
+        inspector.capturing.store(true, Ordering::SeqCst);
+        info!("WebSocket inspector started (redact: {})", config.redact);
+    } else {
+        inspector.capturing.store(false, Ordering::SeqCst);
+        inspector.clear();
+        // Network emulation needs the domain too
+        if !commands::network_emulation_active() {
+            if let Err(e) = cdp::call(&window, "Network.disable", json!({})).await {
+                warn!("Failed to disable DevTools network domain: {}", e);
+            }
+        }
+        info!("WebSocket inspector stopped");
+    }
+    Ok(())
+}
+
+async fn subscribe(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    if SUBSCRIBED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    for (event, outgoing) in [
+        ("Network.webSocketFrameSent", true),
+        ("Network.webSocketFrameReceived", false),
+    ] {
+        let handle = app.clone();
+        if let Err(e) = cdp::subscribe(window, event, move |params| {
+            handle.state::<WsInspector>().record(outgoing, &params);
+        })
+        .await
+        {
+            SUBSCRIBED.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Start capturing if the saved settings enable it (at startup)
+pub fn restore(app: &AppHandle) {
+    let config = app.state::<SettingsState>().get().ws_inspector;
+    if !config.enabled {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = apply(&app, &config).await {
+            warn!("WebSocket inspector unavailable: {}", e);
+        }
+    });
+}
+
+/// DevTools subscriptions belong to the webview; subscribe again on a
+/// rebuilt one
+pub fn resume_on_new_webview(app: &AppHandle) {
+    SUBSCRIBED.store(false, Ordering::SeqCst);
+    app.state::<WsInspector>()
+        .capturing
+        .store(false, Ordering::SeqCst);
+    restore(app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    #[test]
+    fn frames_are_classified_by_protocol_code() {
+        let patch = encode(&[15, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(classify(2, &patch, true), (7, "state_patch".to_string()));
+        assert_eq!(classify(1, "hello", true), (5, "text".to_string()));
+    }
+
+    #[test]
+    fn message_types_only_without_redaction() {
+        let mut frame = vec![13, 0xa0 | 9];
+        frame.extend_from_slice(b"DRAG_DROP");
+        frame.extend_from_slice(&[0x81, 1, 2]);
+        let payload = encode(&frame);
+        assert_eq!(classify(2, &payload, true).1, "room_data");
+        assert_eq!(classify(2, &payload, false).1, "room_data:DRAG_DROP");
+        assert_eq!(message_type(&[0x05]), Some("5".to_string()));
+        assert_eq!(message_type(&[0xc0]), None);
+    }
+
+    #[test]
+    fn stats_cover_the_window() {
+        let inspector = WsInspector::default();
+        let start = Instant::now();
+        for (offset_ms, outgoing, size) in [(0, false, 100), (200, true, 20), (1500, false, 300)] {
+            inspector.push(Frame {
+                at: start + Duration::from_millis(offset_ms),
+                outgoing,
+                size,
+                kind: "room_data".to_string(),
+            });
+        }
+        let stats = inspector.stats_at(start + Duration::from_secs(2));
+        assert_eq!(stats.received.messages, 2);
+        assert_eq!(stats.received.bytes, 400);
+        assert_eq!(stats.sent.max_size, 20);
+        assert_eq!(stats.longest_receive_gap_ms, 1500);
+        let recent: u32 = stats.timeline.iter().map(|b| b.received_messages).sum();
+        assert_eq!(recent, 2);
+
+        let later = inspector.stats_at(start + WINDOW + Duration::from_secs(5));
+        assert_eq!(later.received.messages, 0);
+    }
+}